@@ -2,6 +2,7 @@
 
 #![allow(non_snake_case)]
 
+use crate::brokers::http::{BrokerHttpClient, EndpointClass, OutboundLimits, OutboundStatus};
 use crate::brokers::{AuthResponse, Broker, BrokerCredentials};
 use crate::brokers::types::*;
 use crate::error::{AppError, Result};
@@ -11,22 +12,36 @@ use serde::{Deserialize, Serialize};
 
 const BASE_URL: &str = "https://apiconnect.angelone.in";
 const MASTER_CONTRACT_URL: &str = "https://margincalculator.angelbroking.com/OpenAPI_File/files/OpenAPIScripMaster.json";
+/// GTT rules stay live for up to a year
+const GTT_TIME_PERIOD_DAYS: i32 = 365;
+
+/// Angel One SmartAPI limits (requests per second)
+const OUTBOUND_LIMITS: OutboundLimits = OutboundLimits {
+    orders: 20,
+    quotes: 10,
+    historical: 3,
+    general: 5,
+};
 
 /// Angel One broker implementation
 pub struct AngelBroker {
-    client: Client,
+    client: BrokerHttpClient,
 }
 
 impl AngelBroker {
     pub fn new() -> Self {
         Self {
             // Create HTTP client with connection pooling (matching Flask httpx_client)
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .pool_idle_timeout(std::time::Duration::from_secs(120))
-                .pool_max_idle_per_host(20)
-                .build()
-                .expect("Failed to create HTTP client"),
+            client: BrokerHttpClient::new(
+                "Angel One",
+                Client::builder()
+                    .timeout(std::time::Duration::from_secs(120))
+                    .pool_idle_timeout(std::time::Duration::from_secs(120))
+                    .pool_max_idle_per_host(20)
+                    .build()
+                    .expect("Failed to create HTTP client"),
+                OUTBOUND_LIMITS,
+            ),
         }
     }
 
@@ -103,6 +118,58 @@ struct AngelOrderData {
     text: Option<String>,
 }
 
+/// Map an Angel order to the common order type
+fn map_angel_order(o: AngelOrderData) -> Order {
+    let order_type = match o.ordertype.as_str() {
+        "STOPLOSS_LIMIT" => "SL".to_string(),
+        "STOPLOSS_MARKET" => "SL-M".to_string(),
+        other => other.to_string(),
+    };
+
+    let product = match (o.exchange.as_str(), o.producttype.as_str()) {
+        ("NSE" | "BSE", "DELIVERY") => "CNC".to_string(),
+        (_, "INTRADAY") => "MIS".to_string(),
+        ("NFO" | "MCX" | "BFO" | "CDS", "CARRYFORWARD") => "NRML".to_string(),
+        _ => o.producttype,
+    };
+
+    Order {
+        order_id: o.orderid,
+        exchange_order_id: o.exchange_orderid,
+        symbol: o.tradingsymbol,
+        exchange: o.exchange,
+        side: o.transactiontype,
+        quantity: o.quantity.to_i32(),
+        filled_quantity: o.filledshares.to_i32(),
+        pending_quantity: o.unfilledshares.to_i32(),
+        price: o.price.to_f64(),
+        trigger_price: o.triggerprice.to_f64(),
+        average_price: o.averageprice.to_f64(),
+        order_type,
+        product,
+        status: o.status,
+        validity: o.duration,
+        order_timestamp: o.updatetime,
+        exchange_timestamp: o.exchtime,
+        rejection_reason: o.text,
+    }
+}
+
+/// Parse a message from the Angel order status socket
+///
+/// Updates arrive as `{"order-status": "...", "orderData": {...}}` with the
+/// same fields as the order book. Heartbeat replies and acks are ignored.
+pub fn parse_order_update(text: &str) -> Option<Order> {
+    #[derive(Deserialize)]
+    struct AngelOrderUpdate {
+        #[serde(rename = "orderData")]
+        order_data: Option<AngelOrderData>,
+    }
+
+    let update: AngelOrderUpdate = serde_json::from_str(text).ok()?;
+    update.order_data.filter(|o| !o.orderid.is_empty()).map(map_angel_order)
+}
+
 // Position response
 #[derive(Deserialize)]
 struct AngelPositionData {
@@ -247,6 +314,25 @@ struct AngelDepthLevel {
     orders: StringOrInt,
 }
 
+// Batch margin response
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct AngelBatchMargin {
+    #[serde(default)]
+    total_margin_required: StringOrFloat,
+    #[serde(default)]
+    margin_components: AngelMarginComponents,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct AngelMarginComponents {
+    #[serde(default)]
+    span_margin: StringOrFloat,
+    #[serde(default)]
+    margin_benefit: StringOrFloat,
+}
+
 // Master contract response
 #[derive(Deserialize)]
 struct AngelSymbolData {
@@ -267,6 +353,41 @@ struct AngelSymbolData {
     strike: StringOrFloat,
 }
 
+// GTT rule responses
+#[derive(Deserialize)]
+struct AngelGttRuleId {
+    #[serde(default)]
+    id: StringOrInt64,
+}
+
+#[derive(Deserialize)]
+struct AngelGttRule {
+    #[serde(default)]
+    id: StringOrInt64,
+    #[serde(default)]
+    tradingsymbol: String,
+    #[serde(default)]
+    symboltoken: String,
+    #[serde(default)]
+    exchange: String,
+    #[serde(default)]
+    transactiontype: String,
+    #[serde(default)]
+    producttype: String,
+    #[serde(default)]
+    price: StringOrFloat,
+    #[serde(default)]
+    qty: StringOrInt,
+    #[serde(default)]
+    triggerprice: StringOrFloat,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    createddate: String,
+    #[serde(default)]
+    updateddate: String,
+}
+
 // Helper types for flexible deserialization
 #[derive(Deserialize, Default, Clone)]
 #[serde(untagged)]
@@ -345,6 +466,10 @@ impl Broker for AngelBroker {
         true
     }
 
+    fn outbound_status(&self) -> Option<OutboundStatus> {
+        Some(self.client.status())
+    }
+
     async fn authenticate(&self, credentials: BrokerCredentials) -> Result<AuthResponse> {
         let totp = credentials
             .totp
@@ -373,7 +498,7 @@ impl Broker for AngelBroker {
 
         let response = self
             .client
-            .post(format!(
+            .post(EndpointClass::General, format!(
                 "{}/rest/auth/angelbroking/user/v1/loginByPassword",
                 BASE_URL
             ))
@@ -430,8 +555,12 @@ impl Broker for AngelBroker {
             price: String,
             squareoff: String,
             stoploss: String,
+            #[serde(rename = "trailingStopLoss", skip_serializing_if = "Option::is_none")]
+            trailing_stoploss: Option<String>,
             quantity: String,
             triggerprice: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            ordertag: Option<String>,
         }
 
         // Get symbol token from the order (looked up from master contract)
@@ -454,8 +583,9 @@ impl Broker for AngelBroker {
 
         tracing::info!("Angel place_order: tradingsymbol={}, symboltoken={}", trading_symbol, symbol_token);
 
-        // Map variety based on order type
+        // Map variety based on order type (bracket orders go as ROBO)
         let variety = match order.order_type.as_str() {
+            _ if order.product == "BO" => "ROBO",
             "SL" | "SL-M" => "STOPLOSS",
             _ => if order.amo { "AMO" } else { "NORMAL" },
         };
@@ -474,6 +604,7 @@ impl Broker for AngelBroker {
             "CNC" => "DELIVERY",
             "NRML" => "CARRYFORWARD",
             "MIS" => "INTRADAY",
+            "BO" => "BO",
             _ => "INTRADAY",
         };
 
@@ -487,15 +618,17 @@ impl Broker for AngelBroker {
             producttype: producttype.to_string(),
             duration: order.validity.clone(),
             price: order.price.to_string(),
-            squareoff: "0".to_string(),
-            stoploss: "0".to_string(),
+            squareoff: order.target.unwrap_or(0.0).to_string(),
+            stoploss: order.stoploss.unwrap_or(0.0).to_string(),
+            trailing_stoploss: order.trailing_stoploss.map(|p| p.to_string()),
             quantity: order.quantity.to_string(),
             triggerprice: order.trigger_price.map(|p| p.to_string()),
+            ordertag: order.broker_tag(),
         };
 
         let response = self
             .client
-            .post(format!(
+            .post(EndpointClass::Order, format!(
                 "{}/rest/secure/angelbroking/order/v1/placeOrder",
                 BASE_URL
             ))
@@ -566,7 +699,7 @@ impl Broker for AngelBroker {
 
         let response = self
             .client
-            .post(format!(
+            .post(EndpointClass::Order, format!(
                 "{}/rest/secure/angelbroking/order/v1/modifyOrder",
                 BASE_URL
             ))
@@ -621,7 +754,7 @@ impl Broker for AngelBroker {
 
         let response = self
             .client
-            .post(format!(
+            .post(EndpointClass::Order, format!(
                 "{}/rest/secure/angelbroking/order/v1/cancelOrder",
                 BASE_URL
             ))
@@ -648,7 +781,7 @@ impl Broker for AngelBroker {
     async fn get_order_book(&self, auth_token: &str) -> Result<Vec<Order>> {
         let response = self
             .client
-            .get(format!(
+            .get(EndpointClass::General, format!(
                 "{}/rest/secure/angelbroking/order/v1/getOrderBook",
                 BASE_URL
             ))
@@ -664,50 +797,13 @@ impl Broker for AngelBroker {
 
         let orders = result.data.unwrap_or_default();
 
-        Ok(orders
-            .into_iter()
-            .map(|o| {
-                let order_type = match o.ordertype.as_str() {
-                    "STOPLOSS_LIMIT" => "SL".to_string(),
-                    "STOPLOSS_MARKET" => "SL-M".to_string(),
-                    other => other.to_string(),
-                };
-
-                let product = match (o.exchange.as_str(), o.producttype.as_str()) {
-                    ("NSE" | "BSE", "DELIVERY") => "CNC".to_string(),
-                    (_, "INTRADAY") => "MIS".to_string(),
-                    ("NFO" | "MCX" | "BFO" | "CDS", "CARRYFORWARD") => "NRML".to_string(),
-                    _ => o.producttype,
-                };
-
-                Order {
-                    order_id: o.orderid,
-                    exchange_order_id: o.exchange_orderid,
-                    symbol: o.tradingsymbol,
-                    exchange: o.exchange,
-                    side: o.transactiontype,
-                    quantity: o.quantity.to_i32(),
-                    filled_quantity: o.filledshares.to_i32(),
-                    pending_quantity: o.unfilledshares.to_i32(),
-                    price: o.price.to_f64(),
-                    trigger_price: o.triggerprice.to_f64(),
-                    average_price: o.averageprice.to_f64(),
-                    order_type,
-                    product,
-                    status: o.status,
-                    validity: o.duration,
-                    order_timestamp: o.updatetime,
-                    exchange_timestamp: o.exchtime,
-                    rejection_reason: o.text,
-                }
-            })
-            .collect())
+        Ok(orders.into_iter().map(map_angel_order).collect())
     }
 
     async fn get_trade_book(&self, auth_token: &str) -> Result<Vec<Order>> {
         let response = self
             .client
-            .get(format!(
+            .get(EndpointClass::General, format!(
                 "{}/rest/secure/angelbroking/order/v1/getTradeBook",
                 BASE_URL
             ))
@@ -760,7 +856,7 @@ impl Broker for AngelBroker {
     async fn get_positions(&self, auth_token: &str) -> Result<Vec<Position>> {
         let response = self
             .client
-            .get(format!(
+            .get(EndpointClass::General, format!(
                 "{}/rest/secure/angelbroking/order/v1/getPosition",
                 BASE_URL
             ))
@@ -809,7 +905,7 @@ impl Broker for AngelBroker {
     async fn get_holdings(&self, auth_token: &str) -> Result<Vec<Holding>> {
         let response = self
             .client
-            .get(format!(
+            .get(EndpointClass::General, format!(
                 "{}/rest/secure/angelbroking/portfolio/v1/getAllHolding",
                 BASE_URL
             ))
@@ -857,7 +953,7 @@ impl Broker for AngelBroker {
     async fn get_funds(&self, auth_token: &str) -> Result<Funds> {
         let response = self
             .client
-            .get(format!(
+            .get(EndpointClass::General, format!(
                 "{}/rest/secure/angelbroking/user/v1/getRMS",
                 BASE_URL
             ))
@@ -930,7 +1026,7 @@ impl Broker for AngelBroker {
 
         let response = self
             .client
-            .post(format!(
+            .post(EndpointClass::Quote, format!(
                 "{}/rest/secure/angelbroking/market/v1/quote/",
                 BASE_URL
             ))
@@ -1025,7 +1121,7 @@ impl Broker for AngelBroker {
 
         let response = self
             .client
-            .post(format!(
+            .post(EndpointClass::Quote, format!(
                 "{}/rest/secure/angelbroking/market/v1/quote/",
                 BASE_URL
             ))
@@ -1094,7 +1190,7 @@ impl Broker for AngelBroker {
     async fn download_master_contract(&self, _auth_token: &str) -> Result<Vec<SymbolData>> {
         let response = self
             .client
-            .get(MASTER_CONTRACT_URL)
+            .get(EndpointClass::General, MASTER_CONTRACT_URL)
             .send()
             .await?;
 
@@ -1105,9 +1201,326 @@ impl Broker for AngelBroker {
             .map(|s| Self::process_angel_symbol(s))
             .collect())
     }
+
+    async fn calculate_margin(&self, auth_token: &str, orders: &[OrderRequest]) -> Result<MarginResult> {
+        let positions: Vec<serde_json::Value> = orders
+            .iter()
+            .map(|o| {
+                let product_type = match o.product.as_str() {
+                    "CNC" => "DELIVERY",
+                    "NRML" => "CARRYFORWARD",
+                    _ => "INTRADAY",
+                };
+                let order_type = match o.order_type.as_str() {
+                    "LIMIT" => "LIMIT",
+                    "SL" => "STOPLOSS_LIMIT",
+                    "SL-M" => "STOPLOSS_MARKET",
+                    _ => "MARKET",
+                };
+                serde_json::json!({
+                    "exchange": o.exchange,
+                    "qty": o.quantity,
+                    "price": o.price,
+                    "productType": product_type,
+                    "token": o.symbol_token.clone().unwrap_or_default(),
+                    "tradeType": o.side,
+                    "orderType": order_type,
+                })
+            })
+            .collect();
+
+        let response = self
+            .client
+            .post(EndpointClass::General, format!(
+                "{}/rest/secure/angelbroking/margin/v1/batch",
+                BASE_URL
+            ))
+            .headers(self.get_headers("", Some(auth_token)))
+            .json(&serde_json::json!({ "positions": positions }))
+            .send()
+            .await?;
+
+        let result: AngelResponse<AngelBatchMargin> = response.json().await?;
+
+        if !result.status {
+            return Err(AppError::Broker(result.message));
+        }
+
+        let data = result.data.unwrap_or_default();
+        let total_margin = data.total_margin_required.to_f64();
+        let span_margin = data.margin_components.span_margin.to_f64();
+
+        Ok(MarginResult {
+            span_margin,
+            exposure_margin: (total_margin - span_margin).max(0.0),
+            total_margin,
+            hedge_benefit: data.margin_components.margin_benefit.to_f64(),
+        })
+    }
+
+    fn supports_bracket(&self, order: &OrderRequest) -> bool {
+        // ROBO orders take target, stoploss and trailing; cover orders are not offered
+        order.product == "BO"
+    }
+
+    fn supports_gtt(&self, gtt: &GttRequest) -> bool {
+        // Angel GTT rules carry a single trigger and a single LIMIT order
+        gtt.trigger_type == "single" && gtt.legs.iter().all(|leg| leg.order_type == "LIMIT")
+    }
+
+    async fn place_gtt(&self, auth_token: &str, gtt: GttRequest) -> Result<String> {
+        #[derive(Serialize)]
+        struct CreateRuleRequest {
+            tradingsymbol: String,
+            symboltoken: String,
+            exchange: String,
+            transactiontype: String,
+            producttype: String,
+            price: String,
+            qty: String,
+            triggerprice: String,
+            disclosedqty: String,
+            timeperiod: String,
+        }
+
+        let (trigger_price, leg) = Self::single_gtt_leg(&gtt)?;
+
+        let request = CreateRuleRequest {
+            tradingsymbol: gtt.broker_symbol.clone().unwrap_or_else(|| gtt.symbol.clone()),
+            symboltoken: gtt.symbol_token.clone().unwrap_or_else(|| "0".to_string()),
+            exchange: gtt.exchange.clone(),
+            transactiontype: leg.side.clone(),
+            producttype: Self::map_gtt_product(&leg.product).to_string(),
+            price: leg.price.to_string(),
+            qty: leg.quantity.to_string(),
+            triggerprice: trigger_price.to_string(),
+            disclosedqty: leg.quantity.to_string(),
+            timeperiod: GTT_TIME_PERIOD_DAYS.to_string(),
+        };
+
+        let response = self
+            .client
+            .post(EndpointClass::Order, format!(
+                "{}/rest/secure/angelbroking/gtt/v1/createRule",
+                BASE_URL
+            ))
+            .headers(self.get_headers("", Some(auth_token)))
+            .json(&request)
+            .send()
+            .await?;
+
+        let result: AngelResponse<AngelGttRuleId> = response.json().await?;
+
+        if !result.status {
+            return Err(AppError::Broker(result.message));
+        }
+
+        let data = result
+            .data
+            .ok_or_else(|| AppError::Broker("No rule ID in response".to_string()))?;
+
+        Ok(data.id.to_i64().to_string())
+    }
+
+    async fn modify_gtt(&self, auth_token: &str, gtt_id: &str, gtt: GttRequest) -> Result<String> {
+        #[derive(Serialize)]
+        struct ModifyRuleRequest {
+            id: String,
+            symboltoken: String,
+            exchange: String,
+            price: String,
+            qty: String,
+            triggerprice: String,
+            disclosedqty: String,
+            timeperiod: String,
+        }
+
+        let (trigger_price, leg) = Self::single_gtt_leg(&gtt)?;
+
+        let request = ModifyRuleRequest {
+            id: gtt_id.to_string(),
+            symboltoken: gtt.symbol_token.clone().unwrap_or_else(|| "0".to_string()),
+            exchange: gtt.exchange.clone(),
+            price: leg.price.to_string(),
+            qty: leg.quantity.to_string(),
+            triggerprice: trigger_price.to_string(),
+            disclosedqty: leg.quantity.to_string(),
+            timeperiod: GTT_TIME_PERIOD_DAYS.to_string(),
+        };
+
+        let response = self
+            .client
+            .post(EndpointClass::Order, format!(
+                "{}/rest/secure/angelbroking/gtt/v1/modifyRule",
+                BASE_URL
+            ))
+            .headers(self.get_headers("", Some(auth_token)))
+            .json(&request)
+            .send()
+            .await?;
+
+        let result: AngelResponse<AngelGttRuleId> = response.json().await?;
+
+        if !result.status {
+            return Err(AppError::Broker(result.message));
+        }
+
+        Ok(result
+            .data
+            .map(|d| d.id.to_i64().to_string())
+            .unwrap_or_else(|| gtt_id.to_string()))
+    }
+
+    async fn delete_gtt(&self, auth_token: &str, gtt_id: &str) -> Result<()> {
+        #[derive(Serialize)]
+        struct RuleIdRequest {
+            id: String,
+        }
+
+        #[derive(Serialize)]
+        struct CancelRuleRequest {
+            id: String,
+            symboltoken: String,
+            exchange: String,
+        }
+
+        // cancelRule needs the token and exchange, which only the rule itself knows
+        let response = self
+            .client
+            .post(EndpointClass::General, format!(
+                "{}/rest/secure/angelbroking/gtt/v1/ruleDetails",
+                BASE_URL
+            ))
+            .headers(self.get_headers("", Some(auth_token)))
+            .json(&RuleIdRequest { id: gtt_id.to_string() })
+            .send()
+            .await?;
+
+        let details: AngelResponse<AngelGttRule> = response.json().await?;
+
+        if !details.status {
+            return Err(AppError::Broker(details.message));
+        }
+
+        let rule = details
+            .data
+            .ok_or_else(|| AppError::NotFound(format!("GTT rule {} not found", gtt_id)))?;
+
+        let request = CancelRuleRequest {
+            id: gtt_id.to_string(),
+            symboltoken: rule.symboltoken,
+            exchange: rule.exchange,
+        };
+
+        let response = self
+            .client
+            .post(EndpointClass::Order, format!(
+                "{}/rest/secure/angelbroking/gtt/v1/cancelRule",
+                BASE_URL
+            ))
+            .headers(self.get_headers("", Some(auth_token)))
+            .json(&request)
+            .send()
+            .await?;
+
+        let result: AngelResponse<AngelGttRuleId> = response.json().await?;
+
+        if !result.status {
+            return Err(AppError::Broker(result.message));
+        }
+
+        Ok(())
+    }
+
+    async fn get_gtt_orders(&self, auth_token: &str) -> Result<Vec<GttOrder>> {
+        #[derive(Serialize)]
+        struct RuleListRequest {
+            status: Vec<String>,
+            page: i32,
+            count: i32,
+        }
+
+        let request = RuleListRequest {
+            status: ["NEW", "CANCELLED", "ACTIVE", "SENTTOEXCHANGE", "FORALL"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            page: 1,
+            count: 100,
+        };
+
+        let response = self
+            .client
+            .post(EndpointClass::General, format!(
+                "{}/rest/secure/angelbroking/gtt/v1/ruleList",
+                BASE_URL
+            ))
+            .headers(self.get_headers("", Some(auth_token)))
+            .json(&request)
+            .send()
+            .await?;
+
+        let result: AngelResponse<Vec<AngelGttRule>> = response.json().await?;
+
+        if !result.status {
+            return Err(AppError::Broker(result.message));
+        }
+
+        Ok(result
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| {
+                let product = match r.producttype.as_str() {
+                    "DELIVERY" => "CNC".to_string(),
+                    "MARGIN" => "NRML".to_string(),
+                    _ => r.producttype.clone(),
+                };
+
+                GttOrder {
+                    gtt_id: r.id.to_i64().to_string(),
+                    symbol: r.tradingsymbol,
+                    exchange: r.exchange,
+                    trigger_type: "single".to_string(),
+                    trigger_prices: vec![r.triggerprice.to_f64()],
+                    last_price: 0.0,
+                    legs: vec![GttLeg {
+                        side: r.transactiontype,
+                        quantity: r.qty.to_i32(),
+                        order_type: "LIMIT".to_string(),
+                        price: r.price.to_f64(),
+                        product,
+                    }],
+                    status: r.status,
+                    order_id: None,
+                    emulated: false,
+                    created_at: r.createddate,
+                    updated_at: r.updateddate,
+                }
+            })
+            .collect())
+    }
 }
 
 impl AngelBroker {
+    /// Extract the single trigger and leg of a GTT rule
+    fn single_gtt_leg(gtt: &GttRequest) -> Result<(f64, &GttLeg)> {
+        match (gtt.trigger_prices.first(), gtt.legs.first()) {
+            (Some(trigger), Some(leg)) if gtt.trigger_type == "single" => Ok((*trigger, leg)),
+            _ => Err(AppError::Validation(
+                "Angel GTT rules support a single trigger with one leg".to_string(),
+            )),
+        }
+    }
+
+    /// Map product type to Angel GTT product (only DELIVERY and MARGIN are allowed)
+    fn map_gtt_product(product: &str) -> &'static str {
+        match product {
+            "NRML" => "MARGIN",
+            _ => "DELIVERY",
+        }
+    }
+
     /// Process Angel symbol data to match Flask OpenAlgo's symbol normalization
     fn process_angel_symbol(s: AngelSymbolData) -> SymbolData {
         let brexchange = s.exch_seg.clone(); // Original broker exchange
@@ -2,6 +2,7 @@
 
 #![allow(non_snake_case)]
 
+use crate::brokers::http::{BrokerHttpClient, EndpointClass, OutboundLimits, OutboundStatus};
 use crate::brokers::{AuthResponse, Broker, BrokerCredentials};
 use crate::brokers::types::*;
 use crate::error::{AppError, Result};
@@ -11,6 +12,8 @@ use serde::{Deserialize, Deserializer, Serialize};
 use sha2::{Digest, Sha256};
 
 const BASE_URL: &str = "https://api-t1.fyers.in/api/v3";
+/// Span margin calculator (only available on the v2 API)
+const SPAN_MARGIN_URL: &str = "https://api.fyers.in/api/v2/span_margin";
 
 // ============================================================================
 // Flexible Deserialization Helpers
@@ -148,6 +151,22 @@ struct FundsResponseData {
     fund_limit: Option<Vec<FundLimitEntry>>,
 }
 
+/// Span margin response data
+#[derive(Debug, Deserialize)]
+struct SpanMarginResponseData {
+    data: Option<FyersSpanMargin>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct FyersSpanMargin {
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_float")]
+    span: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_float")]
+    exposure: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_float")]
+    total: Option<f64>,
+}
+
 /// Fyers order data from API
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -179,6 +198,53 @@ struct FyersOrderData {
     message: Option<String>,
 }
 
+/// Map a Fyers order to the common order type
+fn map_fyers_order(o: FyersOrderData) -> Order {
+    let exchange = get_exchange_name(
+        o.exchange.unwrap_or(10),
+        o.segment.unwrap_or(10),
+    );
+    let symbol_name = o.symbol
+        .as_ref()
+        .map(|s| FyersBroker::extract_symbol_name(s))
+        .unwrap_or_default();
+
+    Order {
+        order_id: o.id.unwrap_or_default(),
+        exchange_order_id: None,
+        symbol: symbol_name,
+        exchange,
+        side: map_side(o.side.unwrap_or(1)),
+        quantity: o.qty.unwrap_or(0) as i32,
+        filled_quantity: o.filledQty.unwrap_or(0) as i32,
+        pending_quantity: (o.qty.unwrap_or(0) - o.filledQty.unwrap_or(0)) as i32,
+        price: o.limitPrice.unwrap_or(0.0),
+        trigger_price: o.stopPrice.unwrap_or(0.0),
+        average_price: o.tradedPrice.unwrap_or(0.0),
+        order_type: map_order_type(o.order_type.unwrap_or(2)),
+        product: map_product_type(o.productType.as_deref().unwrap_or("INTRADAY")),
+        status: map_order_status(o.status.unwrap_or(0)),
+        validity: "DAY".to_string(),
+        order_timestamp: o.orderDateTime.unwrap_or_default(),
+        exchange_timestamp: None,
+        rejection_reason: o.message,
+    }
+}
+
+/// Parse a message from the Fyers order socket
+///
+/// Order updates arrive as `{"s": "ok", "orders": {...}}` with the same
+/// fields as the order book; other frames (trades, positions, acks) are ignored.
+pub fn parse_order_update(text: &str) -> Option<Order> {
+    #[derive(Deserialize)]
+    struct FyersOrderUpdate {
+        orders: Option<FyersOrderData>,
+    }
+
+    let update: FyersOrderUpdate = serde_json::from_str(text).ok()?;
+    update.orders.filter(|o| o.id.is_some()).map(map_fyers_order)
+}
+
 /// Fyers trade data from API
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -415,9 +481,17 @@ fn map_product_to_fyers(product: &str) -> String {
 // FyersBroker Implementation
 // ============================================================================
 
+/// Fyers API v3 limits (requests per second)
+const OUTBOUND_LIMITS: OutboundLimits = OutboundLimits {
+    orders: 10,
+    quotes: 10,
+    historical: 1,
+    general: 10,
+};
+
 /// Fyers broker implementation
 pub struct FyersBroker {
-    client: Client,
+    client: BrokerHttpClient,
 }
 
 impl FyersBroker {
@@ -427,12 +501,16 @@ impl FyersBroker {
             // - pool_idle_timeout: Keep idle connections for 120 seconds
             // - pool_max_idle_per_host: Max 20 idle connections per host
             // - timeout: 120 seconds for large historical data requests
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .pool_idle_timeout(std::time::Duration::from_secs(120))
-                .pool_max_idle_per_host(20)
-                .build()
-                .expect("Failed to create HTTP client"),
+            client: BrokerHttpClient::new(
+                "Fyers",
+                Client::builder()
+                    .timeout(std::time::Duration::from_secs(120))
+                    .pool_idle_timeout(std::time::Duration::from_secs(120))
+                    .pool_max_idle_per_host(20)
+                    .build()
+                    .expect("Failed to create HTTP client"),
+                OUTBOUND_LIMITS,
+            ),
         }
     }
 
@@ -491,6 +569,10 @@ impl Broker for FyersBroker {
         false // Fyers uses auth_code from OAuth flow
     }
 
+    fn outbound_status(&self) -> Option<OutboundStatus> {
+        Some(self.client.status())
+    }
+
     async fn authenticate(&self, credentials: BrokerCredentials) -> Result<AuthResponse> {
         let auth_code = credentials
             .auth_code
@@ -520,7 +602,7 @@ impl Broker for FyersBroker {
 
         let response = self
             .client
-            .post(format!("{}/validate-authcode", BASE_URL))
+            .post(EndpointClass::General, format!("{}/validate-authcode", BASE_URL))
             .json(&request)
             .send()
             .await?;
@@ -587,6 +669,8 @@ impl Broker for FyersBroker {
             validity: String,
             disclosed_qty: i32,
             offline_order: bool,
+            stop_loss: f64,
+            take_profit: f64,
         }
 
         let side = if order.side == "BUY" { 1 } else { -1 };
@@ -624,11 +708,13 @@ impl Broker for FyersBroker {
             validity: order.validity.clone(),
             disclosed_qty: order.disclosed_quantity.unwrap_or(0),
             offline_order: order.amo,
+            stop_loss: order.stoploss.unwrap_or(0.0),
+            take_profit: order.target.unwrap_or(0.0),
         };
 
         let response = self
             .client
-            .post(format!("{}/orders/sync", BASE_URL))
+            .post(EndpointClass::Order, format!("{}/orders/sync", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .json(&request)
             .send()
@@ -661,6 +747,60 @@ impl Broker for FyersBroker {
         })
     }
 
+    async fn calculate_margin(&self, auth_token: &str, orders: &[OrderRequest]) -> Result<MarginResult> {
+        let data: Vec<serde_json::Value> = orders
+            .iter()
+            .map(|o| {
+                let order_type = match o.order_type.as_str() {
+                    "LIMIT" => 1,
+                    "SL" => 4,
+                    "SL-M" => 3,
+                    _ => 2,
+                };
+                serde_json::json!({
+                    "symbol": o.broker_symbol.clone().unwrap_or_else(|| format!("{}:{}", o.exchange, o.symbol)),
+                    "qty": o.quantity,
+                    "side": if o.side == "BUY" { 1 } else { -1 },
+                    "type": order_type,
+                    "productType": map_product_to_fyers(&o.product),
+                    "limitPrice": o.price,
+                    "stopLoss": o.trigger_price.unwrap_or(0.0),
+                })
+            })
+            .collect();
+
+        let response = self
+            .client
+            .post(EndpointClass::General, SPAN_MARGIN_URL)
+            .headers(self.get_headers(Some(auth_token)))
+            .json(&serde_json::json!({ "data": data }))
+            .send()
+            .await?;
+
+        let result: FyersResponse<SpanMarginResponseData> = response.json().await?;
+
+        if result.s != "ok" {
+            return Err(AppError::Broker(
+                result.message.unwrap_or_else(|| "Margin calculation failed".to_string()),
+            ));
+        }
+
+        let margin = result.data.and_then(|d| d.data).unwrap_or_default();
+
+        // Fyers only returns the netted basket margin, not the hedge benefit
+        Ok(MarginResult {
+            span_margin: margin.span.unwrap_or(0.0),
+            exposure_margin: margin.exposure.unwrap_or(0.0),
+            total_margin: margin.total.unwrap_or(0.0),
+            hedge_benefit: 0.0,
+        })
+    }
+
+    fn supports_bracket(&self, order: &OrderRequest) -> bool {
+        // Fyers takes stopLoss/takeProfit in points but has no trailing stop
+        order.trailing_stoploss.is_none()
+    }
+
     async fn modify_order(
         &self,
         auth_token: &str,
@@ -698,7 +838,7 @@ impl Broker for FyersBroker {
 
         let response = self
             .client
-            .patch(format!("{}/orders/sync", BASE_URL))
+            .patch(EndpointClass::Order, format!("{}/orders/sync", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .json(&request)
             .send()
@@ -741,7 +881,7 @@ impl Broker for FyersBroker {
 
         let response = self
             .client
-            .delete(format!("{}/orders/sync", BASE_URL))
+            .delete(EndpointClass::Order, format!("{}/orders/sync", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .json(&request)
             .send()
@@ -767,7 +907,7 @@ impl Broker for FyersBroker {
     async fn get_order_book(&self, auth_token: &str) -> Result<Vec<Order>> {
         let response = self
             .client
-            .get(format!("{}/orders", BASE_URL))
+            .get(EndpointClass::General, format!("{}/orders", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .send()
             .await?;
@@ -785,40 +925,7 @@ impl Broker for FyersBroker {
             .and_then(|d| d.orderBook)
             .unwrap_or_default();
 
-        let mapped_orders: Vec<Order> = orders
-            .into_iter()
-            .map(|o| {
-                let exchange = get_exchange_name(
-                    o.exchange.unwrap_or(10),
-                    o.segment.unwrap_or(10),
-                );
-                let symbol_name = o.symbol
-                    .as_ref()
-                    .map(|s| Self::extract_symbol_name(s))
-                    .unwrap_or_default();
-
-                Order {
-                    order_id: o.id.unwrap_or_default(),
-                    exchange_order_id: None,
-                    symbol: symbol_name,
-                    exchange,
-                    side: map_side(o.side.unwrap_or(1)),
-                    quantity: o.qty.unwrap_or(0) as i32,
-                    filled_quantity: o.filledQty.unwrap_or(0) as i32,
-                    pending_quantity: (o.qty.unwrap_or(0) - o.filledQty.unwrap_or(0)) as i32,
-                    price: o.limitPrice.unwrap_or(0.0),
-                    trigger_price: o.stopPrice.unwrap_or(0.0),
-                    average_price: o.tradedPrice.unwrap_or(0.0),
-                    order_type: map_order_type(o.order_type.unwrap_or(2)),
-                    product: map_product_type(o.productType.as_deref().unwrap_or("INTRADAY")),
-                    status: map_order_status(o.status.unwrap_or(0)),
-                    validity: "DAY".to_string(),
-                    order_timestamp: o.orderDateTime.unwrap_or_default(),
-                    exchange_timestamp: None,
-                    rejection_reason: o.message,
-                }
-            })
-            .collect();
+        let mapped_orders: Vec<Order> = orders.into_iter().map(map_fyers_order).collect();
 
         Ok(mapped_orders)
     }
@@ -826,7 +933,7 @@ impl Broker for FyersBroker {
     async fn get_trade_book(&self, auth_token: &str) -> Result<Vec<Order>> {
         let response = self
             .client
-            .get(format!("{}/tradebook", BASE_URL))
+            .get(EndpointClass::General, format!("{}/tradebook", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .send()
             .await?;
@@ -885,7 +992,7 @@ impl Broker for FyersBroker {
     async fn get_positions(&self, auth_token: &str) -> Result<Vec<Position>> {
         let response = self
             .client
-            .get(format!("{}/positions", BASE_URL))
+            .get(EndpointClass::General, format!("{}/positions", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .send()
             .await?;
@@ -944,7 +1051,7 @@ impl Broker for FyersBroker {
     async fn get_holdings(&self, auth_token: &str) -> Result<Vec<Holding>> {
         let response = self
             .client
-            .get(format!("{}/holdings", BASE_URL))
+            .get(EndpointClass::General, format!("{}/holdings", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .send()
             .await?;
@@ -1006,7 +1113,7 @@ impl Broker for FyersBroker {
     async fn get_funds(&self, auth_token: &str) -> Result<Funds> {
         let response = self
             .client
-            .get(format!("{}/funds", BASE_URL))
+            .get(EndpointClass::General, format!("{}/funds", BASE_URL))
             .headers(self.get_headers(Some(auth_token)))
             .send()
             .await?;
@@ -1071,7 +1178,7 @@ impl Broker for FyersBroker {
 
         let response = self
             .client
-            .get(format!("https://api-t1.fyers.in/data/quotes?symbols={}", encoded_symbols))
+            .get(EndpointClass::Quote, format!("https://api-t1.fyers.in/data/quotes?symbols={}", encoded_symbols))
             .headers(self.get_headers(Some(auth_token)))
             .send()
             .await?;
@@ -1129,7 +1236,7 @@ impl Broker for FyersBroker {
 
         let response = self
             .client
-            .get(format!(
+            .get(EndpointClass::Quote, format!(
                 "https://api-t1.fyers.in/data/depth?symbol={}&ohlcv_flag=1",
                 encoded_symbol
             ))
@@ -1200,7 +1307,7 @@ impl Broker for FyersBroker {
         ];
 
         for (exchange_key, url) in csv_urls {
-            match self.client.get(url).send().await {
+            match self.client.get(EndpointClass::General, url).send().await {
                 Ok(response) => {
                     if let Ok(csv_text) = response.text().await {
                         let symbols = Self::process_fyers_csv(&csv_text, exchange_key);
@@ -0,0 +1,481 @@
+//! Shared outbound HTTP client for broker adapters
+//!
+//! Every broker call goes through a `BrokerHttpClient`, which:
+//! - throttles requests per endpoint class (orders, quotes, historical data,
+//!   everything else) to the broker's published limits, waiting for a token
+//!   instead of letting bursts from basket, split or option-chain calls hit 429s
+//! - retries idempotent GETs on timeouts, connection errors, 429 and 502-504
+//!   with jittered exponential backoff (honouring `Retry-After`)
+//! - opens a circuit breaker after repeated failures so calls fail fast while
+//!   the broker is down, probing again after a cooldown
+//!
+//! Order placement is never retried: a timed-out POST may still have reached
+//! the exchange.
+
+use crate::error::{AppError, Result};
+use crate::webhook::rate_limiter::TokenBucket;
+use parking_lot::Mutex;
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, Client, IntoUrl, Method, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Retries of an idempotent GET after the first attempt
+const MAX_RETRIES: u32 = 3;
+
+/// First retry delay, doubled on every retry
+const BASE_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Longest delay between retries
+const MAX_BACKOFF: Duration = Duration::from_secs(4);
+
+/// Consecutive failures that open the circuit
+const FAILURE_THRESHOLD: u32 = 5;
+
+/// How long an open circuit fails fast before probing the broker again
+const OPEN_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Endpoint class, each with its own throttle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointClass {
+    /// Place, modify and cancel orders (including GTT)
+    Order,
+    /// Quotes and market depth
+    Quote,
+    /// Historical candles
+    Historical,
+    /// Books, positions, funds, login, master contracts
+    General,
+}
+
+/// Requests per second a broker allows per endpoint class
+#[derive(Debug, Clone, Copy)]
+pub struct OutboundLimits {
+    pub orders: u32,
+    pub quotes: u32,
+    pub historical: u32,
+    pub general: u32,
+}
+
+impl OutboundLimits {
+    fn for_class(&self, class: EndpointClass) -> u32 {
+        match class {
+            EndpointClass::Order => self.orders,
+            EndpointClass::Quote => self.quotes,
+            EndpointClass::Historical => self.historical,
+            EndpointClass::General => self.general,
+        }
+    }
+}
+
+/// Circuit breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls flow normally
+    Closed,
+    /// Calls fail fast until the cooldown ends
+    Open,
+    /// Cooldown over, one probe call is allowed through
+    HalfOpen,
+}
+
+/// Circuit breaker over consecutive broker failures
+#[derive(Debug)]
+struct CircuitBreaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// A half-open probe is in flight
+    probing: bool,
+}
+
+impl CircuitBreaker {
+    fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probing: false,
+        }
+    }
+
+    /// Whether a call may go out, or how long until the next probe
+    fn try_pass(&mut self, now: Instant) -> std::result::Result<(), Duration> {
+        match self.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                let elapsed = self.opened_at.map(|t| now.duration_since(t)).unwrap_or_default();
+                if elapsed < OPEN_COOLDOWN {
+                    return Err(OPEN_COOLDOWN - elapsed);
+                }
+                self.state = CircuitState::HalfOpen;
+                self.opened_at = Some(now);
+                self.probing = true;
+                Ok(())
+            }
+            CircuitState::HalfOpen => {
+                // A probe that never reported back (e.g. a dropped future)
+                // doesn't block further probes past one cooldown
+                let elapsed = self.opened_at.map(|t| now.duration_since(t)).unwrap_or(OPEN_COOLDOWN);
+                if self.probing && elapsed < OPEN_COOLDOWN {
+                    return Err(OPEN_COOLDOWN - elapsed);
+                }
+                self.opened_at = Some(now);
+                self.probing = true;
+                Ok(())
+            }
+        }
+    }
+
+    fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.probing = false;
+    }
+
+    /// Returns true if this failure opened the circuit
+    fn record_failure(&mut self, now: Instant) -> bool {
+        self.consecutive_failures += 1;
+        self.probing = false;
+        let opens = self.state == CircuitState::HalfOpen
+            || (self.state == CircuitState::Closed && self.consecutive_failures >= FAILURE_THRESHOLD);
+        if opens {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(now);
+        }
+        opens
+    }
+
+    fn retry_in(&self, now: Instant) -> Option<Duration> {
+        match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(opened)) => {
+                Some(OPEN_COOLDOWN.saturating_sub(now.duration_since(opened)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Outbound client health, surfaced in broker status
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboundStatus {
+    pub circuit: CircuitState,
+    pub consecutive_failures: u32,
+    /// Seconds until an open circuit probes the broker again
+    pub retry_in_secs: Option<u64>,
+    pub last_error: Option<String>,
+    pub requests_total: u64,
+    /// Requests that waited for a throttle token
+    pub requests_throttled: u64,
+    pub requests_retried: u64,
+    /// Requests failed fast by the open circuit
+    pub requests_rejected: u64,
+}
+
+#[derive(Debug, Default)]
+struct OutboundStats {
+    total: AtomicU64,
+    throttled: AtomicU64,
+    retried: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// Throttled, retrying HTTP client for one broker
+#[derive(Debug)]
+pub struct BrokerHttpClient {
+    broker: &'static str,
+    client: Client,
+    throttles: Mutex<HashMap<EndpointClass, TokenBucket>>,
+    breaker: Mutex<CircuitBreaker>,
+    last_error: Mutex<Option<String>>,
+    stats: OutboundStats,
+}
+
+impl BrokerHttpClient {
+    /// Wrap a configured `reqwest::Client` with the broker's limits
+    pub fn new(broker: &'static str, client: Client, limits: OutboundLimits) -> Self {
+        let throttles = [
+            EndpointClass::Order,
+            EndpointClass::Quote,
+            EndpointClass::Historical,
+            EndpointClass::General,
+        ]
+        .into_iter()
+        .map(|class| (class, TokenBucket::new(limits.for_class(class))))
+        .collect();
+
+        Self {
+            broker,
+            client,
+            throttles: Mutex::new(throttles),
+            breaker: Mutex::new(CircuitBreaker::new()),
+            last_error: Mutex::new(None),
+            stats: OutboundStats::default(),
+        }
+    }
+
+    pub fn get(&self, class: EndpointClass, url: impl IntoUrl) -> BrokerRequest<'_> {
+        self.request(class, Method::GET, url)
+    }
+
+    pub fn post(&self, class: EndpointClass, url: impl IntoUrl) -> BrokerRequest<'_> {
+        self.request(class, Method::POST, url)
+    }
+
+    pub fn put(&self, class: EndpointClass, url: impl IntoUrl) -> BrokerRequest<'_> {
+        self.request(class, Method::PUT, url)
+    }
+
+    pub fn patch(&self, class: EndpointClass, url: impl IntoUrl) -> BrokerRequest<'_> {
+        self.request(class, Method::PATCH, url)
+    }
+
+    pub fn delete(&self, class: EndpointClass, url: impl IntoUrl) -> BrokerRequest<'_> {
+        self.request(class, Method::DELETE, url)
+    }
+
+    fn request(&self, class: EndpointClass, method: Method, url: impl IntoUrl) -> BrokerRequest<'_> {
+        BrokerRequest {
+            client: self,
+            class,
+            builder: self.client.request(method, url),
+        }
+    }
+
+    /// Current circuit and request counters
+    pub fn status(&self) -> OutboundStatus {
+        let breaker = self.breaker.lock();
+        OutboundStatus {
+            circuit: breaker.state,
+            consecutive_failures: breaker.consecutive_failures,
+            retry_in_secs: breaker.retry_in(Instant::now()).map(|d| d.as_secs_f64().ceil() as u64),
+            last_error: self.last_error.lock().clone(),
+            requests_total: self.stats.total.load(Ordering::Relaxed),
+            requests_throttled: self.stats.throttled.load(Ordering::Relaxed),
+            requests_retried: self.stats.retried.load(Ordering::Relaxed),
+            requests_rejected: self.stats.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Wait for a throttle token of this class
+    async fn throttle(&self, class: EndpointClass) {
+        let mut waited = false;
+        loop {
+            let wait = {
+                let mut throttles = self.throttles.lock();
+                let Some(bucket) = throttles.get_mut(&class) else {
+                    return;
+                };
+                if bucket.try_acquire() {
+                    return;
+                }
+                bucket.time_until_available()
+            };
+            if !waited {
+                waited = true;
+                self.stats.throttled.fetch_add(1, Ordering::Relaxed);
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    async fn execute(&self, class: EndpointClass, builder: RequestBuilder) -> Result<Response> {
+        let request = builder.build()?;
+        let idempotent = request.method() == Method::GET;
+        self.stats.total.fetch_add(1, Ordering::Relaxed);
+
+        if let Err(retry_in) = self.breaker.lock().try_pass(Instant::now()) {
+            self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(AppError::Broker(format!(
+                "{} API is unavailable after repeated failures, retrying in {}s",
+                self.broker,
+                retry_in.as_secs_f64().ceil() as u64
+            )));
+        }
+
+        let mut attempt = 0;
+        let mut request = Some(request);
+        loop {
+            // Keep a copy for a possible retry (GETs have no body to clone)
+            let current = request.take().expect("request is set for every attempt");
+            if idempotent && attempt < MAX_RETRIES {
+                request = current.try_clone();
+            }
+
+            self.throttle(class).await;
+            let result = self.client.execute(current).await;
+
+            let retry_after = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    Some(parse_retry_after(response).unwrap_or_else(|| backoff(attempt)))
+                }
+                Err(e) if e.is_timeout() || e.is_connect() => Some(backoff(attempt)),
+                _ => None,
+            };
+
+            match (retry_after, request.is_some()) {
+                (Some(delay), true) => {
+                    attempt += 1;
+                    self.stats.retried.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "{} {:?} request failed, retry {}/{} in {:?}",
+                        self.broker,
+                        class,
+                        attempt,
+                        MAX_RETRIES,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                _ => {
+                    self.record_outcome(&result);
+                    return result.map_err(Into::into);
+                }
+            }
+        }
+    }
+
+    /// Feed a final outcome to the circuit breaker
+    ///
+    /// Transport errors and 5xx count as failures; any other response,
+    /// including 4xx and 429, shows the broker is up.
+    fn record_outcome(&self, result: &std::result::Result<Response, reqwest::Error>) {
+        let failure = match result {
+            Ok(response) if response.status().is_server_error() => {
+                Some(format!("HTTP {}", response.status()))
+            }
+            Ok(_) => None,
+            Err(e) if e.is_timeout() || e.is_connect() => Some(e.to_string()),
+            Err(_) => None,
+        };
+
+        let mut breaker = self.breaker.lock();
+        match failure {
+            Some(error) => {
+                if breaker.record_failure(Instant::now()) {
+                    tracing::error!(
+                        "{} circuit opened after {} consecutive failures: {}",
+                        self.broker,
+                        breaker.consecutive_failures,
+                        error
+                    );
+                }
+                *self.last_error.lock() = Some(error);
+            }
+            None => {
+                if breaker.state != CircuitState::Closed {
+                    tracing::info!("{} circuit closed, broker is responding again", self.broker);
+                }
+                breaker.record_success();
+            }
+        }
+    }
+}
+
+/// A request being built against a `BrokerHttpClient`
+pub struct BrokerRequest<'a> {
+    client: &'a BrokerHttpClient,
+    class: EndpointClass,
+    builder: RequestBuilder,
+}
+
+impl BrokerRequest<'_> {
+    pub fn headers(mut self, headers: reqwest::header::HeaderMap) -> Self {
+        self.builder = self.builder.headers(headers);
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+        self.builder = self.builder.json(json);
+        self
+    }
+
+    pub fn form<T: Serialize + ?Sized>(mut self, form: &T) -> Self {
+        self.builder = self.builder.form(form);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.builder = self.builder.timeout(timeout);
+        self
+    }
+
+    /// Send through the throttle, retry policy and circuit breaker
+    pub async fn send(self) -> Result<Response> {
+        self.client.execute(self.class, self.builder).await
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// `Retry-After` in seconds, capped at `MAX_BACKOFF`
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .map(|secs| Duration::from_secs_f64(secs.max(0.0)).min(MAX_BACKOFF))
+}
+
+/// Exponential backoff with jitter: half the delay fixed, half random
+fn backoff(attempt: u32) -> Duration {
+    let delay = BASE_BACKOFF.saturating_mul(1 << attempt.min(8)).min(MAX_BACKOFF);
+    let half = delay / 2;
+    half + half.mul_f64(rand::thread_rng().gen::<f64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker_transitions() {
+        let mut breaker = CircuitBreaker::new();
+        let start = Instant::now();
+
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            assert!(!breaker.record_failure(start));
+        }
+        assert!(breaker.try_pass(start).is_ok());
+        assert!(breaker.record_failure(start));
+        assert_eq!(breaker.state, CircuitState::Open);
+        assert!(breaker.try_pass(start + Duration::from_secs(1)).is_err());
+
+        // After the cooldown one probe goes through; a failed probe reopens
+        let later = start + OPEN_COOLDOWN;
+        assert!(breaker.try_pass(later).is_ok());
+        assert_eq!(breaker.state, CircuitState::HalfOpen);
+        assert!(breaker.try_pass(later).is_err());
+        assert!(breaker.record_failure(later));
+        assert_eq!(breaker.retry_in(later), Some(OPEN_COOLDOWN));
+
+        let much_later = later + OPEN_COOLDOWN;
+        assert!(breaker.try_pass(much_later).is_ok());
+        breaker.record_success();
+        assert_eq!(breaker.state, CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_backoff_is_jittered_and_capped() {
+        for attempt in 0..10 {
+            let delay = backoff(attempt);
+            let full = BASE_BACKOFF.saturating_mul(1 << attempt.min(8)).min(MAX_BACKOFF);
+            assert!(delay >= full / 2 && delay <= full);
+        }
+    }
+}
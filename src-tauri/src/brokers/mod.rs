@@ -1,11 +1,12 @@
 //! Broker adapters module
 
 pub mod types;
+pub mod http;
 pub mod angel;
 pub mod zerodha;
 pub mod fyers;
 
-use crate::error::Result;
+use crate::error::{AppError, Result};
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -26,12 +27,24 @@ pub trait Broker: Send + Sync {
     /// Whether this broker requires TOTP for login
     fn requires_totp(&self) -> bool;
 
+    /// Throttle, retry and circuit breaker state of the outbound client
+    fn outbound_status(&self) -> Option<http::OutboundStatus> {
+        None
+    }
+
     /// Authenticate with broker
     async fn authenticate(&self, credentials: BrokerCredentials) -> Result<AuthResponse>;
 
     /// Place a new order
     async fn place_order(&self, auth_token: &str, order: OrderRequest) -> Result<OrderResponse>;
 
+    /// Whether the broker places this bracket/cover order natively
+    ///
+    /// BO/CO orders the broker can't take are emulated locally by `BracketService`.
+    fn supports_bracket(&self, _order: &OrderRequest) -> bool {
+        false
+    }
+
     /// Modify an existing order
     async fn modify_order(
         &self,
@@ -78,8 +91,42 @@ pub trait Broker: Send + Sync {
         symbol: &str,
     ) -> Result<MarketDepth>;
 
+    /// Calculate the margin required for a basket of orders
+    ///
+    /// Orders must carry `broker_symbol`/`symbol_token` from the symbol cache.
+    async fn calculate_margin(&self, _auth_token: &str, _orders: &[OrderRequest]) -> Result<MarginResult> {
+        Err(AppError::Broker(format!("{} does not support margin calculation", self.name())))
+    }
+
     /// Download master contract
     async fn download_master_contract(&self, auth_token: &str) -> Result<Vec<SymbolData>>;
+
+    /// Whether the broker holds this GTT order natively
+    ///
+    /// GTTs the broker can't hold are emulated locally by `GttService`.
+    fn supports_gtt(&self, _gtt: &GttRequest) -> bool {
+        false
+    }
+
+    /// Place a GTT order, returns the broker's GTT ID
+    async fn place_gtt(&self, _auth_token: &str, _gtt: GttRequest) -> Result<String> {
+        Err(AppError::Broker(format!("{} does not support GTT orders", self.name())))
+    }
+
+    /// Modify an existing GTT order, returns the GTT ID
+    async fn modify_gtt(&self, _auth_token: &str, _gtt_id: &str, _gtt: GttRequest) -> Result<String> {
+        Err(AppError::Broker(format!("{} does not support GTT orders", self.name())))
+    }
+
+    /// Delete a GTT order
+    async fn delete_gtt(&self, _auth_token: &str, _gtt_id: &str) -> Result<()> {
+        Err(AppError::Broker(format!("{} does not support GTT orders", self.name())))
+    }
+
+    /// Get GTT order book
+    async fn get_gtt_orders(&self, _auth_token: &str) -> Result<Vec<GttOrder>> {
+        Ok(vec![])
+    }
 }
 
 /// Broker credentials for authentication
@@ -11,11 +11,23 @@ pub struct OrderRequest {
     pub quantity: i32,
     pub price: f64,
     pub order_type: String,   // MARKET, LIMIT, SL, SL-M
-    pub product: String,      // CNC, MIS, NRML
+    pub product: String,      // CNC, MIS, NRML, BO, CO
     pub validity: String,     // DAY, IOC
     pub trigger_price: Option<f64>,
     pub disclosed_quantity: Option<i32>,
     pub amo: bool,
+    /// Bracket/cover order target, in points from the entry price (BO only)
+    #[serde(default)]
+    pub target: Option<f64>,
+    /// Bracket/cover order stoploss, in points from the entry price
+    #[serde(default)]
+    pub stoploss: Option<f64>,
+    /// Trailing stoploss step in points (BO/CO)
+    #[serde(default)]
+    pub trailing_stoploss: Option<f64>,
+    /// Client order id, sent to the broker as an order tag where supported
+    #[serde(default)]
+    pub tag: Option<String>,
     /// Broker-specific symbol format (e.g., "NSE:RELIANCE-EQ" for Fyers)
     /// Set by OrderService after looking up from symbol cache
     #[serde(skip_deserializing)]
@@ -26,6 +38,25 @@ pub struct OrderRequest {
     pub symbol_token: Option<String>,
 }
 
+impl OrderRequest {
+    /// Whether this is a bracket or cover order
+    pub fn is_bracket(&self) -> bool {
+        self.product == "BO" || self.product == "CO"
+    }
+
+    /// Order tag in the form brokers accept (alphanumeric, max 20 chars)
+    pub fn broker_tag(&self) -> Option<String> {
+        let tag: String = self
+            .tag
+            .as_deref()?
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .take(20)
+            .collect();
+        (!tag.is_empty()).then_some(tag)
+    }
+}
+
 /// Modify order request
 #[derive(Debug, Clone, Deserialize)]
 pub struct ModifyOrderRequest {
@@ -115,6 +146,18 @@ pub struct Funds {
     pub collateral: f64,
 }
 
+/// Margin required for a basket of orders
+///
+/// `hedge_benefit` is the margin saved by the legs offsetting each other
+/// (sum of standalone margins minus the basket margin).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarginResult {
+    pub span_margin: f64,
+    pub exposure_margin: f64,
+    pub total_margin: f64,
+    pub hedge_benefit: f64,
+}
+
 /// Quote
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Quote {
@@ -171,3 +214,166 @@ pub struct SymbolData {
     /// Broker's exchange code (e.g., "NSE" for the broker's API)
     pub brexchange: Option<String>,
 }
+
+/// GTT (good-till-triggered) order leg placed when the trigger fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GttLeg {
+    pub side: String,         // BUY or SELL
+    pub quantity: i32,
+    pub order_type: String,   // LIMIT or MARKET
+    pub price: f64,
+    pub product: String,      // CNC, NRML, MIS
+}
+
+/// GTT order request (single trigger or two-leg OCO)
+#[derive(Debug, Clone, Deserialize)]
+pub struct GttRequest {
+    pub symbol: String,
+    pub exchange: String,
+    /// "single" or "two-leg" (OCO)
+    pub trigger_type: String,
+    /// One trigger for single, [stoploss, target] for two-leg
+    pub trigger_prices: Vec<f64>,
+    /// Last traded price when the GTT was created
+    pub last_price: f64,
+    /// One leg per trigger price, in the same order
+    pub legs: Vec<GttLeg>,
+    /// Broker-specific symbol format
+    /// Set by GttService after looking up from symbol cache
+    #[serde(skip_deserializing)]
+    pub broker_symbol: Option<String>,
+    /// Exchange token (required for Angel One)
+    /// Set by GttService after looking up from symbol cache
+    #[serde(skip_deserializing)]
+    pub symbol_token: Option<String>,
+}
+
+/// GTT order from the broker's GTT book or the local emulator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GttOrder {
+    pub gtt_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub trigger_type: String,
+    pub trigger_prices: Vec<f64>,
+    pub last_price: f64,
+    pub legs: Vec<GttLeg>,
+    pub status: String,
+    /// Order placed when the GTT fired (emulated GTTs only)
+    pub order_id: Option<String>,
+    /// true if the GTT is watched locally instead of held by the broker
+    pub emulated: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Bracket (BO) or cover (CO) order emulated locally
+///
+/// The entry is placed as an MIS order. Once it fills, the stoploss (and, for
+/// BO, the target) are held as an emulated GTT linked by `gtt_id`, so the exit
+/// legs act as OCO and survive a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketOrder {
+    pub bracket_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub side: String,
+    pub quantity: i32,
+    /// BO or CO
+    pub product: String,
+    pub entry_order_id: Option<String>,
+    /// Average fill price of the entry, 0 until filled
+    pub entry_price: f64,
+    pub target: Option<f64>,
+    pub stoploss: f64,
+    pub trailing_stoploss: Option<f64>,
+    /// Current stoploss trigger price (moves when trailing)
+    pub stop_price: f64,
+    /// Best price seen since entry, used for trailing
+    pub best_price: f64,
+    /// Emulated GTT holding the exit legs
+    pub gtt_id: Option<String>,
+    /// pending, open, closed, cancelled, rejected, error
+    pub status: String,
+    pub message: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Execution algo order (TWAP, VWAP, iceberg)
+///
+/// The parent quantity is worked as child orders by the algo monitor. All
+/// progress is persisted, so a running algo resumes after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlgoOrder {
+    pub algo_id: String,
+    /// TWAP, VWAP or ICEBERG
+    pub algo_type: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub side: String,
+    pub product: String,
+    pub quantity: i32,
+    /// Child orders are LIMIT at this price, MARKET when unset
+    pub limit_price: Option<f64>,
+    /// TWAP/VWAP execution window
+    pub duration_secs: Option<i64>,
+    /// TWAP: number of equal slices over the window
+    pub num_slices: Option<i32>,
+    /// VWAP: share of market volume to trade (0.1 = 10%)
+    pub participation_rate: Option<f64>,
+    /// ICEBERG: quantity shown per clip
+    pub display_quantity: Option<i32>,
+    /// Exchange disclosed quantity sent with each child order
+    pub disclosed_quantity: Option<i32>,
+    pub placed_quantity: i32,
+    pub filled_quantity: i32,
+    pub slices_placed: i32,
+    /// ICEBERG: clip currently working
+    pub active_order_id: Option<String>,
+    /// VWAP: cumulative market volume when the algo started
+    pub baseline_volume: Option<i64>,
+    /// VWAP: market volume traded since the algo started
+    pub market_volume: i64,
+    pub child_order_ids: Vec<String>,
+    /// TWAP: when the next slice is due (RFC 3339)
+    pub next_slice_at: Option<String>,
+    /// End of the execution window (RFC 3339)
+    pub ends_at: Option<String>,
+    /// running, completed, cancelled, failed
+    pub status: String,
+    pub message: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Position or portfolio exit rule (target, stoploss, trailing)
+///
+/// Evaluated on live ticks by the exit monitor; when a level is hit the
+/// position (or the whole book, for portfolio rules) is closed at market.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitRule {
+    pub rule_id: String,
+    /// position or portfolio
+    pub scope: String,
+    /// Position key, unset for portfolio rules
+    pub exchange: Option<String>,
+    pub symbol: Option<String>,
+    pub product: Option<String>,
+    /// price (absolute), points or percent (from average price), mtm (rupees)
+    pub rule_type: String,
+    /// Profit level: a price for price rules, otherwise a positive gain
+    pub target: Option<f64>,
+    /// Loss level: a price for price rules, otherwise a positive loss
+    pub stoploss: Option<f64>,
+    /// Trailing distance from the best value seen, in rule units
+    pub trailing: Option<f64>,
+    /// Best value seen since the rule was created, used for trailing
+    pub best_value: Option<f64>,
+    /// active, triggered, cancelled, error
+    pub status: String,
+    pub message: Option<String>,
+    pub triggered_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
@@ -1,5 +1,6 @@
 //! Zerodha Kite broker adapter
 
+use crate::brokers::http::{BrokerHttpClient, EndpointClass, OutboundLimits, OutboundStatus};
 use crate::brokers::{AuthResponse, Broker, BrokerCredentials};
 use crate::brokers::types::*;
 use crate::error::{AppError, Result};
@@ -11,21 +12,33 @@ use sha2::{Digest, Sha256};
 const BASE_URL: &str = "https://api.kite.trade";
 const MASTER_CONTRACT_URL: &str = "https://api.kite.trade/instruments";
 
+/// Kite Connect limits (requests per second)
+const OUTBOUND_LIMITS: OutboundLimits = OutboundLimits {
+    orders: 10,
+    quotes: 1,
+    historical: 3,
+    general: 10,
+};
+
 /// Zerodha Kite broker implementation
 pub struct ZerodhaBroker {
-    client: Client,
+    client: BrokerHttpClient,
 }
 
 impl ZerodhaBroker {
     pub fn new() -> Self {
         Self {
             // Create HTTP client with connection pooling (matching Flask httpx_client)
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .pool_idle_timeout(std::time::Duration::from_secs(120))
-                .pool_max_idle_per_host(20)
-                .build()
-                .expect("Failed to create HTTP client"),
+            client: BrokerHttpClient::new(
+                "Zerodha",
+                Client::builder()
+                    .timeout(std::time::Duration::from_secs(120))
+                    .pool_idle_timeout(std::time::Duration::from_secs(120))
+                    .pool_max_idle_per_host(20)
+                    .build()
+                    .expect("Failed to create HTTP client"),
+                OUTBOUND_LIMITS,
+            ),
         }
     }
 
@@ -104,6 +117,49 @@ struct KiteOrderData {
     status_message: Option<String>,
 }
 
+/// Map a Kite order to the common order type
+fn map_kite_order(o: KiteOrderData) -> Order {
+    Order {
+        order_id: o.order_id,
+        exchange_order_id: o.exchange_order_id,
+        symbol: o.tradingsymbol,
+        exchange: o.exchange,
+        side: o.transaction_type,
+        quantity: o.quantity,
+        filled_quantity: o.filled_quantity,
+        pending_quantity: o.pending_quantity,
+        price: o.price,
+        trigger_price: o.trigger_price,
+        average_price: o.average_price,
+        order_type: o.order_type,
+        product: o.product,
+        status: o.status,
+        validity: o.validity,
+        order_timestamp: o.order_timestamp.unwrap_or_default(),
+        exchange_timestamp: o.exchange_timestamp,
+        rejection_reason: o.status_message,
+    }
+}
+
+/// Parse an order update pushed on the Kite ticker socket
+///
+/// Kite sends `{"type": "order", "data": {...}}` text frames carrying the
+/// same fields as the order book.
+pub fn parse_order_update(text: &str) -> Option<Order> {
+    #[derive(Deserialize)]
+    struct KiteOrderUpdate {
+        #[serde(rename = "type")]
+        kind: String,
+        data: Option<KiteOrderData>,
+    }
+
+    let update: KiteOrderUpdate = serde_json::from_str(text).ok()?;
+    if update.kind != "order" {
+        return None;
+    }
+    update.data.map(map_kite_order)
+}
+
 // Positions response
 #[derive(Deserialize, Default)]
 #[allow(dead_code)]
@@ -215,6 +271,25 @@ struct KiteMarginUtilised {
     payout: f64,
 }
 
+// Basket margin response
+#[derive(Deserialize, Default)]
+struct KiteBasketMargin {
+    #[serde(default)]
+    initial: KiteOrderMargin,
+    #[serde(rename = "final", default)]
+    final_margin: KiteOrderMargin,
+}
+
+#[derive(Deserialize, Default)]
+struct KiteOrderMargin {
+    #[serde(default)]
+    span: f64,
+    #[serde(default)]
+    exposure: f64,
+    #[serde(default)]
+    total: f64,
+}
+
 // Quote response
 #[derive(Deserialize)]
 #[allow(dead_code)]
@@ -265,6 +340,54 @@ struct KiteDepthLevel {
     orders: i32,
 }
 
+// GTT responses
+#[derive(Deserialize, Default)]
+struct KiteGttTriggerId {
+    trigger_id: i64,
+}
+
+#[derive(Deserialize)]
+struct KiteGttData {
+    id: i64,
+    #[serde(rename = "type", default)]
+    trigger_type: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    created_at: String,
+    #[serde(default)]
+    updated_at: String,
+    condition: KiteGttCondition,
+    #[serde(default)]
+    orders: Vec<KiteGttOrder>,
+}
+
+#[derive(Deserialize)]
+struct KiteGttCondition {
+    #[serde(default)]
+    exchange: String,
+    #[serde(default)]
+    tradingsymbol: String,
+    #[serde(default)]
+    trigger_values: Vec<f64>,
+    #[serde(default)]
+    last_price: f64,
+}
+
+#[derive(Deserialize)]
+struct KiteGttOrder {
+    #[serde(default)]
+    transaction_type: String,
+    #[serde(default)]
+    quantity: i32,
+    #[serde(default)]
+    order_type: String,
+    #[serde(default)]
+    product: String,
+    #[serde(default)]
+    price: f64,
+}
+
 #[async_trait]
 impl Broker for ZerodhaBroker {
     fn id(&self) -> &'static str {
@@ -283,6 +406,10 @@ impl Broker for ZerodhaBroker {
         false // Zerodha uses request_token from OAuth flow
     }
 
+    fn outbound_status(&self) -> Option<OutboundStatus> {
+        Some(self.client.status())
+    }
+
     async fn authenticate(&self, credentials: BrokerCredentials) -> Result<AuthResponse> {
         let request_token = credentials
             .request_token
@@ -302,7 +429,7 @@ impl Broker for ZerodhaBroker {
 
         let response = self
             .client
-            .post(format!("{}/session/token", BASE_URL))
+            .post(EndpointClass::General, format!("{}/session/token", BASE_URL))
             .form(&params)
             .send()
             .await?;
@@ -357,7 +484,15 @@ impl Broker for ZerodhaBroker {
 
         tracing::info!("Zerodha place_order: tradingsymbol={}", trading_symbol);
 
-        let variety = if order.amo { "amo" } else { "regular" };
+        // Cover orders are an MIS entry with a stoploss trigger price
+        let is_cover = order.product == "CO";
+        let variety = if is_cover {
+            "co"
+        } else if order.amo {
+            "amo"
+        } else {
+            "regular"
+        };
 
         let mut params = vec![
             ("tradingsymbol", trading_symbol),
@@ -365,7 +500,7 @@ impl Broker for ZerodhaBroker {
             ("transaction_type", order.side.clone()),
             ("order_type", order.order_type.clone()),
             ("quantity", order.quantity.to_string()),
-            ("product", order.product.clone()),
+            ("product", if is_cover { "MIS".to_string() } else { order.product.clone() }),
             ("validity", order.validity.clone()),
         ];
 
@@ -373,13 +508,25 @@ impl Broker for ZerodhaBroker {
             params.push(("price", order.price.to_string()));
         }
 
-        if let Some(tp) = order.trigger_price {
+        if is_cover {
+            let points = order.stoploss.unwrap_or(0.0);
+            let trigger = if order.side == "BUY" {
+                order.price - points
+            } else {
+                order.price + points
+            };
+            params.push(("trigger_price", trigger.to_string()));
+        } else if let Some(tp) = order.trigger_price {
             params.push(("trigger_price", tp.to_string()));
         }
 
+        if let Some(tag) = order.broker_tag() {
+            params.push(("tag", tag));
+        }
+
         let response = self
             .client
-            .post(format!("{}/orders/{}", BASE_URL, variety))
+            .post(EndpointClass::Order, format!("{}/orders/{}", BASE_URL, variety))
             .headers(self.get_headers(auth_token))
             .form(&params)
             .send()
@@ -441,7 +588,7 @@ impl Broker for ZerodhaBroker {
 
         let response = self
             .client
-            .put(format!("{}/orders/regular/{}", BASE_URL, order_id))
+            .put(EndpointClass::Order, format!("{}/orders/regular/{}", BASE_URL, order_id))
             .headers(self.get_headers(auth_token))
             .form(&params)
             .send()
@@ -485,7 +632,7 @@ impl Broker for ZerodhaBroker {
 
         let response = self
             .client
-            .delete(format!("{}/orders/{}/{}", BASE_URL, variety, order_id))
+            .delete(EndpointClass::Order, format!("{}/orders/{}/{}", BASE_URL, variety, order_id))
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -510,7 +657,7 @@ impl Broker for ZerodhaBroker {
     async fn get_order_book(&self, auth_token: &str) -> Result<Vec<Order>> {
         let response = self
             .client
-            .get(format!("{}/orders", BASE_URL))
+            .get(EndpointClass::General, format!("{}/orders", BASE_URL))
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -525,35 +672,13 @@ impl Broker for ZerodhaBroker {
 
         let orders = result.data.unwrap_or_default();
 
-        Ok(orders
-            .into_iter()
-            .map(|o| Order {
-                order_id: o.order_id,
-                exchange_order_id: o.exchange_order_id,
-                symbol: o.tradingsymbol,
-                exchange: o.exchange,
-                side: o.transaction_type,
-                quantity: o.quantity,
-                filled_quantity: o.filled_quantity,
-                pending_quantity: o.pending_quantity,
-                price: o.price,
-                trigger_price: o.trigger_price,
-                average_price: o.average_price,
-                order_type: o.order_type,
-                product: o.product,
-                status: o.status,
-                validity: o.validity,
-                order_timestamp: o.order_timestamp.unwrap_or_default(),
-                exchange_timestamp: o.exchange_timestamp,
-                rejection_reason: o.status_message,
-            })
-            .collect())
+        Ok(orders.into_iter().map(map_kite_order).collect())
     }
 
     async fn get_trade_book(&self, auth_token: &str) -> Result<Vec<Order>> {
         let response = self
             .client
-            .get(format!("{}/trades", BASE_URL))
+            .get(EndpointClass::General, format!("{}/trades", BASE_URL))
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -596,7 +721,7 @@ impl Broker for ZerodhaBroker {
     async fn get_positions(&self, auth_token: &str) -> Result<Vec<Position>> {
         let response = self
             .client
-            .get(format!("{}/portfolio/positions", BASE_URL))
+            .get(EndpointClass::General, format!("{}/portfolio/positions", BASE_URL))
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -640,7 +765,7 @@ impl Broker for ZerodhaBroker {
     async fn get_holdings(&self, auth_token: &str) -> Result<Vec<Holding>> {
         let response = self
             .client
-            .get(format!("{}/portfolio/holdings", BASE_URL))
+            .get(EndpointClass::General, format!("{}/portfolio/holdings", BASE_URL))
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -688,7 +813,7 @@ impl Broker for ZerodhaBroker {
     async fn get_funds(&self, auth_token: &str) -> Result<Funds> {
         let response = self
             .client
-            .get(format!("{}/user/margins", BASE_URL))
+            .get(EndpointClass::General, format!("{}/user/margins", BASE_URL))
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -760,7 +885,7 @@ impl Broker for ZerodhaBroker {
 
         let response = self
             .client
-            .get(&url)
+            .get(EndpointClass::Quote, &url)
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -831,7 +956,7 @@ impl Broker for ZerodhaBroker {
 
         let response = self
             .client
-            .get(&url)
+            .get(EndpointClass::Quote, &url)
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -891,7 +1016,7 @@ impl Broker for ZerodhaBroker {
         // Zerodha provides CSV format for instruments
         let response = self
             .client
-            .get(MASTER_CONTRACT_URL)
+            .get(EndpointClass::General, MASTER_CONTRACT_URL)
             .headers(self.get_headers(auth_token))
             .send()
             .await?;
@@ -988,9 +1113,216 @@ impl Broker for ZerodhaBroker {
 
         Ok(symbols)
     }
+
+    async fn calculate_margin(&self, auth_token: &str, orders: &[OrderRequest]) -> Result<MarginResult> {
+        let basket: Vec<serde_json::Value> = orders
+            .iter()
+            .map(|o| {
+                serde_json::json!({
+                    "exchange": o.exchange,
+                    "tradingsymbol": o.broker_symbol.clone().unwrap_or_else(|| o.symbol.clone()),
+                    "transaction_type": o.side,
+                    "variety": "regular",
+                    "product": o.product,
+                    "order_type": o.order_type,
+                    "quantity": o.quantity,
+                    "price": o.price,
+                    "trigger_price": o.trigger_price.unwrap_or(0.0),
+                })
+            })
+            .collect();
+
+        // `initial` margins each order on its own, `final` nets the basket
+        let response = self
+            .client
+            .post(EndpointClass::General, format!("{}/margins/basket?consider_positions=true&mode=compact", BASE_URL))
+            .headers(self.get_headers(auth_token))
+            .json(&basket)
+            .send()
+            .await?;
+
+        let result: KiteResponse<KiteBasketMargin> = response.json().await?;
+
+        if result.status != "success" {
+            return Err(AppError::Broker(
+                result.message.unwrap_or_else(|| "Margin calculation failed".to_string()),
+            ));
+        }
+
+        let data = result.data.unwrap_or_default();
+
+        Ok(MarginResult {
+            span_margin: data.final_margin.span,
+            exposure_margin: data.final_margin.exposure,
+            total_margin: data.final_margin.total,
+            hedge_benefit: (data.initial.total - data.final_margin.total).max(0.0),
+        })
+    }
+
+    fn supports_bracket(&self, order: &OrderRequest) -> bool {
+        // Kite has discontinued bracket orders; cover orders need an absolute
+        // trigger, so only priced entries without trailing go native
+        order.product == "CO" && order.price > 0.0 && order.trailing_stoploss.is_none()
+    }
+
+    fn supports_gtt(&self, gtt: &GttRequest) -> bool {
+        // Kite holds both single and two-leg (OCO) triggers
+        matches!(gtt.trigger_type.as_str(), "single" | "two-leg")
+    }
+
+    async fn place_gtt(&self, auth_token: &str, gtt: GttRequest) -> Result<String> {
+        let params = Self::gtt_params(&gtt);
+
+        let response = self
+            .client
+            .post(EndpointClass::Order, format!("{}/gtt/triggers", BASE_URL))
+            .headers(self.get_headers(auth_token))
+            .form(&params)
+            .send()
+            .await?;
+
+        let result: KiteResponse<KiteGttTriggerId> = response.json().await?;
+
+        if result.status != "success" {
+            return Err(AppError::Broker(
+                result.message.unwrap_or_else(|| "GTT placement failed".to_string()),
+            ));
+        }
+
+        let data = result
+            .data
+            .ok_or_else(|| AppError::Broker("No trigger ID in response".to_string()))?;
+
+        Ok(data.trigger_id.to_string())
+    }
+
+    async fn modify_gtt(&self, auth_token: &str, gtt_id: &str, gtt: GttRequest) -> Result<String> {
+        let params = Self::gtt_params(&gtt);
+
+        let response = self
+            .client
+            .put(EndpointClass::Order, format!("{}/gtt/triggers/{}", BASE_URL, gtt_id))
+            .headers(self.get_headers(auth_token))
+            .form(&params)
+            .send()
+            .await?;
+
+        let result: KiteResponse<KiteGttTriggerId> = response.json().await?;
+
+        if result.status != "success" {
+            return Err(AppError::Broker(
+                result.message.unwrap_or_else(|| "GTT modification failed".to_string()),
+            ));
+        }
+
+        Ok(result
+            .data
+            .map(|d| d.trigger_id.to_string())
+            .unwrap_or_else(|| gtt_id.to_string()))
+    }
+
+    async fn delete_gtt(&self, auth_token: &str, gtt_id: &str) -> Result<()> {
+        let response = self
+            .client
+            .delete(EndpointClass::Order, format!("{}/gtt/triggers/{}", BASE_URL, gtt_id))
+            .headers(self.get_headers(auth_token))
+            .send()
+            .await?;
+
+        let result: KiteResponse<KiteGttTriggerId> = response.json().await?;
+
+        if result.status != "success" {
+            return Err(AppError::Broker(
+                result.message.unwrap_or_else(|| "GTT deletion failed".to_string()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn get_gtt_orders(&self, auth_token: &str) -> Result<Vec<GttOrder>> {
+        let response = self
+            .client
+            .get(EndpointClass::General, format!("{}/gtt/triggers", BASE_URL))
+            .headers(self.get_headers(auth_token))
+            .send()
+            .await?;
+
+        let result: KiteResponse<Vec<KiteGttData>> = response.json().await?;
+
+        if result.status != "success" {
+            return Err(AppError::Broker(
+                result.message.unwrap_or_else(|| "Failed to fetch GTT orders".to_string()),
+            ));
+        }
+
+        Ok(result
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|g| GttOrder {
+                gtt_id: g.id.to_string(),
+                symbol: g.condition.tradingsymbol,
+                exchange: g.condition.exchange,
+                trigger_type: g.trigger_type,
+                trigger_prices: g.condition.trigger_values,
+                last_price: g.condition.last_price,
+                legs: g
+                    .orders
+                    .into_iter()
+                    .map(|o| GttLeg {
+                        side: o.transaction_type,
+                        quantity: o.quantity,
+                        order_type: o.order_type,
+                        price: o.price,
+                        product: o.product,
+                    })
+                    .collect(),
+                status: g.status,
+                order_id: None,
+                emulated: false,
+                created_at: g.created_at,
+                updated_at: g.updated_at,
+            })
+            .collect())
+    }
 }
 
 impl ZerodhaBroker {
+    /// Build the form parameters for creating/modifying a GTT trigger
+    fn gtt_params(gtt: &GttRequest) -> Vec<(&'static str, String)> {
+        let trading_symbol = gtt.broker_symbol.clone().unwrap_or_else(|| gtt.symbol.clone());
+
+        let condition = serde_json::json!({
+            "exchange": gtt.exchange,
+            "tradingsymbol": trading_symbol,
+            "trigger_values": gtt.trigger_prices,
+            "last_price": gtt.last_price,
+        });
+
+        let orders: Vec<serde_json::Value> = gtt
+            .legs
+            .iter()
+            .map(|leg| {
+                serde_json::json!({
+                    "exchange": gtt.exchange,
+                    "tradingsymbol": trading_symbol,
+                    "transaction_type": leg.side,
+                    "quantity": leg.quantity,
+                    "order_type": leg.order_type,
+                    "product": leg.product,
+                    "price": leg.price,
+                })
+            })
+            .collect();
+
+        vec![
+            ("type", gtt.trigger_type.clone()),
+            ("condition", condition.to_string()),
+            ("orders", serde_json::Value::Array(orders).to_string()),
+        ]
+    }
+
     /// Format expiry date from 2024-03-28 to 28-MAR-24
     fn format_expiry_date(date_str: &str) -> String {
         // Try to parse YYYY-MM-DD format
@@ -0,0 +1,40 @@
+//! Price alert commands
+
+use crate::db::sqlite::{AlertHistory, PriceAlert};
+use crate::error::Result;
+use crate::services::{AlertRequest, AlertService};
+use crate::state::AppState;
+use tauri::State;
+
+/// Create a price or indicator alert
+#[tauri::command]
+pub async fn create_alert(state: State<'_, AppState>, request: AlertRequest) -> Result<PriceAlert> {
+    tracing::info!("Creating alert: {:?}", request);
+    AlertService::create(&state, request)
+}
+
+/// Get all alerts, newest first
+#[tauri::command]
+pub async fn get_alerts(state: State<'_, AppState>) -> Result<Vec<PriceAlert>> {
+    AlertService::get_alerts(&state)
+}
+
+/// Cancel an active alert
+#[tauri::command]
+pub async fn cancel_alert(state: State<'_, AppState>, alert_id: String) -> Result<PriceAlert> {
+    tracing::info!("Cancelling alert: {}", alert_id);
+    AlertService::cancel(&state, &alert_id)
+}
+
+/// Delete an alert; its trigger history is kept
+#[tauri::command]
+pub async fn delete_alert(state: State<'_, AppState>, alert_id: String) -> Result<()> {
+    tracing::info!("Deleting alert: {}", alert_id);
+    AlertService::delete(&state, &alert_id)
+}
+
+/// Get the most recent alert triggers
+#[tauri::command]
+pub async fn get_alert_history(state: State<'_, AppState>, limit: Option<u32>) -> Result<Vec<AlertHistory>> {
+    AlertService::get_history(&state, limit)
+}
@@ -1,6 +1,6 @@
 //! API key management commands
 
-use crate::db::sqlite::ApiKeyInfo;
+use crate::db::sqlite::{ApiKeyInfo, ApiKeyRateLimits};
 use crate::error::Result;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
@@ -34,6 +34,14 @@ pub struct ApiKeyListResponse {
     pub api_keys: Vec<ApiKeyInfo>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateApiKeyRateLimitsRequest {
+    pub id: i64,
+    /// Overrides per second; omitted or null uses the global limit
+    #[serde(flatten)]
+    pub rate_limits: ApiKeyRateLimits,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DeleteApiKeyResponse {
     pub status: String,
@@ -56,6 +64,7 @@ pub async fn create_api_key(
         &permissions,
         &state.security,
     )?;
+    state.webhook_server.reload_api_keys(&state);
 
     Ok(CreateApiKeyResponse {
         status: "success".to_string(),
@@ -90,6 +99,7 @@ pub async fn delete_api_key(
     tracing::info!("Deleting API key: {}", name);
 
     let deleted = state.sqlite.delete_api_key(&name)?;
+    state.webhook_server.reload_api_keys(&state);
 
     Ok(DeleteApiKeyResponse {
         status: "success".to_string(),
@@ -111,6 +121,7 @@ pub async fn delete_api_key_by_id(
     tracing::info!("Deleting API key by id: {}", id);
 
     let deleted = state.sqlite.delete_api_key_by_id(id)?;
+    state.webhook_server.reload_api_keys(&state);
 
     Ok(DeleteApiKeyResponse {
         status: "success".to_string(),
@@ -123,6 +134,22 @@ pub async fn delete_api_key_by_id(
     })
 }
 
+/// Set or clear an API key's rate limit overrides
+#[tauri::command]
+pub async fn update_api_key_rate_limits(
+    state: State<'_, AppState>,
+    request: UpdateApiKeyRateLimitsRequest,
+) -> Result<bool> {
+    tracing::info!("Updating rate limits for API key {}: {:?}", request.id, request.rate_limits);
+
+    let updated = state
+        .sqlite
+        .update_api_key_rate_limits(request.id, &request.rate_limits)?;
+    state.webhook_server.reload_api_keys(&state);
+
+    Ok(updated)
+}
+
 /// Get the user's API key (decrypted)
 /// For single-user desktop app - returns the user's API key for display
 #[tauri::command]
@@ -150,6 +177,7 @@ pub async fn regenerate_api_key(state: State<'_, AppState>) -> Result<CreateApiK
     let (id, api_key) = state
         .sqlite
         .create_api_key("default", "read,write", &state.security)?;
+    state.webhook_server.reload_api_keys(&state);
 
     Ok(CreateApiKeyResponse {
         status: "success".to_string(),
@@ -204,6 +204,7 @@ pub async fn setup(state: State<'_, AppState>, request: SetupRequest) -> Result<
     match state.sqlite.create_api_key("default", "read,write", &state.security) {
         Ok((id, _api_key)) => {
             tracing::info!("Auto-generated API key (id: {}) for user '{}'", id, user.username);
+            state.webhook_server.reload_api_keys(&state);
         }
         Err(e) => {
             tracing::warn!("Failed to auto-generate API key: {}", e);
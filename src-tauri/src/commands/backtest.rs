@@ -0,0 +1,39 @@
+//! Backtest commands
+
+use crate::db::duckdb::models::{BacktestReport, BacktestSummary};
+use crate::error::Result;
+use crate::services::{BacktestRequest, BacktestService};
+use crate::state::AppState;
+use tauri::State;
+
+/// Run a backtest over stored candles or recorded ticks
+#[tauri::command]
+pub async fn run_backtest(state: State<'_, AppState>, request: BacktestRequest) -> Result<BacktestReport> {
+    tracing::info!(
+        "Running backtest on {}:{} from {} to {}",
+        request.exchange,
+        request.symbol,
+        request.from_date,
+        request.to_date
+    );
+    BacktestService::run(&state, request).await
+}
+
+/// Get stored backtest runs, newest first
+#[tauri::command]
+pub async fn get_backtests(state: State<'_, AppState>) -> Result<Vec<BacktestSummary>> {
+    BacktestService::get_backtests(&state)
+}
+
+/// Get a backtest report with its equity curve and trades
+#[tauri::command]
+pub async fn get_backtest(state: State<'_, AppState>, run_id: String) -> Result<BacktestReport> {
+    BacktestService::get_backtest(&state, &run_id)
+}
+
+/// Delete a backtest report
+#[tauri::command]
+pub async fn delete_backtest(state: State<'_, AppState>, run_id: String) -> Result<()> {
+    tracing::info!("Deleting backtest: {}", run_id);
+    BacktestService::delete_backtest(&state, &run_id)
+}
@@ -1,5 +1,6 @@
 //! Broker connection commands
 
+use crate::brokers::http::OutboundStatus;
 use crate::brokers::BrokerCredentials;
 use crate::error::{AppError, Result};
 use crate::state::{AppState, BrokerSession};
@@ -26,6 +27,8 @@ pub struct BrokerStatus {
     pub broker_id: Option<String>,
     pub user_id: Option<String>,
     pub authenticated_at: Option<String>,
+    /// Outbound throttle and circuit breaker state of the connected broker
+    pub outbound: Option<OutboundStatus>,
 }
 
 #[derive(Debug, Serialize)]
@@ -108,6 +111,10 @@ pub async fn get_broker_status(state: State<'_, AppState>) -> Result<BrokerStatu
     Ok(match state.get_broker_session() {
         Some(session) => BrokerStatus {
             connected: true,
+            outbound: state
+                .brokers
+                .get(&session.broker_id)
+                .and_then(|broker| broker.outbound_status()),
             broker_id: Some(session.broker_id),
             user_id: Some(session.user_id),
             authenticated_at: Some(session.authenticated_at.to_rfc3339()),
@@ -117,6 +124,7 @@ pub async fn get_broker_status(state: State<'_, AppState>) -> Result<BrokerStatu
             broker_id: None,
             user_id: None,
             authenticated_at: None,
+            outbound: None,
         },
     })
 }
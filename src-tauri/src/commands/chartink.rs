@@ -0,0 +1,138 @@
+//! Chartink strategy commands
+
+use crate::db::sqlite::{
+    ChartinkStrategy, ChartinkSymbol, CreateChartinkStrategyRequest, UpdateChartinkStrategyRequest,
+};
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use serde::Deserialize;
+use tauri::State;
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateChartinkSecurityRequest {
+    pub id: i64,
+    /// none, secret or hmac
+    pub auth_mode: Option<String>,
+    /// Empty string clears the strategy secret
+    pub webhook_secret: Option<String>,
+    /// Comma-separated IPs/CIDRs, empty string allows any
+    pub ip_allowlist: Option<String>,
+    pub replay_window_secs: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddChartinkSymbolRequest {
+    pub strategy_id: i64,
+    pub exchange: String,
+    pub symbol: String,
+    pub quantity: i32,
+    /// Product override (strategy default when omitted)
+    pub product: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateChartinkSymbolRequest {
+    pub id: i64,
+    pub exchange: Option<String>,
+    pub quantity: Option<i32>,
+    /// Empty string resets to the strategy default
+    pub product: Option<String>,
+}
+
+/// Get all Chartink strategies
+#[tauri::command]
+pub async fn get_chartink_strategies(state: State<'_, AppState>) -> Result<Vec<ChartinkStrategy>> {
+    state.sqlite.get_chartink_strategies()
+}
+
+/// Create a Chartink strategy
+#[tauri::command]
+pub async fn create_chartink_strategy(
+    state: State<'_, AppState>,
+    request: CreateChartinkStrategyRequest,
+) -> Result<ChartinkStrategy> {
+    tracing::info!("Creating Chartink strategy: {}", request.name);
+    state.sqlite.create_chartink_strategy(&request)
+}
+
+/// Update a Chartink strategy
+#[tauri::command]
+pub async fn update_chartink_strategy(
+    state: State<'_, AppState>,
+    id: i64,
+    request: UpdateChartinkStrategyRequest,
+) -> Result<ChartinkStrategy> {
+    tracing::info!("Updating Chartink strategy: {}", id);
+    state.sqlite.update_chartink_strategy(id, &request)
+}
+
+/// Update a Chartink strategy's webhook verification settings
+#[tauri::command]
+pub async fn update_chartink_strategy_security(
+    state: State<'_, AppState>,
+    request: UpdateChartinkSecurityRequest,
+) -> Result<ChartinkStrategy> {
+    tracing::info!("Updating webhook security for Chartink strategy: {}", request.id);
+
+    state.sqlite.update_chartink_strategy_security(
+        request.id,
+        request.auth_mode,
+        request.webhook_secret,
+        request.ip_allowlist,
+        request.replay_window_secs,
+    )
+}
+
+/// Delete a Chartink strategy
+#[tauri::command]
+pub async fn delete_chartink_strategy(state: State<'_, AppState>, id: i64) -> Result<()> {
+    tracing::info!("Deleting Chartink strategy: {}", id);
+    state.sqlite.delete_chartink_strategy(id)
+}
+
+/// Get the per-stock overrides of a Chartink strategy
+#[tauri::command]
+pub async fn get_chartink_symbols(
+    state: State<'_, AppState>,
+    strategy_id: i64,
+) -> Result<Vec<ChartinkSymbol>> {
+    state.sqlite.get_chartink_symbols(strategy_id)
+}
+
+/// Add a per-stock override to a Chartink strategy
+#[tauri::command]
+pub async fn add_chartink_symbol(
+    state: State<'_, AppState>,
+    request: AddChartinkSymbolRequest,
+) -> Result<ChartinkSymbol> {
+    state.sqlite.add_chartink_symbol(
+        request.strategy_id,
+        &request.exchange,
+        &request.symbol,
+        request.quantity,
+        request.product.as_deref(),
+    )
+}
+
+/// Update a per-stock override
+#[tauri::command]
+pub async fn update_chartink_symbol(
+    state: State<'_, AppState>,
+    request: UpdateChartinkSymbolRequest,
+) -> Result<ChartinkSymbol> {
+    state.sqlite.update_chartink_symbol(
+        request.id,
+        request.exchange.as_deref(),
+        request.quantity,
+        request.product.as_deref(),
+    )
+}
+
+/// Delete a per-stock override
+#[tauri::command]
+pub async fn delete_chartink_symbol(state: State<'_, AppState>, id: i64) -> Result<()> {
+    if !state.sqlite.delete_chartink_symbol(id)? {
+        return Err(AppError::NotFound(format!("Chartink symbol {} not found", id)));
+    }
+    Ok(())
+}
@@ -1,8 +1,8 @@
 //! Funds/margin commands
 
-use crate::brokers::types::Funds;
+use crate::brokers::types::{Funds, MarginResult, OrderRequest};
 use crate::error::Result;
-use crate::services::FundsService;
+use crate::services::{FundsService, MarginService};
 use crate::state::AppState;
 use tauri::State;
 
@@ -15,3 +15,16 @@ pub async fn get_funds(state: State<'_, AppState>) -> Result<Funds> {
     tracing::info!("Funds retrieved in {} mode", result.mode);
     Ok(result.funds)
 }
+
+/// Calculate the margin required for a basket of orders
+///
+/// Returns a sandbox leverage estimate when in analyze mode.
+#[tauri::command]
+pub async fn calculate_margin(
+    state: State<'_, AppState>,
+    orders: Vec<OrderRequest>,
+) -> Result<MarginResult> {
+    let result = MarginService::calculate_margin(&state, orders, None).await?;
+    tracing::info!("Margin calculated in {} mode", result.mode);
+    Ok(result.margin)
+}
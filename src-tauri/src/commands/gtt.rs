@@ -0,0 +1,54 @@
+//! GTT (good-till-triggered) order commands
+
+use crate::brokers::types::{GttOrder, GttRequest};
+use crate::error::Result;
+use crate::services::{GttResult, GttService};
+use crate::state::AppState;
+use tauri::State;
+
+/// Place a GTT order (single or two-leg OCO)
+///
+/// Emulated locally when the broker has no native GTT, and in analyze mode.
+#[tauri::command]
+pub async fn place_gtt_order(
+    state: State<'_, AppState>,
+    request: GttRequest,
+) -> Result<GttResult> {
+    tracing::info!("Placing GTT: {:?}", request);
+
+    let result = GttService::place_gtt(&state, request, None).await?;
+    tracing::info!("GTT {} placed in {} mode", result.gtt_id, result.mode);
+
+    Ok(result)
+}
+
+/// Modify an existing GTT order
+#[tauri::command]
+pub async fn modify_gtt_order(
+    state: State<'_, AppState>,
+    gtt_id: String,
+    request: GttRequest,
+) -> Result<GttResult> {
+    tracing::info!("Modifying GTT {}: {:?}", gtt_id, request);
+    GttService::modify_gtt(&state, &gtt_id, request, None).await
+}
+
+/// Cancel (delete) a GTT order
+#[tauri::command]
+pub async fn cancel_gtt_order(
+    state: State<'_, AppState>,
+    gtt_id: String,
+) -> Result<GttResult> {
+    tracing::info!("Cancelling GTT: {}", gtt_id);
+    GttService::cancel_gtt(&state, &gtt_id, None).await
+}
+
+/// Get GTT order book
+///
+/// Returns sandbox GTTs in analyze mode.
+#[tauri::command]
+pub async fn get_gtt_orders(state: State<'_, AppState>) -> Result<Vec<GttOrder>> {
+    let result = GttService::get_gtt_orders(&state, None).await?;
+    tracing::info!("GTT book retrieved in {} mode", result.mode);
+    Ok(result.gtt_orders)
+}
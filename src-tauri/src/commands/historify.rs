@@ -1,7 +1,8 @@
 //! Historical data (Historify) commands
 
-use crate::db::duckdb::models::MarketDataRow;
+use crate::db::duckdb::models::{MarketDataRow, RecorderSymbol};
 use crate::error::Result;
+use crate::services::RecorderService;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -70,3 +71,29 @@ pub async fn download_historical_data(
         message: "Historical data download not yet implemented".to_string(),
     })
 }
+
+/// Get the symbols the tick recorder captures
+#[tauri::command]
+pub async fn get_recorder_symbols(state: State<'_, AppState>) -> Result<Vec<RecorderSymbol>> {
+    RecorderService::get_symbols(&state)
+}
+
+/// Start recording ticks and 1m bars for a symbol
+#[tauri::command]
+pub async fn add_recorder_symbol(
+    state: State<'_, AppState>,
+    exchange: String,
+    symbol: String,
+) -> Result<Vec<RecorderSymbol>> {
+    RecorderService::add_symbol(&state, &exchange, &symbol)
+}
+
+/// Stop recording a symbol; recorded data is kept
+#[tauri::command]
+pub async fn remove_recorder_symbol(
+    state: State<'_, AppState>,
+    exchange: String,
+    symbol: String,
+) -> Result<Vec<RecorderSymbol>> {
+    RecorderService::remove_symbol(&state, &exchange, &symbol)
+}
@@ -5,16 +5,23 @@
 pub mod auth;
 pub mod broker;
 pub mod orders;
+pub mod gtt;
 pub mod positions;
 pub mod holdings;
 pub mod funds;
 pub mod quotes;
 pub mod symbols;
 pub mod strategy;
+pub mod chartink;
+pub mod risk;
 pub mod settings;
+pub mod notifications;
+pub mod alerts;
 pub mod api_keys;
 pub mod sandbox;
 pub mod order_logs;
 pub mod market;
 pub mod historify;
+pub mod backtest;
+pub mod replay;
 pub mod websocket;
@@ -0,0 +1,73 @@
+//! Outbound notification commands
+
+use crate::db::sqlite::{NotificationDelivery, NotificationTarget};
+use crate::error::{AppError, Result};
+use crate::services::{NotificationService, NotificationTargetRequest};
+use crate::state::AppState;
+use tauri::State;
+
+/// Get all notification targets
+#[tauri::command]
+pub async fn get_notification_targets(state: State<'_, AppState>) -> Result<Vec<NotificationTarget>> {
+    state.sqlite.get_notification_targets(false)
+}
+
+/// Create a notification target (HTTP, Telegram or Discord)
+#[tauri::command]
+pub async fn create_notification_target(
+    state: State<'_, AppState>,
+    request: NotificationTargetRequest,
+) -> Result<NotificationTarget> {
+    tracing::info!("Creating {} notification target '{}'", request.kind, request.name);
+    NotificationService::create_target(&state, request)
+}
+
+/// Update a notification target
+#[tauri::command]
+pub async fn update_notification_target(
+    state: State<'_, AppState>,
+    id: i64,
+    request: NotificationTargetRequest,
+) -> Result<NotificationTarget> {
+    tracing::info!("Updating notification target {}", id);
+    NotificationService::update_target(&state, id, request)
+}
+
+/// Delete a notification target
+#[tauri::command]
+pub async fn delete_notification_target(state: State<'_, AppState>, id: i64) -> Result<bool> {
+    if !state.sqlite.delete_notification_target(id)? {
+        return Err(AppError::NotFound(format!("Notification target {} not found", id)));
+    }
+    Ok(true)
+}
+
+/// Send a test notification and return its delivery record
+#[tauri::command]
+pub async fn test_notification_target(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<NotificationDelivery> {
+    NotificationService::send_test(&state, id).await
+}
+
+/// Get the notification delivery log, newest first
+#[tauri::command]
+pub async fn get_notification_log(
+    state: State<'_, AppState>,
+    target_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<NotificationDelivery>> {
+    state
+        .sqlite
+        .get_notification_deliveries(target_id, limit.unwrap_or(100).clamp(1, 1000))
+}
+
+/// Delete notification deliveries older than `days`, returning the count removed
+#[tauri::command]
+pub async fn clear_old_notification_log(state: State<'_, AppState>, days: i64) -> Result<usize> {
+    if days < 1 {
+        return Err(AppError::Validation("days must be at least 1".to_string()));
+    }
+    state.sqlite.prune_notification_deliveries(days)
+}
@@ -1,8 +1,11 @@
 //! Order management commands
 
-use crate::brokers::types::{Order, OrderRequest, ModifyOrderRequest};
+use crate::brokers::types::{AlgoOrder, BracketOrder, Order, OrderRequest, ModifyOrderRequest};
 use crate::error::Result;
-use crate::services::{OrderService, OrderbookService};
+use crate::services::{
+    AlgoEvent, AlgoOrderRequest, AlgoService, BracketEvent, BracketService, OrderService,
+    OrderbookService,
+};
 use crate::state::AppState;
 use serde::Serialize;
 use tauri::State;
@@ -12,6 +15,9 @@ pub struct OrderResponse {
     pub success: bool,
     pub order_id: String,
     pub message: Option<String>,
+    /// Child order ids when the order was sliced at the freeze limit
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub child_order_ids: Vec<String>,
 }
 
 /// Place a new order
@@ -31,6 +37,7 @@ pub async fn place_order(
         success: result.success,
         order_id: result.order_id.unwrap_or_default(),
         message: Some(result.message),
+        child_order_ids: result.child_orders.into_iter().map(|c| c.order_id).collect(),
     })
 }
 
@@ -51,6 +58,7 @@ pub async fn modify_order(
         success: result.success,
         order_id: result.order_id,
         message: Some(result.message),
+        child_order_ids: Vec::new(),
     })
 }
 
@@ -71,6 +79,7 @@ pub async fn cancel_order(
         success: result.success,
         order_id: result.order_id,
         message: Some(result.message),
+        child_order_ids: Vec::new(),
     })
 }
 
@@ -93,3 +102,46 @@ pub async fn get_trade_book(state: State<'_, AppState>) -> Result<Vec<Order>> {
     tracing::info!("Trade book retrieved in {} mode", result.mode);
     Ok(result.trades)
 }
+
+/// Get emulated bracket/cover orders for the current mode
+#[tauri::command]
+pub async fn get_bracket_orders(state: State<'_, AppState>) -> Result<Vec<BracketOrder>> {
+    BracketService::get_bracket_orders(&state)
+}
+
+/// Cancel an emulated bracket/cover order
+///
+/// Cancels the entry if it hasn't filled, otherwise the linked exit legs.
+#[tauri::command]
+pub async fn cancel_bracket_order(
+    state: State<'_, AppState>,
+    bracket_id: String,
+) -> Result<BracketEvent> {
+    tracing::info!("Cancelling bracket: {}", bracket_id);
+    BracketService::cancel_bracket_order(&state, &bracket_id).await
+}
+
+/// Start a TWAP, VWAP or iceberg execution algo
+///
+/// Routes to sandbox in analyze mode.
+#[tauri::command]
+pub async fn place_algo_order(
+    state: State<'_, AppState>,
+    request: AlgoOrderRequest,
+) -> Result<AlgoOrder> {
+    tracing::info!("Placing algo order: {:?}", request);
+    AlgoService::submit(&state, request, None).await
+}
+
+/// Get execution algos for the current mode
+#[tauri::command]
+pub async fn get_algo_orders(state: State<'_, AppState>) -> Result<Vec<AlgoOrder>> {
+    AlgoService::get_algo_orders(&state)
+}
+
+/// Cancel a running execution algo and its working child orders
+#[tauri::command]
+pub async fn cancel_algo_order(state: State<'_, AppState>, algo_id: String) -> Result<AlgoEvent> {
+    tracing::info!("Cancelling algo: {}", algo_id);
+    AlgoService::cancel(&state, &algo_id).await
+}
@@ -1,8 +1,8 @@
 //! Position management commands
 
-use crate::brokers::types::Position;
+use crate::brokers::types::{ExitRule, Position};
 use crate::error::Result;
-use crate::services::PositionService;
+use crate::services::{ExitRuleEvent, ExitRuleRequest, ExitRuleService, PositionService, PositionsMtm};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -33,6 +33,14 @@ pub async fn get_positions(state: State<'_, AppState>) -> Result<Vec<Position>>
     Ok(result.positions)
 }
 
+/// Get the live MTM snapshot kept by the MTM engine
+///
+/// Same payload as the `positions_mtm` event.
+#[tauri::command]
+pub async fn get_positions_mtm(state: State<'_, AppState>) -> Result<PositionsMtm> {
+    Ok(state.mtm.snapshot())
+}
+
 /// Close a specific position
 ///
 /// Routes to sandbox in analyze mode.
@@ -73,3 +81,25 @@ pub async fn close_all_positions(state: State<'_, AppState>) -> Result<Vec<Close
         message: r.message,
     }).collect())
 }
+
+/// Create a target/stoploss/trailing exit rule for a position or the portfolio
+///
+/// Rules are scoped to the current mode (live/analyze).
+#[tauri::command]
+pub async fn create_exit_rule(state: State<'_, AppState>, request: ExitRuleRequest) -> Result<ExitRule> {
+    tracing::info!("Creating exit rule: {:?}", request);
+    ExitRuleService::create(&state, request, None).await
+}
+
+/// Get exit rules for the current mode
+#[tauri::command]
+pub async fn get_exit_rules(state: State<'_, AppState>) -> Result<Vec<ExitRule>> {
+    ExitRuleService::get_exit_rules(&state)
+}
+
+/// Cancel an active exit rule, leaving the position open
+#[tauri::command]
+pub async fn cancel_exit_rule(state: State<'_, AppState>, rule_id: String) -> Result<ExitRuleEvent> {
+    tracing::info!("Cancelling exit rule: {}", rule_id);
+    ExitRuleService::cancel(&state, &rule_id)
+}
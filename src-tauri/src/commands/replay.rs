@@ -0,0 +1,55 @@
+//! Sandbox replay commands
+
+use crate::error::Result;
+use crate::services::{ReplayRequest, ReplayService, ReplayStatus};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+/// Start replaying a past trading day into the sandbox
+#[tauri::command]
+pub async fn start_replay(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: ReplayRequest,
+) -> Result<ReplayStatus> {
+    tracing::info!("Starting replay of {} at {}x", request.date, request.speed);
+    ReplayService::start(app, &state, request).await
+}
+
+/// Pause the replay clock
+#[tauri::command]
+pub async fn pause_replay(state: State<'_, AppState>) -> Result<()> {
+    ReplayService::pause(&state)
+}
+
+/// Resume a paused replay
+#[tauri::command]
+pub async fn resume_replay(state: State<'_, AppState>) -> Result<()> {
+    ReplayService::resume(&state)
+}
+
+/// Jump the replay clock to a time of day or timestamp
+#[tauri::command]
+pub async fn seek_replay(state: State<'_, AppState>, time: String) -> Result<()> {
+    tracing::info!("Seeking replay to {}", time);
+    ReplayService::seek(&state, &time)
+}
+
+/// Change the replay speed (1x-100x)
+#[tauri::command]
+pub async fn set_replay_speed(state: State<'_, AppState>, speed: u32) -> Result<()> {
+    ReplayService::set_speed(&state, speed)
+}
+
+/// Stop the replay
+#[tauri::command]
+pub async fn stop_replay(state: State<'_, AppState>) -> Result<()> {
+    tracing::info!("Stopping replay");
+    ReplayService::stop(&state)
+}
+
+/// Get the replay status
+#[tauri::command]
+pub async fn get_replay_status(state: State<'_, AppState>) -> Result<ReplayStatus> {
+    Ok(ReplayService::status(&state))
+}
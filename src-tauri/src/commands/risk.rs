@@ -0,0 +1,46 @@
+//! Risk management commands
+
+use crate::db::sqlite::RiskConfig;
+use crate::error::{AppError, Result};
+use crate::services::{KillSwitchResult, RiskService, RiskStatus};
+use crate::state::AppState;
+use tauri::State;
+
+/// Get risk limits
+#[tauri::command]
+pub async fn get_risk_config(state: State<'_, AppState>) -> Result<RiskConfig> {
+    state.sqlite.get_risk_config()
+}
+
+/// Update risk limits (0 disables an individual limit)
+#[tauri::command]
+pub async fn update_risk_config(state: State<'_, AppState>, config: RiskConfig) -> Result<RiskConfig> {
+    state.sqlite.update_risk_config(&config)
+}
+
+/// Get risk status (limits, day MTM, kill switch)
+#[tauri::command]
+pub async fn get_risk_status(state: State<'_, AppState>) -> Result<RiskStatus> {
+    RiskService::get_status(&state)
+}
+
+/// Activate the kill switch
+///
+/// Cancels all orders, closes all positions and blocks new entries until
+/// the next session. Routes to sandbox in analyze mode.
+#[tauri::command]
+pub async fn activate_kill_switch(
+    state: State<'_, AppState>,
+    reason: Option<String>,
+) -> Result<KillSwitchResult> {
+    let reason = reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty())
+        .unwrap_or_else(|| "Manual kill switch".to_string());
+
+    if reason.len() > 200 {
+        return Err(AppError::Validation("Reason must be at most 200 characters".to_string()));
+    }
+
+    RiskService::activate_kill_switch(&state, &reason, None).await
+}
@@ -1,9 +1,13 @@
 //! Settings management commands
 
-use crate::db::sqlite::models::{RateLimitConfig, Settings};
+use crate::db::sqlite::models::{
+    IdempotencyConfig, MtmConfig, OrderUpdateConfig, RateLimitConfig, Settings, TickRecorderConfig,
+};
 use crate::db::sqlite::{AutoLogoutConfig, WebhookConfig};
-use crate::error::Result;
+use crate::error::{AppError, Result};
+use crate::services::ValidationMode;
 use crate::state::AppState;
+use crate::webhook::WebhookServerStatus;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -32,6 +36,8 @@ pub struct UpdateWebhookConfigRequest {
     pub host: Option<String>,
     pub ngrok_url: Option<String>,
     pub webhook_secret: Option<String>,
+    /// Trust X-Forwarded-For from a local tunnel/proxy
+    pub trust_proxy: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,6 +48,27 @@ pub struct UpdateRateLimitRequest {
     pub smart_order_delay: Option<f64>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateOrderUpdateConfigRequest {
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateIdempotencyConfigRequest {
+    pub window_secs: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMtmConfigRequest {
+    pub event_interval_ms: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTickRecorderConfigRequest {
+    pub enabled: bool,
+    pub flush_interval_secs: u32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SaveBrokerCredentialsRequest {
     pub broker_id: String,
@@ -162,13 +189,52 @@ pub async fn update_webhook_config(
 ) -> Result<WebhookConfig> {
     tracing::info!("Updating webhook config: {:?}", request);
 
-    state.sqlite.update_webhook_config(
+    let config = state.sqlite.update_webhook_config(
         request.enabled,
         request.port,
         request.host,
         request.ngrok_url,
         request.webhook_secret,
-    )
+        request.trust_proxy,
+    )?;
+
+    // Apply host/port/enabled changes to the running server
+    state
+        .webhook_server
+        .apply_config(config.clone())
+        .await
+        .map_err(AppError::Config)?;
+
+    Ok(config)
+}
+
+/// Get webhook/API server status
+#[tauri::command]
+pub async fn get_webhook_server_status(state: State<'_, AppState>) -> Result<WebhookServerStatus> {
+    Ok(state.webhook_server.status().await)
+}
+
+/// Start the webhook/API server with the saved configuration
+#[tauri::command]
+pub async fn start_webhook_server(state: State<'_, AppState>) -> Result<WebhookServerStatus> {
+    let config = state.sqlite.get_webhook_config()?;
+    state.webhook_server.start(config).await.map_err(AppError::Config)?;
+    Ok(state.webhook_server.status().await)
+}
+
+/// Stop the webhook/API server, draining in-flight requests
+#[tauri::command]
+pub async fn stop_webhook_server(state: State<'_, AppState>) -> Result<WebhookServerStatus> {
+    state.webhook_server.stop().await;
+    Ok(state.webhook_server.status().await)
+}
+
+/// Restart the webhook/API server with the saved configuration
+#[tauri::command]
+pub async fn restart_webhook_server(state: State<'_, AppState>) -> Result<WebhookServerStatus> {
+    let config = state.sqlite.get_webhook_config()?;
+    state.webhook_server.restart(config).await.map_err(AppError::Config)?;
+    Ok(state.webhook_server.status().await)
 }
 
 /// Get rate limit configuration
@@ -185,12 +251,91 @@ pub async fn update_rate_limit_config(
 ) -> Result<RateLimitConfig> {
     tracing::info!("Updating rate limit config: {:?}", request);
 
-    state.sqlite.update_rate_limit_config(
+    let config = state.sqlite.update_rate_limit_config(
         request.api_rate_limit,
         request.order_rate_limit,
         request.smart_order_rate_limit,
         request.smart_order_delay,
-    )
+    )?;
+
+    // Live limiter picks up the new limits without a restart
+    state.webhook_server.update_rate_limits(&config);
+
+    Ok(config)
+}
+
+/// Get outbound order update configuration
+#[tauri::command]
+pub async fn get_order_update_config(state: State<'_, AppState>) -> Result<OrderUpdateConfig> {
+    state.sqlite.get_order_update_config()
+}
+
+/// Update outbound order update configuration
+#[tauri::command]
+pub async fn update_order_update_config(
+    state: State<'_, AppState>,
+    request: UpdateOrderUpdateConfigRequest,
+) -> Result<OrderUpdateConfig> {
+    tracing::info!("Updating order update config: {:?}", request);
+
+    state.sqlite.update_order_update_config(request.webhook_url)
+}
+
+/// Get idempotent order placement configuration
+#[tauri::command]
+pub async fn get_idempotency_config(state: State<'_, AppState>) -> Result<IdempotencyConfig> {
+    state.sqlite.get_idempotency_config()
+}
+
+/// Update how long client order ids are remembered
+#[tauri::command]
+pub async fn update_idempotency_config(
+    state: State<'_, AppState>,
+    request: UpdateIdempotencyConfigRequest,
+) -> Result<IdempotencyConfig> {
+    tracing::info!("Updating idempotency config: {:?}", request);
+
+    state.sqlite.update_idempotency_config(request.window_secs)
+}
+
+/// Get live MTM engine configuration
+#[tauri::command]
+pub async fn get_mtm_config(state: State<'_, AppState>) -> Result<MtmConfig> {
+    state.sqlite.get_mtm_config()
+}
+
+/// Update how often `positions_mtm` events may be emitted
+///
+/// The MTM engine picks up the new rate on its next positions refresh.
+#[tauri::command]
+pub async fn update_mtm_config(
+    state: State<'_, AppState>,
+    request: UpdateMtmConfigRequest,
+) -> Result<MtmConfig> {
+    tracing::info!("Updating MTM config: {:?}", request);
+
+    state.sqlite.update_mtm_config(request.event_interval_ms)
+}
+
+/// Get tick recorder configuration
+#[tauri::command]
+pub async fn get_tick_recorder_config(state: State<'_, AppState>) -> Result<TickRecorderConfig> {
+    state.sqlite.get_tick_recorder_config()
+}
+
+/// Turn the tick recorder on or off
+///
+/// The recorder picks up the change on its next refresh.
+#[tauri::command]
+pub async fn update_tick_recorder_config(
+    state: State<'_, AppState>,
+    request: UpdateTickRecorderConfigRequest,
+) -> Result<TickRecorderConfig> {
+    tracing::info!("Updating tick recorder config: {:?}", request);
+
+    state
+        .sqlite
+        .update_tick_recorder_config(request.enabled, request.flush_interval_secs)
 }
 
 // ============================================================================
@@ -469,3 +614,25 @@ pub async fn set_analyze_mode(state: State<'_, AppState>, enabled: bool) -> Resu
         mode: if enabled { "analyzer".to_string() } else { "live".to_string() },
     })
 }
+
+// ============================================================================
+// Pre-trade Validation Commands
+// ============================================================================
+
+/// Get pre-trade validation mode (off/reject/correct)
+#[tauri::command]
+pub async fn get_order_validation_mode(state: State<'_, AppState>) -> Result<String> {
+    state.sqlite.get_order_validation_mode()
+}
+
+/// Set pre-trade validation mode
+#[tauri::command]
+pub async fn set_order_validation_mode(state: State<'_, AppState>, mode: String) -> Result<String> {
+    let mode = ValidationMode::parse(&mode).ok_or_else(|| {
+        AppError::Validation(format!("Invalid validation mode: {} (expected off, reject or correct)", mode))
+    })?;
+
+    tracing::info!("Setting order validation mode to: {}", mode.as_str());
+    state.sqlite.set_order_validation_mode(mode.as_str())?;
+    Ok(mode.as_str().to_string())
+}
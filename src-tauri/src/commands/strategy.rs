@@ -1,7 +1,8 @@
 //! Strategy management commands
 
-use crate::db::sqlite::models::Strategy;
+use crate::db::sqlite::models::{Strategy, StrategySymbolMapping};
 use crate::error::Result;
+use crate::services::{SquareoffSummary, StrategyService};
 use crate::state::AppState;
 use serde::Deserialize;
 use tauri::State;
@@ -15,6 +16,32 @@ pub struct CreateStrategyRequest {
     pub product: String,
     pub quantity: i32,
     pub enabled: bool,
+    #[serde(default)]
+    pub is_intraday: bool,
+    #[serde(default = "default_trading_mode")]
+    pub trading_mode: String,
+    #[serde(default = "default_start_time")]
+    pub start_time: Option<String>,
+    #[serde(default = "default_end_time")]
+    pub end_time: Option<String>,
+    #[serde(default = "default_squareoff_time")]
+    pub squareoff_time: Option<String>,
+}
+
+fn default_trading_mode() -> String {
+    "BOTH".to_string()
+}
+
+fn default_start_time() -> Option<String> {
+    Some("09:15".to_string())
+}
+
+fn default_end_time() -> Option<String> {
+    Some("15:15".to_string())
+}
+
+fn default_squareoff_time() -> Option<String> {
+    Some("15:25".to_string())
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,6 +55,63 @@ pub struct UpdateStrategyRequest {
     pub enabled: Option<bool>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateStrategyTradingRequest {
+    pub id: i64,
+    pub is_intraday: Option<bool>,
+    /// LONG, SHORT or BOTH
+    pub trading_mode: Option<String>,
+    /// "HH:MM" IST
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub squareoff_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateStrategySecurityRequest {
+    pub id: i64,
+    /// none, secret or hmac
+    pub auth_mode: Option<String>,
+    /// Empty string clears the strategy secret
+    pub webhook_secret: Option<String>,
+    /// Comma-separated IPs/CIDRs, empty string allows any
+    pub ip_allowlist: Option<String>,
+    pub replay_window_secs: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddSymbolMappingRequest {
+    pub strategy_id: i64,
+    pub exchange: String,
+    pub symbol: String,
+    pub quantity: i32,
+    #[serde(default = "default_product")]
+    pub product: String,
+}
+
+fn default_product() -> String {
+    "MIS".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSymbolMappingRequest {
+    pub id: i64,
+    pub exchange: Option<String>,
+    pub symbol: Option<String>,
+    pub quantity: Option<i32>,
+    pub product: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportSymbolMappingsRequest {
+    pub strategy_id: i64,
+    /// CSV with header: symbol,exchange,quantity[,product]
+    pub csv: String,
+    /// Remove mappings not present in the CSV
+    #[serde(default)]
+    pub replace: bool,
+}
+
 /// Get all strategies
 #[tauri::command]
 pub async fn get_strategies(state: State<'_, AppState>) -> Result<Vec<Strategy>> {
@@ -51,6 +135,15 @@ pub async fn create_strategy(
         product: request.product,
         quantity: request.quantity,
         enabled: request.enabled,
+        is_intraday: request.is_intraday,
+        trading_mode: request.trading_mode.to_uppercase(),
+        start_time: request.start_time,
+        end_time: request.end_time,
+        squareoff_time: request.squareoff_time,
+        webhook_auth_mode: "none".to_string(),
+        webhook_secret: None,
+        webhook_ip_allowlist: None,
+        replay_window_secs: 300,
         created_at: chrono::Utc::now().to_rfc3339(),
         updated_at: chrono::Utc::now().to_rfc3339(),
     };
@@ -77,6 +170,49 @@ pub async fn update_strategy(
     )
 }
 
+/// Update a strategy's trading mode, intraday flag and trading window
+#[tauri::command]
+pub async fn update_strategy_trading(
+    state: State<'_, AppState>,
+    request: UpdateStrategyTradingRequest,
+) -> Result<Strategy> {
+    tracing::info!("Updating trading settings for strategy: {}", request.id);
+
+    state.sqlite.update_strategy_trading(
+        request.id,
+        request.is_intraday,
+        request.trading_mode,
+        request.start_time,
+        request.end_time,
+        request.squareoff_time,
+    )
+}
+
+/// Update a strategy's webhook verification settings
+#[tauri::command]
+pub async fn update_strategy_security(
+    state: State<'_, AppState>,
+    request: UpdateStrategySecurityRequest,
+) -> Result<Strategy> {
+    tracing::info!("Updating webhook security for strategy: {}", request.id);
+
+    state.sqlite.update_strategy_security(
+        request.id,
+        request.auth_mode,
+        request.webhook_secret,
+        request.ip_allowlist,
+        request.replay_window_secs,
+    )
+}
+
+/// Square off a strategy now: cancel its open orders and close its positions
+#[tauri::command]
+pub async fn square_off_strategy(state: State<'_, AppState>, id: i64) -> Result<SquareoffSummary> {
+    tracing::info!("Manual square-off of strategy: {}", id);
+    let strategy = state.sqlite.get_strategy(id)?;
+    StrategyService::square_off(&state, &strategy).await
+}
+
 /// Delete a strategy
 #[tauri::command]
 pub async fn delete_strategy(state: State<'_, AppState>, id: i64) -> Result<()> {
@@ -90,3 +226,67 @@ pub async fn toggle_strategy(state: State<'_, AppState>, id: i64, enabled: bool)
     tracing::info!("Toggling strategy {} to enabled={}", id, enabled);
     state.sqlite.update_strategy(id, None, None, None, None, None, Some(enabled))
 }
+
+/// Get symbol mappings for a strategy
+#[tauri::command]
+pub async fn get_strategy_symbols(
+    state: State<'_, AppState>,
+    strategy_id: i64,
+) -> Result<Vec<StrategySymbolMapping>> {
+    state.sqlite.get_symbol_mappings(strategy_id)
+}
+
+/// Add a symbol mapping to a strategy
+#[tauri::command]
+pub async fn add_strategy_symbol(
+    state: State<'_, AppState>,
+    request: AddSymbolMappingRequest,
+) -> Result<StrategySymbolMapping> {
+    tracing::info!("Adding {}:{} to strategy {}", request.exchange, request.symbol, request.strategy_id);
+
+    state.sqlite.add_symbol_mapping(
+        request.strategy_id,
+        &request.exchange,
+        &request.symbol,
+        request.quantity,
+        &request.product,
+    )
+}
+
+/// Update a strategy symbol mapping
+#[tauri::command]
+pub async fn update_strategy_symbol(
+    state: State<'_, AppState>,
+    request: UpdateSymbolMappingRequest,
+) -> Result<StrategySymbolMapping> {
+    tracing::info!("Updating symbol mapping: {}", request.id);
+
+    state.sqlite.update_symbol_mapping(
+        request.id,
+        request.exchange,
+        request.symbol,
+        request.quantity,
+        request.product,
+    )
+}
+
+/// Remove a strategy symbol mapping
+#[tauri::command]
+pub async fn delete_strategy_symbol(state: State<'_, AppState>, id: i64) -> Result<()> {
+    tracing::info!("Deleting symbol mapping: {}", id);
+    state.sqlite.delete_symbol_mapping(id)
+}
+
+/// Bulk-import strategy symbol mappings from CSV
+#[tauri::command]
+pub async fn import_strategy_symbols(
+    state: State<'_, AppState>,
+    request: ImportSymbolMappingsRequest,
+) -> Result<Vec<StrategySymbolMapping>> {
+    tracing::info!("Importing symbol mappings for strategy {}", request.strategy_id);
+
+    state
+        .sqlite
+        .import_symbol_mappings(request.strategy_id, &request.csv, request.replace)?;
+    state.sqlite.get_symbol_mappings(request.strategy_id)
+}
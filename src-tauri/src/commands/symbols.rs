@@ -1,8 +1,9 @@
 //! Symbol search and master contract commands
 
+use crate::db::sqlite::FreezeQty;
 use crate::error::{AppError, Result};
 use crate::state::{AppState, SymbolInfo};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::State;
 
 #[derive(Debug, Serialize)]
@@ -150,3 +151,39 @@ pub async fn refresh_symbol_master(state: State<'_, AppState>) -> Result<usize>
 
     Ok(count)
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ImportFreezeQtyRequest {
+    /// CSV with header: exchange,symbol,freeze_qty
+    pub csv: String,
+    /// Remove limits not present in the CSV
+    #[serde(default)]
+    pub replace: bool,
+}
+
+/// Get exchange freeze limits used to slice oversize F&O orders
+#[tauri::command]
+pub async fn get_freeze_quantities(state: State<'_, AppState>) -> Result<Vec<FreezeQty>> {
+    state.sqlite.get_freeze_quantities()
+}
+
+/// Update exchange freeze limits from CSV
+#[tauri::command]
+pub async fn import_freeze_quantities(
+    state: State<'_, AppState>,
+    request: ImportFreezeQtyRequest,
+) -> Result<Vec<FreezeQty>> {
+    tracing::info!("Importing freeze limits (replace={})", request.replace);
+
+    state.sqlite.import_freeze_quantities(&request.csv, request.replace)?;
+    state.sqlite.get_freeze_quantities()
+}
+
+/// Restore the bundled exchange freeze limits
+#[tauri::command]
+pub async fn reset_freeze_quantities(state: State<'_, AppState>) -> Result<Vec<FreezeQty>> {
+    tracing::info!("Resetting freeze limits to bundled defaults");
+
+    state.sqlite.reset_freeze_quantities()?;
+    state.sqlite.get_freeze_quantities()
+}
@@ -4,6 +4,7 @@
 //! from the frontend.
 
 use crate::error::Result;
+use crate::services::GttService;
 use crate::state::AppState;
 use crate::websocket::{SubscriptionMode, SubscriptionRequest};
 use serde::{Deserialize, Serialize};
@@ -48,7 +49,7 @@ pub async fn websocket_connect(state: State<'_, AppState>) -> Result<bool> {
         .get_auth_token(&broker_id, &state.security)?
         .ok_or_else(|| crate::error::AppError::Auth("No auth tokens found".to_string()))?;
 
-    let _auth_token = tokens.0;
+    let auth_token = tokens.0;
     let feed_token = tokens.1
         .ok_or_else(|| crate::error::AppError::Auth("No feed token found".to_string()))?;
 
@@ -64,6 +65,14 @@ pub async fn websocket_connect(state: State<'_, AppState>) -> Result<bool> {
         .connect(&broker_id, &client_id, &api_key, &feed_token)
         .await?;
 
+    // Emulated GTTs (and bracket exits built on them) need their symbols streaming
+    GttService::subscribe_active(&state).await;
+
+    // Order updates are optional - market data stays up if the order socket fails
+    if let Err(e) = state.order_updates.connect(&broker_id, &auth_token).await {
+        tracing::warn!("Order update feed not connected: {}", e);
+    }
+
     Ok(true)
 }
 
@@ -71,6 +80,7 @@ pub async fn websocket_connect(state: State<'_, AppState>) -> Result<bool> {
 #[tauri::command]
 pub async fn websocket_disconnect(state: State<'_, AppState>) -> Result<bool> {
     state.websocket.disconnect().await?;
+    state.order_updates.disconnect();
     Ok(true)
 }
 
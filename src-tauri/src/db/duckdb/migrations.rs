@@ -32,6 +32,9 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
     run_migration(conn, "003_data_catalog", CREATE_DATA_CATALOG)?;
     run_migration(conn, "004_download_jobs", CREATE_DOWNLOAD_JOBS)?;
     run_migration(conn, "005_symbol_metadata", CREATE_SYMBOL_METADATA)?;
+    run_migration(conn, "006_ticks", CREATE_TICKS)?;
+    run_migration(conn, "007_recorder_symbols", CREATE_RECORDER_SYMBOLS)?;
+    run_migration(conn, "008_backtests", CREATE_BACKTESTS)?;
 
     tracing::info!("DuckDB migrations completed");
     Ok(())
@@ -132,3 +135,80 @@ CREATE TABLE IF NOT EXISTS symbol_metadata (
     PRIMARY KEY (symbol, exchange)
 );
 "#;
+
+/// Raw ticks written by the tick recorder
+///
+/// No primary key or indexes: the table is append-only and DuckDB's
+/// columnar compression (dictionary for symbols, RLE/bitpacking for
+/// prices and quantities) works best on plain, sorted row groups.
+const CREATE_TICKS: &str = r#"
+CREATE TABLE IF NOT EXISTS ticks (
+    symbol VARCHAR NOT NULL,
+    exchange VARCHAR NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    ltp DOUBLE NOT NULL,
+    volume BIGINT NOT NULL,
+    oi BIGINT NOT NULL,
+    bid DOUBLE NOT NULL,
+    ask DOUBLE NOT NULL,
+    bid_qty BIGINT NOT NULL,
+    ask_qty BIGINT NOT NULL
+);
+"#;
+
+const CREATE_RECORDER_SYMBOLS: &str = r#"
+CREATE TABLE IF NOT EXISTS recorder_symbols (
+    symbol VARCHAR NOT NULL,
+    exchange VARCHAR NOT NULL,
+    added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (symbol, exchange)
+);
+"#;
+
+const CREATE_BACKTESTS: &str = r#"
+CREATE TABLE IF NOT EXISTS backtest_runs (
+    run_id VARCHAR PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    strategy VARCHAR NOT NULL,
+    symbol VARCHAR NOT NULL,
+    exchange VARCHAR NOT NULL,
+    timeframe VARCHAR NOT NULL,
+    from_date VARCHAR NOT NULL,
+    to_date VARCHAR NOT NULL,
+    config VARCHAR NOT NULL,
+    initial_capital DOUBLE NOT NULL,
+    final_equity DOUBLE NOT NULL,
+    net_pnl DOUBLE NOT NULL,
+    return_pct DOUBLE NOT NULL,
+    max_drawdown DOUBLE NOT NULL,
+    max_drawdown_pct DOUBLE NOT NULL,
+    sharpe DOUBLE NOT NULL,
+    win_rate DOUBLE NOT NULL,
+    total_trades BIGINT NOT NULL,
+    total_charges DOUBLE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS backtest_equity (
+    run_id VARCHAR NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    equity DOUBLE NOT NULL,
+    drawdown DOUBLE NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS backtest_trades (
+    run_id VARCHAR NOT NULL,
+    trade_no BIGINT NOT NULL,
+    side VARCHAR NOT NULL,
+    quantity BIGINT NOT NULL,
+    entry_time TIMESTAMP NOT NULL,
+    entry_price DOUBLE NOT NULL,
+    exit_time TIMESTAMP NOT NULL,
+    exit_price DOUBLE NOT NULL,
+    pnl DOUBLE NOT NULL,
+    charges DOUBLE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_backtest_equity_run ON backtest_equity(run_id);
+CREATE INDEX IF NOT EXISTS idx_backtest_trades_run ON backtest_trades(run_id);
+"#;
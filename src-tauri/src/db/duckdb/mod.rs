@@ -5,7 +5,9 @@ mod migrations;
 
 use crate::error::Result;
 use duckdb::Connection;
-use models::MarketDataRow;
+use models::{
+    BacktestReport, BacktestSummary, BacktestTrade, EquityPoint, MarketDataRow, RecorderSymbol, TickRow,
+};
 use parking_lot::Mutex;
 use std::path::Path;
 
@@ -47,7 +49,7 @@ impl DuckDb {
         let conn = self.conn.lock();
 
         let mut stmt = conn.prepare(
-            "SELECT timestamp, open, high, low, close, volume
+            "SELECT CAST(timestamp AS VARCHAR), open, high, low, close, volume
              FROM market_data
              WHERE symbol = ? AND exchange = ? AND timeframe = ?
                AND timestamp >= ? AND timestamp <= ?
@@ -114,4 +116,322 @@ impl DuckDb {
 
         Ok(count)
     }
+
+    /// Append recorded ticks
+    ///
+    /// Callers pass batches sorted by exchange, symbol and time so row
+    /// groups compress well.
+    pub fn insert_ticks(&self, ticks: &[TickRow]) -> Result<usize> {
+        let mut conn = self.conn.lock();
+
+        let tx = conn.transaction()?;
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO ticks (symbol, exchange, timestamp, ltp, volume, oi, bid, ask, bid_qty, ask_qty)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+
+        let mut count = 0;
+        for tick in ticks {
+            stmt.execute(duckdb::params![
+                tick.symbol,
+                tick.exchange,
+                tick.timestamp,
+                tick.ltp,
+                tick.volume,
+                tick.oi,
+                tick.bid,
+                tick.ask,
+                tick.bid_qty,
+                tick.ask_qty,
+            ])?;
+            count += 1;
+        }
+
+        drop(stmt);
+        tx.commit()?;
+
+        Ok(count)
+    }
+
+    /// Get symbols the tick recorder captures
+    pub fn get_recorder_symbols(&self) -> Result<Vec<RecorderSymbol>> {
+        let conn = self.conn.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT symbol, exchange, CAST(added_at AS VARCHAR)
+             FROM recorder_symbols
+             ORDER BY exchange, symbol",
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(RecorderSymbol {
+                    symbol: row.get(0)?,
+                    exchange: row.get(1)?,
+                    added_at: row.get(2)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Add a symbol to the tick recorder, returns false if already present
+    pub fn add_recorder_symbol(&self, symbol: &str, exchange: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+
+        let rows = conn.execute(
+            "INSERT INTO recorder_symbols (symbol, exchange) VALUES (?, ?)
+             ON CONFLICT (symbol, exchange) DO NOTHING",
+            duckdb::params![symbol, exchange],
+        )?;
+
+        Ok(rows > 0)
+    }
+
+    /// Remove a symbol from the tick recorder; recorded data is kept
+    pub fn remove_recorder_symbol(&self, symbol: &str, exchange: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+
+        let rows = conn.execute(
+            "DELETE FROM recorder_symbols WHERE symbol = ? AND exchange = ?",
+            duckdb::params![symbol, exchange],
+        )?;
+
+        Ok(rows > 0)
+    }
+
+    /// Query recorded ticks
+    pub fn query_ticks(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        from_date: &str,
+        to_date: &str,
+    ) -> Result<Vec<TickRow>> {
+        let conn = self.conn.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT symbol, exchange, CAST(timestamp AS VARCHAR), ltp, volume, oi, bid, ask, bid_qty, ask_qty
+             FROM ticks
+             WHERE symbol = ? AND exchange = ?
+               AND timestamp >= ? AND timestamp <= ?
+             ORDER BY timestamp ASC",
+        )?;
+
+        let rows = stmt
+            .query_map(duckdb::params![symbol, exchange, from_date, to_date], |row| {
+                Ok(TickRow {
+                    symbol: row.get(0)?,
+                    exchange: row.get(1)?,
+                    timestamp: row.get(2)?,
+                    ltp: row.get(3)?,
+                    volume: row.get(4)?,
+                    oi: row.get(5)?,
+                    bid: row.get(6)?,
+                    ask: row.get(7)?,
+                    bid_qty: row.get(8)?,
+                    ask_qty: row.get(9)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Last stored close before `before`, across timeframes
+    pub fn get_previous_close(&self, symbol: &str, exchange: &str, before: &str) -> Result<Option<f64>> {
+        let conn = self.conn.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT close FROM market_data
+             WHERE symbol = ? AND exchange = ? AND timestamp < ?
+             ORDER BY timestamp DESC
+             LIMIT 1",
+        )?;
+
+        let mut rows = stmt.query(duckdb::params![symbol, exchange, before])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a backtest report
+    pub fn insert_backtest(&self, report: &BacktestReport) -> Result<()> {
+        let mut conn = self.conn.lock();
+
+        let tx = conn.transaction()?;
+
+        let run = &report.summary;
+        tx.execute(
+            "INSERT INTO backtest_runs (run_id, name, strategy, symbol, exchange, timeframe, from_date, to_date,
+                                        config, initial_capital, final_equity, net_pnl, return_pct,
+                                        max_drawdown, max_drawdown_pct, sharpe, win_rate, total_trades,
+                                        total_charges)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            duckdb::params![
+                run.run_id,
+                run.name,
+                run.strategy,
+                run.symbol,
+                run.exchange,
+                run.timeframe,
+                run.from_date,
+                run.to_date,
+                run.config,
+                run.initial_capital,
+                run.final_equity,
+                run.net_pnl,
+                run.return_pct,
+                run.max_drawdown,
+                run.max_drawdown_pct,
+                run.sharpe,
+                run.win_rate,
+                run.total_trades,
+                run.total_charges,
+            ],
+        )?;
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO backtest_equity (run_id, timestamp, equity, drawdown) VALUES (?, ?, ?, ?)",
+        )?;
+        for point in &report.equity {
+            stmt.execute(duckdb::params![run.run_id, point.timestamp, point.equity, point.drawdown])?;
+        }
+        drop(stmt);
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO backtest_trades (run_id, trade_no, side, quantity, entry_time, entry_price,
+                                          exit_time, exit_price, pnl, charges)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        for trade in &report.trades {
+            stmt.execute(duckdb::params![
+                run.run_id,
+                trade.trade_no,
+                trade.side,
+                trade.quantity,
+                trade.entry_time,
+                trade.entry_price,
+                trade.exit_time,
+                trade.exit_price,
+                trade.pnl,
+                trade.charges,
+            ])?;
+        }
+        drop(stmt);
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Get backtest runs, newest first
+    pub fn get_backtests(&self) -> Result<Vec<BacktestSummary>> {
+        let conn = self.conn.lock();
+        Self::query_backtest_runs(&conn, None)
+    }
+
+    /// Get a backtest run with its equity curve and trades
+    pub fn get_backtest(&self, run_id: &str) -> Result<Option<BacktestReport>> {
+        let conn = self.conn.lock();
+
+        let Some(summary) = Self::query_backtest_runs(&conn, Some(run_id))?.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT CAST(timestamp AS VARCHAR), equity, drawdown
+             FROM backtest_equity WHERE run_id = ? ORDER BY timestamp ASC",
+        )?;
+        let equity = stmt
+            .query_map([run_id], |row| {
+                Ok(EquityPoint {
+                    timestamp: row.get(0)?,
+                    equity: row.get(1)?,
+                    drawdown: row.get(2)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT trade_no, side, quantity, CAST(entry_time AS VARCHAR), entry_price,
+                    CAST(exit_time AS VARCHAR), exit_price, pnl, charges
+             FROM backtest_trades WHERE run_id = ? ORDER BY trade_no ASC",
+        )?;
+        let trades = stmt
+            .query_map([run_id], |row| {
+                Ok(BacktestTrade {
+                    trade_no: row.get(0)?,
+                    side: row.get(1)?,
+                    quantity: row.get(2)?,
+                    entry_time: row.get(3)?,
+                    entry_price: row.get(4)?,
+                    exit_time: row.get(5)?,
+                    exit_price: row.get(6)?,
+                    pnl: row.get(7)?,
+                    charges: row.get(8)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(Some(BacktestReport { summary, equity, trades }))
+    }
+
+    /// Delete a backtest run and its report
+    pub fn delete_backtest(&self, run_id: &str) -> Result<bool> {
+        let mut conn = self.conn.lock();
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM backtest_equity WHERE run_id = ?", [run_id])?;
+        tx.execute("DELETE FROM backtest_trades WHERE run_id = ?", [run_id])?;
+        let rows = tx.execute("DELETE FROM backtest_runs WHERE run_id = ?", [run_id])?;
+        tx.commit()?;
+
+        Ok(rows > 0)
+    }
+
+    fn query_backtest_runs(conn: &Connection, run_id: Option<&str>) -> Result<Vec<BacktestSummary>> {
+        let sql = format!(
+            "SELECT run_id, name, strategy, symbol, exchange, timeframe, from_date, to_date, config,
+                    initial_capital, final_equity, net_pnl, return_pct, max_drawdown, max_drawdown_pct,
+                    sharpe, win_rate, total_trades, total_charges, CAST(created_at AS VARCHAR)
+             FROM backtest_runs {} ORDER BY created_at DESC",
+            if run_id.is_some() { "WHERE run_id = ?" } else { "" }
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let map_row = |row: &duckdb::Row| {
+            Ok(BacktestSummary {
+                run_id: row.get(0)?,
+                name: row.get(1)?,
+                strategy: row.get(2)?,
+                symbol: row.get(3)?,
+                exchange: row.get(4)?,
+                timeframe: row.get(5)?,
+                from_date: row.get(6)?,
+                to_date: row.get(7)?,
+                config: row.get(8)?,
+                initial_capital: row.get(9)?,
+                final_equity: row.get(10)?,
+                net_pnl: row.get(11)?,
+                return_pct: row.get(12)?,
+                max_drawdown: row.get(13)?,
+                max_drawdown_pct: row.get(14)?,
+                sharpe: row.get(15)?,
+                win_rate: row.get(16)?,
+                total_trades: row.get(17)?,
+                total_charges: row.get(18)?,
+                created_at: row.get(19)?,
+            })
+        };
+        let rows = match run_id {
+            Some(run_id) => stmt.query_map([run_id], map_row)?.collect::<std::result::Result<Vec<_>, _>>()?,
+            None => stmt.query_map([], map_row)?.collect::<std::result::Result<Vec<_>, _>>()?,
+        };
+
+        Ok(rows)
+    }
 }
@@ -13,6 +13,94 @@ pub struct MarketDataRow {
     pub volume: i64,
 }
 
+/// Raw tick captured by the tick recorder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickRow {
+    pub symbol: String,
+    pub exchange: String,
+    /// IST, millisecond precision
+    pub timestamp: String,
+    pub ltp: f64,
+    /// Cumulative day volume as sent by the broker
+    pub volume: i64,
+    pub oi: i64,
+    pub bid: f64,
+    pub ask: f64,
+    pub bid_qty: i64,
+    pub ask_qty: i64,
+}
+
+/// Symbol the tick recorder captures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecorderSymbol {
+    pub symbol: String,
+    pub exchange: String,
+    pub added_at: String,
+}
+
+/// Backtest run with its headline metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestSummary {
+    pub run_id: String,
+    pub name: String,
+    /// rules or signals
+    pub strategy: String,
+    pub symbol: String,
+    pub exchange: String,
+    /// Candle timeframe, or `tick` for recorded ticks
+    pub timeframe: String,
+    pub from_date: String,
+    pub to_date: String,
+    /// Backtest request as JSON, to re-run it
+    pub config: String,
+    pub initial_capital: f64,
+    pub final_equity: f64,
+    pub net_pnl: f64,
+    pub return_pct: f64,
+    pub max_drawdown: f64,
+    pub max_drawdown_pct: f64,
+    /// Annualized from daily returns, 0 for runs shorter than two days
+    pub sharpe: f64,
+    pub win_rate: f64,
+    pub total_trades: i64,
+    pub total_charges: f64,
+    pub created_at: String,
+}
+
+/// Point on a backtest equity curve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: String,
+    pub equity: f64,
+    /// Distance below the running peak
+    pub drawdown: f64,
+}
+
+/// Closed round trip in a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestTrade {
+    pub trade_no: i64,
+    /// LONG or SHORT
+    pub side: String,
+    pub quantity: i64,
+    pub entry_time: String,
+    pub entry_price: f64,
+    pub exit_time: String,
+    pub exit_price: f64,
+    /// Net of slippage and charges
+    pub pnl: f64,
+    pub charges: f64,
+}
+
+/// Full backtest report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    #[serde(flatten)]
+    pub summary: BacktestSummary,
+    pub equity: Vec<EquityPoint>,
+    pub trades: Vec<BacktestTrade>,
+}
+
 /// Watchlist item
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WatchlistItem {
@@ -0,0 +1,332 @@
+//! Price and indicator alert storage
+//!
+//! Active alerts are reloaded by the alert monitor on every refresh, so they
+//! keep watching after a restart. Every trigger is also written to the alert
+//! history together with the outcome of its order action, if it has one.
+
+use crate::error::Result;
+use rusqlite::{params, types::Type, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+/// Price or indicator alert on one symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAlert {
+    pub alert_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    /// crosses_above, crosses_below, percent_change, volume_spike, oi_change
+    pub condition: String,
+    /// Price level, percent (signed) or volume multiple, by condition
+    pub value: f64,
+    /// volume_spike: length of the volume window
+    pub window_secs: Option<i64>,
+    /// LTP when a crossing alert was created, or the OI baseline
+    pub reference_value: Option<f64>,
+    pub note: Option<String>,
+    /// Send an outbound notification when the alert fires
+    pub notify: bool,
+    /// Order placed when the alert fires
+    pub action: Option<AlertAction>,
+    /// active, triggered, cancelled
+    pub status: String,
+    pub triggered_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Predefined order placed when an alert fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertAction {
+    /// BUY or SELL
+    pub action: String,
+    pub quantity: i32,
+    /// Symbol to trade, defaults to the alert's symbol (e.g. alert on the index, trade the future)
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub exchange: Option<String>,
+    #[serde(default = "default_product")]
+    pub product: String,
+    /// MARKET or LIMIT
+    #[serde(default = "default_pricetype")]
+    pub pricetype: String,
+    #[serde(default)]
+    pub price: f64,
+    /// Record the order against this strategy (square-off, strategy P&L)
+    #[serde(default)]
+    pub strategy_id: Option<i64>,
+}
+
+fn default_product() -> String {
+    "MIS".to_string()
+}
+
+fn default_pricetype() -> String {
+    "MARKET".to_string()
+}
+
+/// One alert trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertHistory {
+    pub id: i64,
+    pub alert_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub condition: String,
+    pub value: f64,
+    /// Value that met the condition (LTP, percent, volume multiple)
+    pub observed: f64,
+    pub ltp: f64,
+    pub note: Option<String>,
+    /// placed or failed, unset when the alert has no action
+    pub action_status: Option<String>,
+    pub action_result: Option<String>,
+    pub triggered_at: String,
+}
+
+const SELECT_COLUMNS: &str = "alert_id, symbol, exchange, condition, value, window_secs, reference_value,
+                              note, notify, action, status, triggered_at, created_at, updated_at";
+
+fn row_to_alert(row: &Row) -> rusqlite::Result<PriceAlert> {
+    let action: Option<String> = row.get(9)?;
+    let action = action
+        .map(|raw| serde_json::from_str(&raw))
+        .transpose()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(9, Type::Text, Box::new(e)))?;
+
+    Ok(PriceAlert {
+        alert_id: row.get(0)?,
+        symbol: row.get(1)?,
+        exchange: row.get(2)?,
+        condition: row.get(3)?,
+        value: row.get(4)?,
+        window_secs: row.get(5)?,
+        reference_value: row.get(6)?,
+        note: row.get(7)?,
+        notify: row.get::<_, i32>(8)? != 0,
+        action,
+        status: row.get(10)?,
+        triggered_at: row.get(11)?,
+        created_at: row.get(12)?,
+        updated_at: row.get(13)?,
+    })
+}
+
+fn row_to_history(row: &Row) -> rusqlite::Result<AlertHistory> {
+    Ok(AlertHistory {
+        id: row.get(0)?,
+        alert_id: row.get(1)?,
+        symbol: row.get(2)?,
+        exchange: row.get(3)?,
+        condition: row.get(4)?,
+        value: row.get(5)?,
+        observed: row.get(6)?,
+        ltp: row.get(7)?,
+        note: row.get(8)?,
+        action_status: row.get(9)?,
+        action_result: row.get(10)?,
+        triggered_at: row.get(11)?,
+    })
+}
+
+/// Store a new alert
+pub fn create_alert(conn: &Connection, alert: &PriceAlert) -> Result<()> {
+    let action = alert.action.as_ref().map(serde_json::to_string).transpose()?;
+
+    conn.execute(
+        "INSERT INTO price_alerts (alert_id, symbol, exchange, condition, value, window_secs,
+                                   reference_value, note, notify, action, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            alert.alert_id,
+            alert.symbol,
+            alert.exchange,
+            alert.condition,
+            alert.value,
+            alert.window_secs,
+            alert.reference_value,
+            alert.note,
+            alert.notify as i32,
+            action,
+            alert.status,
+        ],
+    )?;
+
+    tracing::info!(
+        "Created alert {} ({} {}) for {}:{}",
+        alert.alert_id, alert.condition, alert.value, alert.exchange, alert.symbol
+    );
+    Ok(())
+}
+
+/// Get an alert by ID
+pub fn get_alert(conn: &Connection, alert_id: &str) -> Result<Option<PriceAlert>> {
+    let sql = format!("SELECT {} FROM price_alerts WHERE alert_id = ?1", SELECT_COLUMNS);
+    match conn.query_row(&sql, [alert_id], row_to_alert) {
+        Ok(alert) => Ok(Some(alert)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get alerts, newest first, optionally only the active ones
+pub fn get_alerts(conn: &Connection, active_only: bool) -> Result<Vec<PriceAlert>> {
+    let sql = if active_only {
+        format!(
+            "SELECT {} FROM price_alerts WHERE status = 'active' ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    } else {
+        format!("SELECT {} FROM price_alerts ORDER BY created_at DESC", SELECT_COLUMNS)
+    };
+
+    let mut stmt = conn.prepare(&sql)?;
+    let alerts = stmt
+        .query_map([], row_to_alert)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(alerts)
+}
+
+/// Record the reference (baseline) value of an active alert
+pub fn set_reference_value(conn: &Connection, alert_id: &str, reference_value: f64) -> Result<()> {
+    conn.execute(
+        "UPDATE price_alerts SET reference_value = ?1, updated_at = datetime('now')
+         WHERE alert_id = ?2 AND status = 'active'",
+        params![reference_value, alert_id],
+    )?;
+
+    Ok(())
+}
+
+/// Move an active alert to triggered or cancelled
+///
+/// Returns false if the alert was no longer active, so a burst of ticks can't
+/// fire it twice.
+pub fn finish_alert(conn: &Connection, alert_id: &str, status: &str) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE price_alerts SET status = ?1,
+                triggered_at = CASE WHEN ?1 = 'triggered' THEN datetime('now') ELSE triggered_at END,
+                updated_at = datetime('now')
+         WHERE alert_id = ?2 AND status = 'active'",
+        params![status, alert_id],
+    )?;
+
+    Ok(rows > 0)
+}
+
+/// Delete an alert; its history is kept
+pub fn delete_alert(conn: &Connection, alert_id: &str) -> Result<bool> {
+    let rows = conn.execute("DELETE FROM price_alerts WHERE alert_id = ?1", [alert_id])?;
+    Ok(rows > 0)
+}
+
+/// Write a trigger to the alert history, returning its ID
+pub fn record_trigger(conn: &Connection, alert: &PriceAlert, observed: f64, ltp: f64) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO alert_history (alert_id, symbol, exchange, condition, value, observed, ltp, note)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            alert.alert_id,
+            alert.symbol,
+            alert.exchange,
+            alert.condition,
+            alert.value,
+            observed,
+            ltp,
+            alert.note,
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Record the outcome of a trigger's order action
+pub fn set_trigger_action(conn: &Connection, id: i64, status: &str, result: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE alert_history SET action_status = ?1, action_result = ?2 WHERE id = ?3",
+        params![status, result, id],
+    )?;
+
+    Ok(())
+}
+
+/// Get the most recent alert triggers
+pub fn get_history(conn: &Connection, limit: u32) -> Result<Vec<AlertHistory>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, alert_id, symbol, exchange, condition, value, observed, ltp, note,
+                action_status, action_result, triggered_at
+         FROM alert_history ORDER BY id DESC LIMIT ?1",
+    )?;
+    let history = stmt
+        .query_map([limit], row_to_history)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::migrations::run_migrations;
+
+    fn alert(alert_id: &str, action: Option<AlertAction>) -> PriceAlert {
+        PriceAlert {
+            alert_id: alert_id.to_string(),
+            symbol: "NIFTY".to_string(),
+            exchange: "NSE_INDEX".to_string(),
+            condition: "crosses_above".to_string(),
+            value: 25000.0,
+            window_secs: None,
+            reference_value: Some(24900.0),
+            note: Some("NIFTY 25k".to_string()),
+            notify: true,
+            action,
+            status: "active".to_string(),
+            triggered_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_alert_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let action = AlertAction {
+            action: "BUY".to_string(),
+            quantity: 75,
+            symbol: Some("NIFTY24DECFUT".to_string()),
+            exchange: Some("NFO".to_string()),
+            product: "NRML".to_string(),
+            pricetype: "MARKET".to_string(),
+            price: 0.0,
+            strategy_id: Some(3),
+        };
+        create_alert(&conn, &alert("AL1", Some(action.clone()))).unwrap();
+        create_alert(&conn, &alert("AL2", None)).unwrap();
+
+        let stored = get_alert(&conn, "AL1").unwrap().unwrap();
+        assert_eq!(stored.action, Some(action));
+        assert!(stored.notify);
+        assert_eq!(get_alerts(&conn, true).unwrap().len(), 2);
+
+        // Fires once
+        assert!(finish_alert(&conn, "AL1", "triggered").unwrap());
+        assert!(!finish_alert(&conn, "AL1", "triggered").unwrap());
+        let stored = get_alert(&conn, "AL1").unwrap().unwrap();
+        assert_eq!(stored.status, "triggered");
+        assert!(stored.triggered_at.is_some());
+        assert_eq!(get_alerts(&conn, true).unwrap().len(), 1);
+
+        // History survives deleting the alert
+        let id = record_trigger(&conn, &stored, 25001.5, 25001.5).unwrap();
+        set_trigger_action(&conn, id, "placed", "Order 123").unwrap();
+        assert!(delete_alert(&conn, "AL1").unwrap());
+        let history = get_history(&conn, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action_status.as_deref(), Some("placed"));
+        assert_eq!(history[0].note.as_deref(), Some("NIFTY 25k"));
+    }
+}
@@ -0,0 +1,218 @@
+//! Execution algo storage
+//!
+//! Persists TWAP/VWAP/iceberg progress (quantity placed, child orders, next
+//! slice time) so running algos resume after a restart.
+
+use crate::brokers::types::AlgoOrder;
+use crate::error::Result;
+use rusqlite::{params, Connection, Row};
+
+const SELECT_COLUMNS: &str = "algo_id, algo_type, symbol, exchange, side, product, quantity, limit_price,
+                              duration_secs, num_slices, participation_rate, display_quantity,
+                              disclosed_quantity, placed_quantity, filled_quantity, slices_placed,
+                              active_order_id, baseline_volume, market_volume, child_order_ids,
+                              next_slice_at, ends_at, status, message, created_at, updated_at";
+
+fn row_to_algo(row: &Row) -> rusqlite::Result<AlgoOrder> {
+    let child_order_ids: String = row.get(19)?;
+
+    Ok(AlgoOrder {
+        algo_id: row.get(0)?,
+        algo_type: row.get(1)?,
+        symbol: row.get(2)?,
+        exchange: row.get(3)?,
+        side: row.get(4)?,
+        product: row.get(5)?,
+        quantity: row.get(6)?,
+        limit_price: row.get(7)?,
+        duration_secs: row.get(8)?,
+        num_slices: row.get(9)?,
+        participation_rate: row.get(10)?,
+        display_quantity: row.get(11)?,
+        disclosed_quantity: row.get(12)?,
+        placed_quantity: row.get(13)?,
+        filled_quantity: row.get(14)?,
+        slices_placed: row.get(15)?,
+        active_order_id: row.get(16)?,
+        baseline_volume: row.get(17)?,
+        market_volume: row.get(18)?,
+        child_order_ids: serde_json::from_str(&child_order_ids).unwrap_or_default(),
+        next_slice_at: row.get(20)?,
+        ends_at: row.get(21)?,
+        status: row.get(22)?,
+        message: row.get(23)?,
+        created_at: row.get(24)?,
+        updated_at: row.get(25)?,
+    })
+}
+
+/// Store a new execution algo
+pub fn create_algo(conn: &Connection, algo: &AlgoOrder, mode: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO algo_orders (algo_id, mode, algo_type, symbol, exchange, side, product, quantity,
+                                  limit_price, duration_secs, num_slices, participation_rate,
+                                  display_quantity, disclosed_quantity, next_slice_at, ends_at, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+        params![
+            algo.algo_id,
+            mode,
+            algo.algo_type,
+            algo.symbol,
+            algo.exchange,
+            algo.side,
+            algo.product,
+            algo.quantity,
+            algo.limit_price,
+            algo.duration_secs,
+            algo.num_slices,
+            algo.participation_rate,
+            algo.display_quantity,
+            algo.disclosed_quantity,
+            algo.next_slice_at,
+            algo.ends_at,
+            algo.status,
+        ],
+    )?;
+
+    tracing::info!(
+        "Created {} algo {} for {} {}:{}",
+        algo.algo_type, algo.algo_id, algo.quantity, algo.exchange, algo.symbol
+    );
+    Ok(())
+}
+
+/// Get an execution algo by ID
+pub fn get_algo(conn: &Connection, algo_id: &str) -> Result<Option<AlgoOrder>> {
+    let sql = format!("SELECT {} FROM algo_orders WHERE algo_id = ?1", SELECT_COLUMNS);
+    match conn.query_row(&sql, [algo_id], row_to_algo) {
+        Ok(algo) => Ok(Some(algo)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get execution algos for a mode (live/analyze), newest first
+///
+/// With `running_only`, only algos still working their quantity are returned.
+pub fn get_algos(conn: &Connection, mode: &str, running_only: bool) -> Result<Vec<AlgoOrder>> {
+    let sql = if running_only {
+        format!(
+            "SELECT {} FROM algo_orders WHERE mode = ?1 AND status = 'running' ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    } else {
+        format!(
+            "SELECT {} FROM algo_orders WHERE mode = ?1 ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    };
+
+    let mut stmt = conn.prepare(&sql)?;
+    let algos = stmt
+        .query_map([mode], row_to_algo)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(algos)
+}
+
+/// Save the progress of a running algo
+///
+/// Returns false if the algo was no longer running (e.g. cancelled meanwhile).
+pub fn update_algo_progress(conn: &Connection, algo: &AlgoOrder) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE algo_orders SET placed_quantity = ?1, filled_quantity = ?2, slices_placed = ?3,
+                active_order_id = ?4, baseline_volume = ?5, market_volume = ?6, child_order_ids = ?7,
+                next_slice_at = ?8, updated_at = datetime('now')
+         WHERE algo_id = ?9 AND status = 'running'",
+        params![
+            algo.placed_quantity,
+            algo.filled_quantity,
+            algo.slices_placed,
+            algo.active_order_id,
+            algo.baseline_volume,
+            algo.market_volume,
+            serde_json::to_string(&algo.child_order_ids)?,
+            algo.next_slice_at,
+            algo.algo_id,
+        ],
+    )?;
+
+    Ok(rows > 0)
+}
+
+/// Move a running algo to a final status (completed/cancelled/failed)
+///
+/// Returns false if the algo had already finished.
+pub fn finish_algo(conn: &Connection, algo_id: &str, status: &str, message: Option<&str>) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE algo_orders SET status = ?1, message = ?2, updated_at = datetime('now')
+         WHERE algo_id = ?3 AND status = 'running'",
+        params![status, message, algo_id],
+    )?;
+
+    Ok(rows > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::migrations::run_migrations;
+
+    fn twap(algo_id: &str) -> AlgoOrder {
+        AlgoOrder {
+            algo_id: algo_id.to_string(),
+            algo_type: "TWAP".to_string(),
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            side: "BUY".to_string(),
+            product: "MIS".to_string(),
+            quantity: 100,
+            limit_price: None,
+            duration_secs: Some(600),
+            num_slices: Some(10),
+            participation_rate: None,
+            display_quantity: None,
+            disclosed_quantity: None,
+            placed_quantity: 0,
+            filled_quantity: 0,
+            slices_placed: 0,
+            active_order_id: None,
+            baseline_volume: None,
+            market_volume: 0,
+            child_order_ids: Vec::new(),
+            next_slice_at: Some("2024-01-01T09:15:00+00:00".to_string()),
+            ends_at: Some("2024-01-01T09:25:00+00:00".to_string()),
+            status: "running".to_string(),
+            message: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_algo_progress_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let mut algo = twap("ALGO1");
+        create_algo(&conn, &algo, "analyze").unwrap();
+        assert_eq!(get_algos(&conn, "analyze", true).unwrap().len(), 1);
+        assert!(get_algos(&conn, "live", true).unwrap().is_empty());
+
+        algo.placed_quantity = 10;
+        algo.slices_placed = 1;
+        algo.child_order_ids = vec!["SB-1".to_string()];
+        assert!(update_algo_progress(&conn, &algo).unwrap());
+
+        let stored = get_algo(&conn, "ALGO1").unwrap().unwrap();
+        assert_eq!(stored.placed_quantity, 10);
+        assert_eq!(stored.child_order_ids, vec!["SB-1".to_string()]);
+
+        // A finished algo no longer takes progress or status updates
+        assert!(finish_algo(&conn, "ALGO1", "cancelled", Some("Cancelled by user")).unwrap());
+        assert!(!update_algo_progress(&conn, &algo).unwrap());
+        assert!(!finish_algo(&conn, "ALGO1", "completed", None).unwrap());
+        assert!(get_algos(&conn, "analyze", true).unwrap().is_empty());
+        assert_eq!(get_algo(&conn, "ALGO1").unwrap().unwrap().status, "cancelled");
+    }
+}
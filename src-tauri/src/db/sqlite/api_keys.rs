@@ -3,7 +3,7 @@
 use crate::error::{AppError, Result};
 use crate::security::SecurityManager;
 use rusqlite::{params, Connection};
-use super::models::{ApiKey, ApiKeyInfo};
+use super::models::{ApiKey, ApiKeyInfo, ApiKeyRateLimits};
 
 /// Generate a random 64-character hex API key
 pub fn generate_api_key() -> String {
@@ -125,7 +125,8 @@ pub fn list_api_keys(
 ) -> Result<Vec<ApiKeyInfo>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, name, encrypted_key, nonce, permissions, created_at, last_used_at
+        SELECT id, name, encrypted_key, nonce, permissions, created_at, last_used_at,
+               api_rate_limit, order_rate_limit, smart_order_rate_limit
         FROM api_keys
         ORDER BY created_at DESC
         "#
@@ -139,10 +140,11 @@ pub fn list_api_keys(
         let permissions: String = row.get(4)?;
         let created_at: String = row.get(5)?;
         let last_used_at: Option<String> = row.get(6)?;
+        let rate_limits = rate_limits_from_row(row, 7)?;
 
-        Ok((id, name, encrypted_key, nonce, permissions, created_at, last_used_at))
+        Ok((id, name, encrypted_key, nonce, permissions, created_at, last_used_at, rate_limits))
     })?.filter_map(|r| r.ok())
-    .filter_map(|(id, name, encrypted_key, nonce, permissions, created_at, last_used_at)| {
+    .filter_map(|(id, name, encrypted_key, nonce, permissions, created_at, last_used_at, rate_limits)| {
         // Decrypt the key to get masked version
         let key_masked = match security.decrypt(&encrypted_key, &nonce) {
             Ok(decrypted) => mask_api_key(&decrypted),
@@ -156,6 +158,7 @@ pub fn list_api_keys(
             permissions,
             created_at,
             last_used_at,
+            rate_limits,
         })
     })
     .collect();
@@ -163,6 +166,84 @@ pub fn list_api_keys(
     Ok(keys)
 }
 
+/// All API keys (decrypted) with their rate limit overrides
+///
+/// Used to key the REST API rate limiter by API key without running an
+/// Argon2 verification per request.
+pub fn list_api_key_rate_limits(
+    conn: &Connection,
+    security: &SecurityManager,
+) -> Result<Vec<(String, ApiKeyRateLimits)>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT encrypted_key, nonce, api_rate_limit, order_rate_limit, smart_order_rate_limit
+        FROM api_keys
+        "#
+    )?;
+
+    let keys = stmt.query_map([], |row| {
+        let encrypted_key: String = row.get(0)?;
+        let nonce: String = row.get(1)?;
+        Ok((encrypted_key, nonce, rate_limits_from_row(row, 2)?))
+    })?.filter_map(|r| r.ok())
+    .filter_map(|(encrypted_key, nonce, rate_limits)| {
+        security.decrypt(&encrypted_key, &nonce).ok().map(|key| (key, rate_limits))
+    })
+    .collect();
+
+    Ok(keys)
+}
+
+/// Set (or clear, with None) an API key's rate limit overrides
+pub fn update_api_key_rate_limits(
+    conn: &Connection,
+    id: i64,
+    rate_limits: &ApiKeyRateLimits,
+) -> Result<bool> {
+    let checks = [
+        ("api_rate_limit", rate_limits.api_rate_limit, 1000),
+        ("order_rate_limit", rate_limits.order_rate_limit, 100),
+        ("smart_order_rate_limit", rate_limits.smart_order_rate_limit, 20),
+    ];
+    for (name, limit, max) in checks {
+        if let Some(limit) = limit {
+            if limit < 1 || limit > max {
+                return Err(AppError::Validation(format!(
+                    "{} must be between 1 and {} per second",
+                    name, max
+                )));
+            }
+        }
+    }
+
+    let rows_affected = conn.execute(
+        r#"
+        UPDATE api_keys
+        SET api_rate_limit = ?2, order_rate_limit = ?3, smart_order_rate_limit = ?4
+        WHERE id = ?1
+        "#,
+        params![
+            id,
+            rate_limits.api_rate_limit,
+            rate_limits.order_rate_limit,
+            rate_limits.smart_order_rate_limit
+        ],
+    )?;
+
+    if rows_affected > 0 {
+        tracing::info!("Updated rate limits for API key with id {}: {:?}", id, rate_limits);
+    }
+    Ok(rows_affected > 0)
+}
+
+fn rate_limits_from_row(row: &rusqlite::Row, start: usize) -> rusqlite::Result<ApiKeyRateLimits> {
+    Ok(ApiKeyRateLimits {
+        api_rate_limit: row.get(start)?,
+        order_rate_limit: row.get(start + 1)?,
+        smart_order_rate_limit: row.get(start + 2)?,
+    })
+}
+
 /// Get API key by name
 pub fn get_api_key_by_name(
     conn: &Connection,
@@ -292,7 +373,10 @@ mod tests {
                 nonce TEXT NOT NULL,
                 permissions TEXT NOT NULL DEFAULT 'read',
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                last_used_at TEXT
+                last_used_at TEXT,
+                api_rate_limit INTEGER,
+                order_rate_limit INTEGER,
+                smart_order_rate_limit INTEGER
             )
             "#,
             [],
@@ -382,6 +466,29 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn test_api_key_rate_limits() {
+        let (conn, security) = create_test_db();
+
+        let (id, api_key) = create_api_key(&conn, "dashboard", "read", &security).unwrap();
+        let limits = ApiKeyRateLimits {
+            api_rate_limit: Some(5),
+            ..Default::default()
+        };
+        assert!(update_api_key_rate_limits(&conn, id, &limits).unwrap());
+        assert!(!update_api_key_rate_limits(&conn, id + 1, &limits).unwrap());
+
+        let too_fast = ApiKeyRateLimits {
+            order_rate_limit: Some(500),
+            ..Default::default()
+        };
+        assert!(update_api_key_rate_limits(&conn, id, &too_fast).is_err());
+
+        let keys = list_api_key_rate_limits(&conn, &security).unwrap();
+        assert_eq!(keys, vec![(api_key, limits)]);
+        assert_eq!(list_api_keys(&conn, &security).unwrap()[0].rate_limits, limits);
+    }
+
     #[test]
     fn test_duplicate_name_rejected() {
         let (conn, security) = create_test_db();
@@ -0,0 +1,152 @@
+//! Emulated bracket/cover order storage
+//!
+//! Keeps the link between a bracket's entry order and the emulated GTT that
+//! holds its exit legs, so open brackets keep trailing after a restart.
+
+use crate::brokers::types::BracketOrder;
+use crate::error::Result;
+use rusqlite::{params, Connection, Row};
+
+const SELECT_COLUMNS: &str = "bracket_id, symbol, exchange, side, quantity, product, entry_order_id,
+                              entry_price, target, stoploss, trailing_stoploss, stop_price, best_price,
+                              gtt_id, status, message, created_at, updated_at";
+
+fn row_to_bracket(row: &Row) -> rusqlite::Result<BracketOrder> {
+    Ok(BracketOrder {
+        bracket_id: row.get(0)?,
+        symbol: row.get(1)?,
+        exchange: row.get(2)?,
+        side: row.get(3)?,
+        quantity: row.get(4)?,
+        product: row.get(5)?,
+        entry_order_id: row.get(6)?,
+        entry_price: row.get(7)?,
+        target: row.get(8)?,
+        stoploss: row.get(9)?,
+        trailing_stoploss: row.get(10)?,
+        stop_price: row.get(11)?,
+        best_price: row.get(12)?,
+        gtt_id: row.get(13)?,
+        status: row.get(14)?,
+        message: row.get(15)?,
+        created_at: row.get(16)?,
+        updated_at: row.get(17)?,
+    })
+}
+
+/// Store a new emulated bracket/cover order
+pub fn create_bracket(conn: &Connection, bracket: &BracketOrder, mode: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO bracket_orders (bracket_id, mode, symbol, exchange, side, quantity, product,
+                                     entry_order_id, target, stoploss, trailing_stoploss, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            bracket.bracket_id,
+            mode,
+            bracket.symbol,
+            bracket.exchange,
+            bracket.side,
+            bracket.quantity,
+            bracket.product,
+            bracket.entry_order_id,
+            bracket.target,
+            bracket.stoploss,
+            bracket.trailing_stoploss,
+            bracket.status,
+        ],
+    )?;
+
+    tracing::info!(
+        "Created emulated {} {} for {}:{}",
+        bracket.product, bracket.bracket_id, bracket.exchange, bracket.symbol
+    );
+    Ok(())
+}
+
+/// Get an emulated bracket order by ID
+pub fn get_bracket(conn: &Connection, bracket_id: &str) -> Result<Option<BracketOrder>> {
+    let sql = format!("SELECT {} FROM bracket_orders WHERE bracket_id = ?1", SELECT_COLUMNS);
+    match conn.query_row(&sql, [bracket_id], row_to_bracket) {
+        Ok(bracket) => Ok(Some(bracket)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get emulated bracket orders for a mode (live/analyze), newest first
+///
+/// With `live_only`, only brackets still waiting on an entry fill or holding
+/// exit legs (pending/open) are returned.
+pub fn get_brackets(conn: &Connection, mode: &str, live_only: bool) -> Result<Vec<BracketOrder>> {
+    let sql = if live_only {
+        format!(
+            "SELECT {} FROM bracket_orders WHERE mode = ?1 AND status IN ('pending', 'open')
+             ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    } else {
+        format!(
+            "SELECT {} FROM bracket_orders WHERE mode = ?1 ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    };
+
+    let mut stmt = conn.prepare(&sql)?;
+    let brackets = stmt
+        .query_map([mode], row_to_bracket)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(brackets)
+}
+
+/// Mark a pending bracket open once its entry filled and exit legs are linked
+///
+/// Returns false if the bracket was no longer pending.
+pub fn activate_bracket(
+    conn: &Connection,
+    bracket_id: &str,
+    entry_price: f64,
+    stop_price: f64,
+    gtt_id: &str,
+) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE bracket_orders SET status = 'open', entry_price = ?1, stop_price = ?2, best_price = ?1,
+                gtt_id = ?3, updated_at = datetime('now')
+         WHERE bracket_id = ?4 AND status = 'pending'",
+        params![entry_price, stop_price, gtt_id, bracket_id],
+    )?;
+
+    Ok(rows > 0)
+}
+
+/// Record a trailed stoploss and the best price that moved it
+pub fn update_bracket_stop(
+    conn: &Connection,
+    bracket_id: &str,
+    stop_price: f64,
+    best_price: f64,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE bracket_orders SET stop_price = ?1, best_price = ?2, updated_at = datetime('now')
+         WHERE bracket_id = ?3 AND status = 'open'",
+        params![stop_price, best_price, bracket_id],
+    )?;
+
+    Ok(())
+}
+
+/// Move a bracket to a final status (closed/cancelled/rejected)
+pub fn set_bracket_status(
+    conn: &Connection,
+    bracket_id: &str,
+    status: &str,
+    message: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE bracket_orders SET status = ?1, message = ?2, updated_at = datetime('now')
+         WHERE bracket_id = ?3",
+        params![status, message, bracket_id],
+    )?;
+
+    Ok(())
+}
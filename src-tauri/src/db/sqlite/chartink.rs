@@ -0,0 +1,623 @@
+//! Chartink strategies and per-stock overrides
+//!
+//! A Chartink strategy trades whatever stocks a scan alert lists, using the
+//! strategy's default exchange, quantity and product. A symbol mapping
+//! overrides the quantity (and optionally the product) for one stock.
+//! Orders are recorded in `chartink_orders` to enforce the per-day re-entry
+//! limit. Alerts are verified like strategy alerts (auth mode, secret, IP
+//! allowlist, replay window).
+
+use super::strategy::{merge_webhook_security, WebhookSecurity};
+use crate::error::{AppError, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// Chartink strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartinkStrategy {
+    pub id: i64,
+    pub name: String,
+    pub webhook_id: String,
+    pub scan_url: Option<String>,
+    /// Default exchange for stocks without a mapping
+    pub exchange: String,
+    /// Default product (MIS, CNC or NRML)
+    pub product: String,
+    /// Default quantity per stock
+    pub quantity: i32,
+    /// Entries beyond this many stocks in one alert are skipped
+    pub max_symbols_per_alert: i32,
+    /// Entries per stock per day (0 = unlimited)
+    pub max_entries_per_day: i32,
+    pub enabled: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Webhook verification: none, secret or hmac
+    pub webhook_auth_mode: String,
+    pub webhook_secret: Option<String>,
+    /// Comma-separated IPs/CIDRs allowed to send alerts
+    pub webhook_ip_allowlist: Option<String>,
+    pub replay_window_secs: i64,
+}
+
+/// Per-stock override for a Chartink strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartinkSymbol {
+    pub id: i64,
+    pub strategy_id: i64,
+    pub exchange: String,
+    pub symbol: String,
+    pub quantity: i32,
+    /// Product override; the strategy default is used when None
+    pub product: Option<String>,
+    pub created_at: String,
+}
+
+/// Request to create a Chartink strategy
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateChartinkStrategyRequest {
+    pub name: String,
+    pub webhook_id: String,
+    pub scan_url: Option<String>,
+    #[serde(default = "default_exchange")]
+    pub exchange: String,
+    #[serde(default = "default_product")]
+    pub product: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+    #[serde(default = "default_max_symbols")]
+    pub max_symbols_per_alert: i32,
+    #[serde(default = "default_max_entries")]
+    pub max_entries_per_day: i32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Request to update a Chartink strategy (None leaves a field unchanged)
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateChartinkStrategyRequest {
+    pub name: Option<String>,
+    pub scan_url: Option<String>,
+    pub exchange: Option<String>,
+    pub product: Option<String>,
+    pub quantity: Option<i32>,
+    pub max_symbols_per_alert: Option<i32>,
+    pub max_entries_per_day: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+fn default_exchange() -> String {
+    "NSE".to_string()
+}
+
+fn default_product() -> String {
+    "MIS".to_string()
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+fn default_max_symbols() -> i32 {
+    10
+}
+
+fn default_max_entries() -> i32 {
+    1
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+const STRATEGY_COLUMNS: &str = "id, name, webhook_id, scan_url, exchange, product, quantity,
+                                max_symbols_per_alert, max_entries_per_day, enabled,
+                                created_at, updated_at, webhook_auth_mode, webhook_secret,
+                                webhook_ip_allowlist, replay_window_secs";
+
+fn row_to_strategy(row: &Row) -> rusqlite::Result<ChartinkStrategy> {
+    Ok(ChartinkStrategy {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        webhook_id: row.get(2)?,
+        scan_url: row.get(3)?,
+        exchange: row.get(4)?,
+        product: row.get(5)?,
+        quantity: row.get(6)?,
+        max_symbols_per_alert: row.get(7)?,
+        max_entries_per_day: row.get(8)?,
+        enabled: row.get::<_, i32>(9)? != 0,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+        webhook_auth_mode: row.get(12)?,
+        webhook_secret: row.get(13)?,
+        webhook_ip_allowlist: row.get(14)?,
+        replay_window_secs: row.get(15)?,
+    })
+}
+
+fn row_to_symbol(row: &Row) -> rusqlite::Result<ChartinkSymbol> {
+    Ok(ChartinkSymbol {
+        id: row.get(0)?,
+        strategy_id: row.get(1)?,
+        exchange: row.get(2)?,
+        symbol: row.get(3)?,
+        quantity: row.get(4)?,
+        product: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn validate_product(product: &str) -> Result<()> {
+    if !matches!(product, "MIS" | "CNC" | "NRML") {
+        return Err(AppError::Validation(format!(
+            "Invalid product: {} (expected MIS, CNC or NRML)",
+            product
+        )));
+    }
+    Ok(())
+}
+
+fn validate_strategy(strategy: &ChartinkStrategy) -> Result<()> {
+    if strategy.name.trim().is_empty() {
+        return Err(AppError::Validation("Strategy name is required".to_string()));
+    }
+    validate_product(&strategy.product)?;
+    if strategy.quantity <= 0 {
+        return Err(AppError::Validation("Quantity must be positive".to_string()));
+    }
+    if strategy.max_symbols_per_alert < 1 {
+        return Err(AppError::Validation("max_symbols_per_alert must be at least 1".to_string()));
+    }
+    if strategy.max_entries_per_day < 0 {
+        return Err(AppError::Validation("max_entries_per_day cannot be negative".to_string()));
+    }
+    Ok(())
+}
+
+/// Get all Chartink strategies
+pub fn get_strategies(conn: &Connection) -> Result<Vec<ChartinkStrategy>> {
+    let sql = format!("SELECT {} FROM chartink_strategies ORDER BY created_at DESC", STRATEGY_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+
+    let strategies = stmt
+        .query_map([], row_to_strategy)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(strategies)
+}
+
+/// Get a Chartink strategy by ID
+pub fn get_strategy(conn: &Connection, id: i64) -> Result<ChartinkStrategy> {
+    let sql = format!("SELECT {} FROM chartink_strategies WHERE id = ?", STRATEGY_COLUMNS);
+    conn.query_row(&sql, [id], row_to_strategy)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Chartink strategy not found: {}", id)))
+}
+
+/// Get a Chartink strategy by webhook_id (for webhook handler)
+pub fn get_strategy_by_webhook_id(conn: &Connection, webhook_id: &str) -> Result<Option<ChartinkStrategy>> {
+    let sql = format!("SELECT {} FROM chartink_strategies WHERE webhook_id = ?", STRATEGY_COLUMNS);
+    Ok(conn.query_row(&sql, [webhook_id], row_to_strategy).optional()?)
+}
+
+/// Create a Chartink strategy
+pub fn create_strategy(conn: &Connection, req: &CreateChartinkStrategyRequest) -> Result<ChartinkStrategy> {
+    let strategy = ChartinkStrategy {
+        id: 0,
+        name: req.name.trim().to_string(),
+        webhook_id: req.webhook_id.trim().to_string(),
+        scan_url: req.scan_url.clone().filter(|u| !u.trim().is_empty()),
+        exchange: req.exchange.to_uppercase(),
+        product: req.product.to_uppercase(),
+        quantity: req.quantity,
+        max_symbols_per_alert: req.max_symbols_per_alert,
+        max_entries_per_day: req.max_entries_per_day,
+        enabled: req.enabled,
+        created_at: String::new(),
+        updated_at: String::new(),
+        webhook_auth_mode: "none".to_string(),
+        webhook_secret: None,
+        webhook_ip_allowlist: None,
+        replay_window_secs: 300,
+    };
+    validate_strategy(&strategy)?;
+
+    if strategy.webhook_id.is_empty() {
+        return Err(AppError::Validation("webhook_id is required".to_string()));
+    }
+
+    // Webhook IDs share one URL namespace with regular strategies
+    let taken: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM strategies WHERE webhook_id = ?1)
+             OR EXISTS(SELECT 1 FROM chartink_strategies WHERE webhook_id = ?1)",
+        [&strategy.webhook_id],
+        |row| row.get(0),
+    )?;
+    if taken {
+        return Err(AppError::Validation(format!(
+            "webhook_id '{}' is already in use",
+            strategy.webhook_id
+        )));
+    }
+
+    conn.execute(
+        "INSERT INTO chartink_strategies (name, webhook_id, scan_url, exchange, product, quantity,
+                                          max_symbols_per_alert, max_entries_per_day, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            strategy.name,
+            strategy.webhook_id,
+            strategy.scan_url,
+            strategy.exchange,
+            strategy.product,
+            strategy.quantity,
+            strategy.max_symbols_per_alert,
+            strategy.max_entries_per_day,
+            strategy.enabled as i32,
+        ],
+    )?;
+
+    get_strategy(conn, conn.last_insert_rowid())
+}
+
+/// Update a Chartink strategy
+pub fn update_strategy(
+    conn: &Connection,
+    id: i64,
+    req: &UpdateChartinkStrategyRequest,
+) -> Result<ChartinkStrategy> {
+    let mut strategy = get_strategy(conn, id)?;
+
+    if let Some(name) = &req.name {
+        strategy.name = name.trim().to_string();
+    }
+    if let Some(scan_url) = &req.scan_url {
+        strategy.scan_url = Some(scan_url.trim().to_string()).filter(|u| !u.is_empty());
+    }
+    if let Some(exchange) = &req.exchange {
+        strategy.exchange = exchange.to_uppercase();
+    }
+    if let Some(product) = &req.product {
+        strategy.product = product.to_uppercase();
+    }
+    if let Some(quantity) = req.quantity {
+        strategy.quantity = quantity;
+    }
+    if let Some(max_symbols) = req.max_symbols_per_alert {
+        strategy.max_symbols_per_alert = max_symbols;
+    }
+    if let Some(max_entries) = req.max_entries_per_day {
+        strategy.max_entries_per_day = max_entries;
+    }
+    if let Some(enabled) = req.enabled {
+        strategy.enabled = enabled;
+    }
+    validate_strategy(&strategy)?;
+
+    conn.execute(
+        "UPDATE chartink_strategies SET name = ?1, scan_url = ?2, exchange = ?3, product = ?4,
+                quantity = ?5, max_symbols_per_alert = ?6, max_entries_per_day = ?7, enabled = ?8,
+                updated_at = datetime('now')
+         WHERE id = ?9",
+        params![
+            strategy.name,
+            strategy.scan_url,
+            strategy.exchange,
+            strategy.product,
+            strategy.quantity,
+            strategy.max_symbols_per_alert,
+            strategy.max_entries_per_day,
+            strategy.enabled as i32,
+            id,
+        ],
+    )?;
+
+    get_strategy(conn, id)
+}
+
+/// Update a Chartink strategy's webhook verification settings
+///
+/// `None` leaves a field unchanged; an empty secret or allowlist clears it.
+pub fn update_strategy_security(
+    conn: &Connection,
+    id: i64,
+    auth_mode: Option<String>,
+    webhook_secret: Option<String>,
+    ip_allowlist: Option<String>,
+    replay_window_secs: Option<i64>,
+) -> Result<ChartinkStrategy> {
+    let existing = get_strategy(conn, id)?;
+    let security = merge_webhook_security(
+        WebhookSecurity {
+            auth_mode: existing.webhook_auth_mode,
+            webhook_secret: existing.webhook_secret,
+            ip_allowlist: existing.webhook_ip_allowlist,
+            replay_window_secs: existing.replay_window_secs,
+        },
+        auth_mode,
+        webhook_secret,
+        ip_allowlist,
+        replay_window_secs,
+    )?;
+
+    conn.execute(
+        "UPDATE chartink_strategies SET webhook_auth_mode = ?1, webhook_secret = ?2,
+                webhook_ip_allowlist = ?3, replay_window_secs = ?4, updated_at = datetime('now')
+         WHERE id = ?5",
+        params![
+            security.auth_mode,
+            security.webhook_secret,
+            security.ip_allowlist,
+            security.replay_window_secs,
+            id
+        ],
+    )?;
+
+    get_strategy(conn, id)
+}
+
+/// Delete a Chartink strategy and its symbol overrides
+pub fn delete_strategy(conn: &Connection, id: i64) -> Result<()> {
+    // Foreign keys are not enforced, so remove mappings explicitly
+    conn.execute("DELETE FROM chartink_symbol_mappings WHERE strategy_id = ?", [id])?;
+    conn.execute(
+        "DELETE FROM webhook_nonces WHERE source = 'chartink' AND strategy_id = ?",
+        [id],
+    )?;
+
+    let rows = conn.execute("DELETE FROM chartink_strategies WHERE id = ?", [id])?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Chartink strategy not found: {}", id)));
+    }
+
+    Ok(())
+}
+
+/// Get the symbol overrides of a Chartink strategy
+pub fn get_symbols(conn: &Connection, strategy_id: i64) -> Result<Vec<ChartinkSymbol>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, strategy_id, exchange, symbol, quantity, product, created_at
+         FROM chartink_symbol_mappings WHERE strategy_id = ? ORDER BY symbol",
+    )?;
+
+    let symbols = stmt
+        .query_map([strategy_id], row_to_symbol)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(symbols)
+}
+
+/// Get the override for one stock, if any
+pub fn get_symbol(conn: &Connection, strategy_id: i64, symbol: &str) -> Result<Option<ChartinkSymbol>> {
+    Ok(conn
+        .query_row(
+            "SELECT id, strategy_id, exchange, symbol, quantity, product, created_at
+             FROM chartink_symbol_mappings WHERE strategy_id = ? AND symbol = ?",
+            params![strategy_id, symbol],
+            row_to_symbol,
+        )
+        .optional()?)
+}
+
+/// Add a symbol override
+pub fn add_symbol(
+    conn: &Connection,
+    strategy_id: i64,
+    exchange: &str,
+    symbol: &str,
+    quantity: i32,
+    product: Option<&str>,
+) -> Result<ChartinkSymbol> {
+    get_strategy(conn, strategy_id)?;
+
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err(AppError::Validation("Symbol is required".to_string()));
+    }
+    if quantity <= 0 {
+        return Err(AppError::Validation("Quantity must be positive".to_string()));
+    }
+    let product = product.map(|p| p.to_uppercase()).filter(|p| !p.is_empty());
+    if let Some(product) = &product {
+        validate_product(product)?;
+    }
+
+    conn.execute(
+        "INSERT INTO chartink_symbol_mappings (strategy_id, exchange, symbol, quantity, product)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![strategy_id, exchange.to_uppercase(), symbol, quantity, product],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            AppError::Validation(format!("{} already has an override in this strategy", symbol))
+        }
+        _ => e.into(),
+    })?;
+
+    let id = conn.last_insert_rowid();
+    Ok(conn.query_row(
+        "SELECT id, strategy_id, exchange, symbol, quantity, product, created_at
+         FROM chartink_symbol_mappings WHERE id = ?",
+        [id],
+        row_to_symbol,
+    )?)
+}
+
+/// Update a symbol override
+///
+/// An empty `product` clears the override back to the strategy default.
+pub fn update_symbol(
+    conn: &Connection,
+    id: i64,
+    exchange: Option<&str>,
+    quantity: Option<i32>,
+    product: Option<&str>,
+) -> Result<ChartinkSymbol> {
+    let mut symbol = conn
+        .query_row(
+            "SELECT id, strategy_id, exchange, symbol, quantity, product, created_at
+             FROM chartink_symbol_mappings WHERE id = ?",
+            [id],
+            row_to_symbol,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Chartink symbol not found: {}", id)))?;
+
+    if let Some(exchange) = exchange {
+        symbol.exchange = exchange.to_uppercase();
+    }
+    if let Some(quantity) = quantity {
+        if quantity <= 0 {
+            return Err(AppError::Validation("Quantity must be positive".to_string()));
+        }
+        symbol.quantity = quantity;
+    }
+    if let Some(product) = product {
+        symbol.product = Some(product.to_uppercase()).filter(|p| !p.is_empty());
+        if let Some(product) = &symbol.product {
+            validate_product(product)?;
+        }
+    }
+
+    conn.execute(
+        "UPDATE chartink_symbol_mappings SET exchange = ?1, quantity = ?2, product = ?3 WHERE id = ?4",
+        params![symbol.exchange, symbol.quantity, symbol.product, id],
+    )?;
+
+    Ok(symbol)
+}
+
+/// Delete a symbol override
+pub fn delete_symbol(conn: &Connection, id: i64) -> Result<bool> {
+    let rows = conn.execute("DELETE FROM chartink_symbol_mappings WHERE id = ?", [id])?;
+    Ok(rows > 0)
+}
+
+/// Record an order placed for a Chartink alert
+///
+/// `entry_id` groups the slices of one order split at the freeze limit.
+#[allow(clippy::too_many_arguments)]
+pub fn record_order(
+    conn: &Connection,
+    strategy_id: i64,
+    entry_id: &str,
+    order_id: &str,
+    mode: &str,
+    exchange: &str,
+    symbol: &str,
+    product: &str,
+    action: &str,
+    quantity: i32,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO chartink_orders (strategy_id, entry_id, order_id, mode, exchange, symbol, product, action, quantity)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![strategy_id, entry_id, order_id, mode, exchange, symbol, product, action, quantity],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Count entries (BUY/SHORT) for a stock in a mode since a UTC timestamp ("YYYY-MM-DD HH:MM:SS")
+///
+/// A sliced entry counts once.
+pub fn count_entries(
+    conn: &Connection,
+    strategy_id: i64,
+    symbol: &str,
+    mode: &str,
+    since: &str,
+) -> Result<i32> {
+    Ok(conn.query_row(
+        "SELECT COUNT(DISTINCT COALESCE(entry_id, order_id)) FROM chartink_orders
+         WHERE strategy_id = ?1 AND symbol = ?2 AND mode = ?3 AND action IN ('BUY', 'SHORT')
+               AND created_at >= ?4",
+        params![strategy_id, symbol, mode, since],
+        |row| row.get(0),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::migrations::run_migrations;
+
+    fn create_test_db() -> (Connection, i64) {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let req: CreateChartinkStrategyRequest =
+            serde_json::from_str(r#"{"name":"breakouts","webhook_id":"ck-1"}"#).unwrap();
+        let strategy = create_strategy(&conn, &req).unwrap();
+        (conn, strategy.id)
+    }
+
+    #[test]
+    fn test_create_uses_defaults_and_unique_webhook() {
+        let (conn, id) = create_test_db();
+        let strategy = get_strategy(&conn, id).unwrap();
+        assert_eq!(strategy.exchange, "NSE");
+        assert_eq!(strategy.product, "MIS");
+        assert_eq!(strategy.max_symbols_per_alert, 10);
+        assert_eq!(strategy.max_entries_per_day, 1);
+
+        let req: CreateChartinkStrategyRequest =
+            serde_json::from_str(r#"{"name":"again","webhook_id":"ck-1"}"#).unwrap();
+        assert!(create_strategy(&conn, &req).is_err());
+    }
+
+    #[test]
+    fn test_webhook_security() {
+        let (conn, id) = create_test_db();
+        assert_eq!(get_strategy(&conn, id).unwrap().webhook_auth_mode, "none");
+        assert!(update_strategy_security(&conn, id, Some("hmac".into()), None, None, None).is_err());
+
+        let updated = update_strategy_security(
+            &conn,
+            id,
+            Some("secret".into()),
+            Some("s3cret".into()),
+            Some("10.0.0.0/8".into()),
+            Some(120),
+        )
+        .unwrap();
+        assert_eq!(updated.webhook_auth_mode, "secret");
+        assert_eq!(updated.webhook_ip_allowlist.as_deref(), Some("10.0.0.0/8"));
+        let by_webhook = get_strategy_by_webhook_id(&conn, "ck-1").unwrap().unwrap();
+        assert_eq!(by_webhook.replay_window_secs, 120);
+    }
+
+    #[test]
+    fn test_symbol_override_and_product_reset() {
+        let (conn, id) = create_test_db();
+        let symbol = add_symbol(&conn, id, "nse", "sbin", 25, Some("cnc")).unwrap();
+        assert_eq!(symbol.symbol, "SBIN");
+        assert_eq!(symbol.product.as_deref(), Some("CNC"));
+        assert!(add_symbol(&conn, id, "NSE", "SBIN", 5, None).is_err());
+
+        let updated = update_symbol(&conn, symbol.id, None, Some(10), Some("")).unwrap();
+        assert_eq!(updated.quantity, 10);
+        assert!(updated.product.is_none());
+
+        assert!(get_symbol(&conn, id, "SBIN").unwrap().is_some());
+        delete_strategy(&conn, id).unwrap();
+        assert!(get_symbol(&conn, id, "SBIN").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_count_entries_ignores_exits() {
+        let (conn, id) = create_test_db();
+        record_order(&conn, id, "1", "1", "analyze", "NSE", "SBIN", "MIS", "BUY", 1).unwrap();
+        record_order(&conn, id, "2", "2", "analyze", "NSE", "SBIN", "MIS", "SELL", 1).unwrap();
+        record_order(&conn, id, "3", "3", "analyze", "NSE", "SBIN", "MIS", "SHORT", 1).unwrap();
+        // Slices of one entry split at the freeze limit
+        record_order(&conn, id, "3", "4", "analyze", "NSE", "SBIN", "MIS", "SHORT", 1).unwrap();
+
+        assert_eq!(count_entries(&conn, id, "SBIN", "analyze", "2000-01-01 00:00:00").unwrap(), 2);
+        assert_eq!(count_entries(&conn, id, "SBIN", "live", "2000-01-01 00:00:00").unwrap(), 0);
+        assert_eq!(count_entries(&conn, id, "INFY", "analyze", "2000-01-01 00:00:00").unwrap(), 0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Client order id storage (idempotent order placement)
+//!
+//! Remembers the response sent for each client order id so that a retried
+//! request returns the original result instead of placing a duplicate order.
+
+use crate::error::Result;
+use rusqlite::{params, Connection};
+
+/// A pending claim older than this is treated as abandoned (e.g. the app was
+/// closed mid-request) and can be claimed again.
+const PENDING_TIMEOUT_SECS: u32 = 120;
+
+/// Result of claiming a client order id
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientOrderClaim {
+    /// First request with this id; the caller should execute it
+    Claimed,
+    /// The original request is still being processed
+    InProgress,
+    /// The id was already used; replay the stored response
+    Completed { status_code: u16, response: String },
+    /// The id was already used for a different endpoint or payload
+    Mismatch { endpoint: String },
+}
+
+/// Claim a client order id for a request
+///
+/// Ids older than `window_secs` are forgotten, so the same id can be reused
+/// after the window.
+pub fn claim_client_order(
+    conn: &Connection,
+    client_order_id: &str,
+    endpoint: &str,
+    request_hash: &str,
+    window_secs: u32,
+) -> Result<ClientOrderClaim> {
+    conn.execute(
+        "DELETE FROM client_orders WHERE created_at < datetime('now', ?1)",
+        [format!("-{} seconds", window_secs)],
+    )?;
+    conn.execute(
+        "DELETE FROM client_orders
+         WHERE client_order_id = ?1 AND status = 'pending' AND created_at < datetime('now', ?2)",
+        params![client_order_id, format!("-{} seconds", PENDING_TIMEOUT_SECS)],
+    )?;
+
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO client_orders (client_order_id, endpoint, request_hash)
+         VALUES (?1, ?2, ?3)",
+        params![client_order_id, endpoint, request_hash],
+    )?;
+    if inserted > 0 {
+        return Ok(ClientOrderClaim::Claimed);
+    }
+
+    let existing = conn.query_row(
+        "SELECT endpoint, request_hash, status, status_code, response
+         FROM client_orders WHERE client_order_id = ?1",
+        [client_order_id],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<u16>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        },
+    )?;
+
+    let claim = match existing {
+        (stored_endpoint, stored_hash, _, _, _)
+            if stored_endpoint != endpoint || stored_hash != request_hash =>
+        {
+            ClientOrderClaim::Mismatch { endpoint: stored_endpoint }
+        }
+        (_, _, status, Some(status_code), Some(response)) if status == "completed" => {
+            ClientOrderClaim::Completed { status_code, response }
+        }
+        _ => ClientOrderClaim::InProgress,
+    };
+
+    Ok(claim)
+}
+
+/// Store the response sent for a claimed client order id
+pub fn complete_client_order(
+    conn: &Connection,
+    client_order_id: &str,
+    status_code: u16,
+    response: &str,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE client_orders SET status = 'completed', status_code = ?1, response = ?2,
+                updated_at = datetime('now')
+         WHERE client_order_id = ?3",
+        params![status_code, response, client_order_id],
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::migrations::run_migrations;
+
+    #[test]
+    fn test_client_order_replay() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let claim = |hash: &str| {
+            claim_client_order(&conn, "tv-alert-1", "/api/v1/placeorder", hash, 86400).unwrap()
+        };
+
+        assert_eq!(claim("h1"), ClientOrderClaim::Claimed);
+        assert_eq!(claim("h1"), ClientOrderClaim::InProgress);
+
+        complete_client_order(&conn, "tv-alert-1", 200, r#"{"status":"success"}"#).unwrap();
+        assert_eq!(
+            claim("h1"),
+            ClientOrderClaim::Completed {
+                status_code: 200,
+                response: r#"{"status":"success"}"#.to_string(),
+            }
+        );
+
+        // Same id with a different payload is rejected rather than replayed
+        assert_eq!(
+            claim("h2"),
+            ClientOrderClaim::Mismatch { endpoint: "/api/v1/placeorder".to_string() }
+        );
+    }
+}
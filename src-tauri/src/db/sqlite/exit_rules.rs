@@ -0,0 +1,130 @@
+//! Position and portfolio exit rule storage
+//!
+//! Active rules are reloaded by the exit monitor on every refresh, so they
+//! keep watching positions after a restart. The best value seen is saved for
+//! trailing rules so the trail doesn't reset either.
+
+use crate::brokers::types::ExitRule;
+use crate::error::Result;
+use rusqlite::{params, Connection, Row};
+
+const SELECT_COLUMNS: &str = "rule_id, scope, exchange, symbol, product, rule_type, target, stoploss,
+                              trailing, best_value, status, message, triggered_at, created_at, updated_at";
+
+fn row_to_rule(row: &Row) -> rusqlite::Result<ExitRule> {
+    Ok(ExitRule {
+        rule_id: row.get(0)?,
+        scope: row.get(1)?,
+        exchange: row.get(2)?,
+        symbol: row.get(3)?,
+        product: row.get(4)?,
+        rule_type: row.get(5)?,
+        target: row.get(6)?,
+        stoploss: row.get(7)?,
+        trailing: row.get(8)?,
+        best_value: row.get(9)?,
+        status: row.get(10)?,
+        message: row.get(11)?,
+        triggered_at: row.get(12)?,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+    })
+}
+
+/// Store a new exit rule
+pub fn create_rule(conn: &Connection, rule: &ExitRule, mode: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO exit_rules (rule_id, mode, scope, exchange, symbol, product, rule_type,
+                                 target, stoploss, trailing, best_value, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            rule.rule_id,
+            mode,
+            rule.scope,
+            rule.exchange,
+            rule.symbol,
+            rule.product,
+            rule.rule_type,
+            rule.target,
+            rule.stoploss,
+            rule.trailing,
+            rule.best_value,
+            rule.status,
+        ],
+    )?;
+
+    tracing::info!("Created {} exit rule {} ({})", rule.scope, rule.rule_id, rule.rule_type);
+    Ok(())
+}
+
+/// Get an exit rule by ID
+pub fn get_rule(conn: &Connection, rule_id: &str) -> Result<Option<ExitRule>> {
+    let sql = format!("SELECT {} FROM exit_rules WHERE rule_id = ?1", SELECT_COLUMNS);
+    match conn.query_row(&sql, [rule_id], row_to_rule) {
+        Ok(rule) => Ok(Some(rule)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get exit rules for a mode (live/analyze), newest first
+///
+/// With `active_only`, only rules still watching positions are returned.
+pub fn get_rules(conn: &Connection, mode: &str, active_only: bool) -> Result<Vec<ExitRule>> {
+    let sql = if active_only {
+        format!(
+            "SELECT {} FROM exit_rules WHERE mode = ?1 AND status = 'active' ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    } else {
+        format!(
+            "SELECT {} FROM exit_rules WHERE mode = ?1 ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    };
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rules = stmt
+        .query_map([mode], row_to_rule)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(rules)
+}
+
+/// Record the best value seen by an active trailing rule
+pub fn update_best_value(conn: &Connection, rule_id: &str, best_value: f64) -> Result<()> {
+    conn.execute(
+        "UPDATE exit_rules SET best_value = ?1, updated_at = datetime('now')
+         WHERE rule_id = ?2 AND status = 'active'",
+        params![best_value, rule_id],
+    )?;
+
+    Ok(())
+}
+
+/// Move an active rule to a final status (triggered/cancelled/error)
+///
+/// Returns false if the rule was no longer active, so a rule fires at most
+/// once even if a tick and a cancel race.
+pub fn finish_rule(conn: &Connection, rule_id: &str, status: &str, message: Option<&str>) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE exit_rules SET status = ?1, message = ?2,
+                triggered_at = CASE WHEN ?1 = 'triggered' THEN datetime('now') ELSE triggered_at END,
+                updated_at = datetime('now')
+         WHERE rule_id = ?3 AND status = 'active'",
+        params![status, message, rule_id],
+    )?;
+
+    Ok(rows > 0)
+}
+
+/// Set the status of a triggered rule once its close orders are known
+pub fn set_rule_status(conn: &Connection, rule_id: &str, status: &str, message: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE exit_rules SET status = ?1, message = ?2, updated_at = datetime('now')
+         WHERE rule_id = ?3",
+        params![status, message, rule_id],
+    )?;
+
+    Ok(())
+}
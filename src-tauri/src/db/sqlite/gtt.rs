@@ -0,0 +1,140 @@
+//! Emulated GTT order storage
+//!
+//! GTT orders for brokers without native support (and for sandbox) are kept
+//! here and fired by the GTT monitor when a tick crosses the trigger.
+
+use crate::brokers::types::{GttLeg, GttOrder, GttRequest};
+use crate::error::Result;
+use rusqlite::{params, types::Type, Connection, Row};
+
+const SELECT_COLUMNS: &str = "gtt_id, symbol, exchange, trigger_type, trigger_prices, last_price,
+                              legs, status, order_id, created_at, updated_at";
+
+/// Parse a JSON column, surfacing bad data as a conversion error
+fn json_column<T: serde::de::DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<T> {
+    let raw: String = row.get(idx)?;
+    serde_json::from_str(&raw)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(e)))
+}
+
+fn row_to_gtt(row: &Row) -> rusqlite::Result<GttOrder> {
+    Ok(GttOrder {
+        gtt_id: row.get(0)?,
+        symbol: row.get(1)?,
+        exchange: row.get(2)?,
+        trigger_type: row.get(3)?,
+        trigger_prices: json_column::<Vec<f64>>(row, 4)?,
+        last_price: row.get(5)?,
+        legs: json_column::<Vec<GttLeg>>(row, 6)?,
+        status: row.get(7)?,
+        order_id: row.get(8)?,
+        emulated: true,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+/// Store a new emulated GTT order
+pub fn create_gtt(conn: &Connection, gtt: &GttOrder, mode: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO gtt_orders (gtt_id, mode, symbol, exchange, trigger_type, trigger_prices, last_price, legs, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            gtt.gtt_id,
+            mode,
+            gtt.symbol,
+            gtt.exchange,
+            gtt.trigger_type,
+            serde_json::to_string(&gtt.trigger_prices)?,
+            gtt.last_price,
+            serde_json::to_string(&gtt.legs)?,
+            gtt.status,
+        ],
+    )?;
+
+    tracing::info!("Created emulated GTT {} for {}:{}", gtt.gtt_id, gtt.exchange, gtt.symbol);
+    Ok(())
+}
+
+/// Get an emulated GTT order by ID
+pub fn get_gtt(conn: &Connection, gtt_id: &str) -> Result<Option<GttOrder>> {
+    let sql = format!("SELECT {} FROM gtt_orders WHERE gtt_id = ?1", SELECT_COLUMNS);
+    match conn.query_row(&sql, [gtt_id], row_to_gtt) {
+        Ok(gtt) => Ok(Some(gtt)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get emulated GTT orders for a mode (live/analyze), newest first
+pub fn get_gtts(conn: &Connection, mode: &str, active_only: bool) -> Result<Vec<GttOrder>> {
+    let sql = if active_only {
+        format!(
+            "SELECT {} FROM gtt_orders WHERE mode = ?1 AND status = 'active' ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    } else {
+        format!(
+            "SELECT {} FROM gtt_orders WHERE mode = ?1 ORDER BY created_at DESC",
+            SELECT_COLUMNS
+        )
+    };
+
+    let mut stmt = conn.prepare(&sql)?;
+    let gtts = stmt
+        .query_map([mode], row_to_gtt)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(gtts)
+}
+
+/// Replace the trigger and legs of an active GTT order
+///
+/// Returns false if the GTT doesn't exist or has already fired.
+pub fn update_gtt(conn: &Connection, gtt_id: &str, gtt: &GttRequest) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE gtt_orders SET trigger_type = ?1, trigger_prices = ?2, last_price = ?3, legs = ?4,
+                updated_at = datetime('now')
+         WHERE gtt_id = ?5 AND status = 'active'",
+        params![
+            gtt.trigger_type,
+            serde_json::to_string(&gtt.trigger_prices)?,
+            gtt.last_price,
+            serde_json::to_string(&gtt.legs)?,
+            gtt_id,
+        ],
+    )?;
+
+    Ok(rows > 0)
+}
+
+/// Atomically move an active GTT to a new status
+///
+/// Used both to claim a GTT when it fires (so two ticks can't fire it twice)
+/// and to cancel it. Returns false if the GTT was no longer active.
+pub fn transition_gtt(conn: &Connection, gtt_id: &str, status: &str) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE gtt_orders SET status = ?1, updated_at = datetime('now')
+         WHERE gtt_id = ?2 AND status = 'active'",
+        params![status, gtt_id],
+    )?;
+
+    Ok(rows > 0)
+}
+
+/// Record the outcome of a fired GTT
+pub fn set_gtt_result(
+    conn: &Connection,
+    gtt_id: &str,
+    status: &str,
+    order_id: Option<&str>,
+    message: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE gtt_orders SET status = ?1, order_id = ?2, message = ?3, updated_at = datetime('now')
+         WHERE gtt_id = ?4",
+        params![status, order_id, message, gtt_id],
+    )?;
+
+    Ok(())
+}
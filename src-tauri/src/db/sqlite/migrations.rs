@@ -52,6 +52,29 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
     run_migration(conn, "034_broker_credentials", CREATE_BROKER_CREDENTIALS_TABLE)?;
     run_migration(conn, "035_rate_limit_settings", ADD_RATE_LIMIT_SETTINGS)?;
     run_migration(conn, "036_enable_webhook_default", ENABLE_WEBHOOK_BY_DEFAULT)?;
+    run_migration(conn, "037_gtt_orders", CREATE_GTT_ORDERS_TABLE)?;
+    run_migration(conn, "038_bracket_orders", CREATE_BRACKET_ORDERS_TABLE)?;
+    run_migration(conn, "039_order_update_webhook", ADD_ORDER_UPDATE_WEBHOOK)?;
+    run_migration(conn, "040_notifications", CREATE_NOTIFICATION_TABLES)?;
+    run_migration(conn, "041_order_validation_mode", ADD_ORDER_VALIDATION_MODE)?;
+    run_migration(conn, "042_strategy_symbol_mappings_product", EXTEND_STRATEGY_SYMBOL_MAPPINGS)?;
+    run_migration(conn, "043_strategy_trading_window", ADD_STRATEGY_TRADING_WINDOW)?;
+    run_migration(conn, "044_strategy_orders", CREATE_STRATEGY_ORDERS_TABLE)?;
+    run_migration(conn, "045_chartink_strategies", EXTEND_CHARTINK_TABLES)?;
+    run_migration(conn, "046_risk_settings", ADD_RISK_SETTINGS)?;
+    run_migration(conn, "047_algo_orders", CREATE_ALGO_ORDERS_TABLE)?;
+    run_migration(conn, "048_client_orders", CREATE_CLIENT_ORDERS_TABLE)?;
+    run_migration(conn, "049_webhook_verification", ADD_WEBHOOK_VERIFICATION)?;
+    run_migration(conn, "050_api_key_rate_limits", ADD_API_KEY_RATE_LIMITS)?;
+    run_migration(conn, "051_mtm_settings", ADD_MTM_SETTINGS)?;
+    run_migration(conn, "052_exit_rules", CREATE_EXIT_RULES_TABLE)?;
+    run_migration(conn, "053_price_alerts", CREATE_PRICE_ALERTS_TABLES)?;
+    run_migration(conn, "054_tick_recorder_settings", ADD_TICK_RECORDER_SETTINGS)?;
+    run_migration(conn, "055_sandbox_kill_switch", ADD_SANDBOX_KILL_SWITCH)?;
+    run_migration(conn, "056_chartink_webhook_verification", ADD_CHARTINK_WEBHOOK_VERIFICATION)?;
+    run_migration(conn, "057_webhook_trust_proxy", ADD_WEBHOOK_TRUST_PROXY)?;
+    run_migration(conn, "058_webhook_nonce_source", ADD_WEBHOOK_NONCE_SOURCE)?;
+    run_migration(conn, "059_chartink_entry_id", ADD_CHARTINK_ENTRY_ID)?;
 
     tracing::info!("Database migrations completed");
     Ok(())
@@ -591,3 +614,385 @@ const ENABLE_WEBHOOK_BY_DEFAULT: &str = r#"
 -- This is required for Fyers, Zerodha, and other OAuth-based brokers
 UPDATE settings SET webhook_enabled = 1 WHERE id = 1;
 "#;
+
+/// Migration to store locally emulated GTT orders (brokers without native GTT, sandbox)
+const CREATE_GTT_ORDERS_TABLE: &str = r#"
+-- trigger_prices and legs are JSON arrays
+-- mode: 'live' or 'analyze' (GTTs only fire in the mode they were created in)
+CREATE TABLE gtt_orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    gtt_id TEXT NOT NULL UNIQUE,
+    mode TEXT NOT NULL DEFAULT 'live',
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    trigger_type TEXT NOT NULL DEFAULT 'single',
+    trigger_prices TEXT NOT NULL,
+    last_price REAL NOT NULL DEFAULT 0,
+    legs TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active',
+    order_id TEXT,
+    message TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_gtt_orders_status ON gtt_orders(status, mode);
+"#;
+
+/// Migration to store emulated bracket/cover orders and their linked exit GTT
+const CREATE_BRACKET_ORDERS_TABLE: &str = r#"
+-- status: 'pending' (entry not filled), 'open' (exit legs live), 'closed',
+-- 'cancelled', 'rejected' or 'error' (entry filled but exit legs failed)
+CREATE TABLE bracket_orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bracket_id TEXT NOT NULL UNIQUE,
+    mode TEXT NOT NULL DEFAULT 'live',
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    side TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    product TEXT NOT NULL,
+    entry_order_id TEXT,
+    entry_price REAL NOT NULL DEFAULT 0,
+    target REAL,
+    stoploss REAL NOT NULL,
+    trailing_stoploss REAL,
+    stop_price REAL NOT NULL DEFAULT 0,
+    best_price REAL NOT NULL DEFAULT 0,
+    gtt_id TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    message TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_bracket_orders_status ON bracket_orders(status, mode);
+"#;
+
+/// Migration to add the outbound order update webhook URL
+const ADD_ORDER_UPDATE_WEBHOOK: &str = r#"
+ALTER TABLE settings ADD COLUMN order_update_webhook_url TEXT;
+"#;
+
+/// Migration to create outbound notification targets and their delivery log
+const CREATE_NOTIFICATION_TABLES: &str = r#"
+-- kind: 'http' (HMAC-signed JSON POST), 'telegram' or 'discord'
+-- events: comma-separated event filter, '*' for all events
+CREATE TABLE notification_targets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    url TEXT,
+    chat_id TEXT,
+    secret_encrypted TEXT,
+    secret_nonce TEXT,
+    events TEXT NOT NULL DEFAULT '*',
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- status: 'delivered' or 'failed' (after all retries)
+CREATE TABLE notification_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    target_id INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    response_code INTEGER,
+    error TEXT,
+    payload TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_notification_log_target ON notification_log(target_id, id);
+"#;
+
+/// Migration to add the pre-trade validation mode ('off', 'reject' or 'correct')
+const ADD_ORDER_VALIDATION_MODE: &str = r#"
+ALTER TABLE settings ADD COLUMN order_validation_mode TEXT NOT NULL DEFAULT 'reject';
+"#;
+
+/// Migration to give symbol mappings a product, one mapping per symbol, and
+/// to seed a mapping from each existing strategy's own symbol
+const EXTEND_STRATEGY_SYMBOL_MAPPINGS: &str = r#"
+ALTER TABLE strategy_symbol_mappings ADD COLUMN product TEXT NOT NULL DEFAULT 'MIS';
+CREATE UNIQUE INDEX IF NOT EXISTS idx_strategy_symbol_mappings_symbol
+    ON strategy_symbol_mappings(strategy_id, symbol);
+INSERT OR IGNORE INTO strategy_symbol_mappings (strategy_id, exchange, symbol, quantity, product)
+    SELECT id, exchange, symbol, quantity, product FROM strategies WHERE symbol != '';
+"#;
+
+/// Migration to persist each strategy's trading mode and intraday window
+const ADD_STRATEGY_TRADING_WINDOW: &str = r#"
+ALTER TABLE strategies ADD COLUMN is_intraday INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE strategies ADD COLUMN trading_mode TEXT NOT NULL DEFAULT 'BOTH';
+ALTER TABLE strategies ADD COLUMN start_time TEXT DEFAULT '09:15';
+ALTER TABLE strategies ADD COLUMN end_time TEXT DEFAULT '15:15';
+ALTER TABLE strategies ADD COLUMN squareoff_time TEXT DEFAULT '15:25';
+"#;
+
+/// Migration to attribute orders to the strategy that placed them
+const CREATE_STRATEGY_ORDERS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS strategy_orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    strategy_id INTEGER NOT NULL,
+    order_id TEXT NOT NULL,
+    mode TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    product TEXT NOT NULL,
+    side TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    reason TEXT NOT NULL DEFAULT 'signal',
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_strategy_orders_strategy ON strategy_orders(strategy_id, created_at);
+"#;
+
+/// Migration for Chartink strategy defaults, per-stock overrides and order tracking
+const EXTEND_CHARTINK_TABLES: &str = r#"
+ALTER TABLE chartink_strategies ADD COLUMN exchange TEXT NOT NULL DEFAULT 'NSE';
+ALTER TABLE chartink_strategies ADD COLUMN max_symbols_per_alert INTEGER NOT NULL DEFAULT 10;
+ALTER TABLE chartink_strategies ADD COLUMN max_entries_per_day INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE chartink_symbol_mappings ADD COLUMN product TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_chartink_symbol_mappings_symbol
+    ON chartink_symbol_mappings(strategy_id, symbol);
+
+CREATE TABLE IF NOT EXISTS chartink_orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    strategy_id INTEGER NOT NULL,
+    order_id TEXT NOT NULL,
+    mode TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    product TEXT NOT NULL,
+    action TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_chartink_orders_symbol ON chartink_orders(strategy_id, symbol, created_at);
+"#;
+
+/// Migration for risk limits and the kill switch
+const ADD_RISK_SETTINGS: &str = r#"
+ALTER TABLE settings ADD COLUMN risk_enabled INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE settings ADD COLUMN risk_max_daily_loss REAL NOT NULL DEFAULT 0;
+ALTER TABLE settings ADD COLUMN risk_max_open_positions INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE settings ADD COLUMN risk_max_order_value REAL NOT NULL DEFAULT 0;
+ALTER TABLE settings ADD COLUMN risk_max_symbol_quantity INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE settings ADD COLUMN risk_flatten_on_breach INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE settings ADD COLUMN kill_switch_date TEXT;
+ALTER TABLE settings ADD COLUMN kill_switch_reason TEXT;
+"#;
+
+/// Migration for execution algos (TWAP, VWAP, iceberg)
+const CREATE_ALGO_ORDERS_TABLE: &str = r#"
+-- algo_type: 'TWAP', 'VWAP' or 'ICEBERG'
+-- status: 'running', 'completed', 'cancelled' or 'failed'
+CREATE TABLE algo_orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    algo_id TEXT NOT NULL UNIQUE,
+    mode TEXT NOT NULL DEFAULT 'live',
+    algo_type TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    side TEXT NOT NULL,
+    product TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    limit_price REAL,
+    duration_secs INTEGER,
+    num_slices INTEGER,
+    participation_rate REAL,
+    display_quantity INTEGER,
+    disclosed_quantity INTEGER,
+    placed_quantity INTEGER NOT NULL DEFAULT 0,
+    filled_quantity INTEGER NOT NULL DEFAULT 0,
+    slices_placed INTEGER NOT NULL DEFAULT 0,
+    active_order_id TEXT,
+    baseline_volume INTEGER,
+    market_volume INTEGER NOT NULL DEFAULT 0,
+    child_order_ids TEXT NOT NULL DEFAULT '[]',
+    next_slice_at TEXT,
+    ends_at TEXT,
+    status TEXT NOT NULL DEFAULT 'running',
+    message TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX idx_algo_orders_status ON algo_orders(mode, status);
+"#;
+
+/// Migration for idempotent order placement (client order ids)
+const CREATE_CLIENT_ORDERS_TABLE: &str = r#"
+-- status: 'pending' while the request is in flight, then 'completed'
+CREATE TABLE client_orders (
+    client_order_id TEXT PRIMARY KEY,
+    endpoint TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    status_code INTEGER,
+    response TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX idx_client_orders_created ON client_orders(created_at);
+ALTER TABLE settings ADD COLUMN idempotency_window_secs INTEGER NOT NULL DEFAULT 86400;
+"#;
+
+/// Migration for per-strategy webhook verification and replay protection
+const ADD_WEBHOOK_VERIFICATION: &str = r#"
+-- webhook_auth_mode: 'none', 'secret' (shared secret in the body) or 'hmac' (signed header)
+ALTER TABLE strategies ADD COLUMN webhook_auth_mode TEXT NOT NULL DEFAULT 'none';
+ALTER TABLE strategies ADD COLUMN webhook_secret TEXT;
+ALTER TABLE strategies ADD COLUMN webhook_ip_allowlist TEXT;
+ALTER TABLE strategies ADD COLUMN replay_window_secs INTEGER NOT NULL DEFAULT 300;
+CREATE TABLE webhook_nonces (
+    strategy_id INTEGER NOT NULL,
+    nonce TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (strategy_id, nonce)
+);
+CREATE INDEX idx_webhook_nonces_created ON webhook_nonces(created_at);
+"#;
+
+/// Migration for per-API-key rate limit overrides
+const ADD_API_KEY_RATE_LIMITS: &str = r#"
+-- NULL uses the global limit from settings
+ALTER TABLE api_keys ADD COLUMN api_rate_limit INTEGER;
+ALTER TABLE api_keys ADD COLUMN order_rate_limit INTEGER;
+ALTER TABLE api_keys ADD COLUMN smart_order_rate_limit INTEGER;
+"#;
+
+/// Migration for the live MTM engine
+const ADD_MTM_SETTINGS: &str = r#"
+-- Minimum gap between positions_mtm events, in milliseconds
+ALTER TABLE settings ADD COLUMN mtm_event_interval_ms INTEGER NOT NULL DEFAULT 500;
+"#;
+
+/// Migration for position and portfolio exit rules
+const CREATE_EXIT_RULES_TABLE: &str = r#"
+-- scope: 'position' (exchange/symbol/product set) or 'portfolio'
+-- rule_type: 'price', 'points', 'percent' or 'mtm'
+-- status: 'active', 'triggered', 'cancelled' or 'error' (close failed)
+CREATE TABLE exit_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id TEXT NOT NULL UNIQUE,
+    mode TEXT NOT NULL DEFAULT 'live',
+    scope TEXT NOT NULL,
+    exchange TEXT,
+    symbol TEXT,
+    product TEXT,
+    rule_type TEXT NOT NULL,
+    target REAL,
+    stoploss REAL,
+    trailing REAL,
+    best_value REAL,
+    status TEXT NOT NULL DEFAULT 'active',
+    message TEXT,
+    triggered_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX idx_exit_rules_status ON exit_rules(status, mode);
+"#;
+
+/// Migration for price/indicator alerts and their trigger history
+const CREATE_PRICE_ALERTS_TABLES: &str = r#"
+-- condition: 'crosses_above', 'crosses_below', 'percent_change', 'volume_spike' or 'oi_change'
+-- reference_value: LTP when a crossing alert was created, or the OI baseline
+-- action: optional order (JSON) placed when the alert fires
+-- status: 'active', 'triggered' or 'cancelled'
+CREATE TABLE price_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    alert_id TEXT NOT NULL UNIQUE,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    value REAL NOT NULL,
+    window_secs INTEGER,
+    reference_value REAL,
+    note TEXT,
+    notify INTEGER NOT NULL DEFAULT 0,
+    action TEXT,
+    status TEXT NOT NULL DEFAULT 'active',
+    triggered_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX idx_price_alerts_status ON price_alerts(status);
+-- action_status: NULL (no action), 'placed' or 'failed'
+CREATE TABLE alert_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    alert_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    value REAL NOT NULL,
+    observed REAL NOT NULL,
+    ltp REAL NOT NULL,
+    note TEXT,
+    action_status TEXT,
+    action_result TEXT,
+    triggered_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX idx_alert_history_triggered ON alert_history(triggered_at);
+"#;
+
+/// Migration for the DuckDB tick recorder
+const ADD_TICK_RECORDER_SETTINGS: &str = r#"
+-- Recorder is opt-in; symbols are chosen in Historify
+ALTER TABLE settings ADD COLUMN tick_recorder_enabled INTEGER NOT NULL DEFAULT 0;
+-- How often buffered ticks and 1m bars are written to DuckDB, in seconds
+ALTER TABLE settings ADD COLUMN tick_recorder_flush_secs INTEGER NOT NULL DEFAULT 5;
+"#;
+
+/// Migration for a kill switch of its own in analyze mode
+const ADD_SANDBOX_KILL_SWITCH: &str = r#"
+-- kill_switch_date/reason lock live trading only
+ALTER TABLE settings ADD COLUMN sandbox_kill_switch_date TEXT;
+ALTER TABLE settings ADD COLUMN sandbox_kill_switch_reason TEXT;
+"#;
+
+/// Migration for per-strategy webhook verification on Chartink strategies
+const ADD_CHARTINK_WEBHOOK_VERIFICATION: &str = r#"
+-- Same settings as strategies (049); nonces share webhook_nonces under a 'chartink:' key prefix
+ALTER TABLE chartink_strategies ADD COLUMN webhook_auth_mode TEXT NOT NULL DEFAULT 'none';
+ALTER TABLE chartink_strategies ADD COLUMN webhook_secret TEXT;
+ALTER TABLE chartink_strategies ADD COLUMN webhook_ip_allowlist TEXT;
+ALTER TABLE chartink_strategies ADD COLUMN replay_window_secs INTEGER NOT NULL DEFAULT 300;
+"#;
+
+/// Migration for trusting X-Forwarded-For from a local tunnel/proxy
+const ADD_WEBHOOK_TRUST_PROXY: &str = r#"
+-- Off by default: the peer address is the client IP
+ALTER TABLE settings ADD COLUMN webhook_trust_proxy INTEGER NOT NULL DEFAULT 0;
+"#;
+
+/// Migration for keying webhook nonces by strategy table
+const ADD_WEBHOOK_NONCE_SOURCE: &str = r#"
+-- strategy or chartink: the two tables' ids overlap, so the source is part of the key
+CREATE TABLE webhook_nonces_new (
+    source TEXT NOT NULL DEFAULT 'strategy',
+    strategy_id INTEGER NOT NULL,
+    nonce TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (source, strategy_id, nonce)
+);
+
+INSERT INTO webhook_nonces_new (source, strategy_id, nonce, created_at)
+SELECT
+    CASE WHEN nonce LIKE 'chartink:%' THEN 'chartink' ELSE 'strategy' END,
+    strategy_id,
+    CASE WHEN nonce LIKE 'chartink:%' THEN substr(nonce, 10) ELSE nonce END,
+    created_at
+FROM webhook_nonces;
+
+DROP TABLE webhook_nonces;
+ALTER TABLE webhook_nonces_new RENAME TO webhook_nonces;
+CREATE INDEX idx_webhook_nonces_created ON webhook_nonces(created_at);
+"#;
+
+/// Migration for counting Chartink entries rather than order slices
+const ADD_CHARTINK_ENTRY_ID: &str = r#"
+-- Order id of the entry a row belongs to; the slices of one sliced entry share it
+ALTER TABLE chartink_orders ADD COLUMN entry_id TEXT;
+"#;
@@ -15,16 +15,33 @@ mod market;
 mod analyzer_logs;
 mod latency_logs;
 mod traffic_logs;
+mod gtt;
+mod bracket;
+mod algo;
+mod exit_rules;
+mod alerts;
+mod client_orders;
+mod notifications;
+mod chartink;
 
 use crate::error::Result;
 use crate::security::SecurityManager;
 use crate::state::SymbolInfo;
-pub use models::{AutoLogoutConfig, WebhookConfig, ApiKey, ApiKeyInfo, SandboxFunds, SandboxHolding};
+pub use models::{
+    AutoLogoutConfig, WebhookConfig, ApiKey, ApiKeyInfo, ApiKeyRateLimits, FreezeQty, KillSwitchState, RiskConfig,
+    SandboxFunds, SandboxHolding,
+};
 pub use order_logs::{OrderLog, LogStats};
 pub use market::{MarketHoliday, MarketTiming, CreateHolidayRequest, UpdateTimingRequest};
 pub use analyzer_logs::{AnalyzerLog, AnalyzerLogStats};
 pub use latency_logs::{LatencyLog, LatencyStats, BrokerLatencyStats};
 pub use traffic_logs::{TrafficLog, TrafficStats, IPBan};
+pub use client_orders::ClientOrderClaim;
+pub use notifications::{NotificationDelivery, NotificationTarget};
+pub use chartink::{
+    ChartinkStrategy, ChartinkSymbol, CreateChartinkStrategyRequest, UpdateChartinkStrategyRequest,
+};
+pub use alerts::{AlertAction, AlertHistory, PriceAlert};
 use models::*;
 use parking_lot::Mutex;
 use rusqlite::Connection;
@@ -50,6 +67,23 @@ impl SqliteDb {
         // Run migrations
         db.run_migrations()?;
 
+        // Load bundled exchange freeze limits on first run
+        symbol::seed_freeze_quantities(&mut db.conn.lock())?;
+
+        Ok(db)
+    }
+
+    /// Create a scratch in-memory database with the full schema
+    ///
+    /// Backtests run their fills through the sandbox tables of one of these,
+    /// so they share the sandbox accounting without touching the real one.
+    pub fn in_memory() -> Result<Self> {
+        let db = Self {
+            conn: Mutex::new(Connection::open_in_memory()?),
+        };
+
+        db.run_migrations()?;
+
         Ok(db)
     }
 
@@ -145,6 +179,30 @@ impl SqliteDb {
         symbol::load_symbols(&conn)
     }
 
+    /// Get the exchange freeze quantity for a symbol
+    pub fn get_freeze_qty(&self, exchange: &str, symbol: &str) -> Result<Option<i32>> {
+        let conn = self.conn.lock();
+        symbol::get_freeze_qty(&conn, exchange, symbol)
+    }
+
+    /// Get all configured freeze limits
+    pub fn get_freeze_quantities(&self) -> Result<Vec<FreezeQty>> {
+        let conn = self.conn.lock();
+        symbol::get_freeze_quantities(&conn)
+    }
+
+    /// Bulk-import freeze limits from CSV
+    pub fn import_freeze_quantities(&self, csv: &str, replace: bool) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        symbol::import_freeze_quantities(&mut conn, csv, replace)
+    }
+
+    /// Replace all freeze limits with the bundled defaults
+    pub fn reset_freeze_quantities(&self) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        symbol::reset_freeze_quantities(&mut conn)
+    }
+
     // ========== Strategy Methods ==========
 
     /// Get all strategies
@@ -153,6 +211,12 @@ impl SqliteDb {
         strategy::get_strategies(&conn)
     }
 
+    /// Get a strategy by ID
+    pub fn get_strategy(&self, id: i64) -> Result<Strategy> {
+        let conn = self.conn.lock();
+        strategy::get_strategy_by_id(&conn, id)
+    }
+
     /// Create a new strategy
     pub fn create_strategy(&self, strategy: &Strategy) -> Result<Strategy> {
         let conn = self.conn.lock();
@@ -170,8 +234,62 @@ impl SqliteDb {
         quantity: Option<i32>,
         enabled: Option<bool>,
     ) -> Result<Strategy> {
+        let mut conn = self.conn.lock();
+        strategy::update_strategy(&mut conn, id, name, exchange, symbol, product, quantity, enabled)
+    }
+
+    /// Update a strategy's trading mode and intraday window
+    pub fn update_strategy_trading(
+        &self,
+        id: i64,
+        is_intraday: Option<bool>,
+        trading_mode: Option<String>,
+        start_time: Option<String>,
+        end_time: Option<String>,
+        squareoff_time: Option<String>,
+    ) -> Result<Strategy> {
+        let conn = self.conn.lock();
+        strategy::update_strategy_trading(
+            &conn,
+            id,
+            is_intraday,
+            trading_mode,
+            start_time,
+            end_time,
+            squareoff_time,
+        )
+    }
+
+    /// Update a strategy's webhook verification settings
+    pub fn update_strategy_security(
+        &self,
+        id: i64,
+        auth_mode: Option<String>,
+        webhook_secret: Option<String>,
+        ip_allowlist: Option<String>,
+        replay_window_secs: Option<i64>,
+    ) -> Result<Strategy> {
+        let conn = self.conn.lock();
+        strategy::update_strategy_security(
+            &conn,
+            id,
+            auth_mode,
+            webhook_secret,
+            ip_allowlist,
+            replay_window_secs,
+        )
+    }
+
+    /// Remember a webhook nonce, false if it was already seen in the window
+    pub fn claim_webhook_nonce(
+        &self,
+        source: &str,
+        strategy_id: i64,
+        nonce: &str,
+        window_secs: i64,
+    ) -> Result<bool> {
         let conn = self.conn.lock();
-        strategy::update_strategy(&conn, id, name, exchange, symbol, product, quantity, enabled)
+        strategy::claim_webhook_nonce(&conn, source, strategy_id, nonce, window_secs)
     }
 
     /// Delete a strategy
@@ -199,6 +317,205 @@ impl SqliteDb {
         strategy::get_symbol_mapping(&conn, *strategy_id, symbol)
     }
 
+    /// Get all symbol mappings for a strategy
+    pub fn get_symbol_mappings(&self, strategy_id: i64) -> Result<Vec<StrategySymbolMapping>> {
+        let conn = self.conn.lock();
+        strategy::get_symbol_mappings(&conn, strategy_id)
+    }
+
+    /// Add a symbol mapping to a strategy
+    pub fn add_symbol_mapping(
+        &self,
+        strategy_id: i64,
+        exchange: &str,
+        symbol: &str,
+        quantity: i32,
+        product: &str,
+    ) -> Result<StrategySymbolMapping> {
+        let conn = self.conn.lock();
+        strategy::add_symbol_mapping(&conn, strategy_id, exchange, symbol, quantity, product)
+    }
+
+    /// Update a symbol mapping
+    pub fn update_symbol_mapping(
+        &self,
+        id: i64,
+        exchange: Option<String>,
+        symbol: Option<String>,
+        quantity: Option<i32>,
+        product: Option<String>,
+    ) -> Result<StrategySymbolMapping> {
+        let conn = self.conn.lock();
+        strategy::update_symbol_mapping(&conn, id, exchange, symbol, quantity, product)
+    }
+
+    /// Remove a symbol mapping
+    pub fn delete_symbol_mapping(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock();
+        strategy::delete_symbol_mapping(&conn, id)
+    }
+
+    /// Bulk-import symbol mappings from CSV
+    pub fn import_symbol_mappings(&self, strategy_id: i64, csv: &str, replace: bool) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        strategy::import_symbol_mappings(&mut conn, strategy_id, csv, replace)
+    }
+
+    /// Record an order placed on behalf of a strategy
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_strategy_order(
+        &self,
+        strategy_id: i64,
+        order_id: &str,
+        mode: &str,
+        exchange: &str,
+        symbol: &str,
+        product: &str,
+        side: &str,
+        quantity: i32,
+        reason: &str,
+    ) -> Result<i64> {
+        let conn = self.conn.lock();
+        strategy::record_strategy_order(
+            &conn, strategy_id, order_id, mode, exchange, symbol, product, side, quantity, reason,
+        )
+    }
+
+    /// Get a strategy's orders in a mode since a UTC timestamp
+    pub fn get_strategy_orders(&self, strategy_id: i64, mode: &str, since: &str) -> Result<Vec<StrategyOrder>> {
+        let conn = self.conn.lock();
+        strategy::get_strategy_orders(&conn, strategy_id, mode, since)
+    }
+
+    // ========== Chartink Strategy Methods ==========
+
+    /// Get all Chartink strategies
+    pub fn get_chartink_strategies(&self) -> Result<Vec<ChartinkStrategy>> {
+        let conn = self.conn.lock();
+        chartink::get_strategies(&conn)
+    }
+
+    /// Get a Chartink strategy by ID
+    pub fn get_chartink_strategy(&self, id: i64) -> Result<ChartinkStrategy> {
+        let conn = self.conn.lock();
+        chartink::get_strategy(&conn, id)
+    }
+
+    /// Get a Chartink strategy by webhook_id (for webhook handler)
+    pub fn get_chartink_strategy_by_webhook_id(&self, webhook_id: &str) -> Result<Option<ChartinkStrategy>> {
+        let conn = self.conn.lock();
+        chartink::get_strategy_by_webhook_id(&conn, webhook_id)
+    }
+
+    /// Create a Chartink strategy
+    pub fn create_chartink_strategy(&self, req: &CreateChartinkStrategyRequest) -> Result<ChartinkStrategy> {
+        let conn = self.conn.lock();
+        chartink::create_strategy(&conn, req)
+    }
+
+    /// Update a Chartink strategy
+    pub fn update_chartink_strategy(
+        &self,
+        id: i64,
+        req: &UpdateChartinkStrategyRequest,
+    ) -> Result<ChartinkStrategy> {
+        let conn = self.conn.lock();
+        chartink::update_strategy(&conn, id, req)
+    }
+
+    /// Update a Chartink strategy's webhook verification settings
+    pub fn update_chartink_strategy_security(
+        &self,
+        id: i64,
+        auth_mode: Option<String>,
+        webhook_secret: Option<String>,
+        ip_allowlist: Option<String>,
+        replay_window_secs: Option<i64>,
+    ) -> Result<ChartinkStrategy> {
+        let conn = self.conn.lock();
+        chartink::update_strategy_security(
+            &conn,
+            id,
+            auth_mode,
+            webhook_secret,
+            ip_allowlist,
+            replay_window_secs,
+        )
+    }
+
+    /// Delete a Chartink strategy and its symbol overrides
+    pub fn delete_chartink_strategy(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock();
+        chartink::delete_strategy(&conn, id)
+    }
+
+    /// Get the symbol overrides of a Chartink strategy
+    pub fn get_chartink_symbols(&self, strategy_id: i64) -> Result<Vec<ChartinkSymbol>> {
+        let conn = self.conn.lock();
+        chartink::get_symbols(&conn, strategy_id)
+    }
+
+    /// Get the override for one stock, if any
+    pub fn get_chartink_symbol(&self, strategy_id: i64, symbol: &str) -> Result<Option<ChartinkSymbol>> {
+        let conn = self.conn.lock();
+        chartink::get_symbol(&conn, strategy_id, symbol)
+    }
+
+    /// Add a symbol override to a Chartink strategy
+    pub fn add_chartink_symbol(
+        &self,
+        strategy_id: i64,
+        exchange: &str,
+        symbol: &str,
+        quantity: i32,
+        product: Option<&str>,
+    ) -> Result<ChartinkSymbol> {
+        let conn = self.conn.lock();
+        chartink::add_symbol(&conn, strategy_id, exchange, symbol, quantity, product)
+    }
+
+    /// Update a Chartink symbol override
+    pub fn update_chartink_symbol(
+        &self,
+        id: i64,
+        exchange: Option<&str>,
+        quantity: Option<i32>,
+        product: Option<&str>,
+    ) -> Result<ChartinkSymbol> {
+        let conn = self.conn.lock();
+        chartink::update_symbol(&conn, id, exchange, quantity, product)
+    }
+
+    /// Delete a Chartink symbol override
+    pub fn delete_chartink_symbol(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock();
+        chartink::delete_symbol(&conn, id)
+    }
+
+    /// Record an order placed for a Chartink alert
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_chartink_order(
+        &self,
+        strategy_id: i64,
+        entry_id: &str,
+        order_id: &str,
+        mode: &str,
+        exchange: &str,
+        symbol: &str,
+        product: &str,
+        action: &str,
+        quantity: i32,
+    ) -> Result<i64> {
+        let conn = self.conn.lock();
+        chartink::record_order(&conn, strategy_id, entry_id, order_id, mode, exchange, symbol, product, action, quantity)
+    }
+
+    /// Count a stock's Chartink entries in a mode since a UTC timestamp
+    pub fn count_chartink_entries(&self, strategy_id: i64, symbol: &str, mode: &str, since: &str) -> Result<i32> {
+        let conn = self.conn.lock();
+        chartink::count_entries(&conn, strategy_id, symbol, mode, since)
+    }
+
     // ========== API Key Methods ==========
 
     /// Create a new API key
@@ -236,6 +553,21 @@ impl SqliteDb {
         api_keys::get_api_key_by_name(&conn, name)
     }
 
+    /// All API keys (decrypted) with their rate limit overrides
+    pub fn list_api_key_rate_limits(
+        &self,
+        security: &SecurityManager,
+    ) -> Result<Vec<(String, ApiKeyRateLimits)>> {
+        let conn = self.conn.lock();
+        api_keys::list_api_key_rate_limits(&conn, security)
+    }
+
+    /// Set or clear an API key's rate limit overrides
+    pub fn update_api_key_rate_limits(&self, id: i64, rate_limits: &ApiKeyRateLimits) -> Result<bool> {
+        let conn = self.conn.lock();
+        api_keys::update_api_key_rate_limits(&conn, id, rate_limits)
+    }
+
     /// Delete API key by name
     pub fn delete_api_key(&self, name: &str) -> Result<bool> {
         let conn = self.conn.lock();
@@ -328,9 +660,10 @@ impl SqliteDb {
         host: Option<String>,
         ngrok_url: Option<String>,
         webhook_secret: Option<String>,
+        trust_proxy: Option<bool>,
     ) -> Result<WebhookConfig> {
         let conn = self.conn.lock();
-        settings::update_webhook_config(&conn, enabled, port, host, ngrok_url, webhook_secret)
+        settings::update_webhook_config(&conn, enabled, port, host, ngrok_url, webhook_secret, trust_proxy)
     }
 
     /// Get rate limit configuration
@@ -351,6 +684,61 @@ impl SqliteDb {
         settings::update_rate_limit_config(&conn, api_rate_limit, order_rate_limit, smart_order_rate_limit, smart_order_delay)
     }
 
+    /// Get outbound order update configuration
+    pub fn get_order_update_config(&self) -> Result<models::OrderUpdateConfig> {
+        let conn = self.conn.lock();
+        settings::get_order_update_config(&conn)
+    }
+
+    /// Update outbound order update configuration
+    pub fn update_order_update_config(
+        &self,
+        webhook_url: Option<String>,
+    ) -> Result<models::OrderUpdateConfig> {
+        let conn = self.conn.lock();
+        settings::update_order_update_config(&conn, webhook_url)
+    }
+
+    /// Get idempotent order placement configuration
+    pub fn get_idempotency_config(&self) -> Result<models::IdempotencyConfig> {
+        let conn = self.conn.lock();
+        settings::get_idempotency_config(&conn)
+    }
+
+    /// Update how long client order ids are remembered
+    pub fn update_idempotency_config(&self, window_secs: u32) -> Result<models::IdempotencyConfig> {
+        let conn = self.conn.lock();
+        settings::update_idempotency_config(&conn, window_secs)
+    }
+
+    /// Get live MTM engine configuration
+    pub fn get_mtm_config(&self) -> Result<models::MtmConfig> {
+        let conn = self.conn.lock();
+        settings::get_mtm_config(&conn)
+    }
+
+    /// Update how often `positions_mtm` events may be emitted
+    pub fn update_mtm_config(&self, event_interval_ms: u32) -> Result<models::MtmConfig> {
+        let conn = self.conn.lock();
+        settings::update_mtm_config(&conn, event_interval_ms)
+    }
+
+    /// Get tick recorder configuration
+    pub fn get_tick_recorder_config(&self) -> Result<models::TickRecorderConfig> {
+        let conn = self.conn.lock();
+        settings::get_tick_recorder_config(&conn)
+    }
+
+    /// Turn the tick recorder on or off and set its flush interval
+    pub fn update_tick_recorder_config(
+        &self,
+        enabled: bool,
+        flush_interval_secs: u32,
+    ) -> Result<models::TickRecorderConfig> {
+        let conn = self.conn.lock();
+        settings::update_tick_recorder_config(&conn, enabled, flush_interval_secs)
+    }
+
     // ========== Sandbox Methods ==========
 
     /// Get sandbox positions
@@ -404,6 +792,24 @@ impl SqliteDb {
         sandbox::update_position_ltp(&conn, exchange, symbol, ltp)
     }
 
+    /// Get pending sandbox orders for a symbol
+    pub fn get_pending_sandbox_orders(&self, exchange: &str, symbol: &str) -> Result<Vec<SandboxOrder>> {
+        let conn = self.conn.lock();
+        sandbox::get_pending_orders(&conn, exchange, symbol)
+    }
+
+    /// Exchange and symbol of every instrument with a pending sandbox LIMIT order
+    pub fn get_pending_sandbox_symbols(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.conn.lock();
+        sandbox::get_pending_symbols(&conn)
+    }
+
+    /// Fill a pending sandbox order
+    pub fn fill_sandbox_order(&self, order: &SandboxOrder, price: f64) -> Result<bool> {
+        let conn = self.conn.lock();
+        sandbox::fill_order(&conn, order, price)
+    }
+
     /// Cancel sandbox order
     pub fn cancel_sandbox_order(&self, order_id: &str) -> Result<bool> {
         let conn = self.conn.lock();
@@ -440,6 +846,331 @@ impl SqliteDb {
         sandbox::get_pnl_data(&conn)
     }
 
+    // ========== GTT Methods (Emulated) ==========
+
+    /// Store an emulated GTT order
+    pub fn create_gtt_order(&self, gtt: &crate::brokers::types::GttOrder, mode: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        gtt::create_gtt(&conn, gtt, mode)
+    }
+
+    /// Get an emulated GTT order by ID
+    pub fn get_gtt_order(&self, gtt_id: &str) -> Result<Option<crate::brokers::types::GttOrder>> {
+        let conn = self.conn.lock();
+        gtt::get_gtt(&conn, gtt_id)
+    }
+
+    /// Get emulated GTT orders for a mode (live/analyze)
+    pub fn get_gtt_orders(&self, mode: &str, active_only: bool) -> Result<Vec<crate::brokers::types::GttOrder>> {
+        let conn = self.conn.lock();
+        gtt::get_gtts(&conn, mode, active_only)
+    }
+
+    /// Modify an active emulated GTT order
+    pub fn update_gtt_order(&self, gtt_id: &str, request: &crate::brokers::types::GttRequest) -> Result<bool> {
+        let conn = self.conn.lock();
+        gtt::update_gtt(&conn, gtt_id, request)
+    }
+
+    /// Move an active emulated GTT to a new status (triggered/cancelled)
+    pub fn transition_gtt_order(&self, gtt_id: &str, status: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        gtt::transition_gtt(&conn, gtt_id, status)
+    }
+
+    /// Record the order placed (or error) when an emulated GTT fired
+    pub fn set_gtt_order_result(
+        &self,
+        gtt_id: &str,
+        status: &str,
+        order_id: Option<&str>,
+        message: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock();
+        gtt::set_gtt_result(&conn, gtt_id, status, order_id, message)
+    }
+
+    // ========== Bracket Order Methods (Emulated) ==========
+
+    /// Store an emulated bracket/cover order
+    pub fn create_bracket_order(&self, bracket: &crate::brokers::types::BracketOrder, mode: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        bracket::create_bracket(&conn, bracket, mode)
+    }
+
+    /// Get an emulated bracket order by ID
+    pub fn get_bracket_order(&self, bracket_id: &str) -> Result<Option<crate::brokers::types::BracketOrder>> {
+        let conn = self.conn.lock();
+        bracket::get_bracket(&conn, bracket_id)
+    }
+
+    /// Get emulated bracket orders for a mode (live/analyze)
+    pub fn get_bracket_orders(&self, mode: &str, live_only: bool) -> Result<Vec<crate::brokers::types::BracketOrder>> {
+        let conn = self.conn.lock();
+        bracket::get_brackets(&conn, mode, live_only)
+    }
+
+    /// Open a pending bracket once its entry filled
+    pub fn activate_bracket_order(
+        &self,
+        bracket_id: &str,
+        entry_price: f64,
+        stop_price: f64,
+        gtt_id: &str,
+    ) -> Result<bool> {
+        let conn = self.conn.lock();
+        bracket::activate_bracket(&conn, bracket_id, entry_price, stop_price, gtt_id)
+    }
+
+    /// Record a trailed stoploss for an open bracket
+    pub fn update_bracket_stop(&self, bracket_id: &str, stop_price: f64, best_price: f64) -> Result<()> {
+        let conn = self.conn.lock();
+        bracket::update_bracket_stop(&conn, bracket_id, stop_price, best_price)
+    }
+
+    /// Move a bracket to a final status
+    pub fn set_bracket_status(&self, bracket_id: &str, status: &str, message: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock();
+        bracket::set_bracket_status(&conn, bracket_id, status, message)
+    }
+
+    // ========== Algo Order Methods (TWAP/VWAP/Iceberg) ==========
+
+    /// Store a new execution algo
+    pub fn create_algo_order(&self, algo: &crate::brokers::types::AlgoOrder, mode: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        algo::create_algo(&conn, algo, mode)
+    }
+
+    /// Get an execution algo by ID
+    pub fn get_algo_order(&self, algo_id: &str) -> Result<Option<crate::brokers::types::AlgoOrder>> {
+        let conn = self.conn.lock();
+        algo::get_algo(&conn, algo_id)
+    }
+
+    /// Get execution algos for a mode (live/analyze)
+    pub fn get_algo_orders(&self, mode: &str, running_only: bool) -> Result<Vec<crate::brokers::types::AlgoOrder>> {
+        let conn = self.conn.lock();
+        algo::get_algos(&conn, mode, running_only)
+    }
+
+    /// Save the progress of a running algo
+    pub fn update_algo_progress(&self, algo: &crate::brokers::types::AlgoOrder) -> Result<bool> {
+        let conn = self.conn.lock();
+        algo::update_algo_progress(&conn, algo)
+    }
+
+    /// Move a running algo to a final status
+    pub fn finish_algo_order(&self, algo_id: &str, status: &str, message: Option<&str>) -> Result<bool> {
+        let conn = self.conn.lock();
+        algo::finish_algo(&conn, algo_id, status, message)
+    }
+
+    // ========== Exit Rule Methods ==========
+
+    /// Store a new position/portfolio exit rule
+    pub fn create_exit_rule(&self, rule: &crate::brokers::types::ExitRule, mode: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        exit_rules::create_rule(&conn, rule, mode)
+    }
+
+    /// Get an exit rule by ID
+    pub fn get_exit_rule(&self, rule_id: &str) -> Result<Option<crate::brokers::types::ExitRule>> {
+        let conn = self.conn.lock();
+        exit_rules::get_rule(&conn, rule_id)
+    }
+
+    /// Get exit rules for a mode (live/analyze)
+    pub fn get_exit_rules(&self, mode: &str, active_only: bool) -> Result<Vec<crate::brokers::types::ExitRule>> {
+        let conn = self.conn.lock();
+        exit_rules::get_rules(&conn, mode, active_only)
+    }
+
+    /// Record the best value seen by a trailing exit rule
+    pub fn update_exit_rule_best(&self, rule_id: &str, best_value: f64) -> Result<()> {
+        let conn = self.conn.lock();
+        exit_rules::update_best_value(&conn, rule_id, best_value)
+    }
+
+    /// Move an active exit rule to a final status
+    pub fn finish_exit_rule(&self, rule_id: &str, status: &str, message: Option<&str>) -> Result<bool> {
+        let conn = self.conn.lock();
+        exit_rules::finish_rule(&conn, rule_id, status, message)
+    }
+
+    /// Set the status of an exit rule
+    pub fn set_exit_rule_status(&self, rule_id: &str, status: &str, message: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock();
+        exit_rules::set_rule_status(&conn, rule_id, status, message)
+    }
+
+    // ========== Price Alert Methods ==========
+
+    /// Store a new price/indicator alert
+    pub fn create_price_alert(&self, alert: &PriceAlert) -> Result<()> {
+        let conn = self.conn.lock();
+        alerts::create_alert(&conn, alert)
+    }
+
+    /// Get a price alert by ID
+    pub fn get_price_alert(&self, alert_id: &str) -> Result<Option<PriceAlert>> {
+        let conn = self.conn.lock();
+        alerts::get_alert(&conn, alert_id)
+    }
+
+    /// Get price alerts, optionally only the active ones
+    pub fn get_price_alerts(&self, active_only: bool) -> Result<Vec<PriceAlert>> {
+        let conn = self.conn.lock();
+        alerts::get_alerts(&conn, active_only)
+    }
+
+    /// Record the reference (baseline) value of an active alert
+    pub fn set_alert_reference(&self, alert_id: &str, reference_value: f64) -> Result<()> {
+        let conn = self.conn.lock();
+        alerts::set_reference_value(&conn, alert_id, reference_value)
+    }
+
+    /// Move an active alert to triggered or cancelled
+    pub fn finish_price_alert(&self, alert_id: &str, status: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        alerts::finish_alert(&conn, alert_id, status)
+    }
+
+    /// Delete a price alert, keeping its history
+    pub fn delete_price_alert(&self, alert_id: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        alerts::delete_alert(&conn, alert_id)
+    }
+
+    /// Write an alert trigger to the history
+    pub fn record_alert_trigger(&self, alert: &PriceAlert, observed: f64, ltp: f64) -> Result<i64> {
+        let conn = self.conn.lock();
+        alerts::record_trigger(&conn, alert, observed, ltp)
+    }
+
+    /// Record the outcome of an alert trigger's order action
+    pub fn set_alert_trigger_action(&self, id: i64, status: &str, result: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        alerts::set_trigger_action(&conn, id, status, result)
+    }
+
+    /// Get the most recent alert triggers
+    pub fn get_alert_history(&self, limit: u32) -> Result<Vec<AlertHistory>> {
+        let conn = self.conn.lock();
+        alerts::get_history(&conn, limit)
+    }
+
+    // ========== Client Order Methods (Idempotency) ==========
+
+    /// Claim a client order id for an order request
+    pub fn claim_client_order(
+        &self,
+        client_order_id: &str,
+        endpoint: &str,
+        request_hash: &str,
+        window_secs: u32,
+    ) -> Result<ClientOrderClaim> {
+        let conn = self.conn.lock();
+        client_orders::claim_client_order(&conn, client_order_id, endpoint, request_hash, window_secs)
+    }
+
+    /// Store the response of a claimed client order id
+    pub fn complete_client_order(
+        &self,
+        client_order_id: &str,
+        status_code: u16,
+        response: &str,
+    ) -> Result<()> {
+        let conn = self.conn.lock();
+        client_orders::complete_client_order(&conn, client_order_id, status_code, response)
+    }
+
+    // ========== Notification Methods (Outbound Alerts) ==========
+
+    /// Create a notification target (secret already encrypted)
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_notification_target(
+        &self,
+        name: &str,
+        kind: &str,
+        url: Option<&str>,
+        chat_id: Option<&str>,
+        secret: Option<(&str, &str)>,
+        events: &[String],
+        max_retries: u32,
+        enabled: bool,
+    ) -> Result<i64> {
+        let conn = self.conn.lock();
+        notifications::create_target(&conn, name, kind, url, chat_id, secret, events, max_retries, enabled)
+    }
+
+    /// Update a notification target, keeping the stored secret when `secret` is None
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_notification_target(
+        &self,
+        id: i64,
+        name: &str,
+        url: Option<&str>,
+        chat_id: Option<&str>,
+        secret: Option<(&str, &str)>,
+        events: &[String],
+        max_retries: u32,
+        enabled: bool,
+    ) -> Result<bool> {
+        let conn = self.conn.lock();
+        notifications::update_target(&conn, id, name, url, chat_id, secret, events, max_retries, enabled)
+    }
+
+    /// Delete a notification target and its delivery log
+    pub fn delete_notification_target(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock();
+        notifications::delete_target(&conn, id)
+    }
+
+    /// Get a notification target by ID
+    pub fn get_notification_target(&self, id: i64) -> Result<Option<NotificationTarget>> {
+        let conn = self.conn.lock();
+        notifications::get_target(&conn, id)
+    }
+
+    /// Get notification targets
+    pub fn get_notification_targets(&self, enabled_only: bool) -> Result<Vec<NotificationTarget>> {
+        let conn = self.conn.lock();
+        notifications::get_targets(&conn, enabled_only)
+    }
+
+    /// Record a notification delivery
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_notification_delivery(
+        &self,
+        target_id: i64,
+        event: &str,
+        status: &str,
+        attempts: u32,
+        response_code: Option<u16>,
+        error: Option<&str>,
+        payload: &str,
+    ) -> Result<i64> {
+        let conn = self.conn.lock();
+        notifications::log_delivery(&conn, target_id, event, status, attempts, response_code, error, payload)
+    }
+
+    /// Get recent notification deliveries
+    pub fn get_notification_deliveries(
+        &self,
+        target_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<NotificationDelivery>> {
+        let conn = self.conn.lock();
+        notifications::get_deliveries(&conn, target_id, limit)
+    }
+
+    /// Delete notification deliveries older than `days`
+    pub fn prune_notification_deliveries(&self, days: i64) -> Result<usize> {
+        let conn = self.conn.lock();
+        notifications::prune_deliveries(&conn, days)
+    }
+
     // ========== Order Logs Methods ==========
 
     /// Create an order log entry
@@ -589,6 +1320,55 @@ impl SqliteDb {
         Ok(())
     }
 
+    // ========== Order Validation Methods ==========
+
+    /// Get pre-trade validation mode (off/reject/correct)
+    pub fn get_order_validation_mode(&self) -> Result<String> {
+        let conn = self.conn.lock();
+        let mode = conn.query_row(
+            "SELECT order_validation_mode FROM settings WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(mode)
+    }
+
+    /// Set pre-trade validation mode
+    pub fn set_order_validation_mode(&self, mode: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE settings SET order_validation_mode = ?1, updated_at = datetime('now') WHERE id = 1",
+            [mode],
+        )?;
+        Ok(())
+    }
+
+    // ========== Risk Methods ==========
+
+    /// Get risk limits
+    pub fn get_risk_config(&self) -> Result<RiskConfig> {
+        let conn = self.conn.lock();
+        settings::get_risk_config(&conn)
+    }
+
+    /// Update risk limits
+    pub fn update_risk_config(&self, config: &RiskConfig) -> Result<RiskConfig> {
+        let conn = self.conn.lock();
+        settings::update_risk_config(&conn, config)
+    }
+
+    /// Get kill switch state for a mode (live/analyze)
+    pub fn get_kill_switch(&self, mode: &str) -> Result<KillSwitchState> {
+        let conn = self.conn.lock();
+        settings::get_kill_switch(&conn, mode)
+    }
+
+    /// Activate the kill switch of a mode (live/analyze) for an IST date
+    pub fn set_kill_switch(&self, mode: &str, date: &str, reason: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        settings::set_kill_switch(&conn, mode, date, reason)
+    }
+
     // ========== Order Logging Helper ==========
 
     /// Log an order (convenience wrapper for order_logs::create_log)
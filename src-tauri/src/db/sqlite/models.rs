@@ -21,10 +21,63 @@ pub struct Strategy {
     pub product: String,
     pub quantity: i32,
     pub enabled: bool,
+    /// Intraday strategies only trade inside their window and are squared off
+    pub is_intraday: bool,
+    /// LONG, SHORT or BOTH
+    pub trading_mode: String,
+    /// Entry window start (HH:MM IST)
+    pub start_time: Option<String>,
+    /// Entry window end; only exits are accepted after this (HH:MM IST)
+    pub end_time: Option<String>,
+    /// Square-off time (HH:MM IST)
+    pub squareoff_time: Option<String>,
+    /// Alert verification: none, secret (shared secret in the body) or hmac (signed header)
+    pub webhook_auth_mode: String,
+    /// Verification secret; the global webhook secret is used when unset
+    pub webhook_secret: Option<String>,
+    /// Comma-separated IPs/CIDRs allowed to send alerts (any when unset)
+    pub webhook_ip_allowlist: Option<String>,
+    /// Window for alert timestamps and remembered nonces
+    pub replay_window_secs: i64,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Symbol mapping for a multi-symbol strategy
+///
+/// Webhook alerts are resolved by exact match on `symbol`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySymbolMapping {
+    pub id: i64,
+    pub strategy_id: i64,
+    pub exchange: String,
+    pub symbol: String,
+    pub quantity: i32,
+    pub product: String,
+    pub created_at: String,
+}
+
+/// Order placed on behalf of a strategy
+///
+/// Used to attribute broker/sandbox orders and positions back to the
+/// strategy that opened them (e.g. for strategy square-off).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyOrder {
+    pub id: i64,
+    pub strategy_id: i64,
+    pub order_id: String,
+    /// "live" or "analyze"
+    pub mode: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub product: String,
+    pub side: String,
+    pub quantity: i32,
+    /// "signal" (webhook alert) or "squareoff"
+    pub reason: String,
+    pub created_at: String,
+}
+
 /// Settings model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
@@ -61,6 +114,21 @@ pub struct WebhookConfig {
     pub host: String,
     pub ngrok_url: Option<String>,
     pub webhook_secret: Option<String>,
+    /// Take the client IP from X-Forwarded-For set by a local tunnel/proxy
+    #[serde(default)]
+    pub trust_proxy: bool,
+}
+
+/// Exchange quantity freeze limit
+///
+/// `symbol` is either an exact trading symbol or an F&O underlying (NIFTY),
+/// which covers every contract on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreezeQty {
+    pub exchange: String,
+    pub symbol: String,
+    pub freeze_qty: i32,
+    pub updated_at: String,
 }
 
 /// Sandbox order model
@@ -142,6 +210,16 @@ pub struct ApiKeyInfo {
     pub permissions: String,
     pub created_at: String,
     pub last_used_at: Option<String>,
+    #[serde(flatten)]
+    pub rate_limits: ApiKeyRateLimits,
+}
+
+/// Per-API-key rate limit overrides (None uses the global limit)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyRateLimits {
+    pub api_rate_limit: Option<u32>,
+    pub order_rate_limit: Option<u32>,
+    pub smart_order_rate_limit: Option<u32>,
 }
 
 /// Rate limiting configuration
@@ -156,3 +234,57 @@ pub struct RateLimitConfig {
     /// Delay between smart orders (seconds)
     pub smart_order_delay: f64,
 }
+
+/// Risk limits applied before every order (0 disables a limit)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskConfig {
+    pub enabled: bool,
+    /// Max MTM loss for the day (realized + unrealized), in rupees
+    pub max_daily_loss: f64,
+    /// Max number of open positions
+    pub max_open_positions: i32,
+    /// Max value (quantity x price) of a single order
+    pub max_order_value: f64,
+    /// Max absolute net quantity per symbol
+    pub max_symbol_quantity: i32,
+    /// Trigger the kill switch when the daily loss limit is breached
+    pub flatten_on_breach: bool,
+}
+
+/// Kill switch state; trading in its mode stays locked for the IST date it was set on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitchState {
+    /// IST date ("YYYY-MM-DD") the kill switch was activated
+    pub date: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Outbound order update configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderUpdateConfig {
+    /// URL that receives order updates as JSON POSTs (None = disabled)
+    pub webhook_url: Option<String>,
+}
+
+/// Live MTM engine configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MtmConfig {
+    /// Minimum gap between `positions_mtm` events, in milliseconds
+    pub event_interval_ms: u32,
+}
+
+/// DuckDB tick recorder configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickRecorderConfig {
+    /// Record ticks and build 1m bars for the recorder symbols
+    pub enabled: bool,
+    /// How often buffered ticks and bars are written, in seconds
+    pub flush_interval_secs: u32,
+}
+
+/// Idempotent order placement configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyConfig {
+    /// How long a client order id is remembered, in seconds
+    pub window_secs: u32,
+}
@@ -0,0 +1,249 @@
+//! Outbound notification targets and delivery log
+//!
+//! Targets are HTTP (HMAC-signed JSON), Telegram or Discord endpoints, each
+//! with its own event filter. Secrets (HMAC key, Telegram bot token) are
+//! stored encrypted.
+
+use crate::error::Result;
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+/// Outbound notification target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationTarget {
+    pub id: i64,
+    pub name: String,
+    /// "http", "telegram" or "discord"
+    pub kind: String,
+    /// HTTP endpoint or Discord webhook URL
+    pub url: Option<String>,
+    /// Telegram chat ID
+    pub chat_id: Option<String>,
+    /// Event filter ("*" for all events)
+    pub events: Vec<String>,
+    pub max_retries: u32,
+    pub enabled: bool,
+    pub has_secret: bool,
+    #[serde(skip)]
+    pub secret_encrypted: Option<String>,
+    #[serde(skip)]
+    pub secret_nonce: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl NotificationTarget {
+    /// Check whether the target's event filter accepts an event
+    pub fn accepts(&self, event: &str) -> bool {
+        self.events.iter().any(|e| e == "*" || e == event)
+    }
+}
+
+/// Notification delivery log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDelivery {
+    pub id: i64,
+    pub target_id: i64,
+    pub event: String,
+    /// "delivered" or "failed"
+    pub status: String,
+    pub attempts: u32,
+    pub response_code: Option<u16>,
+    pub error: Option<String>,
+    pub payload: String,
+    pub created_at: String,
+}
+
+const SELECT_COLUMNS: &str = "id, name, kind, url, chat_id, events, max_retries, enabled,
+                              secret_encrypted, secret_nonce, created_at, updated_at";
+
+fn row_to_target(row: &Row) -> rusqlite::Result<NotificationTarget> {
+    let events: String = row.get(5)?;
+    let secret_encrypted: Option<String> = row.get(8)?;
+
+    Ok(NotificationTarget {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: row.get(2)?,
+        url: row.get(3)?,
+        chat_id: row.get(4)?,
+        events: split_events(&events),
+        max_retries: row.get(6)?,
+        enabled: row.get::<_, i32>(7)? != 0,
+        has_secret: secret_encrypted.is_some(),
+        secret_encrypted,
+        secret_nonce: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+fn split_events(events: &str) -> Vec<String> {
+    events
+        .split(',')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+/// Create a notification target, returning its ID
+#[allow(clippy::too_many_arguments)]
+pub fn create_target(
+    conn: &Connection,
+    name: &str,
+    kind: &str,
+    url: Option<&str>,
+    chat_id: Option<&str>,
+    secret: Option<(&str, &str)>,
+    events: &[String],
+    max_retries: u32,
+    enabled: bool,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO notification_targets (name, kind, url, chat_id, secret_encrypted, secret_nonce,
+                                           events, max_retries, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            name,
+            kind,
+            url,
+            chat_id,
+            secret.map(|s| s.0),
+            secret.map(|s| s.1),
+            events.join(","),
+            max_retries,
+            enabled as i32,
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Update a notification target
+///
+/// The stored secret is kept when `secret` is None.
+#[allow(clippy::too_many_arguments)]
+pub fn update_target(
+    conn: &Connection,
+    id: i64,
+    name: &str,
+    url: Option<&str>,
+    chat_id: Option<&str>,
+    secret: Option<(&str, &str)>,
+    events: &[String],
+    max_retries: u32,
+    enabled: bool,
+) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE notification_targets SET name = ?1, url = ?2, chat_id = ?3, events = ?4,
+                max_retries = ?5, enabled = ?6, updated_at = datetime('now')
+         WHERE id = ?7",
+        params![name, url, chat_id, events.join(","), max_retries, enabled as i32, id],
+    )?;
+
+    if let Some((encrypted, nonce)) = secret {
+        conn.execute(
+            "UPDATE notification_targets SET secret_encrypted = ?1, secret_nonce = ?2 WHERE id = ?3",
+            params![encrypted, nonce, id],
+        )?;
+    }
+
+    Ok(rows > 0)
+}
+
+/// Delete a notification target and its delivery log
+pub fn delete_target(conn: &Connection, id: i64) -> Result<bool> {
+    conn.execute("DELETE FROM notification_log WHERE target_id = ?1", [id])?;
+    let rows = conn.execute("DELETE FROM notification_targets WHERE id = ?1", [id])?;
+    Ok(rows > 0)
+}
+
+/// Get a notification target by ID
+pub fn get_target(conn: &Connection, id: i64) -> Result<Option<NotificationTarget>> {
+    let sql = format!("SELECT {} FROM notification_targets WHERE id = ?1", SELECT_COLUMNS);
+    match conn.query_row(&sql, [id], row_to_target) {
+        Ok(target) => Ok(Some(target)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get all notification targets
+pub fn get_targets(conn: &Connection, enabled_only: bool) -> Result<Vec<NotificationTarget>> {
+    let sql = if enabled_only {
+        format!("SELECT {} FROM notification_targets WHERE enabled = 1 ORDER BY id", SELECT_COLUMNS)
+    } else {
+        format!("SELECT {} FROM notification_targets ORDER BY id", SELECT_COLUMNS)
+    };
+
+    let mut stmt = conn.prepare(&sql)?;
+    let targets = stmt
+        .query_map([], row_to_target)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(targets)
+}
+
+/// Record a notification delivery attempt
+#[allow(clippy::too_many_arguments)]
+pub fn log_delivery(
+    conn: &Connection,
+    target_id: i64,
+    event: &str,
+    status: &str,
+    attempts: u32,
+    response_code: Option<u16>,
+    error: Option<&str>,
+    payload: &str,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO notification_log (target_id, event, status, attempts, response_code, error, payload)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![target_id, event, status, attempts, response_code, error, payload],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Get recent deliveries, optionally for a single target
+pub fn get_deliveries(
+    conn: &Connection,
+    target_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<NotificationDelivery>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, target_id, event, status, attempts, response_code, error, payload, created_at
+         FROM notification_log
+         WHERE ?1 IS NULL OR target_id = ?1
+         ORDER BY id DESC
+         LIMIT ?2",
+    )?;
+
+    let deliveries = stmt
+        .query_map(params![target_id, limit], |row| {
+            Ok(NotificationDelivery {
+                id: row.get(0)?,
+                target_id: row.get(1)?,
+                event: row.get(2)?,
+                status: row.get(3)?,
+                attempts: row.get(4)?,
+                response_code: row.get(5)?,
+                error: row.get(6)?,
+                payload: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(deliveries)
+}
+
+/// Delete delivery log entries older than `days`
+pub fn prune_deliveries(conn: &Connection, days: i64) -> Result<usize> {
+    let rows = conn.execute(
+        "DELETE FROM notification_log WHERE created_at < datetime('now', ?1)",
+        [format!("-{} days", days)],
+    )?;
+
+    Ok(rows)
+}
@@ -118,6 +118,70 @@ pub fn place_order(
     })
 }
 
+/// Exchange and symbol of every instrument with a pending LIMIT order
+pub fn get_pending_symbols(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT exchange, symbol FROM sandbox_orders
+         WHERE status = 'pending' AND order_type = 'LIMIT'",
+    )?;
+
+    let symbols = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(symbols)
+}
+
+/// Get pending sandbox orders for a symbol, oldest first
+pub fn get_pending_orders(conn: &Connection, exchange: &str, symbol: &str) -> Result<Vec<SandboxOrder>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, order_id, symbol, exchange, side, quantity, price, order_type, product, status,
+                filled_quantity, average_price, created_at, updated_at
+         FROM sandbox_orders WHERE exchange = ?1 AND symbol = ?2 AND status = 'pending' ORDER BY id",
+    )?;
+
+    let orders = stmt
+        .query_map(params![exchange, symbol], |row| {
+            Ok(SandboxOrder {
+                id: row.get(0)?,
+                order_id: row.get(1)?,
+                symbol: row.get(2)?,
+                exchange: row.get(3)?,
+                side: row.get(4)?,
+                quantity: row.get(5)?,
+                price: row.get(6)?,
+                order_type: row.get(7)?,
+                product: row.get(8)?,
+                status: row.get(9)?,
+                filled_quantity: row.get(10)?,
+                average_price: row.get(11)?,
+                created_at: row.get(12)?,
+                updated_at: row.get(13)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(orders)
+}
+
+/// Fill a pending sandbox order at `price` and update the position
+///
+/// Returns false if the order was no longer pending.
+pub fn fill_order(conn: &Connection, order: &SandboxOrder, price: f64) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE sandbox_orders SET status = 'complete', filled_quantity = quantity, average_price = ?1,
+                updated_at = datetime('now')
+         WHERE order_id = ?2 AND status = 'pending'",
+        params![price, order.order_id],
+    )?;
+    if rows == 0 {
+        return Ok(false);
+    }
+
+    update_position(conn, &order.symbol, &order.exchange, &order.side, order.quantity, price, &order.product)?;
+    Ok(true)
+}
+
 /// Update position after order fill
 fn update_position(
     conn: &Connection,
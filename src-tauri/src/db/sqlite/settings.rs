@@ -1,7 +1,10 @@
 //! Settings management
 
-use crate::db::sqlite::models::{AutoLogoutConfig, RateLimitConfig, Settings, WebhookConfig};
-use crate::error::Result;
+use crate::db::sqlite::models::{
+    AutoLogoutConfig, IdempotencyConfig, KillSwitchState, MtmConfig, OrderUpdateConfig, RateLimitConfig, RiskConfig, Settings,
+    TickRecorderConfig, WebhookConfig,
+};
+use crate::error::{AppError, Result};
 use rusqlite::Connection;
 
 /// Get settings
@@ -165,7 +168,8 @@ pub fn update_settings(
 /// Get webhook configuration
 pub fn get_webhook_config(conn: &Connection) -> Result<WebhookConfig> {
     let config = conn.query_row(
-        "SELECT webhook_enabled, webhook_port, webhook_host, ngrok_url, webhook_secret
+        "SELECT webhook_enabled, webhook_port, webhook_host, ngrok_url, webhook_secret,
+                webhook_trust_proxy
          FROM settings WHERE id = 1",
         [],
         |row| {
@@ -175,6 +179,7 @@ pub fn get_webhook_config(conn: &Connection) -> Result<WebhookConfig> {
                 host: row.get::<_, String>(2)?,
                 ngrok_url: row.get::<_, Option<String>>(3)?,
                 webhook_secret: row.get::<_, Option<String>>(4)?,
+                trust_proxy: row.get::<_, i32>(5)? == 1,
             })
         },
     )?;
@@ -190,6 +195,7 @@ pub fn update_webhook_config(
     host: Option<String>,
     ngrok_url: Option<String>,
     webhook_secret: Option<String>,
+    trust_proxy: Option<bool>,
 ) -> Result<WebhookConfig> {
     let mut updates = Vec::new();
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -217,6 +223,10 @@ pub fn update_webhook_config(
         updates.push("webhook_secret = ?");
         params.push(Box::new(secret));
     }
+    if let Some(trust) = trust_proxy {
+        updates.push("webhook_trust_proxy = ?");
+        params.push(Box::new(trust as i32));
+    }
 
     if !updates.is_empty() {
         updates.push("updated_at = datetime('now')");
@@ -306,3 +316,236 @@ pub fn update_rate_limit_config(
 
     get_rate_limit_config(conn)
 }
+
+/// Get outbound order update configuration
+pub fn get_order_update_config(conn: &Connection) -> Result<OrderUpdateConfig> {
+    let config = conn.query_row(
+        "SELECT order_update_webhook_url FROM settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(OrderUpdateConfig {
+                webhook_url: row.get::<_, Option<String>>(0)?,
+            })
+        },
+    )?;
+
+    Ok(config)
+}
+
+/// Update outbound order update configuration
+///
+/// An empty URL disables order update webhooks.
+pub fn update_order_update_config(
+    conn: &Connection,
+    webhook_url: Option<String>,
+) -> Result<OrderUpdateConfig> {
+    if let Some(url) = webhook_url {
+        let url = url.trim();
+        if !url.is_empty() && !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(AppError::Validation(
+                "Order update webhook URL must start with http:// or https://".to_string(),
+            ));
+        }
+
+        let value = if url.is_empty() { None } else { Some(url.to_string()) };
+        conn.execute(
+            "UPDATE settings SET order_update_webhook_url = ?1, updated_at = datetime('now') WHERE id = 1",
+            [value],
+        )?;
+    }
+
+    get_order_update_config(conn)
+}
+
+/// Get idempotent order placement configuration
+pub fn get_idempotency_config(conn: &Connection) -> Result<IdempotencyConfig> {
+    let config = conn.query_row(
+        "SELECT idempotency_window_secs FROM settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(IdempotencyConfig {
+                window_secs: row.get::<_, u32>(0)?,
+            })
+        },
+    )?;
+
+    Ok(config)
+}
+
+/// Update idempotent order placement configuration
+pub fn update_idempotency_config(conn: &Connection, window_secs: u32) -> Result<IdempotencyConfig> {
+    // 1 minute to 7 days
+    if !(60..=604_800).contains(&window_secs) {
+        return Err(AppError::Validation(
+            "Idempotency window must be between 60 seconds and 7 days".to_string(),
+        ));
+    }
+
+    conn.execute(
+        "UPDATE settings SET idempotency_window_secs = ?1, updated_at = datetime('now') WHERE id = 1",
+        [window_secs],
+    )?;
+
+    get_idempotency_config(conn)
+}
+
+/// Get live MTM engine configuration
+pub fn get_mtm_config(conn: &Connection) -> Result<MtmConfig> {
+    let config = conn.query_row(
+        "SELECT mtm_event_interval_ms FROM settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(MtmConfig {
+                event_interval_ms: row.get::<_, u32>(0)?,
+            })
+        },
+    )?;
+
+    Ok(config)
+}
+
+/// Update live MTM engine configuration
+pub fn update_mtm_config(conn: &Connection, event_interval_ms: u32) -> Result<MtmConfig> {
+    // 100 ms to 10 seconds
+    if !(100..=10_000).contains(&event_interval_ms) {
+        return Err(AppError::Validation(
+            "MTM event interval must be between 100 ms and 10 seconds".to_string(),
+        ));
+    }
+
+    conn.execute(
+        "UPDATE settings SET mtm_event_interval_ms = ?1, updated_at = datetime('now') WHERE id = 1",
+        [event_interval_ms],
+    )?;
+
+    get_mtm_config(conn)
+}
+
+/// Get tick recorder configuration
+pub fn get_tick_recorder_config(conn: &Connection) -> Result<TickRecorderConfig> {
+    let config = conn.query_row(
+        "SELECT tick_recorder_enabled, tick_recorder_flush_secs FROM settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(TickRecorderConfig {
+                enabled: row.get::<_, i32>(0)? != 0,
+                flush_interval_secs: row.get::<_, u32>(1)?,
+            })
+        },
+    )?;
+
+    Ok(config)
+}
+
+/// Update tick recorder configuration
+pub fn update_tick_recorder_config(
+    conn: &Connection,
+    enabled: bool,
+    flush_interval_secs: u32,
+) -> Result<TickRecorderConfig> {
+    // 1 second to 1 minute
+    if !(1..=60).contains(&flush_interval_secs) {
+        return Err(AppError::Validation(
+            "Tick recorder flush interval must be between 1 and 60 seconds".to_string(),
+        ));
+    }
+
+    conn.execute(
+        "UPDATE settings SET tick_recorder_enabled = ?1, tick_recorder_flush_secs = ?2,
+                updated_at = datetime('now') WHERE id = 1",
+        rusqlite::params![enabled as i32, flush_interval_secs],
+    )?;
+
+    get_tick_recorder_config(conn)
+}
+
+/// Get risk limits
+pub fn get_risk_config(conn: &Connection) -> Result<RiskConfig> {
+    let config = conn.query_row(
+        "SELECT risk_enabled, risk_max_daily_loss, risk_max_open_positions, risk_max_order_value,
+                risk_max_symbol_quantity, risk_flatten_on_breach
+         FROM settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(RiskConfig {
+                enabled: row.get::<_, i32>(0)? == 1,
+                max_daily_loss: row.get(1)?,
+                max_open_positions: row.get(2)?,
+                max_order_value: row.get(3)?,
+                max_symbol_quantity: row.get(4)?,
+                flatten_on_breach: row.get::<_, i32>(5)? == 1,
+            })
+        },
+    )?;
+
+    Ok(config)
+}
+
+/// Update risk limits
+pub fn update_risk_config(conn: &Connection, config: &RiskConfig) -> Result<RiskConfig> {
+    if config.max_daily_loss < 0.0
+        || config.max_open_positions < 0
+        || config.max_order_value < 0.0
+        || config.max_symbol_quantity < 0
+    {
+        return Err(AppError::Validation(
+            "Risk limits cannot be negative (use 0 to disable a limit)".to_string(),
+        ));
+    }
+
+    conn.execute(
+        "UPDATE settings SET risk_enabled = ?1, risk_max_daily_loss = ?2, risk_max_open_positions = ?3,
+                risk_max_order_value = ?4, risk_max_symbol_quantity = ?5, risk_flatten_on_breach = ?6,
+                updated_at = datetime('now')
+         WHERE id = 1",
+        rusqlite::params![
+            config.enabled as i32,
+            config.max_daily_loss,
+            config.max_open_positions,
+            config.max_order_value,
+            config.max_symbol_quantity,
+            config.flatten_on_breach as i32,
+        ],
+    )?;
+
+    get_risk_config(conn)
+}
+
+/// Settings columns holding the kill switch of a mode (live/analyze)
+fn kill_switch_columns(mode: &str) -> (&'static str, &'static str) {
+    if mode == "analyze" {
+        ("sandbox_kill_switch_date", "sandbox_kill_switch_reason")
+    } else {
+        ("kill_switch_date", "kill_switch_reason")
+    }
+}
+
+/// Get kill switch state for a mode (live/analyze)
+pub fn get_kill_switch(conn: &Connection, mode: &str) -> Result<KillSwitchState> {
+    let (date_column, reason_column) = kill_switch_columns(mode);
+    let state = conn.query_row(
+        &format!("SELECT {}, {} FROM settings WHERE id = 1", date_column, reason_column),
+        [],
+        |row| {
+            Ok(KillSwitchState {
+                date: row.get(0)?,
+                reason: row.get(1)?,
+            })
+        },
+    )?;
+
+    Ok(state)
+}
+
+/// Set the kill switch of a mode (live/analyze) for an IST date
+pub fn set_kill_switch(conn: &Connection, mode: &str, date: &str, reason: &str) -> Result<()> {
+    let (date_column, reason_column) = kill_switch_columns(mode);
+    conn.execute(
+        &format!(
+            "UPDATE settings SET {} = ?1, {} = ?2, updated_at = datetime('now') WHERE id = 1",
+            date_column, reason_column
+        ),
+        [date, reason],
+    )?;
+    Ok(())
+}
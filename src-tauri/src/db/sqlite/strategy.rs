@@ -1,31 +1,48 @@
 //! Strategy management
 
-use crate::db::sqlite::models::Strategy;
+use crate::db::sqlite::models::{Strategy, StrategyOrder, StrategySymbolMapping};
 use crate::error::{AppError, Result};
 use rusqlite::Connection;
 
+const STRATEGY_COLUMNS: &str = "id, name, webhook_id, exchange, symbol, product, quantity, enabled,
+                                is_intraday, trading_mode, start_time, end_time, squareoff_time,
+                                created_at, updated_at, webhook_auth_mode, webhook_secret,
+                                webhook_ip_allowlist, replay_window_secs";
+
+/// Webhook verification modes
+const WEBHOOK_AUTH_MODES: [&str; 3] = ["none", "secret", "hmac"];
+
+fn row_to_strategy(row: &rusqlite::Row) -> rusqlite::Result<Strategy> {
+    Ok(Strategy {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        webhook_id: row.get(2)?,
+        exchange: row.get(3)?,
+        symbol: row.get(4)?,
+        product: row.get(5)?,
+        quantity: row.get(6)?,
+        enabled: row.get::<_, i32>(7)? == 1,
+        is_intraday: row.get::<_, i32>(8)? == 1,
+        trading_mode: row.get(9)?,
+        start_time: row.get(10)?,
+        end_time: row.get(11)?,
+        squareoff_time: row.get(12)?,
+        webhook_auth_mode: row.get(15)?,
+        webhook_secret: row.get(16)?,
+        webhook_ip_allowlist: row.get(17)?,
+        replay_window_secs: row.get(18)?,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+    })
+}
+
 /// Get all strategies
 pub fn get_strategies(conn: &Connection) -> Result<Vec<Strategy>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, name, webhook_id, exchange, symbol, product, quantity, enabled, created_at, updated_at
-         FROM strategies ORDER BY created_at DESC",
-    )?;
+    let sql = format!("SELECT {} FROM strategies ORDER BY created_at DESC", STRATEGY_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
 
     let strategies = stmt
-        .query_map([], |row| {
-            Ok(Strategy {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                webhook_id: row.get(2)?,
-                exchange: row.get(3)?,
-                symbol: row.get(4)?,
-                product: row.get(5)?,
-                quantity: row.get(6)?,
-                enabled: row.get::<_, i32>(7)? == 1,
-                created_at: row.get(8)?,
-                updated_at: row.get(9)?,
-            })
-        })?
+        .query_map([], row_to_strategy)?
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
     Ok(strategies)
@@ -33,9 +50,30 @@ pub fn get_strategies(conn: &Connection) -> Result<Vec<Strategy>> {
 
 /// Create a new strategy
 pub fn create_strategy(conn: &Connection, strategy: &Strategy) -> Result<Strategy> {
+    validate_trading_settings(
+        &strategy.trading_mode,
+        &strategy.start_time,
+        &strategy.end_time,
+        &strategy.squareoff_time,
+    )?;
+
+    // Webhook IDs share one URL namespace with Chartink strategies
+    let taken: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM chartink_strategies WHERE webhook_id = ?)",
+        [&strategy.webhook_id],
+        |row| row.get(0),
+    )?;
+    if taken {
+        return Err(AppError::Validation(format!(
+            "webhook_id '{}' is already used by a Chartink strategy",
+            strategy.webhook_id
+        )));
+    }
+
     conn.execute(
-        "INSERT INTO strategies (name, webhook_id, exchange, symbol, product, quantity, enabled)
-         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        "INSERT INTO strategies (name, webhook_id, exchange, symbol, product, quantity, enabled,
+                                 is_intraday, trading_mode, start_time, end_time, squareoff_time)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         rusqlite::params![
             strategy.name,
             strategy.webhook_id,
@@ -44,47 +82,54 @@ pub fn create_strategy(conn: &Connection, strategy: &Strategy) -> Result<Strateg
             strategy.product,
             strategy.quantity,
             strategy.enabled as i32,
+            strategy.is_intraday as i32,
+            strategy.trading_mode,
+            strategy.start_time,
+            strategy.end_time,
+            strategy.squareoff_time,
         ],
     )?;
 
     let id = conn.last_insert_rowid();
 
+    // The strategy's own symbol is its first mapping
+    if !strategy.symbol.trim().is_empty() {
+        conn.execute(
+            "INSERT OR IGNORE INTO strategy_symbol_mappings (strategy_id, exchange, symbol, quantity, product)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                id,
+                strategy.exchange,
+                strategy.symbol.trim(),
+                strategy.quantity,
+                strategy.product,
+            ],
+        )?;
+    }
+
     // Return the created strategy
     get_strategy_by_id(conn, id)
 }
 
 /// Get strategy by ID
-fn get_strategy_by_id(conn: &Connection, id: i64) -> Result<Strategy> {
-    conn.query_row(
-        "SELECT id, name, webhook_id, exchange, symbol, product, quantity, enabled, created_at, updated_at
-         FROM strategies WHERE id = ?",
-        [id],
-        |row| {
-            Ok(Strategy {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                webhook_id: row.get(2)?,
-                exchange: row.get(3)?,
-                symbol: row.get(4)?,
-                product: row.get(5)?,
-                quantity: row.get(6)?,
-                enabled: row.get::<_, i32>(7)? == 1,
-                created_at: row.get(8)?,
-                updated_at: row.get(9)?,
-            })
-        },
-    )
-    .map_err(|e| match e {
-        rusqlite::Error::QueryReturnedNoRows => {
-            AppError::NotFound(format!("Strategy not found: {}", id))
-        }
-        _ => e.into(),
-    })
+pub fn get_strategy_by_id(conn: &Connection, id: i64) -> Result<Strategy> {
+    let sql = format!("SELECT {} FROM strategies WHERE id = ?", STRATEGY_COLUMNS);
+    conn.query_row(&sql, [id], row_to_strategy)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Strategy not found: {}", id))
+            }
+            _ => e.into(),
+        })
 }
 
 /// Update a strategy
+///
+/// The strategy's own symbol is its seeded mapping, so changes to the
+/// symbol, exchange, quantity or product are carried over to that mapping
+/// in the same transaction.
 pub fn update_strategy(
-    conn: &Connection,
+    conn: &mut Connection,
     id: i64,
     name: Option<String>,
     exchange: Option<String>,
@@ -134,14 +179,268 @@ pub fn update_strategy(
 
     params.push(Box::new(id));
 
+    let tx = conn.transaction()?;
+    let before = get_strategy_by_id(&tx, id)?;
+
     let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
-    conn.execute(&sql, params_refs.as_slice())?;
+    tx.execute(&sql, params_refs.as_slice())?;
+
+    let after = get_strategy_by_id(&tx, id)?;
+    sync_seeded_mapping(&tx, &before, &after)?;
+    tx.commit()?;
+
+    Ok(after)
+}
+
+/// Move the strategy's seeded symbol mapping to its current symbol settings
+fn sync_seeded_mapping(conn: &Connection, before: &Strategy, after: &Strategy) -> Result<()> {
+    let (old_symbol, new_symbol) = (before.symbol.trim(), after.symbol.trim());
+    let unchanged = old_symbol == new_symbol
+        && before.exchange == after.exchange
+        && before.quantity == after.quantity
+        && before.product == after.product;
+    if unchanged {
+        return Ok(());
+    }
+
+    if !old_symbol.is_empty() && old_symbol != new_symbol {
+        conn.execute(
+            "DELETE FROM strategy_symbol_mappings WHERE strategy_id = ?1 AND symbol = ?2",
+            rusqlite::params![after.id, old_symbol],
+        )?;
+    }
+
+    if !new_symbol.is_empty() {
+        conn.execute(
+            "INSERT INTO strategy_symbol_mappings (strategy_id, exchange, symbol, quantity, product)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(strategy_id, symbol) DO UPDATE SET
+                 exchange = excluded.exchange, quantity = excluded.quantity, product = excluded.product",
+            rusqlite::params![after.id, after.exchange, new_symbol, after.quantity, after.product],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Validate a trading mode and "HH:MM" window (start < end <= squareoff)
+fn validate_trading_settings(
+    trading_mode: &str,
+    start_time: &Option<String>,
+    end_time: &Option<String>,
+    squareoff_time: &Option<String>,
+) -> Result<()> {
+    if !matches!(trading_mode, "LONG" | "SHORT" | "BOTH") {
+        return Err(AppError::Validation(format!(
+            "Invalid trading mode: {} (expected LONG, SHORT or BOTH)",
+            trading_mode
+        )));
+    }
+
+    let mut times = Vec::new();
+    for time in [start_time, end_time, squareoff_time] {
+        let parsed = match time {
+            Some(t) => Some(chrono::NaiveTime::parse_from_str(t, "%H:%M").map_err(|_| {
+                AppError::Validation(format!("Invalid time '{}', expected HH:MM", t))
+            })?),
+            None => None,
+        };
+        times.push(parsed);
+    }
+
+    if let [Some(start), Some(end), Some(squareoff)] = times[..] {
+        if !(start < end && end <= squareoff) {
+            return Err(AppError::Validation(
+                "Times must satisfy start < end <= squareoff".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Update a strategy's trading mode and intraday window
+///
+/// Times are "HH:MM" in IST; `None` leaves a field unchanged.
+#[allow(clippy::too_many_arguments)]
+pub fn update_strategy_trading(
+    conn: &Connection,
+    id: i64,
+    is_intraday: Option<bool>,
+    trading_mode: Option<String>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    squareoff_time: Option<String>,
+) -> Result<Strategy> {
+    let existing = get_strategy_by_id(conn, id)?;
+
+    let trading_mode = trading_mode.map(|m| m.to_uppercase()).unwrap_or(existing.trading_mode);
+    let start_time = start_time.or(existing.start_time);
+    let end_time = end_time.or(existing.end_time);
+    let squareoff_time = squareoff_time.or(existing.squareoff_time);
+
+    validate_trading_settings(&trading_mode, &start_time, &end_time, &squareoff_time)?;
+
+    conn.execute(
+        "UPDATE strategies SET is_intraday = ?1, trading_mode = ?2, start_time = ?3, end_time = ?4,
+                squareoff_time = ?5, updated_at = datetime('now')
+         WHERE id = ?6",
+        rusqlite::params![
+            is_intraday.unwrap_or(existing.is_intraday) as i32,
+            trading_mode,
+            start_time,
+            end_time,
+            squareoff_time,
+            id,
+        ],
+    )?;
+
+    get_strategy_by_id(conn, id)
+}
+
+/// Webhook verification settings, shared by strategies and Chartink strategies
+#[derive(Debug, Clone)]
+pub(crate) struct WebhookSecurity {
+    pub auth_mode: String,
+    pub webhook_secret: Option<String>,
+    pub ip_allowlist: Option<String>,
+    pub replay_window_secs: i64,
+}
+
+/// Update a strategy's webhook verification settings
+///
+/// `None` leaves a field unchanged; an empty secret or allowlist clears it.
+pub fn update_strategy_security(
+    conn: &Connection,
+    id: i64,
+    auth_mode: Option<String>,
+    webhook_secret: Option<String>,
+    ip_allowlist: Option<String>,
+    replay_window_secs: Option<i64>,
+) -> Result<Strategy> {
+    let existing = get_strategy_by_id(conn, id)?;
+    let security = merge_webhook_security(
+        WebhookSecurity {
+            auth_mode: existing.webhook_auth_mode,
+            webhook_secret: existing.webhook_secret,
+            ip_allowlist: existing.webhook_ip_allowlist,
+            replay_window_secs: existing.replay_window_secs,
+        },
+        auth_mode,
+        webhook_secret,
+        ip_allowlist,
+        replay_window_secs,
+    )?;
+
+    conn.execute(
+        "UPDATE strategies SET webhook_auth_mode = ?1, webhook_secret = ?2, webhook_ip_allowlist = ?3,
+                replay_window_secs = ?4, updated_at = datetime('now')
+         WHERE id = ?5",
+        rusqlite::params![
+            security.auth_mode,
+            security.webhook_secret,
+            security.ip_allowlist,
+            security.replay_window_secs,
+            id
+        ],
+    )?;
 
     get_strategy_by_id(conn, id)
 }
 
+/// Apply verification setting changes and validate the result
+///
+/// `None` leaves a field unchanged; an empty secret or allowlist clears it.
+pub(crate) fn merge_webhook_security(
+    existing: WebhookSecurity,
+    auth_mode: Option<String>,
+    webhook_secret: Option<String>,
+    ip_allowlist: Option<String>,
+    replay_window_secs: Option<i64>,
+) -> Result<WebhookSecurity> {
+    let auth_mode = auth_mode.map(|m| m.to_lowercase()).unwrap_or(existing.auth_mode);
+    if !WEBHOOK_AUTH_MODES.contains(&auth_mode.as_str()) {
+        return Err(AppError::Validation(format!(
+            "Invalid webhook auth mode '{}', expected none, secret or hmac",
+            auth_mode
+        )));
+    }
+
+    let webhook_secret = match webhook_secret {
+        Some(secret) if secret.trim().is_empty() => None,
+        Some(secret) => Some(secret.trim().to_string()),
+        None => existing.webhook_secret,
+    };
+    if auth_mode == "hmac" && webhook_secret.is_none() {
+        return Err(AppError::Validation(
+            "HMAC verification needs a strategy webhook secret".to_string(),
+        ));
+    }
+
+    let ip_allowlist = match ip_allowlist {
+        Some(list) => {
+            let entries: Vec<&str> = list
+                .split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .collect();
+            if let Some(bad) = entries.iter().find(|e| !crate::webhook::verification::is_valid_ip_rule(e)) {
+                return Err(AppError::Validation(format!("Invalid IP or CIDR in allowlist: {}", bad)));
+            }
+            if entries.is_empty() { None } else { Some(entries.join(",")) }
+        }
+        None => existing.ip_allowlist,
+    };
+
+    let replay_window_secs = replay_window_secs.unwrap_or(existing.replay_window_secs);
+    if !(30..=3600).contains(&replay_window_secs) {
+        return Err(AppError::Validation(
+            "Replay window must be between 30 and 3600 seconds".to_string(),
+        ));
+    }
+
+    Ok(WebhookSecurity {
+        auth_mode,
+        webhook_secret,
+        ip_allowlist,
+        replay_window_secs,
+    })
+}
+
+/// Remember a webhook nonce for replay protection
+///
+/// `source` is the strategy table the id belongs to (strategy or chartink).
+/// Returns false if the nonce was already seen inside the window.
+pub fn claim_webhook_nonce(
+    conn: &Connection,
+    source: &str,
+    strategy_id: i64,
+    nonce: &str,
+    window_secs: i64,
+) -> Result<bool> {
+    conn.execute(
+        "DELETE FROM webhook_nonces
+         WHERE source = ?1 AND strategy_id = ?2 AND created_at < datetime('now', ?3)",
+        rusqlite::params![source, strategy_id, format!("-{} seconds", window_secs)],
+    )?;
+
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO webhook_nonces (source, strategy_id, nonce) VALUES (?1, ?2, ?3)",
+        rusqlite::params![source, strategy_id, nonce],
+    )?;
+
+    Ok(inserted == 1)
+}
+
 /// Delete a strategy
 pub fn delete_strategy(conn: &Connection, id: i64) -> Result<()> {
+    // Foreign keys are not enforced, so remove mappings explicitly
+    conn.execute("DELETE FROM strategy_symbol_mappings WHERE strategy_id = ?", [id])?;
+    conn.execute(
+        "DELETE FROM webhook_nonces WHERE source = 'strategy' AND strategy_id = ?",
+        [id],
+    )?;
+
     let rows = conn.execute("DELETE FROM strategies WHERE id = ?", [id])?;
 
     if rows == 0 {
@@ -158,7 +457,10 @@ pub fn get_strategy_by_webhook_id(
     webhook_id: &str,
 ) -> Result<Option<crate::webhook::handlers::Strategy>> {
     let result = conn.query_row(
-        "SELECT id, name, webhook_id, enabled FROM strategies WHERE webhook_id = ?",
+        "SELECT id, name, webhook_id, enabled, is_intraday, trading_mode, start_time, end_time,
+                squareoff_time, webhook_auth_mode, webhook_secret, webhook_ip_allowlist,
+                replay_window_secs
+         FROM strategies WHERE webhook_id = ?",
         [webhook_id],
         |row| {
             Ok(crate::webhook::handlers::Strategy {
@@ -166,12 +468,15 @@ pub fn get_strategy_by_webhook_id(
                 name: row.get(1)?,
                 webhook_id: row.get(2)?,
                 is_active: row.get::<_, i32>(3)? == 1,
-                // TODO: Add these fields to strategies table in a future migration
-                is_intraday: false,  // Default to positional
-                trading_mode: "BOTH".to_string(),  // Default to both directions
-                start_time: Some("09:15".to_string()),
-                end_time: Some("15:15".to_string()),
-                squareoff_time: Some("15:25".to_string()),
+                is_intraday: row.get::<_, i32>(4)? == 1,
+                trading_mode: row.get(5)?,
+                start_time: row.get(6)?,
+                end_time: row.get(7)?,
+                squareoff_time: row.get(8)?,
+                auth_mode: row.get(9)?,
+                webhook_secret: row.get(10)?,
+                ip_allowlist: row.get(11)?,
+                replay_window_secs: row.get(12)?,
             })
         },
     );
@@ -184,18 +489,18 @@ pub fn get_strategy_by_webhook_id(
 }
 
 /// Get symbol mapping for a strategy
-/// Currently uses the strategy's direct symbol/exchange/quantity fields
-/// TODO: Implement proper strategy_symbol_mappings table for multi-symbol strategies
+///
+/// Matches the alert's symbol exactly against `strategy_symbol_mappings`;
+/// unmapped symbols return None so the alert is rejected.
 pub fn get_symbol_mapping(
     conn: &Connection,
     strategy_id: i64,
     symbol: &str,
 ) -> Result<Option<crate::webhook::handlers::SymbolMapping>> {
-    // For now, get from the strategy's own symbol field
-    // In future, this should query strategy_symbol_mappings table
     let result = conn.query_row(
-        "SELECT symbol, exchange, quantity, product FROM strategies WHERE id = ? AND symbol = ?",
-        rusqlite::params![strategy_id, symbol],
+        "SELECT symbol, exchange, quantity, product FROM strategy_symbol_mappings
+         WHERE strategy_id = ?1 AND symbol = ?2",
+        rusqlite::params![strategy_id, symbol.trim()],
         |row| {
             Ok(crate::webhook::handlers::SymbolMapping {
                 symbol: row.get(0)?,
@@ -208,26 +513,466 @@ pub fn get_symbol_mapping(
 
     match result {
         Ok(mapping) => Ok(Some(mapping)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => {
-            // Try without symbol filter (single-symbol strategy)
-            let result2 = conn.query_row(
-                "SELECT symbol, exchange, quantity, product FROM strategies WHERE id = ?",
-                [strategy_id],
-                |row| {
-                    Ok(crate::webhook::handlers::SymbolMapping {
-                        symbol: row.get(0)?,
-                        exchange: row.get(1)?,
-                        quantity: row.get(2)?,
-                        product_type: row.get(3)?,
-                    })
-                },
-            );
-            match result2 {
-                Ok(mapping) => Ok(Some(mapping)),
-                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-                Err(e) => Err(e.into()),
-            }
-        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(e.into()),
     }
 }
+
+// ============================================================================
+// Strategy Symbol Mappings
+// ============================================================================
+
+const MAPPING_COLUMNS: &str = "id, strategy_id, exchange, symbol, quantity, product, created_at";
+
+fn row_to_mapping(row: &rusqlite::Row) -> rusqlite::Result<StrategySymbolMapping> {
+    Ok(StrategySymbolMapping {
+        id: row.get(0)?,
+        strategy_id: row.get(1)?,
+        exchange: row.get(2)?,
+        symbol: row.get(3)?,
+        quantity: row.get(4)?,
+        product: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn map_unique_violation(e: rusqlite::Error, symbol: &str) -> AppError {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            AppError::Validation(format!("Symbol {} is already mapped in this strategy", symbol))
+        }
+        _ => e.into(),
+    }
+}
+
+fn validate_mapping(exchange: &str, symbol: &str, quantity: i32, product: &str) -> Result<()> {
+    if symbol.trim().is_empty() || exchange.trim().is_empty() {
+        return Err(AppError::Validation("Symbol and exchange are required".to_string()));
+    }
+    if quantity <= 0 {
+        return Err(AppError::Validation(format!("Quantity for {} must be positive", symbol)));
+    }
+    if !matches!(product, "MIS" | "CNC" | "NRML") {
+        return Err(AppError::Validation(format!(
+            "Invalid product {} for {} (expected MIS, CNC or NRML)",
+            product, symbol
+        )));
+    }
+    Ok(())
+}
+
+/// Get all symbol mappings for a strategy
+pub fn get_symbol_mappings(conn: &Connection, strategy_id: i64) -> Result<Vec<StrategySymbolMapping>> {
+    let sql = format!(
+        "SELECT {} FROM strategy_symbol_mappings WHERE strategy_id = ?1 ORDER BY symbol",
+        MAPPING_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mappings = stmt
+        .query_map([strategy_id], row_to_mapping)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(mappings)
+}
+
+fn get_symbol_mapping_by_id(conn: &Connection, id: i64) -> Result<StrategySymbolMapping> {
+    let sql = format!("SELECT {} FROM strategy_symbol_mappings WHERE id = ?1", MAPPING_COLUMNS);
+    conn.query_row(&sql, [id], row_to_mapping).map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            AppError::NotFound(format!("Symbol mapping not found: {}", id))
+        }
+        _ => e.into(),
+    })
+}
+
+/// Add a symbol mapping to a strategy
+pub fn add_symbol_mapping(
+    conn: &Connection,
+    strategy_id: i64,
+    exchange: &str,
+    symbol: &str,
+    quantity: i32,
+    product: &str,
+) -> Result<StrategySymbolMapping> {
+    let (exchange, symbol, product) = (exchange.trim(), symbol.trim(), product.trim());
+    validate_mapping(exchange, symbol, quantity, product)?;
+    get_strategy_by_id(conn, strategy_id)?;
+
+    conn.execute(
+        "INSERT INTO strategy_symbol_mappings (strategy_id, exchange, symbol, quantity, product)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![strategy_id, exchange, symbol, quantity, product],
+    )
+    .map_err(|e| map_unique_violation(e, symbol))?;
+
+    get_symbol_mapping_by_id(conn, conn.last_insert_rowid())
+}
+
+/// Update a symbol mapping
+pub fn update_symbol_mapping(
+    conn: &Connection,
+    id: i64,
+    exchange: Option<String>,
+    symbol: Option<String>,
+    quantity: Option<i32>,
+    product: Option<String>,
+) -> Result<StrategySymbolMapping> {
+    let existing = get_symbol_mapping_by_id(conn, id)?;
+
+    let exchange = exchange.map(|e| e.trim().to_string()).unwrap_or(existing.exchange);
+    let symbol = symbol.map(|s| s.trim().to_string()).unwrap_or(existing.symbol);
+    let quantity = quantity.unwrap_or(existing.quantity);
+    let product = product.map(|p| p.trim().to_string()).unwrap_or(existing.product);
+    validate_mapping(&exchange, &symbol, quantity, &product)?;
+
+    conn.execute(
+        "UPDATE strategy_symbol_mappings SET exchange = ?1, symbol = ?2, quantity = ?3, product = ?4
+         WHERE id = ?5",
+        rusqlite::params![exchange, symbol, quantity, product, id],
+    )
+    .map_err(|e| map_unique_violation(e, &symbol))?;
+
+    get_symbol_mapping_by_id(conn, id)
+}
+
+/// Remove a symbol mapping
+pub fn delete_symbol_mapping(conn: &Connection, id: i64) -> Result<()> {
+    let rows = conn.execute("DELETE FROM strategy_symbol_mappings WHERE id = ?1", [id])?;
+
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Symbol mapping not found: {}", id)));
+    }
+
+    Ok(())
+}
+
+/// Bulk-import symbol mappings from CSV
+///
+/// Expects a header row with `symbol`, `exchange` and `quantity` columns
+/// (any order) and an optional `product` column (default MIS). Existing
+/// mappings for the same symbol are updated; with `replace`, mappings not in
+/// the file are removed. The import is all-or-nothing.
+pub fn import_symbol_mappings(
+    conn: &mut Connection,
+    strategy_id: i64,
+    csv: &str,
+    replace: bool,
+) -> Result<usize> {
+    let rows = parse_mapping_csv(csv)?;
+    get_strategy_by_id(conn, strategy_id)?;
+
+    let tx = conn.transaction()?;
+
+    if replace {
+        tx.execute("DELETE FROM strategy_symbol_mappings WHERE strategy_id = ?1", [strategy_id])?;
+    }
+
+    for (exchange, symbol, quantity, product) in &rows {
+        tx.execute(
+            "INSERT INTO strategy_symbol_mappings (strategy_id, exchange, symbol, quantity, product)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(strategy_id, symbol) DO UPDATE SET
+                 exchange = excluded.exchange, quantity = excluded.quantity, product = excluded.product",
+            rusqlite::params![strategy_id, exchange, symbol, quantity, product],
+        )?;
+    }
+
+    tx.commit()?;
+
+    tracing::info!("Imported {} symbol mappings for strategy {}", rows.len(), strategy_id);
+    Ok(rows.len())
+}
+
+/// Parse mapping CSV into (exchange, symbol, quantity, product) rows
+fn parse_mapping_csv(csv: &str) -> Result<Vec<(String, String, i32, String)>> {
+    let mut lines = csv
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .enumerate();
+
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| AppError::Validation("CSV is empty".to_string()))?;
+    let header: Vec<String> = header.split(',').map(|h| h.trim().to_lowercase()).collect();
+
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (Some(symbol_col), Some(exchange_col), Some(quantity_col)) =
+        (column("symbol"), column("exchange"), column("quantity"))
+    else {
+        return Err(AppError::Validation(
+            "CSV header must include symbol, exchange and quantity".to_string(),
+        ));
+    };
+    let product_col = column("product");
+
+    let mut rows = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for (index, line) in lines {
+        let line_no = index + 1;
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |col: usize| fields.get(col).copied().unwrap_or("");
+
+        let symbol = field(symbol_col).to_string();
+        let exchange = field(exchange_col).to_uppercase();
+        let quantity: i32 = field(quantity_col).parse().map_err(|_| {
+            AppError::Validation(format!("Line {}: invalid quantity '{}'", line_no, field(quantity_col)))
+        })?;
+        let product = match product_col.map(field) {
+            Some(p) if !p.is_empty() => p.to_uppercase(),
+            _ => "MIS".to_string(),
+        };
+
+        validate_mapping(&exchange, &symbol, quantity, &product)
+            .map_err(|e| AppError::Validation(format!("Line {}: {}", line_no, e)))?;
+
+        if !seen.insert(symbol.clone()) {
+            return Err(AppError::Validation(format!(
+                "Line {}: duplicate symbol {}",
+                line_no, symbol
+            )));
+        }
+
+        rows.push((exchange, symbol, quantity, product));
+    }
+
+    Ok(rows)
+}
+
+/// Record an order placed on behalf of a strategy
+#[allow(clippy::too_many_arguments)]
+pub fn record_strategy_order(
+    conn: &Connection,
+    strategy_id: i64,
+    order_id: &str,
+    mode: &str,
+    exchange: &str,
+    symbol: &str,
+    product: &str,
+    side: &str,
+    quantity: i32,
+    reason: &str,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO strategy_orders (strategy_id, order_id, mode, exchange, symbol, product, side, quantity, reason)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        rusqlite::params![strategy_id, order_id, mode, exchange, symbol, product, side, quantity, reason],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Get a strategy's orders in a mode created at or after `since` (UTC, "YYYY-MM-DD HH:MM:SS")
+pub fn get_strategy_orders(
+    conn: &Connection,
+    strategy_id: i64,
+    mode: &str,
+    since: &str,
+) -> Result<Vec<StrategyOrder>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, strategy_id, order_id, mode, exchange, symbol, product, side, quantity, reason, created_at
+         FROM strategy_orders
+         WHERE strategy_id = ?1 AND mode = ?2 AND created_at >= ?3
+         ORDER BY id",
+    )?;
+
+    let orders = stmt
+        .query_map(rusqlite::params![strategy_id, mode, since], |row| {
+            Ok(StrategyOrder {
+                id: row.get(0)?,
+                strategy_id: row.get(1)?,
+                order_id: row.get(2)?,
+                mode: row.get(3)?,
+                exchange: row.get(4)?,
+                symbol: row.get(5)?,
+                product: row.get(6)?,
+                side: row.get(7)?,
+                quantity: row.get(8)?,
+                reason: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(orders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::migrations::run_migrations;
+
+    fn create_test_db() -> (Connection, i64) {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO strategies (name, webhook_id, exchange, symbol, product, quantity, enabled)
+             VALUES ('multi', 'wh-1', 'NSE', '', 'MIS', 1, 1)",
+            [],
+        )
+        .unwrap();
+
+        let id = conn.last_insert_rowid();
+        (conn, id)
+    }
+
+    #[test]
+    fn test_mapping_resolution_is_exact() {
+        let (conn, id) = create_test_db();
+        add_symbol_mapping(&conn, id, "NSE", "SBIN", 10, "MIS").unwrap();
+        add_symbol_mapping(&conn, id, "NFO", "NIFTY24DECFUT", 25, "NRML").unwrap();
+
+        let mapping = get_symbol_mapping(&conn, id, "NIFTY24DECFUT").unwrap().unwrap();
+        assert_eq!(mapping.exchange, "NFO");
+        assert_eq!(mapping.quantity, 25);
+        assert_eq!(mapping.product_type, "NRML");
+
+        // No fallback to another symbol
+        assert!(get_symbol_mapping(&conn, id, "INFY").unwrap().is_none());
+        assert!(get_symbol_mapping(&conn, id, "sbin").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_update_strategy_moves_seeded_mapping() {
+        let (mut conn, id) = create_test_db();
+        add_symbol_mapping(&conn, id, "NSE", "TCS", 3, "CNC").unwrap();
+
+        update_strategy(&mut conn, id, None, None, Some("SBIN".to_string()), None, Some(10), None).unwrap();
+        assert_eq!(get_symbol_mapping(&conn, id, "SBIN").unwrap().unwrap().quantity, 10);
+
+        update_strategy(
+            &mut conn,
+            id,
+            None,
+            Some("BSE".to_string()),
+            Some("INFY".to_string()),
+            Some("CNC".to_string()),
+            Some(5),
+            None,
+        )
+        .unwrap();
+
+        // The old symbol is no longer accepted; the new one uses the new settings
+        assert!(get_symbol_mapping(&conn, id, "SBIN").unwrap().is_none());
+        let mapping = get_symbol_mapping(&conn, id, "INFY").unwrap().unwrap();
+        assert_eq!(mapping.exchange, "BSE");
+        assert_eq!(mapping.quantity, 5);
+        assert_eq!(mapping.product_type, "CNC");
+
+        // Other mappings are left alone
+        assert_eq!(get_symbol_mapping(&conn, id, "TCS").unwrap().unwrap().quantity, 3);
+    }
+
+    #[test]
+    fn test_duplicate_mapping_rejected() {
+        let (conn, id) = create_test_db();
+        add_symbol_mapping(&conn, id, "NSE", "SBIN", 10, "MIS").unwrap();
+
+        let err = add_symbol_mapping(&conn, id, "BSE", "SBIN", 5, "CNC").unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let (mut conn, id) = create_test_db();
+        add_symbol_mapping(&conn, id, "NSE", "SBIN", 10, "MIS").unwrap();
+        add_symbol_mapping(&conn, id, "NSE", "TCS", 1, "CNC").unwrap();
+
+        let csv = "exchange,symbol,quantity,product\nNSE,SBIN,20,CNC\nnse,INFY,5,\n";
+        assert_eq!(import_symbol_mappings(&mut conn, id, csv, false).unwrap(), 2);
+
+        let mappings = get_symbol_mappings(&conn, id).unwrap();
+        assert_eq!(mappings.len(), 3);
+        let sbin = mappings.iter().find(|m| m.symbol == "SBIN").unwrap();
+        assert_eq!((sbin.quantity, sbin.product.as_str()), (20, "CNC"));
+        let infy = mappings.iter().find(|m| m.symbol == "INFY").unwrap();
+        assert_eq!((infy.exchange.as_str(), infy.product.as_str()), ("NSE", "MIS"));
+
+        // Replace drops mappings missing from the file
+        import_symbol_mappings(&mut conn, id, "symbol,exchange,quantity\nRELIANCE,NSE,2", true).unwrap();
+        let mappings = get_symbol_mappings(&conn, id).unwrap();
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].symbol, "RELIANCE");
+    }
+
+    #[test]
+    fn test_import_csv_is_all_or_nothing() {
+        let (mut conn, id) = create_test_db();
+
+        let csv = "symbol,exchange,quantity\nSBIN,NSE,10\nINFY,NSE,abc";
+        assert!(import_symbol_mappings(&mut conn, id, csv, false).is_err());
+        assert!(get_symbol_mappings(&conn, id).unwrap().is_empty());
+
+        assert!(import_symbol_mappings(&mut conn, id, "symbol,quantity\nSBIN,1", false).is_err());
+    }
+
+    #[test]
+    fn test_trading_settings_validated() {
+        let (conn, id) = create_test_db();
+
+        let strategy = get_strategy_by_id(&conn, id).unwrap();
+        assert!(!strategy.is_intraday);
+        assert_eq!(strategy.trading_mode, "BOTH");
+
+        let updated = update_strategy_trading(
+            &conn,
+            id,
+            Some(true),
+            Some("long".to_string()),
+            Some("09:20".to_string()),
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(updated.is_intraday);
+        assert_eq!(updated.trading_mode, "LONG");
+        assert_eq!(updated.start_time.as_deref(), Some("09:20"));
+        assert_eq!(updated.squareoff_time.as_deref(), Some("15:25"));
+
+        let handler_view = get_strategy_by_webhook_id(&conn, "wh-1").unwrap().unwrap();
+        assert!(handler_view.is_intraday);
+        assert_eq!(handler_view.trading_mode, "LONG");
+
+        assert!(update_strategy_trading(&conn, id, None, Some("UP".into()), None, None, None).is_err());
+        assert!(update_strategy_trading(&conn, id, None, None, Some("25:00".into()), None, None).is_err());
+        assert!(
+            update_strategy_trading(&conn, id, None, None, None, Some("15:30".into()), None).is_err()
+        );
+    }
+
+    #[test]
+    fn test_webhook_security_and_nonces() {
+        let (conn, id) = create_test_db();
+        assert_eq!(get_strategy_by_id(&conn, id).unwrap().webhook_auth_mode, "none");
+
+        // HMAC needs a secret of its own
+        assert!(update_strategy_security(&conn, id, Some("hmac".into()), None, None, None).is_err());
+        assert!(update_strategy_security(&conn, id, None, None, Some("1.2.3.x".into()), None).is_err());
+
+        let updated = update_strategy_security(
+            &conn,
+            id,
+            Some("HMAC".into()),
+            Some("s3cret".into()),
+            Some("52.89.214.238, 10.0.0.0/8".into()),
+            Some(120),
+        )
+        .unwrap();
+        assert_eq!(updated.webhook_auth_mode, "hmac");
+        assert_eq!(updated.webhook_ip_allowlist.as_deref(), Some("52.89.214.238,10.0.0.0/8"));
+
+        let handler_view = get_strategy_by_webhook_id(&conn, "wh-1").unwrap().unwrap();
+        assert_eq!(handler_view.auth_mode, "hmac");
+        assert_eq!(handler_view.replay_window_secs, 120);
+
+        assert!(claim_webhook_nonce(&conn, "strategy", id, "n-1", 120).unwrap());
+        assert!(!claim_webhook_nonce(&conn, "strategy", id, "n-1", 120).unwrap());
+        assert!(claim_webhook_nonce(&conn, "strategy", id, "n-2", 120).unwrap());
+        // A Chartink strategy with the same id has nonces of its own
+        assert!(claim_webhook_nonce(&conn, "chartink", id, "n-1", 120).unwrap());
+    }
+}
@@ -4,7 +4,8 @@
 //! Database operations are used for persistence only - runtime lookups
 //! should use the cache in AppState.
 
-use crate::error::Result;
+use crate::db::sqlite::models::FreezeQty;
+use crate::error::{AppError, Result};
 use crate::state::SymbolInfo;
 use rusqlite::{params, Connection};
 
@@ -182,3 +183,185 @@ pub fn search_symbols(
         Ok(symbols)
     }
 }
+
+/// Get the exchange freeze quantity for a symbol, if one is configured
+pub fn get_freeze_qty(conn: &Connection, exchange: &str, symbol: &str) -> Result<Option<i32>> {
+    match conn.query_row(
+        "SELECT freeze_qty FROM qty_freeze WHERE exchange = ?1 AND symbol = ?2",
+        params![exchange, symbol],
+        |row| row.get(0),
+    ) {
+        Ok(qty) => Ok(Some(qty)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Freeze limits bundled with the app, loaded on first run
+const BUNDLED_FREEZE_QTY: &str = include_str!("qtyfreeze.csv");
+
+/// Get all configured freeze limits
+pub fn get_freeze_quantities(conn: &Connection) -> Result<Vec<FreezeQty>> {
+    let mut stmt = conn.prepare(
+        "SELECT exchange, symbol, freeze_qty, updated_at FROM qty_freeze ORDER BY exchange, symbol",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(FreezeQty {
+                exchange: row.get(0)?,
+                symbol: row.get(1)?,
+                freeze_qty: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Bulk-import freeze limits from CSV
+///
+/// Expects a header row with `exchange`, `symbol` and `freeze_qty` columns
+/// (any order). Existing limits are updated; with `replace`, limits not in
+/// the file are removed. The import is all-or-nothing.
+pub fn import_freeze_quantities(conn: &mut Connection, csv: &str, replace: bool) -> Result<usize> {
+    let rows = parse_freeze_csv(csv)?;
+
+    let tx = conn.transaction()?;
+
+    if replace {
+        tx.execute("DELETE FROM qty_freeze", [])?;
+    }
+
+    for (exchange, symbol, freeze_qty) in &rows {
+        tx.execute(
+            "INSERT INTO qty_freeze (exchange, symbol, freeze_qty) VALUES (?1, ?2, ?3)
+             ON CONFLICT(exchange, symbol) DO UPDATE SET
+                 freeze_qty = excluded.freeze_qty, updated_at = datetime('now')",
+            params![exchange, symbol, freeze_qty],
+        )?;
+    }
+
+    tx.commit()?;
+
+    tracing::info!("Imported {} freeze limits", rows.len());
+    Ok(rows.len())
+}
+
+/// Load the bundled freeze limits if none are configured yet
+pub fn seed_freeze_quantities(conn: &mut Connection) -> Result<()> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM qty_freeze", [], |row| row.get(0))?;
+    if count == 0 {
+        import_freeze_quantities(conn, BUNDLED_FREEZE_QTY, false)?;
+    }
+    Ok(())
+}
+
+/// Replace all freeze limits with the bundled defaults
+pub fn reset_freeze_quantities(conn: &mut Connection) -> Result<usize> {
+    import_freeze_quantities(conn, BUNDLED_FREEZE_QTY, true)
+}
+
+/// Parse freeze CSV into (exchange, symbol, freeze_qty) rows
+fn parse_freeze_csv(csv: &str) -> Result<Vec<(String, String, i32)>> {
+    let mut lines = csv
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .enumerate();
+
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| AppError::Validation("CSV is empty".to_string()))?;
+    let header: Vec<String> = header.split(',').map(|h| h.trim().to_lowercase()).collect();
+
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (Some(exchange_col), Some(symbol_col), Some(freeze_col)) =
+        (column("exchange"), column("symbol"), column("freeze_qty"))
+    else {
+        return Err(AppError::Validation(
+            "CSV header must include exchange, symbol and freeze_qty".to_string(),
+        ));
+    };
+
+    let mut rows = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for (index, line) in lines {
+        let line_no = index + 1;
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |col: usize| fields.get(col).copied().unwrap_or("");
+
+        let exchange = field(exchange_col).to_uppercase();
+        let symbol = field(symbol_col).to_uppercase();
+        if exchange.is_empty() || symbol.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Line {}: exchange and symbol are required",
+                line_no
+            )));
+        }
+
+        let freeze_qty: i32 = field(freeze_col)
+            .parse()
+            .ok()
+            .filter(|q| *q > 0)
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Line {}: invalid freeze_qty '{}'",
+                    line_no,
+                    field(freeze_col)
+                ))
+            })?;
+
+        if !seen.insert((exchange.clone(), symbol.clone())) {
+            return Err(AppError::Validation(format!(
+                "Line {}: duplicate symbol {}:{}",
+                line_no, exchange, symbol
+            )));
+        }
+
+        rows.push((exchange, symbol, freeze_qty));
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::migrations::run_migrations;
+
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_seed_and_import_freeze_quantities() {
+        let mut conn = create_test_db();
+        seed_freeze_quantities(&mut conn).unwrap();
+        assert_eq!(get_freeze_qty(&conn, "NFO", "NIFTY").unwrap(), Some(1800));
+
+        let csv = "symbol,exchange,freeze_qty\nnifty,nfo,1500\nRELIANCE,NFO,5000\n";
+        assert_eq!(import_freeze_quantities(&mut conn, csv, false).unwrap(), 2);
+        assert_eq!(get_freeze_qty(&conn, "NFO", "NIFTY").unwrap(), Some(1500));
+        assert_eq!(get_freeze_qty(&conn, "NFO", "RELIANCE").unwrap(), Some(5000));
+
+        // Seeding again keeps the imported limits
+        seed_freeze_quantities(&mut conn).unwrap();
+        assert_eq!(get_freeze_qty(&conn, "NFO", "NIFTY").unwrap(), Some(1500));
+
+        import_freeze_quantities(&mut conn, "exchange,symbol,freeze_qty\nBFO,SENSEX,1000", true).unwrap();
+        assert_eq!(get_freeze_quantities(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_freeze_import_is_all_or_nothing() {
+        let mut conn = create_test_db();
+        let csv = "exchange,symbol,freeze_qty\nNFO,NIFTY,1800\nNFO,BANKNIFTY,0";
+        assert!(import_freeze_quantities(&mut conn, csv, false).is_err());
+        assert!(get_freeze_quantities(&conn).unwrap().is_empty());
+
+        assert!(import_freeze_quantities(&mut conn, "exchange,symbol\nNFO,NIFTY", false).is_err());
+    }
+}
@@ -14,9 +14,12 @@ pub mod error;
 pub mod state;
 pub mod services;
 
-use scheduler::AutoLogoutScheduler;
+use scheduler::{
+    AlertMonitor, AlgoMonitor, AutoLogoutScheduler, BracketMonitor, ExitMonitor, GttMonitor,
+    MtmEngine, OrderUpdateDispatcher, RiskMonitor, SandboxMatcher, StrategySquareoffScheduler,
+    TickRecorder,
+};
 use state::AppState;
-use webhook::WebhookServer;
 use tauri::Manager;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -49,21 +52,48 @@ pub fn run() {
             let scheduler = AutoLogoutScheduler::new(app.handle().clone());
             scheduler.start();
 
+            // Start GTT monitor (fires locally emulated GTT orders on ticks)
+            GttMonitor::new(app.handle().clone()).start();
+
+            // Start bracket monitor (links exit legs and trails stops of emulated BO/CO)
+            BracketMonitor::new(app.handle().clone()).start();
+
+            // Start order update dispatcher (order_update events and outbound webhooks)
+            OrderUpdateDispatcher::new(app.handle().clone()).start();
+
+            // Start strategy square-off scheduler (closes intraday strategy positions)
+            StrategySquareoffScheduler::new(app.handle().clone()).start();
+
+            // Start risk monitor (day MTM, daily loss limit)
+            RiskMonitor::new(app.handle().clone()).start();
+
+            // Start algo monitor (TWAP/VWAP/iceberg execution)
+            AlgoMonitor::new(app.handle().clone()).start();
+
+            // Start MTM engine (tick-driven position P&L, positions_mtm events)
+            MtmEngine::new(app.handle().clone()).start();
+
+            // Start exit monitor (position/portfolio target, stoploss, trailing)
+            ExitMonitor::new(app.handle().clone()).start();
+
+            // Start alert monitor (price/indicator alerts on the tick stream)
+            AlertMonitor::new(app.handle().clone()).start();
+
+            // Start tick recorder (opt-in, ticks and 1m bars to DuckDB)
+            TickRecorder::new(app.handle().clone()).start();
+
+            // Start sandbox matcher (fills analyze-mode LIMIT orders on live ticks)
+            SandboxMatcher::new(app.handle().clone()).start();
+
             // Start webhook server if enabled
             if let Some(config) = webhook_config {
                 if config.enabled {
                     let app_handle = app.handle().clone();
                     tauri::async_runtime::spawn(async move {
-                        let mut server = WebhookServer::new(app_handle.clone());
-                        if let Err(e) = server.start(config).await {
+                        let state = app_handle.state::<AppState>();
+                        if let Err(e) = state.webhook_server.apply_config(config).await {
                             tracing::error!("Failed to start webhook server: {}", e);
                         }
-                        // Keep server running
-                        loop {
-                            tauri::async_runtime::spawn(async {
-                                tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
-                            }).await.ok();
-                        }
                     });
                     tracing::info!("Webhook server starting...");
                 }
@@ -94,14 +124,29 @@ pub fn run() {
             commands::orders::cancel_order,
             commands::orders::get_order_book,
             commands::orders::get_trade_book,
+            commands::orders::get_bracket_orders,
+            commands::orders::cancel_bracket_order,
+            commands::orders::place_algo_order,
+            commands::orders::get_algo_orders,
+            commands::orders::cancel_algo_order,
+            // GTT commands
+            commands::gtt::place_gtt_order,
+            commands::gtt::modify_gtt_order,
+            commands::gtt::cancel_gtt_order,
+            commands::gtt::get_gtt_orders,
             // Position commands
             commands::positions::get_positions,
+            commands::positions::get_positions_mtm,
+            commands::positions::create_exit_rule,
+            commands::positions::get_exit_rules,
+            commands::positions::cancel_exit_rule,
             commands::positions::close_position,
             commands::positions::close_all_positions,
             // Holdings commands
             commands::holdings::get_holdings,
             // Funds commands
             commands::funds::get_funds,
+            commands::funds::calculate_margin,
             // Quote commands
             commands::quotes::get_quote,
             commands::quotes::get_market_depth,
@@ -111,12 +156,38 @@ pub fn run() {
             commands::symbols::get_symbol_by_token,
             commands::symbols::get_symbol_count,
             commands::symbols::refresh_symbol_master,
+            commands::symbols::get_freeze_quantities,
+            commands::symbols::import_freeze_quantities,
+            commands::symbols::reset_freeze_quantities,
             // Strategy commands
             commands::strategy::get_strategies,
             commands::strategy::create_strategy,
             commands::strategy::update_strategy,
+            commands::strategy::update_strategy_trading,
+            commands::strategy::update_strategy_security,
+            commands::strategy::square_off_strategy,
             commands::strategy::delete_strategy,
             commands::strategy::toggle_strategy,
+            commands::strategy::get_strategy_symbols,
+            commands::strategy::add_strategy_symbol,
+            commands::strategy::update_strategy_symbol,
+            commands::strategy::delete_strategy_symbol,
+            commands::strategy::import_strategy_symbols,
+            // Chartink strategy commands
+            commands::chartink::get_chartink_strategies,
+            commands::chartink::create_chartink_strategy,
+            commands::chartink::update_chartink_strategy,
+            commands::chartink::update_chartink_strategy_security,
+            commands::chartink::delete_chartink_strategy,
+            commands::chartink::get_chartink_symbols,
+            commands::chartink::add_chartink_symbol,
+            commands::chartink::update_chartink_symbol,
+            commands::chartink::delete_chartink_symbol,
+            // Risk commands
+            commands::risk::get_risk_config,
+            commands::risk::update_risk_config,
+            commands::risk::get_risk_status,
+            commands::risk::activate_kill_switch,
             // Settings commands
             commands::settings::get_settings,
             commands::settings::update_settings,
@@ -126,8 +197,20 @@ pub fn run() {
             commands::settings::update_auto_logout_config,
             commands::settings::get_webhook_config,
             commands::settings::update_webhook_config,
+            commands::settings::get_webhook_server_status,
+            commands::settings::start_webhook_server,
+            commands::settings::stop_webhook_server,
+            commands::settings::restart_webhook_server,
             commands::settings::get_rate_limit_config,
             commands::settings::update_rate_limit_config,
+            commands::settings::get_order_update_config,
+            commands::settings::update_order_update_config,
+            commands::settings::get_idempotency_config,
+            commands::settings::update_idempotency_config,
+            commands::settings::get_mtm_config,
+            commands::settings::update_mtm_config,
+            commands::settings::get_tick_recorder_config,
+            commands::settings::update_tick_recorder_config,
             commands::settings::get_broker_config,
             commands::settings::get_broker_credentials,
             commands::settings::get_raw_broker_credentials,
@@ -135,6 +218,21 @@ pub fn run() {
             commands::settings::get_broker_credentials_for_edit,
             commands::settings::get_analyze_mode,
             commands::settings::set_analyze_mode,
+            commands::settings::get_order_validation_mode,
+            commands::settings::set_order_validation_mode,
+            // Notification commands
+            commands::notifications::get_notification_targets,
+            commands::notifications::create_notification_target,
+            commands::notifications::update_notification_target,
+            commands::notifications::delete_notification_target,
+            commands::notifications::test_notification_target,
+            commands::notifications::get_notification_log,
+            commands::notifications::clear_old_notification_log,
+            commands::alerts::create_alert,
+            commands::alerts::get_alerts,
+            commands::alerts::cancel_alert,
+            commands::alerts::delete_alert,
+            commands::alerts::get_alert_history,
             // API key commands
             commands::api_keys::create_api_key,
             commands::api_keys::list_api_keys,
@@ -142,6 +240,7 @@ pub fn run() {
             commands::api_keys::delete_api_key_by_id,
             commands::api_keys::get_user_api_key,
             commands::api_keys::regenerate_api_key,
+            commands::api_keys::update_api_key_rate_limits,
             // Sandbox commands
             commands::sandbox::get_sandbox_positions,
             commands::sandbox::get_sandbox_orders,
@@ -175,6 +274,22 @@ pub fn run() {
             // Historify commands
             commands::historify::get_market_data,
             commands::historify::download_historical_data,
+            commands::historify::get_recorder_symbols,
+            commands::historify::add_recorder_symbol,
+            commands::historify::remove_recorder_symbol,
+            // Backtest commands
+            commands::backtest::run_backtest,
+            commands::backtest::get_backtests,
+            commands::backtest::get_backtest,
+            commands::backtest::delete_backtest,
+            // Sandbox replay commands
+            commands::replay::start_replay,
+            commands::replay::pause_replay,
+            commands::replay::resume_replay,
+            commands::replay::seek_replay,
+            commands::replay::set_replay_speed,
+            commands::replay::stop_replay,
+            commands::replay::get_replay_status,
             // WebSocket commands
             commands::websocket::websocket_connect,
             commands::websocket::websocket_disconnect,
@@ -0,0 +1,183 @@
+//! Alert monitor for price and indicator alerts
+//!
+//! Reloads active alerts from SQLite every couple of seconds, subscribes
+//! their symbols on the market data socket and checks every tick against
+//! them. Per-alert tick state (last LTP, volume windows) is kept here; OI
+//! baselines are saved so a restart measures from the same point.
+
+use crate::db::sqlite::PriceAlert;
+use crate::services::{AlertService, AlertTracker};
+use crate::state::AppState;
+use crate::websocket::{SubscriptionMode, SubscriptionRequest};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often active alerts are reloaded and their symbols subscribed
+const REFRESH_INTERVAL_SECS: u64 = 2;
+
+/// Tick-driven monitor for price alerts
+pub struct AlertMonitor {
+    app_handle: AppHandle,
+}
+
+/// Tokens subscribed on the current socket connection
+#[derive(Default)]
+struct Subscriptions {
+    broker: Option<String>,
+    tokens: HashSet<(String, String)>,
+}
+
+impl AlertMonitor {
+    /// Create a new alert monitor
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the alert monitor
+    ///
+    /// Spawns a background task that:
+    /// 1. Reloads active alerts and subscribes their symbols
+    /// 2. Checks each tick against the alerts on its symbol
+    /// 3. Records, notifies and runs the action of alerts that fire
+    /// 4. Emits `alert_triggered` event to frontend
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, alert monitor not started");
+                return;
+            };
+
+            info!("Alert monitor started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut active: Vec<PriceAlert> = Vec::new();
+            let mut trackers: HashMap<String, AlertTracker> = HashMap::new();
+            let mut subscriptions = Subscriptions::default();
+
+            loop {
+                tokio::select! {
+                    _ = refresh.tick() => {
+                        Self::save_references(&state, &active, &trackers);
+                        active = Self::load_active(&state);
+                        trackers.retain(|id, _| active.iter().any(|a| &a.alert_id == id));
+                        for alert in &active {
+                            trackers
+                                .entry(alert.alert_id.clone())
+                                .or_insert_with(|| AlertTracker::new(alert));
+                        }
+                        Self::subscribe_alerts(&state, &active, &mut subscriptions).await;
+                    }
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if active.is_empty() {
+                                    continue;
+                                }
+
+                                let symbol = state
+                                    .get_symbol_by_token(&tick.exchange, &tick.token)
+                                    .map(|s| s.symbol)
+                                    .unwrap_or_else(|| tick.symbol.clone());
+                                let now = Instant::now();
+
+                                let mut fired = Vec::new();
+                                for alert in &active {
+                                    if alert.symbol != symbol || alert.exchange != tick.exchange {
+                                        continue;
+                                    }
+                                    let tracker = trackers
+                                        .entry(alert.alert_id.clone())
+                                        .or_insert_with(|| AlertTracker::new(alert));
+                                    if let Some(observed) = AlertService::check(alert, tracker, &tick, now) {
+                                        fired.push((alert.clone(), observed));
+                                    }
+                                }
+
+                                for (alert, observed) in fired {
+                                    active.retain(|a| a.alert_id != alert.alert_id);
+                                    trackers.remove(&alert.alert_id);
+                                    if let Some(event) = AlertService::trigger(&state, &alert, observed, tick.ltp).await {
+                                        if let Err(e) = self.app_handle.emit("alert_triggered", &event) {
+                                            warn!("Failed to emit alert_triggered: {}", e);
+                                        }
+                                    }
+                                }
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("Alert monitor lagged, skipped {} ticks", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }
+
+            info!("Alert monitor stopped");
+        });
+    }
+
+    /// Subscribe symbols of active alerts not yet subscribed on this connection
+    ///
+    /// Quote mode, so ticks carry volume and OI as well as the LTP.
+    async fn subscribe_alerts(state: &AppState, active: &[PriceAlert], subscriptions: &mut Subscriptions) {
+        let broker = state.websocket.get_broker();
+        if !state.websocket.is_connected() || broker != subscriptions.broker {
+            // A new connection starts without our subscriptions
+            subscriptions.tokens.clear();
+            subscriptions.broker = broker;
+        }
+        if !state.websocket.is_connected() {
+            return;
+        }
+
+        let mut requests = Vec::new();
+        for alert in active {
+            let Some(symbol_info) = state.get_symbol_by_name(&alert.exchange, &alert.symbol) else {
+                continue;
+            };
+            let key = (alert.exchange.clone(), symbol_info.token.clone());
+            if subscriptions.tokens.contains(&key) {
+                continue;
+            }
+            state.websocket.register_symbol(&symbol_info.token, &alert.symbol, &alert.exchange);
+            requests.push(SubscriptionRequest {
+                exchange: alert.exchange.clone(),
+                token: symbol_info.token,
+                mode: SubscriptionMode::Quote,
+            });
+            subscriptions.tokens.insert(key);
+        }
+
+        if requests.is_empty() {
+            return;
+        }
+        info!("Alert monitor subscribing {} alert tokens", requests.len());
+        if let Err(e) = state.websocket.subscribe(requests).await {
+            warn!("Alert monitor failed to subscribe alert tokens: {}", e);
+            subscriptions.tokens.clear();
+        }
+    }
+
+    /// Persist OI baselines first seen since the last refresh
+    fn save_references(state: &AppState, active: &[PriceAlert], trackers: &HashMap<String, AlertTracker>) {
+        for alert in active.iter().filter(|a| a.condition == "oi_change" && a.reference_value.is_none()) {
+            let Some(reference) = trackers.get(&alert.alert_id).and_then(|t| t.reference) else {
+                continue;
+            };
+            if let Err(e) = state.sqlite.set_alert_reference(&alert.alert_id, reference) {
+                warn!("Failed to save OI baseline for alert {}: {}", alert.alert_id, e);
+            }
+        }
+    }
+
+    fn load_active(state: &AppState) -> Vec<PriceAlert> {
+        state.sqlite.get_price_alerts(true).unwrap_or_else(|e| {
+            warn!("Failed to load active alerts: {}", e);
+            Vec::new()
+        })
+    }
+}
@@ -0,0 +1,112 @@
+//! Algo monitor for TWAP/VWAP/iceberg execution algos
+//!
+//! Records market volume from the tick feed for VWAP, and on every refresh
+//! reloads running algos from SQLite, places the slices that are due and
+//! tracks child order fills. Because algos are reloaded each time, they pick
+//! up again after a restart.
+
+use crate::brokers::types::AlgoOrder;
+use crate::services::{AlgoEvent, AlgoService};
+use crate::state::AppState;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often running algos are advanced
+const REFRESH_INTERVAL_SECS: u64 = 2;
+
+/// Monitor for execution algos
+pub struct AlgoMonitor {
+    app_handle: AppHandle,
+}
+
+impl AlgoMonitor {
+    /// Create a new algo monitor
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the algo monitor
+    ///
+    /// Spawns a background task that:
+    /// 1. Records cumulative volume per symbol from ticks (VWAP)
+    /// 2. Places due slices and tracks child fills for running algos
+    /// 3. Emits `algo_update` event to frontend
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, algo monitor not started");
+                return;
+            };
+
+            info!("Algo monitor started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut volumes: HashMap<(String, String), i64> = HashMap::new();
+            let mut subscribed: HashSet<String> = HashSet::new();
+
+            loop {
+                tokio::select! {
+                    _ = refresh.tick() => {
+                        let algos = Self::load_running(&state);
+                        if algos.is_empty() {
+                            continue;
+                        }
+
+                        // Resumed VWAP algos need their volume feed back
+                        for algo in algos.iter().filter(|a| a.algo_type == "VWAP") {
+                            if state.websocket.is_connected() && subscribed.insert(algo.algo_id.clone()) {
+                                AlgoService::subscribe_volume(&state, algo).await;
+                            }
+                        }
+
+                        let events = AlgoService::process(&state, &algos, &volumes).await;
+                        self.emit(&events);
+                    }
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if tick.volume > 0 {
+                                    volumes.insert((tick.exchange, tick.symbol), tick.volume);
+                                }
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("Algo monitor lagged, skipped {} ticks", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }
+
+            info!("Algo monitor stopped");
+        });
+    }
+
+    fn emit(&self, events: &[AlgoEvent]) {
+        for event in events {
+            if let Err(e) = self.app_handle.emit("algo_update", event) {
+                warn!("Failed to emit algo_update: {}", e);
+            }
+        }
+    }
+
+    /// Load running algos for the current mode (live/analyze)
+    ///
+    /// Live algos wait while no broker session is available.
+    fn load_running(state: &AppState) -> Vec<AlgoOrder> {
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        if !analyze_mode && state.get_broker_session().is_none() {
+            return Vec::new();
+        }
+
+        let mode = if analyze_mode { "analyze" } else { "live" };
+        state.sqlite.get_algo_orders(mode, true).unwrap_or_else(|e| {
+            warn!("Failed to load algos: {}", e);
+            Vec::new()
+        })
+    }
+}
@@ -10,6 +10,7 @@
 //! Configuration is stored in SQLite settings table and can be changed via GUI.
 
 use crate::db::sqlite::AutoLogoutConfig;
+use crate::services::{Notification, NotificationService};
 use crate::state::AppState;
 use chrono::{NaiveTime, Timelike, Utc};
 use chrono_tz::Asia::Kolkata;
@@ -247,6 +248,16 @@ impl AutoLogoutScheduler {
             timestamp: Utc::now().to_rfc3339(),
         };
 
+        NotificationService::notify_app(
+            &self.app_handle,
+            Notification::new(
+                "auto_logout",
+                "Broker session logged out",
+                event.reason.clone(),
+                serde_json::Value::Null,
+            ),
+        );
+
         if let Err(e) = self.app_handle.emit("auto_logout", event) {
             warn!("Failed to emit auto_logout event: {}", e);
         }
@@ -0,0 +1,126 @@
+//! Bracket monitor for emulated bracket/cover orders
+//!
+//! Polls the order book while any bracket is waiting on its entry fill, links
+//! the exit GTT once it fills, and closes brackets whose exit GTT has fired.
+//! Trailing stops are moved as ticks arrive. Live brackets are reloaded from
+//! SQLite on every refresh, so they pick up again after a restart.
+
+use crate::brokers::types::BracketOrder;
+use crate::services::{BracketEvent, BracketService};
+use crate::state::AppState;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often pending entries and exit GTTs are checked
+const REFRESH_INTERVAL_SECS: u64 = 3;
+
+/// Monitor for emulated bracket/cover orders
+pub struct BracketMonitor {
+    app_handle: AppHandle,
+}
+
+impl BracketMonitor {
+    /// Create a new bracket monitor
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the bracket monitor
+    ///
+    /// Spawns a background task that:
+    /// 1. Links exit legs for brackets whose entry has filled
+    /// 2. Closes brackets whose exit GTT has fired or been cancelled
+    /// 3. Trails stoplosses on ticks for open brackets
+    /// 4. Emits `bracket_update` event to frontend
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, bracket monitor not started");
+                return;
+            };
+
+            info!("Bracket monitor started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut trailing: Vec<BracketOrder> = Vec::new();
+
+            loop {
+                tokio::select! {
+                    _ = refresh.tick() => {
+                        let brackets = Self::load_live(&state);
+
+                        let (pending, open): (Vec<_>, Vec<_>) =
+                            brackets.into_iter().partition(|b| b.status == "pending");
+
+                        let mut events = Vec::new();
+                        if !pending.is_empty() {
+                            events.extend(BracketService::sync_entries(&state, &pending).await);
+                        }
+                        if !open.is_empty() {
+                            events.extend(BracketService::sync_exits(&state, &open));
+                        }
+                        self.emit(&events);
+
+                        trailing = Self::load_trailing(&state);
+                    }
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if trailing.is_empty() {
+                                    continue;
+                                }
+
+                                let events = BracketService::process_tick(&state, &trailing, &tick);
+                                if events.is_empty() {
+                                    continue;
+                                }
+
+                                self.emit(&events);
+                                trailing = Self::load_trailing(&state);
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("Bracket monitor lagged, skipped {} ticks", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }
+
+            info!("Bracket monitor stopped");
+        });
+    }
+
+    fn emit(&self, events: &[BracketEvent]) {
+        for event in events {
+            if let Err(e) = self.app_handle.emit("bracket_update", event) {
+                warn!("Failed to emit bracket_update: {}", e);
+            }
+        }
+    }
+
+    /// Load pending and open brackets for the current mode (live/analyze)
+    fn load_live(state: &AppState) -> Vec<BracketOrder> {
+        let mode = if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        };
+
+        state.sqlite.get_bracket_orders(mode, true).unwrap_or_else(|e| {
+            warn!("Failed to load brackets: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Load open brackets that have a trailing stoploss
+    fn load_trailing(state: &AppState) -> Vec<BracketOrder> {
+        Self::load_live(state)
+            .into_iter()
+            .filter(|b| b.status == "open" && b.trailing_stoploss.is_some())
+            .collect()
+    }
+}
@@ -0,0 +1,171 @@
+//! Exit monitor for position and portfolio exit rules
+//!
+//! Reloads active rules from SQLite every couple of seconds and checks them
+//! on every tick against the MTM book kept by the MTM engine. A rule that
+//! hits closes its position (or the whole book) at market. Rules whose
+//! position went flat are cancelled on refresh. Trailing best values are
+//! saved on refresh, so a restart resumes the trail where it left off.
+
+use crate::brokers::types::ExitRule;
+use crate::services::{ExitReason, ExitRuleEvent, ExitRuleService};
+use crate::state::AppState;
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often active rules are reloaded and checked at book prices
+const REFRESH_INTERVAL_SECS: u64 = 2;
+
+/// Monitor for position/portfolio exit rules
+pub struct ExitMonitor {
+    app_handle: AppHandle,
+}
+
+impl ExitMonitor {
+    /// Create a new exit monitor
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the exit monitor
+    ///
+    /// Spawns a background task that:
+    /// 1. Reloads active rules and cancels those whose position is flat
+    /// 2. Checks rules on ticks (and on refresh, for quiet symbols)
+    /// 3. Closes positions for rules that hit
+    /// 4. Emits `exit_rule_update` event to frontend
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, exit monitor not started");
+                return;
+            };
+
+            info!("Exit monitor started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut rules: Vec<ExitRule> = Vec::new();
+            // Rules whose trailing best value moved since the last save
+            let mut dirty: HashSet<String> = HashSet::new();
+
+            loop {
+                tokio::select! {
+                    _ = refresh.tick() => {
+                        Self::save_best_values(&state, &rules, &mut dirty);
+                        rules = Self::load_active(&state);
+                        if rules.is_empty() {
+                            continue;
+                        }
+
+                        let events = ExitRuleService::sync_positions(&state, &rules);
+                        if !events.is_empty() {
+                            self.emit(&events);
+                            rules = Self::load_active(&state);
+                        }
+
+                        let hits = Self::evaluate(&state, &mut rules, None, &mut dirty);
+                        self.fire(&state, &mut rules, hits);
+                    }
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if rules.is_empty() {
+                                    continue;
+                                }
+
+                                let hits = Self::evaluate(&state, &mut rules, Some(&tick), &mut dirty);
+                                self.fire(&state, &mut rules, hits);
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("Exit monitor lagged, skipped {} ticks", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }
+
+            info!("Exit monitor stopped");
+        });
+    }
+
+    /// Check rules and note which trailing best values moved
+    fn evaluate(
+        state: &AppState,
+        rules: &mut [ExitRule],
+        tick: Option<&crate::websocket::MarketTick>,
+        dirty: &mut HashSet<String>,
+    ) -> Vec<(ExitRule, ExitReason, f64)> {
+        let before: Vec<Option<f64>> = rules.iter().map(|r| r.best_value).collect();
+        let hits = ExitRuleService::evaluate_rules(state, rules, tick);
+        for (rule, best) in rules.iter().zip(before) {
+            if rule.best_value != best {
+                dirty.insert(rule.rule_id.clone());
+            }
+        }
+        hits
+    }
+
+    /// Trigger rules that hit and drop them from the active set
+    ///
+    /// Rules are claimed here; the closing orders run on their own task so a
+    /// slow broker doesn't hold up the tick stream.
+    fn fire(&self, state: &AppState, rules: &mut Vec<ExitRule>, hits: Vec<(ExitRule, ExitReason, f64)>) {
+        for (rule, reason, value) in hits {
+            rules.retain(|r| r.rule_id != rule.rule_id);
+            let Some(message) = ExitRuleService::claim(state, &rule, reason, value) else {
+                continue;
+            };
+
+            let app_handle = self.app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let Some(state) = app_handle.try_state::<AppState>() else {
+                    return;
+                };
+                let event = ExitRuleService::close(&state, &rule, value, message).await;
+                Self::emit_to(&app_handle, std::slice::from_ref(&event));
+            });
+        }
+    }
+
+    fn emit(&self, events: &[ExitRuleEvent]) {
+        Self::emit_to(&self.app_handle, events);
+    }
+
+    fn emit_to(app_handle: &AppHandle, events: &[ExitRuleEvent]) {
+        for event in events {
+            if let Err(e) = app_handle.emit("exit_rule_update", event) {
+                warn!("Failed to emit exit_rule_update: {}", e);
+            }
+        }
+    }
+
+    /// Persist trailing best values that moved since the last refresh
+    fn save_best_values(state: &AppState, rules: &[ExitRule], dirty: &mut HashSet<String>) {
+        for rule in rules.iter().filter(|r| dirty.contains(&r.rule_id)) {
+            if let Some(best) = rule.best_value {
+                if let Err(e) = state.sqlite.update_exit_rule_best(&rule.rule_id, best) {
+                    warn!("Failed to save best value for exit rule {}: {}", rule.rule_id, e);
+                }
+            }
+        }
+        dirty.clear();
+    }
+
+    /// Load active rules for the current mode (live/analyze)
+    fn load_active(state: &AppState) -> Vec<ExitRule> {
+        let mode = if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        };
+
+        state.sqlite.get_exit_rules(mode, true).unwrap_or_else(|e| {
+            warn!("Failed to load exit rules: {}", e);
+            Vec::new()
+        })
+    }
+}
@@ -0,0 +1,114 @@
+//! GTT monitor for locally emulated GTT orders
+//!
+//! Watches the backend tick stream and fires emulated GTTs (brokers without
+//! native GTT, OCO on Angel, and sandbox) when a tick crosses the trigger.
+//! Active GTTs are cached and reloaded from SQLite every few seconds so new,
+//! modified and cancelled GTTs are picked up without touching the DB per tick.
+
+use crate::brokers::types::GttOrder;
+use crate::services::GttService;
+use crate::state::AppState;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often the active GTT cache is reloaded
+const REFRESH_INTERVAL_SECS: u64 = 2;
+
+/// Tick-driven monitor for emulated GTT orders
+pub struct GttMonitor {
+    app_handle: AppHandle,
+}
+
+impl GttMonitor {
+    /// Create a new GTT monitor
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the GTT monitor
+    ///
+    /// Spawns a background task that:
+    /// 1. Subscribes to the WebSocket manager's tick stream and keeps the
+    ///    symbols of active GTTs subscribed on the market data socket
+    /// 2. Matches each tick against active GTTs for the current mode
+    /// 3. Claims a GTT when it fires and places its leg order via
+    ///    `OrderService` on a separate task
+    /// 4. Emits `gtt_triggered` event to frontend
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, GTT monitor not started");
+                return;
+            };
+
+            info!("GTT monitor started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut active: Vec<GttOrder> = Vec::new();
+
+            loop {
+                tokio::select! {
+                    _ = refresh.tick() => {
+                        active = Self::load_active(&state);
+                        // Covers GTTs reloaded after a restart and socket reconnects
+                        GttService::subscribe_symbols(&state, &active).await;
+                    }
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if active.is_empty() {
+                                    continue;
+                                }
+
+                                let triggered = GttService::process_tick(&state, &active, &tick);
+                                if triggered.is_empty() {
+                                    continue;
+                                }
+
+                                // Orders go out on their own tasks so a slow broker
+                                // doesn't stall the tick stream
+                                let ltp = tick.ltp;
+                                for (gtt, leg_index) in triggered {
+                                    let app_handle = self.app_handle.clone();
+                                    tauri::async_runtime::spawn(async move {
+                                        let Some(state) = app_handle.try_state::<AppState>() else {
+                                            return;
+                                        };
+                                        let event = GttService::fire_gtt(&state, &gtt, leg_index, ltp).await;
+                                        if let Err(e) = app_handle.emit("gtt_triggered", &event) {
+                                            warn!("Failed to emit gtt_triggered: {}", e);
+                                        }
+                                    });
+                                }
+                                active = Self::load_active(&state);
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("GTT monitor lagged, skipped {} ticks", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }
+
+            info!("GTT monitor stopped");
+        });
+    }
+
+    /// Load active emulated GTTs for the current mode (live/analyze)
+    fn load_active(state: &AppState) -> Vec<GttOrder> {
+        let mode = if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        };
+
+        state.sqlite.get_gtt_orders(mode, true).unwrap_or_else(|e| {
+            warn!("Failed to load active GTTs: {}", e);
+            Vec::new()
+        })
+    }
+}
@@ -2,9 +2,42 @@
 //!
 //! Handles scheduled tasks including:
 //! - Auto-logout at 3:00 AM IST (broker compliance)
-//! - Future: Strategy scheduling, market timings
+//! - GTT monitor for locally emulated GTT orders (tick-driven)
+//! - Bracket monitor for emulated bracket/cover orders (fills, trailing stops)
+//! - Order update dispatcher (frontend events, outbound webhooks)
+//! - Strategy square-off at each intraday strategy's square-off time
+//! - Risk monitor (day MTM, loss limit breach, kill switch)
+//! - Algo monitor for TWAP/VWAP/iceberg execution algos
+//! - MTM engine (tick-driven position P&L, sandbox LTP persistence)
+//! - Exit monitor for position/portfolio target, stoploss and trailing exits
+//! - Alert monitor for price and indicator alerts (tick-driven)
+//! - Tick recorder (ticks and live 1m bars to DuckDB)
+//! - Sandbox matcher for pending analyze-mode LIMIT orders (tick-driven)
+//! - Future: Market timings
 
 mod auto_logout;
+mod gtt_monitor;
+mod bracket_monitor;
+mod order_update_dispatcher;
+mod strategy_squareoff;
+mod risk_monitor;
+mod algo_monitor;
+mod mtm_engine;
+mod exit_monitor;
+mod alert_monitor;
+mod tick_recorder;
+mod sandbox_matcher;
 
 pub use auto_logout::AutoLogoutScheduler;
 pub use auto_logout::{AutoLogoutEvent, WarningEvent};
+pub use gtt_monitor::GttMonitor;
+pub use bracket_monitor::BracketMonitor;
+pub use order_update_dispatcher::{OrderUpdateDispatcher, OrderUpdateEvent};
+pub use strategy_squareoff::StrategySquareoffScheduler;
+pub use risk_monitor::{RiskBreachEvent, RiskMonitor};
+pub use algo_monitor::AlgoMonitor;
+pub use mtm_engine::MtmEngine;
+pub use exit_monitor::ExitMonitor;
+pub use alert_monitor::AlertMonitor;
+pub use tick_recorder::TickRecorder;
+pub use sandbox_matcher::SandboxMatcher;
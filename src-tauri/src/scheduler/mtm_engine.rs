@@ -0,0 +1,244 @@
+//! Live MTM engine
+//!
+//! Loads open positions every few seconds, subscribes their tokens on the
+//! market data socket and re-marks them on every tick. Snapshots (per-position
+//! P&L, portfolio MTM, day P&L) go to the frontend as `positions_mtm`, no more
+//! often than the configured event interval, and feed the risk checks. In
+//! analyze mode the sandbox LTP/P&L is persisted every `mtm_update_interval`
+//! seconds from the sandbox config.
+//!
+//! Tokens stay subscribed after a position closes; other consumers (GTTs,
+//! the watchlist) may share them.
+
+use crate::services::PositionService;
+use crate::state::AppState;
+use crate::websocket::{SubscriptionMode, SubscriptionRequest};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{info, warn};
+
+/// How often positions are reloaded and new tokens subscribed
+const REFRESH_INTERVAL_SECS: u64 = 10;
+
+/// Event interval used until settings are loaded
+const DEFAULT_EVENT_INTERVAL_MS: u64 = 500;
+
+/// Sandbox persist interval used until the sandbox config is loaded
+const DEFAULT_PERSIST_INTERVAL_SECS: u64 = 5;
+
+/// Tick-driven MTM engine
+pub struct MtmEngine {
+    app_handle: AppHandle,
+}
+
+/// Tokens subscribed on the current socket connection
+#[derive(Default)]
+struct Subscriptions {
+    broker: Option<String>,
+    tokens: HashSet<(String, String)>,
+}
+
+impl MtmEngine {
+    /// Create a new MTM engine
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the MTM engine
+    ///
+    /// Spawns a background task that:
+    /// 1. Reloads positions and subscribes their tokens
+    /// 2. Re-marks positions on ticks and emits throttled `positions_mtm`
+    /// 3. Persists sandbox LTP/P&L in analyze mode
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, MTM engine not started");
+                return;
+            };
+
+            info!("MTM engine started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut event_interval = Duration::from_millis(DEFAULT_EVENT_INTERVAL_MS);
+            let mut flush = interval(event_interval);
+            let mut persist_interval = Duration::from_secs(DEFAULT_PERSIST_INTERVAL_SECS);
+            let mut persist = interval(persist_interval);
+            let mut subscriptions = Subscriptions::default();
+            let mut last_emit = Instant::now();
+            let mut pending = false;
+
+            loop {
+                tokio::select! {
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if state.mtm.apply_tick(&tick) {
+                                    pending = true;
+                                    if last_emit.elapsed() >= event_interval {
+                                        self.emit(&state);
+                                        last_emit = Instant::now();
+                                        pending = false;
+                                    }
+                                }
+                            }
+                            Err(RecvError::Lagged(_)) => {}
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                    _ = flush.tick() => {
+                        if pending {
+                            self.emit(&state);
+                            last_emit = Instant::now();
+                            pending = false;
+                        }
+                    }
+                    _ = persist.tick() => {
+                        Self::persist(&state);
+                    }
+                    _ = refresh.tick() => {
+                        self.refresh(&state, &mut subscriptions).await;
+                        last_emit = Instant::now();
+                        pending = false;
+
+                        // Pick up interval changes from settings
+                        let configured = Self::event_interval(&state);
+                        if configured != event_interval {
+                            event_interval = configured;
+                            flush = interval(event_interval);
+                        }
+                        let configured = Self::persist_interval(&state);
+                        if configured != persist_interval {
+                            persist_interval = configured;
+                            persist = interval(persist_interval);
+                        }
+                    }
+                }
+            }
+
+            info!("MTM engine stopped");
+        });
+    }
+
+    /// Reload positions, subscribe new tokens and emit a fresh snapshot
+    async fn refresh(&self, state: &AppState, subscriptions: &mut Subscriptions) {
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        if !analyze_mode && state.get_broker_session().is_none() {
+            if state.mtm.is_loaded() {
+                state.mtm.clear();
+            }
+            return;
+        }
+
+        let result = match PositionService::get_positions(state, None).await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("MTM engine failed to load positions: {}", e);
+                return;
+            }
+        };
+        state.mtm.set_positions(&result.positions, &result.mode, |exchange, symbol| {
+            state.risk.ltp(exchange, symbol)
+        });
+
+        self.subscribe_open_positions(state, subscriptions).await;
+        self.emit(state);
+    }
+
+    /// Subscribe tokens of open positions not yet subscribed on this connection
+    async fn subscribe_open_positions(&self, state: &AppState, subscriptions: &mut Subscriptions) {
+        let broker = state.websocket.get_broker();
+        if !state.websocket.is_connected() || broker != subscriptions.broker {
+            // A new connection starts without our subscriptions
+            subscriptions.tokens.clear();
+            subscriptions.broker = broker;
+        }
+        if !state.websocket.is_connected() {
+            return;
+        }
+
+        let mut requests = Vec::new();
+        for (exchange, symbol) in state.mtm.open_symbols() {
+            let Some(symbol_info) = state.get_symbol_by_name(&exchange, &symbol) else {
+                continue;
+            };
+            let key = (exchange.clone(), symbol_info.token.clone());
+            if subscriptions.tokens.contains(&key) {
+                continue;
+            }
+            state.websocket.register_symbol(&symbol_info.token, &symbol, &exchange);
+            requests.push(SubscriptionRequest {
+                exchange,
+                token: symbol_info.token,
+                mode: SubscriptionMode::Quote,
+            });
+            subscriptions.tokens.insert(key);
+        }
+
+        if requests.is_empty() {
+            return;
+        }
+        info!("MTM engine subscribing {} position tokens", requests.len());
+        if let Err(e) = state.websocket.subscribe(requests).await {
+            warn!("MTM engine failed to subscribe position tokens: {}", e);
+            subscriptions.tokens.clear();
+        }
+    }
+
+    /// Emit `positions_mtm` and hand the numbers to the risk checks
+    fn emit(&self, state: &AppState) {
+        if !state.mtm.is_loaded() {
+            return;
+        }
+
+        let snapshot = state.mtm.snapshot();
+        state.risk.set_snapshot(&state.mtm.mode(), snapshot.risk_snapshot());
+
+        if let Err(e) = self.app_handle.emit("positions_mtm", &snapshot) {
+            warn!("Failed to emit positions_mtm: {}", e);
+        }
+    }
+
+    /// Write re-marked sandbox LTPs to SQLite (analyze mode only)
+    fn persist(state: &AppState) {
+        let updates = state.mtm.take_unpersisted();
+        if updates.is_empty() || !state.sqlite.get_analyze_mode().unwrap_or(false) {
+            return;
+        }
+
+        for (exchange, symbol, ltp) in updates {
+            if let Err(e) = state.sqlite.update_sandbox_ltp(&exchange, &symbol, ltp) {
+                warn!("Failed to persist sandbox LTP for {}:{}: {}", exchange, symbol, e);
+            }
+        }
+    }
+
+    fn event_interval(state: &AppState) -> Duration {
+        let ms = state
+            .sqlite
+            .get_mtm_config()
+            .map(|c| c.event_interval_ms as u64)
+            .unwrap_or(DEFAULT_EVENT_INTERVAL_MS);
+        Duration::from_millis(ms.max(1))
+    }
+
+    fn persist_interval(state: &AppState) -> Duration {
+        let secs = state
+            .sqlite
+            .get_sandbox_config()
+            .map(|c| c.mtm_update_interval.max(1) as u64)
+            .unwrap_or(DEFAULT_PERSIST_INTERVAL_SECS);
+        Duration::from_secs(secs)
+    }
+}
+
+/// Interval that skips missed ticks instead of bursting to catch up
+fn interval(period: Duration) -> Interval {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    interval
+}
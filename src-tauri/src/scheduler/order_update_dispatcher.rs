@@ -0,0 +1,228 @@
+//! Order update dispatcher
+//!
+//! Forwards normalized order updates from the broker order feed (or the
+//! sandbox) to the frontend as `order_update` events and, when configured,
+//! POSTs them to an outbound webhook URL. Fills and rejections are also sent
+//! as notifications. Also reconnects the broker order socket if it drops while
+//! market data streaming is active.
+
+use crate::brokers::types::Order;
+use crate::services::{Notification, NotificationService};
+use crate::state::AppState;
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often the broker order socket is checked for reconnect
+const RECONNECT_INTERVAL_SECS: u64 = 30;
+
+/// Timeout for outbound webhook deliveries
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Delivery attempts per order update before it's dropped
+const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+
+/// Backoff before the first retry, doubled on each further retry
+const WEBHOOK_RETRY_BACKOFF_MS: u64 = 1000;
+
+/// Payload POSTed to the order update webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderUpdateEvent {
+    pub event: String,
+    pub mode: String,
+    pub order: Order,
+    pub timestamp: String,
+}
+
+/// Dispatcher for order updates
+pub struct OrderUpdateDispatcher {
+    app_handle: AppHandle,
+}
+
+impl OrderUpdateDispatcher {
+    /// Create a new order update dispatcher
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the order update dispatcher
+    ///
+    /// Spawns a background task that:
+    /// 1. Emits `order_update` event to frontend for every update
+    /// 2. POSTs updates to the configured order update webhook
+    /// 3. Sends `order_filled` / `order_rejected` notifications
+    /// 4. Reconnects the broker order socket while streaming is active
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, order update dispatcher not started");
+                return;
+            };
+
+            info!("Order update dispatcher started");
+
+            let client = Client::builder()
+                .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default();
+
+            let mut updates = state.order_updates.subscribe();
+            let mut reconnect = tokio::time::interval(Duration::from_secs(RECONNECT_INTERVAL_SECS));
+
+            loop {
+                tokio::select! {
+                    update = updates.recv() => {
+                        match update {
+                            Ok(order) => self.dispatch(&state, &client, order),
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("Order update dispatcher lagged, skipped {} updates", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                    _ = reconnect.tick() => {
+                        Self::ensure_connected(&state).await;
+                    }
+                }
+            }
+
+            info!("Order update dispatcher stopped");
+        });
+    }
+
+    fn dispatch(&self, state: &AppState, client: &Client, order: Order) {
+        if let Some(notification) = Self::notification_for(&order) {
+            NotificationService::notify(state, notification);
+        }
+
+        let mode = if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        };
+
+        let event = OrderUpdateEvent {
+            event: "order_update".to_string(),
+            mode: mode.to_string(),
+            order,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+
+        if let Err(e) = self.app_handle.emit("order_update", &event) {
+            warn!("Failed to emit order_update: {}", e);
+        }
+
+        let webhook_url = match state.sqlite.get_order_update_config() {
+            Ok(config) => config.webhook_url,
+            Err(e) => {
+                warn!("Failed to load order update config: {}", e);
+                None
+            }
+        };
+
+        if let Some(url) = webhook_url {
+            let client = client.clone();
+            tauri::async_runtime::spawn(async move {
+                Self::deliver(&client, &url, &event).await;
+            });
+        }
+    }
+
+    /// POST an update to the webhook, retrying server errors with backoff
+    async fn deliver(client: &Client, url: &str, event: &OrderUpdateEvent) {
+        let mut backoff = Duration::from_millis(WEBHOOK_RETRY_BACKOFF_MS);
+
+        for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+            let retryable = match client.post(url).json(event).send().await {
+                Ok(resp) if resp.status().is_success() => return,
+                Ok(resp) => {
+                    let status = resp.status();
+                    warn!(
+                        "Order update webhook returned {} for order {} (attempt {}/{})",
+                        status, event.order.order_id, attempt, WEBHOOK_MAX_ATTEMPTS
+                    );
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => {
+                    warn!(
+                        "Order update webhook failed for order {} (attempt {}/{}): {}",
+                        event.order.order_id, attempt, WEBHOOK_MAX_ATTEMPTS, e
+                    );
+                    true
+                }
+            };
+
+            if !retryable || attempt == WEBHOOK_MAX_ATTEMPTS {
+                break;
+            }
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+
+        warn!(
+            "Dropped order update webhook for order {} ({})",
+            event.order.order_id, event.order.status
+        );
+    }
+
+    /// Build the fill/rejection notification for an order update
+    fn notification_for(order: &Order) -> Option<Notification> {
+        let (event, title) = match order.status.as_str() {
+            "complete" => ("order_filled", "Order filled"),
+            "rejected" => ("order_rejected", "Order rejected"),
+            _ => return None,
+        };
+
+        let message = match order.status.as_str() {
+            "complete" => format!(
+                "{} {} {}:{} @ {}",
+                order.side, order.filled_quantity, order.exchange, order.symbol, order.average_price
+            ),
+            _ => format!(
+                "{} {} {}:{} - {}",
+                order.side,
+                order.quantity,
+                order.exchange,
+                order.symbol,
+                order.rejection_reason.as_deref().unwrap_or("no reason given")
+            ),
+        };
+
+        Some(Notification::new(
+            event,
+            title,
+            message,
+            serde_json::json!({
+                "orderid": order.order_id,
+                "symbol": order.symbol,
+                "exchange": order.exchange,
+                "action": order.side,
+                "quantity": order.quantity,
+                "product": order.product,
+            }),
+        ))
+    }
+
+    /// Reconnect the broker order socket if market data is streaming without it
+    async fn ensure_connected(state: &AppState) {
+        if !state.websocket.is_connected() || state.order_updates.connected_broker().is_some() {
+            return;
+        }
+
+        let Some(session) = state.get_broker_session() else {
+            return;
+        };
+
+        let auth_token = match state.sqlite.get_auth_token(&session.broker_id, &state.security) {
+            Ok(Some((auth_token, _))) => auth_token,
+            _ => return,
+        };
+
+        if let Err(e) = state.order_updates.connect(&session.broker_id, &auth_token).await {
+            warn!("Order update feed reconnect failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Risk monitor
+//!
+//! Keeps the risk tracker's LTPs current from the tick feed and periodically
+//! recomputes day MTM from positions. When the daily loss limit is breached
+//! it emits `risk_breach` (once per day) and, if configured, activates the
+//! kill switch to flatten everything.
+
+use crate::services::{Notification, NotificationService, RiskService};
+use crate::state::AppState;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often day MTM is recomputed from positions
+const REFRESH_INTERVAL_SECS: u64 = 15;
+
+/// Risk breach event payload
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskBreachEvent {
+    pub mtm: f64,
+    pub max_daily_loss: f64,
+    pub flattened: bool,
+    pub timestamp: String,
+}
+
+/// Monitor for the daily loss limit
+pub struct RiskMonitor {
+    app_handle: AppHandle,
+}
+
+impl RiskMonitor {
+    /// Create a new risk monitor
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the risk monitor
+    ///
+    /// Spawns a background task that:
+    /// 1. Records tick LTPs for MTM and order value checks
+    /// 2. Recomputes day MTM and emits `risk_update` to frontend
+    /// 3. Emits `risk_breach` and optionally triggers the kill switch
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, risk monitor not started");
+                return;
+            };
+
+            info!("Risk monitor started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut breached_on: Option<String> = None;
+
+            loop {
+                tokio::select! {
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => state.risk.update_tick(&tick),
+                            Err(RecvError::Lagged(_)) => {}
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                    _ = refresh.tick() => {
+                        self.refresh(&state, &mut breached_on).await;
+                    }
+                }
+            }
+
+            info!("Risk monitor stopped");
+        });
+    }
+
+    async fn refresh(&self, state: &AppState, breached_on: &mut Option<String>) {
+        let config = match state.sqlite.get_risk_config() {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to load risk config: {}", e);
+                return;
+            }
+        };
+
+        if !config.enabled {
+            return;
+        }
+
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        if !analyze_mode && state.get_broker_session().is_none() {
+            return;
+        }
+
+        let mtm = match RiskService::refresh_mtm(state).await {
+            Ok(mtm) => mtm,
+            Err(e) => {
+                warn!("Risk monitor failed to refresh MTM: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = self.app_handle.emit("risk_update", &mtm) {
+            warn!("Failed to emit risk_update: {}", e);
+        }
+
+        if config.max_daily_loss <= 0.0 || mtm.mtm > -config.max_daily_loss {
+            return;
+        }
+
+        let today = chrono::Utc::now()
+            .with_timezone(&chrono_tz::Asia::Kolkata)
+            .format("%Y-%m-%d")
+            .to_string();
+        // A breach is reported once per mode and day
+        let breach_key = format!("{}:{}", if analyze_mode { "analyze" } else { "live" }, today);
+        if breached_on.as_deref() == Some(breach_key.as_str()) {
+            return;
+        }
+        *breached_on = Some(breach_key);
+
+        warn!("Daily loss limit breached: MTM {:.2} / limit {:.2}", mtm.mtm, config.max_daily_loss);
+
+        NotificationService::notify(
+            state,
+            Notification::new(
+                "risk_breach",
+                "Daily loss limit breached",
+                format!("Day MTM {:.2} breached the loss limit of {:.2}", mtm.mtm, config.max_daily_loss),
+                serde_json::json!({
+                    "mtm": mtm.mtm,
+                    "max_daily_loss": config.max_daily_loss,
+                    "flatten": config.flatten_on_breach,
+                }),
+            ),
+        );
+
+        let flattened = config.flatten_on_breach && RiskService::kill_switch_active(state).is_none();
+        if flattened {
+            if let Err(e) = RiskService::activate_kill_switch(state, "Daily loss limit breached", None).await {
+                warn!("Kill switch on loss breach failed: {}", e);
+            }
+        }
+
+        let event = RiskBreachEvent {
+            mtm: mtm.mtm,
+            max_daily_loss: config.max_daily_loss,
+            flattened,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Err(e) = self.app_handle.emit("risk_breach", &event) {
+            warn!("Failed to emit risk_breach: {}", e);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Sandbox matcher for pending analyze-mode LIMIT orders
+//!
+//! Watches the backend tick stream and fills pending sandbox LIMIT orders
+//! once a live tick crosses the limit. Symbols with pending orders are cached
+//! and reloaded from SQLite every few seconds so the DB is only read for
+//! ticks that can fill something. Replay ticks are skipped: the replay clock
+//! matches those itself before publishing them.
+
+use crate::services::AnalyzerService;
+use crate::state::AppState;
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often the pending symbol cache is reloaded
+const REFRESH_INTERVAL_SECS: u64 = 2;
+
+/// Tick-driven matcher for pending sandbox orders
+pub struct SandboxMatcher {
+    app_handle: AppHandle,
+}
+
+impl SandboxMatcher {
+    /// Create a new sandbox matcher
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the sandbox matcher
+    ///
+    /// Spawns a background task that:
+    /// 1. Subscribes to the WebSocket manager's tick stream
+    /// 2. In analyze mode, matches live ticks of symbols with pending LIMIT
+    ///    orders via `AnalyzerService::match_orders`
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, sandbox matcher not started");
+                return;
+            };
+
+            info!("Sandbox matcher started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut refresh = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            let mut pending: HashSet<(String, String)> = HashSet::new();
+
+            loop {
+                tokio::select! {
+                    _ = refresh.tick() => {
+                        pending = Self::load_pending(&state);
+                    }
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if tick.replay || pending.is_empty() {
+                                    continue;
+                                }
+
+                                let symbol = state
+                                    .get_symbol_by_token(&tick.exchange, &tick.token)
+                                    .map(|s| s.symbol)
+                                    .unwrap_or_else(|| tick.symbol.clone());
+                                let key = (tick.exchange.clone(), symbol);
+                                if !pending.contains(&key) {
+                                    continue;
+                                }
+
+                                match AnalyzerService::match_orders(&state, &key.0, &key.1, tick.ltp) {
+                                    Ok(0) => {}
+                                    Ok(_) => pending = Self::load_pending(&state),
+                                    Err(e) => warn!("Failed to match sandbox orders: {}", e),
+                                }
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("Sandbox matcher lagged, skipped {} ticks", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }
+
+            info!("Sandbox matcher stopped");
+        });
+    }
+
+    /// Load symbols with pending sandbox LIMIT orders (none outside analyze mode)
+    fn load_pending(state: &AppState) -> HashSet<(String, String)> {
+        if !state.sqlite.get_analyze_mode().unwrap_or(false) {
+            return HashSet::new();
+        }
+
+        match state.sqlite.get_pending_sandbox_symbols() {
+            Ok(symbols) => symbols.into_iter().collect(),
+            Err(e) => {
+                warn!("Failed to load pending sandbox symbols: {}", e);
+                HashSet::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Strategy square-off scheduler
+//!
+//! At each intraday strategy's `squareoff_time` (IST), cancels the strategy's
+//! open orders and closes the positions it opened via `StrategyService`.
+//! Positions from other strategies or manual trades are left alone. Runs that
+//! report failures are retried on the next check, up to `MAX_RUNS` per day,
+//! and every run emits a `strategy_squareoff` summary event.
+
+use crate::db::sqlite::models::Strategy;
+use crate::services::StrategyService;
+use crate::state::AppState;
+use chrono::{NaiveDate, NaiveTime, Utc};
+use chrono_tz::Asia::Kolkata;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info, warn};
+
+/// How often strategies are checked against their square-off time
+const CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Square-off runs per strategy per day before giving up on failures
+const MAX_RUNS: u32 = 3;
+
+/// Per-strategy progress for the current day
+#[derive(Default)]
+struct RunState {
+    date: Option<NaiveDate>,
+    runs: u32,
+    done: bool,
+}
+
+/// Scheduler that squares off intraday strategies
+pub struct StrategySquareoffScheduler {
+    app_handle: AppHandle,
+}
+
+impl StrategySquareoffScheduler {
+    /// Create a new strategy square-off scheduler
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the strategy square-off scheduler
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, strategy square-off scheduler not started");
+                return;
+            };
+
+            info!("Strategy square-off scheduler started");
+
+            let mut runs: HashMap<i64, RunState> = HashMap::new();
+            let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+
+            loop {
+                interval.tick().await;
+                self.check(&state, &mut runs).await;
+            }
+        });
+    }
+
+    async fn check(&self, state: &AppState, runs: &mut HashMap<i64, RunState>) {
+        let now = Utc::now().with_timezone(&Kolkata);
+        let today = now.date_naive();
+
+        let strategies = match state.sqlite.get_strategies() {
+            Ok(strategies) => strategies,
+            Err(e) => {
+                error!("Failed to load strategies for square-off: {}", e);
+                return;
+            }
+        };
+
+        for strategy in strategies.iter().filter(|s| s.enabled && s.is_intraday) {
+            if !Self::is_due(strategy, now.time()) {
+                continue;
+            }
+
+            let run = runs.entry(strategy.id).or_default();
+            if run.date != Some(today) {
+                *run = RunState { date: Some(today), ..Default::default() };
+            }
+            if run.done {
+                continue;
+            }
+
+            let date = today.format("%Y-%m-%d").to_string();
+            if state.sqlite.is_market_holiday(&strategy.exchange, &date).unwrap_or(false) {
+                run.done = true;
+                continue;
+            }
+
+            // Live square-off needs a broker session; wait for one without using a run
+            let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+            if !analyze_mode && state.get_broker_session().is_none() {
+                continue;
+            }
+
+            run.runs += 1;
+            match StrategyService::square_off(state, strategy).await {
+                Ok(summary) => {
+                    run.done = summary.is_success() || run.runs >= MAX_RUNS;
+                    if !summary.is_success() {
+                        warn!(
+                            "Square-off of {} had failures (run {}/{}): {}",
+                            strategy.name,
+                            run.runs,
+                            MAX_RUNS,
+                            summary.failures.join("; ")
+                        );
+                    }
+                    if let Err(e) = self.app_handle.emit("strategy_squareoff", &summary) {
+                        warn!("Failed to emit strategy_squareoff: {}", e);
+                    }
+                }
+                Err(e) => {
+                    error!("Square-off of {} failed (run {}/{}): {}", strategy.name, run.runs, MAX_RUNS, e);
+                    run.done = run.runs >= MAX_RUNS;
+                }
+            }
+        }
+    }
+
+    /// Whether the strategy's square-off time has passed today
+    fn is_due(strategy: &Strategy, now: NaiveTime) -> bool {
+        strategy
+            .squareoff_time
+            .as_deref()
+            .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok())
+            .map(|squareoff| now >= squareoff)
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,236 @@
+//! Tick recorder writing the tick stream to DuckDB
+//!
+//! When enabled, subscribes the recorder symbols on the market data socket,
+//! buffers their ticks and builds live 1m bars. Every flush interval the
+//! buffered ticks are appended to the DuckDB `ticks` table and changed bars
+//! are upserted into `market_data`. Writes run on the blocking pool so a
+//! slow checkpoint doesn't hold up the tick stream.
+
+use crate::db::duckdb::models::{MarketDataRow, TickRow};
+use crate::db::sqlite::models::TickRecorderConfig;
+use crate::services::recorder_service::tick_row;
+use crate::services::{CandleBuilder, TickOutcome, RECORDER_TIMEFRAME};
+use crate::state::AppState;
+use crate::websocket::{SubscriptionMode, SubscriptionRequest};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// How often the config and recorder symbols are reloaded
+const REFRESH_INTERVAL_SECS: u64 = 5;
+
+/// Flush early once this many ticks are buffered
+const MAX_BUFFERED_TICKS: usize = 20_000;
+
+/// Tick recorder for Historify
+pub struct TickRecorder {
+    app_handle: AppHandle,
+}
+
+/// Tokens subscribed on the current socket connection
+#[derive(Default)]
+struct Subscriptions {
+    broker: Option<String>,
+    tokens: HashSet<(String, String)>,
+}
+
+impl TickRecorder {
+    /// Create a new tick recorder
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// Start the tick recorder
+    ///
+    /// Spawns a background task that:
+    /// 1. Reloads the recorder config and symbols, subscribing new ones
+    /// 2. Buffers ticks of recorded symbols and updates their 1m bars
+    /// 3. Writes ticks and changed bars to DuckDB every flush interval
+    pub fn start(self) {
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = self.app_handle.try_state::<AppState>() else {
+                warn!("AppState not available, tick recorder not started");
+                return;
+            };
+
+            info!("Tick recorder started");
+
+            let mut ticks = state.websocket.subscribe_ticks();
+            let mut timer = tokio::time::interval(Duration::from_secs(1));
+            let mut config = TickRecorderConfig {
+                enabled: false,
+                flush_interval_secs: 5,
+            };
+            let mut symbols: HashSet<(String, String)> = HashSet::new();
+            let mut subscriptions = Subscriptions::default();
+            let mut builder = CandleBuilder::new();
+            let mut buffer: Vec<TickRow> = Vec::new();
+            let mut last_refresh: Option<Instant> = None;
+            let mut last_flush = Instant::now();
+
+            loop {
+                tokio::select! {
+                    _ = timer.tick() => {
+                        if last_refresh.map_or(true, |t| t.elapsed() >= Duration::from_secs(REFRESH_INTERVAL_SECS)) {
+                            last_refresh = Some(Instant::now());
+                            let was_enabled = config.enabled;
+                            config = Self::load_config(&state, &config);
+                            symbols = if config.enabled { Self::load_symbols(&state) } else { HashSet::new() };
+                            builder.retain(&symbols);
+
+                            if config.enabled {
+                                Self::subscribe_symbols(&state, &symbols, &mut subscriptions).await;
+                            } else if was_enabled {
+                                info!("Tick recorder disabled");
+                                Self::flush(&state, &mut buffer, &mut builder).await;
+                                builder.clear();
+                            }
+                        }
+
+                        if last_flush.elapsed() >= Duration::from_secs(config.flush_interval_secs as u64) {
+                            last_flush = Instant::now();
+                            Self::flush(&state, &mut buffer, &mut builder).await;
+                        }
+                    }
+                    tick = ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                // Replayed history is already stored
+                                if symbols.is_empty() || tick.replay {
+                                    continue;
+                                }
+
+                                let symbol = state
+                                    .get_symbol_by_token(&tick.exchange, &tick.token)
+                                    .map(|s| s.symbol)
+                                    .unwrap_or_else(|| tick.symbol.clone());
+                                if !symbols.contains(&(tick.exchange.clone(), symbol.clone())) {
+                                    continue;
+                                }
+
+                                let (ts_ms, row) = tick_row(&tick, &symbol, chrono::Utc::now().timestamp_millis());
+                                match builder.push(&tick.exchange, &symbol, ts_ms, tick.ltp, tick.volume) {
+                                    TickOutcome::Duplicate => continue,
+                                    TickOutcome::Applied | TickOutcome::TooLate => buffer.push(row),
+                                }
+
+                                if buffer.len() >= MAX_BUFFERED_TICKS {
+                                    last_flush = Instant::now();
+                                    Self::flush(&state, &mut buffer, &mut builder).await;
+                                }
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                warn!("Tick recorder lagged, skipped {} ticks", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }
+
+            Self::flush(&state, &mut buffer, &mut builder).await;
+            info!("Tick recorder stopped");
+        });
+    }
+
+    /// Write buffered ticks and changed bars to DuckDB
+    async fn flush(state: &AppState, buffer: &mut Vec<TickRow>, builder: &mut CandleBuilder) {
+        let mut rows = std::mem::take(buffer);
+        let bars = builder.drain();
+        if rows.is_empty() && bars.is_empty() {
+            return;
+        }
+
+        // Sorted row groups compress better
+        rows.sort_by(|a, b| {
+            (&a.exchange, &a.symbol, &a.timestamp).cmp(&(&b.exchange, &b.symbol, &b.timestamp))
+        });
+        let mut by_symbol: HashMap<(String, String), Vec<MarketDataRow>> = HashMap::new();
+        for (exchange, symbol, bar) in bars {
+            by_symbol.entry((exchange, symbol)).or_default().push(bar);
+        }
+
+        let duckdb = state.duckdb.clone();
+        let result = tokio::task::spawn_blocking(move || -> crate::error::Result<()> {
+            if !rows.is_empty() {
+                duckdb.insert_ticks(&rows)?;
+            }
+            for ((exchange, symbol), bars) in &by_symbol {
+                duckdb.insert_market_data(symbol, exchange, RECORDER_TIMEFRAME, bars)?;
+            }
+            Ok(())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Tick recorder failed to write to DuckDB: {}", e),
+            Err(e) => warn!("Tick recorder write task failed: {}", e),
+        }
+    }
+
+    /// Subscribe recorder symbols not yet subscribed on this connection
+    ///
+    /// Quote mode, so ticks carry volume, OI and best bid/ask.
+    async fn subscribe_symbols(
+        state: &AppState,
+        symbols: &HashSet<(String, String)>,
+        subscriptions: &mut Subscriptions,
+    ) {
+        let broker = state.websocket.get_broker();
+        if !state.websocket.is_connected() || broker != subscriptions.broker {
+            // A new connection starts without our subscriptions
+            subscriptions.tokens.clear();
+            subscriptions.broker = broker;
+        }
+        if !state.websocket.is_connected() {
+            return;
+        }
+
+        let mut requests = Vec::new();
+        for (exchange, symbol) in symbols {
+            let Some(symbol_info) = state.get_symbol_by_name(exchange, symbol) else {
+                continue;
+            };
+            let key = (exchange.clone(), symbol_info.token.clone());
+            if subscriptions.tokens.contains(&key) {
+                continue;
+            }
+            state.websocket.register_symbol(&symbol_info.token, symbol, exchange);
+            requests.push(SubscriptionRequest {
+                exchange: exchange.clone(),
+                token: symbol_info.token,
+                mode: SubscriptionMode::Quote,
+            });
+            subscriptions.tokens.insert(key);
+        }
+
+        if requests.is_empty() {
+            return;
+        }
+        info!("Tick recorder subscribing {} tokens", requests.len());
+        if let Err(e) = state.websocket.subscribe(requests).await {
+            warn!("Tick recorder failed to subscribe tokens: {}", e);
+            subscriptions.tokens.clear();
+        }
+    }
+
+    fn load_config(state: &AppState, current: &TickRecorderConfig) -> TickRecorderConfig {
+        state.sqlite.get_tick_recorder_config().unwrap_or_else(|e| {
+            warn!("Failed to load tick recorder config: {}", e);
+            current.clone()
+        })
+    }
+
+    fn load_symbols(state: &AppState) -> HashSet<(String, String)> {
+        match state.duckdb.get_recorder_symbols() {
+            Ok(symbols) => symbols.into_iter().map(|s| (s.exchange, s.symbol)).collect(),
+            Err(e) => {
+                warn!("Failed to load recorder symbols: {}", e);
+                HashSet::new()
+            }
+        }
+    }
+}
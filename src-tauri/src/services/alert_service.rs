@@ -0,0 +1,705 @@
+//! Alert Service
+//!
+//! Price and indicator alerts evaluated on the backend tick stream by the
+//! `AlertMonitor`:
+//! - crosses_above / crosses_below: LTP crosses a price level
+//! - percent_change: change from the previous close reaches a signed percent
+//! - volume_spike: volume traded in a window reaches a multiple of the
+//!   average window volume seen so far
+//! - oi_change: open interest moves a signed percent from its baseline
+//!
+//! A triggered alert is written to the alert history, emitted to the
+//! frontend and optionally sent as a notification or turned into an order.
+
+use crate::brokers::types::OrderRequest;
+use crate::db::sqlite::{AlertAction, AlertHistory, PriceAlert};
+use crate::error::{AppError, Result};
+use crate::services::{Notification, NotificationService, OrderService};
+use crate::state::AppState;
+use crate::websocket::MarketTick;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Default volume window for volume_spike alerts
+const DEFAULT_VOLUME_WINDOW_SECS: i64 = 60;
+
+/// Volume windows allowed for volume_spike alerts
+const VOLUME_WINDOW_RANGE: std::ops::RangeInclusive<i64> = 10..=3600;
+
+/// Completed windows needed before a volume baseline is trusted
+const MIN_BASELINE_WINDOWS: u32 = 3;
+
+/// Weight of the latest window in the volume baseline (EMA)
+const BASELINE_WEIGHT: f64 = 0.2;
+
+/// Alert history entries returned by default
+pub const DEFAULT_HISTORY_LIMIT: u32 = 200;
+
+/// Alert condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertCondition {
+    CrossesAbove,
+    CrossesBelow,
+    PercentChange,
+    VolumeSpike,
+    OiChange,
+}
+
+impl AlertCondition {
+    pub fn parse(condition: &str) -> Option<Self> {
+        match condition.trim().to_lowercase().as_str() {
+            "crosses_above" => Some(Self::CrossesAbove),
+            "crosses_below" => Some(Self::CrossesBelow),
+            "percent_change" => Some(Self::PercentChange),
+            "volume_spike" => Some(Self::VolumeSpike),
+            "oi_change" => Some(Self::OiChange),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CrossesAbove => "crosses_above",
+            Self::CrossesBelow => "crosses_below",
+            Self::PercentChange => "percent_change",
+            Self::VolumeSpike => "volume_spike",
+            Self::OiChange => "oi_change",
+        }
+    }
+
+    fn describe(&self, value: f64) -> String {
+        match self {
+            Self::CrossesAbove => format!("crossed above {}", value),
+            Self::CrossesBelow => format!("crossed below {}", value),
+            Self::PercentChange => format!("moved {:+}% from previous close", value),
+            Self::VolumeSpike => format!("volume spiked {}x", value),
+            Self::OiChange => format!("OI moved {:+}%", value),
+        }
+    }
+}
+
+/// Request to create an alert
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRequest {
+    pub symbol: String,
+    pub exchange: String,
+    pub condition: String,
+    /// Price level, signed percent or volume multiple, by condition
+    pub value: f64,
+    /// volume_spike window (default 60s)
+    pub window_secs: Option<i64>,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub notify: bool,
+    pub action: Option<AlertAction>,
+}
+
+/// Event emitted when an alert fires
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertTriggeredEvent {
+    pub alert_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub condition: String,
+    pub value: f64,
+    pub observed: f64,
+    pub ltp: f64,
+    pub note: Option<String>,
+    pub action_status: Option<String>,
+    pub action_result: Option<String>,
+    pub message: String,
+}
+
+/// Tick-to-tick state the monitor keeps for one alert
+#[derive(Debug, Clone, Default)]
+pub struct AlertTracker {
+    /// Last LTP (crossings) or OI baseline, seeded from the stored reference
+    pub reference: Option<f64>,
+    window_started: Option<Instant>,
+    window_volume: i64,
+    baseline: Option<f64>,
+    windows: u32,
+}
+
+impl AlertTracker {
+    pub fn new(alert: &PriceAlert) -> Self {
+        Self {
+            reference: alert.reference_value,
+            ..Default::default()
+        }
+    }
+}
+
+/// Alert service for business logic
+pub struct AlertService;
+
+impl AlertService {
+    /// Validate an alert request
+    pub fn validate_request(request: &AlertRequest) -> Result<AlertCondition> {
+        let condition = AlertCondition::parse(&request.condition).ok_or_else(|| {
+            AppError::Validation(format!(
+                "Unknown condition '{}', expected crosses_above, crosses_below, percent_change, \
+                 volume_spike or oi_change",
+                request.condition
+            ))
+        })?;
+
+        if !request.value.is_finite() {
+            return Err(AppError::Validation("Alert value must be a number".to_string()));
+        }
+        match condition {
+            AlertCondition::CrossesAbove | AlertCondition::CrossesBelow if request.value <= 0.0 => {
+                return Err(AppError::Validation("Price level must be positive".to_string()));
+            }
+            AlertCondition::PercentChange | AlertCondition::OiChange if request.value == 0.0 => {
+                return Err(AppError::Validation(
+                    "Percent change must be non-zero (negative for a fall)".to_string(),
+                ));
+            }
+            AlertCondition::VolumeSpike if request.value <= 1.0 => {
+                return Err(AppError::Validation(
+                    "Volume spike multiple must be greater than 1".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        if let Some(window) = request.window_secs {
+            if condition != AlertCondition::VolumeSpike {
+                return Err(AppError::Validation(
+                    "window_secs only applies to volume_spike alerts".to_string(),
+                ));
+            }
+            if !VOLUME_WINDOW_RANGE.contains(&window) {
+                return Err(AppError::Validation(format!(
+                    "Volume window must be between {} and {} seconds",
+                    VOLUME_WINDOW_RANGE.start(),
+                    VOLUME_WINDOW_RANGE.end()
+                )));
+            }
+        }
+
+        if let Some(action) = &request.action {
+            Self::validate_action(action)?;
+        }
+
+        Ok(condition)
+    }
+
+    /// Create an alert
+    ///
+    /// Crossing alerts remember the current LTP so the first tick can already
+    /// count as a cross; a level the price is already past is rejected.
+    pub fn create(state: &AppState, request: AlertRequest) -> Result<PriceAlert> {
+        info!("AlertService::create - {:?}", request);
+
+        let condition = Self::validate_request(&request)?;
+        let exchange = request.exchange.trim().to_uppercase();
+        let symbol_info = state
+            .get_symbol_by_name(&exchange, request.symbol.trim())
+            .ok_or_else(|| AppError::Validation(format!("Unknown symbol {}:{}", exchange, request.symbol)))?;
+
+        if let Some(strategy_id) = request.action.as_ref().and_then(|a| a.strategy_id) {
+            state.sqlite.get_strategy(strategy_id).map_err(|_| {
+                AppError::Validation(format!("Strategy {} not found", strategy_id))
+            })?;
+        }
+
+        let ltp = state.risk.ltp(&exchange, &symbol_info.symbol);
+        if let Some(ltp) = ltp {
+            let already = match condition {
+                AlertCondition::CrossesAbove => ltp >= request.value,
+                AlertCondition::CrossesBelow => ltp <= request.value,
+                _ => false,
+            };
+            if already {
+                return Err(AppError::Validation(format!(
+                    "{} is already at {}, past the alert level {}",
+                    symbol_info.symbol, ltp, request.value
+                )));
+            }
+        }
+
+        let reference_value = match condition {
+            AlertCondition::CrossesAbove | AlertCondition::CrossesBelow => ltp,
+            _ => None,
+        };
+        let window_secs = (condition == AlertCondition::VolumeSpike)
+            .then(|| request.window_secs.unwrap_or(DEFAULT_VOLUME_WINDOW_SECS));
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let alert = PriceAlert {
+            alert_id: format!("ALERT{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_uppercase()),
+            symbol: symbol_info.symbol,
+            exchange,
+            condition: condition.as_str().to_string(),
+            value: request.value,
+            window_secs,
+            reference_value,
+            note: request.note.filter(|n| !n.trim().is_empty()),
+            notify: request.notify,
+            action: request.action,
+            status: "active".to_string(),
+            triggered_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        state.sqlite.create_price_alert(&alert)?;
+        Ok(alert)
+    }
+
+    /// Get all alerts, newest first
+    pub fn get_alerts(state: &AppState) -> Result<Vec<PriceAlert>> {
+        state.sqlite.get_price_alerts(false)
+    }
+
+    /// Get the most recent alert triggers
+    pub fn get_history(state: &AppState, limit: Option<u32>) -> Result<Vec<AlertHistory>> {
+        state.sqlite.get_alert_history(limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+    }
+
+    /// Cancel an active alert
+    pub fn cancel(state: &AppState, alert_id: &str) -> Result<PriceAlert> {
+        info!("AlertService::cancel - {}", alert_id);
+
+        let alert = state
+            .sqlite
+            .get_price_alert(alert_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Alert {} not found", alert_id)))?;
+
+        if !state.sqlite.finish_price_alert(alert_id, "cancelled")? {
+            return Err(AppError::Validation(format!(
+                "Alert {} is no longer active ({})",
+                alert_id, alert.status
+            )));
+        }
+
+        Ok(PriceAlert {
+            status: "cancelled".to_string(),
+            ..alert
+        })
+    }
+
+    /// Delete an alert; its trigger history is kept
+    pub fn delete(state: &AppState, alert_id: &str) -> Result<()> {
+        info!("AlertService::delete - {}", alert_id);
+
+        if !state.sqlite.delete_price_alert(alert_id)? {
+            return Err(AppError::NotFound(format!("Alert {} not found", alert_id)));
+        }
+        Ok(())
+    }
+
+    /// Check a tick against an alert, returning the observed value if it fires
+    ///
+    /// `tracker` carries the alert's state between ticks and `now` drives the
+    /// volume windows.
+    pub fn check(alert: &PriceAlert, tracker: &mut AlertTracker, tick: &MarketTick, now: Instant) -> Option<f64> {
+        match AlertCondition::parse(&alert.condition)? {
+            AlertCondition::CrossesAbove => {
+                if tick.ltp <= 0.0 {
+                    return None;
+                }
+                let previous = tracker.reference.replace(tick.ltp);
+                previous
+                    .is_some_and(|p| p < alert.value && tick.ltp >= alert.value)
+                    .then_some(tick.ltp)
+            }
+            AlertCondition::CrossesBelow => {
+                if tick.ltp <= 0.0 {
+                    return None;
+                }
+                let previous = tracker.reference.replace(tick.ltp);
+                previous
+                    .is_some_and(|p| p > alert.value && tick.ltp <= alert.value)
+                    .then_some(tick.ltp)
+            }
+            AlertCondition::PercentChange => {
+                let change = if tick.change_percent != 0.0 {
+                    tick.change_percent
+                } else if tick.close > 0.0 && tick.ltp > 0.0 {
+                    (tick.ltp - tick.close) / tick.close * 100.0
+                } else {
+                    return None;
+                };
+                Self::reached(change, alert.value).then_some(change)
+            }
+            AlertCondition::OiChange => {
+                if tick.oi <= 0 {
+                    return None;
+                }
+                let baseline = *tracker.reference.get_or_insert(tick.oi as f64);
+                if baseline <= 0.0 {
+                    return None;
+                }
+                let change = (tick.oi as f64 - baseline) / baseline * 100.0;
+                Self::reached(change, alert.value).then_some(change)
+            }
+            AlertCondition::VolumeSpike => {
+                let window = Duration::from_secs(alert.window_secs.unwrap_or(DEFAULT_VOLUME_WINDOW_SECS).max(1) as u64);
+                Self::check_volume(tracker, tick.volume, alert.value, window, now)
+            }
+        }
+    }
+
+    /// Fire an alert: claim it, record the trigger, notify and run its action
+    ///
+    /// Returns None if the alert was no longer active.
+    pub async fn trigger(state: &AppState, alert: &PriceAlert, observed: f64, ltp: f64) -> Option<AlertTriggeredEvent> {
+        match state.sqlite.finish_price_alert(&alert.alert_id, "triggered") {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => {
+                error!("Failed to claim alert {}: {}", alert.alert_id, e);
+                return None;
+            }
+        }
+
+        let description = AlertCondition::parse(&alert.condition)
+            .map(|c| c.describe(alert.value))
+            .unwrap_or_else(|| alert.condition.clone());
+        let message = format!("{}:{} {} (at {:.2})", alert.exchange, alert.symbol, description, observed);
+        info!("Alert {} triggered: {}", alert.alert_id, message);
+
+        let history_id = state
+            .sqlite
+            .record_alert_trigger(alert, observed, ltp)
+            .map_err(|e| error!("Failed to record alert {} trigger: {}", alert.alert_id, e))
+            .ok();
+
+        let (action_status, action_result) = match &alert.action {
+            Some(action) => {
+                let (status, result) = match Self::execute_action(state, alert, action).await {
+                    Ok(result) => ("placed", result),
+                    Err(e) => {
+                        warn!("Alert {} action failed: {}", alert.alert_id, e);
+                        ("failed", e.to_string())
+                    }
+                };
+                if let Some(id) = history_id {
+                    if let Err(e) = state.sqlite.set_alert_trigger_action(id, status, &result) {
+                        error!("Failed to record alert {} action: {}", alert.alert_id, e);
+                    }
+                }
+                (Some(status.to_string()), Some(result))
+            }
+            None => (None, None),
+        };
+
+        if alert.notify {
+            let title = alert.note.clone().unwrap_or_else(|| format!("Alert: {}", alert.symbol));
+            NotificationService::notify(
+                state,
+                Notification::new(
+                    "alert_triggered",
+                    title,
+                    message.clone(),
+                    serde_json::json!({
+                        "alert_id": alert.alert_id,
+                        "symbol": alert.symbol,
+                        "exchange": alert.exchange,
+                        "condition": alert.condition,
+                        "value": alert.value,
+                        "observed": observed,
+                        "ltp": ltp,
+                        "action_status": action_status,
+                        "action_result": action_result,
+                    }),
+                ),
+            );
+        }
+
+        Some(AlertTriggeredEvent {
+            alert_id: alert.alert_id.clone(),
+            symbol: alert.symbol.clone(),
+            exchange: alert.exchange.clone(),
+            condition: alert.condition.clone(),
+            value: alert.value,
+            observed,
+            ltp,
+            note: alert.note.clone(),
+            action_status,
+            action_result,
+            message,
+        })
+    }
+
+    // ========================================================================
+    // Private Helper Methods
+    // ========================================================================
+
+    fn validate_action(action: &AlertAction) -> Result<()> {
+        if action.action != "BUY" && action.action != "SELL" {
+            return Err(AppError::Validation(format!(
+                "Alert action must be BUY or SELL, got {}",
+                action.action
+            )));
+        }
+        if action.quantity <= 0 {
+            return Err(AppError::Validation("Alert action quantity must be positive".to_string()));
+        }
+        match action.pricetype.as_str() {
+            "MARKET" => {}
+            "LIMIT" if action.price > 0.0 => {}
+            "LIMIT" => {
+                return Err(AppError::Validation("LIMIT alert actions need a price".to_string()));
+            }
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Alert action must be MARKET or LIMIT, got {}",
+                    other
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// A signed percent threshold: positive waits for a rise, negative for a fall
+    fn reached(change: f64, threshold: f64) -> bool {
+        if threshold > 0.0 {
+            change >= threshold
+        } else {
+            change <= threshold
+        }
+    }
+
+    /// Track volume windows and flag a window that outgrows the baseline
+    ///
+    /// Fires as soon as the running window reaches `multiple` times the
+    /// average window volume, without waiting for the window to close.
+    fn check_volume(tracker: &mut AlertTracker, volume: i64, multiple: f64, window: Duration, now: Instant) -> Option<f64> {
+        if volume <= 0 {
+            return None;
+        }
+
+        let Some(started) = tracker.window_started else {
+            tracker.window_started = Some(now);
+            tracker.window_volume = volume;
+            return None;
+        };
+        if volume < tracker.window_volume {
+            // Cumulative volume went back (new session or a different feed)
+            *tracker = AlertTracker {
+                reference: tracker.reference,
+                window_started: Some(now),
+                window_volume: volume,
+                ..Default::default()
+            };
+            return None;
+        }
+
+        let traded = (volume - tracker.window_volume) as f64;
+        if let Some(baseline) = tracker.baseline.filter(|b| *b > 0.0) {
+            if tracker.windows >= MIN_BASELINE_WINDOWS && traded >= multiple * baseline {
+                return Some(traded / baseline);
+            }
+        }
+
+        if now.duration_since(started) >= window {
+            tracker.baseline = Some(match tracker.baseline {
+                Some(baseline) => baseline * (1.0 - BASELINE_WEIGHT) + traded * BASELINE_WEIGHT,
+                None => traded,
+            });
+            tracker.windows += 1;
+            tracker.window_started = Some(now);
+            tracker.window_volume = volume;
+        }
+
+        None
+    }
+
+    /// Place the alert's order, recording it against its strategy if set
+    async fn execute_action(state: &AppState, alert: &PriceAlert, action: &AlertAction) -> Result<String> {
+        let symbol = action.symbol.clone().unwrap_or_else(|| alert.symbol.clone());
+        let exchange = action.exchange.clone().unwrap_or_else(|| alert.exchange.clone());
+
+        let order = OrderRequest {
+            symbol: symbol.clone(),
+            exchange: exchange.clone(),
+            side: action.action.clone(),
+            quantity: action.quantity,
+            price: action.price,
+            order_type: action.pricetype.clone(),
+            product: action.product.clone(),
+            validity: "DAY".to_string(),
+            trigger_price: None,
+            disclosed_quantity: None,
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: Some(alert.alert_id.clone()),
+            broker_symbol: None,
+            symbol_token: None,
+        };
+
+        let result = OrderService::place_order(state, order, None).await?;
+        let placed = result.placed_orders(action.quantity);
+
+        if let Some(strategy_id) = action.strategy_id {
+            for child in &placed {
+                if let Err(e) = state.sqlite.record_strategy_order(
+                    strategy_id,
+                    &child.order_id,
+                    &result.mode,
+                    &exchange,
+                    &symbol,
+                    &action.product,
+                    &action.action,
+                    child.quantity,
+                    "alert",
+                ) {
+                    warn!("Failed to record strategy order {}: {}", child.order_id, e);
+                }
+            }
+        }
+
+        let order_ids: Vec<_> = placed.iter().map(|c| c.order_id.as_str()).collect();
+        Ok(format!(
+            "{} {} {} {} ({})",
+            action.action,
+            action.quantity,
+            exchange,
+            symbol,
+            if order_ids.is_empty() { result.message } else { order_ids.join(", ") }
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(condition: &str, value: f64, reference_value: Option<f64>) -> PriceAlert {
+        PriceAlert {
+            alert_id: "ALERT1".to_string(),
+            symbol: "NIFTY".to_string(),
+            exchange: "NSE_INDEX".to_string(),
+            condition: condition.to_string(),
+            value,
+            window_secs: Some(60),
+            reference_value,
+            note: None,
+            notify: false,
+            action: None,
+            status: "active".to_string(),
+            triggered_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn tick(ltp: f64, volume: i64, oi: i64) -> MarketTick {
+        MarketTick {
+            symbol: "NIFTY".to_string(),
+            exchange: "NSE_INDEX".to_string(),
+            ltp,
+            close: 24800.0,
+            volume,
+            oi,
+            ..Default::default()
+        }
+    }
+
+    fn request(condition: &str, value: f64) -> AlertRequest {
+        AlertRequest {
+            symbol: "NIFTY".to_string(),
+            exchange: "NSE_INDEX".to_string(),
+            condition: condition.to_string(),
+            value,
+            window_secs: None,
+            note: None,
+            notify: false,
+            action: None,
+        }
+    }
+
+    #[test]
+    fn test_crossing() {
+        let now = Instant::now();
+        let a = alert("crosses_above", 25000.0, Some(24950.0));
+        let mut tracker = AlertTracker::new(&a);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(24990.0, 0, 0), now), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(25001.0, 0, 0), now), Some(25001.0));
+        // Staying above is not another cross
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(25010.0, 0, 0), now), None);
+
+        // Without a reference the first tick only seeds it
+        let a = alert("crosses_below", 25000.0, None);
+        let mut tracker = AlertTracker::new(&a);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(24990.0, 0, 0), now), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(25005.0, 0, 0), now), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(24999.0, 0, 0), now), Some(24999.0));
+    }
+
+    #[test]
+    fn test_percent_and_oi_change() {
+        let now = Instant::now();
+        // Falls from the 24800 close
+        let a = alert("percent_change", -1.0, None);
+        let mut tracker = AlertTracker::new(&a);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(24700.0, 0, 0), now), None);
+        assert!(AlertService::check(&a, &mut tracker, &tick(24500.0, 0, 0), now).is_some_and(|c| c <= -1.0));
+
+        // OI baseline is taken from the first tick
+        let a = alert("oi_change", 10.0, None);
+        let mut tracker = AlertTracker::new(&a);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(24800.0, 0, 1000), now), None);
+        assert_eq!(tracker.reference, Some(1000.0));
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(24800.0, 0, 1050), now), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(24800.0, 0, 1250), now), Some(25.0));
+    }
+
+    #[test]
+    fn test_volume_spike() {
+        let a = alert("volume_spike", 3.0, None);
+        let mut tracker = AlertTracker::new(&a);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // Seed, then three quiet windows of 100 build the baseline
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(1.0, 1000, 0), at(0)), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(1.0, 1100, 0), at(60)), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(1.0, 1200, 0), at(120)), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(1.0, 1300, 0), at(180)), None);
+
+        // 250 into the next window is below 3x, 300 fires mid-window
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(1.0, 1550, 0), at(200)), None);
+        assert_eq!(AlertService::check(&a, &mut tracker, &tick(1.0, 1600, 0), at(210)), Some(3.0));
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert_eq!(
+            AlertService::validate_request(&request("crosses_above", 25000.0)).unwrap(),
+            AlertCondition::CrossesAbove
+        );
+        assert!(AlertService::validate_request(&request("percent_change", -2.0)).is_ok());
+        assert!(AlertService::validate_request(&request("percent_change", 0.0)).is_err());
+        assert!(AlertService::validate_request(&request("volume_spike", 1.0)).is_err());
+        assert!(AlertService::validate_request(&request("rsi_above", 70.0)).is_err());
+
+        let mut windowed = request("crosses_above", 25000.0);
+        windowed.window_secs = Some(60);
+        assert!(AlertService::validate_request(&windowed).is_err());
+        let mut windowed = request("volume_spike", 3.0);
+        windowed.window_secs = Some(5);
+        assert!(AlertService::validate_request(&windowed).is_err());
+
+        let mut with_action = request("crosses_above", 25000.0);
+        with_action.action = Some(AlertAction {
+            action: "BUY".to_string(),
+            quantity: 75,
+            symbol: None,
+            exchange: None,
+            product: "MIS".to_string(),
+            pricetype: "LIMIT".to_string(),
+            price: 0.0,
+            strategy_id: None,
+        });
+        assert!(AlertService::validate_request(&with_action).is_err());
+    }
+}
@@ -0,0 +1,756 @@
+//! Algo Order Service
+//!
+//! Execution algos that work a parent quantity as child orders over time:
+//! - TWAP: equal slices at a fixed interval across a time window
+//! - VWAP: trades a share of the market volume seen on the tick feed
+//! - ICEBERG: shows one clip at a time and refreshes it when it fills
+//!
+//! Algos are persisted and driven by the `AlgoMonitor`, so they keep running
+//! after a restart and can be cancelled at any time.
+
+use crate::brokers::types::{AlgoOrder, Order, OrderRequest};
+use crate::error::{AppError, Result};
+use crate::services::{OrderService, OrderbookService, RiskService};
+use crate::state::AppState;
+use crate::websocket::{normalize_status, SubscriptionMode, SubscriptionRequest};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Longest TWAP/VWAP window (one trading session)
+const MAX_DURATION_SECS: i64 = 8 * 60 * 60;
+
+/// Most slices a TWAP may be split into
+const MAX_SLICES: i32 = 500;
+
+/// Execution algo type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoType {
+    Twap,
+    Vwap,
+    Iceberg,
+}
+
+impl AlgoType {
+    pub fn parse(algo_type: &str) -> Option<Self> {
+        match algo_type.trim().to_uppercase().as_str() {
+            "TWAP" => Some(Self::Twap),
+            "VWAP" => Some(Self::Vwap),
+            "ICEBERG" => Some(Self::Iceberg),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Twap => "TWAP",
+            Self::Vwap => "VWAP",
+            Self::Iceberg => "ICEBERG",
+        }
+    }
+}
+
+/// Request to start an execution algo
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlgoOrderRequest {
+    /// TWAP, VWAP or ICEBERG
+    pub algo_type: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub action: String,
+    pub quantity: i32,
+    pub product: String,
+    /// Limit price for every child order (MARKET when omitted)
+    pub price: Option<f64>,
+    /// TWAP/VWAP execution window in seconds
+    pub duration_secs: Option<i64>,
+    /// TWAP: number of slices
+    pub num_slices: Option<i32>,
+    /// VWAP: share of market volume to trade, 0 < rate <= 1
+    pub participation_rate: Option<f64>,
+    /// ICEBERG: quantity shown per clip
+    pub display_quantity: Option<i32>,
+    /// Exchange disclosed quantity for each child order
+    pub disclosed_quantity: Option<i32>,
+}
+
+/// Event emitted when an algo makes progress or finishes
+#[derive(Debug, Clone, Serialize)]
+pub struct AlgoEvent {
+    pub algo_id: String,
+    pub algo_type: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub side: String,
+    pub status: String,
+    pub quantity: i32,
+    pub placed_quantity: i32,
+    pub filled_quantity: i32,
+    pub message: String,
+}
+
+/// What an algo should do on this pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// Place a child order of this quantity
+    Place(i32),
+    /// Nothing due yet
+    Wait,
+}
+
+/// Algo order service for business logic
+pub struct AlgoService;
+
+impl AlgoService {
+    /// Start an execution algo
+    ///
+    /// Checks the parameters and the risk limits for the full quantity, then
+    /// persists the algo for the monitor to work. Routes to sandbox in
+    /// analyze mode.
+    pub async fn submit(state: &AppState, req: AlgoOrderRequest, api_key: Option<&str>) -> Result<AlgoOrder> {
+        info!(
+            "AlgoService::submit - {} {} {} {}:{}",
+            req.algo_type, req.action, req.quantity, req.exchange, req.symbol
+        );
+
+        let mode = Self::current_mode(state);
+        if mode == "live" && state.get_broker_session().is_none() {
+            return Err(AppError::Auth("Broker not connected".to_string()));
+        }
+
+        let lot_size = state
+            .get_symbol_by_name(&req.exchange, &req.symbol)
+            .map(|s| s.lot_size)
+            .unwrap_or(1);
+        let algo_type = Self::validate_request(&req, lot_size).map_err(AppError::Validation)?;
+        let algo_id = format!("ALGO{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_uppercase());
+
+        // Child orders carry the algo id as their broker tag
+        let parent = OrderRequest {
+            symbol: req.symbol.clone(),
+            exchange: req.exchange.clone(),
+            side: req.action.to_uppercase(),
+            quantity: req.quantity,
+            price: req.price.unwrap_or(0.0),
+            order_type: if req.price.is_some() { "LIMIT" } else { "MARKET" }.to_string(),
+            product: req.product.to_uppercase(),
+            validity: "DAY".to_string(),
+            trigger_price: None,
+            disclosed_quantity: None,
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: Some(algo_id.clone()),
+            broker_symbol: None,  // Set by OrderService from symbol cache
+            symbol_token: None,   // Set by OrderService from symbol cache
+        };
+        RiskService::check_order(state, &parent, api_key).await?;
+
+        let now = Utc::now();
+        let ends_at = req.duration_secs.map(|d| (now + Duration::seconds(d)).to_rfc3339());
+
+        let algo = AlgoOrder {
+            algo_id,
+            algo_type: algo_type.as_str().to_string(),
+            symbol: parent.symbol,
+            exchange: parent.exchange,
+            side: parent.side,
+            product: parent.product,
+            quantity: req.quantity,
+            limit_price: req.price,
+            duration_secs: req.duration_secs.filter(|_| algo_type != AlgoType::Iceberg),
+            num_slices: req.num_slices.filter(|_| algo_type == AlgoType::Twap),
+            participation_rate: req.participation_rate.filter(|_| algo_type == AlgoType::Vwap),
+            display_quantity: req.display_quantity.filter(|_| algo_type == AlgoType::Iceberg),
+            disclosed_quantity: req.disclosed_quantity.filter(|d| *d > 0),
+            placed_quantity: 0,
+            filled_quantity: 0,
+            slices_placed: 0,
+            active_order_id: None,
+            baseline_volume: None,
+            market_volume: 0,
+            child_order_ids: Vec::new(),
+            next_slice_at: (algo_type == AlgoType::Twap).then(|| now.to_rfc3339()),
+            ends_at: ends_at.filter(|_| algo_type != AlgoType::Iceberg),
+            status: "running".to_string(),
+            message: None,
+            created_at: now.to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+        };
+
+        state.sqlite.create_algo_order(&algo, mode)?;
+
+        if algo_type == AlgoType::Vwap {
+            Self::subscribe_volume(state, &algo).await;
+        }
+
+        Ok(algo)
+    }
+
+    /// Get execution algos for the current mode (live/analyze)
+    pub fn get_algo_orders(state: &AppState) -> Result<Vec<AlgoOrder>> {
+        state.sqlite.get_algo_orders(Self::current_mode(state), false)
+    }
+
+    /// Get an execution algo by ID
+    pub fn get_algo_order(state: &AppState, algo_id: &str) -> Result<AlgoOrder> {
+        state
+            .sqlite
+            .get_algo_order(algo_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Algo {} not found", algo_id)))
+    }
+
+    /// Cancel a running algo
+    ///
+    /// Stops further slices first, then cancels any child orders still
+    /// working. Filled quantity is left for the user to manage.
+    pub async fn cancel(state: &AppState, algo_id: &str) -> Result<AlgoEvent> {
+        info!("AlgoService::cancel - {}", algo_id);
+
+        let algo = Self::get_algo_order(state, algo_id)?;
+        if !state.sqlite.finish_algo_order(algo_id, "cancelled", Some("Cancelled by user"))? {
+            return Err(AppError::Validation(format!(
+                "Algo {} is no longer running ({})",
+                algo_id, algo.status
+            )));
+        }
+
+        let mut message = "Cancelled by user".to_string();
+
+        if !algo.child_order_ids.is_empty() {
+            let book = OrderbookService::get_orderbook(state, None).await?;
+            let mut failures = Vec::new();
+
+            for order in Self::children(&algo, &book.orders) {
+                let status = normalize_status(&order.status);
+                if status != "open" && status != "trigger pending" {
+                    continue;
+                }
+                if let Err(e) = OrderService::cancel_order(state, &order.order_id, None, None).await {
+                    failures.push(format!("{}: {}", order.order_id, e));
+                }
+            }
+
+            if !failures.is_empty() {
+                message = format!("{}; failed to cancel {}", message, failures.join(", "));
+                warn!("Algo {} cancel: {}", algo_id, message);
+            }
+        }
+
+        Ok(Self::event(&algo, "cancelled", message))
+    }
+
+    /// Advance running algos: track fills, place due slices, finish algos
+    ///
+    /// Called by the algo monitor. `volumes` holds the latest cumulative
+    /// volume per (exchange, symbol) from the tick feed.
+    pub async fn process(
+        state: &AppState,
+        algos: &[AlgoOrder],
+        volumes: &HashMap<(String, String), i64>,
+    ) -> Vec<AlgoEvent> {
+        let book = if algos.iter().any(|a| !a.child_order_ids.is_empty()) {
+            match OrderbookService::get_orderbook(state, None).await {
+                Ok(result) => result.orders,
+                Err(e) => {
+                    warn!("Algo monitor could not fetch order book: {}", e);
+                    return Vec::new();
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let now = Utc::now();
+        let mut events = Vec::new();
+
+        for algo in algos {
+            let volume = volumes
+                .get(&(algo.exchange.clone(), algo.symbol.clone()))
+                .copied();
+            if let Some(event) = Self::advance(state, algo.clone(), &book, volume, now).await {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    /// Subscribe to quote ticks for a VWAP algo's symbol
+    pub async fn subscribe_volume(state: &AppState, algo: &AlgoOrder) {
+        if !state.websocket.is_connected() {
+            warn!("WebSocket not connected, VWAP {} will start once ticks arrive", algo.algo_id);
+            return;
+        }
+
+        let Some(symbol_info) = state.get_symbol_by_name(&algo.exchange, &algo.symbol) else {
+            return;
+        };
+
+        state.websocket.register_symbol(&symbol_info.token, &algo.symbol, &algo.exchange);
+        let request = SubscriptionRequest {
+            exchange: algo.exchange.clone(),
+            token: symbol_info.token,
+            mode: SubscriptionMode::Quote,
+        };
+        if let Err(e) = state.websocket.subscribe(vec![request]).await {
+            warn!("Failed to subscribe {} for VWAP {}: {}", algo.symbol, algo.algo_id, e);
+        }
+    }
+
+    // ========================================================================
+    // Private Helper Methods
+    // ========================================================================
+
+    fn current_mode(state: &AppState) -> &'static str {
+        if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        }
+    }
+
+    /// Check algo parameters, returning the algo type
+    fn validate_request(req: &AlgoOrderRequest, lot_size: i32) -> std::result::Result<AlgoType, String> {
+        let algo_type = AlgoType::parse(&req.algo_type)
+            .ok_or_else(|| format!("Unknown algo type '{}'. Use TWAP, VWAP or ICEBERG", req.algo_type))?;
+
+        let action = req.action.to_uppercase();
+        if action != "BUY" && action != "SELL" {
+            return Err(format!("Invalid action '{}'. Use BUY or SELL", req.action));
+        }
+
+        let lot = lot_size.max(1);
+        if req.quantity <= 0 || req.quantity % lot != 0 {
+            return Err(format!("Quantity must be a positive multiple of the lot size {}", lot));
+        }
+
+        if matches!(req.price, Some(p) if p <= 0.0) {
+            return Err("Limit price must be positive".to_string());
+        }
+
+        if algo_type != AlgoType::Iceberg {
+            match req.duration_secs {
+                Some(d) if d > 0 && d <= MAX_DURATION_SECS => {}
+                _ => {
+                    return Err(format!(
+                        "{} needs duration_secs between 1 and {}",
+                        algo_type.as_str(),
+                        MAX_DURATION_SECS
+                    ))
+                }
+            }
+        }
+
+        match algo_type {
+            AlgoType::Twap => {
+                let max_slices = (req.quantity / lot).min(MAX_SLICES);
+                match req.num_slices {
+                    Some(n) if n >= 1 && n <= max_slices => {}
+                    _ => return Err(format!("TWAP needs num_slices between 1 and {}", max_slices)),
+                }
+            }
+            AlgoType::Vwap => match req.participation_rate {
+                Some(r) if r > 0.0 && r <= 1.0 => {}
+                _ => return Err("VWAP needs participation_rate above 0 and at most 1".to_string()),
+            },
+            AlgoType::Iceberg => match req.display_quantity {
+                Some(d) if d > 0 && d <= req.quantity && d % lot == 0 => {}
+                _ => {
+                    return Err(format!(
+                        "ICEBERG needs display_quantity up to the order quantity, in lots of {}",
+                        lot
+                    ))
+                }
+            },
+        }
+
+        if let Some(disclosed) = req.disclosed_quantity.filter(|d| *d > 0) {
+            let clip = req.display_quantity.unwrap_or(req.quantity);
+            if disclosed > clip {
+                return Err("disclosed_quantity can't exceed the child order quantity".to_string());
+            }
+        }
+
+        Ok(algo_type)
+    }
+
+    /// Run one pass of a single algo
+    async fn advance(
+        state: &AppState,
+        mut algo: AlgoOrder,
+        book: &[Order],
+        volume: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Option<AlgoEvent> {
+        let before = (algo.placed_quantity, algo.filled_quantity, algo.market_volume, algo.baseline_volume);
+        let algo_type = AlgoType::parse(&algo.algo_type)?;
+
+        let children = Self::children(&algo, book);
+        algo.filled_quantity = children.iter().map(|o| o.filled_quantity).sum();
+        let working = children.iter().any(|o| {
+            let status = normalize_status(&o.status);
+            status == "open" || status == "trigger pending"
+        });
+
+        if let Some(volume) = volume {
+            let baseline = *algo.baseline_volume.get_or_insert(volume);
+            algo.market_volume = (volume - baseline).max(0);
+        }
+
+        // An iceberg clip that didn't fill stops the algo
+        if algo_type == AlgoType::Iceberg {
+            if let Some(clip) = algo
+                .active_order_id
+                .as_ref()
+                .and_then(|id| children.iter().find(|o| &o.order_id == id))
+            {
+                match normalize_status(&clip.status).as_str() {
+                    "complete" => algo.active_order_id = None,
+                    status @ ("rejected" | "cancelled") => {
+                        let reason = clip.rejection_reason.clone().unwrap_or_default();
+                        let message = format!("Clip {} {} {}", clip.order_id, status, reason);
+                        return Self::finish(state, &algo, "failed", message.trim_end().to_string());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if algo.placed_quantity >= algo.quantity {
+            if working {
+                return Self::save(state, &algo, before);
+            }
+            let message = format!("Filled {} of {}", algo.filled_quantity, algo.quantity);
+            return Self::finish(state, &algo, "completed", message);
+        }
+
+        let lot_size = state
+            .get_symbol_by_name(&algo.exchange, &algo.symbol)
+            .map(|s| s.lot_size)
+            .unwrap_or(1);
+
+        let Step::Place(quantity) = Self::next_step(&algo, algo_type, lot_size, now) else {
+            return Self::save(state, &algo, before);
+        };
+
+        // Don't place after a cancel that landed while this pass was running
+        match state.sqlite.get_algo_order(&algo.algo_id) {
+            Ok(Some(current)) if current.status == "running" => {}
+            _ => return None,
+        }
+
+        let child = OrderRequest {
+            symbol: algo.symbol.clone(),
+            exchange: algo.exchange.clone(),
+            side: algo.side.clone(),
+            quantity,
+            price: algo.limit_price.unwrap_or(0.0),
+            order_type: if algo.limit_price.is_some() { "LIMIT" } else { "MARKET" }.to_string(),
+            product: algo.product.clone(),
+            validity: "DAY".to_string(),
+            trigger_price: None,
+            disclosed_quantity: algo.disclosed_quantity.filter(|d| *d < quantity),
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: Some(algo.algo_id.clone()),
+            broker_symbol: None,  // Set by OrderService from symbol cache
+            symbol_token: None,   // Set by OrderService from symbol cache
+        };
+
+        let placed = match OrderService::place_order(state, child, None).await {
+            Ok(result) => result.placed_orders(quantity),
+            Err(e) => {
+                error!("Algo {} slice failed: {}", algo.algo_id, e);
+                return Self::finish(state, &algo, "failed", format!("Slice failed: {}", e));
+            }
+        };
+        if placed.is_empty() {
+            return Self::finish(state, &algo, "failed", "Slice placed without an order id".to_string());
+        }
+
+        algo.placed_quantity += placed.iter().map(|c| c.quantity).sum::<i32>();
+        algo.slices_placed += 1;
+        algo.active_order_id = placed.last().map(|c| c.order_id.clone());
+        algo.child_order_ids.extend(placed.into_iter().map(|c| c.order_id));
+
+        if algo_type == AlgoType::Twap {
+            let interval = Self::twap_interval(&algo);
+            let due = algo
+                .next_slice_at
+                .as_deref()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or(now);
+            algo.next_slice_at = Some((due + interval).to_rfc3339());
+        }
+
+        info!(
+            "Algo {} placed slice {} ({} of {})",
+            algo.algo_id, algo.slices_placed, algo.placed_quantity, algo.quantity
+        );
+
+        Self::save(state, &algo, before)
+    }
+
+    /// Decide whether a slice is due and how large it is
+    fn next_step(algo: &AlgoOrder, algo_type: AlgoType, lot_size: i32, now: DateTime<Utc>) -> Step {
+        let lot = lot_size.max(1);
+        let remaining = algo.quantity - algo.placed_quantity;
+        if remaining <= 0 {
+            return Step::Wait;
+        }
+
+        match algo_type {
+            AlgoType::Twap => {
+                let due = algo
+                    .next_slice_at
+                    .as_deref()
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                    .map(|t| t.with_timezone(&Utc));
+                if matches!(due, Some(due) if now < due) {
+                    return Step::Wait;
+                }
+
+                let slices_left = (algo.num_slices.unwrap_or(1) - algo.slices_placed).max(1);
+                let per_slice = (remaining + slices_left - 1) / slices_left;
+                let rounded = (per_slice + lot - 1) / lot * lot;
+                Step::Place(rounded.min(remaining))
+            }
+            AlgoType::Vwap => {
+                if Self::window_ended(algo, now) {
+                    // Finish the order at the end of the window
+                    return Step::Place(remaining);
+                }
+
+                let rate = algo.participation_rate.unwrap_or(0.0);
+                let target = ((algo.market_volume as f64 * rate).floor() as i64).min(algo.quantity as i64) as i32;
+                let due = (target - algo.placed_quantity) / lot * lot;
+                if due >= lot {
+                    Step::Place(due.min(remaining))
+                } else {
+                    Step::Wait
+                }
+            }
+            AlgoType::Iceberg => {
+                if algo.active_order_id.is_some() {
+                    return Step::Wait;
+                }
+                Step::Place(algo.display_quantity.unwrap_or(remaining).min(remaining))
+            }
+        }
+    }
+
+    fn twap_interval(algo: &AlgoOrder) -> Duration {
+        let slices = algo.num_slices.unwrap_or(1).max(1) as i64;
+        Duration::milliseconds(algo.duration_secs.unwrap_or(0) * 1000 / slices)
+    }
+
+    fn window_ended(algo: &AlgoOrder, now: DateTime<Utc>) -> bool {
+        algo.ends_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|end| now >= end.with_timezone(&Utc))
+    }
+
+    /// Child orders of an algo present in the order book
+    fn children<'a>(algo: &AlgoOrder, book: &'a [Order]) -> Vec<&'a Order> {
+        book.iter()
+            .filter(|o| algo.child_order_ids.contains(&o.order_id))
+            .collect()
+    }
+
+    /// Persist progress and report it if anything moved
+    fn save(
+        state: &AppState,
+        algo: &AlgoOrder,
+        before: (i32, i32, i64, Option<i64>),
+    ) -> Option<AlgoEvent> {
+        let after = (algo.placed_quantity, algo.filled_quantity, algo.market_volume, algo.baseline_volume);
+        if after == before {
+            return None;
+        }
+
+        match state.sqlite.update_algo_progress(algo) {
+            Ok(true) => {
+                let message = format!(
+                    "{} placed, {} filled of {}",
+                    algo.placed_quantity, algo.filled_quantity, algo.quantity
+                );
+                Some(Self::event(algo, "running", message))
+            }
+            Ok(false) => None,
+            Err(e) => {
+                error!("Failed to save algo {}: {}", algo.algo_id, e);
+                None
+            }
+        }
+    }
+
+    /// Save final progress and move the algo to a final status
+    fn finish(state: &AppState, algo: &AlgoOrder, status: &str, message: String) -> Option<AlgoEvent> {
+        if let Err(e) = state.sqlite.update_algo_progress(algo) {
+            error!("Failed to save algo {}: {}", algo.algo_id, e);
+        }
+
+        match state.sqlite.finish_algo_order(&algo.algo_id, status, Some(&message)) {
+            Ok(true) => {
+                info!("Algo {} {}: {}", algo.algo_id, status, message);
+                Some(Self::event(algo, status, message))
+            }
+            Ok(false) => None,
+            Err(e) => {
+                error!("Failed to update algo {}: {}", algo.algo_id, e);
+                None
+            }
+        }
+    }
+
+    fn event(algo: &AlgoOrder, status: &str, message: String) -> AlgoEvent {
+        AlgoEvent {
+            algo_id: algo.algo_id.clone(),
+            algo_type: algo.algo_type.clone(),
+            symbol: algo.symbol.clone(),
+            exchange: algo.exchange.clone(),
+            side: algo.side.clone(),
+            status: status.to_string(),
+            quantity: algo.quantity,
+            placed_quantity: algo.placed_quantity,
+            filled_quantity: algo.filled_quantity,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn algo(algo_type: &str, quantity: i32) -> AlgoOrder {
+        AlgoOrder {
+            algo_id: "ALGO1".to_string(),
+            algo_type: algo_type.to_string(),
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            side: "BUY".to_string(),
+            product: "MIS".to_string(),
+            quantity,
+            limit_price: None,
+            duration_secs: Some(600),
+            num_slices: Some(3),
+            participation_rate: Some(0.1),
+            display_quantity: Some(40),
+            disclosed_quantity: None,
+            placed_quantity: 0,
+            filled_quantity: 0,
+            slices_placed: 0,
+            active_order_id: None,
+            baseline_volume: None,
+            market_volume: 0,
+            child_order_ids: Vec::new(),
+            next_slice_at: None,
+            ends_at: None,
+            status: "running".to_string(),
+            message: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn request(algo_type: &str) -> AlgoOrderRequest {
+        AlgoOrderRequest {
+            algo_type: algo_type.to_string(),
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            action: "BUY".to_string(),
+            quantity: 100,
+            product: "MIS".to_string(),
+            price: None,
+            duration_secs: Some(600),
+            num_slices: Some(10),
+            participation_rate: Some(0.1),
+            display_quantity: Some(20),
+            disclosed_quantity: None,
+        }
+    }
+
+    #[test]
+    fn test_twap_slices_spread_remainder() {
+        let now = Utc::now();
+        let mut twap = algo("TWAP", 100);
+        assert_eq!(AlgoService::next_step(&twap, AlgoType::Twap, 1, now), Step::Place(34));
+
+        twap.placed_quantity = 34;
+        twap.slices_placed = 1;
+        twap.next_slice_at = Some((now + Duration::seconds(200)).to_rfc3339());
+        assert_eq!(AlgoService::next_step(&twap, AlgoType::Twap, 1, now), Step::Wait);
+
+        twap.next_slice_at = Some(now.to_rfc3339());
+        assert_eq!(AlgoService::next_step(&twap, AlgoType::Twap, 1, now), Step::Place(33));
+
+        // Slices stay in whole lots
+        let lots = algo("TWAP", 150);
+        assert_eq!(AlgoService::next_step(&lots, AlgoType::Twap, 25, now), Step::Place(50));
+    }
+
+    #[test]
+    fn test_vwap_follows_market_volume() {
+        let now = Utc::now();
+        let mut vwap = algo("VWAP", 100);
+        vwap.ends_at = Some((now + Duration::seconds(60)).to_rfc3339());
+        assert_eq!(AlgoService::next_step(&vwap, AlgoType::Vwap, 1, now), Step::Wait);
+
+        vwap.market_volume = 255;
+        assert_eq!(AlgoService::next_step(&vwap, AlgoType::Vwap, 1, now), Step::Place(25));
+
+        vwap.placed_quantity = 25;
+        assert_eq!(AlgoService::next_step(&vwap, AlgoType::Vwap, 1, now), Step::Wait);
+
+        // Whatever is left goes out when the window ends
+        vwap.ends_at = Some((now - Duration::seconds(1)).to_rfc3339());
+        assert_eq!(AlgoService::next_step(&vwap, AlgoType::Vwap, 1, now), Step::Place(75));
+    }
+
+    #[test]
+    fn test_iceberg_waits_for_clip() {
+        let now = Utc::now();
+        let mut iceberg = algo("ICEBERG", 100);
+        assert_eq!(AlgoService::next_step(&iceberg, AlgoType::Iceberg, 1, now), Step::Place(40));
+
+        iceberg.active_order_id = Some("1".to_string());
+        assert_eq!(AlgoService::next_step(&iceberg, AlgoType::Iceberg, 1, now), Step::Wait);
+
+        iceberg.active_order_id = None;
+        iceberg.placed_quantity = 80;
+        assert_eq!(AlgoService::next_step(&iceberg, AlgoType::Iceberg, 1, now), Step::Place(20));
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert_eq!(AlgoService::validate_request(&request("twap"), 1), Ok(AlgoType::Twap));
+        assert_eq!(AlgoService::validate_request(&request("ICEBERG"), 1), Ok(AlgoType::Iceberg));
+        assert!(AlgoService::validate_request(&request("POV"), 1).is_err());
+
+        // Quantity must be whole lots
+        assert!(AlgoService::validate_request(&request("TWAP"), 30).is_err());
+
+        let mut twap = request("TWAP");
+        twap.num_slices = Some(101);
+        assert!(AlgoService::validate_request(&twap, 1).is_err());
+
+        let mut vwap = request("VWAP");
+        vwap.participation_rate = Some(1.5);
+        assert!(AlgoService::validate_request(&vwap, 1).is_err());
+
+        let mut iceberg = request("ICEBERG");
+        iceberg.duration_secs = None;
+        assert!(AlgoService::validate_request(&iceberg, 1).is_ok());
+        iceberg.disclosed_quantity = Some(30);
+        assert!(AlgoService::validate_request(&iceberg, 1).is_err());
+    }
+}
@@ -3,7 +3,9 @@
 //! Handles analyze mode (sandbox/paper trading) state management.
 //! Called by both Tauri commands and REST API.
 
+use crate::db::sqlite::models::SandboxOrder;
 use crate::error::Result;
+use crate::services::OrderbookService;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -46,4 +48,68 @@ impl AnalyzerService {
         info!("AnalyzerService::reset_sandbox");
         state.sqlite.reset_sandbox()
     }
+
+    /// Fill pending sandbox LIMIT orders the last traded price crossed
+    ///
+    /// Runs for live ticks (`SandboxMatcher`) and replay ticks (replay clock).
+    /// Buys fill once the price trades at or below the limit, sells at or
+    /// above it, both at the last traded price. Other order types stay
+    /// pending. Returns the number of orders filled.
+    pub fn match_orders(state: &AppState, exchange: &str, symbol: &str, ltp: f64) -> Result<usize> {
+        if ltp <= 0.0 {
+            return Ok(0);
+        }
+
+        let mut filled = 0;
+        for order in state.sqlite.get_pending_sandbox_orders(exchange, symbol)? {
+            if !limit_crossed(&order.order_type, &order.side, order.price, ltp) {
+                continue;
+            }
+            if !state.sqlite.fill_sandbox_order(&order, ltp)? {
+                continue;
+            }
+
+            info!("Sandbox order {} filled at {}", order.order_id, ltp);
+            filled += 1;
+            let filled_order = SandboxOrder {
+                status: "complete".to_string(),
+                filled_quantity: Some(order.quantity),
+                average_price: Some(ltp),
+                updated_at: chrono::Utc::now().to_rfc3339(),
+                ..order
+            };
+            state
+                .order_updates
+                .publish(OrderbookService::sandbox_order_to_order(filled_order));
+        }
+
+        Ok(filled)
+    }
+}
+
+/// Whether a pending order fills at `ltp`
+fn limit_crossed(order_type: &str, side: &str, limit: f64, ltp: f64) -> bool {
+    if order_type != "LIMIT" {
+        return false;
+    }
+    match side {
+        "BUY" => ltp <= limit,
+        "SELL" => ltp >= limit,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_crossed() {
+        assert!(limit_crossed("LIMIT", "BUY", 100.0, 99.5));
+        assert!(limit_crossed("LIMIT", "BUY", 100.0, 100.0));
+        assert!(!limit_crossed("LIMIT", "BUY", 100.0, 100.5));
+        assert!(limit_crossed("LIMIT", "SELL", 100.0, 100.5));
+        assert!(!limit_crossed("LIMIT", "SELL", 100.0, 99.5));
+        assert!(!limit_crossed("SL", "BUY", 100.0, 99.0));
+    }
 }
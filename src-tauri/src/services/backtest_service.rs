@@ -0,0 +1,1627 @@
+//! Offline backtesting over Historify data
+//!
+//! Streams stored candles from DuckDB `market_data` (or ticks captured by the
+//! tick recorder) through a strategy and simulates its fills with slippage
+//! and brokerage. Fills are booked through the sandbox order and position
+//! accounting of a scratch in-memory SQLite database, so averaging and
+//! netting behave exactly as in analyze mode. The report (equity curve,
+//! drawdown, trade list, Sharpe, win rate) is stored in DuckDB.
+//!
+//! Strategies are either rules written in a small DSL:
+//!
+//! ```text
+//! entry_long: crosses_above(ema(close, 9), ema(close, 21)) and rsi(close, 14) < 70
+//! exit_long:  crosses_below(ema(close, 9), ema(close, 21))
+//! ```
+//!
+//! or signals imported from CSV, either `timestamp,action,quantity` rows or
+//! a TradingView "List of trades" export.
+//!
+//! A strategy decides at the close of a bar; by default the order fills at
+//! the next bar's open so the backtest can't trade on a price it hasn't seen.
+
+use crate::db::duckdb::models::{BacktestReport, BacktestSummary, BacktestTrade, EquityPoint};
+use crate::db::duckdb::DuckDb;
+use crate::db::sqlite::SqliteDb;
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+/// Timeframe used when the request doesn't name one
+pub const DEFAULT_TIMEFRAME: &str = "1m";
+
+/// Trading days used to annualize the Sharpe ratio
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// Where the backtest reads its prices from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BacktestSource {
+    /// OHLCV candles in `market_data`
+    #[default]
+    Candles,
+    /// Ticks captured by the tick recorder, each treated as a one-price bar
+    Ticks,
+}
+
+/// Price a strategy's order fills at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillPrice {
+    /// Open of the bar after the signal
+    #[default]
+    NextOpen,
+    /// Close of the signal bar
+    Close,
+}
+
+/// Slippage applied against every fill
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum SlippageModel {
+    #[default]
+    None,
+    /// Fixed price points
+    Points { value: f64 },
+    /// Percent of the price
+    Percent { value: f64 },
+}
+
+impl SlippageModel {
+    /// Fill price after slippage; buys fill higher, sells lower
+    fn apply(&self, price: f64, buy: bool) -> f64 {
+        let slip = match self {
+            Self::None => 0.0,
+            Self::Points { value } => *value,
+            Self::Percent { value } => price * value / 100.0,
+        };
+        if buy {
+            price + slip
+        } else {
+            price - slip
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match self {
+            Self::None => true,
+            Self::Points { value } | Self::Percent { value } => *value >= 0.0,
+        }
+    }
+}
+
+/// Brokerage charged on every fill
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum BrokerageModel {
+    #[default]
+    None,
+    /// Flat amount per order
+    Flat { per_order: f64 },
+    /// Percent of turnover, optionally capped per order
+    Percent {
+        value: f64,
+        #[serde(default)]
+        max_per_order: Option<f64>,
+    },
+}
+
+impl BrokerageModel {
+    fn charges(&self, turnover: f64) -> f64 {
+        match self {
+            Self::None => 0.0,
+            Self::Flat { per_order } => *per_order,
+            Self::Percent { value, max_per_order } => {
+                let charges = turnover * value / 100.0;
+                max_per_order.map_or(charges, |max| charges.min(max))
+            }
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match self {
+            Self::None => true,
+            Self::Flat { per_order } => *per_order >= 0.0,
+            Self::Percent { value, max_per_order } => {
+                *value >= 0.0 && max_per_order.map_or(true, |max| max >= 0.0)
+            }
+        }
+    }
+}
+
+/// Strategy to backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StrategySpec {
+    /// Rule DSL conditions, evaluated at every bar close
+    Rules {
+        #[serde(default)]
+        entry_long: Option<String>,
+        #[serde(default)]
+        exit_long: Option<String>,
+        #[serde(default)]
+        entry_short: Option<String>,
+        #[serde(default)]
+        exit_short: Option<String>,
+        quantity: i32,
+    },
+    /// Signals imported from CSV
+    Signals {
+        csv: String,
+        /// Quantity for rows that don't carry one
+        #[serde(default)]
+        quantity: Option<i32>,
+    },
+}
+
+impl StrategySpec {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Rules { .. } => "rules",
+            Self::Signals { .. } => "signals",
+        }
+    }
+}
+
+/// Backtest request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub symbol: String,
+    pub exchange: String,
+    #[serde(default)]
+    pub source: BacktestSource,
+    /// Candle timeframe; ignored for ticks
+    #[serde(default = "default_timeframe")]
+    pub timeframe: String,
+    /// Start of the range, a date or timestamp (IST)
+    pub from_date: String,
+    /// End of the range; a bare date includes that whole day
+    pub to_date: String,
+    pub strategy: StrategySpec,
+    /// Defaults to the sandbox starting capital
+    #[serde(default)]
+    pub initial_capital: Option<f64>,
+    #[serde(default = "default_product")]
+    pub product: String,
+    #[serde(default)]
+    pub fill_price: FillPrice,
+    #[serde(default)]
+    pub slippage: SlippageModel,
+    #[serde(default)]
+    pub brokerage: BrokerageModel,
+}
+
+fn default_timeframe() -> String {
+    DEFAULT_TIMEFRAME.to_string()
+}
+
+fn default_product() -> String {
+    "MIS".to_string()
+}
+
+/// One bar fed to a strategy
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub time: NaiveDateTime,
+    /// Timestamp as stored, used in the report
+    pub timestamp: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+}
+
+/// Strategy driven by the backtest runner
+pub trait Strategy: Send {
+    /// Called once with the whole series before the first bar
+    fn prepare(&mut self, candles: &[Candle]) -> Result<()>;
+
+    /// Position wanted after bar `index` closes, None to keep the current one
+    fn target(&mut self, index: usize, candles: &[Candle], position: i32) -> Option<i32>;
+}
+
+// ============================================================================
+// Rule DSL
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "open" => Some(Self::Open),
+            "high" => Some(Self::High),
+            "low" => Some(Self::Low),
+            "close" => Some(Self::Close),
+            "volume" => Some(Self::Volume),
+            _ => None,
+        }
+    }
+
+    fn value(&self, candle: &Candle) -> f64 {
+        match self {
+            Self::Open => candle.open,
+            Self::High => candle.high,
+            Self::Low => candle.low,
+            Self::Close => candle.close,
+            Self::Volume => candle.volume as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Indicator {
+    Sma,
+    Ema,
+    Rsi,
+    Highest,
+    Lowest,
+    /// Value `n` bars ago
+    Prev,
+}
+
+impl Indicator {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "sma" => Some(Self::Sma),
+            "ema" => Some(Self::Ema),
+            "rsi" => Some(Self::Rsi),
+            "highest" => Some(Self::Highest),
+            "lowest" => Some(Self::Lowest),
+            "prev" => Some(Self::Prev),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, src: &[Option<f64>], n: usize) -> Vec<Option<f64>> {
+        match self {
+            Self::Sma => rolling(src, n, |w| w.iter().sum::<f64>() / n as f64),
+            Self::Highest => rolling(src, n, |w| w.iter().copied().fold(f64::MIN, f64::max)),
+            Self::Lowest => rolling(src, n, |w| w.iter().copied().fold(f64::MAX, f64::min)),
+            Self::Prev => (0..src.len())
+                .map(|i| if i >= n { src[i - n] } else { None })
+                .collect(),
+            Self::Ema => ema(src, n),
+            Self::Rsi => rsi(src, n),
+        }
+    }
+}
+
+/// Apply `f` to every full window of `n` values
+fn rolling(src: &[Option<f64>], n: usize, f: impl Fn(&[f64]) -> f64) -> Vec<Option<f64>> {
+    (0..src.len())
+        .map(|i| {
+            if i + 1 < n {
+                return None;
+            }
+            let window: Option<Vec<f64>> = src[i + 1 - n..=i].iter().copied().collect();
+            window.map(|w| f(&w))
+        })
+        .collect()
+}
+
+/// Exponential moving average seeded with the SMA of its first `n` values
+fn ema(src: &[Option<f64>], n: usize) -> Vec<Option<f64>> {
+    let alpha = 2.0 / (n as f64 + 1.0);
+    let mut prev: Option<f64> = None;
+    let mut seed = Vec::with_capacity(n);
+    src.iter()
+        .map(|value| {
+            let Some(x) = *value else {
+                prev = None;
+                seed.clear();
+                return None;
+            };
+            match prev {
+                Some(p) => prev = Some(p + alpha * (x - p)),
+                None => {
+                    seed.push(x);
+                    if seed.len() == n {
+                        prev = Some(seed.iter().sum::<f64>() / n as f64);
+                    }
+                }
+            }
+            prev
+        })
+        .collect()
+}
+
+/// Wilder's RSI
+fn rsi(src: &[Option<f64>], n: usize) -> Vec<Option<f64>> {
+    let mut last: Option<f64> = None;
+    let (mut avg_gain, mut avg_loss) = (0.0, 0.0);
+    let mut count = 0;
+    src.iter()
+        .map(|value| {
+            let Some(x) = *value else {
+                last = None;
+                count = 0;
+                return None;
+            };
+            let previous = last.replace(x)?;
+            let change = x - previous;
+            let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+            if count < n {
+                avg_gain += gain / n as f64;
+                avg_loss += loss / n as f64;
+                count += 1;
+                if count < n {
+                    return None;
+                }
+            } else {
+                avg_gain = (avg_gain * (n - 1) as f64 + gain) / n as f64;
+                avg_loss = (avg_loss * (n - 1) as f64 + loss) / n as f64;
+            }
+            if avg_loss == 0.0 {
+                Some(100.0)
+            } else {
+                Some(100.0 - 100.0 / (1.0 + avg_gain / avg_loss))
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Field(Field),
+    Call(Indicator, Box<Expr>, usize),
+}
+
+impl Expr {
+    fn series(&self, candles: &[Candle]) -> Vec<Option<f64>> {
+        match self {
+            Self::Number(value) => vec![Some(*value); candles.len()],
+            Self::Field(field) => candles.iter().map(|c| Some(field.value(c))).collect(),
+            Self::Call(indicator, src, n) => indicator.apply(&src.series(candles), *n),
+        }
+    }
+}
+
+/// Value in a condition, with its series filled in by `prepare`
+#[derive(Debug, Clone)]
+struct Operand {
+    expr: Expr,
+    values: Vec<Option<f64>>,
+}
+
+impl Operand {
+    fn at(&self, index: usize) -> Option<f64> {
+        self.values.get(index).copied().flatten()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Gt,
+    Lt,
+    Ge,
+    Le,
+}
+
+/// Parsed rule condition
+#[derive(Debug, Clone)]
+pub struct Condition(Node);
+
+#[derive(Debug, Clone)]
+enum Node {
+    Compare(Operand, CompareOp, Operand),
+    CrossesAbove(Operand, Operand),
+    CrossesBelow(Operand, Operand),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+}
+
+impl Condition {
+    /// Parse a rule such as `close > sma(close, 20) and rsi(close, 14) < 70`
+    pub fn parse(src: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            pos: 0,
+        };
+        let node = parser.parse_or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(AppError::Validation(format!(
+                "Unexpected {:?} in rule '{}'",
+                token, src
+            )));
+        }
+        Ok(Self(node))
+    }
+
+    fn prepare(&mut self, candles: &[Candle]) {
+        self.0.prepare(candles);
+    }
+
+    /// Whether the condition holds at bar `index`
+    pub fn eval(&self, index: usize) -> bool {
+        self.0.eval(index)
+    }
+}
+
+impl Node {
+    fn prepare(&mut self, candles: &[Candle]) {
+        match self {
+            Self::Compare(a, _, b) | Self::CrossesAbove(a, b) | Self::CrossesBelow(a, b) => {
+                a.values = a.expr.series(candles);
+                b.values = b.expr.series(candles);
+            }
+            Self::And(a, b) | Self::Or(a, b) => {
+                a.prepare(candles);
+                b.prepare(candles);
+            }
+            Self::Not(a) => a.prepare(candles),
+        }
+    }
+
+    fn eval(&self, i: usize) -> bool {
+        match self {
+            Self::Compare(a, op, b) => match (a.at(i), b.at(i)) {
+                (Some(a), Some(b)) => match op {
+                    CompareOp::Gt => a > b,
+                    CompareOp::Lt => a < b,
+                    CompareOp::Ge => a >= b,
+                    CompareOp::Le => a <= b,
+                },
+                _ => false,
+            },
+            Self::CrossesAbove(a, b) => i > 0 && match (a.at(i - 1), b.at(i - 1), a.at(i), b.at(i)) {
+                (Some(a0), Some(b0), Some(a1), Some(b1)) => a0 <= b0 && a1 > b1,
+                _ => false,
+            },
+            Self::CrossesBelow(a, b) => i > 0 && match (a.at(i - 1), b.at(i - 1), a.at(i), b.at(i)) {
+                (Some(a0), Some(b0), Some(a1), Some(b1)) => a0 >= b0 && a1 < b1,
+                _ => false,
+            },
+            Self::And(a, b) => a.eval(i) && b.eval(i),
+            Self::Or(a, b) => a.eval(i) || b.eval(i),
+            Self::Not(a) => !a.eval(i),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    LParen,
+    RParen,
+    Comma,
+    Op(CompareOp),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '>' | '<' => {
+                let or_equal = chars.get(i + 1) == Some(&'=');
+                tokens.push(Token::Op(match (c, or_equal) {
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    ('<', false) => CompareOp::Lt,
+                    _ => CompareOp::Le,
+                }));
+                i += if or_equal { 2 } else { 1 };
+            }
+            _ if c.is_ascii_digit() || c == '.' || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse()
+                    .map_err(|_| AppError::Validation(format!("Invalid number '{}' in rule", text)))?;
+                tokens.push(Token::Number(value));
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(Token::Ident(word.to_lowercase()));
+            }
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "Unexpected character '{}' in rule",
+                    c
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| AppError::Validation("Rule ends unexpectedly".to_string()))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(AppError::Validation(format!(
+                "Expected {:?} in rule, found {:?}",
+                expected, token
+            )));
+        }
+        Ok(())
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(word)) if word == keyword)
+    }
+
+    fn parse_or(&mut self) -> Result<Node> {
+        let mut node = self.parse_and()?;
+        while self.at_keyword("or") {
+            self.pos += 1;
+            node = Node::Or(Box::new(node), Box::new(self.parse_and()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_and(&mut self) -> Result<Node> {
+        let mut node = self.parse_not()?;
+        while self.at_keyword("and") {
+            self.pos += 1;
+            node = Node::And(Box::new(node), Box::new(self.parse_not()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_not(&mut self) -> Result<Node> {
+        if self.at_keyword("not") {
+            self.pos += 1;
+            return Ok(Node::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<Node> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let node = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(node);
+        }
+
+        for (keyword, above) in [("crosses_above", true), ("crosses_below", false)] {
+            if self.at_keyword(keyword) {
+                self.pos += 1;
+                self.expect(Token::LParen)?;
+                let a = self.parse_operand()?;
+                self.expect(Token::Comma)?;
+                let b = self.parse_operand()?;
+                self.expect(Token::RParen)?;
+                return Ok(if above {
+                    Node::CrossesAbove(a, b)
+                } else {
+                    Node::CrossesBelow(a, b)
+                });
+            }
+        }
+
+        let a = self.parse_operand()?;
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            token => {
+                return Err(AppError::Validation(format!(
+                    "Expected a comparison in rule, found {:?}",
+                    token
+                )))
+            }
+        };
+        let b = self.parse_operand()?;
+        Ok(Node::Compare(a, op, b))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand> {
+        Ok(Operand {
+            expr: self.parse_expr()?,
+            values: Vec::new(),
+        })
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr> {
+        match self.next()? {
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Ident(name) => {
+                if let Some(field) = Field::parse(&name) {
+                    return Ok(Expr::Field(field));
+                }
+                let indicator = Indicator::parse(&name)
+                    .ok_or_else(|| AppError::Validation(format!("Unknown name '{}' in rule", name)))?;
+                self.expect(Token::LParen)?;
+                let src = self.parse_expr()?;
+                self.expect(Token::Comma)?;
+                let n = match self.next()? {
+                    Token::Number(n) if n >= 1.0 && n.fract() == 0.0 => n as usize,
+                    token => {
+                        return Err(AppError::Validation(format!(
+                            "{}() needs a whole period of at least 1, found {:?}",
+                            name, token
+                        )))
+                    }
+                };
+                self.expect(Token::RParen)?;
+                Ok(Expr::Call(indicator, Box::new(src), n))
+            }
+            token => Err(AppError::Validation(format!(
+                "Expected a value in rule, found {:?}",
+                token
+            ))),
+        }
+    }
+}
+
+/// Strategy built from rule DSL conditions
+pub struct RuleStrategy {
+    entry_long: Option<Condition>,
+    exit_long: Option<Condition>,
+    entry_short: Option<Condition>,
+    exit_short: Option<Condition>,
+    quantity: i32,
+}
+
+impl RuleStrategy {
+    pub fn new(
+        entry_long: Option<&str>,
+        exit_long: Option<&str>,
+        entry_short: Option<&str>,
+        exit_short: Option<&str>,
+        quantity: i32,
+    ) -> Result<Self> {
+        if quantity <= 0 {
+            return Err(AppError::Validation("Quantity must be positive".to_string()));
+        }
+        let parse = |rule: Option<&str>| -> Result<Option<Condition>> {
+            rule.map(str::trim)
+                .filter(|r| !r.is_empty())
+                .map(Condition::parse)
+                .transpose()
+        };
+        let strategy = Self {
+            entry_long: parse(entry_long)?,
+            exit_long: parse(exit_long)?,
+            entry_short: parse(entry_short)?,
+            exit_short: parse(exit_short)?,
+            quantity,
+        };
+        if strategy.entry_long.is_none() && strategy.entry_short.is_none() {
+            return Err(AppError::Validation(
+                "At least one of entry_long or entry_short is required".to_string(),
+            ));
+        }
+        Ok(strategy)
+    }
+}
+
+impl Strategy for RuleStrategy {
+    fn prepare(&mut self, candles: &[Candle]) -> Result<()> {
+        for condition in [
+            &mut self.entry_long,
+            &mut self.exit_long,
+            &mut self.entry_short,
+            &mut self.exit_short,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            condition.prepare(candles);
+        }
+        Ok(())
+    }
+
+    fn target(&mut self, index: usize, _candles: &[Candle], position: i32) -> Option<i32> {
+        let hit = |condition: &Option<Condition>| condition.as_ref().is_some_and(|c| c.eval(index));
+        let quantity = self.quantity;
+
+        if position > 0 {
+            if hit(&self.entry_short) {
+                Some(-quantity)
+            } else if hit(&self.exit_long) {
+                Some(0)
+            } else {
+                None
+            }
+        } else if position < 0 {
+            if hit(&self.entry_long) {
+                Some(quantity)
+            } else if hit(&self.exit_short) {
+                Some(0)
+            } else {
+                None
+            }
+        } else if hit(&self.entry_long) {
+            Some(quantity)
+        } else if hit(&self.entry_short) {
+            Some(-quantity)
+        } else {
+            None
+        }
+    }
+}
+
+// ============================================================================
+// CSV signals
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SignalAction {
+    /// Add to the position
+    Buy(i32),
+    /// Reduce the position
+    Sell(i32),
+    /// Go long this quantity, reversing a short
+    Long(i32),
+    /// Go short this quantity, reversing a long
+    Short(i32),
+    /// Close any position
+    Flat,
+    ExitLong,
+    ExitShort,
+}
+
+impl SignalAction {
+    fn apply(&self, position: i32) -> i32 {
+        match *self {
+            Self::Buy(quantity) => position + quantity,
+            Self::Sell(quantity) => position - quantity,
+            Self::Long(quantity) => quantity,
+            Self::Short(quantity) => -quantity,
+            Self::Flat => 0,
+            Self::ExitLong if position > 0 => 0,
+            Self::ExitShort if position < 0 => 0,
+            Self::ExitLong | Self::ExitShort => position,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Signal {
+    time: NaiveDateTime,
+    action: SignalAction,
+}
+
+/// Strategy replaying imported signals
+///
+/// A signal is taken at the first bar at or after its time.
+pub struct SignalStrategy {
+    signals: Vec<Signal>,
+    next: usize,
+}
+
+impl SignalStrategy {
+    /// Parse signals from CSV
+    ///
+    /// Accepts a TradingView "List of trades" export (`Type`, `Date/Time`,
+    /// `Contracts` columns) or a plain file with `timestamp`, `action`
+    /// (BUY, SELL, LONG, SHORT, EXIT) and optionally `quantity` columns.
+    pub fn from_csv(csv: &str, default_quantity: Option<i32>) -> Result<Self> {
+        let mut lines = csv.lines().map(str::trim).filter(|l| !l.is_empty());
+        let header: Vec<String> = split_csv_line(
+            lines
+                .next()
+                .ok_or_else(|| AppError::Validation("Signal CSV is empty".to_string()))?,
+        )
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+        let column = |names: &[&str]| header.iter().position(|h| names.iter().any(|n| h.starts_with(n)));
+
+        let tradingview = header.iter().any(|h| h == "type") && column(&["date/time", "date and time"]).is_some();
+        let (time_col, action_col) = if tradingview {
+            (column(&["date/time", "date and time"]), header.iter().position(|h| h == "type"))
+        } else {
+            (
+                column(&["timestamp", "datetime", "time", "date"]),
+                column(&["action", "side", "signal"]),
+            )
+        };
+        let (Some(time_col), Some(action_col)) = (time_col, action_col) else {
+            return Err(AppError::Validation(
+                "Signal CSV needs a time column and an action (or TradingView Type) column".to_string(),
+            ));
+        };
+        let quantity_col = column(&["quantity", "qty", "contracts", "size"]);
+
+        let mut signals = Vec::new();
+        for (row, line) in lines.enumerate() {
+            let row = row + 2;
+            let fields = split_csv_line(line);
+            let field = |col: usize| fields.get(col).map(|f| f.trim()).unwrap_or("");
+
+            let time = parse_time(field(time_col))
+                .ok_or_else(|| AppError::Validation(format!("Row {}: invalid time '{}'", row, field(time_col))))?;
+            let quantity = quantity_col
+                .and_then(|col| field(col).replace(',', "").parse::<f64>().ok())
+                .map(|q| q as i32)
+                .filter(|q| *q > 0)
+                .or(default_quantity);
+            let needs_quantity = || {
+                quantity.ok_or_else(|| AppError::Validation(format!("Row {}: quantity missing", row)))
+            };
+
+            let action = field(action_col).to_uppercase();
+            let action = match action.as_str() {
+                "ENTRY LONG" | "LONG" => SignalAction::Long(needs_quantity()?),
+                "ENTRY SHORT" | "SHORT" => SignalAction::Short(needs_quantity()?),
+                "EXIT LONG" => SignalAction::ExitLong,
+                "EXIT SHORT" => SignalAction::ExitShort,
+                "BUY" => SignalAction::Buy(needs_quantity()?),
+                "SELL" => SignalAction::Sell(needs_quantity()?),
+                "EXIT" | "FLAT" | "CLOSE" => SignalAction::Flat,
+                _ => {
+                    return Err(AppError::Validation(format!(
+                        "Row {}: unknown action '{}'",
+                        row,
+                        field(action_col)
+                    )))
+                }
+            };
+            signals.push(Signal { time, action });
+        }
+
+        if signals.is_empty() {
+            return Err(AppError::Validation("Signal CSV has no signals".to_string()));
+        }
+        // TradingView lists trades newest first, exits before entries
+        signals.sort_by_key(|s| s.time);
+
+        Ok(Self { signals, next: 0 })
+    }
+}
+
+impl Strategy for SignalStrategy {
+    fn prepare(&mut self, candles: &[Candle]) -> Result<()> {
+        // Signals before the loaded data can't be traded
+        self.next = candles
+            .first()
+            .map_or(0, |first| self.signals.partition_point(|s| s.time < first.time));
+        Ok(())
+    }
+
+    fn target(&mut self, index: usize, candles: &[Candle], position: i32) -> Option<i32> {
+        let time = candles[index].time;
+        let mut target = position;
+        while let Some(signal) = self.signals.get(self.next).filter(|s| s.time <= time) {
+            target = signal.action.apply(target);
+            self.next += 1;
+        }
+        (target != position).then_some(target)
+    }
+}
+
+/// Split a CSV line, honouring double-quoted fields
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parse a timestamp as written by DuckDB, TradingView or a spreadsheet
+pub(crate) fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    const FORMATS: [&str; 6] = [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%d-%m-%Y %H:%M:%S",
+        "%d-%m-%Y %H:%M",
+    ];
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+// ============================================================================
+// Simulation
+// ============================================================================
+
+/// Position opened in the backtest and not yet closed
+struct OpenTrade {
+    /// 1 long, -1 short
+    direction: i32,
+    quantity: i32,
+    entry_time: String,
+    entry_price: f64,
+    /// Entry charges not yet assigned to a closed trade
+    charges: f64,
+}
+
+/// Fills and cash of one backtest run
+struct Simulation<'a> {
+    request: &'a BacktestRequest,
+    /// Scratch database whose sandbox books the fills
+    ledger: &'a SqliteDb,
+    cash: f64,
+    position: i32,
+    open: Option<OpenTrade>,
+    trades: Vec<BacktestTrade>,
+    charges: f64,
+}
+
+impl Simulation<'_> {
+    /// Trade from the current position to `target` at `price`
+    fn fill(&mut self, target: i32, price: f64, timestamp: &str) -> Result<()> {
+        // Reversals go through flat so the sandbox averages the new leg afresh
+        if self.position != 0 && self.position.signum() * target < 0 {
+            self.execute(-self.position, price, timestamp)?;
+        }
+        let delta = target - self.position;
+        if delta != 0 {
+            self.execute(delta, price, timestamp)?;
+        }
+        Ok(())
+    }
+
+    /// Book one order of `delta` that doesn't cross zero
+    fn execute(&mut self, delta: i32, price: f64, timestamp: &str) -> Result<()> {
+        let buy = delta > 0;
+        let quantity = delta.abs();
+        let fill_price = self.request.slippage.apply(price, buy);
+        let charges = self.request.brokerage.charges(quantity as f64 * fill_price);
+
+        self.ledger.place_sandbox_order(
+            &self.request.symbol,
+            &self.request.exchange,
+            if buy { "BUY" } else { "SELL" },
+            quantity,
+            fill_price,
+            "MARKET",
+            &self.request.product,
+        )?;
+        self.cash -= delta as f64 * fill_price + charges;
+        self.charges += charges;
+
+        let reducing = self.position != 0 && self.position.signum() != delta.signum();
+        self.position += delta;
+
+        if reducing {
+            let Some(open) = self.open.as_mut() else {
+                return Err(AppError::Internal("Backtest closed a position it never opened".to_string()));
+            };
+            let entry_charges = open.charges * quantity as f64 / open.quantity as f64;
+            open.charges -= entry_charges;
+            open.quantity -= quantity;
+
+            let trade_charges = entry_charges + charges;
+            let gross = (fill_price - open.entry_price) * quantity as f64 * open.direction as f64;
+            let trade = BacktestTrade {
+                trade_no: self.trades.len() as i64 + 1,
+                side: if open.direction > 0 { "LONG" } else { "SHORT" }.to_string(),
+                quantity: quantity as i64,
+                entry_time: open.entry_time.clone(),
+                entry_price: open.entry_price,
+                exit_time: timestamp.to_string(),
+                exit_price: fill_price,
+                pnl: gross - trade_charges,
+                charges: trade_charges,
+            };
+            self.trades.push(trade);
+            if open.quantity == 0 {
+                self.open = None;
+            }
+        } else {
+            let average_price = self.average_price()?.unwrap_or(fill_price);
+            match self.open.as_mut() {
+                Some(open) => {
+                    open.quantity += quantity;
+                    open.entry_price = average_price;
+                    open.charges += charges;
+                }
+                None => {
+                    self.open = Some(OpenTrade {
+                        direction: delta.signum(),
+                        quantity,
+                        entry_time: timestamp.to_string(),
+                        entry_price: average_price,
+                        charges,
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Average price of the position as booked by the sandbox
+    fn average_price(&self) -> Result<Option<f64>> {
+        Ok(self
+            .ledger
+            .get_sandbox_positions()?
+            .into_iter()
+            .find(|p| {
+                p.symbol == self.request.symbol
+                    && p.exchange == self.request.exchange
+                    && p.product == self.request.product
+            })
+            .map(|p| p.average_price))
+    }
+}
+
+/// Result of running a strategy over a series
+struct SimulationResult {
+    equity: Vec<EquityPoint>,
+    trades: Vec<BacktestTrade>,
+    charges: f64,
+}
+
+/// Run a strategy over candles, closing any open position at the last close
+fn simulate(
+    request: &BacktestRequest,
+    candles: &[Candle],
+    strategy: &mut dyn Strategy,
+    ledger: &SqliteDb,
+    initial_capital: f64,
+) -> Result<SimulationResult> {
+    strategy.prepare(candles)?;
+
+    let mut sim = Simulation {
+        request,
+        ledger,
+        cash: initial_capital,
+        position: 0,
+        open: None,
+        trades: Vec::new(),
+        charges: 0.0,
+    };
+    let mut pending: Option<i32> = None;
+    let mut equity = Vec::with_capacity(candles.len());
+
+    for (index, candle) in candles.iter().enumerate() {
+        if let Some(target) = pending.take() {
+            sim.fill(target, candle.open, &candle.timestamp)?;
+        }
+
+        if let Some(target) = strategy.target(index, candles, sim.position) {
+            if target != sim.position {
+                match request.fill_price {
+                    FillPrice::Close => sim.fill(target, candle.close, &candle.timestamp)?,
+                    FillPrice::NextOpen => pending = Some(target),
+                }
+            }
+        }
+
+        if index + 1 == candles.len() && sim.position != 0 {
+            sim.fill(0, candle.close, &candle.timestamp)?;
+        }
+
+        equity.push(EquityPoint {
+            timestamp: candle.timestamp.clone(),
+            equity: sim.cash + sim.position as f64 * candle.close,
+            drawdown: 0.0,
+        });
+    }
+
+    Ok(SimulationResult {
+        equity,
+        trades: sim.trades,
+        charges: sim.charges,
+    })
+}
+
+/// Fill in drawdowns, returning the largest in absolute and percent terms
+fn apply_drawdown(equity: &mut [EquityPoint], initial_capital: f64) -> (f64, f64) {
+    let mut peak = initial_capital;
+    let (mut max_drawdown, mut max_drawdown_pct) = (0.0f64, 0.0f64);
+    for point in equity.iter_mut() {
+        peak = peak.max(point.equity);
+        point.drawdown = peak - point.equity;
+        max_drawdown = max_drawdown.max(point.drawdown);
+        if peak > 0.0 {
+            max_drawdown_pct = max_drawdown_pct.max(point.drawdown / peak * 100.0);
+        }
+    }
+    (max_drawdown, max_drawdown_pct)
+}
+
+/// Annualized Sharpe ratio of daily returns (risk-free rate 0)
+fn sharpe_ratio(equity: &[EquityPoint], initial_capital: f64) -> f64 {
+    // Equity at the end of each day
+    let mut closes: Vec<f64> = Vec::new();
+    let mut day = "";
+    for point in equity {
+        let date = point.timestamp.get(..10).unwrap_or(&point.timestamp);
+        if date == day {
+            if let Some(last) = closes.last_mut() {
+                *last = point.equity;
+            }
+        } else {
+            closes.push(point.equity);
+            day = date;
+        }
+    }
+
+    let mut previous = initial_capital;
+    let returns: Vec<f64> = closes
+        .into_iter()
+        .map(|close| {
+            let r = if previous != 0.0 { close / previous - 1.0 } else { 0.0 };
+            previous = close;
+            r
+        })
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let std_dev = variance.sqrt();
+    if std_dev == 0.0 {
+        return 0.0;
+    }
+    mean / std_dev * TRADING_DAYS_PER_YEAR.sqrt()
+}
+
+/// Backtest service
+pub struct BacktestService;
+
+impl BacktestService {
+    /// Run a backtest and store its report
+    pub async fn run(state: &AppState, request: BacktestRequest) -> Result<BacktestReport> {
+        Self::validate_request(&request)?;
+        let strategy = Self::build_strategy(&request.strategy)?;
+        let initial_capital = match request.initial_capital {
+            Some(capital) => capital,
+            None => state.sqlite.get_sandbox_config()?.starting_capital,
+        };
+
+        info!(
+            "Running {} backtest on {}:{} ({} to {})",
+            request.strategy.kind(),
+            request.exchange,
+            request.symbol,
+            request.from_date,
+            request.to_date
+        );
+
+        // Loading and simulating a long range is CPU and DuckDB bound
+        let duckdb = state.duckdb.clone();
+        tokio::task::spawn_blocking(move || Self::run_blocking(&duckdb, request, strategy, initial_capital))
+            .await
+            .map_err(|e| AppError::Internal(format!("Backtest task failed: {}", e)))?
+    }
+
+    /// Get stored backtest runs, newest first
+    pub fn get_backtests(state: &AppState) -> Result<Vec<BacktestSummary>> {
+        state.duckdb.get_backtests()
+    }
+
+    /// Get a stored backtest report
+    pub fn get_backtest(state: &AppState, run_id: &str) -> Result<BacktestReport> {
+        state
+            .duckdb
+            .get_backtest(run_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Backtest {} not found", run_id)))
+    }
+
+    /// Delete a stored backtest report
+    pub fn delete_backtest(state: &AppState, run_id: &str) -> Result<()> {
+        if !state.duckdb.delete_backtest(run_id)? {
+            return Err(AppError::NotFound(format!("Backtest {} not found", run_id)));
+        }
+        Ok(())
+    }
+
+    fn validate_request(request: &BacktestRequest) -> Result<()> {
+        if request.symbol.trim().is_empty() || request.exchange.trim().is_empty() {
+            return Err(AppError::Validation("Symbol and exchange are required".to_string()));
+        }
+        if request.source == BacktestSource::Candles && request.timeframe.trim().is_empty() {
+            return Err(AppError::Validation("Timeframe is required".to_string()));
+        }
+        let (Some(from), Some(to)) = (parse_time(&request.from_date), parse_time(&range_end(&request.to_date))) else {
+            return Err(AppError::Validation(
+                "from_date and to_date must be dates or timestamps".to_string(),
+            ));
+        };
+        if from > to {
+            return Err(AppError::Validation("from_date is after to_date".to_string()));
+        }
+        if request.initial_capital.is_some_and(|capital| capital <= 0.0) {
+            return Err(AppError::Validation("Initial capital must be positive".to_string()));
+        }
+        if !request.slippage.is_valid() || !request.brokerage.is_valid() {
+            return Err(AppError::Validation(
+                "Slippage and brokerage can't be negative".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn build_strategy(spec: &StrategySpec) -> Result<Box<dyn Strategy>> {
+        Ok(match spec {
+            StrategySpec::Rules {
+                entry_long,
+                exit_long,
+                entry_short,
+                exit_short,
+                quantity,
+            } => Box::new(RuleStrategy::new(
+                entry_long.as_deref(),
+                exit_long.as_deref(),
+                entry_short.as_deref(),
+                exit_short.as_deref(),
+                *quantity,
+            )?),
+            StrategySpec::Signals { csv, quantity } => Box::new(SignalStrategy::from_csv(csv, *quantity)?),
+        })
+    }
+
+    fn run_blocking(
+        duckdb: &DuckDb,
+        request: BacktestRequest,
+        mut strategy: Box<dyn Strategy>,
+        initial_capital: f64,
+    ) -> Result<BacktestReport> {
+        let candles = Self::load_candles(duckdb, &request)?;
+        if candles.is_empty() {
+            return Err(AppError::NotFound(format!(
+                "No {} data for {}:{} between {} and {}",
+                Self::timeframe(&request),
+                request.exchange,
+                request.symbol,
+                request.from_date,
+                request.to_date
+            )));
+        }
+
+        let ledger = SqliteDb::in_memory()?;
+        let SimulationResult {
+            mut equity,
+            trades,
+            charges,
+        } = simulate(&request, &candles, strategy.as_mut(), &ledger, initial_capital)?;
+
+        let (max_drawdown, max_drawdown_pct) = apply_drawdown(&mut equity, initial_capital);
+        let final_equity = equity.last().map_or(initial_capital, |p| p.equity);
+        let wins = trades.iter().filter(|t| t.pnl > 0.0).count();
+        let win_rate = if trades.is_empty() {
+            0.0
+        } else {
+            wins as f64 / trades.len() as f64 * 100.0
+        };
+
+        let run_id = format!("BT{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_uppercase());
+        let summary = BacktestSummary {
+            run_id,
+            name: request.name.clone().unwrap_or_else(|| {
+                format!("{}:{} {}", request.exchange, request.symbol, Self::timeframe(&request))
+            }),
+            strategy: request.strategy.kind().to_string(),
+            symbol: request.symbol.clone(),
+            exchange: request.exchange.clone(),
+            timeframe: Self::timeframe(&request).to_string(),
+            from_date: request.from_date.clone(),
+            to_date: request.to_date.clone(),
+            config: serde_json::to_string(&request)?,
+            initial_capital,
+            final_equity,
+            net_pnl: final_equity - initial_capital,
+            return_pct: (final_equity - initial_capital) / initial_capital * 100.0,
+            max_drawdown,
+            max_drawdown_pct,
+            sharpe: sharpe_ratio(&equity, initial_capital),
+            win_rate,
+            total_trades: trades.len() as i64,
+            total_charges: charges,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let report = BacktestReport { summary, equity, trades };
+        duckdb.insert_backtest(&report)?;
+
+        info!(
+            "Backtest {} finished: {} trades, net P&L {:.2}",
+            report.summary.run_id, report.summary.total_trades, report.summary.net_pnl
+        );
+        Ok(report)
+    }
+
+    fn timeframe(request: &BacktestRequest) -> &str {
+        match request.source {
+            BacktestSource::Candles => &request.timeframe,
+            BacktestSource::Ticks => "tick",
+        }
+    }
+
+    /// Load the request's price series in time order
+    pub fn load_candles(duckdb: &DuckDb, request: &BacktestRequest) -> Result<Vec<Candle>> {
+        let to_date = range_end(&request.to_date);
+        let invalid = |timestamp: &str| AppError::Internal(format!("Unreadable timestamp '{}' in DuckDB", timestamp));
+
+        match request.source {
+            BacktestSource::Candles => duckdb
+                .query_market_data(
+                    &request.symbol,
+                    &request.exchange,
+                    &request.timeframe,
+                    &request.from_date,
+                    &to_date,
+                )?
+                .into_iter()
+                .map(|row| {
+                    Ok(Candle {
+                        time: parse_time(&row.timestamp).ok_or_else(|| invalid(&row.timestamp))?,
+                        timestamp: row.timestamp,
+                        open: row.open,
+                        high: row.high,
+                        low: row.low,
+                        close: row.close,
+                        volume: row.volume,
+                    })
+                })
+                .collect(),
+            BacktestSource::Ticks => {
+                let mut last_volume: Option<i64> = None;
+                duckdb
+                    .query_ticks(&request.symbol, &request.exchange, &request.from_date, &to_date)?
+                    .into_iter()
+                    .map(|tick| {
+                        // Ticks carry cumulative day volume
+                        let volume = last_volume.map_or(0, |last| (tick.volume - last).max(0));
+                        last_volume = Some(tick.volume);
+                        Ok(Candle {
+                            time: parse_time(&tick.timestamp).ok_or_else(|| invalid(&tick.timestamp))?,
+                            timestamp: tick.timestamp,
+                            open: tick.ltp,
+                            high: tick.ltp,
+                            low: tick.ltp,
+                            close: tick.ltp,
+                            volume,
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Inclusive end of a range; a bare date covers that whole day
+fn range_end(to_date: &str) -> String {
+    if to_date.len() == 10 {
+        format!("{} 23:59:59.999", to_date)
+    } else {
+        to_date.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        let start = parse_time("2024-06-03 09:15:00").unwrap();
+        let mut previous = closes[0];
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| {
+                let time = start + chrono::Duration::minutes(i as i64);
+                let open = previous;
+                previous = close;
+                Candle {
+                    time,
+                    timestamp: time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    open,
+                    high: open.max(close),
+                    low: open.min(close),
+                    close,
+                    volume: 100,
+                }
+            })
+            .collect()
+    }
+
+    fn request(strategy: StrategySpec) -> BacktestRequest {
+        BacktestRequest {
+            name: None,
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            source: BacktestSource::Candles,
+            timeframe: DEFAULT_TIMEFRAME.to_string(),
+            from_date: "2024-06-03".to_string(),
+            to_date: "2024-06-03".to_string(),
+            strategy,
+            initial_capital: Some(100_000.0),
+            product: "MIS".to_string(),
+            fill_price: FillPrice::NextOpen,
+            slippage: SlippageModel::None,
+            brokerage: BrokerageModel::None,
+        }
+    }
+
+    #[test]
+    fn test_indicators() {
+        let src: Vec<Option<f64>> = [1.0, 2.0, 3.0, 4.0, 5.0].into_iter().map(Some).collect();
+        assert_eq!(Indicator::Sma.apply(&src, 3), vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+        assert_eq!(Indicator::Highest.apply(&src, 2)[4], Some(5.0));
+        assert_eq!(Indicator::Prev.apply(&src, 1)[1], Some(1.0));
+        // Seeded with SMA 2.0, then 2 + 0.5 * (4 - 2)
+        assert_eq!(Indicator::Ema.apply(&src, 3)[3], Some(3.0));
+        // Only gains
+        assert_eq!(Indicator::Rsi.apply(&src, 2)[2], Some(100.0));
+        assert_eq!(Indicator::Rsi.apply(&src, 2)[1], None);
+    }
+
+    #[test]
+    fn test_rule_conditions() {
+        let bars = candles(&[10.0, 9.0, 8.0, 12.0, 13.0]);
+
+        let mut rule = Condition::parse("crosses_above(close, sma(close, 3)) and not volume < 50").unwrap();
+        rule.prepare(&bars);
+        // SMA(3) at bar 3 is 9.67: close 12 crosses above it from 8 <= 9
+        assert!(!rule.eval(2));
+        assert!(rule.eval(3));
+        assert!(!rule.eval(4));
+
+        let mut rule = Condition::parse("(close >= 13 or close < 9) and high > prev(high, 1)").unwrap();
+        rule.prepare(&bars);
+        assert!(rule.eval(4));
+        assert!(!rule.eval(2));
+
+        assert!(Condition::parse("close >").is_err());
+        assert!(Condition::parse("sma(close, 0) > close").is_err());
+        assert!(Condition::parse("vwap(close, 5) > close").is_err());
+        assert!(Condition::parse("close > 10 10").is_err());
+    }
+
+    #[test]
+    fn test_signal_csv() {
+        let tradingview = "Trade #,Type,Signal,Date/Time,Price INR,Contracts,Profit INR\n\
+                           1,Exit Long,Close,2024-06-03 09:18,812.5,10,125\n\
+                           1,Entry Long,Long,2024-06-03 09:16,800,10,125\n";
+        let mut strategy = SignalStrategy::from_csv(tradingview, None).unwrap();
+        let bars = candles(&[800.0; 5]);
+        strategy.prepare(&bars).unwrap();
+        assert_eq!(strategy.target(0, &bars, 0), None);
+        assert_eq!(strategy.target(1, &bars, 0), Some(10));
+        assert_eq!(strategy.target(3, &bars, 10), Some(0));
+
+        let generic = "timestamp,action,quantity\n\
+                       2024-06-03 09:15:00,BUY,5\n\
+                       \"2024-06-03 09:16:00\",SELL,\n";
+        let mut strategy = SignalStrategy::from_csv(generic, Some(2)).unwrap();
+        strategy.prepare(&bars).unwrap();
+        assert_eq!(strategy.target(0, &bars, 0), Some(5));
+        assert_eq!(strategy.target(1, &bars, 5), Some(3));
+
+        assert!(SignalStrategy::from_csv("timestamp,action\n2024-06-03 09:15,BUY\n", None).is_err());
+        assert!(SignalStrategy::from_csv("when,what\n", None).is_err());
+    }
+
+    #[test]
+    fn test_simulation_fills_and_metrics() {
+        let mut req = request(StrategySpec::Rules {
+            entry_long: Some("close > 100".to_string()),
+            exit_long: Some("close < 100".to_string()),
+            entry_short: None,
+            exit_short: None,
+            quantity: 10,
+        });
+        req.slippage = SlippageModel::Points { value: 0.5 };
+        req.brokerage = BrokerageModel::Flat { per_order: 20.0 };
+
+        // Signal at bar 1 close, buy at bar 2 open (101); exit signal at bar 3,
+        // sell at bar 4 open (99)
+        let bars = candles(&[100.0, 101.0, 104.0, 99.0, 98.0]);
+        let mut strategy = BacktestService::build_strategy(&req.strategy).unwrap();
+        let ledger = SqliteDb::in_memory().unwrap();
+        let result = simulate(&req, &bars, strategy.as_mut(), &ledger, 100_000.0).unwrap();
+
+        assert_eq!(result.trades.len(), 1);
+        let trade = &result.trades[0];
+        assert_eq!(trade.side, "LONG");
+        assert_eq!(trade.entry_price, 101.5);
+        assert_eq!(trade.exit_price, 98.5);
+        assert_eq!(trade.charges, 40.0);
+        assert_eq!(trade.pnl, -30.0 - 40.0);
+        assert_eq!(result.charges, 40.0);
+
+        // Flat at the end, so equity is capital plus trade P&L
+        let mut equity = result.equity;
+        assert_eq!(equity.last().unwrap().equity, 100_000.0 - 70.0);
+        // Peak is the mark at bar 2 close (104): 98,965 cash + 1,040
+        let (max_drawdown, _) = apply_drawdown(&mut equity, 100_000.0);
+        assert_eq!(max_drawdown, 100_005.0 - 99_930.0);
+        assert_eq!(sharpe_ratio(&equity, 100_000.0), 0.0);
+
+        // The sandbox booked the round trip
+        assert!(ledger.get_sandbox_positions().unwrap().is_empty());
+        assert_eq!(ledger.get_sandbox_orders().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_reversal_and_square_off() {
+        let mut req = request(StrategySpec::Rules {
+            entry_long: Some("close > 100".to_string()),
+            exit_long: None,
+            entry_short: Some("close < 100".to_string()),
+            exit_short: None,
+            quantity: 5,
+        });
+        req.fill_price = FillPrice::Close;
+
+        let bars = candles(&[101.0, 99.0, 97.0]);
+        let mut strategy = BacktestService::build_strategy(&req.strategy).unwrap();
+        let ledger = SqliteDb::in_memory().unwrap();
+        let result = simulate(&req, &bars, strategy.as_mut(), &ledger, 100_000.0).unwrap();
+
+        // Long at 101, reversed short at 99, short squared off at 97
+        assert_eq!(result.trades.len(), 2);
+        assert_eq!((result.trades[0].side.as_str(), result.trades[0].pnl), ("LONG", -10.0));
+        assert_eq!((result.trades[1].side.as_str(), result.trades[1].entry_price), ("SHORT", 99.0));
+        assert_eq!(result.trades[1].pnl, 10.0);
+        assert_eq!(result.equity.last().unwrap().equity, 100_000.0);
+    }
+}
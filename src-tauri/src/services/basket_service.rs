@@ -0,0 +1,411 @@
+//! Basket Service
+//!
+//! Executes multi-leg orders (baskets, option spreads and strangles).
+//!
+//! Legs normally go out one after another and failures are just recorded.
+//! In all-or-none mode hedge (BUY) legs are placed first, every leg must be
+//! accepted or filled by the broker, and a failed leg rolls back the legs
+//! already placed: open orders are cancelled and filled quantity is reversed
+//! with a MARKET order, so a spread never ends up with a naked leg.
+
+use crate::brokers::types::{Order, OrderRequest};
+use crate::error::Result;
+use crate::services::{OrderService, OrderbookService};
+use crate::state::AppState;
+use crate::websocket::normalize_status;
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Order book checks before an unconfirmed leg counts as failed
+const CONFIRM_ATTEMPTS: u32 = 5;
+
+/// Delay between order book checks
+const CONFIRM_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Outcome of one basket leg
+#[derive(Debug, Clone, Serialize)]
+pub struct BasketLeg {
+    /// 1-based position of the leg in the request
+    pub leg: usize,
+    pub symbol: String,
+    pub exchange: String,
+    pub side: String,
+    pub quantity: i32,
+    pub order_ids: Vec<String>,
+    pub filled_quantity: i32,
+    /// placed, filled, failed, rejected, skipped, cancelled, reversed or rollback_failed
+    pub status: String,
+    pub message: Option<String>,
+}
+
+impl BasketLeg {
+    fn new(leg: usize, order: &OrderRequest) -> Self {
+        Self {
+            leg,
+            symbol: order.symbol.clone(),
+            exchange: order.exchange.clone(),
+            side: order.side.clone(),
+            quantity: order.quantity,
+            order_ids: Vec::new(),
+            filled_quantity: 0,
+            status: "skipped".to_string(),
+            message: None,
+        }
+    }
+
+    /// Whether the leg reached the broker and is working or filled
+    pub fn is_placed(&self) -> bool {
+        self.status == "placed" || self.status == "filled"
+    }
+}
+
+/// Consolidated result of a basket
+#[derive(Debug, Clone, Serialize)]
+pub struct BasketResult {
+    pub success: bool,
+    pub all_or_none: bool,
+    /// All-or-none basket failed and every placed leg was unwound
+    pub rolled_back: bool,
+    pub mode: String,
+    pub message: String,
+    pub legs: Vec<BasketLeg>,
+}
+
+/// Basket service for multi-leg execution
+pub struct BasketService;
+
+impl BasketService {
+    /// Place a basket of orders
+    ///
+    /// Routes to sandbox in analyze mode (via `OrderService`).
+    pub async fn execute(
+        state: &AppState,
+        orders: Vec<OrderRequest>,
+        all_or_none: bool,
+        api_key: Option<&str>,
+    ) -> Result<BasketResult> {
+        info!(
+            "BasketService::execute - {} legs, all_or_none={}",
+            orders.len(),
+            all_or_none
+        );
+
+        let mode = if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        };
+        let mut legs: Vec<BasketLeg> = orders
+            .iter()
+            .enumerate()
+            .map(|(i, order)| BasketLeg::new(i + 1, order))
+            .collect();
+
+        if !all_or_none {
+            for (leg, order) in legs.iter_mut().zip(orders) {
+                Self::place_leg(state, leg, order, api_key).await;
+            }
+
+            let placed = legs.iter().filter(|l| l.is_placed()).count();
+            return Ok(BasketResult {
+                success: placed == legs.len(),
+                all_or_none,
+                rolled_back: false,
+                mode: mode.to_string(),
+                message: format!("{} of {} orders placed", placed, legs.len()),
+                legs,
+            });
+        }
+
+        let mut executed = Vec::new();
+        let mut failure = None;
+
+        for i in Self::execution_order(&orders) {
+            if failure.is_some() {
+                continue;
+            }
+
+            executed.push(i);
+            let ok = Self::place_leg(state, &mut legs[i], orders[i].clone(), api_key).await
+                && Self::confirm_leg(state, &mut legs[i], api_key).await;
+            if !ok {
+                failure = Some(format!(
+                    "Leg {} ({} {} {}) {}: {}",
+                    legs[i].leg,
+                    legs[i].side,
+                    legs[i].quantity,
+                    legs[i].symbol,
+                    legs[i].status,
+                    legs[i].message.clone().unwrap_or_default()
+                ));
+            }
+        }
+
+        let Some(reason) = failure else {
+            return Ok(BasketResult {
+                success: true,
+                all_or_none,
+                rolled_back: false,
+                mode: mode.to_string(),
+                message: format!("All {} legs placed", legs.len()),
+                legs,
+            });
+        };
+
+        warn!("All-or-none basket failed, rolling back: {}", reason);
+        let rolled_back = Self::rollback(state, &mut legs, &orders, &executed, api_key).await;
+        let message = if rolled_back {
+            format!("{}. Placed legs were rolled back", reason)
+        } else {
+            error!("Basket rollback incomplete: {}", reason);
+            format!("{}. Rollback incomplete, check open orders and positions", reason)
+        };
+
+        Ok(BasketResult {
+            success: false,
+            all_or_none,
+            rolled_back,
+            mode: mode.to_string(),
+            message,
+            legs,
+        })
+    }
+
+    // ========================================================================
+    // Private Helper Methods
+    // ========================================================================
+
+    /// Leg indices in execution order: hedge (BUY) legs first, request order otherwise
+    fn execution_order(orders: &[OrderRequest]) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..orders.len()).collect();
+        indices.sort_by_key(|&i| orders[i].side != "BUY");
+        indices
+    }
+
+    /// Place one leg, returning whether every slice of it was placed
+    async fn place_leg(
+        state: &AppState,
+        leg: &mut BasketLeg,
+        order: OrderRequest,
+        api_key: Option<&str>,
+    ) -> bool {
+        match OrderService::place_order(state, order, api_key).await {
+            Ok(result) if result.success => {
+                leg.order_ids = result
+                    .placed_orders(leg.quantity)
+                    .into_iter()
+                    .map(|c| c.order_id)
+                    .collect();
+                leg.status = "placed".to_string();
+
+                if result.failed_slices.is_empty() {
+                    true
+                } else {
+                    leg.message = Some(result.failed_slices.join(", "));
+                    false
+                }
+            }
+            Ok(result) => {
+                leg.status = "failed".to_string();
+                leg.message = Some(result.message);
+                false
+            }
+            Err(e) => {
+                leg.status = "failed".to_string();
+                leg.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    /// Wait until the broker has accepted or filled every order of a leg
+    async fn confirm_leg(state: &AppState, leg: &mut BasketLeg, api_key: Option<&str>) -> bool {
+        for attempt in 0..CONFIRM_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(CONFIRM_INTERVAL).await;
+            }
+
+            let book = match OrderbookService::get_orderbook(state, api_key).await {
+                Ok(result) => result.orders,
+                Err(e) => {
+                    warn!("Order book unavailable while confirming leg {}: {}", leg.leg, e);
+                    continue;
+                }
+            };
+            let orders: Vec<&Order> = book
+                .iter()
+                .filter(|o| leg.order_ids.contains(&o.order_id))
+                .collect();
+            leg.filled_quantity = orders.iter().map(|o| o.filled_quantity).sum();
+
+            if let Some(rejected) = orders.iter().find(|o| {
+                let status = normalize_status(&o.status);
+                status == "rejected" || status == "cancelled"
+            }) {
+                leg.status = "rejected".to_string();
+                leg.message = Some(
+                    rejected
+                        .rejection_reason
+                        .clone()
+                        .unwrap_or_else(|| format!("Order {} {}", rejected.order_id, rejected.status)),
+                );
+                return false;
+            }
+
+            if orders.len() < leg.order_ids.len() {
+                continue;
+            }
+
+            let statuses: Vec<String> = orders.iter().map(|o| normalize_status(&o.status)).collect();
+            if statuses.iter().all(|s| s == "complete") {
+                leg.status = "filled".to_string();
+                return true;
+            }
+            if statuses
+                .iter()
+                .all(|s| s == "complete" || s == "open" || s == "trigger pending")
+            {
+                return true;
+            }
+        }
+
+        leg.status = "failed".to_string();
+        leg.message = Some("Order not confirmed by broker".to_string());
+        false
+    }
+
+    /// Cancel working orders and reverse filled quantity of executed legs
+    ///
+    /// Returns true if every executed leg was unwound.
+    async fn rollback(
+        state: &AppState,
+        legs: &mut [BasketLeg],
+        orders: &[OrderRequest],
+        executed: &[usize],
+        api_key: Option<&str>,
+    ) -> bool {
+        let mut complete = true;
+
+        // Cancel everything still working first so fills stop growing
+        let book = OrderbookService::get_orderbook(state, api_key)
+            .await
+            .map(|r| r.orders)
+            .unwrap_or_default();
+        for &i in executed.iter().rev() {
+            for order_id in legs[i].order_ids.clone() {
+                let working = book.iter().any(|o| {
+                    let status = normalize_status(&o.status);
+                    o.order_id == order_id && (status == "open" || status == "trigger pending")
+                });
+                if !working {
+                    continue;
+                }
+                if let Err(e) = OrderService::cancel_order(state, &order_id, None, api_key).await {
+                    error!("Rollback: failed to cancel {}: {}", order_id, e);
+                    legs[i].status = "rollback_failed".to_string();
+                    legs[i].message = Some(format!("Cancel of {} failed: {}", order_id, e));
+                    complete = false;
+                }
+            }
+        }
+
+        // Reverse whatever filled, using the order book after the cancels
+        let book = match OrderbookService::get_orderbook(state, api_key).await {
+            Ok(result) => result.orders,
+            Err(e) => {
+                error!("Rollback: order book unavailable: {}", e);
+                for &i in executed {
+                    if !legs[i].order_ids.is_empty() {
+                        legs[i].status = "rollback_failed".to_string();
+                        legs[i].message = Some(format!("Order book unavailable: {}", e));
+                    }
+                }
+                return false;
+            }
+        };
+
+        for &i in executed.iter().rev() {
+            if legs[i].order_ids.is_empty() || legs[i].status == "rollback_failed" {
+                continue;
+            }
+
+            let filled: i32 = book
+                .iter()
+                .filter(|o| legs[i].order_ids.contains(&o.order_id))
+                .map(|o| o.filled_quantity)
+                .sum();
+            legs[i].filled_quantity = filled;
+            if filled <= 0 {
+                if legs[i].is_placed() {
+                    legs[i].status = "cancelled".to_string();
+                }
+                continue;
+            }
+
+            let mut reverse = orders[i].clone();
+            reverse.side = if reverse.side == "BUY" { "SELL" } else { "BUY" }.to_string();
+            reverse.quantity = filled;
+            reverse.order_type = "MARKET".to_string();
+            reverse.price = 0.0;
+            reverse.trigger_price = None;
+
+            match OrderService::place_order(state, reverse, api_key).await {
+                Ok(result) if result.success => {
+                    legs[i].status = "reversed".to_string();
+                    legs[i].message = Some(format!(
+                        "Reversed {} filled with order {}",
+                        filled,
+                        result.order_id.unwrap_or_default()
+                    ));
+                }
+                Ok(result) => {
+                    legs[i].status = "rollback_failed".to_string();
+                    legs[i].message = Some(format!("Reversal failed: {}", result.message));
+                    complete = false;
+                }
+                Err(e) => {
+                    error!("Rollback: failed to reverse leg {}: {}", legs[i].leg, e);
+                    legs[i].status = "rollback_failed".to_string();
+                    legs[i].message = Some(format!("Reversal failed: {}", e));
+                    complete = false;
+                }
+            }
+        }
+
+        complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(side: &str) -> OrderRequest {
+        OrderRequest {
+            symbol: "NIFTY24JAN21500CE".to_string(),
+            exchange: "NFO".to_string(),
+            side: side.to_string(),
+            quantity: 50,
+            price: 0.0,
+            order_type: "MARKET".to_string(),
+            product: "NRML".to_string(),
+            validity: "DAY".to_string(),
+            trigger_price: None,
+            disclosed_quantity: None,
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
+            broker_symbol: None,
+            symbol_token: None,
+        }
+    }
+
+    #[test]
+    fn test_hedge_legs_execute_first() {
+        let orders = vec![order("SELL"), order("BUY"), order("SELL"), order("BUY")];
+        assert_eq!(BasketService::execution_order(&orders), vec![1, 3, 0, 2]);
+    }
+}
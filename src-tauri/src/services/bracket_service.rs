@@ -0,0 +1,580 @@
+//! Bracket Order Service
+//!
+//! Emulates bracket (BO) and cover (CO) orders for brokers that can't take
+//! them natively, and for sandbox. The entry goes out as a plain MIS order;
+//! once it fills, the stoploss (and the target for BO) are held as a linked
+//! emulated GTT so the exit legs cancel each other like OCO. Trailing stops
+//! are moved by the `BracketMonitor` as ticks arrive.
+
+use crate::brokers::types::{BracketOrder, GttLeg, GttRequest, OrderRequest};
+use crate::error::{AppError, Result};
+use crate::services::{GttService, OrderService, OrderbookService, PlaceOrderResult};
+use crate::state::AppState;
+use crate::websocket::MarketTick;
+use serde::Serialize;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Event emitted when an emulated bracket changes state
+#[derive(Debug, Clone, Serialize)]
+pub struct BracketEvent {
+    pub bracket_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub status: String,
+    pub stop_price: f64,
+    pub message: String,
+}
+
+/// Bracket service for business logic
+pub struct BracketService;
+
+impl BracketService {
+    /// Validate a bracket/cover order request
+    ///
+    /// Both need a stoploss; bracket orders also need a target. All legs are
+    /// expressed in points from the entry price.
+    pub fn validate_request(order: &OrderRequest) -> Result<()> {
+        let stoploss = order.stoploss.unwrap_or(0.0);
+        if stoploss <= 0.0 {
+            return Err(AppError::Validation(format!(
+                "{} orders need a positive stoploss (points)",
+                order.product
+            )));
+        }
+
+        match (order.product.as_str(), order.target) {
+            ("BO", Some(target)) if target > 0.0 => {}
+            ("BO", _) => {
+                return Err(AppError::Validation(
+                    "BO orders need a positive target (points)".to_string(),
+                ));
+            }
+            ("CO", Some(target)) if target > 0.0 => {
+                return Err(AppError::Validation("CO orders don't take a target".to_string()));
+            }
+            _ => {}
+        }
+
+        if order.trailing_stoploss.is_some_and(|t| t <= 0.0) {
+            return Err(AppError::Validation(
+                "Trailing stoploss must be positive".to_string(),
+            ));
+        }
+
+        if order.order_type != "MARKET" && order.order_type != "LIMIT" {
+            return Err(AppError::Validation(format!(
+                "{} entry must be MARKET or LIMIT, got {}",
+                order.product, order.order_type
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Place an emulated bracket/cover order
+    ///
+    /// Places the entry as an MIS order and records the bracket as pending.
+    /// The exit legs are linked once the monitor sees the entry fill.
+    pub async fn place_bracket_order(
+        state: &AppState,
+        order: OrderRequest,
+        api_key: Option<&str>,
+    ) -> Result<PlaceOrderResult> {
+        info!("BracketService::place_bracket_order - {:?}", order);
+
+        let mode = Self::current_mode(state);
+
+        let entry = OrderRequest {
+            product: "MIS".to_string(),
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            ..order.clone()
+        };
+
+        let result = OrderService::place_regular_order(state, entry, api_key).await?;
+
+        let bracket_id = format!("BR{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_uppercase());
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let bracket = BracketOrder {
+            bracket_id: bracket_id.clone(),
+            symbol: order.symbol,
+            exchange: order.exchange,
+            side: order.side,
+            quantity: order.quantity,
+            product: order.product,
+            entry_order_id: result.order_id.clone(),
+            entry_price: 0.0,
+            target: order.target,
+            stoploss: order.stoploss.unwrap_or(0.0),
+            trailing_stoploss: order.trailing_stoploss,
+            stop_price: 0.0,
+            best_price: 0.0,
+            gtt_id: None,
+            status: "pending".to_string(),
+            message: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        state.sqlite.create_bracket_order(&bracket, mode)?;
+
+        Ok(PlaceOrderResult {
+            success: true,
+            order_id: result.order_id,
+            message: format!("{} placed (emulated, {})", bracket.product, bracket_id),
+            mode: result.mode,
+            child_orders: Vec::new(),
+            failed_slices: Vec::new(),
+        })
+    }
+
+    /// Get emulated brackets for the current mode (live/analyze)
+    pub fn get_bracket_orders(state: &AppState) -> Result<Vec<BracketOrder>> {
+        state.sqlite.get_bracket_orders(Self::current_mode(state), false)
+    }
+
+    /// Cancel an emulated bracket
+    ///
+    /// A pending bracket cancels its entry order. An open bracket cancels its
+    /// exit legs; the filled position is left for the user to manage.
+    pub async fn cancel_bracket_order(state: &AppState, bracket_id: &str) -> Result<BracketEvent> {
+        info!("BracketService::cancel_bracket_order - {}", bracket_id);
+
+        let bracket = state
+            .sqlite
+            .get_bracket_order(bracket_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Bracket {} not found", bracket_id)))?;
+
+        match bracket.status.as_str() {
+            "pending" => {
+                if let Some(order_id) = &bracket.entry_order_id {
+                    OrderService::cancel_order(state, order_id, None, None).await?;
+                }
+            }
+            "open" => {
+                if let Some(gtt_id) = &bracket.gtt_id {
+                    state.sqlite.transition_gtt_order(gtt_id, "cancelled")?;
+                }
+            }
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Bracket {} is no longer active ({})",
+                    bracket_id, other
+                )));
+            }
+        }
+
+        let message = "Cancelled by user".to_string();
+        state.sqlite.set_bracket_status(bracket_id, "cancelled", Some(&message))?;
+
+        Ok(Self::event(&bracket, "cancelled", message))
+    }
+
+    /// Link exit legs for pending brackets whose entry has filled
+    ///
+    /// Called by the bracket monitor while any bracket is pending.
+    pub async fn sync_entries(state: &AppState, pending: &[BracketOrder]) -> Vec<BracketEvent> {
+        let orders = match OrderbookService::get_orderbook(state, None).await {
+            Ok(result) => result.orders,
+            Err(e) => {
+                warn!("Bracket monitor could not fetch order book: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mode = Self::current_mode(state);
+        let mut events = Vec::new();
+
+        for bracket in pending {
+            let Some(entry) = bracket
+                .entry_order_id
+                .as_ref()
+                .and_then(|id| orders.iter().find(|o| &o.order_id == id))
+            else {
+                continue;
+            };
+
+            let status = entry.status.to_lowercase();
+            if status == "rejected" || status == "cancelled" {
+                let message = entry
+                    .rejection_reason
+                    .clone()
+                    .unwrap_or_else(|| format!("Entry order {}", status));
+                if let Err(e) = state.sqlite.set_bracket_status(&bracket.bracket_id, &status, Some(&message)) {
+                    error!("Failed to update bracket {}: {}", bracket.bracket_id, e);
+                }
+                events.push(Self::event(bracket, &status, message));
+                continue;
+            }
+
+            if status != "complete" && status != "filled" {
+                continue;
+            }
+
+            let entry_price = if entry.average_price > 0.0 { entry.average_price } else { entry.price };
+            events.push(Self::activate(state, bracket, entry_price, mode).await);
+        }
+
+        events
+    }
+
+    /// Close out open brackets whose exit GTT has fired or gone away
+    pub fn sync_exits(state: &AppState, open: &[BracketOrder]) -> Vec<BracketEvent> {
+        let mut events = Vec::new();
+
+        for bracket in open {
+            let Some(gtt_id) = &bracket.gtt_id else {
+                continue;
+            };
+
+            let gtt = match state.sqlite.get_gtt_order(gtt_id) {
+                Ok(Some(gtt)) => gtt,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("Failed to load GTT {} for bracket {}: {}", gtt_id, bracket.bracket_id, e);
+                    continue;
+                }
+            };
+
+            let (status, message) = match gtt.status.as_str() {
+                "triggered" => (
+                    "closed",
+                    format!("Exit order {}", gtt.order_id.unwrap_or_default()),
+                ),
+                "rejected" => ("error", "Exit order was rejected".to_string()),
+                "cancelled" => ("cancelled", "Exit legs cancelled".to_string()),
+                _ => continue,
+            };
+
+            if let Err(e) = state.sqlite.set_bracket_status(&bracket.bracket_id, status, Some(&message)) {
+                error!("Failed to update bracket {}: {}", bracket.bracket_id, e);
+                continue;
+            }
+            events.push(Self::event(bracket, status, message));
+        }
+
+        events
+    }
+
+    /// Trail stops of open brackets on a tick
+    ///
+    /// Moves the linked GTT's stoploss trigger and returns the brackets that
+    /// were updated.
+    pub fn process_tick(state: &AppState, open: &[BracketOrder], tick: &MarketTick) -> Vec<BracketEvent> {
+        let symbol = state
+            .get_symbol_by_token(&tick.exchange, &tick.token)
+            .map(|s| s.symbol)
+            .unwrap_or_else(|| tick.symbol.clone());
+        let mut events = Vec::new();
+
+        for bracket in open {
+            if bracket.symbol != symbol || bracket.exchange != tick.exchange {
+                continue;
+            }
+
+            let Some((stop_price, best_price)) = Self::trail_stop(bracket, tick.ltp) else {
+                continue;
+            };
+            let Some(gtt_id) = &bracket.gtt_id else {
+                continue;
+            };
+
+            let target_price = Self::target_price(state, bracket, bracket.entry_price);
+            let gtt = Self::exit_gtt(bracket, stop_price, target_price, tick.ltp);
+
+            match state.sqlite.update_gtt_order(gtt_id, &gtt) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    error!("Failed to trail GTT {}: {}", gtt_id, e);
+                    continue;
+                }
+            }
+
+            if let Err(e) = state.sqlite.update_bracket_stop(&bracket.bracket_id, stop_price, best_price) {
+                error!("Failed to record trailed stop for {}: {}", bracket.bracket_id, e);
+            }
+
+            info!("Bracket {} stop trailed to {} (ltp {})", bracket.bracket_id, stop_price, tick.ltp);
+            events.push(BracketEvent {
+                stop_price,
+                ..Self::event(bracket, "open", format!("Stoploss trailed to {}", stop_price))
+            });
+        }
+
+        events
+    }
+
+    /// Compute a trailed stop for a price move
+    ///
+    /// The stop moves in whole trailing steps: each time the price improves on
+    /// the last reference price by a full step, the stop and the reference move
+    /// by that many steps. Returns the new (stop_price, best_price), or None if
+    /// the stop doesn't move.
+    pub fn trail_stop(bracket: &BracketOrder, ltp: f64) -> Option<(f64, f64)> {
+        let step = bracket.trailing_stoploss?;
+        if step <= 0.0 || ltp <= 0.0 {
+            return None;
+        }
+
+        let gain = if bracket.side == "BUY" {
+            ltp - bracket.best_price
+        } else {
+            bracket.best_price - ltp
+        };
+
+        let steps = (gain / step).floor();
+        if steps < 1.0 {
+            return None;
+        }
+
+        let shift = steps * step;
+        if bracket.side == "BUY" {
+            Some((bracket.stop_price + shift, bracket.best_price + shift))
+        } else {
+            Some((bracket.stop_price - shift, bracket.best_price - shift))
+        }
+    }
+
+    // ========================================================================
+    // Private Helper Methods
+    // ========================================================================
+
+    fn current_mode(state: &AppState) -> &'static str {
+        if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        }
+    }
+
+    /// Place the linked exit GTT for a filled entry and open the bracket
+    async fn activate(state: &AppState, bracket: &BracketOrder, entry_price: f64, mode: &str) -> BracketEvent {
+        if entry_price <= 0.0 {
+            let message = "Entry fill price unavailable, exit legs not placed".to_string();
+            if let Err(e) = state.sqlite.set_bracket_status(&bracket.bracket_id, "error", Some(&message)) {
+                error!("Failed to update bracket {}: {}", bracket.bracket_id, e);
+            }
+            return Self::event(bracket, "error", message);
+        }
+
+        let stop_price = Self::round_to_tick(
+            state,
+            bracket,
+            if bracket.side == "BUY" {
+                entry_price - bracket.stoploss
+            } else {
+                entry_price + bracket.stoploss
+            },
+        );
+        let target_price = Self::target_price(state, bracket, entry_price);
+        let gtt = Self::exit_gtt(bracket, stop_price, target_price, entry_price);
+
+        let gtt_id = match GttService::place_linked_gtt(state, gtt, mode).await {
+            Ok(gtt_id) => gtt_id,
+            Err(e) => {
+                let message = format!("Entry filled but exit legs failed: {}", e);
+                error!("Bracket {}: {}", bracket.bracket_id, message);
+                if let Err(e) = state.sqlite.set_bracket_status(&bracket.bracket_id, "error", Some(&message)) {
+                    error!("Failed to update bracket {}: {}", bracket.bracket_id, e);
+                }
+                return Self::event(bracket, "error", message);
+            }
+        };
+
+        match state.sqlite.activate_bracket_order(&bracket.bracket_id, entry_price, stop_price, &gtt_id) {
+            Ok(true) => {}
+            Ok(false) => {
+                // Cancelled while the entry was filling - drop the exit legs again
+                let _ = state.sqlite.transition_gtt_order(&gtt_id, "cancelled");
+                return Self::event(bracket, "cancelled", "Bracket cancelled before activation".to_string());
+            }
+            Err(e) => error!("Failed to activate bracket {}: {}", bracket.bracket_id, e),
+        }
+
+        info!(
+            "Bracket {} open: entry {}, stop {}, target {:?}",
+            bracket.bracket_id, entry_price, stop_price, target_price
+        );
+
+        BracketEvent {
+            stop_price,
+            ..Self::event(bracket, "open", format!("Entry filled at {}, exit legs linked ({})", entry_price, gtt_id))
+        }
+    }
+
+    /// Target price for a bracket order (None for cover orders)
+    fn target_price(state: &AppState, bracket: &BracketOrder, entry_price: f64) -> Option<f64> {
+        let points = bracket.target?;
+        let price = if bracket.side == "BUY" {
+            entry_price + points
+        } else {
+            entry_price - points
+        };
+        Some(Self::round_to_tick(state, bracket, price))
+    }
+
+    /// Build the exit GTT: a MARKET stoploss leg, plus a LIMIT target leg for BO
+    fn exit_gtt(bracket: &BracketOrder, stop_price: f64, target_price: Option<f64>, last_price: f64) -> GttRequest {
+        let exit_side = if bracket.side == "BUY" { "SELL" } else { "BUY" };
+        let leg = |order_type: &str, price: f64| GttLeg {
+            side: exit_side.to_string(),
+            quantity: bracket.quantity,
+            order_type: order_type.to_string(),
+            price,
+            product: "MIS".to_string(),
+        };
+
+        let (trigger_type, trigger_prices, legs) = match target_price {
+            // Two-leg triggers are [lower, upper]
+            Some(target) if bracket.side == "BUY" => (
+                "two-leg",
+                vec![stop_price, target],
+                vec![leg("MARKET", 0.0), leg("LIMIT", target)],
+            ),
+            Some(target) => (
+                "two-leg",
+                vec![target, stop_price],
+                vec![leg("LIMIT", target), leg("MARKET", 0.0)],
+            ),
+            None => ("single", vec![stop_price], vec![leg("MARKET", 0.0)]),
+        };
+
+        GttRequest {
+            symbol: bracket.symbol.clone(),
+            exchange: bracket.exchange.clone(),
+            trigger_type: trigger_type.to_string(),
+            trigger_prices,
+            last_price,
+            legs,
+            broker_symbol: None,
+            symbol_token: None,
+        }
+    }
+
+    /// Round a price to the symbol's tick size (0.05 if unknown)
+    fn round_to_tick(state: &AppState, bracket: &BracketOrder, price: f64) -> f64 {
+        let tick = state
+            .get_symbol_by_name(&bracket.exchange, &bracket.symbol)
+            .map(|s| s.tick_size)
+            .filter(|t| *t > 0.0)
+            .unwrap_or(0.05);
+        ((price / tick).round() * tick * 100.0).round() / 100.0
+    }
+
+    fn event(bracket: &BracketOrder, status: &str, message: String) -> BracketEvent {
+        BracketEvent {
+            bracket_id: bracket.bracket_id.clone(),
+            symbol: bracket.symbol.clone(),
+            exchange: bracket.exchange.clone(),
+            status: status.to_string(),
+            stop_price: bracket.stop_price,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bracket(side: &str, trailing: Option<f64>) -> BracketOrder {
+        BracketOrder {
+            bracket_id: "BR1".to_string(),
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            side: side.to_string(),
+            quantity: 10,
+            product: "BO".to_string(),
+            entry_order_id: Some("1".to_string()),
+            entry_price: 100.0,
+            target: Some(10.0),
+            stoploss: 5.0,
+            trailing_stoploss: trailing,
+            stop_price: if side == "BUY" { 95.0 } else { 105.0 },
+            best_price: 100.0,
+            gtt_id: Some("GTT1".to_string()),
+            status: "open".to_string(),
+            message: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn order(product: &str, target: Option<f64>, stoploss: Option<f64>) -> OrderRequest {
+        OrderRequest {
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            side: "BUY".to_string(),
+            quantity: 10,
+            price: 0.0,
+            order_type: "MARKET".to_string(),
+            product: product.to_string(),
+            validity: "DAY".to_string(),
+            trigger_price: None,
+            disclosed_quantity: None,
+            amo: false,
+            target,
+            stoploss,
+            trailing_stoploss: None,
+            tag: None,
+            broker_symbol: None,
+            symbol_token: None,
+        }
+    }
+
+    #[test]
+    fn test_trail_stop_buy() {
+        let b = bracket("BUY", Some(2.0));
+        // Less than one step - no move
+        assert_eq!(BracketService::trail_stop(&b, 101.5), None);
+        // Moves in whole steps
+        assert_eq!(BracketService::trail_stop(&b, 104.5), Some((99.0, 104.0)));
+        // Adverse moves never loosen the stop
+        assert_eq!(BracketService::trail_stop(&b, 97.0), None);
+        // No trailing configured
+        assert_eq!(BracketService::trail_stop(&bracket("BUY", None), 120.0), None);
+    }
+
+    #[test]
+    fn test_trail_stop_sell() {
+        let b = bracket("SELL", Some(1.0));
+        assert_eq!(BracketService::trail_stop(&b, 97.0), Some((102.0, 97.0)));
+        assert_eq!(BracketService::trail_stop(&b, 103.0), None);
+    }
+
+    #[test]
+    fn test_exit_gtt_orders_triggers() {
+        // BUY bracket: stop below, target above
+        let gtt = BracketService::exit_gtt(&bracket("BUY", None), 95.0, Some(110.0), 100.0);
+        assert_eq!(gtt.trigger_type, "two-leg");
+        assert_eq!(gtt.trigger_prices, vec![95.0, 110.0]);
+        assert_eq!(gtt.legs[0].order_type, "MARKET");
+        assert_eq!(gtt.legs[1].side, "SELL");
+
+        // SELL bracket: target below, stop above
+        let gtt = BracketService::exit_gtt(&bracket("SELL", None), 105.0, Some(90.0), 100.0);
+        assert_eq!(gtt.trigger_prices, vec![90.0, 105.0]);
+        assert_eq!(gtt.legs[0].order_type, "LIMIT");
+        assert_eq!(gtt.legs[1].side, "BUY");
+
+        // Cover order: stoploss only
+        let gtt = BracketService::exit_gtt(&bracket("BUY", None), 95.0, None, 100.0);
+        assert_eq!(gtt.trigger_type, "single");
+        assert_eq!(gtt.legs.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert!(BracketService::validate_request(&order("BO", Some(10.0), Some(5.0))).is_ok());
+        assert!(BracketService::validate_request(&order("CO", None, Some(5.0))).is_ok());
+        // BO needs a target, both need a stoploss
+        assert!(BracketService::validate_request(&order("BO", None, Some(5.0))).is_err());
+        assert!(BracketService::validate_request(&order("CO", None, None)).is_err());
+        // CO doesn't take a target
+        assert!(BracketService::validate_request(&order("CO", Some(10.0), Some(5.0))).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Chartink Service
+//!
+//! Turns a Chartink scan alert (a keyword plus a list of stocks) into orders.
+//! BUY and SHORT open positions using the strategy defaults or a per-stock
+//! override; SELL and COVER close the long or short position held in that
+//! stock. Entries are limited per alert and per stock per day.
+
+use crate::brokers::types::OrderRequest;
+use crate::db::sqlite::ChartinkStrategy;
+use crate::error::Result;
+use crate::services::{OrderService, PositionService, StrategyService};
+use crate::state::AppState;
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// Chartink alert keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartinkAction {
+    /// Open a long position
+    Buy,
+    /// Close a long position
+    Sell,
+    /// Open a short position
+    Short,
+    /// Close a short position
+    Cover,
+}
+
+impl ChartinkAction {
+    pub fn parse(keyword: &str) -> Option<Self> {
+        match keyword.trim().to_uppercase().as_str() {
+            "BUY" => Some(Self::Buy),
+            "SELL" => Some(Self::Sell),
+            "SHORT" => Some(Self::Short),
+            "COVER" => Some(Self::Cover),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Buy => "BUY",
+            Self::Sell => "SELL",
+            Self::Short => "SHORT",
+            Self::Cover => "COVER",
+        }
+    }
+
+    pub fn is_entry(&self) -> bool {
+        matches!(self, Self::Buy | Self::Short)
+    }
+
+    /// Order side sent to the broker
+    pub fn side(&self) -> &'static str {
+        match self {
+            Self::Buy | Self::Cover => "BUY",
+            Self::Sell | Self::Short => "SELL",
+        }
+    }
+}
+
+/// Result of processing a Chartink alert
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChartinkAlertResult {
+    pub order_ids: Vec<String>,
+    /// Stocks skipped by a guard (symbol limit, re-entry limit, nothing to exit)
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Chartink service for business logic
+pub struct ChartinkService;
+
+impl ChartinkService {
+    /// Place orders for a Chartink alert
+    pub async fn process_alert(
+        state: &AppState,
+        strategy: &ChartinkStrategy,
+        action: ChartinkAction,
+        symbols: &[String],
+    ) -> Result<ChartinkAlertResult> {
+        info!(
+            "ChartinkService::process_alert - {} {} {} stocks",
+            strategy.name,
+            action.as_str(),
+            symbols.len()
+        );
+
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        let mode = if analyze_mode { "analyze" } else { "live" };
+        let since = StrategyService::session_start_utc();
+
+        let mut result = ChartinkAlertResult::default();
+        let mut entries = 0;
+
+        for symbol in symbols {
+            let symbol = symbol.trim().to_uppercase();
+
+            // A noisy scan can't open more than the per-alert limit; exits are never capped
+            if action.is_entry() && entries >= strategy.max_symbols_per_alert {
+                result
+                    .skipped
+                    .push(format!("{}: exceeds {} symbols per alert", symbol, strategy.max_symbols_per_alert));
+                continue;
+            }
+
+            let (exchange, quantity, product) = match state.sqlite.get_chartink_symbol(strategy.id, &symbol) {
+                Ok(Some(o)) => (o.exchange, o.quantity, o.product.unwrap_or_else(|| strategy.product.clone())),
+                Ok(None) => (strategy.exchange.clone(), strategy.quantity, strategy.product.clone()),
+                Err(e) => {
+                    result.errors.push(format!("{}: {}", symbol, e));
+                    continue;
+                }
+            };
+
+            let quantity = if action.is_entry() {
+                if strategy.max_entries_per_day > 0 {
+                    let taken = state
+                        .sqlite
+                        .count_chartink_entries(strategy.id, &symbol, mode, &since)
+                        .unwrap_or(0);
+                    if taken >= strategy.max_entries_per_day {
+                        result.skipped.push(format!(
+                            "{}: re-entry limit of {} per day reached",
+                            symbol, strategy.max_entries_per_day
+                        ));
+                        continue;
+                    }
+                }
+                entries += 1;
+                quantity
+            } else {
+                let position = match PositionService::get_open_position(
+                    state, &exchange, &symbol, &product, None,
+                )
+                .await
+                {
+                    Ok(position) => position.map(|p| p.quantity).unwrap_or(0),
+                    Err(e) => {
+                        result.errors.push(format!("{}: {}", symbol, e));
+                        continue;
+                    }
+                };
+
+                let held = match action {
+                    ChartinkAction::Sell => position.max(0),
+                    _ => (-position).max(0),
+                };
+                if held == 0 {
+                    result.skipped.push(format!("{}: no position to {}", symbol, action.as_str()));
+                    continue;
+                }
+                held
+            };
+
+            let order = OrderRequest {
+                symbol: symbol.clone(),
+                exchange: exchange.clone(),
+                side: action.side().to_string(),
+                quantity,
+                price: 0.0,
+                order_type: "MARKET".to_string(),
+                product: product.clone(),
+                validity: "DAY".to_string(),
+                trigger_price: None,
+                disclosed_quantity: None,
+                amo: false,
+                target: None,
+                stoploss: None,
+                trailing_stoploss: None,
+                tag: Some(format!("chartink{}", strategy.id)),
+                broker_symbol: None,  // Set by OrderService from symbol cache
+                symbol_token: None,   // Set by OrderService from symbol cache
+            };
+
+            match OrderService::place_order(state, order, None).await {
+                Ok(placed) => {
+                    let children = placed.placed_orders(quantity);
+                    // The slices of one entry count once against max_entries_per_day
+                    let entry_id = children.first().map(|c| c.order_id.clone()).unwrap_or_default();
+                    for child in children {
+                        if let Err(e) = state.sqlite.record_chartink_order(
+                            strategy.id,
+                            &entry_id,
+                            &child.order_id,
+                            mode,
+                            &exchange,
+                            &symbol,
+                            &product,
+                            action.as_str(),
+                            child.quantity,
+                        ) {
+                            warn!("Failed to record Chartink order {}: {}", child.order_id, e);
+                        }
+                        result.order_ids.push(child.order_id);
+                    }
+                    result
+                        .errors
+                        .extend(placed.failed_slices.iter().map(|f| format!("{}: {}", symbol, f)));
+                }
+                Err(e) => result.errors.push(format!("{}: {}", symbol, e)),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_semantics() {
+        let buy = ChartinkAction::parse("buy").unwrap();
+        assert!(buy.is_entry());
+        assert_eq!(buy.side(), "BUY");
+
+        let short = ChartinkAction::parse("SHORT").unwrap();
+        assert!(short.is_entry());
+        assert_eq!(short.side(), "SELL");
+
+        let sell = ChartinkAction::parse("SELL").unwrap();
+        assert!(!sell.is_entry());
+        assert_eq!(sell.side(), "SELL");
+
+        let cover = ChartinkAction::parse("Cover").unwrap();
+        assert!(!cover.is_entry());
+        assert_eq!(cover.side(), "BUY");
+
+        assert!(ChartinkAction::parse("EXIT").is_none());
+    }
+}
@@ -0,0 +1,665 @@
+//! Exit Rule Service
+//!
+//! Position-level and portfolio-level exits: a target, a stoploss and/or a
+//! trailing stop, expressed as an absolute price, points or percent from the
+//! average price, or MTM rupees. Rules are persisted per mode and evaluated
+//! on ticks by the `ExitMonitor` against the live MTM book; a rule that hits
+//! closes its position (or every position, for portfolio rules) through
+//! `PositionService`.
+
+use crate::brokers::types::{ExitRule, Position};
+use crate::error::{AppError, Result};
+use crate::services::{PositionMtm, PositionService};
+use crate::state::AppState;
+use crate::websocket::MarketTick;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// How an exit rule measures a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitRuleType {
+    /// Absolute LTP levels
+    Price,
+    /// Points from the average price
+    Points,
+    /// Percent from the average price
+    Percent,
+    /// Rupee P&L of the position, or the day P&L for portfolio rules
+    Mtm,
+}
+
+impl ExitRuleType {
+    pub fn parse(rule_type: &str) -> Option<Self> {
+        match rule_type.trim().to_lowercase().as_str() {
+            "price" => Some(Self::Price),
+            "points" => Some(Self::Points),
+            "percent" => Some(Self::Percent),
+            "mtm" => Some(Self::Mtm),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Price => "price",
+            Self::Points => "points",
+            Self::Percent => "percent",
+            Self::Mtm => "mtm",
+        }
+    }
+}
+
+/// Which level of a rule was hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    Target,
+    Stoploss,
+    Trailing,
+}
+
+impl ExitReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Target => "target",
+            Self::Stoploss => "stoploss",
+            Self::Trailing => "trailing stop",
+        }
+    }
+}
+
+/// Request to create an exit rule
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExitRuleRequest {
+    /// position (default) or portfolio
+    #[serde(default = "default_scope")]
+    pub scope: String,
+    pub exchange: Option<String>,
+    pub symbol: Option<String>,
+    pub product: Option<String>,
+    /// price, points, percent or mtm (portfolio rules are always mtm)
+    pub rule_type: String,
+    pub target: Option<f64>,
+    pub stoploss: Option<f64>,
+    pub trailing: Option<f64>,
+}
+
+fn default_scope() -> String {
+    "position".to_string()
+}
+
+/// Event emitted when an exit rule fires or stops watching
+#[derive(Debug, Clone, Serialize)]
+pub struct ExitRuleEvent {
+    pub rule_id: String,
+    pub scope: String,
+    pub exchange: Option<String>,
+    pub symbol: Option<String>,
+    pub product: Option<String>,
+    pub status: String,
+    /// Measured value when the rule fired
+    pub value: Option<f64>,
+    pub message: String,
+}
+
+/// Exit rule service for business logic
+pub struct ExitRuleService;
+
+impl ExitRuleService {
+    /// Validate an exit rule request
+    ///
+    /// At least one of target, stoploss or trailing is needed, all positive.
+    /// Portfolio rules only watch the day MTM.
+    pub fn validate_request(request: &ExitRuleRequest) -> Result<ExitRuleType> {
+        let rule_type = ExitRuleType::parse(&request.rule_type).ok_or_else(|| {
+            AppError::Validation(format!(
+                "Unknown rule type '{}', expected price, points, percent or mtm",
+                request.rule_type
+            ))
+        })?;
+
+        match request.scope.as_str() {
+            "position" => {
+                let missing = |field: &Option<String>| field.as_deref().map_or(true, |v| v.trim().is_empty());
+                if missing(&request.exchange) || missing(&request.symbol) || missing(&request.product) {
+                    return Err(AppError::Validation(
+                        "Position rules need exchange, symbol and product".to_string(),
+                    ));
+                }
+            }
+            "portfolio" if rule_type != ExitRuleType::Mtm => {
+                return Err(AppError::Validation("Portfolio rules must be mtm rules".to_string()));
+            }
+            "portfolio" => {}
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Unknown scope '{}', expected position or portfolio",
+                    other
+                )));
+            }
+        }
+
+        let levels = [
+            ("target", request.target),
+            ("stoploss", request.stoploss),
+            ("trailing", request.trailing),
+        ];
+        if levels.iter().all(|(_, level)| level.is_none()) {
+            return Err(AppError::Validation(
+                "Set at least one of target, stoploss or trailing".to_string(),
+            ));
+        }
+        for (name, level) in levels {
+            if level.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+                return Err(AppError::Validation(format!("{} must be positive", name)));
+            }
+        }
+
+        Ok(rule_type)
+    }
+
+    /// Create an exit rule for the current mode (live/analyze)
+    ///
+    /// Position rules need the position to be open. Price levels must sit on
+    /// the right side of the LTP, or the rule would fire straight away.
+    pub async fn create(state: &AppState, request: ExitRuleRequest, api_key: Option<&str>) -> Result<ExitRule> {
+        info!("ExitRuleService::create - {:?}", request);
+
+        let rule_type = Self::validate_request(&request)?;
+        let mode = Self::current_mode(state);
+
+        let positions = PositionService::get_positions(state, api_key).await?;
+        // Refresh the book so the new rule is evaluated against current quantities
+        state.mtm.set_positions(&positions.positions, &positions.mode, |exchange, symbol| {
+            state.risk.ltp(exchange, symbol)
+        });
+
+        let (exchange, symbol, product) = if request.scope == "position" {
+            let position = Self::find_open_position(&positions.positions, &request).ok_or_else(|| {
+                AppError::Validation(format!(
+                    "No open {} position in {}:{}",
+                    request.product.as_deref().unwrap_or_default(),
+                    request.exchange.as_deref().unwrap_or_default(),
+                    request.symbol.as_deref().unwrap_or_default()
+                ))
+            })?;
+
+            if rule_type == ExitRuleType::Price {
+                let ltp = state
+                    .risk
+                    .ltp(&position.exchange, &position.symbol)
+                    .unwrap_or(position.ltp);
+                Self::validate_price_levels(&request, position.quantity, ltp)?;
+            }
+
+            (
+                Some(position.exchange.clone()),
+                Some(position.symbol.clone()),
+                Some(position.product.clone()),
+            )
+        } else {
+            (None, None, None)
+        };
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let rule = ExitRule {
+            rule_id: format!("EXIT{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_uppercase()),
+            scope: request.scope,
+            exchange,
+            symbol,
+            product,
+            rule_type: rule_type.as_str().to_string(),
+            target: request.target,
+            stoploss: request.stoploss,
+            trailing: request.trailing,
+            best_value: None,
+            status: "active".to_string(),
+            message: None,
+            triggered_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        state.sqlite.create_exit_rule(&rule, mode)?;
+        Ok(rule)
+    }
+
+    /// Get exit rules for the current mode (live/analyze)
+    pub fn get_exit_rules(state: &AppState) -> Result<Vec<ExitRule>> {
+        state.sqlite.get_exit_rules(Self::current_mode(state), false)
+    }
+
+    /// Get an exit rule by ID
+    pub fn get_exit_rule(state: &AppState, rule_id: &str) -> Result<ExitRule> {
+        state
+            .sqlite
+            .get_exit_rule(rule_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Exit rule {} not found", rule_id)))
+    }
+
+    /// Cancel an active exit rule; the position is left as it is
+    pub fn cancel(state: &AppState, rule_id: &str) -> Result<ExitRuleEvent> {
+        info!("ExitRuleService::cancel - {}", rule_id);
+
+        let rule = Self::get_exit_rule(state, rule_id)?;
+        let message = "Cancelled by user".to_string();
+        if !state.sqlite.finish_exit_rule(rule_id, "cancelled", Some(&message))? {
+            return Err(AppError::Validation(format!(
+                "Exit rule {} is no longer active ({})",
+                rule_id, rule.status
+            )));
+        }
+
+        Ok(Self::event(&rule, "cancelled", None, message))
+    }
+
+    /// Cancel position rules whose position has gone flat
+    ///
+    /// Only trusts the MTM book when it was loaded in the rules' mode, so a
+    /// mode switch never cancels rules against the other mode's positions.
+    pub fn sync_positions(state: &AppState, rules: &[ExitRule]) -> Vec<ExitRuleEvent> {
+        if !state.mtm.is_loaded_for(Self::current_mode(state)) {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        for rule in rules {
+            let Some(position) = Self::position_for(state, rule) else {
+                continue;
+            };
+            if position.quantity != 0 {
+                continue;
+            }
+
+            let message = "Position closed".to_string();
+            match state.sqlite.finish_exit_rule(&rule.rule_id, "cancelled", Some(&message)) {
+                Ok(true) => events.push(Self::event(rule, "cancelled", None, message)),
+                Ok(false) => {}
+                Err(e) => error!("Failed to update exit rule {}: {}", rule.rule_id, e),
+            }
+        }
+
+        events
+    }
+
+    /// Evaluate active rules against the MTM book
+    ///
+    /// With a tick, only rules on the tick's symbol are checked, at the tick's
+    /// LTP; without one, every rule is checked at the book's prices. Updates
+    /// best values in place and returns the rules that hit, with the reason
+    /// and the measured value.
+    pub fn evaluate_rules(
+        state: &AppState,
+        rules: &mut [ExitRule],
+        tick: Option<&MarketTick>,
+    ) -> Vec<(ExitRule, ExitReason, f64)> {
+        if !state.mtm.is_loaded_for(Self::current_mode(state)) {
+            return Vec::new();
+        }
+
+        let tick = tick.filter(|t| t.ltp > 0.0).map(|t| {
+            let symbol = state
+                .get_symbol_by_token(&t.exchange, &t.token)
+                .map(|s| s.symbol)
+                .unwrap_or_else(|| t.symbol.clone());
+            (t.exchange.as_str(), symbol, t.ltp)
+        });
+
+        let mut hits = Vec::new();
+        for rule in rules.iter_mut() {
+            let measured = if rule.scope == "portfolio" {
+                Some((state.mtm.day_pnl(), 1.0))
+            } else {
+                Self::position_for(state, rule).and_then(|position| {
+                    let ltp = match &tick {
+                        Some((exchange, symbol, ltp))
+                            if rule.exchange.as_deref() == Some(*exchange)
+                                && rule.symbol.as_deref() == Some(symbol.as_str()) =>
+                        {
+                            *ltp
+                        }
+                        Some(_) => return None,
+                        None => position.ltp,
+                    };
+                    let direction = (position.quantity as f64).signum();
+                    Self::measure(rule, &position, ltp).map(|value| (value, direction))
+                })
+            };
+
+            let Some((value, direction)) = measured else {
+                continue;
+            };
+            if let Some(reason) = Self::evaluate(rule, value, direction) {
+                hits.push((rule.clone(), reason, value));
+            }
+        }
+
+        hits
+    }
+
+    /// Mark a rule that hit as triggered
+    ///
+    /// Returns the trigger message, or None if the rule was no longer
+    /// active. Claiming first means a burst of ticks can't fire it twice.
+    pub fn claim(state: &AppState, rule: &ExitRule, reason: ExitReason, value: f64) -> Option<String> {
+        let message = format!("{} hit at {:.2} ({})", reason.as_str(), value, rule.rule_type);
+        match state.sqlite.finish_exit_rule(&rule.rule_id, "triggered", Some(&message)) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => {
+                error!("Failed to claim exit rule {}: {}", rule.rule_id, e);
+                return None;
+            }
+        }
+
+        info!("Exit rule {} triggered: {}", rule.rule_id, message);
+        Some(message)
+    }
+
+    /// Close the position(s) of a claimed rule
+    pub async fn close(state: &AppState, rule: &ExitRule, value: f64, message: String) -> ExitRuleEvent {
+        let errors = match (&rule.exchange, &rule.symbol, &rule.product) {
+            (Some(exchange), Some(symbol), Some(product)) if rule.scope == "position" => {
+                match PositionService::close_position(state, exchange, symbol, product, None).await {
+                    Ok(result) if result.success => Vec::new(),
+                    Ok(result) => vec![result.message],
+                    Err(e) => vec![e.to_string()],
+                }
+            }
+            _ => match PositionService::close_all_positions(state, None).await {
+                Ok(results) => results.into_iter().filter(|r| !r.success).map(|r| r.message).collect(),
+                Err(e) => vec![e.to_string()],
+            },
+        };
+
+        if errors.is_empty() {
+            return Self::event(rule, "triggered", Some(value), message);
+        }
+
+        let message = format!("{}, close failed: {}", message, errors.join("; "));
+        warn!("Exit rule {}: {}", rule.rule_id, message);
+        if let Err(e) = state.sqlite.set_exit_rule_status(&rule.rule_id, "error", Some(&message)) {
+            error!("Failed to update exit rule {}: {}", rule.rule_id, e);
+        }
+        Self::event(rule, "error", Some(value), message)
+    }
+
+    /// Measure a position in the rule's units at an LTP
+    ///
+    /// Points and percent are signed so that a gain is positive for both long
+    /// and short positions.
+    pub fn measure(rule: &ExitRule, position: &PositionMtm, ltp: f64) -> Option<f64> {
+        if position.quantity == 0 || ltp <= 0.0 {
+            return None;
+        }
+
+        let direction = (position.quantity as f64).signum();
+        match ExitRuleType::parse(&rule.rule_type)? {
+            ExitRuleType::Price => Some(ltp),
+            ExitRuleType::Points => Some((ltp - position.average_price) * direction),
+            ExitRuleType::Percent if position.average_price > 0.0 => {
+                Some((ltp - position.average_price) / position.average_price * 100.0 * direction)
+            }
+            ExitRuleType::Percent => None,
+            ExitRuleType::Mtm => {
+                Some(position.realized_pnl + (ltp - position.average_price) * position.quantity as f64)
+            }
+        }
+    }
+
+    /// Check a measured value against a rule's levels
+    ///
+    /// `direction` is +1 for long and -1 for short; it only matters for price
+    /// rules, whose levels are absolute prices. For the other types target is
+    /// a gain and stoploss a loss, both positive. The trailing stop follows
+    /// the best value seen at a fixed distance and never loosens. Records a
+    /// new best value on the rule and returns the level that was hit, if any.
+    pub fn evaluate(rule: &mut ExitRule, value: f64, direction: f64) -> Option<ExitReason> {
+        let is_price = rule.rule_type == ExitRuleType::Price.as_str();
+        let orient = if is_price { direction } else { 1.0 };
+        // Favourable value: higher is always better
+        let favourable = value * orient;
+
+        if let Some(trailing) = rule.trailing {
+            let best = rule.best_value.map(|b| b * orient).unwrap_or(favourable).max(favourable);
+            rule.best_value = Some(best * orient);
+            if favourable <= best - trailing {
+                return Some(ExitReason::Trailing);
+            }
+        }
+
+        if let Some(stoploss) = rule.stoploss {
+            let level = if is_price { stoploss * orient } else { -stoploss };
+            if favourable <= level {
+                return Some(ExitReason::Stoploss);
+            }
+        }
+
+        if let Some(target) = rule.target {
+            let level = if is_price { target * orient } else { target };
+            if favourable >= level {
+                return Some(ExitReason::Target);
+            }
+        }
+
+        None
+    }
+
+    // ========================================================================
+    // Private Helper Methods
+    // ========================================================================
+
+    fn current_mode(state: &AppState) -> &'static str {
+        if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        }
+    }
+
+    fn find_open_position<'a>(positions: &'a [Position], request: &ExitRuleRequest) -> Option<&'a Position> {
+        let matches = |field: &str, value: &Option<String>| {
+            value.as_deref().is_some_and(|v| field.eq_ignore_ascii_case(v.trim()))
+        };
+        positions.iter().find(|p| {
+            p.quantity != 0
+                && matches(&p.exchange, &request.exchange)
+                && matches(&p.symbol, &request.symbol)
+                && matches(&p.product, &request.product)
+        })
+    }
+
+    /// Price targets must be beyond the LTP in the position's favour, stops against it
+    fn validate_price_levels(request: &ExitRuleRequest, quantity: i32, ltp: f64) -> Result<()> {
+        if ltp <= 0.0 {
+            return Ok(());
+        }
+
+        let long = quantity > 0;
+        if let Some(target) = request.target {
+            if (long && target <= ltp) || (!long && target >= ltp) {
+                return Err(AppError::Validation(format!(
+                    "Target {} must be {} the LTP {} for a {} position",
+                    target,
+                    if long { "above" } else { "below" },
+                    ltp,
+                    if long { "long" } else { "short" }
+                )));
+            }
+        }
+        if let Some(stoploss) = request.stoploss {
+            if (long && stoploss >= ltp) || (!long && stoploss <= ltp) {
+                return Err(AppError::Validation(format!(
+                    "Stoploss {} must be {} the LTP {} for a {} position",
+                    stoploss,
+                    if long { "below" } else { "above" },
+                    ltp,
+                    if long { "long" } else { "short" }
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn position_for(state: &AppState, rule: &ExitRule) -> Option<PositionMtm> {
+        match (&rule.exchange, &rule.symbol, &rule.product) {
+            (Some(exchange), Some(symbol), Some(product)) => state.mtm.position(exchange, symbol, product),
+            _ => None,
+        }
+    }
+
+    fn event(rule: &ExitRule, status: &str, value: Option<f64>, message: String) -> ExitRuleEvent {
+        ExitRuleEvent {
+            rule_id: rule.rule_id.clone(),
+            scope: rule.scope.clone(),
+            exchange: rule.exchange.clone(),
+            symbol: rule.symbol.clone(),
+            product: rule.product.clone(),
+            status: status.to_string(),
+            value,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(rule_type: &str, target: Option<f64>, stoploss: Option<f64>, trailing: Option<f64>) -> ExitRule {
+        ExitRule {
+            rule_id: "EXIT1".to_string(),
+            scope: "position".to_string(),
+            exchange: Some("NSE".to_string()),
+            symbol: Some("SBIN".to_string()),
+            product: Some("MIS".to_string()),
+            rule_type: rule_type.to_string(),
+            target,
+            stoploss,
+            trailing,
+            best_value: None,
+            status: "active".to_string(),
+            message: None,
+            triggered_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn position(quantity: i32, average_price: f64) -> PositionMtm {
+        PositionMtm {
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            product: "MIS".to_string(),
+            quantity,
+            average_price,
+            ltp: average_price,
+            realized_pnl: 100.0,
+            unrealized_pnl: 0.0,
+            pnl: 100.0,
+        }
+    }
+
+    fn request(scope: &str, rule_type: &str, target: Option<f64>) -> ExitRuleRequest {
+        ExitRuleRequest {
+            scope: scope.to_string(),
+            exchange: Some("NSE".to_string()),
+            symbol: Some("SBIN".to_string()),
+            product: Some("MIS".to_string()),
+            rule_type: rule_type.to_string(),
+            target,
+            stoploss: None,
+            trailing: None,
+        }
+    }
+
+    #[test]
+    fn test_measure() {
+        let long = position(10, 800.0);
+        let short = position(-10, 800.0);
+        let points = rule("points", None, None, None);
+        assert_eq!(ExitRuleService::measure(&points, &long, 810.0), Some(10.0));
+        assert_eq!(ExitRuleService::measure(&points, &short, 810.0), Some(-10.0));
+        assert_eq!(ExitRuleService::measure(&rule("percent", None, None, None), &short, 600.0), Some(25.0));
+        // Realized P&L plus the open position re-marked
+        assert_eq!(ExitRuleService::measure(&rule("mtm", None, None, None), &long, 790.0), Some(0.0));
+        assert_eq!(ExitRuleService::measure(&points, &position(0, 800.0), 810.0), None);
+    }
+
+    #[test]
+    fn test_evaluate_price_levels() {
+        // Long: target above, stop below
+        let mut r = rule("price", Some(820.0), Some(790.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, 805.0, 1.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, 820.5, 1.0), Some(ExitReason::Target));
+        assert_eq!(ExitRuleService::evaluate(&mut r, 789.0, 1.0), Some(ExitReason::Stoploss));
+
+        // Short: target below, stop above
+        let mut r = rule("price", Some(780.0), Some(810.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, 805.0, -1.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, 779.0, -1.0), Some(ExitReason::Target));
+        assert_eq!(ExitRuleService::evaluate(&mut r, 811.0, -1.0), Some(ExitReason::Stoploss));
+    }
+
+    #[test]
+    fn test_evaluate_gain_loss_levels() {
+        let mut r = rule("mtm", Some(5000.0), Some(2000.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, -1999.0, 1.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, -2000.0, 1.0), Some(ExitReason::Stoploss));
+        assert_eq!(ExitRuleService::evaluate(&mut r, 5000.0, 1.0), Some(ExitReason::Target));
+        // Direction doesn't flip gains, they are already signed
+        assert_eq!(ExitRuleService::evaluate(&mut r, 5000.0, -1.0), Some(ExitReason::Target));
+    }
+
+    #[test]
+    fn test_evaluate_trailing() {
+        let mut r = rule("points", None, None, Some(5.0));
+        assert_eq!(ExitRuleService::evaluate(&mut r, 2.0, 1.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, 12.0, 1.0), None);
+        assert_eq!(r.best_value, Some(12.0));
+        // Pullbacks don't move the best value
+        assert_eq!(ExitRuleService::evaluate(&mut r, 8.0, 1.0), None);
+        assert_eq!(r.best_value, Some(12.0));
+        assert_eq!(ExitRuleService::evaluate(&mut r, 7.0, 1.0), Some(ExitReason::Trailing));
+
+        // Short price rule trails the lowest price
+        let mut r = rule("price", None, None, Some(3.0));
+        assert_eq!(ExitRuleService::evaluate(&mut r, 800.0, -1.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, 790.0, -1.0), None);
+        assert_eq!(r.best_value, Some(790.0));
+        assert_eq!(ExitRuleService::evaluate(&mut r, 792.0, -1.0), None);
+        assert_eq!(ExitRuleService::evaluate(&mut r, 793.0, -1.0), Some(ExitReason::Trailing));
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert_eq!(
+            ExitRuleService::validate_request(&request("position", "percent", Some(2.0))).unwrap(),
+            ExitRuleType::Percent
+        );
+        assert!(ExitRuleService::validate_request(&request("portfolio", "mtm", Some(5000.0))).is_ok());
+        // Portfolio rules are MTM only
+        assert!(ExitRuleService::validate_request(&request("portfolio", "points", Some(5.0))).is_err());
+        // Needs a level, and levels are positive
+        assert!(ExitRuleService::validate_request(&request("position", "points", None)).is_err());
+        assert!(ExitRuleService::validate_request(&request("position", "points", Some(-1.0))).is_err());
+        assert!(ExitRuleService::validate_request(&request("position", "ticks", Some(1.0))).is_err());
+        assert!(ExitRuleService::validate_request(&request("account", "mtm", Some(1.0))).is_err());
+
+        let mut no_symbol = request("position", "points", Some(5.0));
+        no_symbol.symbol = None;
+        assert!(ExitRuleService::validate_request(&no_symbol).is_err());
+    }
+
+    #[test]
+    fn test_validate_price_levels() {
+        let mut req = request("position", "price", Some(820.0));
+        req.stoploss = Some(790.0);
+        assert!(ExitRuleService::validate_price_levels(&req, 10, 800.0).is_ok());
+        // Same levels are on the wrong side for a short
+        assert!(ExitRuleService::validate_price_levels(&req, -10, 800.0).is_err());
+        // A target already crossed would fire immediately
+        assert!(ExitRuleService::validate_price_levels(&req, 10, 825.0).is_err());
+    }
+}
@@ -0,0 +1,668 @@
+//! GTT Service
+//!
+//! Handles good-till-triggered orders (single trigger and two-leg OCO).
+//! GTTs are held natively by brokers that support them (Zerodha, Angel
+//! single-leg rules). Everything else - Fyers, OCO on Angel, and all
+//! sandbox GTTs - is emulated locally: stored in SQLite and fired by the
+//! `GttMonitor` when a tick crosses the trigger.
+
+use crate::brokers::types::{GttOrder, GttRequest, OrderRequest};
+use crate::error::{AppError, Result};
+use crate::services::OrderService;
+use crate::state::AppState;
+use crate::websocket::{MarketTick, SubscriptionMode, SubscriptionRequest};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Result of placing, modifying or cancelling a GTT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GttResult {
+    pub success: bool,
+    pub gtt_id: String,
+    pub message: String,
+    pub mode: String, // "live" or "analyze"
+}
+
+/// Result of getting the GTT book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GttBookResult {
+    pub success: bool,
+    pub gtt_orders: Vec<GttOrder>,
+    pub mode: String,
+}
+
+/// Event emitted when an emulated GTT fires
+#[derive(Debug, Clone, Serialize)]
+pub struct GttTriggeredEvent {
+    pub gtt_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub leg: usize,
+    pub ltp: f64,
+    pub success: bool,
+    pub order_id: Option<String>,
+    pub message: String,
+}
+
+/// GTT service for business logic
+pub struct GttService;
+
+impl GttService {
+    /// Place a GTT order
+    ///
+    /// In analyze mode, or when the broker can't hold this trigger type,
+    /// the GTT is emulated locally.
+    pub async fn place_gtt(
+        state: &AppState,
+        gtt: GttRequest,
+        api_key: Option<&str>,
+    ) -> Result<GttResult> {
+        info!("GttService::place_gtt - {:?}", gtt);
+
+        let mut gtt = Self::validate_request(gtt)?;
+
+        // Check if in analyze mode
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+
+        if analyze_mode {
+            return Self::place_emulated_gtt(state, gtt, "analyze").await;
+        }
+
+        let (auth_token, broker_id) = Self::get_auth(state, api_key)?;
+
+        let broker = state
+            .brokers
+            .get(&broker_id)
+            .ok_or_else(|| AppError::Broker(format!("Broker '{}' not found", broker_id)))?;
+
+        if !broker.supports_gtt(&gtt) {
+            info!("{} can't hold this {} GTT natively, emulating locally", broker.name(), gtt.trigger_type);
+            return Self::place_emulated_gtt(state, gtt, "live").await;
+        }
+
+        Self::resolve_symbol(state, &mut gtt);
+
+        match broker.place_gtt(&auth_token, gtt).await {
+            Ok(gtt_id) => Ok(GttResult {
+                success: true,
+                gtt_id,
+                message: "GTT placed successfully".to_string(),
+                mode: "live".to_string(),
+            }),
+            Err(e) => {
+                error!("Failed to place GTT: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Modify an existing GTT order
+    pub async fn modify_gtt(
+        state: &AppState,
+        gtt_id: &str,
+        gtt: GttRequest,
+        api_key: Option<&str>,
+    ) -> Result<GttResult> {
+        info!("GttService::modify_gtt - {} {:?}", gtt_id, gtt);
+
+        let mut gtt = Self::validate_request(gtt)?;
+
+        // Emulated GTTs are modified locally
+        if let Some(existing) = state.sqlite.get_gtt_order(gtt_id)? {
+            if existing.symbol != gtt.symbol || existing.exchange != gtt.exchange {
+                return Err(AppError::Validation(
+                    "Symbol and exchange of a GTT cannot be changed".to_string(),
+                ));
+            }
+            if !state.sqlite.update_gtt_order(gtt_id, &gtt)? {
+                return Err(AppError::Validation(format!(
+                    "GTT {} is no longer active ({})",
+                    gtt_id, existing.status
+                )));
+            }
+            return Ok(GttResult {
+                success: true,
+                gtt_id: gtt_id.to_string(),
+                message: "GTT modified successfully".to_string(),
+                mode: Self::current_mode(state).to_string(),
+            });
+        }
+
+        if Self::current_mode(state) == "analyze" {
+            return Err(AppError::NotFound(format!("GTT {} not found in sandbox", gtt_id)));
+        }
+
+        let (auth_token, broker_id) = Self::get_auth(state, api_key)?;
+
+        let broker = state
+            .brokers
+            .get(&broker_id)
+            .ok_or_else(|| AppError::Broker(format!("Broker '{}' not found", broker_id)))?;
+
+        Self::resolve_symbol(state, &mut gtt);
+
+        match broker.modify_gtt(&auth_token, gtt_id, gtt).await {
+            Ok(gtt_id) => Ok(GttResult {
+                success: true,
+                gtt_id,
+                message: "GTT modified successfully".to_string(),
+                mode: "live".to_string(),
+            }),
+            Err(e) => {
+                error!("Failed to modify GTT: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Cancel (delete) a GTT order
+    pub async fn cancel_gtt(
+        state: &AppState,
+        gtt_id: &str,
+        api_key: Option<&str>,
+    ) -> Result<GttResult> {
+        info!("GttService::cancel_gtt - {}", gtt_id);
+
+        // Emulated GTTs are cancelled locally
+        if let Some(existing) = state.sqlite.get_gtt_order(gtt_id)? {
+            if !state.sqlite.transition_gtt_order(gtt_id, "cancelled")? {
+                return Err(AppError::Validation(format!(
+                    "GTT {} is no longer active ({})",
+                    gtt_id, existing.status
+                )));
+            }
+            return Ok(GttResult {
+                success: true,
+                gtt_id: gtt_id.to_string(),
+                message: "GTT cancelled successfully".to_string(),
+                mode: Self::current_mode(state).to_string(),
+            });
+        }
+
+        if Self::current_mode(state) == "analyze" {
+            return Err(AppError::NotFound(format!("GTT {} not found in sandbox", gtt_id)));
+        }
+
+        let (auth_token, broker_id) = Self::get_auth(state, api_key)?;
+
+        let broker = state
+            .brokers
+            .get(&broker_id)
+            .ok_or_else(|| AppError::Broker(format!("Broker '{}' not found", broker_id)))?;
+
+        broker.delete_gtt(&auth_token, gtt_id).await?;
+
+        Ok(GttResult {
+            success: true,
+            gtt_id: gtt_id.to_string(),
+            message: "GTT cancelled successfully".to_string(),
+            mode: "live".to_string(),
+        })
+    }
+
+    /// Get GTT book
+    ///
+    /// In analyze mode, returns sandbox GTTs.
+    /// Otherwise, returns the broker's GTTs plus locally emulated ones.
+    pub async fn get_gtt_orders(
+        state: &AppState,
+        api_key: Option<&str>,
+    ) -> Result<GttBookResult> {
+        info!("GttService::get_gtt_orders");
+
+        let mode = Self::current_mode(state);
+        let mut gtt_orders = state.sqlite.get_gtt_orders(mode, false)?;
+
+        if mode == "live" {
+            let (auth_token, broker_id) = Self::get_auth(state, api_key)?;
+
+            let broker = state
+                .brokers
+                .get(&broker_id)
+                .ok_or_else(|| AppError::Broker(format!("Broker '{}' not found", broker_id)))?;
+
+            let mut native = broker.get_gtt_orders(&auth_token).await?;
+            native.append(&mut gtt_orders);
+            gtt_orders = native;
+        }
+
+        Ok(GttBookResult {
+            success: true,
+            gtt_orders,
+            mode: mode.to_string(),
+        })
+    }
+
+    /// Place an emulated GTT on behalf of another service
+    ///
+    /// Used by `BracketService` to hold the exit legs of an emulated bracket
+    /// or cover order. Returns the GTT ID.
+    pub async fn place_linked_gtt(state: &AppState, gtt: GttRequest, mode: &str) -> Result<String> {
+        let gtt = Self::validate_request(gtt)?;
+        let result = Self::place_emulated_gtt(state, gtt, mode).await?;
+        Ok(result.gtt_id)
+    }
+
+    /// Subscribe the symbols of active emulated GTTs for the current mode
+    ///
+    /// Called after the market data socket connects, so GTTs reloaded from
+    /// SQLite after a restart keep receiving ticks.
+    pub async fn subscribe_active(state: &AppState) {
+        match state.sqlite.get_gtt_orders(Self::current_mode(state), true) {
+            Ok(active) => Self::subscribe_symbols(state, &active).await,
+            Err(e) => warn!("Failed to load active GTTs: {}", e),
+        }
+    }
+
+    /// Subscribe GTT symbols not yet streaming on the current connection
+    ///
+    /// The monitor only sees ticks for subscribed symbols.
+    pub async fn subscribe_symbols(state: &AppState, gtts: &[GttOrder]) {
+        if !state.websocket.is_connected() {
+            return;
+        }
+
+        let mut requests: Vec<SubscriptionRequest> = Vec::new();
+        for gtt in gtts {
+            let Some(symbol_info) = state.get_symbol_by_name(&gtt.exchange, &gtt.symbol) else {
+                continue;
+            };
+            if state.websocket.is_subscribed(&gtt.exchange, &symbol_info.token)
+                || requests.iter().any(|r| r.exchange == gtt.exchange && r.token == symbol_info.token)
+            {
+                continue;
+            }
+            state.websocket.register_symbol(&symbol_info.token, &gtt.symbol, &gtt.exchange);
+            requests.push(SubscriptionRequest {
+                exchange: gtt.exchange.clone(),
+                token: symbol_info.token,
+                mode: SubscriptionMode::Ltp,
+            });
+        }
+
+        if requests.is_empty() {
+            return;
+        }
+        info!("Subscribing {} GTT symbols", requests.len());
+        if let Err(e) = state.websocket.subscribe(requests).await {
+            warn!("Failed to subscribe GTT symbols: {}", e);
+        }
+    }
+
+    /// Check a tick against active emulated GTTs and claim any that trigger
+    ///
+    /// Called by the GTT monitor for every tick. `active` is the monitor's
+    /// cached list of active GTTs for the current mode. Returns the claimed
+    /// GTTs with the leg that fired; the caller places them via `fire_gtt`.
+    pub fn process_tick(state: &AppState, active: &[GttOrder], tick: &MarketTick) -> Vec<(GttOrder, usize)> {
+        let symbol = Self::tick_symbol(state, tick);
+        let mut triggered = Vec::new();
+
+        for gtt in active {
+            if gtt.symbol != symbol || gtt.exchange != tick.exchange {
+                continue;
+            }
+
+            let Some(leg_index) = Self::check_trigger(gtt, tick.ltp) else {
+                continue;
+            };
+
+            // Claim the GTT first so a burst of ticks can't fire it twice
+            match state.sqlite.transition_gtt_order(&gtt.gtt_id, "triggered") {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    error!("Failed to claim GTT {}: {}", gtt.gtt_id, e);
+                    continue;
+                }
+            }
+
+            triggered.push((gtt.clone(), leg_index));
+        }
+
+        triggered
+    }
+
+    /// Determine which leg (if any) a price triggers
+    ///
+    /// Single: the trigger direction is set by where the trigger sat relative
+    /// to the price when the GTT was created. Two-leg: leg 0 fires at or below
+    /// the lower (stoploss) trigger, leg 1 at or above the upper (target) trigger.
+    pub fn check_trigger(gtt: &GttOrder, ltp: f64) -> Option<usize> {
+        if ltp <= 0.0 {
+            return None;
+        }
+
+        match gtt.trigger_type.as_str() {
+            "two-leg" => {
+                let lower = *gtt.trigger_prices.first()?;
+                let upper = *gtt.trigger_prices.get(1)?;
+                if ltp <= lower {
+                    Some(0)
+                } else if ltp >= upper {
+                    Some(1)
+                } else {
+                    None
+                }
+            }
+            _ => {
+                let trigger = *gtt.trigger_prices.first()?;
+                let crossed = if trigger >= gtt.last_price {
+                    ltp >= trigger
+                } else {
+                    ltp <= trigger
+                };
+                crossed.then_some(0)
+            }
+        }
+    }
+
+    // ========================================================================
+    // Private Helper Methods
+    // ========================================================================
+
+    fn get_auth(state: &AppState, api_key: Option<&str>) -> Result<(String, String)> {
+        match api_key {
+            Some(key) => {
+                let _api_key_info = state.sqlite.validate_api_key(key, &state.security)?;
+                let session = state
+                    .get_broker_session()
+                    .ok_or_else(|| AppError::Auth("Broker not connected".to_string()))?;
+                Ok((session.auth_token, session.broker_id))
+            }
+            None => {
+                let session = state
+                    .get_broker_session()
+                    .ok_or_else(|| AppError::Auth("Broker not connected".to_string()))?;
+                Ok((session.auth_token, session.broker_id))
+            }
+        }
+    }
+
+    fn current_mode(state: &AppState) -> &'static str {
+        if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        }
+    }
+
+    /// Validate a GTT request and normalize its casing
+    fn validate_request(mut gtt: GttRequest) -> Result<GttRequest> {
+        gtt.trigger_type = match gtt.trigger_type.to_lowercase().as_str() {
+            "single" => "single".to_string(),
+            "two-leg" | "oco" => "two-leg".to_string(),
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Invalid trigger type '{}', expected single or two-leg",
+                    other
+                )));
+            }
+        };
+
+        let expected = if gtt.trigger_type == "single" { 1 } else { 2 };
+        if gtt.trigger_prices.len() != expected || gtt.legs.len() != expected {
+            return Err(AppError::Validation(format!(
+                "A {} GTT needs {} trigger price(s) and {} leg(s)",
+                gtt.trigger_type, expected, expected
+            )));
+        }
+
+        if gtt.trigger_prices.iter().any(|p| *p <= 0.0) {
+            return Err(AppError::Validation("Trigger prices must be positive".to_string()));
+        }
+
+        if gtt.last_price <= 0.0 {
+            return Err(AppError::Validation("Last price is required".to_string()));
+        }
+
+        if gtt.trigger_type == "two-leg" {
+            let (lower, upper) = (gtt.trigger_prices[0], gtt.trigger_prices[1]);
+            if lower >= upper {
+                return Err(AppError::Validation(
+                    "Stoploss trigger must be below target trigger".to_string(),
+                ));
+            }
+            if gtt.last_price <= lower || gtt.last_price >= upper {
+                return Err(AppError::Validation(
+                    "Last price must lie between the two triggers".to_string(),
+                ));
+            }
+        } else if gtt.trigger_prices[0] == gtt.last_price {
+            return Err(AppError::Validation(
+                "Trigger price must differ from last price".to_string(),
+            ));
+        }
+
+        for leg in &mut gtt.legs {
+            leg.side = leg.side.to_uppercase();
+            leg.order_type = leg.order_type.to_uppercase();
+            leg.product = leg.product.to_uppercase();
+
+            if leg.side != "BUY" && leg.side != "SELL" {
+                return Err(AppError::Validation(format!("Invalid action '{}'", leg.side)));
+            }
+            if leg.quantity <= 0 {
+                return Err(AppError::Validation("Leg quantity must be positive".to_string()));
+            }
+            match leg.order_type.as_str() {
+                "LIMIT" if leg.price <= 0.0 => {
+                    return Err(AppError::Validation("LIMIT legs need a price".to_string()));
+                }
+                "LIMIT" | "MARKET" => {}
+                other => {
+                    return Err(AppError::Validation(format!(
+                        "Invalid GTT leg order type '{}', expected LIMIT or MARKET",
+                        other
+                    )));
+                }
+            }
+        }
+
+        Ok(gtt)
+    }
+
+    /// Fill in broker symbol and token from the symbol cache
+    fn resolve_symbol(state: &AppState, gtt: &mut GttRequest) {
+        if let Some(symbol_info) = state.get_symbol_by_name(&gtt.exchange, &gtt.symbol) {
+            gtt.broker_symbol = symbol_info.brsymbol;
+            gtt.symbol_token = Some(symbol_info.token);
+        } else {
+            warn!("Symbol not found in cache: {}:{}", gtt.exchange, gtt.symbol);
+        }
+    }
+
+    /// Store a GTT for local emulation and make sure its symbol is streaming
+    async fn place_emulated_gtt(state: &AppState, gtt: GttRequest, mode: &str) -> Result<GttResult> {
+        let gtt_id = format!("GTT{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_uppercase());
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let order = GttOrder {
+            gtt_id: gtt_id.clone(),
+            symbol: gtt.symbol.clone(),
+            exchange: gtt.exchange.clone(),
+            trigger_type: gtt.trigger_type,
+            trigger_prices: gtt.trigger_prices,
+            last_price: gtt.last_price,
+            legs: gtt.legs,
+            status: "active".to_string(),
+            order_id: None,
+            emulated: true,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        state.sqlite.create_gtt_order(&order, mode)?;
+
+        if state.websocket.is_connected() {
+            Self::subscribe_symbols(state, std::slice::from_ref(&order)).await;
+        } else {
+            warn!("WebSocket not connected, GTT {} will fire once ticks arrive", gtt_id);
+        }
+
+        Ok(GttResult {
+            success: true,
+            gtt_id,
+            message: "GTT placed (emulated)".to_string(),
+            mode: mode.to_string(),
+        })
+    }
+
+    /// Place the order for a claimed leg and record the outcome
+    pub async fn fire_gtt(state: &AppState, gtt: &GttOrder, leg_index: usize, ltp: f64) -> GttTriggeredEvent {
+        let leg = &gtt.legs[leg_index];
+        info!("GTT {} triggered at {} (leg {})", gtt.gtt_id, ltp, leg_index);
+
+        let order = OrderRequest {
+            symbol: gtt.symbol.clone(),
+            exchange: gtt.exchange.clone(),
+            side: leg.side.clone(),
+            quantity: leg.quantity,
+            // Sandbox fills market orders at the order price
+            price: if leg.order_type == "MARKET" && Self::current_mode(state) == "analyze" {
+                ltp
+            } else {
+                leg.price
+            },
+            order_type: leg.order_type.clone(),
+            product: leg.product.clone(),
+            validity: "DAY".to_string(),
+            trigger_price: None,
+            disclosed_quantity: None,
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
+            broker_symbol: None, // Set by OrderService from symbol cache
+            symbol_token: None,  // Set by OrderService from symbol cache
+        };
+
+        let (success, order_id, message) = match OrderService::place_order(state, order, None).await {
+            Ok(result) => (true, result.order_id, result.message),
+            Err(e) => (false, None, e.to_string()),
+        };
+
+        let status = if success { "triggered" } else { "rejected" };
+        if let Err(e) = state.sqlite.set_gtt_order_result(&gtt.gtt_id, status, order_id.as_deref(), Some(&message)) {
+            error!("Failed to record GTT {} result: {}", gtt.gtt_id, e);
+        }
+
+        GttTriggeredEvent {
+            gtt_id: gtt.gtt_id.clone(),
+            symbol: gtt.symbol.clone(),
+            exchange: gtt.exchange.clone(),
+            leg: leg_index,
+            ltp,
+            success,
+            order_id,
+            message,
+        }
+    }
+
+    /// Resolve the trading symbol of a tick (ticks may only carry the token)
+    fn tick_symbol(state: &AppState, tick: &MarketTick) -> String {
+        state
+            .get_symbol_by_token(&tick.exchange, &tick.token)
+            .map(|s| s.symbol)
+            .unwrap_or_else(|| tick.symbol.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::types::GttLeg;
+
+    fn leg(side: &str, price: f64) -> GttLeg {
+        GttLeg {
+            side: side.to_string(),
+            quantity: 10,
+            order_type: "LIMIT".to_string(),
+            price,
+            product: "CNC".to_string(),
+        }
+    }
+
+    fn gtt(trigger_type: &str, trigger_prices: Vec<f64>, last_price: f64, legs: Vec<GttLeg>) -> GttOrder {
+        GttOrder {
+            gtt_id: "GTT1".to_string(),
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            trigger_type: trigger_type.to_string(),
+            trigger_prices,
+            last_price,
+            legs,
+            status: "active".to_string(),
+            order_id: None,
+            emulated: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn request(trigger_type: &str, trigger_prices: Vec<f64>, last_price: f64, legs: Vec<GttLeg>) -> GttRequest {
+        GttRequest {
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            trigger_type: trigger_type.to_string(),
+            trigger_prices,
+            last_price,
+            legs,
+            broker_symbol: None,
+            symbol_token: None,
+        }
+    }
+
+    #[test]
+    fn test_single_trigger_direction() {
+        // Trigger above the price at creation fires on the way up
+        let above = gtt("single", vec![110.0], 100.0, vec![leg("BUY", 111.0)]);
+        assert_eq!(GttService::check_trigger(&above, 105.0), None);
+        assert_eq!(GttService::check_trigger(&above, 110.0), Some(0));
+
+        // Trigger below fires on the way down
+        let below = gtt("single", vec![90.0], 100.0, vec![leg("SELL", 89.0)]);
+        assert_eq!(GttService::check_trigger(&below, 95.0), None);
+        assert_eq!(GttService::check_trigger(&below, 89.5), Some(0));
+        assert_eq!(GttService::check_trigger(&below, 0.0), None);
+    }
+
+    #[test]
+    fn test_two_leg_trigger() {
+        let oco = gtt("two-leg", vec![90.0, 120.0], 100.0, vec![leg("SELL", 89.0), leg("SELL", 121.0)]);
+        assert_eq!(GttService::check_trigger(&oco, 100.0), None);
+        assert_eq!(GttService::check_trigger(&oco, 90.0), Some(0));
+        assert_eq!(GttService::check_trigger(&oco, 125.0), Some(1));
+    }
+
+    #[test]
+    fn test_validate_request() {
+        // OCO alias is normalized
+        let ok = GttService::validate_request(request(
+            "OCO",
+            vec![90.0, 120.0],
+            100.0,
+            vec![leg("sell", 89.0), leg("sell", 121.0)],
+        ))
+        .unwrap();
+        assert_eq!(ok.trigger_type, "two-leg");
+        assert_eq!(ok.legs[0].side, "SELL");
+
+        // Leg count must match trigger type
+        assert!(GttService::validate_request(request("single", vec![110.0], 100.0, vec![])).is_err());
+
+        // Two-leg triggers must bracket the last price
+        assert!(GttService::validate_request(request(
+            "two-leg",
+            vec![120.0, 90.0],
+            100.0,
+            vec![leg("SELL", 89.0), leg("SELL", 121.0)],
+        ))
+        .is_err());
+
+        // LIMIT legs need a price
+        assert!(GttService::validate_request(request("single", vec![110.0], 100.0, vec![leg("BUY", 0.0)])).is_err());
+    }
+}
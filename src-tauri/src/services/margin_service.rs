@@ -0,0 +1,386 @@
+//! Margin Service
+//!
+//! Calculates the margin required for a basket of orders before placing it.
+//! Called by both Tauri commands and REST API.
+//!
+//! Live margins come from the broker's basket margin API. In analyze mode a
+//! SPAN-like estimate is built from the sandbox leverage settings: margin is
+//! the notional (strike for options, price otherwise) over the leverage for
+//! the exchange and product, long options pay their premium, and a short
+//! option covered by a long option of the same type on the same underlying
+//! only needs margin for the spread's maximum loss.
+
+use crate::brokers::types::{MarginResult, OrderRequest};
+use crate::db::sqlite::sandbox::SandboxConfig;
+use crate::error::{AppError, Result};
+use crate::services::QuotesService;
+use crate::state::AppState;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Share of derivative margin reported as exposure margin in the estimate
+const EXPOSURE_SHARE: f64 = 0.25;
+
+/// Result of a margin calculation
+#[derive(Debug, Clone, Serialize)]
+pub struct MarginCalculation {
+    pub success: bool,
+    pub margin: MarginResult,
+    pub mode: String,
+}
+
+/// Order leg priced for the local estimate
+#[derive(Debug, Clone)]
+struct EstimateLeg {
+    underlying: String,
+    exchange: String,
+    product: String,
+    side: String,
+    quantity: f64,
+    /// Premium for options, traded price otherwise
+    price: f64,
+    /// CE or PE for options
+    option_type: Option<String>,
+    strike: Option<f64>,
+}
+
+/// Long and short option legs of one underlying and option type
+type HedgeGroups<'a> = HashMap<(&'a str, &'a str), (Vec<&'a EstimateLeg>, Vec<&'a EstimateLeg>)>;
+
+/// Margin service for business logic
+pub struct MarginService;
+
+impl MarginService {
+    /// Calculate the margin for a basket of orders
+    ///
+    /// In analyze mode, returns a local estimate from the sandbox leverage.
+    /// Otherwise, returns the broker's basket margin (hedge benefit included).
+    pub async fn calculate_margin(
+        state: &AppState,
+        orders: Vec<OrderRequest>,
+        api_key: Option<&str>,
+    ) -> Result<MarginCalculation> {
+        info!("MarginService::calculate_margin - {} orders", orders.len());
+
+        if orders.is_empty() {
+            return Err(AppError::Validation("At least one order is required".to_string()));
+        }
+
+        // Check if in analyze mode
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+
+        if analyze_mode {
+            return Self::estimate_sandbox_margin(state, &orders, api_key).await;
+        }
+
+        let (auth_token, broker_id) = Self::get_auth(state, api_key)?;
+
+        // Look up broker-specific symbols and tokens from cache
+        let orders: Vec<OrderRequest> = orders
+            .into_iter()
+            .map(|mut order| {
+                if let Some(symbol_info) = state.get_symbol_by_name(&order.exchange, &order.symbol) {
+                    order.broker_symbol = symbol_info.brsymbol;
+                    order.symbol_token = Some(symbol_info.token);
+                } else {
+                    warn!("Symbol not found in cache: {}:{}", order.exchange, order.symbol);
+                }
+                order
+            })
+            .collect();
+
+        let broker = state
+            .brokers
+            .get(&broker_id)
+            .ok_or_else(|| AppError::Broker(format!("Broker '{}' not found", broker_id)))?;
+
+        let margin = broker.calculate_margin(&auth_token, &orders).await?;
+
+        Ok(MarginCalculation {
+            success: true,
+            margin,
+            mode: "live".to_string(),
+        })
+    }
+
+    // ========================================================================
+    // Private Helper Methods
+    // ========================================================================
+
+    fn get_auth(state: &AppState, api_key: Option<&str>) -> Result<(String, String)> {
+        match api_key {
+            Some(key) => {
+                let _api_key_info = state.sqlite.validate_api_key(key, &state.security)?;
+                let session = state
+                    .get_broker_session()
+                    .ok_or_else(|| AppError::Auth("Broker not connected".to_string()))?;
+                Ok((session.auth_token, session.broker_id))
+            }
+            None => {
+                let session = state
+                    .get_broker_session()
+                    .ok_or_else(|| AppError::Auth("Broker not connected".to_string()))?;
+                Ok((session.auth_token, session.broker_id))
+            }
+        }
+    }
+
+    async fn estimate_sandbox_margin(
+        state: &AppState,
+        orders: &[OrderRequest],
+        api_key: Option<&str>,
+    ) -> Result<MarginCalculation> {
+        let config = state.sqlite.get_sandbox_config()?;
+
+        // Market orders are priced at the last traded price
+        let unpriced: Vec<(String, String)> = orders
+            .iter()
+            .filter(|o| o.price <= 0.0)
+            .map(|o| (o.exchange.clone(), o.symbol.clone()))
+            .collect();
+        let mut ltps: HashMap<(String, String), f64> = HashMap::new();
+        if !unpriced.is_empty() {
+            match QuotesService::get_quotes(state, unpriced, api_key).await {
+                Ok(result) => {
+                    for quote in result.quotes {
+                        ltps.insert((quote.exchange, quote.symbol), quote.ltp);
+                    }
+                }
+                Err(e) => warn!("Quotes unavailable for margin estimate: {}", e),
+            }
+        }
+
+        let legs: Vec<EstimateLeg> = orders
+            .iter()
+            .map(|order| {
+                let underlying = state
+                    .get_symbol_by_name(&order.exchange, &order.symbol)
+                    .map(|s| s.name)
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| order.symbol.clone());
+                let price = if order.price > 0.0 {
+                    order.price
+                } else {
+                    ltps.get(&(order.exchange.clone(), order.symbol.clone()))
+                        .copied()
+                        .unwrap_or(0.0)
+                };
+                Self::estimate_leg(order, underlying, price)
+            })
+            .collect();
+
+        if legs.iter().any(|l| l.price <= 0.0 && l.strike.is_none()) {
+            warn!("Margin estimate is missing prices for some legs");
+        }
+
+        Ok(MarginCalculation {
+            success: true,
+            margin: Self::estimate(&legs, &config),
+            mode: "analyze".to_string(),
+        })
+    }
+
+    /// Build an estimate leg, reading strike and option type from the symbol
+    ///
+    /// OpenAlgo option symbols are `<underlying><DDMMMYY><strike><CE|PE>`.
+    fn estimate_leg(order: &OrderRequest, underlying: String, price: f64) -> EstimateLeg {
+        let symbol = order.symbol.as_str();
+        let option_type = ["CE", "PE"]
+            .into_iter()
+            .find(|t| symbol.ends_with(t) && symbol.len() > underlying.len() + 9)
+            .map(|t| t.to_string());
+        let strike = option_type.as_ref().and_then(|_| {
+            symbol
+                .get(underlying.len() + 7..symbol.len() - 2)
+                .and_then(|s| s.parse::<f64>().ok())
+        });
+
+        EstimateLeg {
+            underlying,
+            exchange: order.exchange.clone(),
+            product: order.product.clone(),
+            side: order.side.clone(),
+            quantity: order.quantity as f64,
+            price,
+            option_type,
+            strike,
+        }
+    }
+
+    /// SPAN-like margin estimate for a basket
+    fn estimate(legs: &[EstimateLeg], config: &SandboxConfig) -> MarginResult {
+        let mut gross_margin = 0.0;
+        let mut derivative_margin = 0.0;
+        let mut premium = 0.0;
+
+        for leg in legs {
+            if leg.option_type.is_some() && leg.side == "BUY" {
+                premium += leg.quantity * leg.price;
+                continue;
+            }
+            let margin = leg.quantity * Self::margin_per_unit(leg, config);
+            gross_margin += margin;
+            if !matches!(leg.exchange.as_str(), "NSE" | "BSE") {
+                derivative_margin += margin;
+            }
+        }
+
+        let hedge_benefit = Self::hedge_benefit(legs, config).min(gross_margin);
+        let scale = if gross_margin > 0.0 {
+            (gross_margin - hedge_benefit) / gross_margin
+        } else {
+            0.0
+        };
+        let exposure_margin = derivative_margin * EXPOSURE_SHARE * scale;
+        let span_margin = derivative_margin * (1.0 - EXPOSURE_SHARE) * scale;
+
+        MarginResult {
+            span_margin,
+            exposure_margin,
+            total_margin: (gross_margin - hedge_benefit) + premium,
+            hedge_benefit,
+        }
+    }
+
+    /// Margin blocked per unit of a leg that isn't a long option
+    fn margin_per_unit(leg: &EstimateLeg, config: &SandboxConfig) -> f64 {
+        let notional = leg.strike.unwrap_or(leg.price);
+        notional / Self::leverage(&leg.exchange, &leg.product, config)
+    }
+
+    /// Margin saved by short options covered by long options of the same type
+    ///
+    /// The covered quantity of a short leg only needs margin for the spread's
+    /// maximum loss (the strike difference) when that is lower than its own margin.
+    fn hedge_benefit(legs: &[EstimateLeg], config: &SandboxConfig) -> f64 {
+        let mut groups: HedgeGroups = HashMap::new();
+        for leg in legs {
+            let Some(option_type) = leg.option_type.as_deref() else {
+                continue;
+            };
+            let entry = groups.entry((leg.underlying.as_str(), option_type)).or_default();
+            if leg.side == "BUY" {
+                entry.0.push(leg);
+            } else {
+                entry.1.push(leg);
+            }
+        }
+
+        let mut benefit = 0.0;
+        for (longs, shorts) in groups.values() {
+            let mut available: Vec<(f64, f64)> = longs
+                .iter()
+                .map(|l| (l.strike.unwrap_or(0.0), l.quantity))
+                .collect();
+
+            for short in shorts {
+                let short_strike = short.strike.unwrap_or(0.0);
+                let short_margin = Self::margin_per_unit(short, config);
+                let mut remaining = short.quantity;
+
+                // Cover with the closest strikes first
+                available.sort_by(|a, b| {
+                    (a.0 - short_strike)
+                        .abs()
+                        .total_cmp(&(b.0 - short_strike).abs())
+                });
+                for (long_strike, long_qty) in available.iter_mut() {
+                    if remaining <= 0.0 {
+                        break;
+                    }
+                    let covered = remaining.min(*long_qty);
+                    if covered <= 0.0 {
+                        continue;
+                    }
+                    let max_loss = (short_strike - *long_strike).abs();
+                    benefit += covered * (short_margin - max_loss).max(0.0);
+                    *long_qty -= covered;
+                    remaining -= covered;
+                }
+            }
+        }
+
+        benefit
+    }
+
+    /// Sandbox leverage for an exchange and product
+    fn leverage(exchange: &str, product: &str, config: &SandboxConfig) -> f64 {
+        let intraday = product == "MIS";
+        let leverage = match exchange {
+            "NFO" | "BFO" => if intraday { config.nfo_mis_leverage } else { config.nfo_nrml_leverage },
+            "CDS" | "BCD" => if intraday { config.cds_mis_leverage } else { config.cds_nrml_leverage },
+            "MCX" => if intraday { config.mcx_mis_leverage } else { config.mcx_nrml_leverage },
+            _ => if intraday { config.nse_mis_leverage } else { config.nse_cnc_leverage },
+        };
+        if leverage > 0.0 { leverage } else { 1.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SandboxConfig {
+        SandboxConfig {
+            starting_capital: 10_000_000.0,
+            reset_day: "Sunday".to_string(),
+            reset_time: "00:00".to_string(),
+            order_check_interval: 5,
+            mtm_update_interval: 5,
+            nse_mis_leverage: 5.0,
+            nfo_mis_leverage: 2.0,
+            cds_mis_leverage: 2.0,
+            mcx_mis_leverage: 2.0,
+            nse_cnc_leverage: 1.0,
+            nfo_nrml_leverage: 10.0,
+            cds_nrml_leverage: 1.0,
+            mcx_nrml_leverage: 1.0,
+            nse_square_off_time: "15:15".to_string(),
+            nfo_square_off_time: "15:15".to_string(),
+            cds_square_off_time: "16:45".to_string(),
+            mcx_square_off_time: "23:30".to_string(),
+        }
+    }
+
+    fn leg(symbol: &str, side: &str, price: f64) -> EstimateLeg {
+        let order = OrderRequest {
+            symbol: symbol.to_string(),
+            exchange: "NFO".to_string(),
+            side: side.to_string(),
+            quantity: 50,
+            price,
+            order_type: "LIMIT".to_string(),
+            product: "NRML".to_string(),
+            validity: "DAY".to_string(),
+            trigger_price: None,
+            disclosed_quantity: None,
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
+            broker_symbol: None,
+            symbol_token: None,
+        };
+        MarginService::estimate_leg(&order, "NIFTY".to_string(), price)
+    }
+
+    #[test]
+    fn test_bull_put_spread_estimate() {
+        let short = leg("NIFTY28MAR2422000PE", "SELL", 120.0);
+        assert_eq!(short.strike, Some(22000.0));
+        assert_eq!(short.option_type.as_deref(), Some("PE"));
+
+        // Naked short: 22000 * 50 / 10
+        let naked = MarginService::estimate(std::slice::from_ref(&short), &config());
+        assert_eq!(naked.total_margin, 110_000.0);
+        assert_eq!(naked.hedge_benefit, 0.0);
+
+        // Covered by a long 21900 put: max loss 100 * 50 plus the long premium
+        let spread = MarginService::estimate(&[short, leg("NIFTY28MAR2421900PE", "BUY", 80.0)], &config());
+        assert_eq!(spread.hedge_benefit, 105_000.0);
+        assert_eq!(spread.total_margin, 5_000.0 + 4_000.0);
+        assert!((spread.span_margin + spread.exposure_margin - 5_000.0).abs() < 1e-6);
+    }
+}
@@ -18,35 +18,84 @@
 //! - `PositionService` - Get positions, close positions
 //! - `HoldingsService` - Get holdings
 //! - `FundsService` - Get funds/margin
+//! - `MarginService` - Basket margin (broker API or sandbox estimate)
 //! - `QuotesService` - Get quotes, market depth
 //! - `OrderbookService` - Get order book, trade book
 //! - `SmartOrderService` - Smart orders, split orders, basket orders
+//! - `BasketService` - Multi-leg execution with all-or-none rollback
 //! - `SymbolService` - Symbol search, lookup, master contract
 //! - `AnalyzerService` - Analyze mode (sandbox) management
 //! - `OptionsService` - Option chain, Greeks, option orders
 //! - `HistoryService` - Historical data
+//! - `GttService` - GTT orders (native or locally emulated)
+//! - `BracketService` - Emulated bracket/cover orders with trailing stops
+//! - `NotificationService` - Outbound notifications (HTTP, Telegram, Discord)
+//! - `ValidationService` - Pre-trade checks against the symbol master
+//! - `StrategyService` - Strategy-scoped square-off
+//! - `ChartinkService` - Chartink scan alerts to orders
+//! - `RiskService` - Pre-trade risk limits and kill switch
+//! - `AlgoService` - TWAP, VWAP and iceberg execution algos
+//! - `MtmBook` - Live position MTM re-marked from the tick feed
+//! - `ExitRuleService` - Position and portfolio target/stoploss/trailing exits
+//! - `AlertService` - Price and indicator alerts on the tick stream
+//! - `RecorderService` - Tick recorder symbols and the live 1m candle builder
+//! - `BacktestService` - Offline backtests over Historify data
 
 pub mod order_service;
 pub mod position_service;
 pub mod holdings_service;
 pub mod funds_service;
+pub mod margin_service;
 pub mod quotes_service;
 pub mod orderbook_service;
 pub mod smart_order_service;
+pub mod basket_service;
 pub mod symbol_service;
 pub mod analyzer_service;
 pub mod options_service;
 pub mod history_service;
+pub mod gtt_service;
+pub mod bracket_service;
+pub mod notification_service;
+pub mod validation_service;
+pub mod strategy_service;
+pub mod chartink_service;
+pub mod risk_service;
+pub mod algo_service;
+pub mod mtm_service;
+pub mod exit_rule_service;
+pub mod alert_service;
+pub mod recorder_service;
+pub mod backtest_service;
+pub mod replay_service;
 
 // Re-export commonly used types and services
-pub use order_service::{OrderService, PlaceOrderResult, ModifyOrderResult, CancelOrderResult};
+pub use order_service::{
+    ChildOrder, OrderService, PlaceOrderResult, ModifyOrderResult, CancelOrderResult,
+};
 pub use position_service::{PositionService, PositionResult, ClosePositionResult};
 pub use holdings_service::{HoldingsService, HoldingsResult};
 pub use funds_service::{FundsService, FundsResult};
+pub use margin_service::{MarginCalculation, MarginService};
 pub use quotes_service::{QuotesService, QuoteResult, DepthResult};
 pub use orderbook_service::{OrderbookService, OrderbookResult, TradebookResult, OrderStatusResult};
 pub use smart_order_service::{SmartOrderService, SmartOrderResult, SplitOrderResult};
+pub use basket_service::{BasketLeg, BasketResult, BasketService};
 pub use symbol_service::{SymbolService, SymbolSearchResult, ExpiryResult};
 pub use analyzer_service::{AnalyzerService, AnalyzerStatus};
 pub use options_service::{OptionsService, OptionChainResult, OptionGreeks, OptionSymbolResult, SyntheticFutureResult};
 pub use history_service::{HistoryService, HistoryResult, IntervalsResult, CandleData};
+pub use gtt_service::{GttService, GttResult, GttBookResult, GttTriggeredEvent};
+pub use bracket_service::{BracketService, BracketEvent};
+pub use notification_service::{Notification, NotificationService, NotificationTargetRequest};
+pub use validation_service::{ValidationIssue, ValidationMode, ValidationService, ValidatedOrder};
+pub use strategy_service::{SquareoffLeg, SquareoffSummary, StrategyService};
+pub use chartink_service::{ChartinkAction, ChartinkAlertResult, ChartinkService};
+pub use risk_service::{KillSwitchResult, MtmSnapshot, RiskService, RiskStatus, RiskTracker};
+pub use algo_service::{AlgoEvent, AlgoOrderRequest, AlgoService, AlgoType};
+pub use mtm_service::{MtmBook, PositionMtm, PositionsMtm};
+pub use exit_rule_service::{ExitReason, ExitRuleEvent, ExitRuleRequest, ExitRuleService, ExitRuleType};
+pub use alert_service::{AlertCondition, AlertRequest, AlertService, AlertTracker, AlertTriggeredEvent};
+pub use recorder_service::{CandleBuilder, RecorderService, TickOutcome, RECORDER_TIMEFRAME};
+pub use backtest_service::{BacktestRequest, BacktestService};
+pub use replay_service::{ReplayController, ReplayRequest, ReplayService, ReplayStatus};
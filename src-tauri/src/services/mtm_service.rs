@@ -0,0 +1,310 @@
+//! Live mark-to-market book
+//!
+//! Holds the open positions loaded by the MTM engine and re-marks them on
+//! every tick, so P&L is as fresh as the tick feed rather than the last
+//! positions poll. The engine emits snapshots as `positions_mtm` events and
+//! persists sandbox LTPs from here.
+
+use crate::brokers::types::Position;
+use crate::services::MtmSnapshot;
+use crate::websocket::MarketTick;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// One position marked at the latest LTP
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionMtm {
+    pub symbol: String,
+    pub exchange: String,
+    pub product: String,
+    pub quantity: i32,
+    pub average_price: f64,
+    pub ltp: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    /// Realized plus unrealized
+    pub pnl: f64,
+}
+
+impl PositionMtm {
+    fn from_position(position: &Position) -> Self {
+        let mut mtm = Self {
+            symbol: position.symbol.clone(),
+            exchange: position.exchange.clone(),
+            product: position.product.clone(),
+            quantity: position.quantity,
+            average_price: position.average_price,
+            ltp: position.ltp,
+            realized_pnl: position.realized_pnl,
+            unrealized_pnl: position.unrealized_pnl,
+            pnl: position.pnl,
+        };
+        if position.ltp > 0.0 {
+            mtm.mark(position.ltp);
+        }
+        mtm
+    }
+
+    /// Re-mark at a new LTP
+    fn mark(&mut self, ltp: f64) {
+        self.ltp = ltp;
+        self.unrealized_pnl = if self.quantity != 0 {
+            (ltp - self.average_price) * self.quantity as f64
+        } else {
+            0.0
+        };
+        self.pnl = self.realized_pnl + self.unrealized_pnl;
+    }
+}
+
+/// Portfolio MTM snapshot, the `positions_mtm` event payload
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PositionsMtm {
+    pub positions: Vec<PositionMtm>,
+    pub open_positions: usize,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    /// Mark-to-market of open positions
+    pub portfolio_mtm: f64,
+    /// Realized plus unrealized P&L for the day
+    pub day_pnl: f64,
+    pub timestamp: String,
+}
+
+impl PositionsMtm {
+    /// The same numbers in the shape the risk checks use
+    pub fn risk_snapshot(&self) -> MtmSnapshot {
+        MtmSnapshot {
+            realized_pnl: self.realized_pnl,
+            unrealized_pnl: self.unrealized_pnl,
+            mtm: self.day_pnl,
+            open_positions: self.open_positions,
+            timestamp: self.timestamp.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Book {
+    loaded: bool,
+    /// Mode (live/analyze) the positions were loaded in
+    mode: String,
+    /// Positions by "EXCHANGE:SYMBOL:PRODUCT"
+    positions: HashMap<String, PositionMtm>,
+    /// Symbols re-marked since the last sandbox persist
+    unpersisted: HashSet<(String, String)>,
+}
+
+/// Positions re-marked from the tick feed
+#[derive(Debug, Default)]
+pub struct MtmBook {
+    book: RwLock<Book>,
+}
+
+impl MtmBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the book with freshly loaded positions
+    ///
+    /// Positions keep the newest LTP already seen on the tick feed.
+    pub fn set_positions<F>(&self, positions: &[Position], mode: &str, ltp: F)
+    where
+        F: Fn(&str, &str) -> Option<f64>,
+    {
+        let positions = positions
+            .iter()
+            .map(|position| {
+                let mut mtm = PositionMtm::from_position(position);
+                if let Some(ltp) = ltp(&position.exchange, &position.symbol) {
+                    mtm.mark(ltp);
+                }
+                (format!("{}:{}:{}", position.exchange, position.symbol, position.product), mtm)
+            })
+            .collect();
+
+        let mut book = self.book.write();
+        book.positions = positions;
+        book.mode = mode.to_string();
+        book.loaded = true;
+    }
+
+    /// Drop all positions (e.g. on broker logout)
+    pub fn clear(&self) {
+        *self.book.write() = Book::default();
+    }
+
+    /// Whether positions have been loaded since the last clear
+    pub fn is_loaded(&self) -> bool {
+        self.book.read().loaded
+    }
+
+    /// Mode (live/analyze) the positions were last loaded in
+    pub fn mode(&self) -> String {
+        self.book.read().mode.clone()
+    }
+
+    /// Whether positions were last loaded in the given mode
+    pub fn is_loaded_for(&self, mode: &str) -> bool {
+        let book = self.book.read();
+        book.loaded && book.mode == mode
+    }
+
+    /// A single position by its key
+    pub fn position(&self, exchange: &str, symbol: &str, product: &str) -> Option<PositionMtm> {
+        self.book
+            .read()
+            .positions
+            .get(&format!("{}:{}:{}", exchange, symbol, product))
+            .cloned()
+    }
+
+    /// Realized plus unrealized P&L for the day, without building a snapshot
+    pub fn day_pnl(&self) -> f64 {
+        self.book
+            .read()
+            .positions
+            .values()
+            .map(|p| p.realized_pnl + if p.quantity != 0 { p.unrealized_pnl } else { 0.0 })
+            .sum()
+    }
+
+    /// Open positions as (exchange, symbol), for tick subscriptions
+    pub fn open_symbols(&self) -> Vec<(String, String)> {
+        let book = self.book.read();
+        let symbols: HashSet<_> = book
+            .positions
+            .values()
+            .filter(|p| p.quantity != 0)
+            .map(|p| (p.exchange.clone(), p.symbol.clone()))
+            .collect();
+        symbols.into_iter().collect()
+    }
+
+    /// Re-mark every position in the tick's symbol, returns true if any changed
+    pub fn apply_tick(&self, tick: &MarketTick) -> bool {
+        if tick.ltp <= 0.0 {
+            return false;
+        }
+
+        let mut book = self.book.write();
+        let mut changed = false;
+        for position in book.positions.values_mut() {
+            if position.exchange == tick.exchange && position.symbol == tick.symbol && position.ltp != tick.ltp {
+                position.mark(tick.ltp);
+                changed = true;
+            }
+        }
+        if changed {
+            book.unpersisted.insert((tick.exchange.clone(), tick.symbol.clone()));
+        }
+        changed
+    }
+
+    /// Symbols re-marked since the last call, with their LTP
+    pub fn take_unpersisted(&self) -> Vec<(String, String, f64)> {
+        let mut book = self.book.write();
+        let symbols: Vec<_> = book.unpersisted.drain().collect();
+        symbols
+            .into_iter()
+            .filter_map(|(exchange, symbol)| {
+                book.positions
+                    .values()
+                    .find(|p| p.exchange == exchange && p.symbol == symbol)
+                    .map(|p| (exchange, symbol, p.ltp))
+            })
+            .collect()
+    }
+
+    /// Current portfolio MTM
+    pub fn snapshot(&self) -> PositionsMtm {
+        let book = self.book.read();
+        let mut snapshot = PositionsMtm {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            ..Default::default()
+        };
+
+        for position in book.positions.values() {
+            snapshot.realized_pnl += position.realized_pnl;
+            if position.quantity != 0 {
+                snapshot.open_positions += 1;
+                snapshot.unrealized_pnl += position.unrealized_pnl;
+            }
+            snapshot.positions.push(position.clone());
+        }
+        snapshot.positions.sort_by(|a, b| {
+            (&a.exchange, &a.symbol, &a.product).cmp(&(&b.exchange, &b.symbol, &b.product))
+        });
+
+        snapshot.portfolio_mtm = snapshot.unrealized_pnl;
+        snapshot.day_pnl = snapshot.realized_pnl + snapshot.unrealized_pnl;
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(symbol: &str, product: &str, quantity: i32, average_price: f64, realized_pnl: f64) -> Position {
+        Position {
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            product: product.to_string(),
+            quantity,
+            overnight_quantity: 0,
+            average_price,
+            ltp: average_price,
+            pnl: realized_pnl,
+            realized_pnl,
+            unrealized_pnl: 0.0,
+            buy_quantity: 0,
+            buy_value: 0.0,
+            sell_quantity: 0,
+            sell_value: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_ticks_remark_positions() {
+        let book = MtmBook::new();
+        book.set_positions(
+            &[
+                position("SBIN", "MIS", 10, 800.0, 0.0),
+                position("SBIN", "CNC", -5, 810.0, 0.0),
+                position("INFY", "MIS", 0, 1500.0, 250.0),
+            ],
+            "live",
+            |_, symbol| (symbol == "INFY").then_some(1510.0),
+        );
+        assert_eq!(book.open_symbols(), vec![("NSE".to_string(), "SBIN".to_string())]);
+
+        let tick = MarketTick {
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            ltp: 820.0,
+            ..Default::default()
+        };
+        assert!(book.apply_tick(&tick));
+        assert!(!book.apply_tick(&tick));
+
+        let snapshot = book.snapshot();
+        assert_eq!(snapshot.open_positions, 2);
+        // 10 * (820 - 800) - 5 * (820 - 810)
+        assert_eq!(snapshot.portfolio_mtm, 150.0);
+        assert_eq!(snapshot.day_pnl, 400.0);
+        assert_eq!(snapshot.risk_snapshot().mtm, 400.0);
+        assert_eq!(book.day_pnl(), 400.0);
+        assert_eq!(book.position("NSE", "SBIN", "CNC").map(|p| p.pnl), Some(-50.0));
+        assert!(book.is_loaded_for("live"));
+        assert!(!book.is_loaded_for("analyze"));
+
+        assert_eq!(
+            book.take_unpersisted(),
+            vec![("NSE".to_string(), "SBIN".to_string(), 820.0)]
+        );
+        assert!(book.take_unpersisted().is_empty());
+    }
+}
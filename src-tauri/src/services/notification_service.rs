@@ -0,0 +1,615 @@
+//! Notification Service
+//!
+//! Sends outbound notifications for trading and session events to
+//! user-configured targets:
+//! - HTTP: JSON POST signed with HMAC-SHA256 (`X-OpenAlgo-Signature`)
+//! - Telegram: bot `sendMessage`
+//! - Discord: channel webhook
+//!
+//! Each target filters events, retries with exponential backoff and records
+//! the outcome in the delivery log.
+
+use crate::db::sqlite::{NotificationDelivery, NotificationTarget, SqliteDb};
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+/// Supported notification events
+pub const NOTIFICATION_EVENTS: &[&str] = &[
+    "order_placed",
+    "order_filled",
+    "order_rejected",
+    "position_closed",
+    "auto_logout",
+    "websocket_down",
+    "kill_switch",
+    "risk_breach",
+    "alert_triggered",
+];
+
+/// Supported target kinds
+const TARGET_KINDS: &[&str] = &["http", "telegram", "discord"];
+
+/// Timeout for a single delivery attempt
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// Upper bound for retry backoff
+const MAX_BACKOFF_SECS: u64 = 60;
+
+/// Discord message content limit
+const DISCORD_MAX_CHARS: usize = 2000;
+
+/// Telegram Bot API base URL (the bot token goes in the path)
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Outbound notification payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub event: String,
+    pub title: String,
+    pub message: String,
+    pub data: serde_json::Value,
+    pub timestamp: String,
+}
+
+impl Notification {
+    /// Create a notification for an event
+    pub fn new(event: &str, title: impl Into<String>, message: impl Into<String>, data: serde_json::Value) -> Self {
+        Self {
+            event: event.to_string(),
+            title: title.into(),
+            message: message.into(),
+            data,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Notification target create/update request
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationTargetRequest {
+    pub name: String,
+    pub kind: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub chat_id: Option<String>,
+    /// HMAC secret (http) or bot token (telegram); None keeps the stored one
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_events")]
+    pub events: Vec<String>,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_events() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Target with its decrypted secret, ready for delivery
+struct ResolvedTarget {
+    target: NotificationTarget,
+    secret: Option<String>,
+}
+
+/// Outcome of a delivery (after retries)
+struct DeliveryOutcome {
+    delivered: bool,
+    attempts: u32,
+    response_code: Option<u16>,
+    error: Option<String>,
+}
+
+/// Outcome of a single attempt
+enum AttemptError {
+    /// Worth retrying (network error, 429, 5xx)
+    Retry(Option<u16>, String),
+    /// Permanent failure (other 4xx, bad configuration)
+    Fatal(Option<u16>, String),
+}
+
+/// Notification service
+pub struct NotificationService;
+
+impl NotificationService {
+    /// Send a notification to every enabled target that accepts its event
+    ///
+    /// Deliveries run in the background; this never blocks the caller.
+    pub fn notify(state: &AppState, notification: Notification) {
+        let targets = match state.sqlite.get_notification_targets(true) {
+            Ok(targets) => targets,
+            Err(e) => {
+                warn!("Failed to load notification targets: {}", e);
+                return;
+            }
+        };
+
+        for target in targets.into_iter().filter(|t| t.accepts(&notification.event)) {
+            let resolved = match Self::resolve(state, target) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    warn!("Skipping notification target: {}", e);
+                    continue;
+                }
+            };
+
+            let sqlite = state.sqlite.clone();
+            let notification = notification.clone();
+            tauri::async_runtime::spawn(async move {
+                Self::deliver_and_log(&sqlite, &resolved, &notification).await;
+            });
+        }
+    }
+
+    /// Send a notification from a component that only holds the app handle
+    pub fn notify_app(app_handle: &AppHandle, notification: Notification) {
+        match app_handle.try_state::<AppState>() {
+            Some(state) => Self::notify(&state, notification),
+            None => warn!("AppState not available, notification {} dropped", notification.event),
+        }
+    }
+
+    /// Send a test notification to one target and return its delivery record
+    pub async fn send_test(state: &AppState, target_id: i64) -> Result<NotificationDelivery> {
+        let target = state
+            .sqlite
+            .get_notification_target(target_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Notification target {} not found", target_id)))?;
+
+        let resolved = Self::resolve(state, target)?;
+        let notification = Notification::new(
+            "test",
+            "Test notification",
+            format!("Notifications to '{}' are working", resolved.target.name),
+            serde_json::Value::Null,
+        );
+
+        let delivery_id = Self::deliver_and_log(&state.sqlite, &resolved, &notification).await;
+
+        state
+            .sqlite
+            .get_notification_deliveries(Some(target_id), 1)?
+            .into_iter()
+            .find(|d| Some(d.id) == delivery_id)
+            .ok_or_else(|| AppError::Internal("Failed to record test delivery".to_string()))
+    }
+
+    /// Create a notification target
+    pub fn create_target(state: &AppState, request: NotificationTargetRequest) -> Result<NotificationTarget> {
+        let events = Self::validate_request(&request)?;
+        let secret = Self::encrypt_secret(state, request.secret.as_deref())?;
+
+        let id = state.sqlite.create_notification_target(
+            request.name.trim(),
+            &request.kind,
+            request.url.as_deref(),
+            request.chat_id.as_deref(),
+            secret.as_ref().map(|(c, n)| (c.as_str(), n.as_str())),
+            &events,
+            request.max_retries,
+            request.enabled,
+        )?;
+
+        info!("Created {} notification target {}", request.kind, id);
+
+        state
+            .sqlite
+            .get_notification_target(id)?
+            .ok_or_else(|| AppError::Internal("Failed to create notification target".to_string()))
+    }
+
+    /// Update a notification target (its kind cannot change)
+    pub fn update_target(
+        state: &AppState,
+        id: i64,
+        request: NotificationTargetRequest,
+    ) -> Result<NotificationTarget> {
+        let existing = state
+            .sqlite
+            .get_notification_target(id)?
+            .ok_or_else(|| AppError::NotFound(format!("Notification target {} not found", id)))?;
+
+        if existing.kind != request.kind {
+            return Err(AppError::Validation("Notification target kind cannot be changed".to_string()));
+        }
+
+        let events = Self::validate_request(&request)?;
+        let secret = Self::encrypt_secret(state, request.secret.as_deref())?;
+
+        state.sqlite.update_notification_target(
+            id,
+            request.name.trim(),
+            request.url.as_deref(),
+            request.chat_id.as_deref(),
+            secret.as_ref().map(|(c, n)| (c.as_str(), n.as_str())),
+            &events,
+            request.max_retries,
+            request.enabled,
+        )?;
+
+        state
+            .sqlite
+            .get_notification_target(id)?
+            .ok_or_else(|| AppError::NotFound(format!("Notification target {} not found", id)))
+    }
+
+    /// Validate a target request, returning the normalized event filter
+    pub fn validate_request(request: &NotificationTargetRequest) -> Result<Vec<String>> {
+        if request.name.trim().is_empty() {
+            return Err(AppError::Validation("Notification target name is required".to_string()));
+        }
+
+        if !TARGET_KINDS.contains(&request.kind.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Invalid notification target kind: {} (expected http, telegram or discord)",
+                request.kind
+            )));
+        }
+
+        let has_url = request
+            .url
+            .as_deref()
+            .map(|u| u.starts_with("http://") || u.starts_with("https://"))
+            .unwrap_or(false);
+
+        match request.kind.as_str() {
+            "http" | "discord" if !has_url => {
+                return Err(AppError::Validation(format!(
+                    "A valid http(s) URL is required for {} targets",
+                    request.kind
+                )));
+            }
+            "telegram" if request.chat_id.as_deref().unwrap_or("").is_empty() => {
+                return Err(AppError::Validation("Telegram targets need a chat_id".to_string()));
+            }
+            _ => {}
+        }
+
+        if request.max_retries > 10 {
+            return Err(AppError::Validation("max_retries must be between 0 and 10".to_string()));
+        }
+
+        let events: Vec<String> = request
+            .events
+            .iter()
+            .map(|e| e.trim().to_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect();
+
+        if events.is_empty() {
+            return Err(AppError::Validation("At least one event is required".to_string()));
+        }
+
+        for event in &events {
+            if event != "*" && !NOTIFICATION_EVENTS.contains(&event.as_str()) {
+                return Err(AppError::Validation(format!("Unknown notification event: {}", event)));
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// HMAC-SHA256 signature of a payload, hex encoded
+    pub fn sign(secret: &str, payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// Plain-text rendering used for Telegram and Discord
+    pub fn format_text(notification: &Notification) -> String {
+        let mut text = format!("[OpenAlgo] {}\n{}", notification.title, notification.message);
+
+        if let serde_json::Value::Object(fields) = &notification.data {
+            for (key, value) in fields {
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Null => continue,
+                    other => other.to_string(),
+                };
+                text.push_str(&format!("\n{}: {}", key, value));
+            }
+        }
+
+        text
+    }
+
+    fn client() -> &'static Client {
+        static CLIENT: OnceLock<Client> = OnceLock::new();
+        CLIENT.get_or_init(|| {
+            Client::builder()
+                .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default()
+        })
+    }
+
+    fn resolve(state: &AppState, target: NotificationTarget) -> Result<ResolvedTarget> {
+        let secret = match (&target.secret_encrypted, &target.secret_nonce) {
+            (Some(encrypted), Some(nonce)) => Some(state.security.decrypt(encrypted, nonce)?),
+            _ => None,
+        };
+
+        Ok(ResolvedTarget { target, secret })
+    }
+
+    fn encrypt_secret(state: &AppState, secret: Option<&str>) -> Result<Option<(String, String)>> {
+        match secret.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(secret) => Ok(Some(state.security.encrypt(secret)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Deliver with retries and record the outcome, returning the log entry ID
+    async fn deliver_and_log(
+        sqlite: &Arc<SqliteDb>,
+        resolved: &ResolvedTarget,
+        notification: &Notification,
+    ) -> Option<i64> {
+        let outcome = Self::deliver(resolved, notification, TELEGRAM_API_URL).await;
+        let payload = serde_json::to_string(notification).unwrap_or_default();
+
+        if outcome.delivered {
+            info!(
+                "Notification {} delivered to '{}' after {} attempt(s)",
+                notification.event, resolved.target.name, outcome.attempts
+            );
+        } else {
+            warn!(
+                "Notification {} to '{}' failed after {} attempt(s): {}",
+                notification.event,
+                resolved.target.name,
+                outcome.attempts,
+                outcome.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+
+        match sqlite.log_notification_delivery(
+            resolved.target.id,
+            &notification.event,
+            if outcome.delivered { "delivered" } else { "failed" },
+            outcome.attempts,
+            outcome.response_code,
+            outcome.error.as_deref(),
+            &payload,
+        ) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                warn!("Failed to log notification delivery: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn deliver(resolved: &ResolvedTarget, notification: &Notification, telegram_api: &str) -> DeliveryOutcome {
+        let max_attempts = resolved.target.max_retries + 1;
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            match Self::send_once(resolved, notification, telegram_api).await {
+                Ok(code) => {
+                    return DeliveryOutcome {
+                        delivered: true,
+                        attempts,
+                        response_code: Some(code),
+                        error: None,
+                    };
+                }
+                Err(AttemptError::Retry(_, error)) if attempts < max_attempts => {
+                    let backoff = Self::backoff(attempts);
+                    warn!(
+                        "Notification to '{}' failed ({}), retrying in {}s",
+                        resolved.target.name, error, backoff.as_secs()
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(AttemptError::Retry(code, error)) | Err(AttemptError::Fatal(code, error)) => {
+                    return DeliveryOutcome {
+                        delivered: false,
+                        attempts,
+                        response_code: code,
+                        error: Some(error),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Backoff before the next attempt: 1s, 2s, 4s, ... capped at a minute
+    fn backoff(attempt: u32) -> Duration {
+        let secs = 1u64 << (attempt - 1).min(6);
+        Duration::from_secs(secs.min(MAX_BACKOFF_SECS))
+    }
+
+    async fn send_once(
+        resolved: &ResolvedTarget,
+        notification: &Notification,
+        telegram_api: &str,
+    ) -> std::result::Result<u16, AttemptError> {
+        let client = Self::client();
+        let target = &resolved.target;
+
+        let request = match target.kind.as_str() {
+            "http" => {
+                let url = target
+                    .url
+                    .as_deref()
+                    .ok_or_else(|| AttemptError::Fatal(None, "Missing URL".to_string()))?;
+                let body = serde_json::to_vec(notification)
+                    .map_err(|e| AttemptError::Fatal(None, e.to_string()))?;
+
+                let mut request = client
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .header("X-OpenAlgo-Event", &notification.event);
+                if let Some(secret) = &resolved.secret {
+                    request = request.header(
+                        "X-OpenAlgo-Signature",
+                        format!("sha256={}", Self::sign(secret, &body)),
+                    );
+                }
+                request.body(body)
+            }
+            "telegram" => {
+                let token = resolved
+                    .secret
+                    .as_deref()
+                    .ok_or_else(|| AttemptError::Fatal(None, "Missing Telegram bot token".to_string()))?;
+                client
+                    .post(format!("{}/bot{}/sendMessage", telegram_api, token))
+                    .json(&serde_json::json!({
+                        "chat_id": target.chat_id,
+                        "text": Self::format_text(notification),
+                        "disable_web_page_preview": true,
+                    }))
+            }
+            "discord" => {
+                let url = target
+                    .url
+                    .as_deref()
+                    .ok_or_else(|| AttemptError::Fatal(None, "Missing webhook URL".to_string()))?;
+                let content: String = Self::format_text(notification)
+                    .chars()
+                    .take(DISCORD_MAX_CHARS)
+                    .collect();
+                client.post(url).json(&serde_json::json!({ "content": content }))
+            }
+            other => {
+                return Err(AttemptError::Fatal(None, format!("Unsupported target kind: {}", other)));
+            }
+        };
+
+        // The URL is dropped from the error: Telegram's carries the bot token
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AttemptError::Retry(None, e.without_url().to_string()))?;
+
+        let status = response.status();
+        let code = status.as_u16();
+
+        if status.is_success() {
+            Ok(code)
+        } else if status.is_server_error() || code == 429 {
+            Err(AttemptError::Retry(Some(code), format!("HTTP {}", code)))
+        } else {
+            Err(AttemptError::Fatal(Some(code), format!("HTTP {}", code)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(kind: &str) -> NotificationTargetRequest {
+        NotificationTargetRequest {
+            name: "alerts".to_string(),
+            kind: kind.to_string(),
+            url: Some("https://example.com/hook".to_string()),
+            chat_id: None,
+            secret: None,
+            events: vec!["order_filled".to_string(), "ORDER_REJECTED".to_string()],
+            max_retries: 3,
+            enabled: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_telegram_send_hides_token() {
+        let token = "123456:SECRET-bot-token";
+        let resolved = ResolvedTarget {
+            target: NotificationTarget {
+                id: 1,
+                name: "telegram".to_string(),
+                kind: "telegram".to_string(),
+                url: None,
+                chat_id: Some("12345".to_string()),
+                events: vec!["*".to_string()],
+                max_retries: 0,
+                enabled: true,
+                has_secret: true,
+                secret_encrypted: None,
+                secret_nonce: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+            },
+            secret: Some(token.to_string()),
+        };
+        let notification = Notification::new("order_filled", "Filled", "SBIN", serde_json::json!({}));
+
+        // Nothing listens on port 1, so the send fails before any response
+        let outcome = NotificationService::deliver(&resolved, &notification, "http://127.0.0.1:1").await;
+        assert!(!outcome.delivered);
+        let error = outcome.error.unwrap();
+        assert!(!error.contains(token), "token leaked: {}", error);
+    }
+
+    #[test]
+    fn test_sign_matches_rfc4231() {
+        // RFC 4231 test case 2
+        let signature = NotificationService::sign("Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            signature,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let events = NotificationService::validate_request(&request("http")).unwrap();
+        assert_eq!(events, vec!["order_filled", "order_rejected"]);
+
+        let mut telegram = request("telegram");
+        assert!(NotificationService::validate_request(&telegram).is_err());
+        telegram.chat_id = Some("12345".to_string());
+        assert!(NotificationService::validate_request(&telegram).is_ok());
+
+        let mut unknown = request("discord");
+        unknown.events = vec!["margin_call".to_string()];
+        assert!(NotificationService::validate_request(&unknown).is_err());
+
+        assert!(NotificationService::validate_request(&request("sms")).is_err());
+    }
+
+    #[test]
+    fn test_format_text() {
+        let notification = Notification::new(
+            "order_filled",
+            "Order filled",
+            "BUY 10 SBIN @ 600.5",
+            serde_json::json!({ "order_id": "123", "rejection_reason": null }),
+        );
+
+        let text = NotificationService::format_text(&notification);
+        assert_eq!(text, "[OpenAlgo] Order filled\nBUY 10 SBIN @ 600.5\norder_id: 123");
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(NotificationService::backoff(1), Duration::from_secs(1));
+        assert_eq!(NotificationService::backoff(3), Duration::from_secs(4));
+        assert_eq!(NotificationService::backoff(10), Duration::from_secs(MAX_BACKOFF_SECS));
+    }
+}
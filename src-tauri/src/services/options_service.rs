@@ -5,7 +5,7 @@
 
 use crate::brokers::types::OrderRequest;
 use crate::error::{AppError, Result};
-use crate::services::{OrderService, PlaceOrderResult, QuotesService};
+use crate::services::{BasketResult, BasketService, OrderService, PlaceOrderResult, QuotesService};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -85,6 +85,9 @@ pub struct OptionsOrderRequest {
     pub quantity: i32,
     pub product: String,
     pub pricetype: Option<String>,
+    /// Broker order tag (client order id)
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 /// Options multi-order leg
@@ -94,6 +97,37 @@ pub struct OptionsLeg {
     pub strike_selection: String,
     pub action: String,
     pub quantity: i32,
+    /// Leg expiry (defaults to the order's expiry)
+    #[serde(default)]
+    pub expiry_date: Option<String>,
+    /// Leg product (defaults to the order's product)
+    #[serde(default)]
+    pub product: Option<String>,
+    #[serde(default)]
+    pub pricetype: Option<String>,
+    /// Limit price for LIMIT/SL legs
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub trigger_price: Option<f64>,
+}
+
+/// Multi-leg options order (spreads, strangles, condors)
+#[derive(Debug, Clone, Deserialize)]
+pub struct OptionsMultiOrderRequest {
+    pub underlying: String,
+    pub exchange: String,
+    /// Default expiry for legs without their own
+    pub expiry_date: Option<String>,
+    /// Default product for legs without their own
+    pub product: String,
+    pub legs: Vec<OptionsLeg>,
+    /// Place hedge legs first and roll back if any leg fails
+    #[serde(default)]
+    pub all_or_none: bool,
+    /// Broker order tag (client order id)
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 /// Options service for business logic
@@ -261,6 +295,10 @@ impl OptionsService {
             disclosed_quantity: None,
             validity: "DAY".to_string(),
             amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: req.tag,
             broker_symbol: None,  // Set by OrderService from symbol cache
             symbol_token: None,   // Set by OrderService from symbol cache
         };
@@ -269,63 +307,55 @@ impl OptionsService {
     }
 
     /// Place multi-leg options order
+    ///
+    /// Every leg's option symbol is resolved before anything is placed. With
+    /// `all_or_none`, a failed leg rolls back the legs already placed (see
+    /// `BasketService`).
     pub async fn place_options_multi_order(
         state: &AppState,
-        underlying: &str,
-        exchange: &str,
-        expiry_date: Option<&str>,
-        product: &str,
-        legs: Vec<OptionsLeg>,
+        req: OptionsMultiOrderRequest,
         api_key: Option<&str>,
-    ) -> Result<Vec<PlaceOrderResult>> {
-        info!("OptionsService::place_options_multi_order - {} legs", legs.len());
+    ) -> Result<BasketResult> {
+        info!("OptionsService::place_options_multi_order - {} legs", req.legs.len());
 
         // Get underlying LTP
-        let underlying_quote = QuotesService::get_quote(state, exchange, underlying, api_key).await?;
+        let underlying_quote = QuotesService::get_quote(state, &req.exchange, &req.underlying, api_key).await?;
 
-        let mut results = Vec::new();
+        let mut orders = Vec::with_capacity(req.legs.len());
 
-        for leg in legs {
+        for leg in req.legs {
             let option_symbol = Self::get_option_symbol(
                 state,
-                underlying,
-                exchange,
+                &req.underlying,
+                &req.exchange,
                 &leg.option_type,
                 &leg.strike_selection,
-                expiry_date,
+                leg.expiry_date.as_deref().or(req.expiry_date.as_deref()),
                 underlying_quote.ltp,
             )?;
 
-            let order_request = OrderRequest {
+            orders.push(OrderRequest {
                 symbol: option_symbol.symbol,
-                exchange: exchange.to_string(),
+                exchange: req.exchange.clone(),
                 side: leg.action,
                 quantity: leg.quantity,
-                order_type: "MARKET".to_string(),
-                product: product.to_string(),
-                price: 0.0,
-                trigger_price: None,
+                order_type: leg.pricetype.unwrap_or_else(|| "MARKET".to_string()),
+                product: leg.product.unwrap_or_else(|| req.product.clone()),
+                price: leg.price.unwrap_or(0.0),
+                trigger_price: leg.trigger_price.filter(|p| *p > 0.0),
                 disclosed_quantity: None,
                 validity: "DAY".to_string(),
                 amo: false,
+                target: None,
+                stoploss: None,
+                trailing_stoploss: None,
+                tag: req.tag.clone(),
                 broker_symbol: None,  // Set by OrderService from symbol cache
                 symbol_token: None,   // Set by OrderService from symbol cache
-            };
-
-            match OrderService::place_order(state, order_request, api_key).await {
-                Ok(result) => results.push(result),
-                Err(e) => {
-                    results.push(PlaceOrderResult {
-                        success: false,
-                        order_id: None,
-                        message: e.to_string(),
-                        mode: "live".to_string(),
-                    });
-                }
-            }
+            });
         }
 
-        Ok(results)
+        BasketService::execute(state, orders, req.all_or_none, api_key).await
     }
 
     // ========================================================================
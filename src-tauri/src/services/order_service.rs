@@ -5,6 +5,10 @@
 
 use crate::brokers::types::{ModifyOrderRequest, OrderRequest, OrderResponse};
 use crate::error::{AppError, Result};
+use crate::services::{
+    BracketService, Notification, NotificationService, OrderbookService, RiskService,
+    SmartOrderService, ValidationService,
+};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
@@ -16,6 +20,33 @@ pub struct PlaceOrderResult {
     pub order_id: Option<String>,
     pub message: String,
     pub mode: String, // "live" or "analyze"
+    /// Child orders when the order was sliced at the exchange freeze limit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub child_orders: Vec<ChildOrder>,
+    /// Slices that could not be placed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_slices: Vec<String>,
+}
+
+impl PlaceOrderResult {
+    /// Orders actually placed: the child orders of a sliced order, otherwise
+    /// the single order with the requested quantity
+    pub fn placed_orders(&self, quantity: i32) -> Vec<ChildOrder> {
+        if !self.child_orders.is_empty() {
+            return self.child_orders.clone();
+        }
+        self.order_id
+            .iter()
+            .map(|order_id| ChildOrder { order_id: order_id.clone(), quantity })
+            .collect()
+    }
+}
+
+/// One slice of an order split at the exchange freeze limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildOrder {
+    pub order_id: String,
+    pub quantity: i32,
 }
 
 /// Result of modifying an order
@@ -45,6 +76,12 @@ impl OrderService {
     /// - Session-based auth (api_key is None) - for Tauri command calls
     ///
     /// In analyze mode, routes to sandbox instead of live broker.
+    /// Every order first passes pre-trade validation (`ValidationService`),
+    /// which rejects or corrects it per the configured mode, and then the
+    /// risk limits and kill switch (`RiskService`).
+    /// Bracket/cover orders the broker can't take natively (and all of
+    /// them in analyze mode) are emulated by `BracketService`.
+    /// Orders above the exchange freeze limit are sliced into child orders.
     pub async fn place_order(
         state: &AppState,
         order: OrderRequest,
@@ -52,9 +89,191 @@ impl OrderService {
     ) -> Result<PlaceOrderResult> {
         info!("OrderService::place_order - {:?}", order);
 
+        if !order.is_bracket() {
+            if let Some(freeze_qty) = ValidationService::freeze_qty(state, &order.exchange, &order.symbol) {
+                if order.quantity > freeze_qty {
+                    return Self::place_sliced_order(state, order, freeze_qty, api_key).await;
+                }
+            }
+        }
+
+        Self::place_single_order(state, order, api_key).await
+    }
+
+    /// Place an order without freeze slicing
+    pub async fn place_single_order(
+        state: &AppState,
+        order: OrderRequest,
+        api_key: Option<&str>,
+    ) -> Result<PlaceOrderResult> {
+        Self::place_unsliced(state, order, api_key, true).await
+    }
+
+    /// Place one child of a sliced order
+    ///
+    /// Skips the risk check: the caller checks the parent order once, so
+    /// slices don't each fetch positions from the broker.
+    pub async fn place_slice(
+        state: &AppState,
+        order: OrderRequest,
+        api_key: Option<&str>,
+    ) -> Result<PlaceOrderResult> {
+        Self::place_unsliced(state, order, api_key, false).await
+    }
+
+    async fn place_unsliced(
+        state: &AppState,
+        order: OrderRequest,
+        api_key: Option<&str>,
+        check_risk: bool,
+    ) -> Result<PlaceOrderResult> {
+        let validated = ValidationService::validate_order(state, order)?;
+        let order = validated.order;
+
+        if check_risk {
+            RiskService::check_order(state, &order, api_key).await?;
+        }
+
+        let notification = Notification::new(
+            "order_placed",
+            "Order placed",
+            format!(
+                "{} {} {}:{} {} {}",
+                order.side, order.quantity, order.exchange, order.symbol, order.order_type, order.product
+            ),
+            serde_json::json!({
+                "symbol": order.symbol,
+                "exchange": order.exchange,
+                "action": order.side,
+                "quantity": order.quantity,
+                "price": order.price,
+                "pricetype": order.order_type,
+                "product": order.product,
+            }),
+        );
+
+        let emulate_bracket = if order.is_bracket() {
+            BracketService::validate_request(&order)?;
+            !Self::supports_native_bracket(state, &order, api_key)?
+        } else {
+            false
+        };
+
+        let mut result = if emulate_bracket {
+            BracketService::place_bracket_order(state, order, api_key).await?
+        } else {
+            Self::place_regular_order(state, order, api_key).await?
+        };
+
+        if result.success && !validated.corrections.is_empty() {
+            let adjusted: Vec<String> = validated
+                .corrections
+                .iter()
+                .map(|c| format!("{} -> {}", c.field, c.corrected.as_deref().unwrap_or("")))
+                .collect();
+            result.message = format!("{} (adjusted: {})", result.message, adjusted.join(", "));
+        }
+
+        if result.success {
+            let mut notification = notification;
+            notification.data["orderid"] = serde_json::json!(result.order_id);
+            notification.data["mode"] = serde_json::json!(result.mode);
+            NotificationService::notify(state, notification);
+        }
+
+        Ok(result)
+    }
+
+    /// Slice an order above the freeze limit into freeze-compliant children
+    ///
+    /// Risk limits are checked against the full quantity first so a breach
+    /// can't leave the order half placed. Fails only when no slice was placed.
+    async fn place_sliced_order(
+        state: &AppState,
+        order: OrderRequest,
+        freeze_qty: i32,
+        api_key: Option<&str>,
+    ) -> Result<PlaceOrderResult> {
+        RiskService::check_order(state, &order, api_key).await?;
+
+        let lot_size = state
+            .get_symbol_by_name(&order.exchange, &order.symbol)
+            .map(|s| s.lot_size)
+            .unwrap_or(0);
+        let slice_size = ValidationService::freeze_slice_size(freeze_qty, lot_size);
+
+        info!(
+            "Slicing {} {} into orders of up to {} (freeze limit {})",
+            order.quantity, order.symbol, slice_size, freeze_qty
+        );
+
+        let (child_orders, mut failures) =
+            SmartOrderService::place_slices(state, &order, slice_size, api_key).await;
+
+        if child_orders.is_empty() {
+            if let Some((_, e)) = failures.pop() {
+                return Err(e);
+            }
+        }
+
+        let placed: i32 = child_orders.iter().map(|c| c.quantity).sum();
+        let failed_slices: Vec<String> = failures
+            .iter()
+            .map(|(slice, e)| format!("Slice {}: {}", slice, e))
+            .collect();
+
+        let message = if failed_slices.is_empty() {
+            format!(
+                "Order sliced into {} orders at freeze limit {}",
+                child_orders.len(),
+                freeze_qty
+            )
+        } else {
+            warn!("Sliced order partially placed: {}", failed_slices.join("; "));
+            format!(
+                "{} of {} slices placed ({} of {} qty); failed: {}",
+                child_orders.len(),
+                child_orders.len() + failed_slices.len(),
+                placed,
+                order.quantity,
+                failed_slices.join("; ")
+            )
+        };
+
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+
+        Ok(PlaceOrderResult {
+            success: true,
+            order_id: child_orders.first().map(|c| c.order_id.clone()),
+            message,
+            mode: if analyze_mode { "analyze" } else { "live" }.to_string(),
+            child_orders,
+            failed_slices,
+        })
+    }
+
+    /// Place an order without bracket handling
+    ///
+    /// Used directly by `BracketService` for the entry of an emulated bracket,
+    /// which has already been through pre-trade validation.
+    pub async fn place_regular_order(
+        state: &AppState,
+        order: OrderRequest,
+        api_key: Option<&str>,
+    ) -> Result<PlaceOrderResult> {
         // Check if in analyze mode
         let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        Self::place_regular_order_in(state, order, analyze_mode, api_key).await
+    }
 
+    /// Place an order without bracket handling in a given mode, whichever
+    /// mode is active
+    pub async fn place_regular_order_in(
+        state: &AppState,
+        order: OrderRequest,
+        analyze_mode: bool,
+        api_key: Option<&str>,
+    ) -> Result<PlaceOrderResult> {
         if analyze_mode {
             return Self::place_sandbox_order(state, order, api_key).await;
         }
@@ -93,6 +312,8 @@ impl OrderService {
                     order_id: Some(response.order_id),
                     message: response.message.unwrap_or_else(|| "Order placed successfully".to_string()),
                     mode: "live".to_string(),
+                    child_orders: Vec::new(),
+                    failed_slices: Vec::new(),
                 })
             }
             Err(e) => {
@@ -158,11 +379,22 @@ impl OrderService {
 
         // Check if in analyze mode
         let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        Self::cancel_order_in(state, order_id, variety, analyze_mode, api_key).await
+    }
 
+    /// Cancel an order in a given mode, whichever mode is active
+    pub async fn cancel_order_in(
+        state: &AppState,
+        order_id: &str,
+        variety: Option<&str>,
+        analyze_mode: bool,
+        api_key: Option<&str>,
+    ) -> Result<CancelOrderResult> {
         if analyze_mode {
             // Try to cancel in sandbox
             match state.sqlite.cancel_sandbox_order(order_id) {
                 Ok(true) => {
+                    Self::publish_sandbox_update(state, order_id);
                     return Ok(CancelOrderResult {
                         success: true,
                         order_id: order_id.to_string(),
@@ -194,6 +426,70 @@ impl OrderService {
         })
     }
 
+    /// Place an exit in a given mode, whichever mode is active
+    ///
+    /// Squares off orders left behind in the mode that is not active, so it
+    /// skips pre-trade validation and risk checks (exits always pass them).
+    /// Exits above the freeze limit go out as freeze-sized slices; fails
+    /// only when no slice was placed.
+    pub async fn place_exit_in(
+        state: &AppState,
+        order: OrderRequest,
+        analyze_mode: bool,
+    ) -> Result<PlaceOrderResult> {
+        let slice_size = match ValidationService::freeze_qty(state, &order.exchange, &order.symbol) {
+            Some(freeze_qty) if order.quantity > freeze_qty => {
+                let lot_size = state
+                    .get_symbol_by_name(&order.exchange, &order.symbol)
+                    .map(|s| s.lot_size)
+                    .unwrap_or(0);
+                ValidationService::freeze_slice_size(freeze_qty, lot_size)
+            }
+            _ => order.quantity,
+        }
+        .max(1);
+
+        let mut child_orders = Vec::new();
+        let mut failed_slices = Vec::new();
+        let mut last_error = None;
+        let mut remaining = order.quantity;
+        let mut slice = 0;
+        while remaining > 0 {
+            let qty = remaining.min(slice_size);
+            remaining -= qty;
+            slice += 1;
+
+            let mut child = order.clone();
+            child.quantity = qty;
+            match Self::place_regular_order_in(state, child, analyze_mode, None).await {
+                Ok(result) => {
+                    if let Some(order_id) = result.order_id {
+                        child_orders.push(ChildOrder { order_id, quantity: qty });
+                    }
+                }
+                Err(e) => {
+                    failed_slices.push(format!("Slice {}: {}", slice, e));
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if child_orders.is_empty() {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
+
+        Ok(PlaceOrderResult {
+            success: true,
+            order_id: child_orders.first().map(|c| c.order_id.clone()),
+            message: format!("Exit placed in {} order(s)", child_orders.len()),
+            mode: if analyze_mode { "analyze" } else { "live" }.to_string(),
+            child_orders,
+            failed_slices,
+        })
+    }
+
     /// Cancel all open orders
     pub async fn cancel_all_orders(
         state: &AppState,
@@ -205,9 +501,37 @@ impl OrderService {
         let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
 
         if analyze_mode {
-            // In sandbox mode, we could cancel all sandbox orders
-            // For now, just return empty - sandbox has its own reset function
-            return Ok(vec![]);
+            let pending: Vec<String> = state
+                .sqlite
+                .get_sandbox_orders()?
+                .into_iter()
+                .filter(|o| o.status == "pending")
+                .map(|o| o.order_id)
+                .collect();
+
+            let mut results = Vec::new();
+            for order_id in pending {
+                let result = match state.sqlite.cancel_sandbox_order(&order_id) {
+                    Ok(cancelled) => {
+                        if cancelled {
+                            Self::publish_sandbox_update(state, &order_id);
+                        }
+                        CancelOrderResult {
+                            success: cancelled,
+                            order_id,
+                            message: if cancelled { "Cancelled" } else { "Not pending" }.to_string(),
+                        }
+                    }
+                    Err(e) => CancelOrderResult {
+                        success: false,
+                        order_id,
+                        message: e.to_string(),
+                    },
+                };
+                results.push(result);
+            }
+
+            return Ok(results);
         }
 
         let (auth_token, broker_id) = Self::get_auth(state, api_key)?;
@@ -278,6 +602,22 @@ impl OrderService {
         }
     }
 
+    /// Whether the connected broker takes this bracket/cover order natively
+    fn supports_native_bracket(state: &AppState, order: &OrderRequest, api_key: Option<&str>) -> Result<bool> {
+        if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            return Ok(false);
+        }
+
+        let (_, broker_id) = Self::get_auth(state, api_key)?;
+
+        let broker = state
+            .brokers
+            .get(&broker_id)
+            .ok_or_else(|| AppError::Broker(format!("Broker '{}' not found", broker_id)))?;
+
+        Ok(broker.supports_bracket(order))
+    }
+
     /// Place order in sandbox (analyze mode)
     async fn place_sandbox_order(
         state: &AppState,
@@ -286,24 +626,52 @@ impl OrderService {
     ) -> Result<PlaceOrderResult> {
         info!("Routing to sandbox (analyze mode)");
 
+        // Unpriced market orders fill at the last traded price when it's known
+        let mut price = order.price;
+        if order.order_type == "MARKET" && price <= 0.0 {
+            if let Some(ltp) = state.risk.ltp(&order.exchange, &order.symbol) {
+                price = ltp;
+            }
+        }
+
         let sandbox_order = state.sqlite.place_sandbox_order(
             &order.symbol,
             &order.exchange,
             &order.side,
             order.quantity,
-            order.price,
+            price,
             &order.order_type,
             &order.product,
         )?;
 
+        let order_id = sandbox_order.order_id.clone();
+        state
+            .order_updates
+            .publish(OrderbookService::sandbox_order_to_order(sandbox_order));
+
         Ok(PlaceOrderResult {
             success: true,
-            order_id: Some(sandbox_order.order_id),
+            order_id: Some(order_id),
             message: "Order placed in sandbox".to_string(),
             mode: "analyze".to_string(),
+            child_orders: Vec::new(),
+            failed_slices: Vec::new(),
         })
     }
 
+    /// Publish the current state of a sandbox order as an order update
+    fn publish_sandbox_update(state: &AppState, order_id: &str) {
+        let Ok(orders) = state.sqlite.get_sandbox_orders() else {
+            return;
+        };
+
+        if let Some(order) = orders.into_iter().find(|o| o.order_id == order_id) {
+            state
+                .order_updates
+                .publish(OrderbookService::sandbox_order_to_order(order));
+        }
+    }
+
     /// Log successful order to order_logs table
     fn log_order(
         state: &AppState,
@@ -4,6 +4,7 @@
 //! Called by both Tauri commands and REST API.
 
 use crate::brokers::types::Order;
+use crate::db::sqlite::models::SandboxOrder;
 use crate::error::{AppError, Result};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
@@ -48,7 +49,15 @@ impl OrderbookService {
 
         // Check if in analyze mode
         let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        Self::get_orderbook_in(state, analyze_mode, api_key).await
+    }
 
+    /// Get the order book of a given mode, whichever mode is active
+    pub async fn get_orderbook_in(
+        state: &AppState,
+        analyze_mode: bool,
+        api_key: Option<&str>,
+    ) -> Result<OrderbookResult> {
         if analyze_mode {
             return Self::get_sandbox_orderbook(state);
         }
@@ -148,26 +157,7 @@ impl OrderbookService {
         // Convert sandbox orders to broker Order type
         let orders: Vec<Order> = sandbox_orders
             .into_iter()
-            .map(|so| Order {
-                order_id: so.order_id,
-                exchange_order_id: None,
-                symbol: so.symbol,
-                exchange: so.exchange,
-                side: so.side,
-                quantity: so.quantity,
-                filled_quantity: so.filled_quantity.unwrap_or(0),
-                pending_quantity: so.quantity - so.filled_quantity.unwrap_or(0),
-                price: so.price,
-                trigger_price: 0.0,
-                average_price: so.average_price.unwrap_or(0.0),
-                order_type: so.order_type,
-                product: so.product,
-                status: so.status,
-                validity: "DAY".to_string(),
-                order_timestamp: so.created_at,
-                exchange_timestamp: None,
-                rejection_reason: None,
-            })
+            .map(Self::sandbox_order_to_order)
             .collect();
 
         Ok(OrderbookResult {
@@ -177,6 +167,30 @@ impl OrderbookService {
         })
     }
 
+    /// Convert a sandbox order to the broker `Order` type
+    pub fn sandbox_order_to_order(so: SandboxOrder) -> Order {
+        Order {
+            order_id: so.order_id,
+            exchange_order_id: None,
+            symbol: so.symbol,
+            exchange: so.exchange,
+            side: so.side,
+            quantity: so.quantity,
+            filled_quantity: so.filled_quantity.unwrap_or(0),
+            pending_quantity: so.quantity - so.filled_quantity.unwrap_or(0),
+            price: so.price,
+            trigger_price: 0.0,
+            average_price: so.average_price.unwrap_or(0.0),
+            order_type: so.order_type,
+            product: so.product,
+            status: so.status,
+            validity: "DAY".to_string(),
+            order_timestamp: so.created_at,
+            exchange_timestamp: None,
+            rejection_reason: None,
+        }
+    }
+
     fn get_sandbox_tradebook(state: &AppState) -> Result<TradebookResult> {
         // In sandbox, completed orders are trades
         let sandbox_orders = state.sqlite.get_sandbox_orders()?;
@@ -5,6 +5,7 @@
 
 use crate::brokers::types::Position;
 use crate::error::{AppError, Result};
+use crate::services::{Notification, NotificationService};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
@@ -41,7 +42,15 @@ impl PositionService {
 
         // Check if in analyze mode
         let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        Self::get_positions_in(state, analyze_mode, api_key).await
+    }
 
+    /// Get the positions of a given mode, whichever mode is active
+    pub async fn get_positions_in(
+        state: &AppState,
+        analyze_mode: bool,
+        api_key: Option<&str>,
+    ) -> Result<PositionResult> {
         if analyze_mode {
             return Self::get_sandbox_positions(state);
         }
@@ -70,7 +79,20 @@ impl PositionService {
         product: &str,
         api_key: Option<&str>,
     ) -> Result<Option<Position>> {
-        let result = Self::get_positions(state, api_key).await?;
+        let analyze_mode = state.sqlite.get_analyze_mode().unwrap_or(false);
+        Self::get_open_position_in(state, exchange, symbol, product, analyze_mode, api_key).await
+    }
+
+    /// Get open position for a specific symbol in a given mode
+    pub async fn get_open_position_in(
+        state: &AppState,
+        exchange: &str,
+        symbol: &str,
+        product: &str,
+        analyze_mode: bool,
+        api_key: Option<&str>,
+    ) -> Result<Option<Position>> {
+        let result = Self::get_positions_in(state, analyze_mode, api_key).await?;
 
         // Find matching position
         let position = result.positions.into_iter().find(|p| {
@@ -130,6 +152,8 @@ impl PositionService {
                 product,
             )?;
 
+            Self::notify_closed(state, exchange, symbol, product, position.quantity, Some(&order.order_id));
+
             return Ok(ClosePositionResult {
                 success: true,
                 order_id: Some(order.order_id),
@@ -150,12 +174,20 @@ impl PositionService {
             disclosed_quantity: None,
             validity: "DAY".to_string(),
             amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
             broker_symbol: None,  // Set by OrderService from symbol cache
             symbol_token: None,   // Set by OrderService from symbol cache
         };
 
         let result = crate::services::OrderService::place_order(state, order_request, api_key).await?;
 
+        if result.success {
+            Self::notify_closed(state, exchange, symbol, product, position.quantity, result.order_id.as_deref());
+        }
+
         Ok(ClosePositionResult {
             success: result.success,
             order_id: result.order_id,
@@ -163,6 +195,32 @@ impl PositionService {
         })
     }
 
+    /// Send a `position_closed` notification
+    fn notify_closed(
+        state: &AppState,
+        exchange: &str,
+        symbol: &str,
+        product: &str,
+        quantity: i32,
+        order_id: Option<&str>,
+    ) {
+        NotificationService::notify(
+            state,
+            Notification::new(
+                "position_closed",
+                "Position closed",
+                format!("Closing {} {}:{} ({})", quantity, exchange, symbol, product),
+                serde_json::json!({
+                    "symbol": symbol,
+                    "exchange": exchange,
+                    "product": product,
+                    "quantity": quantity,
+                    "orderid": order_id,
+                }),
+            ),
+        );
+    }
+
     /// Close all open positions
     pub async fn close_all_positions(
         state: &AppState,
@@ -35,6 +35,14 @@ impl QuotesService {
     ) -> Result<QuoteResult> {
         info!("QuotesService::get_quotes - {} symbols", symbols.len());
 
+        // A sandbox replay answers for the symbols it's replaying
+        if let Some(quotes) = state.replay.quotes(&symbols) {
+            return Ok(QuoteResult {
+                success: true,
+                quotes,
+            });
+        }
+
         let (auth_token, broker_id) = Self::get_auth(state, api_key)?;
 
         let broker = state
@@ -0,0 +1,335 @@
+//! Tick recorder and live 1-minute candle builder
+//!
+//! The tick recorder scheduler feeds ticks for the recorder symbols through
+//! a `CandleBuilder`, buffers the raw ticks and periodically writes both to
+//! DuckDB: ticks to the `ticks` table and bars to `market_data` with
+//! timeframe `1m`. The bar for the current minute is rewritten on every
+//! flush until it closes.
+//!
+//! Ticks repeated by the broker (same exchange timestamp, LTP and volume)
+//! are dropped. Ticks that arrive late still land in their own minute as
+//! long as that bar is within `LATE_WINDOW_MINUTES` of the newest one;
+//! older stragglers are kept as raw ticks only.
+
+use crate::db::duckdb::models::{MarketDataRow, RecorderSymbol, TickRow};
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use crate::websocket::MarketTick;
+use chrono::DateTime;
+use chrono_tz::Asia::Kolkata;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Bar timeframe written by the recorder
+pub const RECORDER_TIMEFRAME: &str = "1m";
+
+/// How many minutes behind the newest bar a late tick may still update its bar
+pub const LATE_WINDOW_MINUTES: i64 = 2;
+
+const MINUTE_MS: i64 = 60_000;
+
+/// Outcome of feeding a tick to the candle builder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickOutcome {
+    /// Applied to its 1m bar
+    Applied,
+    /// Too old to change a bar, record the raw tick only
+    TooLate,
+    /// Already seen, drop it
+    Duplicate,
+}
+
+/// One 1m bar being built
+#[derive(Debug, Clone)]
+struct Bar {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: i64,
+    /// Timestamps of the ticks that set open and close
+    first_ts: i64,
+    last_ts: i64,
+    /// Ticks applied to this bar, to drop duplicates
+    seen: HashSet<(i64, u64, i64)>,
+    /// Changed since the last drain
+    dirty: bool,
+}
+
+/// Bars and volume state for one symbol
+#[derive(Debug, Default)]
+struct SymbolBars {
+    /// Keyed by epoch minute
+    bars: BTreeMap<i64, Bar>,
+    newest_minute: i64,
+    /// Highest cumulative day volume seen, to turn it into per-bar volume
+    last_volume: Option<i64>,
+    /// IST day of `last_volume`; cumulative volume restarts every session
+    volume_day: i64,
+}
+
+/// Builds 1m OHLCV bars from ticks, per exchange and symbol
+#[derive(Debug, Default)]
+pub struct CandleBuilder {
+    symbols: HashMap<(String, String), SymbolBars>,
+}
+
+impl CandleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a tick at `ts_ms` (epoch milliseconds) to its bar
+    pub fn push(&mut self, exchange: &str, symbol: &str, ts_ms: i64, ltp: f64, volume: i64) -> TickOutcome {
+        let entry = self
+            .symbols
+            .entry((exchange.to_string(), symbol.to_string()))
+            .or_default();
+
+        let minute = ts_ms.div_euclid(MINUTE_MS);
+        if minute < entry.newest_minute - LATE_WINDOW_MINUTES {
+            return TickOutcome::TooLate;
+        }
+
+        let key = (ts_ms, ltp.to_bits(), volume);
+        if entry.bars.get(&minute).is_some_and(|bar| bar.seen.contains(&key)) {
+            return TickOutcome::Duplicate;
+        }
+
+        // Volume traded since the previous tick; the first tick of a session
+        // (or of the recording) only sets the baseline
+        let day = ist_day(ts_ms);
+        if day > entry.volume_day {
+            entry.volume_day = day;
+            entry.last_volume = None;
+        }
+        let traded = match entry.last_volume {
+            Some(last) if volume > last => volume - last,
+            _ => 0,
+        };
+        if entry.last_volume.map_or(true, |last| volume > last) {
+            entry.last_volume = Some(volume);
+        }
+
+        let bar = entry.bars.entry(minute).or_insert_with(|| Bar {
+            open: ltp,
+            high: ltp,
+            low: ltp,
+            close: ltp,
+            volume: 0,
+            first_ts: ts_ms,
+            last_ts: ts_ms,
+            seen: HashSet::new(),
+            dirty: true,
+        });
+        if ts_ms < bar.first_ts {
+            bar.first_ts = ts_ms;
+            bar.open = ltp;
+        }
+        if ts_ms >= bar.last_ts {
+            bar.last_ts = ts_ms;
+            bar.close = ltp;
+        }
+        bar.high = bar.high.max(ltp);
+        bar.low = bar.low.min(ltp);
+        bar.volume += traded;
+        bar.seen.insert(key);
+        bar.dirty = true;
+
+        entry.newest_minute = entry.newest_minute.max(minute);
+        TickOutcome::Applied
+    }
+
+    /// Take bars changed since the last drain, as (exchange, symbol, bar)
+    ///
+    /// Bars that can no longer receive late ticks are dropped afterwards.
+    pub fn drain(&mut self) -> Vec<(String, String, MarketDataRow)> {
+        let mut rows = Vec::new();
+        for ((exchange, symbol), entry) in self.symbols.iter_mut() {
+            for (minute, bar) in entry.bars.iter_mut().filter(|(_, bar)| bar.dirty) {
+                bar.dirty = false;
+                rows.push((
+                    exchange.clone(),
+                    symbol.clone(),
+                    MarketDataRow {
+                        timestamp: ist_timestamp(minute * MINUTE_MS, false),
+                        open: bar.open,
+                        high: bar.high,
+                        low: bar.low,
+                        close: bar.close,
+                        volume: bar.volume,
+                    },
+                ));
+            }
+            let oldest = entry.newest_minute - LATE_WINDOW_MINUTES;
+            entry.bars.retain(|minute, _| *minute >= oldest);
+        }
+        rows
+    }
+
+    /// Forget symbols that are no longer recorded
+    pub fn retain(&mut self, symbols: &HashSet<(String, String)>) {
+        self.symbols.retain(|key, _| symbols.contains(key));
+    }
+
+    pub fn clear(&mut self) {
+        self.symbols.clear();
+    }
+}
+
+/// Raw tick row for the `ticks` table
+///
+/// Brokers that don't send an exchange timestamp report 0; those ticks are
+/// stamped with `received_ms`.
+pub fn tick_row(tick: &MarketTick, symbol: &str, received_ms: i64) -> (i64, TickRow) {
+    let ts_ms = if tick.timestamp > 0 { tick.timestamp } else { received_ms };
+    let row = TickRow {
+        symbol: symbol.to_string(),
+        exchange: tick.exchange.clone(),
+        timestamp: ist_timestamp(ts_ms, true),
+        ltp: tick.ltp,
+        volume: tick.volume,
+        oi: tick.oi,
+        bid: tick.bid,
+        ask: tick.ask,
+        bid_qty: tick.bid_qty,
+        ask_qty: tick.ask_qty,
+    };
+    (ts_ms, row)
+}
+
+/// Format epoch milliseconds as an IST DuckDB timestamp
+fn ist_timestamp(ts_ms: i64, with_millis: bool) -> String {
+    let time = DateTime::from_timestamp_millis(ts_ms)
+        .unwrap_or_default()
+        .with_timezone(&Kolkata);
+    if with_millis {
+        time.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+    } else {
+        time.format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+}
+
+/// IST calendar day number of epoch milliseconds
+fn ist_day(ts_ms: i64) -> i64 {
+    // IST is UTC+5:30 with no DST
+    (ts_ms + 330 * MINUTE_MS).div_euclid(24 * 60 * MINUTE_MS)
+}
+
+/// Service managing which symbols the tick recorder captures
+pub struct RecorderService;
+
+impl RecorderService {
+    /// Get the recorder symbols
+    pub fn get_symbols(state: &AppState) -> Result<Vec<RecorderSymbol>> {
+        state.duckdb.get_recorder_symbols()
+    }
+
+    /// Start recording a symbol
+    ///
+    /// The symbol must be in the master contract, so record expiring
+    /// contracts before they drop out of it.
+    pub fn add_symbol(state: &AppState, exchange: &str, symbol: &str) -> Result<Vec<RecorderSymbol>> {
+        let exchange = exchange.trim().to_uppercase();
+        let symbol = symbol.trim().to_uppercase();
+        if state.get_symbol_by_name(&exchange, &symbol).is_none() {
+            return Err(AppError::NotFound(format!(
+                "Symbol {}:{} not found in master contract",
+                exchange, symbol
+            )));
+        }
+
+        if state.duckdb.add_recorder_symbol(&symbol, &exchange)? {
+            tracing::info!("Recording ticks for {}:{}", exchange, symbol);
+        }
+        Self::get_symbols(state)
+    }
+
+    /// Stop recording a symbol; ticks and bars already stored are kept
+    pub fn remove_symbol(state: &AppState, exchange: &str, symbol: &str) -> Result<Vec<RecorderSymbol>> {
+        if !state.duckdb.remove_recorder_symbol(symbol, exchange)? {
+            return Err(AppError::NotFound(format!(
+                "{}:{} is not being recorded",
+                exchange, symbol
+            )));
+        }
+
+        tracing::info!("Stopped recording ticks for {}:{}", exchange, symbol);
+        Self::get_symbols(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-06-03 09:15:00 IST
+    const OPEN_MS: i64 = 1_717_386_300_000;
+
+    fn bars(builder: &mut CandleBuilder) -> Vec<MarketDataRow> {
+        let mut rows: Vec<_> = builder.drain().into_iter().map(|(_, _, row)| row).collect();
+        rows.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        rows
+    }
+
+    #[test]
+    fn test_builds_minute_bars() {
+        let mut builder = CandleBuilder::new();
+        builder.push("NSE", "SBIN", OPEN_MS + 1_000, 800.0, 1_000);
+        builder.push("NSE", "SBIN", OPEN_MS + 20_000, 805.0, 1_500);
+        builder.push("NSE", "SBIN", OPEN_MS + 40_000, 798.0, 1_700);
+        builder.push("NSE", "SBIN", OPEN_MS + 59_999, 802.0, 2_000);
+        builder.push("NSE", "SBIN", OPEN_MS + 60_000, 803.0, 2_100);
+
+        let rows = bars(&mut builder);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].timestamp, "2024-06-03 09:15:00");
+        assert_eq!((rows[0].open, rows[0].high, rows[0].low, rows[0].close), (800.0, 805.0, 798.0, 802.0));
+        // First tick only sets the volume baseline
+        assert_eq!(rows[0].volume, 1_000);
+        assert_eq!(rows[1].timestamp, "2024-06-03 09:16:00");
+        assert_eq!(rows[1].volume, 100);
+
+        // Nothing changed since the drain
+        assert!(builder.drain().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_and_late_ticks() {
+        let mut builder = CandleBuilder::new();
+        assert_eq!(builder.push("NSE", "SBIN", OPEN_MS + 30_000, 800.0, 1_000), TickOutcome::Applied);
+        assert_eq!(builder.push("NSE", "SBIN", OPEN_MS + 30_000, 800.0, 1_000), TickOutcome::Duplicate);
+        builder.push("NSE", "SBIN", OPEN_MS + 65_000, 801.0, 1_200);
+        bars(&mut builder);
+
+        // Late tick earlier in the first minute becomes its open, without volume
+        assert_eq!(builder.push("NSE", "SBIN", OPEN_MS + 2_000, 799.0, 900), TickOutcome::Applied);
+        let rows = bars(&mut builder);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].timestamp, "2024-06-03 09:15:00");
+        assert_eq!((rows[0].open, rows[0].low, rows[0].close), (799.0, 799.0, 800.0));
+        assert_eq!(rows[0].volume, 0);
+
+        // Beyond the late window the bar is left alone
+        builder.push("NSE", "SBIN", OPEN_MS + 5 * MINUTE_MS, 802.0, 1_300);
+        assert_eq!(builder.push("NSE", "SBIN", OPEN_MS + 10_000, 790.0, 950), TickOutcome::TooLate);
+        let rows = bars(&mut builder);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].timestamp, "2024-06-03 09:20:00");
+    }
+
+    #[test]
+    fn test_volume_resets_each_session() {
+        let mut builder = CandleBuilder::new();
+        builder.push("NSE", "SBIN", OPEN_MS, 800.0, 5_000_000);
+        builder.push("NSE", "SBIN", OPEN_MS + 1_000, 800.5, 5_000_100);
+        bars(&mut builder);
+
+        let next_day = OPEN_MS + 24 * 60 * MINUTE_MS;
+        builder.push("NSE", "SBIN", next_day, 810.0, 200);
+        builder.push("NSE", "SBIN", next_day + 1_000, 811.0, 350);
+        let rows = bars(&mut builder);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].timestamp, "2024-06-04 09:15:00");
+        assert_eq!(rows[0].volume, 150);
+    }
+}
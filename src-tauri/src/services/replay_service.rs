@@ -0,0 +1,801 @@
+//! Sandbox historical replay
+//!
+//! Replays a past trading day from Historify as if it were live. Stored
+//! candles (or ticks captured by the tick recorder) are laid out on a
+//! timeline and a replay clock running at 1x-100x publishes them as
+//! synthetic `market_tick` events. Everything downstream of the tick
+//! stream (MTM, exit rules, alerts, risk checks) sees them like broker
+//! ticks, pending sandbox LIMIT orders are matched against them, and
+//! quotes for the replayed symbols are served from the replay, so
+//! webhooks and API orders behave as if that day were live.
+//!
+//! A candle is expanded into four ticks within its bar: open, low, high,
+//! close for a rising bar and open, high, low, close for a falling one.
+//!
+//! Replay only runs in analyze mode.
+
+use crate::brokers::types::Quote;
+use crate::db::duckdb::DuckDb;
+use crate::error::{AppError, Result};
+use crate::services::backtest_service::{parse_time, BacktestSource, DEFAULT_TIMEFRAME};
+use crate::services::AnalyzerService;
+use crate::state::AppState;
+use crate::websocket::MarketTick;
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Asia::Kolkata;
+use dashmap::DashMap;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Fastest replay speed
+pub const MAX_REPLAY_SPEED: u32 = 100;
+
+/// Longest the clock sleeps before re-checking, so status stays fresh
+const MAX_SLEEP_MS: u64 = 250;
+
+/// How often `replay_status` is emitted while running
+const STATUS_INTERVAL_MS: u128 = 1000;
+
+/// Events published before yielding to other tasks
+const EVENTS_PER_YIELD: usize = 500;
+
+/// Timestamp format of the replay clock
+const CLOCK_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn default_timeframe() -> String {
+    DEFAULT_TIMEFRAME.to_string()
+}
+
+fn default_speed() -> u32 {
+    1
+}
+
+/// Symbol to replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaySymbol {
+    pub symbol: String,
+    pub exchange: String,
+}
+
+/// Replay request
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayRequest {
+    /// Trading day to replay (YYYY-MM-DD)
+    pub date: String,
+    pub symbols: Vec<ReplaySymbol>,
+    #[serde(default)]
+    pub source: BacktestSource,
+    /// Candle timeframe, for the candle source
+    #[serde(default = "default_timeframe")]
+    pub timeframe: String,
+    #[serde(default = "default_speed")]
+    pub speed: u32,
+    /// Start the clock at this time of day (HH:MM[:SS]) instead of the first event
+    #[serde(default)]
+    pub start_time: Option<String>,
+}
+
+/// Replay clock state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayState {
+    #[default]
+    Idle,
+    Running,
+    Paused,
+    Finished,
+    Stopped,
+}
+
+/// Replay status, also emitted as the `replay_status` event
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayStatus {
+    pub state: ReplayState,
+    pub date: Option<String>,
+    pub symbols: Vec<ReplaySymbol>,
+    pub speed: u32,
+    /// Current replay time
+    pub clock: Option<String>,
+    /// Time of the first event
+    pub start: Option<String>,
+    /// Time of the last event
+    pub end: Option<String>,
+    pub events_total: usize,
+    pub events_sent: usize,
+}
+
+/// Command sent to a running replay
+#[derive(Debug, Clone)]
+pub enum ReplayCommand {
+    Pause,
+    Resume,
+    Seek(NaiveDateTime),
+    Speed(u32),
+    Stop,
+}
+
+/// One synthetic tick on the replay timeline
+#[derive(Debug, Clone)]
+struct ReplayEvent {
+    time: NaiveDateTime,
+    symbol: String,
+    exchange: String,
+    token: String,
+    ltp: f64,
+    /// Cumulative day volume
+    volume: i64,
+    bid: f64,
+    ask: f64,
+    bid_qty: i64,
+    ask_qty: i64,
+    oi: i64,
+    /// Day open, high and low up to this event
+    open: f64,
+    high: f64,
+    low: f64,
+    /// Previous day's close
+    close: f64,
+}
+
+impl ReplayEvent {
+    fn tick(&self) -> MarketTick {
+        let (change, change_percent) = change(self.ltp, self.close);
+        MarketTick {
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            token: self.token.clone(),
+            ltp: self.ltp,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            bid: self.bid,
+            ask: self.ask,
+            bid_qty: self.bid_qty,
+            ask_qty: self.ask_qty,
+            oi: self.oi,
+            timestamp: ist_millis(self.time),
+            change,
+            change_percent,
+            replay: true,
+        }
+    }
+
+    fn quote(&self) -> Quote {
+        let (change, change_percent) = change(self.ltp, self.close);
+        Quote {
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            ltp: self.ltp,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            bid: self.bid,
+            ask: self.ask,
+            bid_qty: self.bid_qty as i32,
+            ask_qty: self.ask_qty as i32,
+            oi: self.oi,
+            change,
+            change_percent,
+            timestamp: self.time.format(CLOCK_FORMAT).to_string(),
+        }
+    }
+}
+
+fn change(ltp: f64, close: f64) -> (f64, f64) {
+    if close > 0.0 {
+        (ltp - close, (ltp - close) / close * 100.0)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
+/// Epoch milliseconds of an IST wall-clock time
+fn ist_millis(time: NaiveDateTime) -> i64 {
+    Kolkata
+        .from_local_datetime(&time)
+        .single()
+        .map(|t| t.timestamp_millis())
+        .unwrap_or_default()
+}
+
+/// Length of an intraday timeframe such as `1m`, `15m` or `1h`
+fn timeframe_secs(timeframe: &str) -> Option<i64> {
+    let unit = timeframe.chars().last()?;
+    let count: i64 = timeframe[..timeframe.len() - unit.len_utf8()].parse().ok()?;
+    if count <= 0 {
+        return None;
+    }
+    match unit {
+        's' => Some(count),
+        'm' => Some(count * 60),
+        'h' => Some(count * 3600),
+        _ => None,
+    }
+}
+
+/// Price path of a candle as (offset ms, price, cumulative share of volume)
+///
+/// A bullish bar is assumed to dip first (open, low, high, close) and a
+/// bearish bar to rally first (open, high, low, close).
+fn candle_path(open: f64, high: f64, low: f64, close: f64, bar_ms: i64) -> [(i64, f64, f64); 4] {
+    let (first, second) = if close >= open { (low, high) } else { (high, low) };
+    let step = bar_ms / 4;
+    [
+        (0, open, 0.25),
+        (step, first, 0.5),
+        (2 * step, second, 0.75),
+        (3 * step, close, 1.0),
+    ]
+}
+
+/// Running day open/high/low for one symbol's events
+fn apply_day_stats(events: &mut [ReplayEvent], previous_close: Option<f64>) {
+    let Some(first) = events.first() else {
+        return;
+    };
+    let open = first.ltp;
+    let close = previous_close.unwrap_or(open);
+    let (mut high, mut low) = (open, open);
+    for event in events.iter_mut() {
+        high = high.max(event.ltp);
+        low = low.min(event.ltp);
+        event.open = open;
+        event.high = high;
+        event.low = low;
+        event.close = close;
+    }
+}
+
+/// Replay clock time after `elapsed` wall time at `speed`
+fn advance(clock: NaiveDateTime, elapsed: Duration, speed: u32) -> NaiveDateTime {
+    let millis = elapsed.as_millis() as i64 * speed as i64;
+    clock + ChronoDuration::milliseconds(millis)
+}
+
+/// Index of the first event after `time`
+fn seek_index(events: &[ReplayEvent], time: NaiveDateTime) -> usize {
+    events.partition_point(|event| event.time <= time)
+}
+
+/// Parse a clock time: a time of day on the replay date or a full timestamp
+fn parse_clock(date: NaiveDate, value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .map(|time| date.and_time(time))
+        .ok()
+        .or_else(|| parse_time(value))
+}
+
+/// Replay clock mapping wall time to replay time
+struct Clock {
+    time: NaiveDateTime,
+    anchor: Instant,
+    speed: u32,
+    paused: bool,
+}
+
+impl Clock {
+    fn new(time: NaiveDateTime, speed: u32) -> Self {
+        Self {
+            time,
+            anchor: Instant::now(),
+            speed,
+            paused: false,
+        }
+    }
+
+    fn now(&self) -> NaiveDateTime {
+        if self.paused {
+            self.time
+        } else {
+            advance(self.time, self.anchor.elapsed(), self.speed)
+        }
+    }
+
+    /// Fold elapsed time into the clock before changing it
+    fn rebase(&mut self) {
+        self.time = self.now();
+        self.anchor = Instant::now();
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.rebase();
+        self.paused = paused;
+    }
+
+    fn set_speed(&mut self, speed: u32) {
+        self.rebase();
+        self.speed = speed;
+    }
+
+    fn seek(&mut self, time: NaiveDateTime) {
+        self.time = time;
+        self.anchor = Instant::now();
+    }
+
+    /// Wall time until the replay clock reaches `time`
+    fn wall_until(&self, time: NaiveDateTime) -> Duration {
+        let millis = (time - self.now()).num_milliseconds().max(0) as u64;
+        Duration::from_millis(millis / self.speed.max(1) as u64)
+    }
+}
+
+/// Replay shared with the rest of the app
+///
+/// Holds the command channel of the running replay, its status and the
+/// latest quote of every replayed symbol.
+#[derive(Default)]
+pub struct ReplayController {
+    commands: Mutex<Option<mpsc::UnboundedSender<ReplayCommand>>>,
+    status: RwLock<ReplayStatus>,
+    quotes: DashMap<String, Quote>,
+}
+
+impl ReplayController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current replay status
+    pub fn status(&self) -> ReplayStatus {
+        self.status.read().clone()
+    }
+
+    /// Whether a replay is running or paused
+    pub fn is_active(&self) -> bool {
+        self.commands.lock().is_some()
+    }
+
+    /// Replay quotes for all of `symbols`, if every one is being replayed
+    pub fn quotes(&self, symbols: &[(String, String)]) -> Option<Vec<Quote>> {
+        if !self.is_active() {
+            return None;
+        }
+        symbols
+            .iter()
+            .map(|(exchange, symbol)| {
+                self.quotes
+                    .get(&format!("{}:{}", exchange, symbol))
+                    .map(|quote| quote.clone())
+            })
+            .collect()
+    }
+
+    fn send(&self, command: ReplayCommand) -> Result<()> {
+        let commands = self.commands.lock();
+        let sender = commands
+            .as_ref()
+            .ok_or_else(|| AppError::Validation("No replay is running".to_string()))?;
+        sender
+            .send(command)
+            .map_err(|_| AppError::Validation("No replay is running".to_string()))
+    }
+
+    fn update_status<F: FnOnce(&mut ReplayStatus)>(&self, f: F) -> ReplayStatus {
+        let mut status = self.status.write();
+        f(&mut status);
+        status.clone()
+    }
+
+    fn finish(&self, state: ReplayState) -> ReplayStatus {
+        *self.commands.lock() = None;
+        self.quotes.clear();
+        self.update_status(|status| status.state = state)
+    }
+}
+
+/// Replay service for business logic
+pub struct ReplayService;
+
+impl ReplayService {
+    /// Load the day and start the replay clock
+    pub async fn start(app_handle: AppHandle, state: &AppState, request: ReplayRequest) -> Result<ReplayStatus> {
+        if !state.sqlite.get_analyze_mode().unwrap_or(false) {
+            return Err(AppError::Validation("Replay runs in analyze mode only".to_string()));
+        }
+        if state.replay.is_active() {
+            return Err(AppError::Validation("A replay is already running".to_string()));
+        }
+        if request.symbols.is_empty() {
+            return Err(AppError::Validation("Select at least one symbol to replay".to_string()));
+        }
+        if !(1..=MAX_REPLAY_SPEED).contains(&request.speed) {
+            return Err(AppError::Validation(format!(
+                "Replay speed must be between 1x and {}x",
+                MAX_REPLAY_SPEED
+            )));
+        }
+        let date = NaiveDate::parse_from_str(&request.date, "%Y-%m-%d")
+            .map_err(|_| AppError::Validation(format!("Invalid replay date '{}'", request.date)))?;
+        let start_time = match &request.start_time {
+            Some(value) => Some(
+                parse_clock(date, value)
+                    .ok_or_else(|| AppError::Validation(format!("Invalid start time '{}'", value)))?,
+            ),
+            None => None,
+        };
+
+        let tokens: HashMap<(String, String), String> = request
+            .symbols
+            .iter()
+            .filter_map(|s| {
+                state
+                    .get_symbol_by_name(&s.exchange, &s.symbol)
+                    .map(|info| ((s.exchange.clone(), s.symbol.clone()), info.token))
+            })
+            .collect();
+
+        let duckdb = state.duckdb.clone();
+        let load_request = request.clone();
+        let events = tokio::task::spawn_blocking(move || Self::load_events(&duckdb, &load_request, date, &tokens))
+            .await
+            .map_err(|e| AppError::Internal(format!("Replay load task failed: {}", e)))??;
+
+        let (Some(first), Some(last)) = (events.first(), events.last()) else {
+            return Err(AppError::NotFound(format!("No stored data to replay on {}", request.date)));
+        };
+        let clock = start_time.unwrap_or(first.time).max(first.time);
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        {
+            let mut commands = state.replay.commands.lock();
+            if commands.is_some() {
+                return Err(AppError::Validation("A replay is already running".to_string()));
+            }
+            *commands = Some(tx);
+        }
+        state.replay.quotes.clear();
+        let status = state.replay.update_status(|status| {
+            *status = ReplayStatus {
+                state: ReplayState::Running,
+                date: Some(request.date.clone()),
+                symbols: request.symbols.clone(),
+                speed: request.speed,
+                clock: Some(clock.format(CLOCK_FORMAT).to_string()),
+                start: Some(first.time.format(CLOCK_FORMAT).to_string()),
+                end: Some(last.time.format(CLOCK_FORMAT).to_string()),
+                events_total: events.len(),
+                events_sent: 0,
+            }
+        });
+
+        info!(
+            "Starting replay of {} ({} symbols, {} events) at {}x",
+            request.date,
+            request.symbols.len(),
+            events.len(),
+            request.speed
+        );
+        let _ = app_handle.emit("replay_status", &status);
+
+        let speed = request.speed;
+        tauri::async_runtime::spawn(async move {
+            Self::run(app_handle, rx, events, Clock::new(clock, speed)).await;
+        });
+
+        Ok(status)
+    }
+
+    /// Pause the replay clock
+    pub fn pause(state: &AppState) -> Result<()> {
+        state.replay.send(ReplayCommand::Pause)
+    }
+
+    /// Resume a paused replay
+    pub fn resume(state: &AppState) -> Result<()> {
+        state.replay.send(ReplayCommand::Resume)
+    }
+
+    /// Jump the clock to a time of day (HH:MM[:SS]) or full timestamp
+    ///
+    /// Quotes jump to that time; sandbox orders filled earlier stay filled.
+    pub fn seek(state: &AppState, time: &str) -> Result<()> {
+        let status = state.replay.status();
+        let date = status
+            .date
+            .as_deref()
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .ok_or_else(|| AppError::Validation("No replay is running".to_string()))?;
+        let time = parse_clock(date, time).ok_or_else(|| AppError::Validation(format!("Invalid seek time '{}'", time)))?;
+        if time.date() != date {
+            return Err(AppError::Validation(format!("Seek time must be on {}", date)));
+        }
+        state.replay.send(ReplayCommand::Seek(time))
+    }
+
+    /// Change the replay speed
+    pub fn set_speed(state: &AppState, speed: u32) -> Result<()> {
+        if !(1..=MAX_REPLAY_SPEED).contains(&speed) {
+            return Err(AppError::Validation(format!(
+                "Replay speed must be between 1x and {}x",
+                MAX_REPLAY_SPEED
+            )));
+        }
+        state.replay.send(ReplayCommand::Speed(speed))
+    }
+
+    /// Stop the replay
+    pub fn stop(state: &AppState) -> Result<()> {
+        state.replay.send(ReplayCommand::Stop)
+    }
+
+    /// Current replay status
+    pub fn status(state: &AppState) -> ReplayStatus {
+        state.replay.status()
+    }
+
+    /// Replay clock loop
+    async fn run(
+        app_handle: AppHandle,
+        mut commands: mpsc::UnboundedReceiver<ReplayCommand>,
+        events: Vec<ReplayEvent>,
+        mut clock: Clock,
+    ) {
+        let state = app_handle.state::<AppState>();
+        let mut cursor = seek_index(&events, clock.time - ChronoDuration::milliseconds(1));
+        Self::prime(&state, &events[..cursor]);
+        let mut last_status = Instant::now();
+
+        let final_state = loop {
+            let now = clock.now();
+            let mut published = 0;
+            while cursor < events.len() && events[cursor].time <= now {
+                Self::publish(&state, &events[cursor]);
+                cursor += 1;
+                published += 1;
+                if published % EVENTS_PER_YIELD == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+
+            if cursor >= events.len() {
+                break ReplayState::Finished;
+            }
+
+            if last_status.elapsed().as_millis() >= STATUS_INTERVAL_MS {
+                last_status = Instant::now();
+                Self::emit_status(&app_handle, &state, &clock, cursor);
+            }
+
+            let wait = if clock.paused {
+                Duration::from_millis(MAX_SLEEP_MS)
+            } else {
+                clock
+                    .wall_until(events[cursor].time)
+                    .clamp(Duration::from_millis(1), Duration::from_millis(MAX_SLEEP_MS))
+            };
+
+            tokio::select! {
+                command = commands.recv() => {
+                    match command {
+                        Some(ReplayCommand::Pause) => clock.set_paused(true),
+                        Some(ReplayCommand::Resume) => clock.set_paused(false),
+                        Some(ReplayCommand::Speed(speed)) => clock.set_speed(speed),
+                        Some(ReplayCommand::Seek(time)) => {
+                            clock.seek(time);
+                            cursor = seek_index(&events, time);
+                            Self::prime(&state, &events[..cursor]);
+                        }
+                        Some(ReplayCommand::Stop) | None => break ReplayState::Stopped,
+                    }
+                    Self::emit_status(&app_handle, &state, &clock, cursor);
+                }
+                _ = tokio::time::sleep(wait) => {}
+            }
+        };
+
+        state.replay.update_status(|status| {
+            status.events_sent = cursor;
+            status.clock = Some(clock.now().min(events[events.len() - 1].time).format(CLOCK_FORMAT).to_string());
+        });
+        let status = state.replay.finish(final_state);
+        info!("Replay {:?} after {} of {} events", final_state, cursor, events.len());
+        let _ = app_handle.emit("replay_status", &status);
+    }
+
+    /// Publish one event as a live tick and match pending orders against it
+    fn publish(state: &AppState, event: &ReplayEvent) {
+        let tick = event.tick();
+        state.risk.update_tick(&tick);
+        state
+            .replay
+            .quotes
+            .insert(format!("{}:{}", event.exchange, event.symbol), event.quote());
+        if let Err(e) = AnalyzerService::match_orders(state, &event.exchange, &event.symbol, event.ltp) {
+            warn!("Replay failed to match sandbox orders: {}", e);
+        }
+        state.websocket.publish_tick(tick);
+    }
+
+    /// Bring quotes and LTPs up to the clock without matching orders
+    fn prime(state: &AppState, skipped: &[ReplayEvent]) {
+        let mut latest: HashMap<(&str, &str), &ReplayEvent> = HashMap::new();
+        for event in skipped {
+            latest.insert((event.exchange.as_str(), event.symbol.as_str()), event);
+        }
+        for event in latest.into_values() {
+            state.risk.update_tick(&event.tick());
+            state
+                .replay
+                .quotes
+                .insert(format!("{}:{}", event.exchange, event.symbol), event.quote());
+        }
+    }
+
+    fn emit_status(app_handle: &AppHandle, state: &AppState, clock: &Clock, cursor: usize) {
+        let status = state.replay.update_status(|status| {
+            status.state = if clock.paused { ReplayState::Paused } else { ReplayState::Running };
+            status.speed = clock.speed;
+            status.clock = Some(clock.now().format(CLOCK_FORMAT).to_string());
+            status.events_sent = cursor;
+        });
+        let _ = app_handle.emit("replay_status", &status);
+    }
+
+    /// Build the day's timeline for all requested symbols, in time order
+    fn load_events(
+        duckdb: &DuckDb,
+        request: &ReplayRequest,
+        date: NaiveDate,
+        tokens: &HashMap<(String, String), String>,
+    ) -> Result<Vec<ReplayEvent>> {
+        let from = format!("{} 00:00:00", date);
+        let to = format!("{} 23:59:59.999", date);
+        let bar_ms = match request.source {
+            BacktestSource::Candles => timeframe_secs(&request.timeframe).ok_or_else(|| {
+                AppError::Validation(format!("Replay needs an intraday timeframe, got '{}'", request.timeframe))
+            })? * 1000,
+            BacktestSource::Ticks => 0,
+        };
+        let invalid = |timestamp: &str| AppError::Internal(format!("Unreadable timestamp '{}' in DuckDB", timestamp));
+
+        let mut events = Vec::new();
+        for s in &request.symbols {
+            let token = tokens
+                .get(&(s.exchange.clone(), s.symbol.clone()))
+                .cloned()
+                .unwrap_or_default();
+            let event = |time: NaiveDateTime, ltp: f64, volume: i64| ReplayEvent {
+                time,
+                symbol: s.symbol.clone(),
+                exchange: s.exchange.clone(),
+                token: token.clone(),
+                ltp,
+                volume,
+                bid: 0.0,
+                ask: 0.0,
+                bid_qty: 0,
+                ask_qty: 0,
+                oi: 0,
+                open: 0.0,
+                high: 0.0,
+                low: 0.0,
+                close: 0.0,
+            };
+
+            let mut symbol_events = Vec::new();
+            match request.source {
+                BacktestSource::Candles => {
+                    let mut day_volume = 0;
+                    for row in duckdb.query_market_data(&s.symbol, &s.exchange, &request.timeframe, &from, &to)? {
+                        let time = parse_time(&row.timestamp).ok_or_else(|| invalid(&row.timestamp))?;
+                        for (offset, price, share) in candle_path(row.open, row.high, row.low, row.close, bar_ms) {
+                            let volume = day_volume + (row.volume as f64 * share).round() as i64;
+                            symbol_events.push(event(time + ChronoDuration::milliseconds(offset), price, volume));
+                        }
+                        day_volume += row.volume;
+                    }
+                }
+                BacktestSource::Ticks => {
+                    for tick in duckdb.query_ticks(&s.symbol, &s.exchange, &from, &to)? {
+                        let time = parse_time(&tick.timestamp).ok_or_else(|| invalid(&tick.timestamp))?;
+                        symbol_events.push(ReplayEvent {
+                            bid: tick.bid,
+                            ask: tick.ask,
+                            bid_qty: tick.bid_qty,
+                            ask_qty: tick.ask_qty,
+                            oi: tick.oi,
+                            ..event(time, tick.ltp, tick.volume)
+                        });
+                    }
+                }
+            }
+
+            if symbol_events.is_empty() {
+                warn!("No replay data for {}:{} on {}", s.exchange, s.symbol, date);
+                continue;
+            }
+            let previous_close = duckdb.get_previous_close(&s.symbol, &s.exchange, &from)?;
+            apply_day_stats(&mut symbol_events, previous_close);
+            events.extend(symbol_events);
+        }
+
+        // Stable, so each symbol's events keep their order
+        events.sort_by_key(|event| event.time);
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("2024-01-15 {}", time), "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn event(time: &str, ltp: f64) -> ReplayEvent {
+        ReplayEvent {
+            time: at(time),
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            token: String::new(),
+            ltp,
+            volume: 0,
+            bid: 0.0,
+            ask: 0.0,
+            bid_qty: 0,
+            ask_qty: 0,
+            oi: 0,
+            open: 0.0,
+            high: 0.0,
+            low: 0.0,
+            close: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_candle_path() {
+        let bullish = candle_path(100.0, 105.0, 98.0, 104.0, 60_000);
+        let prices: Vec<f64> = bullish.iter().map(|p| p.1).collect();
+        assert_eq!(prices, vec![100.0, 98.0, 105.0, 104.0]);
+        assert_eq!(bullish.map(|p| p.0), [0, 15_000, 30_000, 45_000]);
+        assert_eq!(bullish[3].2, 1.0);
+
+        let bearish = candle_path(100.0, 102.0, 95.0, 96.0, 60_000);
+        let prices: Vec<f64> = bearish.iter().map(|p| p.1).collect();
+        assert_eq!(prices, vec![100.0, 102.0, 95.0, 96.0]);
+    }
+
+    #[test]
+    fn test_day_stats() {
+        let mut events = vec![event("09:15:00", 100.0), event("09:16:00", 103.0), event("09:17:00", 97.0)];
+        apply_day_stats(&mut events, Some(99.0));
+
+        let last = &events[2];
+        assert_eq!((last.open, last.high, last.low, last.close), (100.0, 103.0, 97.0, 99.0));
+        assert_eq!(events[1].low, 100.0);
+
+        let tick = last.tick();
+        assert!(tick.replay);
+        assert!((tick.change - -2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_clock_and_seek() {
+        assert_eq!(advance(at("09:15:00"), Duration::from_secs(3), 100), at("09:20:00"));
+        assert_eq!(timeframe_secs("1m"), Some(60));
+        assert_eq!(timeframe_secs("1h"), Some(3600));
+        assert_eq!(timeframe_secs("1d"), None);
+
+        let events = vec![event("09:15:00", 1.0), event("09:16:00", 2.0), event("09:17:00", 3.0)];
+        assert_eq!(seek_index(&events, at("09:14:00")), 0);
+        assert_eq!(seek_index(&events, at("09:16:00")), 2);
+        assert_eq!(seek_index(&events, at("10:00:00")), 3);
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(parse_clock(date, "10:30"), Some(at("10:30:00")));
+        assert_eq!(parse_clock(date, "2024-01-15 10:30:15"), Some(at("10:30:15")));
+        assert_eq!(parse_clock(date, "later"), None);
+    }
+}
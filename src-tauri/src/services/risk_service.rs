@@ -0,0 +1,449 @@
+//! Risk Service
+//!
+//! Pre-trade risk checks and the kill switch. Every live and sandbox order
+//! passes `check_order`, which blocks new entries once a limit is breached
+//! (daily MTM loss, open positions, order value, per-symbol quantity).
+//! Exits that reduce an existing position are always allowed, so positions
+//! can still be closed while trading is locked.
+//!
+//! The kill switch cancels all orders, closes all positions and locks new
+//! entries for the rest of the IST trading day. Live and analyze mode each
+//! have their own kill switch and MTM snapshot, so a sandbox breach never
+//! locks live trading.
+
+use crate::brokers::types::{OrderRequest, Position};
+use crate::db::sqlite::{KillSwitchState, RiskConfig};
+use crate::error::{AppError, Result};
+use crate::services::{Notification, NotificationService, OrderService, PositionService};
+use crate::state::AppState;
+use crate::websocket::MarketTick;
+use dashmap::DashMap;
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// Latest LTPs and MTM snapshots, shared between the risk monitor and order checks
+#[derive(Default)]
+pub struct RiskTracker {
+    /// LTP by "EXCHANGE:SYMBOL", updated from the tick feed
+    ltps: DashMap<String, f64>,
+    /// Last MTM by mode (live/analyze)
+    snapshots: DashMap<String, MtmSnapshot>,
+}
+
+impl RiskTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the LTP from a market tick
+    pub fn update_tick(&self, tick: &MarketTick) {
+        if tick.ltp > 0.0 {
+            self.ltps.insert(format!("{}:{}", tick.exchange, tick.symbol), tick.ltp);
+        }
+    }
+
+    /// Latest traded price seen on the tick feed
+    pub fn ltp(&self, exchange: &str, symbol: &str) -> Option<f64> {
+        self.ltps.get(&format!("{}:{}", exchange, symbol)).map(|v| *v)
+    }
+
+    /// Last MTM computed for a mode (live/analyze)
+    pub fn snapshot(&self, mode: &str) -> Option<MtmSnapshot> {
+        self.snapshots.get(mode).map(|s| s.clone())
+    }
+
+    pub fn set_snapshot(&self, mode: &str, snapshot: MtmSnapshot) {
+        self.snapshots.insert(mode.to_string(), snapshot);
+    }
+}
+
+/// Day MTM across all positions
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MtmSnapshot {
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub mtm: f64,
+    pub open_positions: usize,
+    pub timestamp: String,
+}
+
+/// Risk status for the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskStatus {
+    pub config: RiskConfig,
+    pub kill_switch_active: bool,
+    pub kill_switch_reason: Option<String>,
+    pub mtm: Option<MtmSnapshot>,
+    pub loss_limit_breached: bool,
+}
+
+/// Result of activating the kill switch
+#[derive(Debug, Clone, Serialize)]
+pub struct KillSwitchResult {
+    pub reason: String,
+    pub orders_cancelled: usize,
+    pub positions_closed: usize,
+    pub errors: Vec<String>,
+    pub locked_until: String,
+}
+
+/// Risk service for business logic
+pub struct RiskService;
+
+impl RiskService {
+    /// Check an order against the kill switch and risk limits
+    ///
+    /// Returns `AppError::Validation("[CODE] ...")` when the order is blocked.
+    pub async fn check_order(state: &AppState, order: &OrderRequest, api_key: Option<&str>) -> Result<()> {
+        let kill_switch = Self::kill_switch_active(state);
+        let config = state.sqlite.get_risk_config()?;
+
+        if kill_switch.is_none() && !config.enabled {
+            return Ok(());
+        }
+
+        let result = PositionService::get_positions(state, api_key).await?;
+        let positions = result.positions;
+        let current = positions
+            .iter()
+            .find(|p| p.exchange == order.exchange && p.symbol == order.symbol && p.product == order.product)
+            .map(|p| p.quantity)
+            .unwrap_or(0);
+
+        let signed = if order.side.eq_ignore_ascii_case("BUY") { order.quantity } else { -order.quantity };
+        if is_reducing(current, signed) {
+            return Ok(());
+        }
+
+        if let Some(reason) = kill_switch {
+            return Err(AppError::Validation(format!(
+                "[KILL_SWITCH] Trading locked until the next session: {}",
+                reason
+            )));
+        }
+
+        let price = if order.price > 0.0 {
+            Some(order.price)
+        } else {
+            state.risk.ltp(&order.exchange, &order.symbol).or_else(|| {
+                positions
+                    .iter()
+                    .find(|p| p.exchange == order.exchange && p.symbol == order.symbol && p.ltp > 0.0)
+                    .map(|p| p.ltp)
+            })
+        };
+
+        let mtm = compute_mtm(&positions, |exchange, symbol| state.risk.ltp(exchange, symbol));
+        state.risk.set_snapshot(&result.mode, mtm.clone());
+
+        check_limits(&config, &mtm, &positions, current, signed, price).map_err(AppError::Validation)
+    }
+
+    /// Current risk status
+    pub fn get_status(state: &AppState) -> Result<RiskStatus> {
+        let config = state.sqlite.get_risk_config()?;
+        let kill_switch = Self::kill_switch_active(state);
+        let mtm = state.risk.snapshot(Self::mode(state));
+
+        let loss_limit_breached = config.max_daily_loss > 0.0
+            && mtm.as_ref().map(|m| m.mtm <= -config.max_daily_loss).unwrap_or(false);
+
+        Ok(RiskStatus {
+            config,
+            kill_switch_active: kill_switch.is_some(),
+            kill_switch_reason: kill_switch,
+            mtm,
+            loss_limit_breached,
+        })
+    }
+
+    /// Refresh the MTM snapshot from current positions and ticks
+    pub async fn refresh_mtm(state: &AppState) -> Result<MtmSnapshot> {
+        let result = PositionService::get_positions(state, None).await?;
+        let mtm = compute_mtm(&result.positions, |exchange, symbol| state.risk.ltp(exchange, symbol));
+        state.risk.set_snapshot(&result.mode, mtm.clone());
+        Ok(mtm)
+    }
+
+    /// Activate the kill switch for the current mode
+    ///
+    /// Locks entries first so nothing new goes out while orders are
+    /// cancelled and positions closed.
+    pub async fn activate_kill_switch(
+        state: &AppState,
+        reason: &str,
+        api_key: Option<&str>,
+    ) -> Result<KillSwitchResult> {
+        let today = Self::today();
+        let mode = Self::mode(state);
+        warn!("Kill switch activated ({}): {}", mode, reason);
+        state.sqlite.set_kill_switch(mode, &today, reason)?;
+
+        let mut errors = Vec::new();
+
+        let orders_cancelled = match OrderService::cancel_all_orders(state, api_key).await {
+            Ok(results) => {
+                errors.extend(results.iter().filter(|r| !r.success).map(|r| {
+                    format!("Cancel {} failed: {}", r.order_id, r.message)
+                }));
+                results.iter().filter(|r| r.success).count()
+            }
+            Err(e) => {
+                errors.push(format!("Cancel all orders failed: {}", e));
+                0
+            }
+        };
+
+        let positions_closed = match PositionService::close_all_positions(state, api_key).await {
+            Ok(results) => {
+                errors.extend(results.iter().filter(|r| !r.success).map(|r| r.message.clone()));
+                results.iter().filter(|r| r.success).count()
+            }
+            Err(e) => {
+                errors.push(format!("Close all positions failed: {}", e));
+                0
+            }
+        };
+
+        info!(
+            "Kill switch: {} orders cancelled, {} positions closed, {} errors",
+            orders_cancelled,
+            positions_closed,
+            errors.len()
+        );
+
+        NotificationService::notify(
+            state,
+            Notification::new(
+                "kill_switch",
+                "Kill switch activated",
+                format!(
+                    "{} - {} orders cancelled, {} positions closed. Trading locked for {}",
+                    reason, orders_cancelled, positions_closed, today
+                ),
+                serde_json::json!({
+                    "reason": reason,
+                    "orders_cancelled": orders_cancelled,
+                    "positions_closed": positions_closed,
+                    "errors": errors,
+                }),
+            ),
+        );
+
+        Ok(KillSwitchResult {
+            reason: reason.to_string(),
+            orders_cancelled,
+            positions_closed,
+            errors,
+            locked_until: today,
+        })
+    }
+
+    /// Reason for the current mode's kill switch if it is active for today's session
+    pub fn kill_switch_active(state: &AppState) -> Option<String> {
+        match state.sqlite.get_kill_switch(Self::mode(state)) {
+            Ok(kill_switch) => active_reason(kill_switch, &Self::today()),
+            Err(e) => {
+                warn!("Failed to read kill switch state: {}", e);
+                None
+            }
+        }
+    }
+
+    fn mode(state: &AppState) -> &'static str {
+        if state.sqlite.get_analyze_mode().unwrap_or(false) {
+            "analyze"
+        } else {
+            "live"
+        }
+    }
+
+    fn today() -> String {
+        chrono::Utc::now()
+            .with_timezone(&chrono_tz::Asia::Kolkata)
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+}
+
+/// Reason for a kill switch set on `today`
+fn active_reason(kill_switch: KillSwitchState, today: &str) -> Option<String> {
+    match kill_switch {
+        KillSwitchState { date: Some(date), reason } if date == today => {
+            Some(reason.unwrap_or_else(|| "Kill switch".to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Whether an order only reduces the current position (an exit)
+fn is_reducing(current: i32, signed: i32) -> bool {
+    current != 0 && current.signum() != signed.signum() && signed.abs() <= current.abs()
+}
+
+/// Day MTM: realized P&L plus unrealized marked at the tick LTP when known
+fn compute_mtm<F>(positions: &[Position], ltp: F) -> MtmSnapshot
+where
+    F: Fn(&str, &str) -> Option<f64>,
+{
+    let mut snapshot = MtmSnapshot {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+
+    for position in positions {
+        snapshot.realized_pnl += position.realized_pnl;
+
+        if position.quantity != 0 {
+            snapshot.open_positions += 1;
+            snapshot.unrealized_pnl += match ltp(&position.exchange, &position.symbol) {
+                Some(ltp) => (ltp - position.average_price) * position.quantity as f64,
+                None => position.unrealized_pnl,
+            };
+        }
+    }
+
+    snapshot.mtm = snapshot.realized_pnl + snapshot.unrealized_pnl;
+    snapshot
+}
+
+/// Check a new entry against the configured limits
+fn check_limits(
+    config: &RiskConfig,
+    mtm: &MtmSnapshot,
+    positions: &[Position],
+    current: i32,
+    signed: i32,
+    price: Option<f64>,
+) -> std::result::Result<(), String> {
+    if config.max_daily_loss > 0.0 && mtm.mtm <= -config.max_daily_loss {
+        return Err(format!(
+            "[DAILY_LOSS] Day MTM {:.2} breaches the loss limit of {:.2}",
+            mtm.mtm, config.max_daily_loss
+        ));
+    }
+
+    if config.max_open_positions > 0 && current == 0 {
+        let open = positions.iter().filter(|p| p.quantity != 0).count();
+        if open >= config.max_open_positions as usize {
+            return Err(format!(
+                "[MAX_POSITIONS] {} positions open, limit is {}",
+                open, config.max_open_positions
+            ));
+        }
+    }
+
+    if config.max_order_value > 0.0 {
+        if let Some(price) = price {
+            let value = price * signed.abs() as f64;
+            if value > config.max_order_value {
+                return Err(format!(
+                    "[ORDER_VALUE] Order value {:.2} exceeds the limit of {:.2}",
+                    value, config.max_order_value
+                ));
+            }
+        }
+    }
+
+    if config.max_symbol_quantity > 0 && (current + signed).abs() > config.max_symbol_quantity {
+        return Err(format!(
+            "[SYMBOL_QUANTITY] Net quantity {} exceeds the per-symbol limit of {}",
+            current + signed,
+            config.max_symbol_quantity
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(symbol: &str, quantity: i32, average_price: f64, realized_pnl: f64) -> Position {
+        Position {
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            product: "MIS".to_string(),
+            quantity,
+            overnight_quantity: 0,
+            average_price,
+            ltp: average_price,
+            pnl: 0.0,
+            realized_pnl,
+            unrealized_pnl: 0.0,
+            buy_quantity: 0,
+            buy_value: 0.0,
+            sell_quantity: 0,
+            sell_value: 0.0,
+        }
+    }
+
+    fn config() -> RiskConfig {
+        RiskConfig {
+            enabled: true,
+            max_daily_loss: 5000.0,
+            max_open_positions: 2,
+            max_order_value: 100000.0,
+            max_symbol_quantity: 500,
+            flatten_on_breach: false,
+        }
+    }
+
+    #[test]
+    fn test_sandbox_kill_switch_leaves_live_alone() {
+        let db = crate::db::sqlite::SqliteDb::in_memory().unwrap();
+        let today = "2024-06-03";
+        db.set_kill_switch("analyze", today, "Daily loss limit breached").unwrap();
+
+        assert!(active_reason(db.get_kill_switch("analyze").unwrap(), today).is_some());
+        assert!(active_reason(db.get_kill_switch("live").unwrap(), today).is_none());
+        // Locks only last for the day they were set on
+        assert!(active_reason(db.get_kill_switch("analyze").unwrap(), "2024-06-04").is_none());
+
+        let tracker = RiskTracker::new();
+        tracker.set_snapshot("analyze", MtmSnapshot { mtm: -10_000.0, ..Default::default() });
+        assert!(tracker.snapshot("live").is_none());
+    }
+
+    #[test]
+    fn test_exits_are_reducing() {
+        assert!(is_reducing(100, -50));
+        assert!(is_reducing(-100, 100));
+        assert!(!is_reducing(100, -150));
+        assert!(!is_reducing(0, -10));
+        assert!(!is_reducing(100, 10));
+    }
+
+    #[test]
+    fn test_mtm_uses_tick_ltp() {
+        let positions = vec![position("SBIN", 100, 800.0, -200.0), position("INFY", 0, 0.0, 300.0)];
+        let mtm = compute_mtm(&positions, |_, symbol| (symbol == "SBIN").then_some(790.0));
+
+        assert_eq!(mtm.open_positions, 1);
+        assert_eq!(mtm.realized_pnl, 100.0);
+        assert_eq!(mtm.unrealized_pnl, -1000.0);
+        assert_eq!(mtm.mtm, -900.0);
+    }
+
+    #[test]
+    fn test_limits() {
+        let positions = vec![position("SBIN", 100, 800.0, 0.0), position("INFY", -50, 1500.0, 0.0)];
+        let mut mtm = MtmSnapshot::default();
+
+        assert!(check_limits(&config(), &mtm, &positions, 100, 100, Some(800.0)).is_ok());
+
+        let err = check_limits(&config(), &mtm, &positions, 0, 10, Some(100.0)).unwrap_err();
+        assert!(err.starts_with("[MAX_POSITIONS]"));
+
+        let err = check_limits(&config(), &mtm, &positions, 100, 200, Some(800.0)).unwrap_err();
+        assert!(err.starts_with("[ORDER_VALUE]"));
+
+        let err = check_limits(&config(), &mtm, &positions, 100, 450, Some(1.0)).unwrap_err();
+        assert!(err.starts_with("[SYMBOL_QUANTITY]"));
+
+        mtm.mtm = -5000.0;
+        let err = check_limits(&config(), &mtm, &positions, 100, 1, Some(800.0)).unwrap_err();
+        assert!(err.starts_with("[DAILY_LOSS]"));
+    }
+}
@@ -4,8 +4,10 @@
 //! Called by both Tauri commands and REST API.
 
 use crate::brokers::types::OrderRequest;
-use crate::error::Result;
-use crate::services::{OrderService, PlaceOrderResult, PositionService};
+use crate::error::{AppError, Result};
+use crate::services::{
+    BasketResult, BasketService, ChildOrder, OrderService, PositionService, RiskService,
+};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -20,6 +22,9 @@ pub struct SmartOrderRequest {
     pub product: String,
     pub pricetype: Option<String>,
     pub price: Option<f64>,
+    /// Broker order tag (client order id)
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 /// Split order request
@@ -33,6 +38,9 @@ pub struct SplitOrderRequest {
     pub product: String,
     pub pricetype: Option<String>,
     pub price: Option<f64>,
+    /// Broker order tag (client order id)
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 /// Result of smart order
@@ -43,6 +51,9 @@ pub struct SmartOrderResult {
     pub action_taken: String, // "BUY", "SELL", or "NONE"
     pub quantity: i32,
     pub message: String,
+    /// Orders placed (several when sliced at the freeze limit)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub child_orders: Vec<ChildOrder>,
 }
 
 /// Result of split order
@@ -100,6 +111,7 @@ impl SmartOrderService {
                 action_taken: "NONE".to_string(),
                 quantity: 0,
                 message: format!("No action needed. Current position: {}", current_qty),
+                child_orders: Vec::new(),
             });
         }
 
@@ -116,6 +128,10 @@ impl SmartOrderService {
             disclosed_quantity: None,
             validity: "DAY".to_string(),
             amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: req.tag.clone(),
             broker_symbol: None,  // Set by OrderService from symbol cache
             symbol_token: None,   // Set by OrderService from symbol cache
         };
@@ -124,6 +140,7 @@ impl SmartOrderService {
 
         Ok(SmartOrderResult {
             success: result.success,
+            child_orders: result.placed_orders(order_qty),
             order_id: result.order_id,
             action_taken: order_action,
             quantity: order_qty,
@@ -146,77 +163,95 @@ impl SmartOrderService {
         let total_qty = req.quantity;
         let num_orders = (total_qty + split_size - 1) / split_size;
 
-        let mut order_ids = Vec::new();
-        let mut failed_orders = Vec::new();
-        let mut remaining = total_qty;
+        let order_request = OrderRequest {
+            symbol: req.symbol.clone(),
+            exchange: req.exchange.clone(),
+            side: req.action.clone(),
+            quantity: total_qty,
+            order_type: req.pricetype.clone().unwrap_or_else(|| "MARKET".to_string()),
+            product: req.product.clone(),
+            price: req.price.unwrap_or(0.0),
+            trigger_price: None,
+            disclosed_quantity: None,
+            validity: "DAY".to_string(),
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: req.tag.clone(),
+            broker_symbol: None,  // Set by OrderService from symbol cache
+            symbol_token: None,   // Set by OrderService from symbol cache
+        };
+
+        RiskService::check_order(state, &order_request, api_key).await?;
+
+        let (children, failures) = Self::place_slices(state, &order_request, split_size, api_key).await;
 
-        for i in 0..num_orders {
-            let qty = std::cmp::min(remaining, split_size);
+        Ok(SplitOrderResult {
+            success: failures.is_empty(),
+            total_quantity: total_qty,
+            split_size,
+            num_orders,
+            order_ids: children.into_iter().map(|c| c.order_id).collect(),
+            failed_orders: failures
+                .into_iter()
+                .map(|(i, e)| format!("Order {}: {}", i, e))
+                .collect(),
+        })
+    }
+
+    /// Place an order as consecutive slices of at most `slice_size`
+    ///
+    /// Shared by split orders and freeze-limit slicing. Callers risk-check
+    /// the parent order; each slice then goes through
+    /// `OrderService::place_slice`, and a failed slice doesn't stop the rest.
+    /// Returns the placed children and the failed slice numbers (1-based)
+    /// with their errors.
+    pub async fn place_slices(
+        state: &AppState,
+        order: &OrderRequest,
+        slice_size: i32,
+        api_key: Option<&str>,
+    ) -> (Vec<ChildOrder>, Vec<(i32, AppError)>) {
+        let slice_size = slice_size.max(1);
+        let mut children = Vec::new();
+        let mut failures = Vec::new();
+        let mut remaining = order.quantity;
+        let mut slice = 0;
+
+        while remaining > 0 {
+            let qty = std::cmp::min(remaining, slice_size);
             remaining -= qty;
+            slice += 1;
 
-            let order_request = OrderRequest {
-                symbol: req.symbol.clone(),
-                exchange: req.exchange.clone(),
-                side: req.action.clone(),
-                quantity: qty,
-                order_type: req.pricetype.clone().unwrap_or_else(|| "MARKET".to_string()),
-                product: req.product.clone(),
-                price: req.price.unwrap_or(0.0),
-                trigger_price: None,
-                disclosed_quantity: None,
-                validity: "DAY".to_string(),
-                amo: false,
-                broker_symbol: None,  // Set by OrderService from symbol cache
-                symbol_token: None,   // Set by OrderService from symbol cache
-            };
+            let mut child = order.clone();
+            child.quantity = qty;
 
-            match OrderService::place_order(state, order_request, api_key).await {
+            match OrderService::place_slice(state, child, api_key).await {
                 Ok(result) => {
                     if let Some(order_id) = result.order_id {
-                        order_ids.push(order_id);
+                        children.push(ChildOrder { order_id, quantity: qty });
                     }
                 }
-                Err(e) => {
-                    failed_orders.push(format!("Order {}: {}", i + 1, e));
-                }
+                Err(e) => failures.push((slice, e)),
             }
         }
 
-        Ok(SplitOrderResult {
-            success: failed_orders.is_empty(),
-            total_quantity: total_qty,
-            split_size,
-            num_orders,
-            order_ids,
-            failed_orders,
-        })
+        (children, failures)
     }
 
     /// Place basket order (multiple orders at once)
+    ///
+    /// With `all_or_none`, a failed leg rolls back the legs already placed
+    /// (see `BasketService`).
     pub async fn place_basket_order(
         state: &AppState,
         orders: Vec<OrderRequest>,
+        all_or_none: bool,
         api_key: Option<&str>,
-    ) -> Result<Vec<PlaceOrderResult>> {
+    ) -> Result<BasketResult> {
         info!("SmartOrderService::place_basket_order - {} orders", orders.len());
-
-        let mut results = Vec::new();
-
-        for order in orders {
-            match OrderService::place_order(state, order, api_key).await {
-                Ok(result) => results.push(result),
-                Err(e) => {
-                    results.push(PlaceOrderResult {
-                        success: false,
-                        order_id: None,
-                        message: e.to_string(),
-                        mode: "live".to_string(),
-                    });
-                }
-            }
-        }
-
-        Ok(results)
+        BasketService::execute(state, orders, all_or_none, api_key).await
     }
 
     // ========================================================================
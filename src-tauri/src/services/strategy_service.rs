@@ -0,0 +1,422 @@
+//! Strategy Service
+//!
+//! Strategy-scoped order management. Orders placed for a strategy are
+//! recorded in `strategy_orders`, which lets a strategy be squared off
+//! without touching orders or positions that belong to anything else.
+
+use crate::brokers::types::{Order, OrderRequest};
+use crate::db::sqlite::models::{Strategy, StrategyOrder};
+use crate::error::{AppError, Result};
+use crate::services::{OrderService, OrderbookService, PlaceOrderResult, PositionService};
+use crate::state::AppState;
+use crate::websocket::normalize_status;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Attempts per square-off action (orderbook fetch, cancel, close)
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Base delay between attempts, multiplied by the attempt number
+const RETRY_DELAY_SECS: u64 = 2;
+
+/// A position leg closed during square-off
+#[derive(Debug, Clone, Serialize)]
+pub struct SquareoffLeg {
+    /// Mode (live/analyze) the leg was closed in
+    pub mode: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub product: String,
+    /// Signed quantity closed (positive = was long)
+    pub quantity: i32,
+    pub order_id: Option<String>,
+}
+
+/// Summary of a strategy square-off, emitted as `strategy_squareoff`
+#[derive(Debug, Clone, Serialize)]
+pub struct SquareoffSummary {
+    pub strategy_id: i64,
+    pub strategy_name: String,
+    pub mode: String,
+    pub cancelled_orders: Vec<String>,
+    pub closed: Vec<SquareoffLeg>,
+    pub failures: Vec<String>,
+    pub timestamp: String,
+}
+
+impl SquareoffSummary {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Strategy service for business logic
+pub struct StrategyService;
+
+impl StrategyService {
+    /// Square off a strategy
+    ///
+    /// Cancels the strategy's open orders from today and closes the net
+    /// position its filled orders opened, capped at the position actually
+    /// held. Orders are squared off against the book of the mode they were
+    /// placed in, so switching between live and analyze mode during the
+    /// session doesn't leave the other mode's positions open.
+    pub async fn square_off(state: &AppState, strategy: &Strategy) -> Result<SquareoffSummary> {
+        let current = state.sqlite.get_analyze_mode().unwrap_or(false);
+
+        info!("StrategyService::square_off - {} ({})", strategy.name, mode_name(current));
+
+        let mut summary = SquareoffSummary {
+            strategy_id: strategy.id,
+            strategy_name: strategy.name.clone(),
+            mode: mode_name(current).to_string(),
+            cancelled_orders: Vec::new(),
+            closed: Vec::new(),
+            failures: Vec::new(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let since = Self::session_start_utc();
+        for analyze_mode in [current, !current] {
+            let orders = state
+                .sqlite
+                .get_strategy_orders(strategy.id, mode_name(analyze_mode), &since)?;
+            if orders.is_empty() {
+                continue;
+            }
+
+            let result = Self::square_off_mode(state, strategy, &orders, analyze_mode, current, &mut summary).await;
+            match result {
+                Ok(()) => {}
+                Err(e) if analyze_mode == current => return Err(e),
+                // The other mode's book (e.g. live without a broker session) doesn't block this one
+                Err(e) => summary
+                    .failures
+                    .push(format!("Square-off in {} mode failed: {}", mode_name(analyze_mode), e)),
+            }
+        }
+
+        info!(
+            "Strategy {} squared off: {} cancelled, {} closed, {} failed",
+            strategy.name,
+            summary.cancelled_orders.len(),
+            summary.closed.len(),
+            summary.failures.len()
+        );
+
+        Ok(summary)
+    }
+
+    /// Square off a strategy's orders of one mode against that mode's book
+    async fn square_off_mode(
+        state: &AppState,
+        strategy: &Strategy,
+        orders: &[StrategyOrder],
+        analyze_mode: bool,
+        current: bool,
+        summary: &mut SquareoffSummary,
+    ) -> Result<()> {
+        let mode = mode_name(analyze_mode);
+        let fetch_book = || async {
+            let book = retry("fetch orderbook", || {
+                OrderbookService::get_orderbook_in(state, analyze_mode, None)
+            })
+            .await?;
+            Ok::<_, AppError>(
+                book.orders
+                    .into_iter()
+                    .filter(|o| orders.iter().any(|so| so.order_id == o.order_id))
+                    .collect::<Vec<Order>>(),
+            )
+        };
+
+        let mut own_orders = fetch_book().await?;
+
+        // Cancel first so nothing fills behind the closing orders
+        let mut cancelled = false;
+        for order in &own_orders {
+            let status = normalize_status(&order.status);
+            if status != "open" && status != "trigger pending" {
+                continue;
+            }
+
+            match retry("cancel order", || {
+                OrderService::cancel_order_in(state, &order.order_id, None, analyze_mode, None)
+            })
+            .await
+            {
+                Ok(_) => {
+                    cancelled = true;
+                    summary.cancelled_orders.push(order.order_id.clone());
+                }
+                Err(e) => summary
+                    .failures
+                    .push(format!("Cancel {} failed: {}", order.order_id, e)),
+            }
+        }
+
+        // Fills that landed while cancelling are closed too
+        if cancelled {
+            own_orders = fetch_book().await?;
+        }
+        let own_refs: Vec<&Order> = own_orders.iter().collect();
+
+        for ((exchange, symbol, product), net_qty) in net_positions(orders, &own_refs) {
+            let position = match retry("fetch position", || {
+                PositionService::get_open_position_in(state, &exchange, &symbol, &product, analyze_mode, None)
+            })
+            .await
+            {
+                Ok(position) => position,
+                Err(e) => {
+                    summary
+                        .failures
+                        .push(format!("Position lookup for {}:{} failed: {}", exchange, symbol, e));
+                    continue;
+                }
+            };
+
+            let qty = close_quantity(net_qty, position.as_ref().map(|p| p.quantity).unwrap_or(0));
+            if qty == 0 {
+                continue;
+            }
+
+            let side = if qty > 0 { "SELL" } else { "BUY" };
+            let order = OrderRequest {
+                symbol: symbol.clone(),
+                exchange: exchange.clone(),
+                side: side.to_string(),
+                quantity: qty.abs(),
+                price: 0.0,
+                order_type: "MARKET".to_string(),
+                product: product.clone(),
+                validity: "DAY".to_string(),
+                trigger_price: None,
+                disclosed_quantity: None,
+                amo: false,
+                target: None,
+                stoploss: None,
+                trailing_stoploss: None,
+                tag: None,
+                broker_symbol: None,  // Set by OrderService from symbol cache
+                symbol_token: None,   // Set by OrderService from symbol cache
+            };
+
+            let placed = retry("close position", || {
+                place_close(state, order.clone(), analyze_mode, analyze_mode == current)
+            })
+            .await;
+            match placed {
+                Ok(result) => {
+                    for child in result.placed_orders(qty.abs()) {
+                        if let Err(e) = state.sqlite.record_strategy_order(
+                            strategy.id,
+                            &child.order_id,
+                            mode,
+                            &exchange,
+                            &symbol,
+                            &product,
+                            side,
+                            child.quantity,
+                            "squareoff",
+                        ) {
+                            warn!("Failed to record square-off order {}: {}", child.order_id, e);
+                        }
+                    }
+                    summary.failures.extend(
+                        result
+                            .failed_slices
+                            .iter()
+                            .map(|f| format!("Close {}:{} {}", exchange, symbol, f)),
+                    );
+
+                    summary.closed.push(SquareoffLeg {
+                        mode: mode.to_string(),
+                        exchange,
+                        symbol,
+                        product,
+                        quantity: qty,
+                        order_id: result.order_id,
+                    });
+                }
+                Err(e) => summary
+                    .failures
+                    .push(format!("Close {}:{} failed: {}", exchange, symbol, e)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start of today's IST session as a UTC SQLite timestamp
+    pub(crate) fn session_start_utc() -> String {
+        use chrono::TimeZone;
+        use chrono_tz::Asia::Kolkata;
+
+        let today = chrono::Utc::now().with_timezone(&Kolkata).date_naive();
+        Kolkata
+            .from_local_datetime(&today.and_hms_opt(0, 0, 0).unwrap_or_default())
+            .single()
+            .map(|dt| dt.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    }
+}
+
+fn mode_name(analyze_mode: bool) -> &'static str {
+    if analyze_mode {
+        "analyze"
+    } else {
+        "live"
+    }
+}
+
+/// Place a closing order in a mode
+///
+/// The active mode goes through the full order pipeline; the other mode
+/// places the exit directly against its own book.
+async fn place_close(
+    state: &AppState,
+    order: OrderRequest,
+    analyze_mode: bool,
+    is_current: bool,
+) -> Result<PlaceOrderResult> {
+    if is_current {
+        OrderService::place_order(state, order, None).await
+    } else {
+        OrderService::place_exit_in(state, order, analyze_mode).await
+    }
+}
+
+/// Net filled quantity per (exchange, symbol, product) across a strategy's orders
+fn net_positions(
+    orders: &[StrategyOrder],
+    book: &[&Order],
+) -> BTreeMap<(String, String, String), i32> {
+    let mut net = BTreeMap::new();
+
+    for order in orders {
+        let Some(booked) = book.iter().find(|o| o.order_id == order.order_id) else {
+            continue;
+        };
+
+        let filled = booked.filled_quantity;
+        if filled == 0 {
+            continue;
+        }
+
+        let signed = if order.side.eq_ignore_ascii_case("BUY") { filled } else { -filled };
+        *net.entry((order.exchange.clone(), order.symbol.clone(), order.product.clone()))
+            .or_insert(0) += signed;
+    }
+
+    net.retain(|_, qty| *qty != 0);
+    net
+}
+
+/// Quantity to close: the strategy's net, capped at the position actually held
+///
+/// Returns 0 when the position is flat or on the other side (already closed
+/// or reversed by something else).
+fn close_quantity(strategy_net: i32, position_qty: i32) -> i32 {
+    if strategy_net > 0 && position_qty > 0 {
+        strategy_net.min(position_qty)
+    } else if strategy_net < 0 && position_qty < 0 {
+        strategy_net.max(position_qty)
+    } else {
+        0
+    }
+}
+
+/// Run an action up to `MAX_ATTEMPTS` times with a linear backoff
+async fn retry<T, F, Fut>(what: &str, mut action: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match action().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                warn!("Square-off: {} failed (attempt {}): {}", what, attempt, e);
+                tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECS * attempt as u64)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy_order(order_id: &str, symbol: &str, side: &str, quantity: i32) -> StrategyOrder {
+        StrategyOrder {
+            id: 0,
+            strategy_id: 1,
+            order_id: order_id.to_string(),
+            mode: "analyze".to_string(),
+            exchange: "NSE".to_string(),
+            symbol: symbol.to_string(),
+            product: "MIS".to_string(),
+            side: side.to_string(),
+            quantity,
+            reason: "signal".to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    fn book_order(order_id: &str, filled: i32) -> Order {
+        Order {
+            order_id: order_id.to_string(),
+            exchange_order_id: None,
+            symbol: String::new(),
+            exchange: "NSE".to_string(),
+            side: String::new(),
+            quantity: filled,
+            filled_quantity: filled,
+            pending_quantity: 0,
+            price: 0.0,
+            trigger_price: 0.0,
+            average_price: 0.0,
+            order_type: "MARKET".to_string(),
+            product: "MIS".to_string(),
+            status: "complete".to_string(),
+            validity: "DAY".to_string(),
+            order_timestamp: String::new(),
+            exchange_timestamp: None,
+            rejection_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_net_positions_uses_fills() {
+        let orders = vec![
+            strategy_order("1", "SBIN", "BUY", 10),
+            strategy_order("2", "SBIN", "SELL", 4),
+            strategy_order("3", "INFY", "SELL", 5),
+            strategy_order("4", "TCS", "BUY", 5),
+        ];
+        let book = [book_order("1", 10), book_order("2", 4), book_order("3", 5), book_order("4", 0)];
+        let refs: Vec<&Order> = book.iter().collect();
+
+        let net = net_positions(&orders, &refs);
+        assert_eq!(net.len(), 2);
+        assert_eq!(net[&("NSE".into(), "SBIN".into(), "MIS".into())], 6);
+        assert_eq!(net[&("NSE".into(), "INFY".into(), "MIS".into())], -5);
+    }
+
+    #[test]
+    fn test_close_quantity_capped_by_position() {
+        assert_eq!(close_quantity(10, 25), 10);
+        assert_eq!(close_quantity(10, 4), 4);
+        assert_eq!(close_quantity(-5, -8), -5);
+        assert_eq!(close_quantity(-5, -2), -2);
+        assert_eq!(close_quantity(10, 0), 0);
+        assert_eq!(close_quantity(10, -3), 0);
+    }
+}
@@ -0,0 +1,425 @@
+//! Pre-trade Validation Service
+//!
+//! Checks orders against the symbol master before they reach the broker or
+//! the sandbox:
+//! - Symbol exists in the master contract
+//! - Quantity is a multiple of the lot size
+//! - Price and trigger price are aligned to the tick size
+//! - Product is valid for the exchange (no CNC on derivatives, no NRML on cash)
+//! - Quantity is within the exchange freeze limit
+//!
+//! Depending on the configured mode, problems are rejected or auto-corrected
+//! where a safe correction exists.
+
+use crate::brokers::types::OrderRequest;
+use crate::error::{AppError, Result};
+use crate::state::{AppState, SymbolInfo};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Exchanges that trade derivatives (NRML carry-forward, no CNC)
+const DERIVATIVE_EXCHANGES: &[&str] = &["NFO", "BFO", "CDS", "BCD", "MCX", "NCDEX"];
+
+/// Cash segment exchanges (CNC delivery, no NRML)
+const CASH_EXCHANGES: &[&str] = &["NSE", "BSE"];
+
+/// Tolerance for floating point tick alignment
+const TICK_EPSILON: f64 = 1e-6;
+
+/// How pre-trade validation problems are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationMode {
+    /// Skip pre-trade validation
+    Off,
+    /// Reject any order that fails validation
+    Reject,
+    /// Correct lot size, tick size and product; reject the rest
+    Correct,
+}
+
+impl ValidationMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationMode::Off => "off",
+            ValidationMode::Reject => "reject",
+            ValidationMode::Correct => "correct",
+        }
+    }
+
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.to_lowercase().as_str() {
+            "off" => Some(ValidationMode::Off),
+            "reject" => Some(ValidationMode::Reject),
+            "correct" => Some(ValidationMode::Correct),
+            _ => None,
+        }
+    }
+}
+
+/// A single validation finding
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidationIssue {
+    /// SYMBOL_NOT_FOUND, EXCHANGE, QUANTITY, LOT_SIZE, TICK_SIZE, PRODUCT or FREEZE_QTY
+    pub code: String,
+    pub field: String,
+    pub message: String,
+    /// Corrected value, when the issue was auto-corrected
+    pub corrected: Option<String>,
+}
+
+impl ValidationIssue {
+    fn new(code: &str, field: &str, message: String) -> Self {
+        Self {
+            code: code.to_string(),
+            field: field.to_string(),
+            message,
+            corrected: None,
+        }
+    }
+
+    fn corrected(mut self, value: impl ToString) -> Self {
+        self.corrected = Some(value.to_string());
+        self
+    }
+}
+
+/// Validated order with any corrections that were applied
+#[derive(Debug, Clone)]
+pub struct ValidatedOrder {
+    pub order: OrderRequest,
+    pub corrections: Vec<ValidationIssue>,
+}
+
+/// Pre-trade validation service
+pub struct ValidationService;
+
+impl ValidationService {
+    /// Validate an order using the configured mode and the symbol master
+    ///
+    /// Returns the (possibly corrected) order, or a validation error listing
+    /// every problem as `[CODE] message`.
+    pub fn validate_order(state: &AppState, order: OrderRequest) -> Result<ValidatedOrder> {
+        let mode = state
+            .sqlite
+            .get_order_validation_mode()
+            .ok()
+            .and_then(|m| ValidationMode::parse(&m))
+            .unwrap_or(ValidationMode::Reject);
+
+        if mode == ValidationMode::Off {
+            return Ok(ValidatedOrder { order, corrections: Vec::new() });
+        }
+
+        let symbol = state.get_symbol_by_name(&order.exchange, &order.symbol);
+        let freeze_qty = Self::freeze_qty(state, &order.exchange, &order.symbol);
+
+        match Self::check(order, symbol.as_ref(), freeze_qty, mode) {
+            Ok(validated) => {
+                for issue in &validated.corrections {
+                    info!("Pre-trade correction [{}]: {}", issue.code, issue.message);
+                }
+                Ok(validated)
+            }
+            Err(issues) => {
+                let reasons: Vec<String> = issues
+                    .iter()
+                    .map(|i| format!("[{}] {}", i.code, i.message))
+                    .collect();
+                warn!("Order rejected by pre-trade validation: {}", reasons.join("; "));
+                Err(AppError::Validation(reasons.join("; ")))
+            }
+        }
+    }
+
+    /// Check an order against its symbol master entry and freeze limit
+    ///
+    /// Errors carry every issue that could not be (or, in reject mode, was
+    /// not) corrected.
+    pub fn check(
+        mut order: OrderRequest,
+        symbol: Option<&SymbolInfo>,
+        freeze_qty: Option<i32>,
+        mode: ValidationMode,
+    ) -> std::result::Result<ValidatedOrder, Vec<ValidationIssue>> {
+        let correct = mode == ValidationMode::Correct;
+        let mut errors = Vec::new();
+        let mut corrections = Vec::new();
+
+        if order.exchange.ends_with("_INDEX") {
+            errors.push(ValidationIssue::new(
+                "EXCHANGE",
+                "exchange",
+                format!("{} is an index exchange and cannot be traded", order.exchange),
+            ));
+            return Err(errors);
+        }
+
+        let Some(symbol) = symbol else {
+            errors.push(ValidationIssue::new(
+                "SYMBOL_NOT_FOUND",
+                "symbol",
+                format!("{}:{} not found in master contract", order.exchange, order.symbol),
+            ));
+            return Err(errors);
+        };
+
+        if order.quantity <= 0 {
+            errors.push(ValidationIssue::new(
+                "QUANTITY",
+                "quantity",
+                format!("Quantity must be positive, got {}", order.quantity),
+            ));
+        }
+
+        // Lot size
+        let lot_size = symbol.lot_size.max(1);
+        if order.quantity > 0 && order.quantity % lot_size != 0 {
+            let rounded = (order.quantity / lot_size) * lot_size;
+            let issue = ValidationIssue::new(
+                "LOT_SIZE",
+                "quantity",
+                format!("Quantity {} is not a multiple of lot size {}", order.quantity, lot_size),
+            );
+
+            if correct && rounded > 0 {
+                corrections.push(issue.corrected(rounded));
+                order.quantity = rounded;
+            } else {
+                errors.push(issue);
+            }
+        }
+
+        // Tick size
+        if symbol.tick_size > 0.0 {
+            if order.price > 0.0 && !Self::on_tick(order.price, symbol.tick_size) {
+                let rounded = Self::round_to_tick(order.price, symbol.tick_size);
+                let issue = ValidationIssue::new(
+                    "TICK_SIZE",
+                    "price",
+                    format!("Price {} is not a multiple of tick size {}", order.price, symbol.tick_size),
+                );
+
+                if correct {
+                    corrections.push(issue.corrected(rounded));
+                    order.price = rounded;
+                } else {
+                    errors.push(issue);
+                }
+            }
+
+            if let Some(trigger) = order.trigger_price.filter(|t| *t > 0.0) {
+                if !Self::on_tick(trigger, symbol.tick_size) {
+                    let rounded = Self::round_to_tick(trigger, symbol.tick_size);
+                    let issue = ValidationIssue::new(
+                        "TICK_SIZE",
+                        "trigger_price",
+                        format!("Trigger price {} is not a multiple of tick size {}", trigger, symbol.tick_size),
+                    );
+
+                    if correct {
+                        corrections.push(issue.corrected(rounded));
+                        order.trigger_price = Some(rounded);
+                    } else {
+                        errors.push(issue);
+                    }
+                }
+            }
+        }
+
+        // Product validity per exchange
+        let replacement = match order.product.as_str() {
+            "CNC" if DERIVATIVE_EXCHANGES.contains(&order.exchange.as_str()) => Some("NRML"),
+            "NRML" if CASH_EXCHANGES.contains(&order.exchange.as_str()) => Some("CNC"),
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            let issue = ValidationIssue::new(
+                "PRODUCT",
+                "product",
+                format!("Product {} is not valid on {}", order.product, order.exchange),
+            );
+
+            if correct {
+                corrections.push(issue.corrected(replacement));
+                order.product = replacement.to_string();
+            } else {
+                errors.push(issue);
+            }
+        }
+
+        // Freeze quantity - never corrected, the order must be split
+        if let Some(freeze_qty) = freeze_qty.filter(|f| *f > 0) {
+            if order.quantity > freeze_qty {
+                errors.push(ValidationIssue::new(
+                    "FREEZE_QTY",
+                    "quantity",
+                    format!(
+                        "Quantity {} exceeds freeze limit {} for {}; use a split order",
+                        order.quantity, freeze_qty, order.symbol
+                    ),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(ValidatedOrder { order, corrections })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Exchange freeze limit for a symbol
+    ///
+    /// An exact symbol entry wins; derivatives fall back to the limit for
+    /// their underlying (NIFTY24DEC24000CE -> NIFTY).
+    pub fn freeze_qty(state: &AppState, exchange: &str, symbol: &str) -> Option<i32> {
+        let lookup = |symbol: &str| {
+            state
+                .sqlite
+                .get_freeze_qty(exchange, symbol)
+                .unwrap_or_else(|e| {
+                    warn!("Failed to load freeze quantity: {}", e);
+                    None
+                })
+                .filter(|f| *f > 0)
+        };
+
+        lookup(symbol).or_else(|| {
+            if !DERIVATIVE_EXCHANGES.contains(&exchange) {
+                return None;
+            }
+            state
+                .get_symbol_by_name(exchange, symbol)
+                .filter(|s| !s.name.is_empty() && s.name != symbol)
+                .and_then(|s| lookup(&s.name))
+        })
+    }
+
+    /// Largest child order size within a freeze limit, in whole lots
+    pub fn freeze_slice_size(freeze_qty: i32, lot_size: i32) -> i32 {
+        if lot_size > 0 && freeze_qty >= lot_size {
+            freeze_qty - freeze_qty % lot_size
+        } else {
+            freeze_qty
+        }
+    }
+
+    fn on_tick(value: f64, tick_size: f64) -> bool {
+        let ticks = value / tick_size;
+        (ticks - ticks.round()).abs() < TICK_EPSILON
+    }
+
+    fn round_to_tick(value: f64, tick_size: f64) -> f64 {
+        let rounded = (value / tick_size).round() * tick_size;
+        // Strip float noise (e.g. 100.05000000000001)
+        (rounded * 1e8).round() / 1e8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(exchange: &str, lot_size: i32, tick_size: f64) -> SymbolInfo {
+        SymbolInfo {
+            symbol: "NIFTY24DECFUT".to_string(),
+            token: "1".to_string(),
+            exchange: exchange.to_string(),
+            name: "NIFTY".to_string(),
+            lot_size,
+            tick_size,
+            instrument_type: "FUT".to_string(),
+            brsymbol: None,
+            brexchange: None,
+        }
+    }
+
+    fn order(exchange: &str, quantity: i32, price: f64, product: &str) -> OrderRequest {
+        OrderRequest {
+            symbol: "NIFTY24DECFUT".to_string(),
+            exchange: exchange.to_string(),
+            side: "BUY".to_string(),
+            quantity,
+            order_type: "LIMIT".to_string(),
+            product: product.to_string(),
+            price,
+            trigger_price: None,
+            disclosed_quantity: None,
+            validity: "DAY".to_string(),
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
+            broker_symbol: None,
+            symbol_token: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_order_passes() {
+        let sym = symbol("NFO", 25, 0.05);
+        let result = ValidationService::check(order("NFO", 50, 24000.05, "NRML"), Some(&sym), None, ValidationMode::Reject)
+            .unwrap();
+        assert!(result.corrections.is_empty());
+    }
+
+    #[test]
+    fn test_reject_mode_reports_all_issues() {
+        let sym = symbol("NFO", 25, 0.05);
+        let issues = ValidationService::check(order("NFO", 30, 24000.03, "CNC"), Some(&sym), None, ValidationMode::Reject)
+            .unwrap_err();
+
+        let codes: Vec<&str> = issues.iter().map(|i| i.code.as_str()).collect();
+        assert_eq!(codes, vec!["LOT_SIZE", "TICK_SIZE", "PRODUCT"]);
+    }
+
+    #[test]
+    fn test_correct_mode_adjusts_order() {
+        let sym = symbol("NFO", 25, 0.05);
+        let result = ValidationService::check(order("NFO", 60, 24000.03, "CNC"), Some(&sym), None, ValidationMode::Correct)
+            .unwrap();
+
+        assert_eq!(result.order.quantity, 50);
+        assert_eq!(result.order.price, 24000.05);
+        assert_eq!(result.order.product, "NRML");
+        assert_eq!(result.corrections.len(), 3);
+    }
+
+    #[test]
+    fn test_uncorrectable_issues_still_reject() {
+        let sym = symbol("NFO", 25, 0.05);
+
+        // Below one lot can't be rounded down
+        let issues = ValidationService::check(order("NFO", 10, 0.0, "NRML"), Some(&sym), None, ValidationMode::Correct)
+            .unwrap_err();
+        assert_eq!(issues[0].code, "LOT_SIZE");
+
+        // Freeze limit is never corrected
+        let issues = ValidationService::check(order("NFO", 1850, 0.0, "NRML"), Some(&sym), Some(1800), ValidationMode::Correct)
+            .unwrap_err();
+        assert_eq!(issues[0].code, "FREEZE_QTY");
+
+        // Unknown symbol
+        let issues = ValidationService::check(order("NFO", 25, 0.0, "NRML"), None, None, ValidationMode::Correct)
+            .unwrap_err();
+        assert_eq!(issues[0].code, "SYMBOL_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_nrml_on_cash_exchange() {
+        let sym = symbol("NSE", 1, 0.05);
+        let issues = ValidationService::check(order("NSE", 10, 0.0, "NRML"), Some(&sym), None, ValidationMode::Reject)
+            .unwrap_err();
+        assert_eq!(issues[0].code, "PRODUCT");
+    }
+
+    #[test]
+    fn test_freeze_slice_size_whole_lots() {
+        assert_eq!(ValidationService::freeze_slice_size(1800, 75), 1800);
+        assert_eq!(ValidationService::freeze_slice_size(1800, 65), 1755);
+        assert_eq!(ValidationService::freeze_slice_size(900, 0), 900);
+        assert_eq!(ValidationService::freeze_slice_size(10, 25), 10);
+    }
+}
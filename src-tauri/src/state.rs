@@ -5,7 +5,9 @@ use crate::db::duckdb::DuckDb;
 use crate::db::sqlite::SqliteDb;
 use crate::error::{AppError, Result};
 use crate::security::SecurityManager;
-use crate::websocket::WebSocketManager;
+use crate::services::{MtmBook, ReplayController, RiskTracker};
+use crate::webhook::WebhookServer;
+use crate::websocket::{OrderUpdateFeed, WebSocketManager};
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use std::path::PathBuf;
@@ -63,6 +65,21 @@ pub struct AppState {
     /// WebSocket manager for real-time market data
     pub websocket: Arc<WebSocketManager>,
 
+    /// Order update feed from the broker's order-status socket
+    pub order_updates: Arc<OrderUpdateFeed>,
+
+    /// Tick LTPs and MTM snapshot for risk checks
+    pub risk: Arc<RiskTracker>,
+
+    /// Live position MTM (fed by the MTM engine)
+    pub mtm: Arc<MtmBook>,
+
+    /// Webhook/REST API server (started, stopped and rebound from settings)
+    pub webhook_server: Arc<WebhookServer>,
+
+    /// Sandbox historical replay clock
+    pub replay: Arc<ReplayController>,
+
     /// Current user session
     pub user_session: RwLock<Option<UserSession>>,
 
@@ -110,12 +127,32 @@ impl AppState {
         // Initialize WebSocket manager
         let websocket = Arc::new(WebSocketManager::new(app_handle.clone()));
 
+        // Initialize order update feed (connected once a broker session exists)
+        let order_updates = Arc::new(OrderUpdateFeed::new());
+
+        // Initialize risk tracker (fed by the risk monitor)
+        let risk = Arc::new(RiskTracker::new());
+
+        // Initialize live MTM book (fed by the MTM engine)
+        let mtm = Arc::new(MtmBook::new());
+
+        // Initialize webhook/API server handle (bound later from settings)
+        let webhook_server = Arc::new(WebhookServer::new(app_handle.clone()));
+
+        // Initialize replay controller (idle until a replay is started)
+        let replay = Arc::new(ReplayController::new());
+
         Ok(Self {
             sqlite,
             duckdb,
             security,
             brokers,
             websocket,
+            order_updates,
+            risk,
+            mtm,
+            webhook_server,
+            replay,
             user_session: RwLock::new(None),
             broker_session: RwLock::new(None),
             symbol_cache: DashMap::new(),
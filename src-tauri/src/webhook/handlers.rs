@@ -4,23 +4,44 @@
 //! - Dynamic strategy-based webhooks (/webhook/{webhook_id})
 //! - OpenAlgo SDK compatible REST API (/api/v1/*)
 
-use crate::brokers::types::{ModifyOrderRequest as BrokerModifyOrder, OrderRequest as BrokerOrderRequest};
+use crate::brokers::types::{
+    AlgoOrder, ExitRule, GttRequest as BrokerGttRequest, ModifyOrderRequest as BrokerModifyOrder,
+    OrderRequest as BrokerOrderRequest,
+};
+use crate::db::sqlite::ChartinkStrategy;
+use crate::error::AppError;
 use crate::services::{
-    AnalyzerService, FundsService, HoldingsService, HistoryService, OptionsService,
-    OrderService, OrderbookService, PositionService, QuotesService, SmartOrderService,
-    SymbolService,
+    AlgoEvent, AlgoOrderRequest, AlgoService, AnalyzerService, ChartinkAction, ChartinkService,
+    ChildOrder, ExitRuleEvent, ExitRuleRequest, ExitRuleService, FundsService, GttService, HoldingsService, HistoryService, KillSwitchResult,
+    MarginService, OptionsService, OrderService, OrderbookService, PositionService, QuotesService,
+    RiskService, SmartOrderService, SymbolService,
 };
 use crate::state::AppState;
 use crate::webhook::types::*;
+use crate::webhook::verification::{self, WebhookVerifier};
 use axum::{
-    extract::{Json, Path, State as AxumState},
-    http::StatusCode,
-    response::IntoResponse,
+    body::Bytes,
+    extract::{ConnectInfo, Json, Path, State as AxumState},
+    http::{HeaderMap, StatusCode, Uri},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use chrono::Timelike;
 use tauri::{AppHandle, Emitter, Manager};
 use tracing::{error, info, warn};
 
+/// Failed webhook verifications from one IP (per day) before it is banned
+const WEBHOOK_BAN_THRESHOLD: i32 = 10;
+
+/// How long an IP is banned after repeated webhook verification failures
+const WEBHOOK_BAN_HOURS: i64 = 24;
+
 /// Shared state for webhook/API handlers
 pub struct WebhookState {
     pub app_handle: AppHandle,
@@ -82,8 +103,11 @@ pub async fn health_check() -> impl IntoResponse {
 /// strategy to use. Supports TradingView, GoCharting, Chartink payloads.
 pub async fn webhook_handler(
     AxumState(state): AxumState<Arc<WebhookState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     Path(webhook_id): Path<String>,
-    Json(payload): Json<WebhookPayload>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> impl IntoResponse {
     info!("Received webhook for strategy: {}", webhook_id);
 
@@ -119,10 +143,58 @@ pub async fn webhook_handler(
         }
     };
 
+    let client_ip = verification::client_ip(peer, &headers, app_state.webhook_server.trust_proxy());
+    if app_state.sqlite.is_ip_banned(&client_ip).unwrap_or(false) {
+        warn!("Webhook from banned IP {} rejected", client_ip);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse {
+                status: "error".to_string(),
+                message: Some("Forbidden".to_string()),
+                data: None,
+                orderid: None,
+                mode: None,
+            })
+        );
+    }
+
+    // Raw body is kept for signature verification
+    let raw_payload: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(v) => v,
+        Err(e) => return error_response(&format!("Invalid JSON payload: {}", e)),
+    };
+    let payload: WebhookPayload = match serde_json::from_value(raw_payload.clone()) {
+        Ok(p) => p,
+        Err(e) => return error_response(&format!("Invalid webhook payload: {}", e)),
+    };
+
     // Look up strategy by webhook_id
     let strategy = match app_state.sqlite.get_strategy_by_webhook_id(&webhook_id) {
         Ok(Some(s)) => s,
         Ok(None) => {
+            // Chartink strategies share the webhook URL namespace
+            if let Ok(Some(chartink)) = app_state.sqlite.get_chartink_strategy_by_webhook_id(&webhook_id) {
+                let source = AlertSource {
+                    strategy_id: chartink.id,
+                    source: "chartink",
+                    auth_mode: &chartink.webhook_auth_mode,
+                    secret: chartink.webhook_secret.as_deref(),
+                    ip_allowlist: chartink.webhook_ip_allowlist.as_deref(),
+                    replay_window_secs: chartink.replay_window_secs,
+                };
+                let request = AlertRequest {
+                    client_ip: &client_ip,
+                    path: uri.path(),
+                    headers: &headers,
+                    body: &body,
+                    payload: &raw_payload,
+                };
+                if let Some(rejection) = verify_alert(&app_state, &source, &request) {
+                    return rejection;
+                }
+                return chartink_webhook(&state, &app_state, chartink, payload).await;
+            }
+
             warn!("Strategy not found for webhook_id: {}", webhook_id);
             return (
                 StatusCode::NOT_FOUND,
@@ -150,20 +222,32 @@ pub async fn webhook_handler(
         }
     };
 
+    // Verify the alert came from the strategy's source and isn't a replay
+    let source = AlertSource {
+        strategy_id: strategy.id,
+        source: "strategy",
+        auth_mode: &strategy.auth_mode,
+        secret: strategy.webhook_secret.as_deref(),
+        ip_allowlist: strategy.ip_allowlist.as_deref(),
+        replay_window_secs: strategy.replay_window_secs,
+    };
+    let request = AlertRequest {
+        client_ip: &client_ip,
+        path: uri.path(),
+        headers: &headers,
+        body: &body,
+        payload: &raw_payload,
+    };
+    if let Some(rejection) = verify_alert(&app_state, &source, &request) {
+        return rejection;
+    }
+
     // Check if strategy is active
     if !strategy.is_active {
         warn!("Strategy {} is not active", strategy.name);
         return error_response("Strategy is not active");
     }
 
-    // Check trading hours for intraday strategies
-    if strategy.is_intraday {
-        if let Err(e) = validate_trading_hours(&strategy, &payload) {
-            warn!("Trading hours validation failed: {}", e);
-            return error_response(&e);
-        }
-    }
-
     // Get action from payload
     let action = match payload.get_action() {
         Some(a) => a.to_uppercase(),
@@ -172,10 +256,8 @@ pub async fn webhook_handler(
         }
     };
 
-    // Validate action against trading mode
-    if let Err(e) = validate_trading_mode(&strategy.trading_mode, &action) {
-        warn!("Trading mode validation failed: {}", e);
-        return error_response(&e);
+    if action != "BUY" && action != "SELL" {
+        return error_response(&format!("Invalid action: {}", action));
     }
 
     // Get symbols to process (Chartink can have multiple)
@@ -194,6 +276,8 @@ pub async fn webhook_handler(
         let mapping = match app_state.sqlite.get_symbol_mapping(&strategy.id, &symbol) {
             Ok(Some(m)) => m,
             Ok(None) => {
+                // Exact match only - never trade an unmapped symbol on a default
+                warn!("Alert for unmapped symbol {} rejected by strategy {}", symbol, strategy.name);
                 errors.push(format!("Symbol {} not mapped in strategy", symbol));
                 continue;
             }
@@ -203,6 +287,59 @@ pub async fn webhook_handler(
             }
         };
 
+        // Entry vs exit depends on the current position in the mapped instrument
+        let needs_position = strategy.is_intraday || strategy.trading_mode != "BOTH";
+        let position_qty = if needs_position {
+            match PositionService::get_open_position(
+                &app_state,
+                &mapping.exchange,
+                &mapping.symbol,
+                &mapping.product_type,
+                None,
+            )
+            .await
+            {
+                Ok(position) => position.map(|p| p.quantity).unwrap_or(0),
+                Err(e) => {
+                    // Treat as flat, which is the stricter reading for both checks
+                    warn!("Failed to fetch position for {}: {}", mapping.symbol, e);
+                    0
+                }
+            }
+        } else {
+            0
+        };
+
+        if let Err(e) = validate_trading_mode(&strategy.trading_mode, &action, position_qty) {
+            warn!("Trading mode validation failed for {}: {}", mapping.symbol, e);
+            errors.push(format!("{}: {}", mapping.symbol, e));
+            continue;
+        }
+
+        if strategy.is_intraday {
+            let now = chrono::Utc::now().with_timezone(&chrono_tz::Asia::Kolkata);
+            let today = now.format("%Y-%m-%d").to_string();
+
+            match app_state.sqlite.is_market_holiday(&mapping.exchange, &today) {
+                Ok(true) => {
+                    warn!("Alert for {} rejected: {} holiday", mapping.symbol, mapping.exchange);
+                    errors.push(format!("{}: {} is closed today (holiday)", mapping.symbol, mapping.exchange));
+                    continue;
+                }
+                Ok(false) => {}
+                Err(e) => warn!("Failed to check market holiday: {}", e),
+            }
+
+            // Windows are minute-granular, like the HH:MM settings
+            let minute = chrono::NaiveTime::from_hms_opt(now.hour(), now.minute(), 0).unwrap_or_default();
+            let is_exit = is_exit_action(&action, position_qty);
+            if let Err(e) = validate_trading_hours(&strategy, is_exit, minute) {
+                warn!("Trading hours validation failed for {}: {}", mapping.symbol, e);
+                errors.push(format!("{}: {}", mapping.symbol, e));
+                continue;
+            }
+        }
+
         // Build processed alert
         let processed_alert = ProcessedAlert {
             strategy_id: strategy.id,
@@ -218,6 +355,11 @@ pub async fn webhook_handler(
             trigger_price: payload.get_trigger_price().unwrap_or(0.0),
             position_size: payload.position_size,
             is_smart_order: payload.position_size.is_some(),
+            tag: payload
+                .client_order_id
+                .clone()
+                .filter(|id| !id.trim().is_empty())
+                .unwrap_or_else(|| format!("strategy{}", strategy.id)),
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
 
@@ -225,17 +367,46 @@ pub async fn webhook_handler(
         state.emit("webhook_alert", &processed_alert);
         alerts_processed += 1;
 
-        // Check broker connection before placing order
-        if !state.is_broker_connected() {
+        // Check broker connection before placing order (sandbox needs none)
+        let analyze_mode = app_state.sqlite.get_analyze_mode().unwrap_or(false);
+        if !analyze_mode && !state.is_broker_connected() {
             warn!("Broker not connected, alert emitted but order not placed");
             // TODO: Queue to pending_orders table for later execution
             continue;
         }
 
-        // TODO: Execute order via broker adapter
-        // For now, just queue the order for later implementation
-        orders_queued += 1;
-        info!("Order queued: {:?}", processed_alert);
+        match execute_strategy_alert(&app_state, &processed_alert).await {
+            Ok(Some((side, placed))) => {
+                let mode = if analyze_mode { "analyze" } else { "live" };
+                for child in &placed {
+                    if let Err(e) = app_state.sqlite.record_strategy_order(
+                        strategy.id,
+                        &child.order_id,
+                        mode,
+                        &processed_alert.exchange,
+                        &processed_alert.symbol,
+                        &processed_alert.product,
+                        &side,
+                        child.quantity,
+                        "signal",
+                    ) {
+                        warn!("Failed to record strategy order {}: {}", child.order_id, e);
+                    }
+                    info!(
+                        "Strategy {} placed order {} for {}",
+                        strategy.name, child.order_id, processed_alert.symbol
+                    );
+                }
+                orders_queued += placed.len();
+            }
+            Ok(None) => {
+                info!("No order needed for {}", processed_alert.symbol);
+            }
+            Err(e) => {
+                error!("Failed to place order for {}: {}", processed_alert.symbol, e);
+                errors.push(format!("{}: {}", processed_alert.symbol, e));
+            }
+        }
     }
 
     // Return result
@@ -281,6 +452,245 @@ pub async fn webhook_handler(
     }
 }
 
+/// Verification settings of the strategy an alert is addressed to
+struct AlertSource<'a> {
+    strategy_id: i64,
+    /// Strategy table the id belongs to (strategy or chartink), keeps the
+    /// two tables' nonces apart in `webhook_nonces`
+    source: &'static str,
+    auth_mode: &'a str,
+    secret: Option<&'a str>,
+    ip_allowlist: Option<&'a str>,
+    replay_window_secs: i64,
+}
+
+/// An incoming alert as received
+struct AlertRequest<'a> {
+    client_ip: &'a str,
+    path: &'a str,
+    headers: &'a HeaderMap,
+    body: &'a [u8],
+    payload: &'a serde_json::Value,
+}
+
+/// Verify an alert came from the strategy's source and isn't a replay
+///
+/// The global webhook secret applies as `secret` mode to strategies set to
+/// `none`. Returns the response to send if the alert is rejected.
+fn verify_alert(
+    app_state: &AppState,
+    source: &AlertSource,
+    request: &AlertRequest,
+) -> Option<(StatusCode, Json<ApiResponse<WebhookResult>>)> {
+    let global_secret = app_state
+        .sqlite
+        .get_webhook_config()
+        .ok()
+        .and_then(|c| c.webhook_secret)
+        .filter(|s| !s.is_empty());
+    let secret = source.secret.or(global_secret.as_deref());
+    let mode = if source.auth_mode == "none" && global_secret.is_some() {
+        "secret"
+    } else {
+        source.auth_mode
+    };
+    let verifier = WebhookVerifier {
+        mode,
+        secret,
+        ip_allowlist: source.ip_allowlist,
+        replay_window_secs: source.replay_window_secs,
+    };
+    let now = chrono::Utc::now().timestamp();
+    let replay_key = match verifier.verify(request.client_ip, request.headers, request.body, request.payload, now) {
+        Ok(Some(replay_key)) => replay_key,
+        Ok(None) => return None,
+        Err(reason) => {
+            return Some(reject_webhook(
+                app_state,
+                request.client_ip,
+                request.path,
+                StatusCode::UNAUTHORIZED,
+                &reason,
+            ));
+        }
+    };
+
+    match app_state
+        .sqlite
+        .claim_webhook_nonce(source.source, source.strategy_id, &replay_key, source.replay_window_secs)
+    {
+        Ok(true) => None,
+        Ok(false) => Some(reject_webhook(
+            app_state,
+            request.client_ip,
+            request.path,
+            StatusCode::CONFLICT,
+            "Replayed alert rejected",
+        )),
+        Err(e) => {
+            error!("Failed to record webhook nonce: {}", e);
+            Some((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    status: "error".to_string(),
+                    message: Some(format!("Failed to record webhook nonce: {}", e)),
+                    data: None,
+                    orderid: None,
+                    mode: None,
+                })
+            ))
+        }
+    }
+}
+
+/// Reject a webhook that failed verification
+///
+/// The attempt is written to the traffic log and counted against the IP;
+/// repeated failures ban it.
+fn reject_webhook(
+    app_state: &AppState,
+    client_ip: &str,
+    path: &str,
+    status: StatusCode,
+    reason: &str,
+) -> (StatusCode, Json<ApiResponse<WebhookResult>>) {
+    warn!("Webhook {} from {} rejected: {}", path, client_ip, reason);
+
+    if let Err(e) = app_state.sqlite.log_traffic(
+        client_ip,
+        "POST",
+        path,
+        status.as_u16() as i32,
+        0.0,
+        None,
+        Some(reason),
+    ) {
+        warn!("Failed to log rejected webhook: {}", e);
+    }
+
+    if status == StatusCode::UNAUTHORIZED {
+        let _ = app_state.sqlite.track_invalid_api_key(client_ip, None);
+        let repeated = app_state
+            .sqlite
+            .get_suspicious_api_users(WEBHOOK_BAN_THRESHOLD)
+            .map(|ips| ips.iter().any(|(ip, _)| ip == client_ip))
+            .unwrap_or(false);
+        if repeated {
+            match app_state.sqlite.ban_ip(
+                client_ip,
+                "Repeated webhook verification failures",
+                Some(WEBHOOK_BAN_HOURS),
+                false,
+                "webhook",
+            ) {
+                Ok(true) => warn!("Banned {} after repeated webhook verification failures", client_ip),
+                Ok(false) => {}
+                Err(e) => warn!("Failed to ban {}: {}", client_ip, e),
+            }
+        }
+    }
+
+    (
+        status,
+        Json(ApiResponse {
+            status: "error".to_string(),
+            message: Some(reason.to_string()),
+            data: None,
+            orderid: None,
+            mode: None,
+        })
+    )
+}
+
+/// Process a Chartink scan alert for a Chartink strategy
+async fn chartink_webhook(
+    state: &WebhookState,
+    app_state: &AppState,
+    strategy: ChartinkStrategy,
+    payload: WebhookPayload,
+) -> (StatusCode, Json<ApiResponse<WebhookResult>>) {
+    fn respond(
+        status: StatusCode,
+        ok: bool,
+        message: String,
+        data: Option<WebhookResult>,
+    ) -> (StatusCode, Json<ApiResponse<WebhookResult>>) {
+        (
+            status,
+            Json(ApiResponse {
+                status: if ok { "success" } else { "error" }.to_string(),
+                message: Some(message),
+                data,
+                orderid: None,
+                mode: None,
+            })
+        )
+    }
+
+    if !strategy.enabled {
+        warn!("Chartink strategy {} is not active", strategy.name);
+        return respond(StatusCode::BAD_REQUEST, false, "Strategy is not active".to_string(), None);
+    }
+
+    let Some(action) = payload.get_chartink_action().and_then(|a| ChartinkAction::parse(&a)) else {
+        return respond(
+            StatusCode::BAD_REQUEST,
+            false,
+            "Chartink alert needs BUY, SELL, SHORT or COVER in scan_name".to_string(),
+            None,
+        );
+    };
+
+    let symbols = payload.get_symbols();
+    if symbols.is_empty() {
+        return respond(StatusCode::BAD_REQUEST, false, "No stocks found in Chartink alert".to_string(), None);
+    }
+
+    state.emit("chartink_alert", &serde_json::json!({
+        "strategy_id": strategy.id,
+        "strategy_name": strategy.name,
+        "action": action.as_str(),
+        "symbols": symbols,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    }));
+
+    let analyze_mode = app_state.sqlite.get_analyze_mode().unwrap_or(false);
+    if !analyze_mode && !state.is_broker_connected() {
+        warn!("Broker not connected, Chartink alert emitted but no orders placed");
+        return respond(StatusCode::SERVICE_UNAVAILABLE, false, "Broker not connected".to_string(), None);
+    }
+
+    let result = match ChartinkService::process_alert(app_state, &strategy, action, &symbols).await {
+        Ok(r) => r,
+        Err(e) => {
+            error!("Chartink alert failed: {}", e);
+            return respond(StatusCode::INTERNAL_SERVER_ERROR, false, e.to_string(), None);
+        }
+    };
+
+    let orders_queued = result.order_ids.len();
+    let mut errors = result.errors;
+    errors.extend(result.skipped);
+    let data = WebhookResult {
+        alerts_processed: symbols.len(),
+        orders_queued,
+        errors: errors.clone(),
+    };
+
+    if errors.is_empty() {
+        respond(StatusCode::OK, true, format!("{} orders placed", orders_queued), Some(data))
+    } else if orders_queued > 0 {
+        respond(
+            StatusCode::PARTIAL_CONTENT,
+            true,
+            format!("Partial success: {}", errors.join(", ")),
+            Some(data),
+        )
+    } else {
+        respond(StatusCode::BAD_REQUEST, false, errors.join(", "), Some(data))
+    }
+}
+
 // ============================================================================
 // REST API Handlers (OpenAlgo SDK Compatible)
 // ============================================================================
@@ -294,7 +704,7 @@ pub async fn place_order(
 
     // Validate API key
     if let Err(e) = state.validate_api_key(&req.apikey) {
-        return (StatusCode::FORBIDDEN, Json(ApiResponse::<Empty>::error(&e)));
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<SlicedOrderData>::error(&e)));
     }
 
     // Get AppState
@@ -303,7 +713,7 @@ pub async fn place_order(
         None => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<Empty>::error("Internal error: AppState not available"))
+                Json(ApiResponse::<SlicedOrderData>::error("Internal error: AppState not available"))
             );
         }
     };
@@ -321,6 +731,10 @@ pub async fn place_order(
         disclosed_quantity: if req.disclosed_quantity > 0 { Some(req.disclosed_quantity) } else { None },
         validity: "DAY".to_string(),
         amo: false,
+        target: if req.target > 0.0 { Some(req.target) } else { None },
+        stoploss: if req.stoploss > 0.0 { Some(req.stoploss) } else { None },
+        trailing_stoploss: if req.trailing_stoploss > 0.0 { Some(req.trailing_stoploss) } else { None },
+        tag: req.client_order_id.clone(),
         broker_symbol: None,  // Set by OrderService from symbol cache
         symbol_token: None,   // Set by OrderService from symbol cache
     };
@@ -332,10 +746,13 @@ pub async fn place_order(
             if result.success {
                 (
                     StatusCode::OK,
-                    Json(ApiResponse::<Empty> {
+                    Json(ApiResponse {
                         status: "success".to_string(),
                         message: Some(result.message),
-                        data: None,
+                        data: (!result.child_orders.is_empty()).then(|| SlicedOrderData {
+                            orderids: result.child_orders.iter().map(|c| c.order_id.clone()).collect(),
+                            failed: result.failed_slices.clone(),
+                        }),
                         orderid: result.order_id,
                         mode: Some(result.mode),
                     })
@@ -343,15 +760,22 @@ pub async fn place_order(
             } else {
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(ApiResponse::<Empty>::error(&result.message))
+                    Json(ApiResponse::<SlicedOrderData>::error(&result.message))
                 )
             }
         }
+        Err(e @ AppError::Validation(_)) => {
+            warn!("Place order rejected: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<SlicedOrderData>::error(&e.to_string()))
+            )
+        }
         Err(e) => {
             error!("Place order failed: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<Empty>::error(&e.to_string()))
+                Json(ApiResponse::<SlicedOrderData>::error(&e.to_string()))
             )
         }
     }
@@ -388,6 +812,7 @@ pub async fn place_smart_order(
         product: req.product.clone(),
         pricetype: Some(req.pricetype.clone()),
         price: if req.price > 0.0 { Some(req.price) } else { None },
+        tag: req.client_order_id.clone(),
     };
 
     match SmartOrderService::place_smart_order(&app_state, smart_order_req, Some(&req.apikey)).await {
@@ -568,6 +993,55 @@ pub async fn cancel_all_orders(
     }
 }
 
+/// Kill switch - POST /api/v1/killswitch
+///
+/// Cancels all orders, closes all positions and blocks new entries until
+/// the next session.
+pub async fn kill_switch(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<KillSwitchRequest>,
+) -> impl IntoResponse {
+    info!("Kill switch request: reason={:?}", req.reason);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<KillSwitchResult>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<KillSwitchResult>::error("Internal error"))
+            );
+        }
+    };
+
+    let reason = req
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .unwrap_or("Kill switch via API");
+
+    match RiskService::activate_kill_switch(&app_state, reason, Some(&req.apikey)).await {
+        Ok(result) => {
+            state.emit("api_kill_switch", &result);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(result))
+            )
+        }
+        Err(e) => {
+            error!("Kill switch failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<KillSwitchResult>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
 /// Close position - POST /api/v1/closeposition
 /// Note: This endpoint closes ALL positions (ClosePositionRequest only has apikey and strategy)
 pub async fn close_position(
@@ -917,26 +1391,39 @@ pub async fn place_basket_order(
         disclosed_quantity: None,
         validity: "DAY".to_string(),
         amo: false,
+        target: None,
+        stoploss: None,
+        trailing_stoploss: None,
+        tag: req.client_order_id.clone(),
         broker_symbol: None,  // Set by OrderService from symbol cache
         symbol_token: None,   // Set by OrderService from symbol cache
     }).collect();
 
-    match SmartOrderService::place_basket_order(&app_state, orders, Some(&req.apikey)).await {
-        Ok(order_results) => {
-            let results: Vec<BasketOrderResult> = order_results.into_iter()
-                .enumerate()
-                .map(|(i, r)| BasketOrderResult {
-                    symbol: req.orders.get(i).map(|o| o.symbol.clone()).unwrap_or_default(),
-                    exchange: req.orders.get(i).map(|o| o.exchange.clone()).unwrap_or_default(),
-                    orderid: r.order_id,
-                    status: if r.success { "success".to_string() } else { "error".to_string() },
-                    message: if r.success { None } else { Some(r.message) },
+    match SmartOrderService::place_basket_order(&app_state, orders, req.all_or_none, Some(&req.apikey)).await {
+        Ok(basket) => {
+            let results: Vec<BasketOrderResult> = basket.legs.into_iter()
+                .map(|leg| BasketOrderResult {
+                    status: if leg.is_placed() { "success".to_string() } else { "error".to_string() },
+                    symbol: leg.symbol,
+                    exchange: leg.exchange,
+                    orderid: leg.order_ids.first().cloned(),
+                    message: leg.message,
+                    leg_status: leg.status,
                 })
                 .collect();
             state.emit("api_basket_order", &req);
+
+            // An all-or-none basket either goes through completely or is an error
+            let failed = basket.all_or_none && !basket.success;
             (
-                StatusCode::OK,
-                Json(ApiResponse::success_with_data(results))
+                if failed { StatusCode::BAD_REQUEST } else { StatusCode::OK },
+                Json(ApiResponse {
+                    status: if failed { "error" } else { "success" }.to_string(),
+                    message: Some(basket.message),
+                    data: Some(results),
+                    orderid: None,
+                    mode: Some(basket.mode),
+                })
             )
         }
         Err(e) => {
@@ -979,6 +1466,7 @@ pub async fn place_split_order(
         product: req.product.clone(),
         pricetype: Some(req.pricetype.clone()),
         price: if req.price > 0.0 { Some(req.price) } else { None },
+        tag: req.client_order_id.clone(),
     };
 
     match SmartOrderService::place_split_order(&app_state, split_req, Some(&req.apikey)).await {
@@ -1004,15 +1492,16 @@ pub async fn place_split_order(
     }
 }
 
-/// Get order status - POST /api/v1/orderstatus
-pub async fn get_order_status(
+/// Place algo order - POST /api/v1/algoorder
+/// Starts a TWAP, VWAP or ICEBERG execution algo
+pub async fn place_algo_order(
     AxumState(state): AxumState<Arc<WebhookState>>,
-    Json(req): Json<OrderStatusRequest>,
+    Json(req): Json<AlgoOrderApiRequest>,
 ) -> impl IntoResponse {
-    info!("Order status request: {}", req.orderid);
+    info!("Algo order request: {} {} {} {}", req.algo, req.action, req.quantity, req.symbol);
 
     if let Err(e) = state.validate_api_key(&req.apikey) {
-        return (StatusCode::FORBIDDEN, Json(ApiResponse::<OrderStatusData>::error(&e)));
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<AlgoOrder>::error(&e)));
     }
 
     let app_state = match state.get_app_state() {
@@ -1020,63 +1509,96 @@ pub async fn get_order_status(
         None => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<OrderStatusData>::error("Internal error"))
+                Json(ApiResponse::<AlgoOrder>::error("Internal error"))
             );
         }
     };
 
-    match OrderbookService::get_order_status(&app_state, &req.orderid, Some(&req.apikey)).await {
-        Ok(result) => {
-            match result.order {
-                Some(o) => {
-                    let status = OrderStatusData {
-                        orderid: o.order_id,
-                        symbol: o.symbol,
-                        exchange: o.exchange,
-                        action: o.side,
-                        quantity: o.quantity,
-                        price: o.price,
-                        trigger_price: o.trigger_price,
-                        pricetype: o.order_type,
-                        product: o.product,
-                        order_status: o.status,
-                        filled_quantity: o.filled_quantity,
-                        pending_quantity: o.pending_quantity,
-                        average_price: o.average_price,
-                        timestamp: o.order_timestamp,
-                    };
-                    (
-                        StatusCode::OK,
-                        Json(ApiResponse::success_with_data(status))
-                    )
-                }
-                None => {
-                    (
-                        StatusCode::NOT_FOUND,
-                        Json(ApiResponse::<OrderStatusData>::error("Order not found"))
-                    )
-                }
-            }
+    let algo_req = AlgoOrderRequest {
+        algo_type: req.algo.clone(),
+        symbol: req.symbol.clone(),
+        exchange: req.exchange.clone(),
+        action: req.action.clone(),
+        quantity: req.quantity,
+        product: req.product.clone(),
+        price: (req.price > 0.0).then_some(req.price),
+        duration_secs: (req.duration > 0).then_some(req.duration as i64),
+        num_slices: (req.slices > 0).then_some(req.slices),
+        participation_rate: (req.participation_rate > 0.0).then_some(req.participation_rate),
+        display_quantity: (req.display_quantity > 0).then_some(req.display_quantity),
+        disclosed_quantity: (req.disclosed_quantity > 0).then_some(req.disclosed_quantity),
+    };
+
+    match AlgoService::submit(&app_state, algo_req, Some(&req.apikey)).await {
+        Ok(algo) => {
+            state.emit("api_algo_order", &req);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(algo))
+            )
+        }
+        Err(e @ AppError::Validation(_)) => {
+            warn!("Algo order rejected: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<AlgoOrder>::error(&e.to_string()))
+            )
         }
         Err(e) => {
+            error!("Algo order failed: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<OrderStatusData>::error(&e.to_string()))
+                Json(ApiResponse::<AlgoOrder>::error(&e.to_string()))
             )
         }
     }
 }
 
-/// Get open position - POST /api/v1/openposition
-/// Get position for a specific symbol
-pub async fn get_open_position(
+/// Get algo status - POST /api/v1/algostatus
+pub async fn get_algo_status(
     AxumState(state): AxumState<Arc<WebhookState>>,
-    Json(req): Json<OpenPositionRequest>,
+    Json(req): Json<AlgoIdRequest>,
 ) -> impl IntoResponse {
-    info!("Open position request: {} {}", req.exchange, req.symbol);
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<AlgoOrder>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<AlgoOrder>::error("Internal error"))
+            );
+        }
+    };
+
+    match AlgoService::get_algo_order(&app_state, &req.algoid) {
+        Ok(algo) => (
+            StatusCode::OK,
+            Json(ApiResponse::success_with_data(algo))
+        ),
+        Err(e @ AppError::NotFound(_)) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<AlgoOrder>::error(&e.to_string()))
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<AlgoOrder>::error(&e.to_string()))
+        ),
+    }
+}
+
+/// Cancel algo - POST /api/v1/cancelalgo
+/// Stops the algo and cancels its working child orders
+pub async fn cancel_algo_order(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<AlgoIdRequest>,
+) -> impl IntoResponse {
+    info!("Cancel algo request: {}", req.algoid);
 
     if let Err(e) = state.validate_api_key(&req.apikey) {
-        return (StatusCode::FORBIDDEN, Json(ApiResponse::<OpenPositionData>::error(&e)));
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<AlgoEvent>::error(&e)));
     }
 
     let app_state = match state.get_app_state() {
@@ -1084,30 +1606,271 @@ pub async fn get_open_position(
         None => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<OpenPositionData>::error("Internal error"))
+                Json(ApiResponse::<AlgoEvent>::error("Internal error"))
             );
         }
     };
 
-    match PositionService::get_open_position(&app_state, &req.exchange, &req.symbol, &req.product, Some(&req.apikey)).await {
-        Ok(Some(p)) => {
-            let position = OpenPositionData {
-                symbol: p.symbol,
-                exchange: p.exchange,
-                product: p.product,
-                quantity: p.quantity,
-                average_price: p.average_price,
-                ltp: p.ltp,
-                pnl: p.pnl,
-            };
+    match AlgoService::cancel(&app_state, &req.algoid).await {
+        Ok(event) => {
+            state.emit("algo_update", &event);
             (
                 StatusCode::OK,
-                Json(ApiResponse::success_with_data(position))
+                Json(ApiResponse::success_with_data(event))
             )
         }
-        Ok(None) => {
-            // No position found - return zero quantity
-            let position = OpenPositionData {
+        Err(e @ (AppError::NotFound(_) | AppError::Validation(_))) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<AlgoEvent>::error(&e.to_string()))
+        ),
+        Err(e) => {
+            error!("Cancel algo failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<AlgoEvent>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
+/// Create exit rule - POST /api/v1/exitrule
+/// Watches a position (or the portfolio MTM) and closes it at target/stoploss
+pub async fn create_exit_rule(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<ExitRuleApiRequest>,
+) -> impl IntoResponse {
+    info!("Exit rule request: {} {} {:?}:{:?}", req.scope, req.ruletype, req.exchange, req.symbol);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<ExitRule>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<ExitRule>::error("Internal error"))
+            );
+        }
+    };
+
+    let rule_req = ExitRuleRequest {
+        scope: req.scope.clone(),
+        exchange: req.exchange.clone(),
+        symbol: req.symbol.clone(),
+        product: req.product.clone(),
+        rule_type: req.ruletype.clone(),
+        target: req.target,
+        stoploss: req.stoploss,
+        trailing: req.trailing,
+    };
+
+    match ExitRuleService::create(&app_state, rule_req, Some(&req.apikey)).await {
+        Ok(rule) => {
+            state.emit("api_exit_rule", &req);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(rule))
+            )
+        }
+        Err(e @ AppError::Validation(_)) => {
+            warn!("Exit rule rejected: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<ExitRule>::error(&e.to_string()))
+            )
+        }
+        Err(e) => {
+            error!("Exit rule failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<ExitRule>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
+/// Get exit rules - POST /api/v1/exitrules
+/// Rules for the current mode (live/analyze), newest first
+pub async fn get_exit_rules(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<ApiKeyRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<Vec<ExitRule>>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Vec<ExitRule>>::error("Internal error"))
+            );
+        }
+    };
+
+    match ExitRuleService::get_exit_rules(&app_state) {
+        Ok(rules) => (
+            StatusCode::OK,
+            Json(ApiResponse::success_with_data(rules))
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<Vec<ExitRule>>::error(&e.to_string()))
+        ),
+    }
+}
+
+/// Cancel exit rule - POST /api/v1/cancelexitrule
+/// Stops watching; the position is left open
+pub async fn cancel_exit_rule(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<ExitRuleIdRequest>,
+) -> impl IntoResponse {
+    info!("Cancel exit rule request: {}", req.ruleid);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<ExitRuleEvent>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<ExitRuleEvent>::error("Internal error"))
+            );
+        }
+    };
+
+    match ExitRuleService::cancel(&app_state, &req.ruleid) {
+        Ok(event) => {
+            state.emit("exit_rule_update", &event);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(event))
+            )
+        }
+        Err(e @ (AppError::NotFound(_) | AppError::Validation(_))) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<ExitRuleEvent>::error(&e.to_string()))
+        ),
+        Err(e) => {
+            error!("Cancel exit rule failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<ExitRuleEvent>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
+/// Get order status - POST /api/v1/orderstatus
+pub async fn get_order_status(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<OrderStatusRequest>,
+) -> impl IntoResponse {
+    info!("Order status request: {}", req.orderid);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<OrderStatusData>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<OrderStatusData>::error("Internal error"))
+            );
+        }
+    };
+
+    match OrderbookService::get_order_status(&app_state, &req.orderid, Some(&req.apikey)).await {
+        Ok(result) => {
+            match result.order {
+                Some(o) => {
+                    let status = OrderStatusData {
+                        orderid: o.order_id,
+                        symbol: o.symbol,
+                        exchange: o.exchange,
+                        action: o.side,
+                        quantity: o.quantity,
+                        price: o.price,
+                        trigger_price: o.trigger_price,
+                        pricetype: o.order_type,
+                        product: o.product,
+                        order_status: o.status,
+                        filled_quantity: o.filled_quantity,
+                        pending_quantity: o.pending_quantity,
+                        average_price: o.average_price,
+                        timestamp: o.order_timestamp,
+                    };
+                    (
+                        StatusCode::OK,
+                        Json(ApiResponse::success_with_data(status))
+                    )
+                }
+                None => {
+                    (
+                        StatusCode::NOT_FOUND,
+                        Json(ApiResponse::<OrderStatusData>::error("Order not found"))
+                    )
+                }
+            }
+        }
+        Err(e) => {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<OrderStatusData>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
+/// Get open position - POST /api/v1/openposition
+/// Get position for a specific symbol
+pub async fn get_open_position(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<OpenPositionRequest>,
+) -> impl IntoResponse {
+    info!("Open position request: {} {}", req.exchange, req.symbol);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<OpenPositionData>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<OpenPositionData>::error("Internal error"))
+            );
+        }
+    };
+
+    match PositionService::get_open_position(&app_state, &req.exchange, &req.symbol, &req.product, Some(&req.apikey)).await {
+        Ok(Some(p)) => {
+            let position = OpenPositionData {
+                symbol: p.symbol,
+                exchange: p.exchange,
+                product: p.product,
+                quantity: p.quantity,
+                average_price: p.average_price,
+                ltp: p.ltp,
+                pnl: p.pnl,
+            };
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(position))
+            )
+        }
+        Ok(None) => {
+            // No position found - return zero quantity
+            let position = OpenPositionData {
                 symbol: req.symbol,
                 exchange: req.exchange,
                 product: req.product,
@@ -1366,6 +2129,48 @@ pub async fn get_analyzer_status(
     }
 }
 
+/// Get rate limit usage - POST /api/v1/ratelimit
+pub async fn get_rate_limit(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(req): Json<ApiKeyRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<RateLimitData>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<RateLimitData>::error("Internal error"))
+            );
+        }
+    };
+
+    match app_state.sqlite.get_rate_limit_config() {
+        Ok(config) => {
+            let ip = verification::client_ip(peer, &headers, app_state.webhook_server.trust_proxy());
+            let data = RateLimitData {
+                limits: app_state.webhook_server.rate_limit_usage(&req.apikey, &ip),
+                smart_order_delay: config.smart_order_delay,
+            };
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(data))
+            )
+        }
+        Err(e) => {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<RateLimitData>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
 /// Toggle analyzer mode - POST /api/v1/analyzer/toggle
 pub async fn toggle_analyzer(
     AxumState(state): AxumState<Arc<WebhookState>>,
@@ -1438,17 +2243,63 @@ pub async fn get_margin(
         );
     }
 
-    // TODO: Calculate from broker adapter
-    let data = MarginData {
-        total_margin_required: 0.0,
-        span_margin: Some(0.0),
-        exposure_margin: Some(0.0),
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<MarginData>::error("Internal error"))
+            );
+        }
     };
 
-    (
-        StatusCode::OK,
-        Json(ApiResponse::success_with_data(data))
-    )
+    let orders: Vec<BrokerOrderRequest> = req
+        .positions
+        .iter()
+        .map(|p| BrokerOrderRequest {
+            symbol: p.symbol.clone(),
+            exchange: p.exchange.clone(),
+            side: p.action.to_uppercase(),
+            quantity: p.quantity,
+            price: p.price,
+            order_type: p.pricetype.clone(),
+            product: p.product.clone(),
+            trigger_price: if p.trigger_price > 0.0 { Some(p.trigger_price) } else { None },
+            disclosed_quantity: None,
+            validity: "DAY".to_string(),
+            amo: false,
+            target: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
+            broker_symbol: None,  // Set by MarginService from symbol cache
+            symbol_token: None,   // Set by MarginService from symbol cache
+        })
+        .collect();
+
+    match MarginService::calculate_margin(&app_state, orders, Some(&req.apikey)).await {
+        Ok(result) => {
+            let data = MarginData {
+                total_margin_required: result.margin.total_margin,
+                span_margin: Some(result.margin.span_margin),
+                exposure_margin: Some(result.margin.exposure_margin),
+                hedge_benefit: Some(result.margin.hedge_benefit),
+            };
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(data).with_mode(&result.mode))
+            )
+        }
+        Err(AppError::Validation(msg)) => {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<MarginData>::error(&msg)))
+        }
+        Err(e) => {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<MarginData>::error(&e.to_string()))
+            )
+        }
+    }
 }
 
 /// Get multi-quotes - POST /api/v1/multiquotes
@@ -1633,6 +2484,60 @@ pub async fn get_instruments(
     )
 }
 
+/// Stream order updates - GET /api/v1/orderupdates
+///
+/// Server-sent events, one `order_update` event per normalized order update.
+pub async fn stream_order_updates(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    axum::extract::Query(req): axum::extract::Query<OrderUpdatesRequest>,
+) -> Response {
+    info!("Order updates stream requested");
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<()>::error(&e))).into_response();
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error("Internal error"))
+            ).into_response();
+        }
+    };
+
+    let updates = app_state.order_updates.subscribe();
+    Sse::new(order_update_stream(updates))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Turn the order update channel into an SSE event stream
+fn order_update_stream(
+    updates: tokio::sync::broadcast::Receiver<crate::brokers::types::Order>,
+) -> impl Stream<Item = std::result::Result<Event, Infallible>> {
+    use tokio::sync::broadcast::error::RecvError;
+
+    stream::unfold(updates, |mut updates| async move {
+        loop {
+            match updates.recv().await {
+                Ok(order) => {
+                    let event = Event::default()
+                        .event("order_update")
+                        .json_data(&order)
+                        .unwrap_or_else(|_| Event::default().event("order_update"));
+                    return Some((Ok(event), updates));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Order updates stream lagged, skipped {} updates", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
 /// Calculate synthetic future - POST /api/v1/syntheticfuture
 pub async fn get_synthetic_future(
     AxumState(state): AxumState<Arc<WebhookState>>,
@@ -1815,6 +2720,7 @@ pub async fn place_options_order(
         quantity: req.quantity,
         product: req.product.clone(),
         pricetype: Some(req.price_type.clone()),
+        tag: req.client_order_id.clone(),
     };
 
     match OptionsService::place_options_order(&app_state, options_req, Some(&req.apikey)).await {
@@ -1919,33 +2825,50 @@ pub async fn place_options_multi_order(
         }
     };
 
-    // Get product from first leg (or default to MIS)
-    let product = req.legs.first().map(|l| l.product.clone()).unwrap_or_else(|| "MIS".to_string());
-
-    let legs: Vec<crate::services::options_service::OptionsLeg> = req.legs.iter().map(|leg| {
-        crate::services::options_service::OptionsLeg {
+    let multi_req = crate::services::options_service::OptionsMultiOrderRequest {
+        underlying: req.underlying.clone(),
+        exchange: req.exchange.clone(),
+        expiry_date: req.expiry_date.clone(),
+        product: req.legs.first().map(|l| l.product.clone()).unwrap_or_else(|| "MIS".to_string()),
+        legs: req.legs.iter().map(|leg| crate::services::options_service::OptionsLeg {
             option_type: leg.option_type.clone(),
             strike_selection: offset_to_strike_selection(leg.offset),
             action: leg.action.clone(),
             quantity: leg.quantity,
-        }
-    }).collect();
+            expiry_date: leg.expiry_date.clone(),
+            product: Some(leg.product.clone()),
+            pricetype: Some(leg.price_type.clone()),
+            price: if leg.price > 0.0 { Some(leg.price) } else { None },
+            trigger_price: if leg.trigger_price > 0.0 { Some(leg.trigger_price) } else { None },
+        }).collect(),
+        all_or_none: req.all_or_none,
+        tag: req.client_order_id.clone(),
+    };
 
-    match OptionsService::place_options_multi_order(&app_state, &req.underlying, &req.exchange, req.expiry_date.as_deref(), &product, legs, Some(&req.apikey)).await {
-        Ok(order_results) => {
-            let results: Vec<OptionsOrderLegResult> = order_results.into_iter().enumerate().map(|(i, r)| {
+    match OptionsService::place_options_multi_order(&app_state, multi_req, Some(&req.apikey)).await {
+        Ok(basket) => {
+            let results: Vec<OptionsOrderLegResult> = basket.legs.into_iter().map(|leg| {
                 OptionsOrderLegResult {
-                    leg: (i + 1) as i32,
-                    symbol: r.order_id.clone().unwrap_or_default(),
-                    orderid: r.order_id,
-                    status: if r.success { "success".to_string() } else { "error".to_string() },
-                    message: if r.success { None } else { Some(r.message) },
+                    leg: leg.leg as i32,
+                    status: if leg.is_placed() { "success".to_string() } else { "error".to_string() },
+                    symbol: leg.symbol,
+                    orderid: leg.order_ids.first().cloned(),
+                    message: leg.message,
+                    leg_status: leg.status,
                 }
             }).collect();
-            let data = OptionsMultiOrderResult { results };
+
+            let failed = basket.all_or_none && !basket.success;
+            let data = OptionsMultiOrderResult { results, rolled_back: basket.rolled_back };
             (
-                StatusCode::OK,
-                Json(ApiResponse::success_with_data(data))
+                if failed { StatusCode::BAD_REQUEST } else { StatusCode::OK },
+                Json(ApiResponse {
+                    status: if failed { "error" } else { "success" }.to_string(),
+                    message: Some(basket.message),
+                    data: Some(data),
+                    orderid: None,
+                    mode: Some(basket.mode),
+                })
             )
         }
         Err(e) => {
@@ -1957,6 +2880,237 @@ pub async fn place_options_multi_order(
     }
 }
 
+// ============================================================================
+// GTT API Handlers
+// ============================================================================
+
+/// Convert REST GTT legs into broker GTT legs
+fn to_gtt_legs(legs: &[GttLegItem]) -> Vec<crate::brokers::types::GttLeg> {
+    legs.iter()
+        .map(|leg| crate::brokers::types::GttLeg {
+            side: leg.action.clone(),
+            quantity: leg.quantity,
+            order_type: leg.pricetype.clone(),
+            price: leg.price,
+            product: leg.product.clone(),
+        })
+        .collect()
+}
+
+/// Place GTT order - POST /api/v1/gttorder
+pub async fn place_gtt_order(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<PlaceGttRequest>,
+) -> impl IntoResponse {
+    info!("Place GTT request: {} {} {}", req.exchange, req.symbol, req.trigger_type);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<Empty>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Empty>::error("Internal error"))
+            );
+        }
+    };
+
+    let gtt = BrokerGttRequest {
+        symbol: req.symbol.clone(),
+        exchange: req.exchange.clone(),
+        trigger_type: req.trigger_type.clone(),
+        trigger_prices: req.trigger_prices.clone(),
+        last_price: req.last_price,
+        legs: to_gtt_legs(&req.legs),
+        broker_symbol: None,  // Set by GttService from symbol cache
+        symbol_token: None,   // Set by GttService from symbol cache
+    };
+
+    match GttService::place_gtt(&app_state, gtt, Some(&req.apikey)).await {
+        Ok(result) => {
+            state.emit("api_gtt_order", &req);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::<Empty> {
+                    status: "success".to_string(),
+                    message: Some(result.message),
+                    data: None,
+                    orderid: Some(result.gtt_id),
+                    mode: Some(result.mode),
+                })
+            )
+        }
+        Err(AppError::Validation(msg)) => {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<Empty>::error(&msg)))
+        }
+        Err(e) => {
+            error!("Place GTT failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Empty>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
+/// Modify GTT order - POST /api/v1/gttmodify
+pub async fn modify_gtt_order(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<ModifyGttRequest>,
+) -> impl IntoResponse {
+    info!("Modify GTT request: {}", req.gttid);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<Empty>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Empty>::error("Internal error"))
+            );
+        }
+    };
+
+    let gtt = BrokerGttRequest {
+        symbol: req.symbol.clone(),
+        exchange: req.exchange.clone(),
+        trigger_type: req.trigger_type.clone(),
+        trigger_prices: req.trigger_prices.clone(),
+        last_price: req.last_price,
+        legs: to_gtt_legs(&req.legs),
+        broker_symbol: None,
+        symbol_token: None,
+    };
+
+    match GttService::modify_gtt(&app_state, &req.gttid, gtt, Some(&req.apikey)).await {
+        Ok(result) => {
+            state.emit("api_gtt_modify", &req);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::<Empty>::success_with_orderid(&result.gtt_id).with_mode(&result.mode))
+            )
+        }
+        Err(AppError::Validation(msg)) => {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<Empty>::error(&msg)))
+        }
+        Err(AppError::NotFound(msg)) => {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<Empty>::error(&msg)))
+        }
+        Err(e) => {
+            error!("Modify GTT failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Empty>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
+/// Cancel GTT order - POST /api/v1/gttcancel
+pub async fn cancel_gtt_order(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<CancelGttRequest>,
+) -> impl IntoResponse {
+    info!("Cancel GTT request: {}", req.gttid);
+
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<Empty>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Empty>::error("Internal error"))
+            );
+        }
+    };
+
+    match GttService::cancel_gtt(&app_state, &req.gttid, Some(&req.apikey)).await {
+        Ok(result) => {
+            state.emit("api_gtt_cancel", &req);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::<Empty>::success_with_orderid(&result.gtt_id).with_mode(&result.mode))
+            )
+        }
+        Err(AppError::Validation(msg)) => {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<Empty>::error(&msg)))
+        }
+        Err(AppError::NotFound(msg)) => {
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<Empty>::error(&msg)))
+        }
+        Err(e) => {
+            error!("Cancel GTT failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Empty>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
+/// Get GTT book - POST /api/v1/gttbook
+pub async fn get_gtt_book(
+    AxumState(state): AxumState<Arc<WebhookState>>,
+    Json(req): Json<ApiKeyRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = state.validate_api_key(&req.apikey) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::<Vec<GttOrderData>>::error(&e)));
+    }
+
+    let app_state = match state.get_app_state() {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Vec<GttOrderData>>::error("Internal error"))
+            );
+        }
+    };
+
+    match GttService::get_gtt_orders(&app_state, Some(&req.apikey)).await {
+        Ok(result) => {
+            let data: Vec<GttOrderData> = result.gtt_orders.into_iter().map(|g| GttOrderData {
+                gttid: g.gtt_id,
+                symbol: g.symbol,
+                exchange: g.exchange,
+                trigger_type: g.trigger_type,
+                trigger_prices: g.trigger_prices,
+                last_price: g.last_price,
+                legs: g.legs.into_iter().map(|l| GttLegData {
+                    action: l.side,
+                    quantity: l.quantity,
+                    pricetype: l.order_type,
+                    price: l.price,
+                    product: l.product,
+                }).collect(),
+                status: g.status,
+                orderid: g.order_id,
+                emulated: g.emulated,
+                timestamp: g.created_at,
+            }).collect();
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success_with_data(data).with_mode(&result.mode))
+            )
+        }
+        Err(e) => {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<Vec<GttOrderData>>::error(&e.to_string()))
+            )
+        }
+    }
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -1973,6 +3127,11 @@ pub struct Strategy {
     pub start_time: Option<String>,
     pub end_time: Option<String>,
     pub squareoff_time: Option<String>,
+    /// none, secret or hmac
+    pub auth_mode: String,
+    pub webhook_secret: Option<String>,
+    pub ip_allowlist: Option<String>,
+    pub replay_window_secs: i64,
 }
 
 /// Symbol mapping model
@@ -1984,40 +3143,107 @@ pub struct SymbolMapping {
     pub product_type: String,
 }
 
-/// Validate trading hours for intraday strategies
-fn validate_trading_hours(strategy: &Strategy, payload: &WebhookPayload) -> Result<(), String> {
-    use chrono::{Timelike, Utc};
-    use chrono_tz::Asia::Kolkata;
+/// Place the order for a processed strategy alert
+///
+/// Alerts carrying a position size go through `SmartOrderService`. Returns the
+/// order id, side and quantity of the order placed, or `None` when the
+/// position was already at the target size.
+async fn execute_strategy_alert(
+    app_state: &AppState,
+    alert: &ProcessedAlert,
+) -> Result<Option<(String, Vec<ChildOrder>)>, AppError> {
+    if let Some(position_size) = alert.position_size {
+        let result = SmartOrderService::place_smart_order(
+            app_state,
+            crate::services::smart_order_service::SmartOrderRequest {
+                symbol: alert.symbol.clone(),
+                exchange: alert.exchange.clone(),
+                action: alert.action.clone(),
+                position_size,
+                product: alert.product.clone(),
+                pricetype: Some(alert.pricetype.clone()),
+                price: Some(alert.price),
+                tag: Some(alert.tag.clone()),
+            },
+            None,
+        )
+        .await?;
 
-    let now = Utc::now().with_timezone(&Kolkata);
-    let current_time = format!("{:02}:{:02}", now.hour(), now.minute());
+        if result.child_orders.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some((result.action_taken, result.child_orders)));
+    }
 
-    let action = payload.get_action().unwrap_or_default().to_uppercase();
-    let is_entry = action == "BUY" || action == "SELL";
+    let order = BrokerOrderRequest {
+        symbol: alert.symbol.clone(),
+        exchange: alert.exchange.clone(),
+        side: alert.action.clone(),
+        quantity: alert.quantity,
+        price: alert.price,
+        order_type: alert.pricetype.clone(),
+        product: alert.product.clone(),
+        validity: "DAY".to_string(),
+        trigger_price: (alert.trigger_price > 0.0).then_some(alert.trigger_price),
+        disclosed_quantity: None,
+        amo: false,
+        target: None,
+        stoploss: None,
+        trailing_stoploss: None,
+        tag: Some(alert.tag.clone()),
+        broker_symbol: None,
+        symbol_token: None,
+    };
+
+    let result = OrderService::place_order(app_state, order, None).await?;
+    let placed = result.placed_orders(alert.quantity);
+
+    Ok((!placed.is_empty()).then(|| (alert.action.clone(), placed)))
+}
+
+/// Validate trading hours for intraday strategies
+fn validate_trading_hours(
+    strategy: &Strategy,
+    is_exit: bool,
+    now: chrono::NaiveTime,
+) -> Result<(), String> {
+    let parse = |time: Option<&str>, default: &str| {
+        chrono::NaiveTime::parse_from_str(time.unwrap_or(default), "%H:%M")
+            .unwrap_or_else(|_| chrono::NaiveTime::parse_from_str(default, "%H:%M").unwrap())
+    };
 
     // Get time boundaries
-    let start_time = strategy.start_time.as_deref().unwrap_or("09:15");
-    let end_time = strategy.end_time.as_deref().unwrap_or("15:15");
-    let squareoff_time = strategy.squareoff_time.as_deref().unwrap_or("15:25");
+    let start_time = parse(strategy.start_time.as_deref(), "09:15");
+    let end_time = parse(strategy.end_time.as_deref(), "15:15");
+    let squareoff_time = parse(strategy.squareoff_time.as_deref(), "15:25");
 
     // Before start time - reject all
-    if current_time < start_time.to_string() {
-        return Err(format!("Trading not started. Starts at {}", start_time));
+    if now < start_time {
+        return Err(format!("Trading not started. Starts at {}", start_time.format("%H:%M")));
     }
 
     // After squareoff time - reject all
-    if current_time > squareoff_time.to_string() {
-        return Err(format!("Trading ended. Squareoff was at {}", squareoff_time));
+    if now > squareoff_time {
+        return Err(format!("Trading ended. Squareoff was at {}", squareoff_time.format("%H:%M")));
     }
 
     // Between end_time and squareoff_time - only exit orders allowed
-    if current_time > end_time.to_string() && is_entry {
-        return Err(format!("Entry orders not allowed after {}. Only exit orders until {}", end_time, squareoff_time));
+    if now > end_time && !is_exit {
+        return Err(format!(
+            "Entry orders not allowed after {}. Only exit orders until {}",
+            end_time.format("%H:%M"),
+            squareoff_time.format("%H:%M")
+        ));
     }
 
     Ok(())
 }
 
+/// An action is an exit when it reduces the current position
+fn is_exit_action(action: &str, position_qty: i32) -> bool {
+    (action == "SELL" && position_qty > 0) || (action == "BUY" && position_qty < 0)
+}
+
 /// Convert offset integer to strike_selection string
 /// Offset 0 = ATM, positive = OTM, negative = ITM
 fn offset_to_strike_selection(offset: i32) -> String {
@@ -2029,17 +3255,20 @@ fn offset_to_strike_selection(offset: i32) -> String {
 }
 
 /// Validate action against strategy trading mode
-fn validate_trading_mode(trading_mode: &str, action: &str) -> Result<(), String> {
+///
+/// LONG strategies may only SELL to exit a long position and SHORT strategies
+/// may only BUY to cover a short position.
+fn validate_trading_mode(trading_mode: &str, action: &str, position_qty: i32) -> Result<(), String> {
     match trading_mode.to_uppercase().as_str() {
         "LONG" => {
-            if action == "SELL" {
-                // SELL in LONG mode is an exit, which is allowed
+            if action == "SELL" && position_qty <= 0 {
+                return Err("SELL rejected: LONG-only strategy has no long position to exit".to_string());
             }
             Ok(())
         }
         "SHORT" => {
-            if action == "BUY" {
-                // BUY in SHORT mode is a cover, which is allowed
+            if action == "BUY" && position_qty >= 0 {
+                return Err("BUY rejected: SHORT-only strategy has no short position to cover".to_string());
             }
             Ok(())
         }
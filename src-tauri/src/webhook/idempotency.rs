@@ -0,0 +1,226 @@
+//! Idempotent order placement for the REST API
+//!
+//! Order requests may carry a client order id, either as `client_order_id`
+//! in the JSON body or as an `Idempotency-Key` header. The first request with
+//! an id is executed and its response stored; repeats within the configured
+//! window (e.g. a retried TradingView alert or an SDK timeout retry) get the
+//! stored response back instead of placing a duplicate order.
+
+use crate::db::sqlite::ClientOrderClaim;
+use crate::state::AppState;
+use crate::webhook::types::{ApiResponse, Empty};
+use axum::{
+    body::{to_bytes, Body},
+    extract::State,
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+/// Header carrying the client order id
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header set on responses replayed from a previous request
+pub const IDEMPOTENT_REPLAY_HEADER: &str = "Idempotent-Replayed";
+
+/// Largest order request body buffered for hashing (1 MB)
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Maximum client order id length
+const MAX_CLIENT_ORDER_ID_LEN: usize = 64;
+
+/// Whether requests to this path place orders and honour client order ids
+pub fn is_idempotent_path(path: &str) -> bool {
+    path.starts_with("/webhook/")
+        || path.starts_with("/strategy/webhook/")
+        || path.ends_with("/placeorder")
+        || path.ends_with("/placesmartorder")
+        || path.ends_with("/basketorder")
+        || path.ends_with("/splitorder")
+        || path.ends_with("/optionsorder")
+        || path.ends_with("/optionsmultiorder")
+        || path.ends_with("/algoorder")
+        || path.ends_with("/gttorder")
+}
+
+/// Validate a client order id (1-64 chars of letters, digits, '-' and '_')
+pub fn validate_client_order_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > MAX_CLIENT_ORDER_ID_LEN {
+        return Err(format!(
+            "client_order_id must be 1-{} characters",
+            MAX_CLIENT_ORDER_ID_LEN
+        ));
+    }
+    if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("client_order_id may only contain letters, digits, '-' and '_'".to_string());
+    }
+    Ok(())
+}
+
+/// Idempotency middleware for order endpoints
+pub async fn idempotency_middleware(
+    State(app_handle): State<AppHandle>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    if !is_idempotent_path(&path) {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &format!("Invalid request body: {}", e)),
+    };
+
+    let header_id = parts
+        .headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let mut json = serde_json::from_slice::<serde_json::Value>(&bytes).ok();
+    let body_id = json
+        .as_ref()
+        .and_then(|v| v.get("client_order_id"))
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    let client_order_id = match (body_id, header_id) {
+        (None, None) => return next.run(Request::from_parts(parts, Body::from(bytes))).await,
+        (Some(body_id), Some(header_id)) if body_id != header_id => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "client_order_id does not match the Idempotency-Key header",
+            );
+        }
+        (Some(id), _) | (None, Some(id)) => id,
+    };
+
+    if let Err(e) = validate_client_order_id(&client_order_id) {
+        return error_response(StatusCode::BAD_REQUEST, &e);
+    }
+
+    let Some(app_state) = app_handle.try_state::<AppState>() else {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal error: AppState not available");
+    };
+
+    let window_secs = app_state
+        .sqlite
+        .get_idempotency_config()
+        .map(|c| c.window_secs)
+        .unwrap_or(86_400);
+    let request_hash = format!("{:x}", Sha256::digest(&bytes));
+
+    match app_state
+        .sqlite
+        .claim_client_order(&client_order_id, &path, &request_hash, window_secs)
+    {
+        Ok(ClientOrderClaim::Claimed) => {}
+        Ok(ClientOrderClaim::Completed { status_code, response }) => {
+            tracing::info!("Replaying response for client order id {}", client_order_id);
+            return replay_response(status_code, response);
+        }
+        Ok(ClientOrderClaim::InProgress) => {
+            return error_response(
+                StatusCode::CONFLICT,
+                &format!("Order with client_order_id {} is still being processed", client_order_id),
+            );
+        }
+        Ok(ClientOrderClaim::Mismatch { endpoint }) => {
+            return error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                &format!(
+                    "client_order_id {} was already used for a different request to {}",
+                    client_order_id, endpoint
+                ),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to claim client order id {}: {}", client_order_id, e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+        }
+    }
+
+    // Hand the id to the handler in the body so it can be used as the broker
+    // tag; webhook bodies are left untouched as they may be signed
+    let is_webhook = path.contains("/webhook/");
+    let bytes = match json.as_mut().and_then(|v| v.as_object_mut()) {
+        Some(object) if !is_webhook && !object.contains_key("client_order_id") => {
+            object.insert("client_order_id".to_string(), client_order_id.clone().into());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            serde_json::to_vec(&object).map(Into::into).unwrap_or(bytes)
+        }
+        _ => bytes,
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+
+    // Store the response for replay
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read response for client order id {}: {}", client_order_id, e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+        }
+    };
+
+    if let Err(e) = app_state.sqlite.complete_client_order(
+        &client_order_id,
+        parts.status.as_u16(),
+        &String::from_utf8_lossy(&bytes),
+    ) {
+        tracing::error!("Failed to store response for client order id {}: {}", client_order_id, e);
+    }
+
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Rebuild a stored response
+fn replay_response(status_code: u16, body: String) -> Response {
+    let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+    let mut response = (status, body).into_response();
+
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response.headers_mut().insert(
+        IDEMPOTENT_REPLAY_HEADER,
+        HeaderValue::from_static("true"),
+    );
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(ApiResponse::<Empty>::error(message))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idempotent_paths() {
+        assert!(is_idempotent_path("/api/v1/placeorder"));
+        assert!(is_idempotent_path("/api/v1/placesmartorder"));
+        assert!(is_idempotent_path("/api/v1/optionsmultiorder"));
+        assert!(is_idempotent_path("/webhook/abc123"));
+        assert!(!is_idempotent_path("/api/v1/orderbook"));
+        assert!(!is_idempotent_path("/api/v1/cancelorder"));
+    }
+
+    #[test]
+    fn test_validate_client_order_id() {
+        assert!(validate_client_order_id("tv-20240101-0915_1").is_ok());
+        assert!(validate_client_order_id("").is_err());
+        assert!(validate_client_order_id("has space").is_err());
+        assert!(validate_client_order_id(&"a".repeat(65)).is_err());
+    }
+}
@@ -20,8 +20,10 @@ mod server;
 pub mod handlers;
 mod types;
 pub mod rate_limiter;
+pub mod idempotency;
+pub mod verification;
 
-pub use server::WebhookServer;
+pub use server::{WebhookServer, WebhookServerStatus};
 pub use types::{
     // REST API types
     ApiResponse,
@@ -5,23 +5,78 @@
 //! - General API: api_rate_limit (default 100/s)
 //! - Order placement: order_rate_limit (default 10/s)
 //! - Smart orders: smart_order_rate_limit (default 2/s)
-
+//!
+//! Buckets are kept per client: requests carrying a known API key (`apikey`
+//! in the JSON body or query string) share that key's buckets, everything
+//! else is limited per client IP. The smart order delay is kept per client
+//! too. API keys may override the global limits.
+//! Responses carry `X-RateLimit-Limit/Remaining/Reset` headers.
+
+use crate::db::sqlite::ApiKeyRateLimits;
+use crate::webhook::verification::client_ip;
 use axum::{
-    body::Body,
-    extract::State,
-    http::{Request, StatusCode},
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Query, State},
+    http::{HeaderMap, HeaderValue, Method, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
 use parking_lot::Mutex;
+use serde::Serialize;
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+/// Header with the client's limit for the endpoint category
+pub const LIMIT_HEADER: &str = "X-RateLimit-Limit";
+
+/// Header with the requests left in the current bucket
+pub const REMAINING_HEADER: &str = "X-RateLimit-Remaining";
+
+/// Header with the seconds until the bucket is full again
+pub const RESET_HEADER: &str = "X-RateLimit-Reset";
+
+/// Largest request body buffered to read the API key (1 MB)
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Idle buckets are dropped once this many clients are tracked
+const MAX_BUCKETS: usize = 1024;
+
+/// How long a bucket may sit unused before it can be dropped
+const BUCKET_IDLE_TTL: Duration = Duration::from_secs(600);
+
+/// Rate-limited routes and their category; unlisted routes are General
+const ROUTE_LIMITS: &[(&str, RateLimitType)] = &[
+    // Smart orders (position sizing, multi-leg)
+    ("/api/v1/placesmartorder", RateLimitType::SmartOrder),
+    ("/api/v1/optionsorder", RateLimitType::SmartOrder),
+    ("/api/v1/optionsmultiorder", RateLimitType::SmartOrder),
+    ("/api/v1/basketorder", RateLimitType::SmartOrder),
+    ("/api/v1/splitorder", RateLimitType::SmartOrder),
+    ("/api/v1/algoorder", RateLimitType::SmartOrder),
+    // Orders
+    ("/api/v1/placeorder", RateLimitType::Order),
+    ("/api/v1/modifyorder", RateLimitType::Order),
+    ("/api/v1/cancelorder", RateLimitType::Order),
+    ("/api/v1/cancelallorder", RateLimitType::Order),
+    ("/api/v1/closeposition", RateLimitType::Order),
+    ("/api/v1/killswitch", RateLimitType::Order),
+    ("/api/v1/cancelalgo", RateLimitType::Order),
+    ("/api/v1/exitrule", RateLimitType::Order),
+    ("/api/v1/cancelexitrule", RateLimitType::Order),
+    ("/api/v1/gttorder", RateLimitType::Order),
+    ("/api/v1/gttmodify", RateLimitType::Order),
+    ("/api/v1/gttcancel", RateLimitType::Order),
+];
+
 /// Rate limit type for different endpoint categories
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RateLimitType {
     /// General API calls (quotes, positions, etc.)
     General,
@@ -31,6 +86,60 @@ pub enum RateLimitType {
     SmartOrder,
 }
 
+impl RateLimitType {
+    const ALL: [RateLimitType; 3] = [Self::General, Self::Order, Self::SmartOrder];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::General => "general",
+            Self::Order => "order",
+            Self::SmartOrder => "smart_order",
+        }
+    }
+
+    /// This category's override, if the key has one
+    fn override_from(self, limits: &ApiKeyRateLimits) -> Option<u32> {
+        match self {
+            Self::General => limits.api_rate_limit,
+            Self::Order => limits.order_rate_limit,
+            Self::SmartOrder => limits.smart_order_rate_limit,
+        }
+    }
+}
+
+/// Who a bucket belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitClient {
+    /// A known API key, by SHA-256 fingerprint
+    ApiKey(String),
+    /// An unauthenticated client, by IP
+    Ip(String),
+}
+
+/// Outcome of a rate limit check
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the bucket is full again
+    pub reset_after: Duration,
+    /// Time until the next request is allowed (zero if allowed)
+    pub retry_after: Duration,
+}
+
+/// Current usage of one endpoint category
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitUsage {
+    pub limit_type: RateLimitType,
+    /// Requests per second
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_secs: u64,
+    /// Whether the limit is a per-key override
+    pub overridden: bool,
+}
+
 /// Token bucket rate limiter
 #[derive(Debug)]
 pub struct TokenBucket {
@@ -65,15 +174,19 @@ impl TokenBucket {
         }
     }
 
-    /// Try to consume a token, returns true if allowed
-    pub fn try_acquire(&mut self) -> bool {
-        // Refill tokens based on elapsed time
+    /// Add the tokens earned since the last refill
+    fn refill(&mut self) {
         let now = Instant::now();
         let elapsed = now.duration_since(self.last_refill);
         let refill_amount = elapsed.as_secs_f64() * self.refill_rate;
 
         self.tokens = (self.tokens + refill_amount).min(self.capacity as f64);
         self.last_refill = now;
+    }
+
+    /// Try to consume a token, returns true if allowed
+    pub fn try_acquire(&mut self) -> bool {
+        self.refill();
 
         // Try to consume a token
         if self.tokens >= 1.0 {
@@ -93,78 +206,192 @@ impl TokenBucket {
             Duration::from_secs_f64(tokens_needed / self.refill_rate)
         }
     }
+
+    /// Maximum tokens (requests per second)
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Whole tokens currently available
+    pub fn remaining(&self) -> u32 {
+        self.tokens.max(0.0).floor() as u32
+    }
+
+    /// Get time until the bucket is full again
+    pub fn time_until_full(&self) -> Duration {
+        let missing = (self.capacity as f64 - self.tokens).max(0.0);
+        Duration::from_secs_f64(missing / self.refill_rate)
+    }
 }
 
 /// Shared rate limiter state
 #[derive(Debug)]
 pub struct RateLimiterState {
-    /// Rate limiters per type
-    limiters: Mutex<HashMap<RateLimitType, TokenBucket>>,
+    /// Global limits per type
+    limits: Mutex<HashMap<RateLimitType, u32>>,
+    /// Known API keys (by fingerprint) and their overrides
+    api_keys: Mutex<HashMap<String, ApiKeyRateLimits>>,
+    /// Rate limiters per client and type
+    buckets: Mutex<HashMap<(RateLimitClient, RateLimitType), TokenBucket>>,
     /// Smart order delay in seconds
     smart_order_delay: Mutex<f64>,
-    /// Last smart order time
-    last_smart_order: Mutex<Option<Instant>>,
+    /// Last smart order time per client
+    last_smart_order: Mutex<HashMap<RateLimitClient, Instant>>,
+    /// Whether X-Forwarded-For from a loopback proxy names the client
+    trust_proxy: AtomicBool,
 }
 
 impl RateLimiterState {
     /// Create new rate limiter state with default values
     pub fn new(api_rate: u32, order_rate: u32, smart_order_rate: u32, smart_order_delay: f64) -> Self {
-        let mut limiters = HashMap::new();
-        limiters.insert(RateLimitType::General, TokenBucket::new(api_rate));
-        limiters.insert(RateLimitType::Order, TokenBucket::new(order_rate));
-        limiters.insert(RateLimitType::SmartOrder, TokenBucket::new(smart_order_rate));
+        let mut limits = HashMap::new();
+        limits.insert(RateLimitType::General, api_rate);
+        limits.insert(RateLimitType::Order, order_rate);
+        limits.insert(RateLimitType::SmartOrder, smart_order_rate);
 
         Self {
-            limiters: Mutex::new(limiters),
+            limits: Mutex::new(limits),
+            api_keys: Mutex::new(HashMap::new()),
+            buckets: Mutex::new(HashMap::new()),
             smart_order_delay: Mutex::new(smart_order_delay),
-            last_smart_order: Mutex::new(None),
+            trust_proxy: AtomicBool::new(false),
+            last_smart_order: Mutex::new(HashMap::new()),
         }
     }
 
     /// Update rate limits from config
     pub fn update_config(&self, api_rate: u32, order_rate: u32, smart_order_rate: u32, smart_order_delay: f64) {
-        let mut limiters = self.limiters.lock();
-        if let Some(limiter) = limiters.get_mut(&RateLimitType::General) {
-            limiter.update_rate(api_rate);
+        {
+            let mut limits = self.limits.lock();
+            limits.insert(RateLimitType::General, api_rate);
+            limits.insert(RateLimitType::Order, order_rate);
+            limits.insert(RateLimitType::SmartOrder, smart_order_rate);
         }
-        if let Some(limiter) = limiters.get_mut(&RateLimitType::Order) {
-            limiter.update_rate(order_rate);
+        *self.smart_order_delay.lock() = smart_order_delay;
+        self.rerate_buckets();
+    }
+
+    /// Set whether X-Forwarded-For from a loopback proxy is trusted
+    pub fn set_trust_proxy(&self, trust: bool) {
+        self.trust_proxy.store(trust, Ordering::Relaxed);
+    }
+
+    pub fn trust_proxy(&self) -> bool {
+        self.trust_proxy.load(Ordering::Relaxed)
+    }
+
+    /// Replace the known API keys and their overrides
+    ///
+    /// Buckets of removed keys are dropped; their clients fall back to
+    /// per-IP limits.
+    pub fn set_api_keys(&self, keys: Vec<(String, ApiKeyRateLimits)>) {
+        let keys: HashMap<String, ApiKeyRateLimits> = keys
+            .into_iter()
+            .map(|(key, limits)| (fingerprint(&key), limits))
+            .collect();
+
+        self.buckets.lock().retain(|(client, _), _| match client {
+            RateLimitClient::ApiKey(id) => keys.contains_key(id),
+            RateLimitClient::Ip(_) => true,
+        });
+        *self.api_keys.lock() = keys;
+        self.rerate_buckets();
+    }
+
+    /// Client a request is limited as: its API key if known, else its IP
+    pub fn client_for(&self, api_key: Option<&str>, ip: &str) -> RateLimitClient {
+        if let Some(id) = api_key.map(fingerprint) {
+            if self.api_keys.lock().contains_key(&id) {
+                return RateLimitClient::ApiKey(id);
+            }
         }
-        if let Some(limiter) = limiters.get_mut(&RateLimitType::SmartOrder) {
-            limiter.update_rate(smart_order_rate);
+        RateLimitClient::Ip(ip.to_string())
+    }
+
+    /// Limit for a client and type, and whether it is a per-key override
+    fn limit_for(&self, client: &RateLimitClient, rate_type: RateLimitType) -> (u32, bool) {
+        if let RateLimitClient::ApiKey(id) = client {
+            if let Some(limit) = self
+                .api_keys
+                .lock()
+                .get(id)
+                .and_then(|limits| rate_type.override_from(limits))
+            {
+                return (limit, true);
+            }
         }
-        *self.smart_order_delay.lock() = smart_order_delay;
+        let limit = self.limits.lock().get(&rate_type).copied().unwrap_or(u32::MAX);
+        (limit, false)
     }
 
-    /// Try to acquire a token for the given rate limit type
-    pub fn try_acquire(&self, rate_type: RateLimitType) -> bool {
-        let mut limiters = self.limiters.lock();
-        if let Some(limiter) = limiters.get_mut(&rate_type) {
-            limiter.try_acquire()
-        } else {
-            true // If limiter doesn't exist, allow
+    /// Apply current limits to existing buckets
+    fn rerate_buckets(&self) {
+        let mut buckets = self.buckets.lock();
+        for ((client, rate_type), bucket) in buckets.iter_mut() {
+            bucket.update_rate(self.limit_for(client, *rate_type).0);
         }
     }
 
-    /// Get time until rate limit allows a request
-    pub fn time_until_available(&self, rate_type: RateLimitType) -> Duration {
-        let limiters = self.limiters.lock();
-        if let Some(limiter) = limiters.get(&rate_type) {
-            limiter.time_until_available()
-        } else {
-            Duration::ZERO
+    /// Try to acquire a token for the given client and rate limit type
+    pub fn try_acquire(&self, client: &RateLimitClient, rate_type: RateLimitType) -> RateLimitDecision {
+        let (limit, _) = self.limit_for(client, rate_type);
+        let mut buckets = self.buckets.lock();
+
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|_, bucket| bucket.last_refill.elapsed() < BUCKET_IDLE_TTL);
+        }
+
+        let bucket = buckets
+            .entry((client.clone(), rate_type))
+            .or_insert_with(|| TokenBucket::new(limit));
+        let allowed = bucket.try_acquire();
+
+        RateLimitDecision {
+            allowed,
+            limit: bucket.capacity(),
+            remaining: bucket.remaining(),
+            reset_after: bucket.time_until_full(),
+            retry_after: if allowed { Duration::ZERO } else { bucket.time_until_available() },
         }
     }
 
-    /// Check and apply smart order delay
+    /// Current usage of a client across all types, without consuming tokens
+    pub fn usage(&self, client: &RateLimitClient) -> Vec<RateLimitUsage> {
+        RateLimitType::ALL
+            .iter()
+            .map(|&rate_type| {
+                let (limit, overridden) = self.limit_for(client, rate_type);
+                let mut buckets = self.buckets.lock();
+                let (remaining, reset_after) = match buckets.get_mut(&(client.clone(), rate_type)) {
+                    Some(bucket) => {
+                        bucket.refill();
+                        (bucket.remaining(), bucket.time_until_full())
+                    }
+                    None => (limit, Duration::ZERO),
+                };
+                RateLimitUsage {
+                    limit_type: rate_type,
+                    limit,
+                    remaining,
+                    reset_secs: reset_after.as_secs_f64().ceil() as u64,
+                    overridden,
+                }
+            })
+            .collect()
+    }
+
+    /// Check and apply smart order delay for a client
     /// Returns Ok(()) if allowed to proceed, Err(wait_time) if need to wait
-    pub fn check_smart_order_delay(&self) -> Result<(), Duration> {
-        let delay = *self.smart_order_delay.lock();
-        let mut last_order = self.last_smart_order.lock();
+    pub fn check_smart_order_delay(&self, client: &RateLimitClient) -> Result<(), Duration> {
+        let required_delay = Duration::from_secs_f64(*self.smart_order_delay.lock());
+        let mut last_orders = self.last_smart_order.lock();
+
+        if last_orders.len() >= MAX_BUCKETS {
+            last_orders.retain(|_, last_time| last_time.elapsed() < required_delay);
+        }
 
-        if let Some(last_time) = *last_order {
+        if let Some(last_time) = last_orders.get(client) {
             let elapsed = last_time.elapsed();
-            let required_delay = Duration::from_secs_f64(delay);
 
             if elapsed < required_delay {
                 return Err(required_delay - elapsed);
@@ -172,35 +399,24 @@ impl RateLimiterState {
         }
 
         // Update last order time
-        *last_order = Some(Instant::now());
+        last_orders.insert(client.clone(), Instant::now());
         Ok(())
     }
 }
 
 /// Determine rate limit type based on request path
 pub fn get_rate_limit_type(path: &str) -> RateLimitType {
-    // Smart order endpoints
-    if path.contains("/placesmartorder")
-        || path.contains("/optionsorder")
-        || path.contains("/optionsmultiorder")
-        || path.contains("/basketorder")
-        || path.contains("/splitorder")
-    {
-        return RateLimitType::SmartOrder;
-    }
-
-    // Order endpoints
-    if path.contains("/placeorder")
-        || path.contains("/modifyorder")
-        || path.contains("/cancelorder")
-        || path.contains("/cancelallorder")
-        || path.contains("/closeposition")
-    {
-        return RateLimitType::Order;
-    }
+    let path = path.trim_end_matches('/');
+    ROUTE_LIMITS
+        .iter()
+        .find(|(route, _)| *route == path)
+        .map(|(_, rate_type)| *rate_type)
+        .unwrap_or(RateLimitType::General)
+}
 
-    // Everything else is general
-    RateLimitType::General
+/// SHA-256 fingerprint of an API key, so raw keys aren't kept in memory maps
+pub fn fingerprint(api_key: &str) -> String {
+    hex::encode(Sha256::digest(api_key.trim().as_bytes()))
 }
 
 /// Rate limiting middleware
@@ -212,9 +428,20 @@ pub async fn rate_limit_middleware(
     let path = request.uri().path().to_string();
     let rate_type = get_rate_limit_type(&path);
 
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| client_ip(*peer, request.headers(), state.trust_proxy()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let (request, api_key) = match read_api_key(request).await {
+        Ok(result) => result,
+        Err(response) => return response,
+    };
+    let client = state.client_for(api_key.as_deref(), &ip);
+
     // For smart orders, also check the delay between orders
     if rate_type == RateLimitType::SmartOrder {
-        if let Err(wait_time) = state.check_smart_order_delay() {
+        if let Err(wait_time) = state.check_smart_order_delay(&client) {
             tracing::warn!(
                 "Smart order delay not met, need to wait {:?}ms",
                 wait_time.as_millis()
@@ -224,18 +451,61 @@ pub async fn rate_limit_middleware(
     }
 
     // Check rate limit
-    if !state.try_acquire(rate_type) {
-        let wait_time = state.time_until_available(rate_type);
+    let decision = state.try_acquire(&client, rate_type);
+    if !decision.allowed {
         tracing::warn!(
-            "Rate limit exceeded for {:?}, path: {}, retry after {:?}ms",
+            "Rate limit exceeded for {:?} ({:?}), path: {}, retry after {:?}ms",
             rate_type,
+            client,
             path,
-            wait_time.as_millis()
+            decision.retry_after.as_millis()
         );
-        return rate_limit_response(wait_time, &format!("{:?}", rate_type).to_lowercase());
+        let mut response = rate_limit_response(decision.retry_after, rate_type.as_str());
+        insert_rate_limit_headers(response.headers_mut(), &decision);
+        return response;
     }
 
-    next.run(request).await
+    let mut response = next.run(request).await;
+    insert_rate_limit_headers(response.headers_mut(), &decision);
+    response
+}
+
+/// Read the `apikey` of a REST API request from its query string or JSON body
+///
+/// The body is buffered and handed back in a rebuilt request.
+async fn read_api_key(request: Request<Body>) -> Result<(Request<Body>, Option<String>), Response> {
+    if !request.uri().path().starts_with("/api/") {
+        return Ok((request, None));
+    }
+
+    let from_query = Query::<HashMap<String, String>>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(params)| params.get("apikey").cloned());
+    if from_query.is_some() || request.method() == Method::GET {
+        return Ok((request, from_query));
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_BODY_BYTES).await.map_err(|e| {
+        let body = Json(json!({
+            "status": "error",
+            "message": format!("Invalid request body: {}", e)
+        }));
+        (StatusCode::PAYLOAD_TOO_LARGE, body).into_response()
+    })?;
+    let api_key = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|v| v.get("apikey").and_then(|k| k.as_str()).map(str::to_string));
+
+    Ok((Request::from_parts(parts, Body::from(bytes)), api_key))
+}
+
+/// Add the standard rate limit headers
+fn insert_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    let reset_secs = decision.reset_after.as_secs_f64().ceil() as u64;
+    headers.insert(LIMIT_HEADER, HeaderValue::from(decision.limit));
+    headers.insert(REMAINING_HEADER, HeaderValue::from(decision.remaining));
+    headers.insert(RESET_HEADER, HeaderValue::from(reset_secs));
 }
 
 /// Create a rate limit exceeded response
@@ -309,17 +579,67 @@ mod tests {
         assert_eq!(get_rate_limit_type("/api/v1/quotes"), RateLimitType::General);
         assert_eq!(get_rate_limit_type("/api/v1/basketorder"), RateLimitType::SmartOrder);
         assert_eq!(get_rate_limit_type("/api/v1/cancelorder"), RateLimitType::Order);
+        assert_eq!(get_rate_limit_type("/api/v1/gttorder"), RateLimitType::Order);
+        assert_eq!(get_rate_limit_type("/api/v1/killswitch"), RateLimitType::Order);
+        assert_eq!(get_rate_limit_type("/api/v1/algoorder"), RateLimitType::SmartOrder);
+        assert_eq!(get_rate_limit_type("/api/v1/cancelalgo"), RateLimitType::Order);
+        assert_eq!(get_rate_limit_type("/api/v1/algostatus"), RateLimitType::General);
+        assert_eq!(get_rate_limit_type("/api/v1/gttbook"), RateLimitType::General);
+        assert_eq!(get_rate_limit_type("/api/v1/orderupdates"), RateLimitType::General);
+        // Exact routes only: no substring matches
+        assert_eq!(get_rate_limit_type("/api/v1/placeorder/"), RateLimitType::Order);
+        assert_eq!(get_rate_limit_type("/webhook/placeorder"), RateLimitType::General);
+    }
+
+    #[test]
+    fn test_per_client_buckets() {
+        let state = RateLimiterState::new(100, 2, 2, 0.5);
+        state.set_api_keys(vec![
+            ("dashboard-key".to_string(), ApiKeyRateLimits::default()),
+            (
+                "bot-key".to_string(),
+                ApiKeyRateLimits { order_rate_limit: Some(5), ..Default::default() },
+            ),
+        ]);
+
+        let dashboard = state.client_for(Some("dashboard-key"), "127.0.0.1");
+        let bot = state.client_for(Some("bot-key"), "127.0.0.1");
+        let unknown = state.client_for(Some("made-up-key"), "127.0.0.1");
+        assert!(matches!(dashboard, RateLimitClient::ApiKey(_)));
+        assert_eq!(unknown, RateLimitClient::Ip("127.0.0.1".to_string()));
+
+        // One client draining its bucket doesn't affect the others
+        assert!(state.try_acquire(&dashboard, RateLimitType::Order).allowed);
+        let last = state.try_acquire(&dashboard, RateLimitType::Order);
+        assert!(last.allowed);
+        assert_eq!((last.limit, last.remaining), (2, 0));
+        assert!(!state.try_acquire(&dashboard, RateLimitType::Order).allowed);
+        assert!(state.try_acquire(&unknown, RateLimitType::Order).allowed);
+
+        // Overrides apply per key
+        let decision = state.try_acquire(&bot, RateLimitType::Order);
+        assert_eq!((decision.limit, decision.remaining), (5, 4));
+        let usage = state.usage(&bot);
+        let order = usage.iter().find(|u| u.limit_type == RateLimitType::Order).unwrap();
+        assert!(order.overridden);
+        assert_eq!(order.remaining, 4);
     }
 
     #[test]
     fn test_smart_order_delay() {
         let state = RateLimiterState::new(100, 10, 2, 0.5);
+        let client = RateLimitClient::Ip("127.0.0.1".to_string());
 
         // First order should pass
-        assert!(state.check_smart_order_delay().is_ok());
+        assert!(state.check_smart_order_delay(&client).is_ok());
+
+        // Another client isn't held back by it
+        assert!(state
+            .check_smart_order_delay(&RateLimitClient::Ip("10.0.0.2".to_string()))
+            .is_ok());
 
         // Immediate second order should fail
-        let result = state.check_smart_order_delay();
+        let result = state.check_smart_order_delay(&client);
         assert!(result.is_err());
 
         // Should need to wait ~0.5 seconds
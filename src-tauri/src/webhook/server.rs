@@ -3,82 +3,206 @@
 //! Provides:
 //! - Dynamic strategy-based webhooks (/webhook/{webhook_id})
 //! - OpenAlgo SDK compatible REST API (/api/v1/*)
-//! - Rate limiting to prevent hitting broker API limits
+//! - Per-API-key rate limiting to prevent hitting broker API limits
+//! - Idempotent order placement via client order ids
+//!
+//! The server is a managed handle in `AppState`: it can be started, stopped
+//! and restarted from settings, and applies host/port/enabled changes live.
+//! Stopping drains in-flight requests for up to `DRAIN_TIMEOUT`.
 
+use crate::db::sqlite::models::RateLimitConfig;
 use crate::db::sqlite::WebhookConfig;
 use crate::state::AppState;
 use crate::webhook::handlers::{self, WebhookState};
-use crate::webhook::rate_limiter::{rate_limit_middleware, RateLimiterState};
+use crate::webhook::idempotency::idempotency_middleware;
+use crate::webhook::rate_limiter::{rate_limit_middleware, RateLimitUsage, RateLimiterState};
 use axum::{
-    middleware,
+    body::Body,
+    extract::State,
+    http::Request,
+    middleware::{self, Next},
+    response::Response,
     routing::{get, post},
     Router,
 };
+use serde::Serialize;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Manager};
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+/// How long a stop waits for in-flight requests before closing connections
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Request counters since the server was started
+#[derive(Debug, Default)]
+struct RequestStats {
+    total: AtomicU64,
+    failed: AtomicU64,
+    rate_limited: AtomicU64,
+}
+
+/// A bound, running server
+struct RunningServer {
+    addr: SocketAddr,
+    started: Instant,
+    started_at: String,
+    stats: Arc<RequestStats>,
+    shutdown_tx: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+/// Webhook/API server status
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookServerStatus {
+    pub running: bool,
+    /// Bound address (host:port)
+    pub address: Option<String>,
+    pub started_at: Option<String>,
+    pub uptime_secs: u64,
+    pub requests_total: u64,
+    /// Responses with status >= 400
+    pub requests_failed: u64,
+    pub requests_rate_limited: u64,
+}
 
 /// Webhook/API server manager
 pub struct WebhookServer {
     app_handle: AppHandle,
-    shutdown_tx: Option<oneshot::Sender<()>>,
+    /// Shared across restarts so limits can change without one
+    rate_limiter: Arc<RateLimiterState>,
+    running: Mutex<Option<RunningServer>>,
 }
 
 impl WebhookServer {
-    /// Create a new server
+    /// Create a new server (not yet bound)
     pub fn new(app_handle: AppHandle) -> Self {
         Self {
             app_handle,
-            shutdown_tx: None,
+            rate_limiter: Arc::new(RateLimiterState::new(100, 10, 2, 0.5)),
+            running: Mutex::new(None),
         }
     }
 
-    /// Start the server
-    pub async fn start(&mut self, config: WebhookConfig) -> Result<(), String> {
+    /// Apply a webhook config: start, stop or rebind as needed
+    pub async fn apply_config(&self, config: WebhookConfig) -> Result<(), String> {
+        self.rate_limiter.set_trust_proxy(config.trust_proxy);
         if !config.enabled {
-            info!("Webhook/API server is disabled");
+            if self.stop().await {
+                info!("Webhook/API server disabled");
+            }
+            return Ok(());
+        }
+
+        let wanted = Self::parse_addr(&config)?;
+        let current = self.running.lock().await.as_ref().map(|r| r.addr);
+        match current {
+            Some(addr) if addr == wanted => Ok(()),
+            Some(_) => self.restart(config).await,
+            None => self.start(config).await,
+        }
+    }
+
+    /// Restart the server, e.g. to bind a new host or port
+    pub async fn restart(&self, config: WebhookConfig) -> Result<(), String> {
+        self.stop().await;
+        self.start(config).await
+    }
+
+    /// Whether client IPs are taken from a local proxy's X-Forwarded-For
+    pub fn trust_proxy(&self) -> bool {
+        self.rate_limiter.trust_proxy()
+    }
+
+    /// Reload rate limits into the live limiter
+    pub fn update_rate_limits(&self, config: &RateLimitConfig) {
+        self.rate_limiter.update_config(
+            config.api_rate_limit,
+            config.order_rate_limit,
+            config.smart_order_rate_limit,
+            config.smart_order_delay,
+        );
+        info!(
+            "Rate limits updated: API={}/s, Order={}/s, SmartOrder={}/s, Delay={}s",
+            config.api_rate_limit,
+            config.order_rate_limit,
+            config.smart_order_rate_limit,
+            config.smart_order_delay
+        );
+    }
+
+    /// Reload API keys and their overrides into the live limiter
+    ///
+    /// Call after keys are created, deleted or their limits change.
+    pub fn reload_api_keys(&self, state: &AppState) {
+        match state.sqlite.list_api_key_rate_limits(&state.security) {
+            Ok(keys) => self.rate_limiter.set_api_keys(keys),
+            Err(e) => error!("Failed to load API key rate limits: {}", e),
+        }
+    }
+
+    /// Current rate limit usage of an API key
+    pub fn rate_limit_usage(&self, api_key: &str, ip: &str) -> Vec<RateLimitUsage> {
+        let client = self.rate_limiter.client_for(Some(api_key), ip);
+        self.rate_limiter.usage(&client)
+    }
+
+    /// Current server status
+    pub async fn status(&self) -> WebhookServerStatus {
+        let running = self.running.lock().await;
+        match running.as_ref() {
+            Some(server) => WebhookServerStatus {
+                running: true,
+                address: Some(server.addr.to_string()),
+                started_at: Some(server.started_at.clone()),
+                uptime_secs: server.started.elapsed().as_secs(),
+                requests_total: server.stats.total.load(Ordering::Relaxed),
+                requests_failed: server.stats.failed.load(Ordering::Relaxed),
+                requests_rate_limited: server.stats.rate_limited.load(Ordering::Relaxed),
+            },
+            None => WebhookServerStatus {
+                running: false,
+                address: None,
+                started_at: None,
+                uptime_secs: 0,
+                requests_total: 0,
+                requests_failed: 0,
+                requests_rate_limited: 0,
+            },
+        }
+    }
+
+    /// Start the server (regardless of the enabled flag)
+    ///
+    /// Does nothing if it is already running.
+    pub async fn start(&self, config: WebhookConfig) -> Result<(), String> {
+        let mut running = self.running.lock().await;
+        if running.is_some() {
             return Ok(());
         }
 
         let host = config.host.clone();
         let port = config.port;
 
-        // Parse address
-        let addr: SocketAddr = format!("{}:{}", host, port)
-            .parse()
-            .map_err(|e| format!("Invalid address: {}", e))?;
+        let addr = Self::parse_addr(&config)?;
+        self.rate_limiter.set_trust_proxy(config.trust_proxy);
 
-        // Get rate limit config from database
-        let (api_rate, order_rate, smart_order_rate, smart_order_delay) = {
-            let app_state = self.app_handle.state::<AppState>();
+        // Load rate limits from database
+        if let Some(app_state) = self.app_handle.try_state::<AppState>() {
             match app_state.sqlite.get_rate_limit_config() {
-                Ok(config) => (
-                    config.api_rate_limit,
-                    config.order_rate_limit,
-                    config.smart_order_rate_limit,
-                    config.smart_order_delay,
-                ),
-                Err(e) => {
-                    error!("Failed to get rate limit config, using defaults: {}", e);
-                    (100, 10, 2, 0.5) // Default values
-                }
+                Ok(config) => self.update_rate_limits(&config),
+                Err(e) => error!("Failed to get rate limit config, keeping current limits: {}", e),
             }
-        };
-
-        info!("Rate limits: API={}/s, Order={}/s, SmartOrder={}/s, Delay={}s",
-              api_rate, order_rate, smart_order_rate, smart_order_delay);
-
-        // Create rate limiter state
-        let rate_limiter = Arc::new(RateLimiterState::new(
-            api_rate,
-            order_rate,
-            smart_order_rate,
-            smart_order_delay,
-        ));
+            self.reload_api_keys(&app_state);
+        }
+        let rate_limiter = self.rate_limiter.clone();
+        let stats = Arc::new(RequestStats::default());
 
         // Create shared state
         let state = Arc::new(WebhookState::new(self.app_handle.clone()));
@@ -123,11 +247,25 @@ impl WebhookServer {
             .route("/api/v1/cancelorder", post(handlers::cancel_order))
             .route("/api/v1/cancelallorder", post(handlers::cancel_all_orders))
             .route("/api/v1/closeposition", post(handlers::close_position))
+            .route("/api/v1/killswitch", post(handlers::kill_switch))
             .route("/api/v1/basketorder", post(handlers::place_basket_order))
             .route("/api/v1/splitorder", post(handlers::place_split_order))
+            .route("/api/v1/algoorder", post(handlers::place_algo_order))
+            .route("/api/v1/algostatus", post(handlers::get_algo_status))
+            .route("/api/v1/cancelalgo", post(handlers::cancel_algo_order))
+            .route("/api/v1/exitrule", post(handlers::create_exit_rule))
+            .route("/api/v1/exitrules", post(handlers::get_exit_rules))
+            .route("/api/v1/cancelexitrule", post(handlers::cancel_exit_rule))
+
+            // GTT orders
+            .route("/api/v1/gttorder", post(handlers::place_gtt_order))
+            .route("/api/v1/gttmodify", post(handlers::modify_gtt_order))
+            .route("/api/v1/gttcancel", post(handlers::cancel_gtt_order))
+            .route("/api/v1/gttbook", post(handlers::get_gtt_book))
 
             // Order/Position status
             .route("/api/v1/orderstatus", post(handlers::get_order_status))
+            .route("/api/v1/orderupdates", get(handlers::stream_order_updates))
             .route("/api/v1/openposition", post(handlers::get_open_position))
 
             // Data retrieval
@@ -153,6 +291,7 @@ impl WebhookServer {
             .route("/api/v1/analyzer", post(handlers::get_analyzer_status))
             .route("/api/v1/analyzer/toggle", post(handlers::toggle_analyzer))
             .route("/api/v1/margin", post(handlers::get_margin))
+            .route("/api/v1/ratelimit", post(handlers::get_rate_limit))
 
             // Options API
             .route("/api/v1/optionchain", post(handlers::get_option_chain))
@@ -165,14 +304,17 @@ impl WebhookServer {
             // Add state and middleware
             // ================================================================
             .with_state(state)
+            // Client order id replay (order endpoints only)
+            .layer(middleware::from_fn_with_state(self.app_handle.clone(), idempotency_middleware))
             // Rate limiting middleware (applied to all API routes)
-            .layer(middleware::from_fn_with_state(rate_limiter.clone(), rate_limit_middleware))
+            .layer(middleware::from_fn_with_state(rate_limiter, rate_limit_middleware))
+            // Request counters for the status report
+            .layer(middleware::from_fn_with_state(stats.clone(), count_requests))
             .layer(cors)
             .layer(TraceLayer::new_for_http());
 
         // Create shutdown channel
         let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
-        self.shutdown_tx = Some(shutdown_tx);
 
         // Start server
         info!("Starting OpenAlgo Desktop API server on {}", addr);
@@ -182,8 +324,9 @@ impl WebhookServer {
             .map_err(|e| format!("Failed to bind to {}: {}", addr, e))?;
 
         // Spawn server task
-        tokio::spawn(async move {
-            let server = axum::serve(listener, app)
+        let task = tokio::spawn(async move {
+            // Peer addresses are needed for webhook IP allowlists
+            let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
                 .with_graceful_shutdown(async {
                     let _ = shutdown_rx.await;
                     info!("API server shutting down");
@@ -194,6 +337,16 @@ impl WebhookServer {
             }
         });
 
+        *running = Some(RunningServer {
+            addr,
+            started: Instant::now(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            stats,
+            shutdown_tx,
+            task,
+        });
+        drop(running);
+
         info!("OpenAlgo Desktop API server started successfully");
         info!("");
         info!("=== Endpoints ===");
@@ -212,11 +365,24 @@ impl WebhookServer {
         info!("    POST http://{}:{}/api/v1/cancelorder", host, port);
         info!("    POST http://{}:{}/api/v1/cancelallorder", host, port);
         info!("    POST http://{}:{}/api/v1/closeposition", host, port);
+        info!("    POST http://{}:{}/api/v1/killswitch", host, port);
         info!("    POST http://{}:{}/api/v1/basketorder", host, port);
         info!("    POST http://{}:{}/api/v1/splitorder", host, port);
+        info!("    POST http://{}:{}/api/v1/algoorder", host, port);
+        info!("    POST http://{}:{}/api/v1/algostatus", host, port);
+        info!("    POST http://{}:{}/api/v1/cancelalgo", host, port);
+        info!("    POST http://{}:{}/api/v1/exitrule", host, port);
+        info!("    POST http://{}:{}/api/v1/exitrules", host, port);
+        info!("    POST http://{}:{}/api/v1/cancelexitrule", host, port);
+        info!("  GTT Orders:");
+        info!("    POST http://{}:{}/api/v1/gttorder", host, port);
+        info!("    POST http://{}:{}/api/v1/gttmodify", host, port);
+        info!("    POST http://{}:{}/api/v1/gttcancel", host, port);
+        info!("    POST http://{}:{}/api/v1/gttbook", host, port);
         info!("  Order/Position Status:");
         info!("    POST http://{}:{}/api/v1/orderstatus", host, port);
         info!("    POST http://{}:{}/api/v1/openposition", host, port);
+        info!("    GET  http://{}:{}/api/v1/orderupdates (SSE)", host, port);
         info!("  Data Retrieval:");
         info!("    POST http://{}:{}/api/v1/orderbook", host, port);
         info!("    POST http://{}:{}/api/v1/tradebook", host, port);
@@ -250,22 +416,63 @@ impl WebhookServer {
         Ok(())
     }
 
-    /// Stop the server
-    pub fn stop(&mut self) {
-        if let Some(tx) = self.shutdown_tx.take() {
-            let _ = tx.send(());
-            info!("API server stop signal sent");
+    /// Stop the server, draining in-flight requests
+    ///
+    /// Returns false if it wasn't running.
+    pub async fn stop(&self) -> bool {
+        let Some(server) = self.running.lock().await.take() else {
+            return false;
+        };
+
+        let _ = server.shutdown_tx.send(());
+        info!("API server stop signal sent, draining requests");
+
+        // Long-lived streams (SSE) never finish on their own
+        let mut task = server.task;
+        if tokio::time::timeout(DRAIN_TIMEOUT, &mut task).await.is_err() {
+            warn!("API server did not drain within {:?}, closing connections", DRAIN_TIMEOUT);
+            task.abort();
         }
+
+        info!("API server stopped ({} released)", server.addr);
+        true
     }
 
     /// Check if server is running
-    pub fn is_running(&self) -> bool {
-        self.shutdown_tx.is_some()
+    pub async fn is_running(&self) -> bool {
+        self.running.lock().await.is_some()
+    }
+
+    fn parse_addr(config: &WebhookConfig) -> Result<SocketAddr, String> {
+        format!("{}:{}", config.host, config.port)
+            .parse()
+            .map_err(|e| format!("Invalid address: {}", e))
     }
 }
 
 impl Drop for WebhookServer {
     fn drop(&mut self) {
-        self.stop();
+        if let Some(server) = self.running.get_mut().take() {
+            let _ = server.shutdown_tx.send(());
+        }
+    }
+}
+
+/// Count requests, failures and rate-limited requests
+async fn count_requests(
+    State(stats): State<Arc<RequestStats>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+
+    stats.total.fetch_add(1, Ordering::Relaxed);
+    if response.status().as_u16() == 429 {
+        stats.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+    if response.status().as_u16() >= 400 {
+        stats.failed.fetch_add(1, Ordering::Relaxed);
     }
+
+    response
 }
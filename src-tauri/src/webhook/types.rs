@@ -52,6 +52,20 @@ where
     }
 }
 
+/// Deserialize a list of numbers that may be sent as strings
+fn deserialize_flexible_f64_vec<'de, D>(deserializer: D) -> Result<Vec<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Flexible(#[serde(deserialize_with = "deserialize_flexible_f64")] f64);
+
+    Ok(Vec::<Flexible>::deserialize(deserializer)?
+        .into_iter()
+        .map(|f| f.0)
+        .collect())
+}
+
 #[allow(dead_code)]
 fn deserialize_optional_i32<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
 where
@@ -76,7 +90,6 @@ where
     }
 }
 
-#[allow(dead_code)]
 fn deserialize_optional_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
 where
     D: Deserializer<'de>,
@@ -185,6 +198,14 @@ impl<T: Serialize> ApiResponse<T> {
     }
 }
 
+/// Child orders of a placeorder sliced at the exchange freeze limit
+#[derive(Debug, Clone, Serialize)]
+pub struct SlicedOrderData {
+    pub orderids: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<String>,
+}
+
 /// Empty data type for responses without data
 #[derive(Debug, Clone, Serialize)]
 pub struct Empty {}
@@ -214,6 +235,18 @@ pub struct PlaceOrderRequest {
     pub trigger_price: f64,
     #[serde(default = "default_i32", deserialize_with = "deserialize_flexible_i32")]
     pub disclosed_quantity: i32,
+    /// Bracket order target in points (product BO)
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub target: f64,
+    /// Bracket/cover order stoploss in points (product BO/CO)
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub stoploss: f64,
+    /// Trailing stoploss in points (product BO/CO)
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub trailing_stoploss: f64,
+    /// Idempotency key, also sent to the broker as the order tag
+    #[serde(default)]
+    pub client_order_id: Option<String>,
 }
 
 /// Place smart order request - POST /api/v1/placesmartorder
@@ -239,6 +272,9 @@ pub struct PlaceSmartOrderRequest {
     pub trigger_price: f64,
     #[serde(default = "default_i32", deserialize_with = "deserialize_flexible_i32")]
     pub disclosed_quantity: i32,
+    /// Idempotency key, also sent to the broker as the order tag
+    #[serde(default)]
+    pub client_order_id: Option<String>,
 }
 
 /// Modify order request - POST /api/v1/modifyorder
@@ -278,6 +314,14 @@ pub struct CancelAllOrdersRequest {
     pub strategy: String,
 }
 
+/// Kill switch request - POST /api/v1/killswitch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitchRequest {
+    pub apikey: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
 /// Close position request - POST /api/v1/closeposition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClosePositionRequest {
@@ -306,6 +350,12 @@ pub struct BasketOrderRequest {
     pub apikey: String,
     pub strategy: String,
     pub orders: Vec<BasketOrderItem>,
+    /// Place hedge (BUY) legs first and roll back placed legs if any order fails
+    #[serde(default)]
+    pub all_or_none: bool,
+    /// Idempotency key, also sent to the broker as the order tag
+    #[serde(default)]
+    pub client_order_id: Option<String>,
 }
 
 /// Individual order in a basket
@@ -347,6 +397,92 @@ pub struct SplitOrderRequest {
     pub price: f64,
     #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
     pub trigger_price: f64,
+    /// Idempotency key, also sent to the broker as the order tag
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+}
+
+/// Algo order request - POST /api/v1/algoorder
+/// Works the quantity as TWAP, VWAP or ICEBERG child orders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlgoOrderApiRequest {
+    pub apikey: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub action: String,
+    #[serde(deserialize_with = "deserialize_flexible_i32")]
+    pub quantity: i32,
+    /// TWAP, VWAP or ICEBERG
+    pub algo: String,
+    #[serde(default = "default_product")]
+    pub product: String,
+    /// Limit price for child orders (0 = MARKET)
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub price: f64,
+    /// TWAP/VWAP window in seconds
+    #[serde(default = "default_i32", deserialize_with = "deserialize_flexible_i32")]
+    pub duration: i32,
+    /// TWAP number of slices
+    #[serde(default = "default_i32", deserialize_with = "deserialize_flexible_i32")]
+    pub slices: i32,
+    /// VWAP share of market volume (0.1 = 10%)
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub participation_rate: f64,
+    /// ICEBERG clip size
+    #[serde(default = "default_i32", deserialize_with = "deserialize_flexible_i32")]
+    pub display_quantity: i32,
+    #[serde(default = "default_i32", deserialize_with = "deserialize_flexible_i32")]
+    pub disclosed_quantity: i32,
+}
+
+/// Algo status / cancel request - POST /api/v1/algostatus, /api/v1/cancelalgo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlgoIdRequest {
+    pub apikey: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+    pub algoid: String,
+}
+
+/// Exit rule request - POST /api/v1/exitrule
+/// Target/stoploss/trailing exit for a position, or MTM exit for the portfolio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitRuleApiRequest {
+    pub apikey: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+    /// position or portfolio
+    #[serde(default = "default_exit_scope")]
+    pub scope: String,
+    #[serde(default)]
+    pub exchange: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub product: Option<String>,
+    /// price, points, percent or mtm
+    pub ruletype: String,
+    #[serde(default, deserialize_with = "deserialize_optional_f64")]
+    pub target: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_f64")]
+    pub stoploss: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_f64")]
+    pub trailing: Option<f64>,
+}
+
+fn default_exit_scope() -> String {
+    "position".to_string()
+}
+
+/// Exit rule cancel request - POST /api/v1/cancelexitrule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitRuleIdRequest {
+    pub apikey: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+    pub ruleid: String,
 }
 
 /// Order status request - POST /api/v1/orderstatus
@@ -480,6 +616,12 @@ pub struct InstrumentsRequest {
     pub exchange: Option<String>,
 }
 
+/// Order updates stream request - GET /api/v1/orderupdates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderUpdatesRequest {
+    pub apikey: String,
+}
+
 /// Synthetic future request - POST /api/v1/syntheticfuture
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyntheticFutureRequest {
@@ -541,6 +683,9 @@ pub struct OptionsOrderRequest {
     pub price_type: String,
     #[serde(default = "default_product")]
     pub product: String,
+    /// Idempotency key, also sent to the broker as the order tag
+    #[serde(default)]
+    pub client_order_id: Option<String>,
 }
 
 /// Options symbol request - POST /api/v1/optionsymbol
@@ -572,6 +717,12 @@ pub struct OptionsMultiOrderRequest {
     pub expiry_date: Option<String>,
     #[serde(default)]
     pub strike_int: Option<i32>,
+    /// Place hedge (BUY) legs first and roll back placed legs if any leg fails
+    #[serde(default)]
+    pub all_or_none: bool,
+    /// Idempotency key, also sent to the broker as the order tag
+    #[serde(default)]
+    pub client_order_id: Option<String>,
 }
 
 /// Option leg for multi-order
@@ -587,6 +738,73 @@ pub struct OptionLeg {
     pub price_type: String,
     #[serde(default = "default_product")]
     pub product: String,
+    /// Leg expiry (defaults to the order's expiry_date)
+    #[serde(default)]
+    pub expiry_date: Option<String>,
+    /// Limit price for LIMIT/SL legs
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub price: f64,
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub trigger_price: f64,
+}
+
+/// GTT order request - POST /api/v1/gttorder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaceGttRequest {
+    pub apikey: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+    pub exchange: String,
+    pub symbol: String,
+    /// "single" or "two-leg" (OCO)
+    #[serde(default = "default_trigger_type")]
+    pub trigger_type: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64_vec")]
+    pub trigger_prices: Vec<f64>,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    pub last_price: f64,
+    pub legs: Vec<GttLegItem>,
+}
+
+/// Modify GTT order request - POST /api/v1/gttmodify
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifyGttRequest {
+    pub apikey: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+    pub gttid: String,
+    pub exchange: String,
+    pub symbol: String,
+    #[serde(default = "default_trigger_type")]
+    pub trigger_type: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64_vec")]
+    pub trigger_prices: Vec<f64>,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    pub last_price: f64,
+    pub legs: Vec<GttLegItem>,
+}
+
+/// Cancel GTT order request - POST /api/v1/gttcancel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelGttRequest {
+    pub apikey: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+    pub gttid: String,
+}
+
+/// Order leg placed when a GTT triggers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GttLegItem {
+    pub action: String,
+    #[serde(deserialize_with = "deserialize_flexible_i32")]
+    pub quantity: i32,
+    #[serde(default = "default_gtt_pricetype")]
+    pub pricetype: String,
+    #[serde(default = "default_f64", deserialize_with = "deserialize_flexible_f64")]
+    pub price: f64,
+    #[serde(default = "default_gtt_product")]
+    pub product: String,
 }
 
 // ============================================================================
@@ -686,6 +904,8 @@ pub struct BasketOrderResult {
     pub orderid: Option<String>,
     pub status: String,
     pub message: Option<String>,
+    /// placed, filled, failed, rejected, skipped, cancelled, reversed or rollback_failed
+    pub leg_status: String,
 }
 
 /// Split order response
@@ -814,6 +1034,44 @@ pub struct MarginData {
     pub span_margin: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exposure_margin: Option<f64>,
+    /// Margin saved by legs offsetting each other
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hedge_benefit: Option<f64>,
+}
+
+/// Rate limit usage of the calling API key
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitData {
+    pub limits: Vec<crate::webhook::rate_limiter::RateLimitUsage>,
+    /// Minimum delay between smart orders (seconds), shared by all clients
+    pub smart_order_delay: f64,
+}
+
+/// GTT order in GTT book
+#[derive(Debug, Clone, Serialize)]
+pub struct GttOrderData {
+    pub gttid: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub trigger_type: String,
+    pub trigger_prices: Vec<f64>,
+    pub last_price: f64,
+    pub legs: Vec<GttLegData>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orderid: Option<String>,
+    pub emulated: bool,
+    pub timestamp: String,
+}
+
+/// GTT leg in GTT book
+#[derive(Debug, Clone, Serialize)]
+pub struct GttLegData {
+    pub action: String,
+    pub quantity: i32,
+    pub pricetype: String,
+    pub price: f64,
+    pub product: String,
 }
 
 /// Multi-quotes data (array of quotes)
@@ -931,6 +1189,8 @@ pub struct OptionSymbolResult {
 #[derive(Debug, Clone, Serialize)]
 pub struct OptionsMultiOrderResult {
     pub results: Vec<OptionsOrderLegResult>,
+    /// All-or-none order failed and placed legs were unwound
+    pub rolled_back: bool,
 }
 
 /// Options multi-order leg result
@@ -940,6 +1200,8 @@ pub struct OptionsOrderLegResult {
     pub symbol: String,
     pub orderid: Option<String>,
     pub status: String,
+    /// placed, filled, failed, rejected, skipped, cancelled, reversed or rollback_failed
+    pub leg_status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
 }
@@ -981,6 +1243,9 @@ pub struct WebhookPayload {
     // Chartink specific - comma-separated stock list
     pub stocks: Option<String>,
     pub scan_name: Option<String>,
+
+    /// Sent to the broker as the order tag (the strategy's tag otherwise)
+    pub client_order_id: Option<String>,
 }
 
 impl WebhookPayload {
@@ -1027,6 +1292,26 @@ impl WebhookPayload {
             .to_uppercase()
     }
 
+    /// Get the Chartink keyword (BUY, SELL, SHORT or COVER)
+    ///
+    /// An explicit action field wins; otherwise the keyword is taken from the
+    /// words of `scan_name` (e.g. "Short Cover Scan" is COVER).
+    pub fn get_chartink_action(&self) -> Option<String> {
+        const KEYWORDS: [&str; 4] = ["COVER", "SHORT", "BUY", "SELL"];
+
+        if let Some(action) = self.action.as_ref().or(self.order.as_ref()).or(self.side.as_ref()) {
+            let action = action.trim().to_uppercase();
+            return KEYWORDS.contains(&action.as_str()).then_some(action);
+        }
+
+        let name = self.scan_name.as_ref()?.to_uppercase();
+        let words: Vec<&str> = name.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+        KEYWORDS
+            .iter()
+            .find(|keyword| words.contains(keyword))
+            .map(|keyword| keyword.to_string())
+    }
+
     /// Check if this is a Chartink multi-stock payload
     pub fn is_chartink_multi_stock(&self) -> bool {
         self.stocks.is_some()
@@ -1063,6 +1348,8 @@ pub struct ProcessedAlert {
     pub trigger_price: f64,
     pub position_size: Option<i32>,
     pub is_smart_order: bool,
+    /// Broker order tag
+    pub tag: String,
     pub timestamp: String,
 }
 
@@ -1094,6 +1381,18 @@ fn default_strategy() -> String {
     "Python".to_string()
 }
 
+fn default_trigger_type() -> String {
+    "single".to_string()
+}
+
+fn default_gtt_pricetype() -> String {
+    "LIMIT".to_string()
+}
+
+fn default_gtt_product() -> String {
+    "CNC".to_string()
+}
+
 #[allow(dead_code)]
 fn default_timestamp() -> String {
     chrono::Utc::now().to_rfc3339()
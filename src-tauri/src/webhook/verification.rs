@@ -0,0 +1,305 @@
+//! Webhook alert verification
+//!
+//! Each strategy chooses how its alerts prove where they came from:
+//! - `secret`: a shared secret in the JSON body (`"secret": "..."`), for
+//!   sources like TradingView that can't sign requests
+//! - `hmac`: an `X-OpenAlgo-Signature: sha256=<hex>` header, the HMAC-SHA256
+//!   of `<timestamp>.<body>` with the timestamp in `X-OpenAlgo-Timestamp`
+//! - `none`: no check (unless a global webhook secret is set, which then
+//!   applies as `secret`)
+//!
+//! An optional IP allowlist (IPs or CIDRs, e.g. TradingView's published
+//! addresses) applies in every mode. Alerts carrying a timestamp must fall
+//! inside the strategy's replay window, and alerts carrying a nonce (or a
+//! signature) are only accepted once within it. `secret` alerts must carry a
+//! timestamp inside the window, since replay keys are only remembered for
+//! the window; without a nonce the body hash is the replay key, so a
+//! captured alert can't be resent inside the window either.
+
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, SocketAddr};
+
+/// Header carrying the HMAC signature
+pub const SIGNATURE_HEADER: &str = "X-OpenAlgo-Signature";
+
+/// Header carrying the signed unix timestamp
+pub const TIMESTAMP_HEADER: &str = "X-OpenAlgo-Timestamp";
+
+/// Header carrying an optional single-use nonce
+pub const NONCE_HEADER: &str = "X-OpenAlgo-Nonce";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Verification settings for one strategy
+#[derive(Debug, Clone)]
+pub struct WebhookVerifier<'a> {
+    /// none, secret or hmac
+    pub mode: &'a str,
+    pub secret: Option<&'a str>,
+    pub ip_allowlist: Option<&'a str>,
+    pub replay_window_secs: i64,
+}
+
+impl WebhookVerifier<'_> {
+    /// Verify an alert
+    ///
+    /// Returns the replay key to remember (nonce or signature) if the alert
+    /// has one, or the reason for rejecting it.
+    pub fn verify(
+        &self,
+        client_ip: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+        payload: &serde_json::Value,
+        now: i64,
+    ) -> Result<Option<String>, String> {
+        if let Some(allowlist) = self.ip_allowlist.filter(|l| !l.trim().is_empty()) {
+            if !ip_allowed(client_ip, allowlist) {
+                return Err(format!("IP {} is not in the strategy allowlist", client_ip));
+            }
+        }
+
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let (timestamp, mut replay_key) = match self.mode {
+            "none" => (None, None),
+            "secret" => {
+                let expected = self
+                    .secret
+                    .ok_or_else(|| "No webhook secret configured for this strategy".to_string())?;
+                let provided = payload.get("secret").and_then(|v| v.as_str()).unwrap_or_default();
+                if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+                    return Err("Invalid webhook secret".to_string());
+                }
+                let timestamp = payload
+                    .get("timestamp")
+                    .map(parse_timestamp)
+                    .ok_or_else(|| "Secret alerts need a timestamp".to_string())?;
+                (Some(timestamp), Some(format!("body:{}", hex::encode(Sha256::digest(body)))))
+            }
+            "hmac" => {
+                let secret = self
+                    .secret
+                    .ok_or_else(|| "No webhook secret configured for this strategy".to_string())?;
+                let timestamp = header(TIMESTAMP_HEADER)
+                    .ok_or_else(|| format!("Missing {} header", TIMESTAMP_HEADER))?;
+                let signature = header(SIGNATURE_HEADER)
+                    .ok_or_else(|| format!("Missing {} header", SIGNATURE_HEADER))?;
+                if !verify_signature(secret, &timestamp, body, &signature) {
+                    return Err("Invalid webhook signature".to_string());
+                }
+                let parsed = parse_timestamp(&serde_json::Value::String(timestamp));
+                (Some(parsed), Some(signature.to_lowercase()))
+            }
+            other => return Err(format!("Unknown webhook auth mode: {}", other)),
+        };
+
+        if let Some(timestamp) = timestamp {
+            let timestamp = timestamp.ok_or_else(|| "Invalid alert timestamp".to_string())?;
+            if (now - timestamp).abs() > self.replay_window_secs {
+                return Err(format!(
+                    "Alert timestamp is outside the {}s replay window",
+                    self.replay_window_secs
+                ));
+            }
+        }
+
+        let nonce = header(NONCE_HEADER).or_else(|| {
+            payload
+                .get("nonce")
+                .and_then(|v| v.as_str())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        });
+        if let Some(nonce) = nonce {
+            replay_key = Some(format!("nonce:{}", nonce));
+        }
+
+        Ok(replay_key)
+    }
+}
+
+/// Client IP of a webhook request
+///
+/// Tunnels (ngrok, cloudflared) connect from localhost and append the caller
+/// to `X-Forwarded-For`. With `trust_proxy` on, a loopback peer's rightmost
+/// entry (the one the tunnel added) is used; earlier entries come from the
+/// caller and can be forged, so they are never read.
+pub fn client_ip(peer: SocketAddr, headers: &HeaderMap, trust_proxy: bool) -> String {
+    if trust_proxy && peer.ip().is_loopback() {
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .next_back()
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit(',').next())
+            .and_then(|v| v.trim().parse::<IpAddr>().ok());
+        if let Some(ip) = forwarded {
+            return ip.to_string();
+        }
+    }
+    peer.ip().to_string()
+}
+
+/// Whether an allowlist rule is an IP address or CIDR block
+pub fn is_valid_ip_rule(rule: &str) -> bool {
+    parse_ip_rule(rule).is_some()
+}
+
+/// Whether an IP matches any rule of a comma-separated allowlist
+pub fn ip_allowed(ip: &str, allowlist: &str) -> bool {
+    let Ok(ip) = ip.parse::<IpAddr>() else {
+        return false;
+    };
+
+    allowlist
+        .split(',')
+        .filter_map(|rule| parse_ip_rule(rule.trim()))
+        .any(|(network, prefix)| match (ip, network) {
+            (IpAddr::V4(ip), IpAddr::V4(network)) => {
+                let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+                u32::from(ip) & mask == u32::from(network) & mask
+            }
+            (IpAddr::V6(ip), IpAddr::V6(network)) => {
+                let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+                u128::from(ip) & mask == u128::from(network) & mask
+            }
+            _ => false,
+        })
+}
+
+/// Expected HMAC signature (hex) of a timestamped body
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn verify_signature(secret: &str, timestamp: &str, body: &[u8], signature: &str) -> bool {
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature).to_lowercase();
+    constant_time_eq(sign(secret, timestamp, body).as_bytes(), signature.as_bytes())
+}
+
+/// Parse "1.2.3.4" or "1.2.3.0/24" into network and prefix length
+fn parse_ip_rule(rule: &str) -> Option<(IpAddr, u32)> {
+    let (addr, prefix) = match rule.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix.parse::<u32>().ok()?)),
+        None => (rule, None),
+    };
+    let addr = addr.parse::<IpAddr>().ok()?;
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(max);
+    (prefix <= max).then_some((addr, prefix))
+}
+
+/// Parse a unix timestamp (seconds or milliseconds) or an RFC 3339 time
+fn parse_timestamp(value: &serde_json::Value) -> Option<i64> {
+    let from_number = |n: i64| if n > 10_000_000_000 { n / 1000 } else { n };
+
+    match value {
+        serde_json::Value::Number(n) => n.as_i64().map(from_number),
+        serde_json::Value::String(s) => s
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .map(from_number)
+            .or_else(|| {
+                chrono::DateTime::parse_from_rfc3339(s.trim())
+                    .ok()
+                    .map(|t| t.timestamp())
+            }),
+        _ => None,
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_allowlist() {
+        let allowlist = "52.89.214.238, 34.212.75.30, 10.0.0.0/8, 2001:db8::/32";
+        assert!(ip_allowed("52.89.214.238", allowlist));
+        assert!(ip_allowed("10.20.30.40", allowlist));
+        assert!(ip_allowed("2001:db8::1", allowlist));
+        assert!(!ip_allowed("52.89.214.239", allowlist));
+        assert!(!ip_allowed("not-an-ip", allowlist));
+        assert!(!is_valid_ip_rule("10.0.0.0/33"));
+    }
+
+    #[test]
+    fn test_client_ip_ignores_spoofed_forwarded_for() {
+        let tunnel: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+        let remote: SocketAddr = "203.0.113.7:50000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        // The caller claims an allowlisted IP; the tunnel appends the real one
+        headers.insert("x-forwarded-for", "52.89.214.238, 198.51.100.20".parse().unwrap());
+        headers.insert("x-real-ip", "52.89.214.238".parse().unwrap());
+
+        assert_eq!(client_ip(tunnel, &headers, true), "198.51.100.20");
+        assert_eq!(client_ip(tunnel, &headers, false), "127.0.0.1");
+        assert_eq!(client_ip(remote, &headers, true), "203.0.113.7");
+    }
+
+    #[test]
+    fn test_secret_and_hmac_verification() {
+        let now = 1_700_000_000;
+        let body = br#"{"symbol":"SBIN","action":"BUY"}"#;
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+
+        let secret_mode = WebhookVerifier {
+            mode: "secret",
+            secret: Some("s3cret"),
+            ip_allowlist: None,
+            replay_window_secs: 300,
+        };
+        let headers = HeaderMap::new();
+        assert!(secret_mode.verify("1.2.3.4", &headers, body, &payload, now).is_err());
+        let with_secret = serde_json::json!({"symbol": "SBIN", "secret": "s3cret", "timestamp": now - 10});
+        let replay_key = secret_mode.verify("1.2.3.4", &headers, body, &with_secret, now).unwrap();
+        assert!(replay_key.unwrap().starts_with("body:"));
+        let untimed = serde_json::json!({"symbol": "SBIN", "secret": "s3cret"});
+        assert!(secret_mode.verify("1.2.3.4", &headers, body, &untimed, now).is_err());
+        let untimed_nonce = serde_json::json!({"secret": "s3cret", "nonce": "n-1"});
+        assert!(secret_mode.verify("1.2.3.4", &headers, body, &untimed_nonce, now).is_err());
+        let with_nonce = serde_json::json!({"secret": "s3cret", "nonce": "n-1", "timestamp": now});
+        assert_eq!(
+            secret_mode.verify("1.2.3.4", &headers, body, &with_nonce, now),
+            Ok(Some("nonce:n-1".to_string()))
+        );
+        let empty_nonce = serde_json::json!({"secret": "s3cret", "nonce": "", "timestamp": now});
+        assert!(secret_mode
+            .verify("1.2.3.4", &headers, body, &empty_nonce, now)
+            .unwrap()
+            .unwrap()
+            .starts_with("body:"));
+        let stale = serde_json::json!({"secret": "s3cret", "timestamp": "2023-01-01T00:00:00Z"});
+        assert!(secret_mode.verify("1.2.3.4", &headers, body, &stale, now).is_err());
+
+        let hmac_mode = WebhookVerifier { mode: "hmac", ..secret_mode };
+        let timestamp = now.to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert(TIMESTAMP_HEADER, timestamp.parse().unwrap());
+        headers.insert(
+            SIGNATURE_HEADER,
+            format!("sha256={}", sign("s3cret", &timestamp, body)).parse().unwrap(),
+        );
+        assert!(hmac_mode.verify("1.2.3.4", &headers, body, &payload, now).unwrap().is_some());
+        assert!(hmac_mode.verify("1.2.3.4", &headers, b"{}", &payload, now).is_err());
+        assert!(hmac_mode.verify("1.2.3.4", &headers, body, &payload, now + 600).is_err());
+    }
+}
@@ -6,6 +6,7 @@
 //! - Fyers HSM: Big-endian binary protocol for auth and subscribe
 
 use crate::error::{AppError, Result};
+use crate::services::{Notification, NotificationService};
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::RwLock;
@@ -14,7 +15,7 @@ use std::collections::HashMap;
 use std::io::Cursor;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, warn};
 
@@ -53,6 +54,8 @@ pub struct MarketTick {
     pub timestamp: i64,
     pub change: f64,
     pub change_percent: f64,
+    /// Synthetic tick from the sandbox replay clock
+    pub replay: bool,
 }
 
 impl Default for MarketTick {
@@ -75,6 +78,7 @@ impl Default for MarketTick {
             timestamp: 0,
             change: 0.0,
             change_percent: 0.0,
+            replay: false,
         }
     }
 }
@@ -120,6 +124,9 @@ pub struct SubscriptionRequest {
     pub mode: SubscriptionMode,
 }
 
+/// Capacity of the backend tick channel (slow receivers lag instead of blocking)
+const TICK_CHANNEL_CAPACITY: usize = 4096;
+
 /// Token to symbol mapping for reverse lookup
 type TokenMap = Arc<RwLock<HashMap<String, (String, String)>>>; // token -> (symbol, exchange)
 
@@ -131,11 +138,13 @@ pub struct WebSocketManager {
     sender: RwLock<Option<mpsc::Sender<WebSocketCommand>>>,
     token_map: TokenMap,
     broker_id: RwLock<Option<String>>,
+    tick_tx: broadcast::Sender<MarketTick>,
 }
 
 impl WebSocketManager {
     /// Create new WebSocket manager
     pub fn new(app_handle: AppHandle) -> Self {
+        let (tick_tx, _) = broadcast::channel(TICK_CHANNEL_CAPACITY);
         Self {
             app_handle,
             state: RwLock::new(ConnectionState::Disconnected),
@@ -143,7 +152,27 @@ impl WebSocketManager {
             sender: RwLock::new(None),
             token_map: Arc::new(RwLock::new(HashMap::new())),
             broker_id: RwLock::new(None),
+            tick_tx,
+        }
+    }
+
+    /// Subscribe to parsed ticks from the backend (GTT emulation, MTM, etc.)
+    ///
+    /// Every tick emitted to the frontend as `market_tick` is also
+    /// broadcast here. Receivers that fall behind skip the oldest ticks.
+    pub fn subscribe_ticks(&self) -> broadcast::Receiver<MarketTick> {
+        self.tick_tx.subscribe()
+    }
+
+    /// Publish a tick that didn't come from the broker socket
+    ///
+    /// Emitted as `market_tick` and broadcast to backend consumers exactly
+    /// like a broker tick. Used by the sandbox replay clock.
+    pub fn publish_tick(&self, tick: MarketTick) {
+        if let Err(e) = self.app_handle.emit("market_tick", &tick) {
+            warn!("Failed to emit tick: {}", e);
         }
+        let _ = self.tick_tx.send(tick);
     }
 
     /// Connect to broker WebSocket
@@ -221,6 +250,7 @@ impl WebSocketManager {
         let app_handle = self.app_handle.clone();
         let broker = broker_id.to_string();
         let token_map = self.token_map.clone();
+        let tick_tx = self.tick_tx.clone();
 
         info!("{} WebSocket connected", broker_id);
 
@@ -240,6 +270,8 @@ impl WebSocketManager {
                                     if let Err(e) = app_handle.emit("market_tick", &tick) {
                                         warn!("Failed to emit tick: {}", e);
                                     }
+                                    // No receivers is fine - backend consumers are optional
+                                    let _ = tick_tx.send(tick);
                                 }
                             }
                             Some(Ok(Message::Text(text))) => {
@@ -256,11 +288,13 @@ impl WebSocketManager {
                             Some(Ok(Message::Close(_))) => {
                                 info!("WebSocket closed by server");
                                 let _ = app_handle.emit("websocket_disconnected", &broker);
+                                notify_websocket_down(&app_handle, &broker, "closed by server");
                                 break;
                             }
                             Some(Err(e)) => {
                                 error!("WebSocket error: {}", e);
                                 let _ = app_handle.emit("websocket_error", e.to_string());
+                                notify_websocket_down(&app_handle, &broker, &e.to_string());
                                 break;
                             }
                             None => {
@@ -326,7 +360,9 @@ impl WebSocketManager {
         {
             let mut map = self.token_map.write();
             for req in &requests {
-                map.insert(req.token.clone(), (req.token.clone(), req.exchange.clone()));
+                // Keep symbol names registered via register_symbol
+                map.entry(req.token.clone())
+                    .or_insert_with(|| (req.token.clone(), req.exchange.clone()));
             }
         }
 
@@ -403,6 +439,11 @@ impl WebSocketManager {
         matches!(*self.state.read(), ConnectionState::Connected)
     }
 
+    /// Whether a token is subscribed on the current connection
+    pub fn is_subscribed(&self, exchange: &str, token: &str) -> bool {
+        self.subscriptions.read().contains_key(&format!("{}:{}", exchange, token))
+    }
+
     /// Get current broker
     pub fn get_broker(&self) -> Option<String> {
         self.broker_id.read().clone()
@@ -415,6 +456,19 @@ impl WebSocketManager {
     }
 }
 
+/// Send a `websocket_down` notification for an unexpected disconnect
+fn notify_websocket_down(app_handle: &AppHandle, broker: &str, reason: &str) {
+    NotificationService::notify_app(
+        app_handle,
+        Notification::new(
+            "websocket_down",
+            "Market data disconnected",
+            format!("{} WebSocket disconnected: {}", broker, reason),
+            serde_json::json!({ "broker": broker }),
+        ),
+    );
+}
+
 // ============================================================================
 // Binary Protocol Parsing
 // ============================================================================
@@ -855,6 +909,7 @@ fn parse_fyers_snapshot(data: &[u8], _token_map: &TokenMap) -> Option<(MarketTic
         timestamp: fields.get(3).copied().unwrap_or(0) as i64 * 1000,
         change: if close > 0.0 { ltp - close } else { 0.0 },
         change_percent: if close > 0.0 { ((ltp - close) / close) * 100.0 } else { 0.0 },
+        replay: false,
     };
 
     Some((tick, offset))
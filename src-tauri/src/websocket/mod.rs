@@ -4,11 +4,15 @@
 //! - Angel One SmartAPI: wss://smartapisocket.angelone.in
 //! - Zerodha Kite: wss://ws.kite.trade
 //! - Fyers HSM: wss://socket.fyers.in
+//!
+//! Broker order-status sockets are handled by `OrderUpdateFeed`.
 
 mod handlers;
 mod manager;
+mod order_updates;
 
 pub use handlers::*;
 pub use manager::{
     DepthLevel, MarketDepth, MarketTick, SubscriptionMode, SubscriptionRequest, WebSocketManager,
 };
+pub use order_updates::{normalize_status, parse_order_update, OrderUpdateFeed};
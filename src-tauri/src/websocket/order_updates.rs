@@ -0,0 +1,342 @@
+//! Order update feed from broker order-status sockets
+//!
+//! Each broker pushes order state changes on its own channel:
+//! - Angel One: order status socket (wss://tns.angelone.in/smart-order-update)
+//! - Zerodha Kite: `order` text frames on the Kite ticker socket
+//! - Fyers: order socket (wss://socket.fyers.in/trade/v3)
+//!
+//! Updates are normalized into the common `Order` type and broadcast to
+//! backend consumers (Tauri events, the streaming API, outbound webhooks).
+//! `publish` feeds updates in directly, which the sandbox uses as its local
+//! stand-in for a broker feed.
+
+use crate::brokers::types::Order;
+use crate::brokers::{angel, fyers, zerodha};
+use crate::error::{AppError, Result};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast, oneshot};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{http::Request, Message},
+};
+use tracing::{debug, error, info, warn};
+
+/// Capacity of the order update channel
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+/// Heartbeat interval for order sockets
+const HEARTBEAT_SECS: u64 = 10;
+
+/// Live broker order socket
+struct OrderSocket {
+    broker_id: String,
+    alive: Arc<AtomicBool>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+/// Order update feed for the connected broker
+pub struct OrderUpdateFeed {
+    update_tx: broadcast::Sender<Order>,
+    socket: RwLock<Option<OrderSocket>>,
+}
+
+impl Default for OrderUpdateFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderUpdateFeed {
+    /// Create a new, disconnected order update feed
+    pub fn new() -> Self {
+        let (update_tx, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        Self {
+            update_tx,
+            socket: RwLock::new(None),
+        }
+    }
+
+    /// Subscribe to normalized order updates
+    pub fn subscribe(&self) -> broadcast::Receiver<Order> {
+        self.update_tx.subscribe()
+    }
+
+    /// Push an order update to all subscribers
+    ///
+    /// Used by the sandbox (and tests) in place of a broker socket. The
+    /// status is normalized like broker updates, so sandbox "pending" goes
+    /// out as "open".
+    pub fn publish(&self, mut order: Order) {
+        order.status = normalize_status(&order.status);
+        // No receivers is fine - consumers are optional
+        let _ = self.update_tx.send(order);
+    }
+
+    /// Broker whose order socket is currently connected
+    pub fn connected_broker(&self) -> Option<String> {
+        self.socket
+            .read()
+            .as_ref()
+            .filter(|s| s.alive.load(Ordering::SeqCst))
+            .map(|s| s.broker_id.clone())
+    }
+
+    /// Connect to the broker's order update channel
+    ///
+    /// `auth_token` is the broker session token (Zerodha and Fyers tokens are
+    /// in `api_key:access_token` form).
+    pub async fn connect(&self, broker_id: &str, auth_token: &str) -> Result<()> {
+        self.disconnect();
+
+        let request = match broker_id {
+            "angel" => Request::builder()
+                .uri("wss://tns.angelone.in/smart-order-update")
+                .header("Authorization", format!("Bearer {}", auth_token)),
+            "zerodha" => {
+                let (api_key, access_token) = auth_token.split_once(':').ok_or_else(|| {
+                    AppError::Auth("Invalid Zerodha session token".to_string())
+                })?;
+                Request::builder().uri(format!(
+                    "wss://ws.kite.trade?api_key={}&access_token={}",
+                    api_key, access_token
+                ))
+            }
+            "fyers" => Request::builder()
+                .uri("wss://socket.fyers.in/trade/v3")
+                .header("Authorization", auth_token),
+            _ => {
+                return Err(AppError::Broker(format!(
+                    "Order updates not supported for broker: {}",
+                    broker_id
+                )))
+            }
+        }
+        .body(())
+        .map_err(|e| AppError::Internal(format!("Failed to build request: {}", e)))?;
+
+        info!("Connecting to {} order update feed...", broker_id);
+
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        // Fyers needs an explicit subscription to order events
+        if broker_id == "fyers" {
+            let sub = r#"{"T":"SUB_ORD","SLIST":["orders"],"SUB_T":1}"#;
+            write.send(Message::Text(sub.to_string())).await?;
+        }
+
+        let alive = Arc::new(AtomicBool::new(true));
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+        *self.socket.write() = Some(OrderSocket {
+            broker_id: broker_id.to_string(),
+            alive: alive.clone(),
+            shutdown: Some(shutdown_tx),
+        });
+
+        let broker = broker_id.to_string();
+        let update_tx = self.update_tx.clone();
+
+        info!("{} order update feed connected", broker_id);
+
+        tokio::spawn(async move {
+            let mut heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(HEARTBEAT_SECS));
+
+            loop {
+                tokio::select! {
+                    msg = read.next() => {
+                        match msg {
+                            Some(Ok(Message::Text(text))) => {
+                                if let Some(order) = parse_order_update(&broker, &text) {
+                                    debug!("Order update: {} {}", order.order_id, order.status);
+                                    let _ = update_tx.send(order);
+                                }
+                            }
+                            Some(Ok(Message::Close(_))) => {
+                                info!("{} order feed closed by server", broker);
+                                break;
+                            }
+                            Some(Err(e)) => {
+                                error!("{} order feed error: {}", broker, e);
+                                break;
+                            }
+                            None => break,
+                            _ => {}
+                        }
+                    }
+
+                    _ = &mut shutdown_rx => {
+                        let _ = write.close().await;
+                        break;
+                    }
+
+                    _ = heartbeat.tick() => {
+                        let ping = match broker.as_str() {
+                            "angel" => Message::Text("ping".to_string()),
+                            "fyers" => Message::Text("ping".to_string()),
+                            _ => Message::Ping(Vec::new()),
+                        };
+                        if let Err(e) = write.send(ping).await {
+                            warn!("Failed to send order feed heartbeat: {}", e);
+                        }
+                    }
+                }
+            }
+
+            alive.store(false, Ordering::SeqCst);
+            info!("{} order update feed ended", broker);
+        });
+
+        Ok(())
+    }
+
+    /// Disconnect the broker order socket
+    pub fn disconnect(&self) {
+        if let Some(mut socket) = self.socket.write().take() {
+            if let Some(tx) = socket.shutdown.take() {
+                let _ = tx.send(());
+            }
+            socket.alive.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Parse a broker order socket message into a normalized order
+pub fn parse_order_update(broker: &str, text: &str) -> Option<Order> {
+    let mut order = match broker {
+        "angel" => angel::parse_order_update(text),
+        "zerodha" => zerodha::parse_order_update(text),
+        "fyers" => fyers::parse_order_update(text),
+        _ => None,
+    }?;
+
+    order.status = normalize_status(&order.status);
+    Some(order)
+}
+
+/// Normalize broker order statuses to open/complete/cancelled/rejected/trigger pending
+pub fn normalize_status(status: &str) -> String {
+    let lower = status.trim().to_lowercase();
+    match lower.as_str() {
+        "complete" | "completed" | "filled" | "traded" | "executed" => "complete",
+        "cancelled" | "canceled" => "cancelled",
+        "rejected" => "rejected",
+        "trigger pending" | "trigger_pending" => "trigger pending",
+        "open" | "pending" | "open pending" | "modified" | "put order req received"
+        | "validation pending" | "modify validation pending" | "modify pending" => "open",
+        _ => return lower,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_status() {
+        assert_eq!(normalize_status("COMPLETE"), "complete");
+        assert_eq!(normalize_status("Traded"), "complete");
+        assert_eq!(normalize_status("CANCELED"), "cancelled");
+        assert_eq!(normalize_status("TRIGGER PENDING"), "trigger pending");
+        assert_eq!(normalize_status("open pending"), "open");
+        assert_eq!(normalize_status("AMO REQ RECEIVED"), "amo req received");
+    }
+
+    #[test]
+    fn test_publish_normalizes_sandbox_status() {
+        let feed = OrderUpdateFeed::new();
+        let mut updates = feed.subscribe();
+        feed.publish(Order {
+            order_id: "SB-1".to_string(),
+            exchange_order_id: None,
+            symbol: "SBIN".to_string(),
+            exchange: "NSE".to_string(),
+            side: "BUY".to_string(),
+            quantity: 1,
+            filled_quantity: 0,
+            pending_quantity: 1,
+            price: 600.0,
+            trigger_price: 0.0,
+            average_price: 0.0,
+            order_type: "LIMIT".to_string(),
+            product: "MIS".to_string(),
+            status: "pending".to_string(),
+            validity: "DAY".to_string(),
+            order_timestamp: String::new(),
+            exchange_timestamp: None,
+            rejection_reason: None,
+        });
+
+        assert_eq!(updates.try_recv().unwrap().status, "open");
+    }
+
+    #[test]
+    fn test_parse_zerodha_order_update() {
+        let text = r#"{"type":"order","data":{"order_id":"220303000308932","tradingsymbol":"SBIN",
+            "exchange":"NSE","transaction_type":"BUY","quantity":1,"filled_quantity":1,
+            "pending_quantity":0,"price":0,"trigger_price":0,"average_price":470.0,
+            "order_type":"MARKET","product":"CNC","status":"COMPLETE","validity":"DAY"}}"#;
+
+        let order = parse_order_update("zerodha", text).unwrap();
+        assert_eq!(order.order_id, "220303000308932");
+        assert_eq!(order.status, "complete");
+        assert_eq!(order.average_price, 470.0);
+
+        // Other text frames are ignored
+        assert!(parse_order_update("zerodha", r#"{"type":"message","data":"hello"}"#).is_none());
+    }
+
+    #[test]
+    fn test_parse_angel_order_update() {
+        let text = r#"{"user-id":"A123","status-code":"200","order-status":"AB02",
+            "orderData":{"orderid":"231010000000970","tradingsymbol":"SBIN-EQ","exchange":"NSE",
+            "transactiontype":"SELL","quantity":"10","filledshares":"0","unfilledshares":"10",
+            "price":"600.5","triggerprice":"0","averageprice":"0","ordertype":"LIMIT",
+            "producttype":"INTRADAY","status":"cancelled","duration":"DAY","updatetime":"",
+            "text":""}}"#;
+
+        let order = parse_order_update("angel", text).unwrap();
+        assert_eq!(order.order_id, "231010000000970");
+        assert_eq!(order.product, "MIS");
+        assert_eq!(order.quantity, 10);
+        assert_eq!(order.status, "cancelled");
+
+        // Initial ack has no order
+        assert!(parse_order_update("angel", r#"{"order-status":"AB00","orderData":{"orderid":"","tradingsymbol":"","exchange":"","transactiontype":""}}"#).is_none());
+    }
+
+    #[test]
+    fn test_parse_fyers_order_update() {
+        let text = r#"{"s":"ok","orders":{"id":"23080444447604","symbol":"NSE:SBIN-EQ",
+            "exchange":10,"segment":10,"side":1,"type":2,"status":2,"qty":5,"filledQty":5,
+            "limitPrice":0,"stopPrice":0,"tradedPrice":571.25,"productType":"INTRADAY"}}"#;
+
+        let order = parse_order_update("fyers", text).unwrap();
+        assert_eq!(order.symbol, "SBIN-EQ");
+        assert_eq!(order.side, "BUY");
+        assert_eq!(order.status, "complete");
+        assert_eq!(order.product, "MIS");
+    }
+
+    #[tokio::test]
+    async fn test_publish_reaches_subscribers() {
+        let feed = OrderUpdateFeed::new();
+        let mut rx = feed.subscribe();
+
+        let order = parse_order_update(
+            "zerodha",
+            r#"{"type":"order","data":{"order_id":"1","status":"OPEN"}}"#,
+        )
+        .unwrap();
+        feed.publish(order);
+
+        let received = rx.recv().await.unwrap();
+        assert_eq!(received.order_id, "1");
+        assert_eq!(received.status, "open");
+        assert!(feed.connected_broker().is_none());
+    }
+}